@@ -0,0 +1,268 @@
+//! # Account Fetch & Deserialize Helpers
+//!
+//! Decoding validates the 8-byte Anchor discriminator, so passing the wrong
+//! account type fails loudly instead of producing garbage.
+//!
+//! Fetching is abstracted behind [`AccountFetcher`] so the SDK does not pin
+//! a particular RPC client version; implement it over `RpcClient`, a test
+//! bank, or a cached snapshot.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use lockbox::state::{
+    Alias, CategoryIndex, CategoryRegistry, ClientEntryIds, ContactBook, DenyList, EmergencyAccess, EmergencyGrantRecord, EntryNotes, ExportLog,
+    GuardianAgreement, Inbox, Invoice, MasterLockbox, ProgramConfig, RefundRecord, RecoveryConfig, RecoveryConfigV2, RecoveryRequest,
+    RecoveryRequestV2, SearchFilter, SecurityPolicy, SharedEntry, SharedVault, StorageChunk, TagRegistry,
+};
+
+use crate::error::{ClientError, Result};
+use crate::pda;
+
+/// Source of raw account data
+pub trait AccountFetcher {
+    /// Return the account's data, or `None` if the account does not exist
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>>;
+}
+
+impl AccountFetcher for HashMap<Pubkey, Vec<u8>> {
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        Ok(self.get(address).cloned())
+    }
+}
+
+/// Decode raw account data (including discriminator) into a program account
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    let mut slice = data;
+    T::try_deserialize(&mut slice).map_err(|e| ClientError::Deserialize(e.to_string()))
+}
+
+/// Fetch and decode an account, returning `None` if it does not exist
+pub fn fetch<T: AccountDeserialize>(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Option<T>> {
+    fetcher
+        .fetch_account_data(address)?
+        .map(|data| decode(&data))
+        .transpose()
+}
+
+/// Fetch and decode an account that must exist
+pub fn fetch_required<T: AccountDeserialize>(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<T> {
+    fetch(fetcher, address)?.ok_or_else(|| ClientError::AccountNotFound(address.to_string()))
+}
+
+/// Fetch the owner's master lockbox
+pub fn fetch_master_lockbox(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<MasterLockbox>> {
+    fetch(fetcher, &pda::master_lockbox(owner).0)
+}
+
+/// Fetch one of the owner's storage chunks
+pub fn fetch_storage_chunk(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    chunk_index: u16,
+) -> Result<Option<StorageChunk>> {
+    fetch(fetcher, &pda::storage_chunk_for_owner(owner, chunk_index).0)
+}
+
+/// Fetch every storage chunk registered in the master lockbox
+///
+/// Chunks registered in the master but missing on-chain are skipped.
+pub fn fetch_all_storage_chunks(
+    fetcher: &impl AccountFetcher,
+    master: &MasterLockbox,
+) -> Result<Vec<StorageChunk>> {
+    let mut chunks = Vec::with_capacity(master.storage_chunks.len());
+    for info in &master.storage_chunks {
+        if let Some(chunk) = fetch(fetcher, &info.chunk_address)? {
+            chunks.push(chunk);
+        }
+    }
+    Ok(chunks)
+}
+
+/// Fetch the owner's category registry
+pub fn fetch_category_registry(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<CategoryRegistry>> {
+    fetch(fetcher, &pda::category_registry(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the notes attached to an entry
+pub fn fetch_entry_notes(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    entry_id: u64,
+) -> Result<Option<EntryNotes>> {
+    fetch(fetcher, &pda::entry_notes(&pda::master_lockbox(owner).0, entry_id).0)
+}
+
+/// Fetch the owner's security policy, if one was created
+pub fn fetch_security_policy(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<SecurityPolicy>> {
+    fetch(fetcher, &pda::security_policy(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's contact book, if one was created
+pub fn fetch_contact_book(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<ContactBook>> {
+    fetch(fetcher, &pda::contact_book(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's deny-list, if a key was ever denied
+pub fn fetch_deny_list(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<DenyList>> {
+    fetch(fetcher, &pda::deny_list(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's tag registry, if an entry was ever tagged
+pub fn fetch_tag_registry(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<TagRegistry>> {
+    fetch(fetcher, &pda::tag_registry(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's search filter, if tokens were ever added
+///
+/// Check tokens locally with `SearchFilter::contains` to rule out the vault
+/// or single chunks before fetching their headers.
+pub fn fetch_search_filter(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<SearchFilter>> {
+    fetch(fetcher, &pda::search_filter(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's category index, if one was created
+///
+/// `CategoryIndex::entries_in` lists a category without the 127-entry cap
+/// of `list_category_entries`.
+pub fn fetch_category_index(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<CategoryIndex>> {
+    fetch(fetcher, &pda::category_index(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the client entry IDs reserved in the owner's vault, if the account exists
+pub fn fetch_client_entry_ids(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<ClientEntryIds>> {
+    fetch(fetcher, &pda::client_entry_ids(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch the owner's export log, if a manifest was ever committed
+pub fn fetch_export_log(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<ExportLog>> {
+    fetch(fetcher, &pda::export_log(&pda::master_lockbox(owner).0).0)
+}
+
+/// Fetch a wallet's inbox, if one was created
+pub fn fetch_inbox(fetcher: &impl AccountFetcher, owner: &Pubkey) -> Result<Option<Inbox>> {
+    fetch(fetcher, &pda::inbox(owner).0)
+}
+
+/// Fetch the owner's V1 recovery config
+pub fn fetch_recovery_config(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<RecoveryConfig>> {
+    fetch(fetcher, &pda::recovery_config(owner).0)
+}
+
+/// Fetch the agreement the owner recorded for a guardian
+pub fn fetch_guardian_agreement(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    guardian: &Pubkey,
+) -> Result<Option<GuardianAgreement>> {
+    fetch(fetcher, &pda::guardian_agreement(&pda::recovery_config(owner).0, guardian).0)
+}
+
+/// Fetch a V1 recovery request
+pub fn fetch_recovery_request(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    request_id: u64,
+) -> Result<Option<RecoveryRequest>> {
+    fetch(fetcher, &pda::recovery_request(owner, request_id).0)
+}
+
+/// Fetch the owner's V2 recovery config
+pub fn fetch_recovery_config_v2(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<RecoveryConfigV2>> {
+    fetch(fetcher, &pda::recovery_config_v2(owner).0)
+}
+
+/// Fetch a V2 recovery request
+pub fn fetch_recovery_request_v2(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    request_id: u64,
+) -> Result<Option<RecoveryRequestV2>> {
+    fetch(fetcher, &pda::recovery_request_v2(owner, request_id).0)
+}
+
+/// Fetch the owner's emergency access config
+pub fn fetch_emergency_access(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+) -> Result<Option<EmergencyAccess>> {
+    fetch(fetcher, &pda::emergency_access(owner).0)
+}
+
+/// Fetch the grant record written when emergency access activated
+pub fn fetch_emergency_grant_record(
+    fetcher: &impl AccountFetcher,
+    owner: &Pubkey,
+    countdown_started: i64,
+) -> Result<Option<EmergencyGrantRecord>> {
+    let emergency_access = pda::emergency_access(owner).0;
+    fetch(fetcher, &pda::emergency_grant_record(&emergency_access, countdown_started).0)
+}
+
+/// Fetch a shared vault created by `master_lockbox`
+pub fn fetch_shared_vault(
+    fetcher: &impl AccountFetcher,
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+) -> Result<Option<SharedVault>> {
+    fetch(fetcher, &pda::shared_vault(master_lockbox, vault_id).0)
+}
+
+/// Fetch an entry of a shared vault
+pub fn fetch_shared_entry(
+    fetcher: &impl AccountFetcher,
+    shared_vault: &Pubkey,
+    entry_id: u64,
+) -> Result<Option<SharedEntry>> {
+    fetch(fetcher, &pda::shared_entry(shared_vault, entry_id).0)
+}
+
+/// Fetch the program config naming the billing admin
+pub fn fetch_program_config(fetcher: &impl AccountFetcher) -> Result<Option<ProgramConfig>> {
+    fetch(fetcher, &pda::program_config().0)
+}
+
+/// Fetch an invoice issued to `master_lockbox`
+pub fn fetch_invoice(
+    fetcher: &impl AccountFetcher,
+    master_lockbox: &Pubkey,
+    invoice_id: u64,
+) -> Result<Option<Invoice>> {
+    fetch(fetcher, &pda::invoice(master_lockbox, invoice_id).0)
+}
+
+/// Fetch a refund record by its sequence number
+pub fn fetch_refund_record(fetcher: &impl AccountFetcher, refund_id: u64) -> Result<Option<RefundRecord>> {
+    fetch(fetcher, &pda::refund_record(refund_id).0)
+}
+
+/// Resolve a handle hash to its alias record, if registered
+pub fn fetch_alias(fetcher: &impl AccountFetcher, handle_hash: &[u8; 32]) -> Result<Option<Alias>> {
+    fetch(fetcher, &pda::alias(handle_hash).0)
+}
@@ -0,0 +1,210 @@
+//! # Client-Side Cryptography
+//!
+//! All encryption happens off-chain; the program only ever sees ciphertext.
+//! These primitives match the web client so either side can decrypt data
+//! written by the other.
+//!
+//! ## Key Derivation (HKDF-SHA256, RFC 5869)
+//!
+//! - IKM: `wallet_pubkey || signature || salt`
+//! - Salt: 32 bytes (random, or deterministic `SHA256(pubkey || "lockbox-salt-v1")`)
+//! - Info: `"lockbox-session-key"` (entries) / `"lockbox-search-key-v1"` (blind indexes)
+//!
+//! ## Entry Payload Format
+//!
+//! `[version (1) | nonce length (1) | suite (1) | nonce (24) | ciphertext | Poly1305 tag (16)]`
+//!
+//! The program rejects entry payloads without a valid framing header
+//! (`MalformedPayload`). Entries written before framing (`[nonce |
+//! ciphertext | tag]`) still decrypt.
+
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::error::{ClientError, Result};
+
+/// XChaCha20-Poly1305 nonce size (24 bytes)
+pub const NONCE_SIZE: usize = 24;
+
+/// Poly1305 authentication tag size (16 bytes)
+pub const TAG_SIZE: usize = 16;
+
+/// HKDF salt size (32 bytes)
+pub const SALT_SIZE: usize = 32;
+
+/// Framing header length (version, nonce length, suite)
+pub const HEADER_SIZE: usize = lockbox::instructions::PAYLOAD_HEADER_SIZE;
+
+/// Framing header of every payload [`encrypt_entry`] produces
+pub const PAYLOAD_HEADER: [u8; HEADER_SIZE] = [
+    lockbox::instructions::PAYLOAD_FRAMING_VERSION,
+    NONCE_SIZE as u8,
+    lockbox::instructions::PAYLOAD_SUITE_XCHACHA20_POLY1305,
+];
+
+/// Minimum payload accepted by `store_password_entry` / `update_password_entry`
+pub const MIN_PAYLOAD_SIZE: usize = HEADER_SIZE + NONCE_SIZE + TAG_SIZE;
+
+/// Minimum payload written before framing (nonce + tag)
+const MIN_LEGACY_PAYLOAD_SIZE: usize = NONCE_SIZE + TAG_SIZE;
+
+/// HKDF info string for entry encryption keys
+pub const SESSION_KEY_INFO: &[u8] = b"lockbox-session-key";
+
+/// HKDF info string for blind-index (search) keys
+pub const SEARCH_KEY_INFO: &[u8] = b"lockbox-search-key-v1";
+
+/// Domain separator for the deterministic per-wallet salt
+pub const SALT_DOMAIN: &[u8] = b"lockbox-salt-v1";
+
+fn hkdf_expand(ikm_parts: &[&[u8]], salt: &[u8], info: &[u8]) -> [u8; 32] {
+    let ikm: Vec<u8> = ikm_parts.concat();
+    let hk = Hkdf::<Sha256>::new(Some(salt), &ikm);
+    let mut okm = [0u8; 32];
+    // 32 bytes is always a valid HKDF-SHA256 output length
+    hk.expand(info, &mut okm).expect("valid HKDF output length");
+    okm
+}
+
+/// Deterministic per-wallet salt: `SHA256(pubkey || "lockbox-salt-v1")`
+pub fn deterministic_salt(wallet: &Pubkey) -> [u8; SALT_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(wallet.as_ref());
+    hasher.update(SALT_DOMAIN);
+    hasher.finalize().into()
+}
+
+/// Derive the 32-byte entry encryption key from a wallet signature
+pub fn derive_session_key(wallet: &Pubkey, signature: &[u8], salt: &[u8; SALT_SIZE]) -> [u8; 32] {
+    hkdf_expand(&[wallet.as_ref(), signature, salt], salt, SESSION_KEY_INFO)
+}
+
+/// Derive the session key using the deterministic per-wallet salt
+pub fn derive_session_key_deterministic(wallet: &Pubkey, signature: &[u8]) -> [u8; 32] {
+    derive_session_key(wallet, signature, &deterministic_salt(wallet))
+}
+
+/// Derive the blind-index key, domain-separated from the session key
+pub fn derive_search_key(wallet: &Pubkey, signature: &[u8]) -> [u8; 32] {
+    let salt = deterministic_salt(wallet);
+    hkdf_expand(&[wallet.as_ref(), signature, &salt], &salt, SEARCH_KEY_INFO)
+}
+
+/// Encrypt an entry with a fresh random nonce
+pub fn encrypt_entry(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>> {
+    encrypt_entry_with_rng(key, plaintext, &mut OsRng)
+}
+
+/// Encrypt an entry using the supplied RNG for the nonce
+pub fn encrypt_entry_with_rng(
+    key: &[u8; 32],
+    plaintext: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.fill_bytes(&mut nonce);
+    encrypt_entry_with_nonce(key, plaintext, &nonce)
+}
+
+/// Encrypt an entry with an explicit nonce
+///
+/// Reusing a nonce under the same key breaks confidentiality; prefer
+/// [`encrypt_entry`] unless the nonce is known to be unique.
+pub fn encrypt_entry_with_nonce(
+    key: &[u8; 32],
+    plaintext: &[u8],
+    nonce: &[u8; NONCE_SIZE],
+) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(nonce), plaintext)
+        .map_err(|_| ClientError::Encryption)?;
+
+    let mut payload = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE + ciphertext.len());
+    payload.extend_from_slice(&PAYLOAD_HEADER);
+    payload.extend_from_slice(nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(payload)
+}
+
+/// Decrypt an entry payload produced by [`encrypt_entry`]
+///
+/// Unframed payloads from before framing are accepted too. A legacy nonce
+/// can start with the framing header by chance, so a framed-looking payload
+/// that fails authentication is retried as legacy.
+pub fn decrypt_entry(key: &[u8; 32], payload: &[u8]) -> Result<Vec<u8>> {
+    if payload.len() < MIN_LEGACY_PAYLOAD_SIZE {
+        return Err(ClientError::PayloadTooShort(payload.len(), MIN_LEGACY_PAYLOAD_SIZE));
+    }
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let open = |body: &[u8]| {
+        let (nonce, ciphertext) = body.split_at(NONCE_SIZE);
+        cipher.decrypt(XNonce::from_slice(nonce), ciphertext)
+    };
+
+    if payload.len() >= MIN_PAYLOAD_SIZE && payload[..HEADER_SIZE] == PAYLOAD_HEADER {
+        if let Ok(plaintext) = open(&payload[HEADER_SIZE..]) {
+            return Ok(plaintext);
+        }
+    }
+    open(payload).map_err(|_| ClientError::Decryption)
+}
+
+/// Compute a blind index: `HMAC-SHA256(search_key, token)`
+pub fn blind_index(search_key: &[u8; 32], token: &str) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(search_key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(token.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// Compute the `title_hash` stored in `DataEntryHeader`
+///
+/// Titles are normalized (trimmed, lowercased) so lookups are case-insensitive.
+pub fn title_hash(search_key: &[u8; 32], title: &str) -> [u8; 32] {
+    blind_index(search_key, &title.trim().to_lowercase())
+}
+
+/// Compute the search index token of one keyword (lowercased)
+pub fn search_token(search_key: &[u8; 32], keyword: &str) -> [u8; 32] {
+    blind_index(search_key, &keyword.to_lowercase())
+}
+
+/// Compute the search index tokens of an entry's searchable text
+///
+/// The text is split into alphanumeric words; each distinct word gives one
+/// [`search_token`], keeping the first `MAX_SEARCH_TOKENS_PER_ENTRY`. Pass
+/// the result to `index_entry`.
+pub fn search_tokens(search_key: &[u8; 32], text: &str) -> Vec<[u8; 32]> {
+    let mut words: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let word = word.to_lowercase();
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+        .iter()
+        .take(lockbox::state::MAX_SEARCH_TOKENS_PER_ENTRY)
+        .map(|word| blind_index(search_key, word))
+        .collect()
+}
+
+/// Derive a client-assigned entry ID from a stable identifier (e.g. a UUID)
+///
+/// Truncated `SHA256(stable_id)` with the high bit set, so the ID never
+/// collides with program-assigned counter IDs. Pass the result as
+/// `client_entry_id` to `store_password_entry`.
+pub fn client_entry_id(stable_id: &[u8]) -> u64 {
+    let digest = Sha256::digest(stable_id);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes) | lockbox::state::MasterLockbox::CLIENT_ENTRY_ID_BIT
+}
@@ -0,0 +1,40 @@
+use thiserror::Error;
+
+/// Errors produced by the client SDK
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Account not found: {0}")]
+    AccountNotFound(String),
+
+    #[error("Account deserialization failed: {0}")]
+    Deserialize(String),
+
+    #[error("Account fetch failed: {0}")]
+    Fetch(String),
+
+    #[error("Encryption failed")]
+    Encryption,
+
+    #[error("Decryption failed: ciphertext is corrupt or key is wrong")]
+    Decryption,
+
+    #[error("Encrypted payload too short ({0} bytes, minimum {1})")]
+    PayloadTooShort(usize, usize),
+
+    #[error("Invalid key length (expected 32 bytes)")]
+    InvalidKeyLength,
+
+    #[error("Invalid Shamir parameters: {0}")]
+    InvalidShamirParameters(&'static str),
+
+    #[error("Invalid shares: {0}")]
+    InvalidShares(&'static str),
+
+    #[error("Invalid setup: {0}")]
+    InvalidSetup(&'static str),
+
+    #[error("On-chain setup does not match the plan: {0}")]
+    SetupMismatch(&'static str),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
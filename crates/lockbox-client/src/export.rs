@@ -0,0 +1,103 @@
+//! # Export Manifests
+//!
+//! A manifest records what left the vault in an export without revealing
+//! any of it: per entry the ID, type, ciphertext size and SHA-256 of the
+//! ciphertext. Commit [`ExportManifest::hash`] on-chain with
+//! `commit_export_manifest` and keep the manifest; re-hashing it later and
+//! matching the vault's `ExportLog` proves what was exported and when.
+//!
+//! ## Encoding (format version 1)
+//!
+//! `SHA256("lockbox-export-manifest" || borsh(manifest))`, with entries
+//! sorted by entry ID so the hash does not depend on chunk layout. Aliases
+//! (no ciphertext of their own) and trashed entries are left out; entries
+//! spanning chunks are hashed whole, so every chunk holding a segment must
+//! be passed.
+
+use anchor_lang::prelude::{borsh, Pubkey};
+use anchor_lang::AnchorSerialize;
+use lockbox::instructions::reassemble_entry;
+use lockbox::state::{PasswordEntryType, StorageChunk, EXPORT_MANIFEST_VERSION};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ClientError, Result};
+
+/// Domain separator for manifest hashes
+pub const MANIFEST_DOMAIN: &[u8] = b"lockbox-export-manifest";
+
+/// One exported entry
+#[derive(AnchorSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub entry_id: u64,
+    pub entry_type: PasswordEntryType,
+    pub size: u32,
+    /// SHA-256 of the entry's ciphertext
+    pub ciphertext_hash: [u8; 32],
+}
+
+/// Everything an export contained, minus the data itself
+#[derive(AnchorSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportManifest {
+    pub format_version: u8,
+    pub master_lockbox: Pubkey,
+    pub exported_at: i64,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl ExportManifest {
+    /// Build the manifest for every live entry in `chunks`
+    pub fn from_chunks(
+        master_lockbox: &Pubkey,
+        chunks: &[StorageChunk],
+        exported_at: i64,
+    ) -> Result<Self> {
+        let mut entries = Vec::new();
+        for chunk in chunks {
+            let live = chunk
+                .entry_headers
+                .iter()
+                .filter(|h| !h.is_alias() && !h.is_trashed() && !h.is_continuation());
+            for header in live {
+                let out_of_bounds =
+                    || ClientError::Deserialize(format!("entry {} is out of bounds", header.entry_id));
+                let ciphertext = if header.has_next() {
+                    reassemble_entry(chunks, header.entry_id).map_err(|_| out_of_bounds())?
+                } else {
+                    let start = header.offset as usize;
+                    chunk
+                        .encrypted_data
+                        .get(start..start + header.size as usize)
+                        .ok_or_else(out_of_bounds)?
+                        .to_vec()
+                };
+                entries.push(ManifestEntry {
+                    entry_id: header.entry_id,
+                    entry_type: header.entry_type,
+                    size: ciphertext.len() as u32,
+                    ciphertext_hash: Sha256::digest(&ciphertext).into(),
+                });
+            }
+        }
+        entries.sort_by_key(|entry| entry.entry_id);
+
+        Ok(Self {
+            format_version: EXPORT_MANIFEST_VERSION,
+            master_lockbox: *master_lockbox,
+            exported_at,
+            entries,
+        })
+    }
+
+    /// Number of entries, as passed to `commit_export_manifest`
+    pub fn entry_count(&self) -> u32 {
+        self.entries.len() as u32
+    }
+
+    /// Hash committed on-chain
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(MANIFEST_DOMAIN);
+        hasher.update(self.try_to_vec().expect("manifest serializes"));
+        hasher.finalize().into()
+    }
+}
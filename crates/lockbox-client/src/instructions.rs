@@ -0,0 +1,3202 @@
+//! # Instruction Builders
+//!
+//! One builder per program instruction. Each builder derives the required
+//! PDAs, encodes the Anchor instruction data, and returns an `Instruction`
+//! ready to be added to a transaction.
+//!
+//! Builders take the vault owner (or the acting guardian/contact) as plain
+//! pubkeys; signing is left to the caller's transaction layer.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use lockbox::state::{
+    CooldownMode, EmergencyAccessLevel, InboxItemKind, PasswordEntryType, SharedVaultGate, SharedVaultRole, StorageType,
+    SubscriptionTier,
+};
+use lockbox::instructions::{NewEmergencyContact, NewGuardian, NewPasswordEntry};
+use lockbox::{accounts, instruction};
+
+use crate::export::ExportManifest;
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: lockbox::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// ============================================================================
+// Vault Setup
+// ============================================================================
+
+/// Build `initialize_master_lockbox`
+pub fn initialize_master_lockbox(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeMasterLockbox {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeMasterLockbox {},
+    )
+}
+
+/// Build `initialize_program_owned_lockbox`
+///
+/// `owner` is a PDA of `program_id`; the instruction only succeeds when
+/// `program_id` invokes it with `owner` signing via `invoke_signed`.
+pub fn initialize_program_owned_lockbox(
+    owner: &Pubkey,
+    program_id: Pubkey,
+    owner_seeds: Vec<Vec<u8>>,
+) -> Instruction {
+    build(
+        accounts::InitializeMasterLockbox {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProgramOwnedLockbox {
+            program_id,
+            owner_seeds,
+        },
+    )
+}
+
+/// Build `initialize_storage_chunk`
+///
+/// `chunk_index` must be the master lockbox's `storage_chunks_count` or the
+/// index of a closed chunk; `MasterLockbox::next_chunk_index` picks one.
+pub fn initialize_storage_chunk(
+    owner: &Pubkey,
+    chunk_index: u16,
+    initial_capacity: u32,
+    data_type: StorageType,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::InitializeStorageChunk {
+            master_lockbox,
+            storage_chunk: pda::storage_chunk(&master_lockbox, chunk_index).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeStorageChunk {
+            chunk_index,
+            initial_capacity,
+            data_type,
+        },
+    )
+}
+
+/// Build `expand_chunk` (owner pays the additional rent)
+///
+/// Pass `device` when a writer lease is held; it must co-sign.
+pub fn expand_chunk(
+    owner: &Pubkey,
+    chunk_index: u16,
+    additional_size: u32,
+    device: Option<Pubkey>,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::ExpandChunk {
+            master_lockbox,
+            storage_chunk: pda::storage_chunk(&master_lockbox, chunk_index).0,
+            owner: *owner,
+            payer: *owner,
+            system_program: system_program::ID,
+            device,
+        },
+        instruction::ExpandChunk { additional_size },
+    )
+}
+
+/// Build `can_store`
+///
+/// Simulate it and decode the return data with
+/// `PreflightResult::try_from_slice` to explain a failing store up front.
+pub fn can_store(owner: &Pubkey, chunk_index: u16, size: u32) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::CanStore {
+            master_lockbox,
+            storage_chunk,
+            security_policy: pda::security_policy(&master_lockbox).0,
+        },
+        instruction::CanStore { chunk_index, size },
+    )
+}
+
+/// Build `can_expand` (simulate and decode like `can_store`)
+pub fn can_expand(owner: &Pubkey, chunk_index: u16, additional_size: u32) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::CanExpand {
+            master_lockbox,
+            storage_chunk,
+        },
+        instruction::CanExpand {
+            chunk_index,
+            additional_size,
+        },
+    )
+}
+
+/// Build `shrink_chunk`
+///
+/// Pass `device` when a writer lease is held; it must co-sign.
+pub fn shrink_chunk(
+    owner: &Pubkey,
+    chunk_index: u16,
+    reduce_by: u32,
+    device: Option<Pubkey>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::ShrinkChunk {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            device,
+        },
+        instruction::ShrinkChunk {
+            chunk_index,
+            reduce_by,
+        },
+    )
+}
+
+/// Build `migrate_archived_entries`
+///
+/// `cold_chunk_index` must be a chunk created with `StorageType::ArchivedPasswords`.
+/// Pass `device` when a writer lease is held; it must co-sign.
+pub fn migrate_archived_entries(
+    owner: &Pubkey,
+    source_chunk_index: u16,
+    cold_chunk_index: u16,
+    max_entries: u8,
+    device: Option<Pubkey>,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::MigrateArchivedEntries {
+            master_lockbox,
+            source_chunk: pda::storage_chunk(&master_lockbox, source_chunk_index).0,
+            cold_chunk: pda::storage_chunk(&master_lockbox, cold_chunk_index).0,
+            owner: *owner,
+            device,
+        },
+        instruction::MigrateArchivedEntries {
+            source_chunk_index,
+            cold_chunk_index,
+            max_entries,
+        },
+    )
+}
+
+/// Build `acquire_writer_lease`; `device` must co-sign
+pub fn acquire_writer_lease(owner: &Pubkey, device: &Pubkey, duration: i64) -> Instruction {
+    build(
+        accounts::ManageWriterLease {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            device: *device,
+        },
+        instruction::AcquireWriterLease { duration },
+    )
+}
+
+/// Build `release_writer_lease`; `device` must co-sign
+pub fn release_writer_lease(owner: &Pubkey, device: &Pubkey) -> Instruction {
+    build(
+        accounts::ManageWriterLease {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            device: *device,
+        },
+        instruction::ReleaseWriterLease {},
+    )
+}
+
+/// Build `emit_chunk_report`; any wallet may submit it for `owner`'s vault
+pub fn emit_chunk_report(owner: &Pubkey, chunk_index: u16) -> Instruction {
+    build(
+        accounts::EmitChunkReport {
+            storage_chunk: pda::storage_chunk_for_owner(owner, chunk_index).0,
+        },
+        instruction::EmitChunkReport { chunk_index },
+    )
+}
+
+// ============================================================================
+// Password Entries
+// ============================================================================
+
+fn entry_accounts(owner: &Pubkey, chunk_index: u16) -> (Pubkey, Pubkey) {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let storage_chunk = pda::storage_chunk(&master_lockbox, chunk_index).0;
+    (master_lockbox, storage_chunk)
+}
+
+/// Build `store_password_entry`
+///
+/// `encrypted_data` should come from [`crate::crypto::encrypt_entry`].
+/// `client_entry_id` (see [`crate::crypto::client_entry_id`]) pins a stable
+/// ID, reserved in the vault's client entry ID account (create it first with
+/// [`initialize_client_entry_ids`]); `None` lets the program assign the next
+/// counter ID.
+pub fn store_password_entry(
+    owner: &Pubkey,
+    chunk_index: u16,
+    encrypted_data: Vec<u8>,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+    client_entry_id: Option<u64>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::StorePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            category_index: None,
+            treasury: Some(pda::treasury().0),
+            payer: None,
+            system_program: Some(system_program::ID),
+            client_entry_ids: client_entry_id.map(|_| pda::client_entry_ids(&master_lockbox).0),
+        },
+        instruction::StorePasswordEntry {
+            chunk_index,
+            encrypted_data,
+            entry_type,
+            category,
+            title_hash,
+            client_entry_id,
+        },
+    )
+}
+
+/// Build `initialize_client_entry_ids`
+///
+/// `chunk_indexes` must list every chunk registered in the master lockbox;
+/// they are passed read-only so existing client IDs can be reserved.
+pub fn initialize_client_entry_ids(owner: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::InitializeClientEntryIds {
+            master_lockbox,
+            client_entry_ids: pda::client_entry_ids(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeClientEntryIds {},
+    );
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new_readonly(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+/// Build `store_password_entries_batch`
+///
+/// Writes up to `MAX_STORE_BATCH` entries into one chunk with consecutive
+/// counter IDs. [`with_auto_expand`] and [`with_category_registry`] apply
+/// as for `store_password_entry`.
+pub fn store_password_entries_batch(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entries: Vec<NewPasswordEntry>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::StorePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            category_index: None,
+            treasury: Some(pda::treasury().0),
+            payer: None,
+            system_program: Some(system_program::ID),
+            client_entry_ids: None,
+        },
+        instruction::StorePasswordEntriesBatch { chunk_index, entries },
+    )
+}
+
+/// Let a `store_password_entry` instruction expand its chunk
+///
+/// When the chunk is short by at most `AUTO_EXPAND_MAX_MARGIN` bytes, the
+/// store reallocs it first with `payer` covering the extra rent.
+pub fn with_auto_expand(mut ix: Instruction, payer: &Pubkey) -> Instruction {
+    // Omitted optional accounts are encoded as the program ID; payer and
+    // system_program come just before client_entry_ids, the last account
+    let len = ix.accounts.len();
+    ix.accounts[len - 3] = AccountMeta::new(*payer, true);
+    ix.accounts[len - 2] = AccountMeta::new_readonly(system_program::ID, false);
+    ix
+}
+
+/// Build `store_spanning_entry`
+///
+/// `chunk_indexes` are filled in order until the ciphertext is placed;
+/// list enough chunks to cover its size. Pass `device` when a writer lease
+/// is held; it must co-sign.
+pub fn store_spanning_entry(
+    owner: &Pubkey,
+    chunk_indexes: &[u16],
+    encrypted_data: Vec<u8>,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+    device: Option<Pubkey>,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let ix = build(
+        accounts::StoreSpanningEntry {
+            master_lockbox,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            category_index: None,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            device,
+        },
+        instruction::StoreSpanningEntry {
+            encrypted_data,
+            entry_type,
+            category,
+            title_hash,
+        },
+    );
+    with_chunks(ix, owner, chunk_indexes)
+}
+
+/// Build `retrieve_spanning_entry`; `chunk_indexes` must include every
+/// chunk holding a segment of the entry
+pub fn retrieve_spanning_entry(owner: &Pubkey, entry_id: u64, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let ix = build(
+        accounts::RetrieveSpanningEntry {
+            master_lockbox,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+        },
+        instruction::RetrieveSpanningEntry { entry_id },
+    );
+    with_chunks(ix, owner, chunk_indexes)
+}
+
+/// Build `delete_spanning_entry`; `chunk_indexes` must include every
+/// chunk holding a segment of the entry
+///
+/// Pass `device` when a writer lease is held; it must co-sign.
+pub fn delete_spanning_entry(
+    owner: &Pubkey,
+    entry_id: u64,
+    chunk_indexes: &[u16],
+    device: Option<Pubkey>,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let ix = build(
+        accounts::DeleteSpanningEntry {
+            master_lockbox,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            category_index: None,
+            delete_cosigner: None,
+            device,
+        },
+        instruction::DeleteSpanningEntry { entry_id },
+    );
+    with_chunks(ix, owner, chunk_indexes)
+}
+
+/// Build `retrieve_password_entry`
+///
+/// Set `simulate` for RPC simulation reads that should not count as an
+/// access or start a read cooldown.
+pub fn retrieve_password_entry(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    simulate: bool,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::RetrievePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+        },
+        instruction::RetrievePasswordEntry {
+            chunk_index,
+            entry_id,
+            simulate,
+        },
+    )
+}
+
+/// Build `update_password_entry`
+///
+/// `expected_layout_epoch` is the chunk's `layout_epoch` as last fetched.
+pub fn update_password_entry(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    new_encrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::UpdatePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            entry_copies: pda::entry_copies(&master_lockbox, entry_id).0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+        },
+        instruction::UpdatePasswordEntry {
+            chunk_index,
+            entry_id,
+            new_encrypted_data,
+            expected_layout_epoch,
+        },
+    )
+}
+
+/// Build `delete_password_entry`
+///
+/// `expected_layout_epoch` is the chunk's `layout_epoch` as last fetched.
+pub fn delete_password_entry(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    expected_layout_epoch: u32,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::DeletePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            category_registry: None,
+            category_index: None,
+            entry_copies: pda::entry_copies(&master_lockbox, entry_id).0,
+            delete_cosigner: None,
+        },
+        instruction::DeletePasswordEntry {
+            chunk_index,
+            entry_id,
+            expected_layout_epoch,
+        },
+    )
+}
+
+/// Build `delete_password_entry`, also removing aliases held in `alias_chunks`
+///
+/// Aliases in the entry's own chunk are always removed; list any other
+/// chunks that may hold aliases of it.
+pub fn delete_password_entry_with_aliases(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    expected_layout_epoch: u32,
+    alias_chunks: &[u16],
+) -> Instruction {
+    with_chunks(
+        delete_password_entry(owner, chunk_index, entry_id, expected_layout_epoch),
+        owner,
+        alias_chunks,
+    )
+}
+
+/// Build `copy_entry_to_vault`
+///
+/// `next_entry_id` is the receiving vault's current `next_entry_id`, which
+/// the copy is assigned. `reencrypted_data` is the source entry decrypted
+/// and re-encrypted under the receiving vault's key.
+/// `expected_layout_epoch` is the source chunk's `layout_epoch` as fetched
+/// when the entry was read.
+#[allow(clippy::too_many_arguments)]
+pub fn copy_entry_to_vault(
+    source_owner: &Pubkey,
+    source_chunk_index: u16,
+    source_entry_id: u64,
+    owner: &Pubkey,
+    chunk_index: u16,
+    next_entry_id: u64,
+    reencrypted_data: Vec<u8>,
+    title_hash: [u8; 32],
+    expected_layout_epoch: u32,
+) -> Instruction {
+    let (source_master_lockbox, source_chunk) = entry_accounts(source_owner, source_chunk_index);
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::CopyEntryToVault {
+            source_master_lockbox,
+            source_chunk,
+            entry_copies: pda::entry_copies(&source_master_lockbox, source_entry_id).0,
+            source_owner: *source_owner,
+            master_lockbox,
+            storage_chunk,
+            entry_provenance: pda::entry_provenance(&master_lockbox, next_entry_id).0,
+            owner: *owner,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            treasury: Some(pda::treasury().0),
+            system_program: system_program::ID,
+            deny_list: pda::deny_list(&source_master_lockbox).0,
+        },
+        instruction::CopyEntryToVault {
+            source_chunk_index,
+            chunk_index,
+            source_entry_id,
+            reencrypted_data,
+            title_hash,
+            expected_layout_epoch,
+        },
+    )
+}
+
+/// Build `list_entry_grants`
+///
+/// Simulate it and decode the return data with
+/// `Vec::<EntryGrant>::try_from_slice`.
+pub fn list_entry_grants(owner: &Pubkey, chunk_index: u16, entry_id: u64) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::ListEntryGrants {
+            master_lockbox,
+            storage_chunk,
+            entry_copies: pda::entry_copies(&master_lockbox, entry_id).0,
+            emergency_access: pda::emergency_access(owner).0,
+        },
+        instruction::ListEntryGrants {
+            chunk_index,
+            entry_id,
+        },
+    )
+}
+
+/// Build `move_password_entry`
+///
+/// List any chunks other than the source that may hold aliases of the entry
+/// in `alias_chunks` so they are re-pointed too. Pass `device` when a
+/// writer lease is held; it must co-sign. `expected_layout_epoch` is the
+/// source chunk's `layout_epoch` as last fetched.
+#[allow(clippy::too_many_arguments)]
+pub fn move_password_entry(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    dest_chunk_index: u16,
+    expected_layout_epoch: u32,
+    alias_chunks: &[u16],
+    device: Option<Pubkey>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    let ix = build(
+        accounts::MovePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+            device,
+        },
+        instruction::MovePasswordEntry {
+            chunk_index,
+            entry_id,
+            dest_chunk_index,
+            expected_layout_epoch,
+        },
+    );
+    let extra: Vec<u16> = std::iter::once(dest_chunk_index)
+        .chain(alias_chunks.iter().copied().filter(|i| *i != dest_chunk_index))
+        .collect();
+    with_chunks(ix, owner, &extra)
+}
+
+/// Append `owner`'s chunks as writable remaining accounts
+///
+/// Used by instructions that act on several chunks at once; the program
+/// checks each against the master lockbox's chunk list.
+pub fn with_chunks(mut ix: Instruction, owner: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+/// Build `create_entry_alias`
+pub fn create_entry_alias(
+    owner: &Pubkey,
+    chunk_index: u16,
+    target_chunk_index: u16,
+    target_entry_id: u64,
+    category: u32,
+    title_hash: [u8; 32],
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::CreateEntryAlias {
+            master_lockbox,
+            storage_chunk,
+            target_chunk: pda::storage_chunk(&master_lockbox, target_chunk_index).0,
+            owner: *owner,
+        },
+        instruction::CreateEntryAlias {
+            chunk_index,
+            target_chunk_index,
+            target_entry_id,
+            category,
+            title_hash,
+        },
+    )
+}
+
+/// Build `set_entry_archived`
+pub fn set_entry_archived(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    archived: bool,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::SetEntryArchived {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::SetEntryArchived {
+            chunk_index,
+            entry_id,
+            archived,
+        },
+    )
+}
+
+/// Build `set_entry_flags` (`DataEntryHeader::USER_FLAGS` bits)
+pub fn set_entry_flags(owner: &Pubkey, chunk_index: u16, entry_id: u64, flags: u8) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::SetEntryArchived {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::SetEntryFlags {
+            chunk_index,
+            entry_id,
+            flags,
+        },
+    )
+}
+
+/// Build `attach_totp`
+pub fn attach_totp(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    parent_chunk_index: u16,
+    parent_entry_id: u64,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::AttachTotp {
+            master_lockbox,
+            storage_chunk,
+            parent_chunk: pda::storage_chunk(&master_lockbox, parent_chunk_index).0,
+            owner: *owner,
+        },
+        instruction::AttachTotp {
+            chunk_index,
+            entry_id,
+            parent_chunk_index,
+            parent_entry_id,
+        },
+    )
+}
+
+/// Build `detach_totp`
+pub fn detach_totp(owner: &Pubkey, chunk_index: u16, entry_id: u64) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::SetEntryArchived {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::DetachTotp {
+            chunk_index,
+            entry_id,
+        },
+    )
+}
+
+fn link_accounts(owner: &Pubkey, chunk_index: u16, linked_chunk_index: u16) -> accounts::LinkEntries {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    accounts::LinkEntries {
+        master_lockbox,
+        storage_chunk,
+        linked_chunk: (linked_chunk_index != chunk_index)
+            .then(|| pda::storage_chunk(&master_lockbox, linked_chunk_index).0),
+        owner: *owner,
+        system_program: system_program::ID,
+    }
+}
+
+/// Build `link_entries`
+///
+/// Passes the second chunk only when the entries are in different chunks.
+pub fn link_entries(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    linked_chunk_index: u16,
+    linked_entry_id: u64,
+) -> Instruction {
+    build(
+        link_accounts(owner, chunk_index, linked_chunk_index),
+        instruction::LinkEntries {
+            chunk_index,
+            entry_id,
+            linked_chunk_index,
+            linked_entry_id,
+        },
+    )
+}
+
+/// Build `unlink_entries`
+pub fn unlink_entries(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    linked_chunk_index: u16,
+    linked_entry_id: u64,
+) -> Instruction {
+    build(
+        link_accounts(owner, chunk_index, linked_chunk_index),
+        instruction::UnlinkEntries {
+            chunk_index,
+            entry_id,
+            linked_chunk_index,
+            linked_entry_id,
+        },
+    )
+}
+
+/// Build `initialize_search_index`
+pub fn initialize_search_index(owner: &Pubkey, chunk_index: u16, initial_capacity: u32) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::InitializeStorageChunk {
+            master_lockbox,
+            storage_chunk: pda::storage_chunk(&master_lockbox, chunk_index).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeSearchIndex {
+            chunk_index,
+            initial_capacity,
+        },
+    )
+}
+
+/// Build `index_entry`
+///
+/// `tokens` should come from [`crate::crypto::search_tokens`]. Add it to the
+/// transaction that stores or updates the entry.
+pub fn index_entry(
+    owner: &Pubkey,
+    index_chunk_index: u16,
+    entry_id: u64,
+    tokens: Vec<[u8; 32]>,
+) -> Instruction {
+    let (master_lockbox, index_chunk) = entry_accounts(owner, index_chunk_index);
+    build(
+        accounts::UpdateSearchIndex {
+            master_lockbox,
+            index_chunk,
+            owner: *owner,
+        },
+        instruction::IndexEntry {
+            index_chunk_index,
+            entry_id,
+            tokens,
+        },
+    )
+}
+
+/// Build `deindex_entry`
+///
+/// Add it to the transaction that deletes the entry.
+pub fn deindex_entry(owner: &Pubkey, index_chunk_index: u16, entry_id: u64) -> Instruction {
+    let (master_lockbox, index_chunk) = entry_accounts(owner, index_chunk_index);
+    build(
+        accounts::UpdateSearchIndex {
+            master_lockbox,
+            index_chunk,
+            owner: *owner,
+        },
+        instruction::DeindexEntry {
+            index_chunk_index,
+            entry_id,
+        },
+    )
+}
+
+/// Build `search_index`
+///
+/// `token` comes from [`crate::crypto::search_token`]. Simulate it and
+/// decode the return data with `Vec::<u64>::try_from_slice`.
+pub fn search_index(owner: &Pubkey, index_chunk_index: u16, token: [u8; 32]) -> Instruction {
+    let (master_lockbox, index_chunk) = entry_accounts(owner, index_chunk_index);
+    build(
+        accounts::QuerySearchIndex {
+            master_lockbox,
+            index_chunk,
+        },
+        instruction::SearchIndex {
+            index_chunk_index,
+            token,
+        },
+    )
+}
+
+/// Build `add_search_tokens`
+///
+/// `chunk_index` is the chunk holding the entry and `tokens` the same
+/// [`crate::crypto::search_tokens`] passed to [`index_entry`]. Add it to the
+/// transaction that stores or updates the entry.
+pub fn add_search_tokens(owner: &Pubkey, chunk_index: u16, tokens: Vec<[u8; 32]>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::AddSearchTokens {
+            master_lockbox,
+            search_filter: pda::search_filter(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::AddSearchTokens { chunk_index, tokens },
+    )
+}
+
+/// Build `check_token`
+///
+/// Simulate it and decode the return data with `bool::try_from_slice`;
+/// pass `chunk_index` to check a single chunk instead of the whole vault.
+pub fn check_token(owner: &Pubkey, token: [u8; 32], chunk_index: Option<u16>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::QuerySearchFilter {
+            master_lockbox,
+            search_filter: pda::search_filter(&master_lockbox).0,
+        },
+        instruction::CheckToken { token, chunk_index },
+    )
+}
+
+/// Build `reset_search_filter`
+pub fn reset_search_filter(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::ResetSearchFilter {
+            master_lockbox,
+            search_filter: pda::search_filter(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::ResetSearchFilter {},
+    )
+}
+
+/// Build `close_search_filter`
+pub fn close_search_filter(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseSearchFilter {
+            master_lockbox,
+            search_filter: pda::search_filter(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CloseSearchFilter {},
+    )
+}
+
+/// Build `find_entries_by_title`
+///
+/// `title_hash` comes from [`crate::crypto::title_hash`] and `chunk_indexes`
+/// lists the chunks to search, passed read-only. Simulate it and decode the
+/// return data with `Vec::<EntryLocation>::try_from_slice`.
+pub fn find_entries_by_title(owner: &Pubkey, title_hash: [u8; 32], chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::FindEntriesByTitle { master_lockbox },
+        instruction::FindEntriesByTitle { title_hash },
+    );
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new_readonly(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+/// Build `set_rotation_date`
+///
+/// `rotate_after` is a Unix timestamp; 0 clears the schedule.
+pub fn set_rotation_date(owner: &Pubkey, chunk_index: u16, entry_id: u64, rotate_after: i64) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::SetEntryArchived {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::SetRotationDate {
+            chunk_index,
+            entry_id,
+            rotate_after,
+        },
+    )
+}
+
+/// Build `emit_rotation_due` for a chunk of the vault owned by `owner`
+///
+/// Any account may sign the transaction.
+pub fn emit_rotation_due(owner: &Pubkey, chunk_index: u16) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::EmitRotationDue {
+            master_lockbox,
+            storage_chunk,
+        },
+        instruction::EmitRotationDue { chunk_index },
+    )
+}
+
+/// Build `tag_entry`
+///
+/// `tag_hash` is the client-side `HMAC(tag_key, tag_name)`.
+pub fn tag_entry(owner: &Pubkey, chunk_index: u16, entry_id: u64, tag_hash: [u8; 32]) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::TagEntry {
+            master_lockbox,
+            storage_chunk,
+            tag_registry: pda::tag_registry(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::TagEntry {
+            chunk_index,
+            entry_id,
+            tag_hash,
+        },
+    )
+}
+
+/// Build `untag_entry`
+pub fn untag_entry(owner: &Pubkey, chunk_index: u16, entry_id: u64, tag_hash: [u8; 32]) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::TagEntry {
+            master_lockbox,
+            storage_chunk,
+            tag_registry: pda::tag_registry(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::UntagEntry {
+            chunk_index,
+            entry_id,
+            tag_hash,
+        },
+    )
+}
+
+/// Build `delete_tag`
+///
+/// `chunk_indexes` must list every chunk of the vault.
+pub fn delete_tag(owner: &Pubkey, tag_hash: [u8; 32], chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let ix = build(
+        accounts::DeleteTag {
+            master_lockbox,
+            tag_registry: pda::tag_registry(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::DeleteTag { tag_hash },
+    );
+    with_chunks(ix, owner, chunk_indexes)
+}
+
+/// Build `list_tagged_entries`
+///
+/// Simulate it once per chunk and decode the return data with
+/// `Vec::<u64>::try_from_slice`.
+pub fn list_tagged_entries(owner: &Pubkey, chunk_index: u16, tag_hash: [u8; 32]) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::ListTaggedEntries {
+            master_lockbox,
+            storage_chunk,
+            tag_registry: pda::tag_registry(&master_lockbox).0,
+        },
+        instruction::ListTaggedEntries {
+            chunk_index,
+            tag_hash,
+        },
+    )
+}
+
+/// Build `close_tag_registry`
+pub fn close_tag_registry(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseTagRegistry {
+            master_lockbox,
+            tag_registry: pda::tag_registry(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CloseTagRegistry {},
+    )
+}
+
+/// Build `trash_password_entry`
+pub fn trash_password_entry(owner: &Pubkey, chunk_index: u16, entry_id: u64) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::TrashPasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::TrashPasswordEntry {
+            chunk_index,
+            entry_id,
+        },
+    )
+}
+
+/// Build `restore_password_entry`
+pub fn restore_password_entry(owner: &Pubkey, chunk_index: u16, entry_id: u64) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::RestorePasswordEntry {
+            master_lockbox,
+            storage_chunk,
+            owner: *owner,
+        },
+        instruction::RestorePasswordEntry {
+            chunk_index,
+            entry_id,
+        },
+    )
+}
+
+/// Build `update_settings` (empty `encrypted_settings` clears them)
+pub fn update_settings(owner: &Pubkey, encrypted_settings: Vec<u8>) -> Instruction {
+    build(
+        accounts::UpdateSettings {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::UpdateSettings { encrypted_settings },
+    )
+}
+
+/// Build `set_trash_retention` (seconds)
+pub fn set_trash_retention(owner: &Pubkey, retention: i64) -> Instruction {
+    build(
+        accounts::SetTrashRetention {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::SetTrashRetention { retention },
+    )
+}
+
+/// Build `purge_expired_trash`; any wallet may submit it for `owner`'s vault
+pub fn purge_expired_trash(owner: &Pubkey, chunk_index: u16) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::PurgeExpiredTrash {
+            master_lockbox,
+            storage_chunk,
+            category_registry: None,
+            category_index: None,
+        },
+        instruction::PurgeExpiredTrash { chunk_index },
+    )
+}
+
+/// Build `set_recent_entries_tracking`
+pub fn set_recent_entries_tracking(owner: &Pubkey, enabled: bool) -> Instruction {
+    build(
+        accounts::SetRecentEntriesTracking {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::SetRecentEntriesTracking { enabled },
+    )
+}
+
+/// Build `update_security_policy`
+///
+/// Pass `current_cosigner` once the policy names a delete co-signer; it must
+/// sign policy changes too.
+pub fn update_security_policy(
+    owner: &Pubkey,
+    current_cosigner: Option<Pubkey>,
+    delete_cosigner: Option<Pubkey>,
+    read_cooldown: u16,
+    padded_sizes: bool,
+    privacy_flags: u8,
+    freeze_on_honeytoken: bool,
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::UpdateSecurityPolicy {
+            master_lockbox,
+            security_policy: pda::security_policy(&master_lockbox).0,
+            owner: *owner,
+            delete_cosigner: current_cosigner,
+            system_program: system_program::ID,
+        },
+        instruction::UpdateSecurityPolicy {
+            delete_cosigner,
+            read_cooldown,
+            padded_sizes,
+            privacy_flags,
+            freeze_on_honeytoken,
+        },
+    )
+}
+
+/// Add the policy's delete co-signer to a `delete_password_entry` instruction
+pub fn with_delete_cosigner(mut ix: Instruction, cosigner: &Pubkey) -> Instruction {
+    // The co-signer placeholder is the last optional account
+    if let Some(meta) = ix.accounts.iter_mut().rev().find(|m| m.pubkey == lockbox::ID) {
+        *meta = AccountMeta::new_readonly(*cosigner, true);
+    }
+    ix
+}
+
+/// Build `append_entry_note`
+///
+/// `encrypted_note` should come from [`crate::crypto::encrypt_entry`].
+pub fn append_entry_note(
+    owner: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    encrypted_note: Vec<u8>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::AppendEntryNote {
+            master_lockbox,
+            storage_chunk,
+            entry_notes: pda::entry_notes(&master_lockbox, entry_id).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::AppendEntryNote {
+            chunk_index,
+            entry_id,
+            encrypted_note,
+        },
+    )
+}
+
+/// Build `close_entry_notes`
+pub fn close_entry_notes(owner: &Pubkey, entry_id: u64) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseEntryNotes {
+            master_lockbox,
+            entry_notes: pda::entry_notes(&master_lockbox, entry_id).0,
+            owner: *owner,
+        },
+        instruction::CloseEntryNotes { entry_id },
+    )
+}
+
+// ============================================================================
+// Subscriptions
+// ============================================================================
+
+/// Build `upgrade_subscription`
+pub fn upgrade_subscription(
+    owner: &Pubkey,
+    fee_receiver: &Pubkey,
+    new_tier: SubscriptionTier,
+) -> Instruction {
+    build(
+        accounts::UpgradeSubscription {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            fee_receiver: *fee_receiver,
+            system_program: system_program::ID,
+        },
+        instruction::UpgradeSubscription { new_tier },
+    )
+}
+
+/// Build `renew_subscription`
+pub fn renew_subscription(owner: &Pubkey, fee_receiver: &Pubkey) -> Instruction {
+    build(
+        accounts::RenewSubscription {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            fee_receiver: *fee_receiver,
+            system_program: system_program::ID,
+        },
+        instruction::RenewSubscription {},
+    )
+}
+
+/// Build `downgrade_subscription`
+pub fn downgrade_subscription(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::DowngradeSubscription {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::DowngradeSubscription {},
+    )
+}
+
+/// Build `process_subscription_expiry` for `owner`'s vault (anyone may send it)
+pub fn process_subscription_expiry(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ProcessSubscriptionExpiry { master_lockbox: pda::master_lockbox(owner).0 },
+        instruction::ProcessSubscriptionExpiry {},
+    )
+}
+
+/// Build `set_dunning_window`
+pub fn set_dunning_window(owner: &Pubkey, window: i64) -> Instruction {
+    build(
+        accounts::SetDunningWindow {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::SetDunningWindow { window },
+    )
+}
+
+/// Build `set_write_burst_limit`
+pub fn set_write_burst_limit(owner: &Pubkey, limit: u32) -> Instruction {
+    build(
+        accounts::SetWriteBurstLimit {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::SetWriteBurstLimit { limit },
+    )
+}
+
+/// Build `purchase_capacity_addon`; the payment goes to the treasury PDA
+pub fn purchase_capacity_addon(owner: &Pubkey, packs: u8) -> Instruction {
+    build(
+        accounts::PurchaseCapacityAddon {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            treasury: pda::treasury().0,
+            system_program: system_program::ID,
+        },
+        instruction::PurchaseCapacityAddon { packs },
+    )
+}
+
+/// Build `initialize_billing_receipts`
+pub fn initialize_billing_receipts(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::InitializeBillingReceipts {
+            master_lockbox,
+            billing_receipts: pda::billing_receipts(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeBillingReceipts {},
+    )
+}
+
+/// Build `emit_billing_snapshot` for `owner`'s vault
+///
+/// Anyone may send it; pass `record_receipt` when the owner has created a
+/// billing receipts account.
+pub fn emit_billing_snapshot(owner: &Pubkey, record_receipt: bool) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::EmitBillingSnapshot {
+            master_lockbox,
+            billing_receipts: record_receipt.then(|| pda::billing_receipts(&master_lockbox).0),
+        },
+        instruction::EmitBillingSnapshot {},
+    )
+}
+
+// ============================================================================
+// Categories
+// ============================================================================
+
+fn category_accounts(owner: &Pubkey) -> (Pubkey, Pubkey) {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    (master_lockbox, pda::category_registry(&master_lockbox).0)
+}
+
+/// Build `initialize_category_registry`
+pub fn initialize_category_registry(owner: &Pubkey) -> Instruction {
+    let (master_lockbox, category_registry) = category_accounts(owner);
+    build(
+        accounts::InitializeCategoryRegistry {
+            master_lockbox,
+            category_registry,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeCategoryRegistry {},
+    )
+}
+
+/// Build `create_category`
+pub fn create_category(
+    owner: &Pubkey,
+    name_encrypted: Vec<u8>,
+    icon: u8,
+    color: u8,
+    parent_id: Option<u8>,
+) -> Instruction {
+    let (master_lockbox, category_registry) = category_accounts(owner);
+    build(
+        accounts::CreateCategory {
+            master_lockbox,
+            category_registry,
+            owner: *owner,
+        },
+        instruction::CreateCategory {
+            name_encrypted,
+            icon,
+            color,
+            parent_id,
+        },
+    )
+}
+
+/// Build `update_category`
+pub fn update_category(
+    owner: &Pubkey,
+    category_id: u8,
+    name_encrypted: Option<Vec<u8>>,
+    icon: Option<u8>,
+    color: Option<u8>,
+    parent_id: Option<Option<u8>>,
+) -> Instruction {
+    let (master_lockbox, category_registry) = category_accounts(owner);
+    build(
+        accounts::UpdateCategory {
+            master_lockbox,
+            category_registry,
+            owner: *owner,
+        },
+        instruction::UpdateCategory {
+            category_id,
+            name_encrypted,
+            icon,
+            color,
+            parent_id,
+        },
+    )
+}
+
+/// Build `emit_category_summary`; any wallet may submit it for `owner`'s vault
+pub fn emit_category_summary(owner: &Pubkey) -> Instruction {
+    let (_, category_registry) = category_accounts(owner);
+    build(
+        accounts::EmitCategorySummary { category_registry },
+        instruction::EmitCategorySummary {},
+    )
+}
+
+/// Pass `owner`'s category registry to an entry instruction
+///
+/// Applies to `store_password_entry`, `store_password_entries_batch`,
+/// `store_spanning_entry`, `update_password_entry`, `delete_password_entry`,
+/// `delete_spanning_entry` and `purge_expired_trash`, which keep the per-category entry and byte counts current when the
+/// registry is present, and to `rebuild_indexes`, which recounts them. Once
+/// the vault has a registry, entry instructions fail without it.
+pub fn with_category_registry(mut ix: Instruction, owner: &Pubkey) -> Instruction {
+    let (_, category_registry) = category_accounts(owner);
+    // Omitted optional accounts are encoded as the program ID
+    if let Some(meta) = ix.accounts.iter_mut().find(|m| m.pubkey == lockbox::ID) {
+        *meta = AccountMeta::new(category_registry, false);
+    }
+    ix
+}
+
+/// Pass `owner`'s category index to an entry instruction
+///
+/// Applies to `store_password_entry`, `store_password_entries_batch`,
+/// `store_spanning_entry`, `delete_password_entry`, `delete_spanning_entry`
+/// and `purge_expired_trash`, which add and remove entries in the index
+/// when it is present, and to `rebuild_indexes`, which rebuilds it. Once the
+/// vault has an index, those entry instructions fail without it. Combines with [`with_category_registry`] in either
+/// order.
+pub fn with_category_index(mut ix: Instruction, owner: &Pubkey) -> Instruction {
+    let (master_lockbox, category_registry) = category_accounts(owner);
+    // The index directly follows the registry slot, set or omitted
+    if let Some(at) = ix
+        .accounts
+        .iter()
+        .position(|m| m.pubkey == lockbox::ID || m.pubkey == category_registry)
+    {
+        ix.accounts[at + 1] = AccountMeta::new(pda::category_index(&master_lockbox).0, false);
+    }
+    ix
+}
+
+/// Build `initialize_category_index`
+pub fn initialize_category_index(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::InitializeCategoryIndex {
+            master_lockbox,
+            category_index: pda::category_index(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeCategoryIndex {},
+    )
+}
+
+/// Build `rebuild_category_index`
+///
+/// `chunk_indexes` must list every chunk registered in the master lockbox;
+/// they are passed read-only.
+pub fn rebuild_category_index(owner: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::RebuildCategoryIndex {
+            master_lockbox,
+            category_index: pda::category_index(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::RebuildCategoryIndex {},
+    );
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new_readonly(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+/// Build `rebuild_indexes`
+///
+/// `chunk_indexes` must list every chunk registered in the master lockbox;
+/// they are passed read-only. Add the category registry and index with
+/// [`with_category_registry`] and [`with_category_index`] to repair them too.
+/// Pass `device` when a writer lease is held; it must co-sign.
+pub fn rebuild_indexes(owner: &Pubkey, chunk_indexes: &[u16], device: Option<Pubkey>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::RebuildIndexes {
+            master_lockbox,
+            category_registry: None,
+            category_index: None,
+            owner: *owner,
+            system_program: system_program::ID,
+            device,
+        },
+        instruction::RebuildIndexes {},
+    );
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new_readonly(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+/// Build `list_category_entries`
+///
+/// Simulate it and decode the return data with `Vec::<u64>::try_from_slice`.
+pub fn list_category_entries(owner: &Pubkey, category: u32) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::QueryCategoryIndex {
+            master_lockbox,
+            category_index: pda::category_index(&master_lockbox).0,
+        },
+        instruction::ListCategoryEntries { category },
+    )
+}
+
+/// Build `close_category_index`
+pub fn close_category_index(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseCategoryIndex {
+            master_lockbox,
+            category_index: pda::category_index(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CloseCategoryIndex {},
+    )
+}
+
+/// Build `delete_category`
+pub fn delete_category(owner: &Pubkey, category_id: u8) -> Instruction {
+    let (master_lockbox, category_registry) = category_accounts(owner);
+    build(
+        accounts::DeleteCategory {
+            master_lockbox,
+            category_registry,
+            owner: *owner,
+        },
+        instruction::DeleteCategory { category_id },
+    )
+}
+
+// ============================================================================
+// Account Closing
+// ============================================================================
+
+/// Build `request_vault_deletion`
+pub fn request_vault_deletion(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::VaultDeletion {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::RequestVaultDeletion {},
+    )
+}
+
+/// Build `cancel_vault_deletion`
+pub fn cancel_vault_deletion(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::VaultDeletion {
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+        },
+        instruction::CancelVaultDeletion {},
+    )
+}
+
+/// Build `finalize_vault_deletion`
+///
+/// `chunk_indexes` lists every chunk still registered in the master lockbox.
+/// Those chunks and the other auxiliary accounts are passed read-only so the
+/// program can check they were closed first.
+pub fn finalize_vault_deletion(owner: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::FinalizeVaultDeletion {
+            master_lockbox,
+            owner: *owner,
+        },
+        instruction::FinalizeVaultDeletion {},
+    );
+    let auxiliary = chunk_indexes
+        .iter()
+        .map(|index| pda::storage_chunk(&master_lockbox, *index).0)
+        .chain([
+            pda::category_registry(&master_lockbox).0,
+            pda::security_policy(&master_lockbox).0,
+            pda::contact_book(&master_lockbox).0,
+            pda::deny_list(&master_lockbox).0,
+            pda::tag_registry(&master_lockbox).0,
+            pda::search_filter(&master_lockbox).0,
+            pda::category_index(&master_lockbox).0,
+            pda::recovery_config(owner).0,
+            pda::recovery_config_v2(owner).0,
+            pda::emergency_access(owner).0,
+        ]);
+    ix.accounts.extend(auxiliary.map(|address| AccountMeta::new_readonly(address, false)));
+    ix
+}
+
+/// Build `close_storage_chunk`
+///
+/// Pass `allow_non_empty` to destroy entries still stored in the chunk.
+/// Add the category registry and index with [`with_category_registry`] and
+/// [`with_category_index`] once the vault has them. Pass `device` when a
+/// writer lease is held; it must co-sign.
+pub fn close_storage_chunk(
+    owner: &Pubkey,
+    chunk_index: u16,
+    allow_non_empty: bool,
+    device: Option<Pubkey>,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::CloseStorageChunk {
+            storage_chunk,
+            master_lockbox,
+            owner: *owner,
+            category_registry: None,
+            category_index: None,
+            system_program: system_program::ID,
+            device,
+        },
+        instruction::CloseStorageChunk { chunk_index, allow_non_empty },
+    )
+}
+
+/// Build `force_close_orphaned_chunk`
+pub fn force_close_orphaned_chunk(owner: &Pubkey, chunk_index: u16) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(owner, chunk_index);
+    build(
+        accounts::ForceCloseOrphanedChunk {
+            storage_chunk,
+            master_lockbox,
+            owner: *owner,
+        },
+        instruction::ForceCloseOrphanedChunk { chunk_index },
+    )
+}
+
+/// Build `detect_orphans` over the given candidate chunk indexes
+pub fn detect_orphans(owner: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    let mut ix = build(
+        accounts::DetectOrphans { master_lockbox },
+        instruction::DetectOrphans {},
+    );
+    ix.accounts.extend(chunk_indexes.iter().map(|index| {
+        AccountMeta::new_readonly(pda::storage_chunk(&master_lockbox, *index).0, false)
+    }));
+    ix
+}
+
+// ============================================================================
+// Social Recovery (V1)
+// ============================================================================
+
+/// Build `initialize_recovery_config`
+pub fn initialize_recovery_config(owner: &Pubkey, threshold: u8, recovery_delay: i64) -> Instruction {
+    build(
+        accounts::InitializeRecoveryConfig {
+            recovery_config: pda::recovery_config(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeRecoveryConfig {
+            threshold,
+            recovery_delay,
+        },
+    )
+}
+
+/// Build `add_guardian`
+pub fn add_guardian(
+    owner: &Pubkey,
+    guardian_pubkey: Pubkey,
+    share_index: u8,
+    encrypted_share: Vec<u8>,
+    nickname_encrypted: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::AddGuardian {
+            recovery_config: pda::recovery_config(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+            guardian_inbox: None,
+            deny_list: pda::deny_list(&pda::master_lockbox(owner).0).0,
+        },
+        instruction::AddGuardian {
+            guardian_pubkey,
+            share_index,
+            encrypted_share,
+            nickname_encrypted,
+        },
+    )
+}
+
+/// Build `set_guardian_agreement`
+pub fn set_guardian_agreement(
+    owner: &Pubkey,
+    guardian_pubkey: Pubkey,
+    agreement_hash: [u8; 32],
+) -> Instruction {
+    let recovery_config = pda::recovery_config(owner).0;
+    build(
+        accounts::SetGuardianAgreement {
+            recovery_config,
+            guardian_agreement: pda::guardian_agreement(&recovery_config, &guardian_pubkey).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::SetGuardianAgreement {
+            guardian_pubkey,
+            agreement_hash,
+        },
+    )
+}
+
+/// Build `accept_guardianship` for the vault owned by `owner`
+///
+/// Pass the hash of the agreed terms; it is ignored if the owner set none.
+pub fn accept_guardianship(
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    agreement_hash: [u8; 32],
+) -> Instruction {
+    let recovery_config = pda::recovery_config(owner).0;
+    build(
+        accounts::AcceptGuardianship {
+            recovery_config,
+            guardian_agreement: pda::guardian_agreement(&recovery_config, guardian).0,
+            guardian: *guardian,
+        },
+        instruction::AcceptGuardianship { agreement_hash },
+    )
+}
+
+/// Build `remove_guardian`
+pub fn remove_guardian(owner: &Pubkey, guardian_pubkey: Pubkey) -> Instruction {
+    build(
+        accounts::RemoveGuardian {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+        },
+        instruction::RemoveGuardian { guardian_pubkey },
+    )
+}
+
+/// Build `resend_guardian_invitation`
+///
+/// Wrap with [`notify_inbox`] to deliver the invitation to the guardian's
+/// inbox again.
+pub fn resend_guardian_invitation(owner: &Pubkey, guardian_pubkey: Pubkey) -> Instruction {
+    build(
+        accounts::ResendGuardianInvitation {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+            guardian_inbox: None,
+        },
+        instruction::ResendGuardianInvitation { guardian_pubkey },
+    )
+}
+
+/// Build `expire_guardian_invitations` (permissionless crank)
+pub fn expire_guardian_invitations(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ExpireGuardianInvitations {
+            recovery_config: pda::recovery_config(owner).0,
+        },
+        instruction::ExpireGuardianInvitations {},
+    )
+}
+
+/// Build `set_guardian_invitation_ttl`
+pub fn set_guardian_invitation_ttl(owner: &Pubkey, invitation_ttl: i64) -> Instruction {
+    build(
+        accounts::SetGuardianInvitationTtl {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+        },
+        instruction::SetGuardianInvitationTtl { invitation_ttl },
+    )
+}
+
+/// Build `disable_recovery`
+pub fn disable_recovery(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::SetRecoveryEnabled {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+        },
+        instruction::DisableRecovery {},
+    )
+}
+
+/// Build `enable_recovery`
+pub fn enable_recovery(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::SetRecoveryEnabled {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+        },
+        instruction::EnableRecovery {},
+    )
+}
+
+/// Build `set_owner_activity_window`
+pub fn set_owner_activity_window(owner: &Pubkey, window: i64) -> Instruction {
+    build(
+        accounts::SetOwnerActivityWindow {
+            recovery_config: pda::recovery_config(owner).0,
+            owner: *owner,
+        },
+        instruction::SetOwnerActivityWindow { window },
+    )
+}
+
+/// Build `initiate_recovery`
+pub fn initiate_recovery(
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    request_id: u64,
+    new_owner: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::InitiateRecovery {
+            recovery_config: pda::recovery_config(owner).0,
+            recovery_request: pda::recovery_request(owner, request_id).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            guardian: *guardian,
+            system_program: system_program::ID,
+        },
+        instruction::InitiateRecovery {
+            request_id,
+            new_owner,
+        },
+    )
+}
+
+/// Build `approve_recovery`
+pub fn approve_recovery(
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    request_id: u64,
+    share_decrypted: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::ApproveRecovery {
+            recovery_config: pda::recovery_config(owner).0,
+            recovery_request: pda::recovery_request(owner, request_id).0,
+            guardian: *guardian,
+        },
+        instruction::ApproveRecovery { share_decrypted },
+    )
+}
+
+/// Build `complete_recovery`
+///
+/// `caller` must be the request's requester or its new owner.
+pub fn complete_recovery(owner: &Pubkey, request_id: u64, caller: &Pubkey) -> Instruction {
+    build(
+        accounts::CompleteRecovery {
+            recovery_config: pda::recovery_config(owner).0,
+            recovery_request: pda::recovery_request(owner, request_id).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            caller: *caller,
+        },
+        instruction::CompleteRecovery {},
+    )
+}
+
+/// Build `cancel_recovery`
+pub fn cancel_recovery(owner: &Pubkey, request_id: u64) -> Instruction {
+    build(
+        accounts::CancelRecovery {
+            recovery_config: pda::recovery_config(owner).0,
+            recovery_request: pda::recovery_request(owner, request_id).0,
+            owner: *owner,
+        },
+        instruction::CancelRecovery {},
+    )
+}
+
+/// Build `close_recovery_config`
+///
+/// `last_request_id` comes from the fetched recovery config.
+pub fn close_recovery_config(owner: &Pubkey, last_request_id: u64) -> Instruction {
+    build(
+        accounts::CloseRecoveryConfig {
+            recovery_config: pda::recovery_config(owner).0,
+            latest_request: pda::recovery_request(owner, last_request_id).0,
+            owner: *owner,
+        },
+        instruction::CloseRecoveryConfig {},
+    )
+}
+
+// ============================================================================
+// Social Recovery (V2)
+// ============================================================================
+
+/// Build `initialize_recovery_config_v2`
+pub fn initialize_recovery_config_v2(
+    owner: &Pubkey,
+    threshold: u8,
+    recovery_delay: i64,
+    master_secret_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::InitializeRecoveryConfigV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeRecoveryConfigV2 {
+            threshold,
+            recovery_delay,
+            master_secret_hash,
+        },
+    )
+}
+
+/// Build `add_guardian_v2`
+///
+/// `share_commitment` should come from [`crate::shamir::share_commitment`].
+pub fn add_guardian_v2(
+    owner: &Pubkey,
+    guardian_pubkey: Pubkey,
+    share_index: u8,
+    share_commitment: [u8; 32],
+    nickname_encrypted: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::AddGuardianV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+            guardian_inbox: None,
+            deny_list: pda::deny_list(&pda::master_lockbox(owner).0).0,
+        },
+        instruction::AddGuardianV2 {
+            guardian_pubkey,
+            share_index,
+            share_commitment,
+            nickname_encrypted,
+        },
+    )
+}
+
+/// Build `add_guardians_batch`
+///
+/// Send it in the same transaction as `initialize_recovery_config_v2` so
+/// recovery is either fully set up or not at all.
+pub fn add_guardians_batch(owner: &Pubkey, guardians: Vec<NewGuardian>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::AddGuardiansBatch {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            master_lockbox,
+            owner: *owner,
+            system_program: system_program::ID,
+            deny_list: pda::deny_list(&master_lockbox).0,
+        },
+        instruction::AddGuardiansBatch { guardians },
+    )
+}
+
+/// Build `initiate_recovery_v2`
+///
+/// `request_id` must be the config's `last_request_id + 1`.
+pub fn initiate_recovery_v2(
+    owner: &Pubkey,
+    guardian: &Pubkey,
+    request_id: u64,
+    encrypted_challenge: Vec<u8>,
+    challenge_hash: [u8; 32],
+    new_owner: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::InitiateRecoveryV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            recovery_request: pda::recovery_request_v2(owner, request_id).0,
+            guardian: *guardian,
+            system_program: system_program::ID,
+        },
+        instruction::InitiateRecoveryV2 {
+            encrypted_challenge,
+            challenge_hash,
+            new_owner,
+        },
+    )
+}
+
+/// Build `confirm_participation`
+pub fn confirm_participation(owner: &Pubkey, guardian: &Pubkey, request_id: u64) -> Instruction {
+    build(
+        accounts::ConfirmParticipation {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            recovery_request: pda::recovery_request_v2(owner, request_id).0,
+            guardian: *guardian,
+        },
+        instruction::ConfirmParticipation {},
+    )
+}
+
+/// Build `complete_recovery_with_proof`
+pub fn complete_recovery_with_proof(
+    owner: &Pubkey,
+    requester: &Pubkey,
+    request_id: u64,
+    challenge_plaintext: [u8; 32],
+    master_secret: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::CompleteRecoveryV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            recovery_request: pda::recovery_request_v2(owner, request_id).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            requester: *requester,
+        },
+        instruction::CompleteRecoveryWithProof {
+            challenge_plaintext,
+            master_secret,
+        },
+    )
+}
+
+/// Build `set_recovery_cooldown_v2`
+pub fn set_recovery_cooldown_v2(owner: &Pubkey, mode: CooldownMode, cooldown: u64) -> Instruction {
+    build(
+        accounts::SetRecoveryCooldownV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            owner: *owner,
+        },
+        instruction::SetRecoveryCooldownV2 { mode, cooldown },
+    )
+}
+
+/// Build `close_recovery_config_v2`
+///
+/// `last_request_id` comes from the fetched recovery config.
+pub fn close_recovery_config_v2(owner: &Pubkey, last_request_id: u64) -> Instruction {
+    build(
+        accounts::CloseRecoveryConfigV2 {
+            recovery_config: pda::recovery_config_v2(owner).0,
+            latest_request: pda::recovery_request_v2(owner, last_request_id).0,
+            owner: *owner,
+        },
+        instruction::CloseRecoveryConfigV2 {},
+    )
+}
+
+// ============================================================================
+// Emergency Access
+// ============================================================================
+
+/// Build `initialize_emergency_access`
+pub fn initialize_emergency_access(
+    owner: &Pubkey,
+    inactivity_period: i64,
+    grace_period: i64,
+    notification_interval: i64,
+) -> Instruction {
+    build(
+        accounts::InitializeEmergencyAccess {
+            emergency_access: pda::emergency_access(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeEmergencyAccess {
+            inactivity_period,
+            grace_period,
+            notification_interval,
+        },
+    )
+}
+
+/// Build `add_emergency_contact`
+pub fn add_emergency_contact(
+    owner: &Pubkey,
+    contact_pubkey: Pubkey,
+    contact_name_encrypted: Vec<u8>,
+    access_level: EmergencyAccessLevel,
+    encrypted_key: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::AddEmergencyContact {
+            emergency_access: pda::emergency_access(owner).0,
+            master_lockbox: pda::master_lockbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+            contact_inbox: None,
+            deny_list: pda::deny_list(&pda::master_lockbox(owner).0).0,
+        },
+        instruction::AddEmergencyContact {
+            contact_pubkey,
+            contact_name_encrypted,
+            access_level,
+            encrypted_key,
+        },
+    )
+}
+
+/// Build `add_emergency_contacts_batch`
+///
+/// Send it in the same transaction as `initialize_emergency_access` so the
+/// dead man's switch is either fully set up or not at all.
+pub fn add_emergency_contacts_batch(owner: &Pubkey, contacts: Vec<NewEmergencyContact>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::AddEmergencyContactsBatch {
+            emergency_access: pda::emergency_access(owner).0,
+            master_lockbox,
+            owner: *owner,
+            system_program: system_program::ID,
+            deny_list: pda::deny_list(&master_lockbox).0,
+        },
+        instruction::AddEmergencyContactsBatch { contacts },
+    )
+}
+
+/// Build `accept_emergency_contact` for the vault owned by `owner`
+pub fn accept_emergency_contact(owner: &Pubkey, contact: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptEmergencyContact {
+            emergency_access: pda::emergency_access(owner).0,
+            contact: *contact,
+        },
+        instruction::AcceptEmergencyContact {},
+    )
+}
+
+/// Build `remove_emergency_contact`
+pub fn remove_emergency_contact(owner: &Pubkey, contact_pubkey: Pubkey) -> Instruction {
+    build(
+        accounts::RemoveEmergencyContact {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::RemoveEmergencyContact { contact_pubkey },
+    )
+}
+
+/// Build `resend_emergency_contact_invitation`
+///
+/// Wrap with [`notify_inbox`] to deliver the designation to the contact's
+/// inbox again.
+pub fn resend_emergency_contact_invitation(owner: &Pubkey, contact_pubkey: Pubkey) -> Instruction {
+    build(
+        accounts::ResendEmergencyContactInvitation {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+            contact_inbox: None,
+        },
+        instruction::ResendEmergencyContactInvitation { contact_pubkey },
+    )
+}
+
+/// Build `expire_pending_contacts` (permissionless crank)
+pub fn expire_pending_contacts(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ExpirePendingContacts {
+            emergency_access: pda::emergency_access(owner).0,
+        },
+        instruction::ExpirePendingContacts {},
+    )
+}
+
+/// Build `set_emergency_invitation_ttl`
+pub fn set_emergency_invitation_ttl(owner: &Pubkey, invitation_ttl: i64) -> Instruction {
+    build(
+        accounts::SetEmergencyInvitationTtl {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::SetEmergencyInvitationTtl { invitation_ttl },
+    )
+}
+
+/// Build `set_export_consent`
+pub fn set_export_consent(
+    owner: &Pubkey,
+    contacts: Vec<Pubkey>,
+    scope_hash: [u8; 32],
+    conditions_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::SetExportConsent {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::SetExportConsent { contacts, scope_hash, conditions_hash },
+    )
+}
+
+/// Build `revoke_export_consent`
+pub fn revoke_export_consent(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::SetExportConsent {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::RevokeExportConsent {},
+    )
+}
+
+/// Build `record_activity`
+pub fn record_activity(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::RecordActivity {
+            emergency_access: pda::emergency_access(owner).0,
+        },
+        instruction::RecordActivity {},
+    )
+}
+
+/// Build `manual_activity_ping`
+pub fn manual_activity_ping(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ManualActivityPing {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::ManualActivityPing {},
+    )
+}
+
+/// Build `check_and_start_countdown` (permissionless crank)
+pub fn check_and_start_countdown(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CheckAndStartCountdown {
+            emergency_access: pda::emergency_access(owner).0,
+        },
+        instruction::CheckAndStartCountdown {},
+    )
+}
+
+/// Build `notify_emergency_contacts` (permissionless crank)
+pub fn notify_emergency_contacts(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::NotifyEmergencyContacts {
+            emergency_access: pda::emergency_access(owner).0,
+        },
+        instruction::NotifyEmergencyContacts {},
+    )
+}
+
+/// Build `set_notification_interval`
+pub fn set_notification_interval(owner: &Pubkey, notification_interval: i64) -> Instruction {
+    build(
+        accounts::SetNotificationInterval {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::SetNotificationInterval {
+            notification_interval,
+        },
+    )
+}
+
+/// Build `activate_emergency_access` (permissionless crank)
+///
+/// `countdown_started` comes from the fetched emergency access config;
+/// `payer` funds the grant record.
+pub fn activate_emergency_access(owner: &Pubkey, payer: &Pubkey, countdown_started: i64) -> Instruction {
+    let emergency_access = pda::emergency_access(owner).0;
+    build(
+        accounts::ActivateEmergencyAccess {
+            emergency_access,
+            master_lockbox: pda::master_lockbox(owner).0,
+            grant_record: pda::emergency_grant_record(&emergency_access, countdown_started).0,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::ActivateEmergencyAccess {},
+    )
+}
+
+/// Build `test_emergency_flow`
+pub fn test_emergency_flow(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::TestEmergencyFlow {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::TestEmergencyFlow {},
+    )
+}
+
+/// Build `cancel_emergency_countdown`
+pub fn cancel_emergency_countdown(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelEmergencyCountdown {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::CancelEmergencyCountdown {},
+    )
+}
+
+/// Build `close_emergency_access`
+pub fn close_emergency_access(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseEmergencyAccess {
+            emergency_access: pda::emergency_access(owner).0,
+            owner: *owner,
+        },
+        instruction::CloseEmergencyAccess {},
+    )
+}
+
+// ============================================================================
+// Handle Aliases
+// ============================================================================
+
+/// Build `register_alias` (owner pays the fee and rent)
+pub fn register_alias(owner: &Pubkey, handle_hash: [u8; 32]) -> Instruction {
+    build(
+        accounts::RegisterAlias {
+            master_lockbox: pda::master_lockbox(owner).0,
+            alias: pda::alias(&handle_hash).0,
+            treasury: pda::treasury().0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterAlias { handle_hash },
+    )
+}
+
+/// Build `transfer_alias`
+pub fn transfer_alias(owner: &Pubkey, handle_hash: [u8; 32], new_owner: &Pubkey) -> Instruction {
+    build(
+        accounts::TransferAlias {
+            alias: pda::alias(&handle_hash).0,
+            new_master_lockbox: pda::master_lockbox(new_owner).0,
+            owner: *owner,
+        },
+        instruction::TransferAlias { new_owner: *new_owner },
+    )
+}
+
+/// Build `release_alias`
+pub fn release_alias(owner: &Pubkey, handle_hash: [u8; 32]) -> Instruction {
+    build(
+        accounts::ReleaseAlias {
+            alias: pda::alias(&handle_hash).0,
+            owner: *owner,
+        },
+        instruction::ReleaseAlias {},
+    )
+}
+
+// ============================================================================
+// Contact Book
+// ============================================================================
+
+/// Build `initialize_contact_book`
+pub fn initialize_contact_book(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::InitializeContactBook {
+            master_lockbox,
+            contact_book: pda::contact_book(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeContactBook {},
+    )
+}
+
+/// Build `add_contact`
+pub fn add_contact(owner: &Pubkey, contact: &Pubkey, label_encrypted: Vec<u8>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::AddContact {
+            master_lockbox,
+            contact_book: pda::contact_book(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+            deny_list: pda::deny_list(&master_lockbox).0,
+        },
+        instruction::AddContact {
+            contact: *contact,
+            label_encrypted,
+        },
+    )
+}
+
+/// Build `update_contact`
+pub fn update_contact(owner: &Pubkey, contact: &Pubkey, label_encrypted: Vec<u8>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::UpdateContact {
+            master_lockbox,
+            contact_book: pda::contact_book(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::UpdateContact {
+            contact: *contact,
+            label_encrypted,
+        },
+    )
+}
+
+/// Build `remove_contact`
+pub fn remove_contact(owner: &Pubkey, contact: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::RemoveContact {
+            master_lockbox,
+            contact_book: pda::contact_book(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::RemoveContact { contact: *contact },
+    )
+}
+
+/// Build `close_contact_book`
+pub fn close_contact_book(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseContactBook {
+            master_lockbox,
+            contact_book: pda::contact_book(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CloseContactBook {},
+    )
+}
+
+/// Build `deny_key`
+pub fn deny_key(owner: &Pubkey, key: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::DenyKey {
+            master_lockbox,
+            deny_list: pda::deny_list(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::DenyKey { key: *key },
+    )
+}
+
+/// Build `allow_key`
+pub fn allow_key(owner: &Pubkey, key: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::AllowKey {
+            master_lockbox,
+            deny_list: pda::deny_list(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::AllowKey { key: *key },
+    )
+}
+
+/// Build `close_deny_list`
+pub fn close_deny_list(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CloseDenyList {
+            master_lockbox,
+            deny_list: pda::deny_list(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CloseDenyList {},
+    )
+}
+
+// ============================================================================
+// Inbox
+// ============================================================================
+
+/// Build `initialize_inbox`
+pub fn initialize_inbox(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeInbox {
+            inbox: pda::inbox(owner).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeInbox {},
+    )
+}
+
+/// Build `accept_inbox_item`
+pub fn accept_inbox_item(owner: &Pubkey, kind: InboxItemKind, account: Pubkey) -> Instruction {
+    build(
+        accounts::ResolveInboxItem {
+            inbox: pda::inbox(owner).0,
+            owner: *owner,
+        },
+        instruction::AcceptInboxItem { kind, account },
+    )
+}
+
+/// Build `dismiss_inbox_item`
+pub fn dismiss_inbox_item(owner: &Pubkey, kind: InboxItemKind, account: Pubkey) -> Instruction {
+    build(
+        accounts::ResolveInboxItem {
+            inbox: pda::inbox(owner).0,
+            owner: *owner,
+        },
+        instruction::DismissInboxItem { kind, account },
+    )
+}
+
+/// Build `close_inbox`
+pub fn close_inbox(owner: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseInbox {
+            inbox: pda::inbox(owner).0,
+            owner: *owner,
+        },
+        instruction::CloseInbox {},
+    )
+}
+
+/// Deliver an `add_guardian`, `add_guardian_v2`, `add_emergency_contact` or
+/// `resend_*_invitation` instruction's invitation to `recipient`'s inbox
+///
+/// The recipient must have called `initialize_inbox`; the optional inbox
+/// is the last account of each of those instructions.
+pub fn notify_inbox(mut ix: Instruction, recipient: &Pubkey) -> Instruction {
+    let len = ix.accounts.len();
+    ix.accounts[len - 1] = AccountMeta::new(pda::inbox(recipient).0, false);
+    ix
+}
+
+// ============================================================================
+// Export Manifests
+// ============================================================================
+
+/// Build `commit_export_manifest` for a manifest built with
+/// [`crate::export::ExportManifest`]
+pub fn commit_export_manifest(owner: &Pubkey, manifest: &ExportManifest) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CommitExportManifest {
+            master_lockbox,
+            export_log: pda::export_log(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::CommitExportManifest {
+            manifest_hash: manifest.hash(),
+            format_version: manifest.format_version,
+            entry_count: manifest.entry_count(),
+        },
+    )
+}
+
+// ============================================================================
+// Vault Transfer Escrow
+// ============================================================================
+
+/// Build `list_vault_transfer`
+pub fn list_vault_transfer(owner: &Pubkey, buyer: &Pubkey, price: u64, duration: i64) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::ListVaultTransfer {
+            master_lockbox,
+            vault_transfer: pda::vault_transfer(&master_lockbox).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::ListVaultTransfer {
+            buyer: *buyer,
+            price,
+            duration,
+        },
+    )
+}
+
+/// Build `cancel_vault_transfer`
+pub fn cancel_vault_transfer(owner: &Pubkey) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CancelVaultTransfer {
+            master_lockbox,
+            vault_transfer: pda::vault_transfer(&master_lockbox).0,
+            owner: *owner,
+        },
+        instruction::CancelVaultTransfer {},
+    )
+}
+
+/// Build `accept_vault_transfer` for `seller`'s vault
+///
+/// `chunk_indexes` must list every chunk of the vault; `price` is the
+/// listing price the buyer agreed to. The seller's recovery and emergency
+/// access configs are passed so the program can close them.
+pub fn accept_vault_transfer(
+    seller: &Pubkey,
+    buyer: &Pubkey,
+    price: u64,
+    chunk_indexes: &[u16],
+) -> Instruction {
+    let master_lockbox = pda::master_lockbox(seller).0;
+    let ix = build(
+        accounts::AcceptVaultTransfer {
+            master_lockbox,
+            vault_transfer: pda::vault_transfer(&master_lockbox).0,
+            seller: *seller,
+            buyer: *buyer,
+            system_program: system_program::ID,
+            seller_recovery_config: pda::recovery_config(seller).0,
+            seller_recovery_config_v2: pda::recovery_config_v2(seller).0,
+            seller_emergency_access: pda::emergency_access(seller).0,
+        },
+        instruction::AcceptVaultTransfer { price },
+    );
+    with_chunks(ix, seller, chunk_indexes)
+}
+
+/// Build `rekey_transferred_entry`
+///
+/// The vault keeps its address, so it is derived from `seller`.
+/// `expected_layout_epoch` is the chunk's `layout_epoch` as last fetched.
+pub fn rekey_transferred_entry(
+    seller: &Pubkey,
+    buyer: &Pubkey,
+    chunk_index: u16,
+    entry_id: u64,
+    reencrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
+) -> Instruction {
+    let (master_lockbox, storage_chunk) = entry_accounts(seller, chunk_index);
+    build(
+        accounts::RekeyTransferredEntry {
+            vault_transfer: pda::vault_transfer(&master_lockbox).0,
+            master_lockbox,
+            storage_chunk,
+            category_registry: None,
+            buyer: *buyer,
+        },
+        instruction::RekeyTransferredEntry {
+            chunk_index,
+            entry_id,
+            reencrypted_data,
+            expected_layout_epoch,
+        },
+    )
+}
+
+/// Build `complete_vault_rekey`; `chunk_indexes` must list every chunk
+pub fn complete_vault_rekey(seller: &Pubkey, buyer: &Pubkey, chunk_indexes: &[u16]) -> Instruction {
+    let master_lockbox = pda::master_lockbox(seller).0;
+    let ix = build(
+        accounts::CompleteVaultRekey {
+            vault_transfer: pda::vault_transfer(&master_lockbox).0,
+            master_lockbox,
+            seller: *seller,
+            buyer: *buyer,
+        },
+        instruction::CompleteVaultRekey {},
+    );
+    with_chunks(ix, seller, chunk_indexes)
+}
+
+// ============================================================================
+// Shared Vaults
+// ============================================================================
+//
+// Shared vault builders take the creating master lockbox's address (stored
+// in every `SharedVault`) rather than an owner, since members other than
+// the creator do not otherwise know it.
+
+/// Build `create_shared_vault`
+pub fn create_shared_vault(owner: &Pubkey, vault_id: u32, wrapped_vault_key: Vec<u8>) -> Instruction {
+    let master_lockbox = pda::master_lockbox(owner).0;
+    build(
+        accounts::CreateSharedVault {
+            master_lockbox,
+            shared_vault: pda::shared_vault(&master_lockbox, vault_id).0,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSharedVault {
+            vault_id,
+            wrapped_vault_key,
+        },
+    )
+}
+
+/// Build `add_shared_vault_member`
+pub fn add_shared_vault_member(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    admin: &Pubkey,
+    member: &Pubkey,
+    role: SharedVaultRole,
+    wrapped_vault_key: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::AddSharedVaultMember {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::AddSharedVaultMember {
+            member: *member,
+            role,
+            wrapped_vault_key,
+        },
+    )
+}
+
+/// Build `remove_shared_vault_member`
+pub fn remove_shared_vault_member(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    admin: &Pubkey,
+    member: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RemoveSharedVaultMember {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::RemoveSharedVaultMember { member: *member },
+    )
+}
+
+/// Build `remove_member_with_rotation_flag`
+///
+/// `entry_ids` must list every live entry of the shared vault; they are
+/// passed writable and flagged `needs_rotation`.
+pub fn remove_member_with_rotation_flag(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    admin: &Pubkey,
+    member: &Pubkey,
+    entry_ids: &[u64],
+) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    let mut ix = build(
+        accounts::RemoveSharedVaultMember {
+            shared_vault,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::RemoveMemberWithRotationFlag { member: *member },
+    );
+    ix.accounts.extend(entry_ids.iter().map(|entry_id| {
+        AccountMeta::new(pda::shared_entry(&shared_vault, *entry_id).0, false)
+    }));
+    ix
+}
+
+/// Build `store_shared_entry`
+///
+/// `entry_id` must be the shared vault's current `next_entry_id`.
+pub fn store_shared_entry(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    member: &Pubkey,
+    entry_id: u64,
+    encrypted_data: Vec<u8>,
+) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::StoreSharedEntry {
+            master_lockbox: *master_lockbox,
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+            system_program: system_program::ID,
+        },
+        instruction::StoreSharedEntry { encrypted_data },
+    )
+}
+
+/// Build `update_shared_entry`
+pub fn update_shared_entry(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    member: &Pubkey,
+    entry_id: u64,
+    encrypted_data: Vec<u8>,
+) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::UpdateSharedEntry {
+            master_lockbox: *master_lockbox,
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+            system_program: system_program::ID,
+        },
+        instruction::UpdateSharedEntry {
+            entry_id,
+            encrypted_data,
+        },
+    )
+}
+
+/// Build `delete_shared_entry`
+pub fn delete_shared_entry(master_lockbox: &Pubkey, vault_id: u32, member: &Pubkey, entry_id: u64) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::DeleteSharedEntry {
+            master_lockbox: *master_lockbox,
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+        },
+        instruction::DeleteSharedEntry { entry_id },
+    )
+}
+
+/// Build `checkout_entry`
+pub fn checkout_entry(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    member: &Pubkey,
+    entry_id: u64,
+    duration: i64,
+) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::ManageEntryCheckout {
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+        },
+        instruction::CheckoutEntry { entry_id, duration },
+    )
+}
+
+/// Build `checkin_entry`
+pub fn checkin_entry(master_lockbox: &Pubkey, vault_id: u32, member: &Pubkey, entry_id: u64) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::ManageEntryCheckout {
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+        },
+        instruction::CheckinEntry { entry_id },
+    )
+}
+
+/// Build `retrieve_shared_entry`
+///
+/// A member who joined through the vault's token gate passes the token
+/// account holding the gate token, and for a collection gate the NFT's
+/// metadata account (see [`pda::token_metadata`]); other members pass `None`.
+pub fn retrieve_shared_entry(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    member: &Pubkey,
+    entry_id: u64,
+    token_account: Option<Pubkey>,
+    token_metadata: Option<Pubkey>,
+) -> Instruction {
+    let shared_vault = pda::shared_vault(master_lockbox, vault_id).0;
+    build(
+        accounts::RetrieveSharedEntry {
+            shared_vault,
+            shared_entry: pda::shared_entry(&shared_vault, entry_id).0,
+            member: *member,
+            token_account,
+            token_metadata,
+        },
+        instruction::RetrieveSharedEntry { entry_id },
+    )
+}
+
+/// Build `set_shared_vault_gate`
+pub fn set_shared_vault_gate(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    admin: &Pubkey,
+    gate: Option<SharedVaultGate>,
+) -> Instruction {
+    build(
+        accounts::SetSharedVaultGate {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            admin: *admin,
+        },
+        instruction::SetSharedVaultGate { gate },
+    )
+}
+
+/// Build `join_shared_vault_with_token`
+///
+/// `token_metadata` is required for a collection gate (see
+/// [`pda::token_metadata`]).
+pub fn join_shared_vault_with_token(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    member: &Pubkey,
+    token_account: &Pubkey,
+    token_metadata: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::JoinSharedVaultWithToken {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            member: *member,
+            token_account: *token_account,
+            token_metadata,
+            system_program: system_program::ID,
+        },
+        instruction::JoinSharedVaultWithToken {},
+    )
+}
+
+/// Build `set_member_vault_key`
+pub fn set_member_vault_key(
+    master_lockbox: &Pubkey,
+    vault_id: u32,
+    admin: &Pubkey,
+    member: &Pubkey,
+    wrapped_vault_key: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::SetMemberVaultKey {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            admin: *admin,
+        },
+        instruction::SetMemberVaultKey {
+            member: *member,
+            wrapped_vault_key,
+        },
+    )
+}
+
+/// Build `close_shared_vault`
+pub fn close_shared_vault(master_lockbox: &Pubkey, vault_id: u32, admin: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseSharedVault {
+            shared_vault: pda::shared_vault(master_lockbox, vault_id).0,
+            admin: *admin,
+        },
+        instruction::CloseSharedVault {},
+    )
+}
+
+// ============================================================================
+// Account Schema Migrations
+// ============================================================================
+
+/// Build `migrate_account_v1` for any unversioned account recorded under `owner`
+pub fn migrate_account_v1(owner: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            account: *account,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccountV1 {},
+    )
+}
+
+/// Build `migrate_account_v2` for a schema v1 emergency access or recovery config (v1 or v2) account
+pub fn migrate_account_v2(owner: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            account: *account,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccountV2 {},
+    )
+}
+
+/// Build `migrate_account_v3` for a schema v2 emergency access account
+pub fn migrate_account_v3(owner: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            account: *account,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccountV3 {},
+    )
+}
+
+/// Build `migrate_account_v4` for a schema v3 storage chunk
+pub fn migrate_account_v4(owner: &Pubkey, account: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            account: *account,
+            owner: *owner,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccountV4 {},
+    )
+}
+
+// ============================================================================
+// Program Config, Organization Invoices & Refunds
+// ============================================================================
+
+/// Build `initialize_program_config`
+///
+/// `authority` must be the program's upgrade authority.
+pub fn initialize_program_config(authority: &Pubkey, admin: &Pubkey, invoice_receiver: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeProgramConfig {
+            program_config: pda::program_config().0,
+            program: lockbox::ID,
+            program_data: pda::program_data().0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProgramConfig {
+            admin: *admin,
+            invoice_receiver: *invoice_receiver,
+        },
+    )
+}
+
+/// Build `set_program_config`
+pub fn set_program_config(current_admin: &Pubkey, admin: &Pubkey, invoice_receiver: &Pubkey) -> Instruction {
+    build(
+        accounts::SetProgramConfig {
+            program_config: pda::program_config().0,
+            admin: *current_admin,
+        },
+        instruction::SetProgramConfig {
+            admin: *admin,
+            invoice_receiver: *invoice_receiver,
+        },
+    )
+}
+
+/// Build `issue_invoice`
+pub fn issue_invoice(
+    admin: &Pubkey,
+    master_lockbox: &Pubkey,
+    invoice_id: u64,
+    tier: SubscriptionTier,
+    amount: u64,
+    period: i64,
+    memo_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::IssueInvoice {
+            program_config: pda::program_config().0,
+            master_lockbox: *master_lockbox,
+            invoice: pda::invoice(master_lockbox, invoice_id).0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::IssueInvoice {
+            invoice_id,
+            tier,
+            amount,
+            period,
+            memo_hash,
+        },
+    )
+}
+
+/// Build `pay_invoice`
+///
+/// `invoice_receiver` is the program config's current receiver and
+/// `token_program` the program owning both token accounts.
+pub fn pay_invoice(
+    master_lockbox: &Pubkey,
+    invoice_id: u64,
+    source_token_account: &Pubkey,
+    authority: &Pubkey,
+    invoice_receiver: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::PayInvoice {
+            program_config: pda::program_config().0,
+            master_lockbox: *master_lockbox,
+            invoice: pda::invoice(master_lockbox, invoice_id).0,
+            source_token_account: *source_token_account,
+            invoice_receiver: *invoice_receiver,
+            authority: *authority,
+            token_program: *token_program,
+        },
+        instruction::PayInvoice { invoice_id },
+    )
+}
+
+/// Build `void_invoice`
+pub fn void_invoice(admin: &Pubkey, master_lockbox: &Pubkey, invoice_id: u64) -> Instruction {
+    build(
+        accounts::VoidInvoice {
+            program_config: pda::program_config().0,
+            invoice: pda::invoice(master_lockbox, invoice_id).0,
+            admin: *admin,
+        },
+        instruction::VoidInvoice { invoice_id },
+    )
+}
+
+/// Build `issue_refund`
+///
+/// `refund_id` must be the program config's current `refund_count`.
+pub fn issue_refund(
+    admin: &Pubkey,
+    master_lockbox: &Pubkey,
+    owner: &Pubkey,
+    refund_id: u64,
+    amount: u64,
+    reason_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::IssueRefund {
+            program_config: pda::program_config().0,
+            master_lockbox: *master_lockbox,
+            refund_record: pda::refund_record(refund_id).0,
+            treasury: pda::treasury().0,
+            recipient: *owner,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::IssueRefund {
+            owner: *owner,
+            amount,
+            reason_hash,
+        },
+    )
+}
+
+// ============================================================================
+// V1 (Legacy)
+// ============================================================================
+
+/// Build `store_encrypted` (v1 - LEGACY)
+pub fn store_encrypted(
+    user: &Pubkey,
+    fee_receiver: &Pubkey,
+    ciphertext: Vec<u8>,
+    nonce: [u8; 24],
+    salt: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::StoreEncrypted {
+            lockbox: pda::lockbox_v1(user).0,
+            user: *user,
+            fee_receiver: *fee_receiver,
+            system_program: system_program::ID,
+        },
+        instruction::StoreEncrypted {
+            ciphertext,
+            nonce,
+            salt,
+        },
+    )
+}
+
+/// Build `retrieve_encrypted` (v1 - LEGACY)
+pub fn retrieve_encrypted(user: &Pubkey) -> Instruction {
+    build(
+        accounts::RetrieveEncrypted {
+            lockbox: pda::lockbox_v1(user).0,
+            user: *user,
+        },
+        instruction::RetrieveEncrypted {},
+    )
+}
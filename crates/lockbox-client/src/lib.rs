@@ -0,0 +1,41 @@
+//! # Lockbox Client SDK
+//!
+//! Rust client library for the Lockbox program. Integrators use this crate
+//! instead of re-implementing PDA seeds, instruction encoding, and payload
+//! formats by hand.
+//!
+//! ## Modules
+//!
+//! - **pda**: Program Derived Address helpers for every account type
+//! - **instructions**: Typed builders producing ready-to-sign `Instruction`s
+//! - **accounts**: Fetch and deserialize helpers for on-chain accounts
+//! - **crypto**: HKDF session keys, XChaCha20-Poly1305 payloads, blind indexes
+//! - **export**: Export manifests for `commit_export_manifest`
+//! - **setup**: Validated, ordered transaction sets for recovery and emergency setup
+//! - **shamir**: GF(2^8) Shamir Secret Sharing and guardian share commitments
+//! - **space**: Exact account sizes and rent-exempt balances
+//!
+//! ## Compatibility
+//!
+//! Instruction data and account layouts come directly from the `lockbox`
+//! program crate, so discriminators and Borsh encodings cannot drift from
+//! the deployed program. Crypto primitives match the web client
+//! (`nextjs-app/lib/crypto.ts`) byte-for-byte.
+
+pub mod accounts;
+pub mod crypto;
+pub mod error;
+pub mod export;
+pub mod instructions;
+pub mod pda;
+pub mod setup;
+pub mod shamir;
+pub mod space;
+
+pub use error::ClientError;
+
+/// Re-export of the program crate for account and enum types
+pub use lockbox;
+
+/// Program ID the builders target by default
+pub use lockbox::ID as PROGRAM_ID;
@@ -0,0 +1,345 @@
+//! # PDA Derivation
+//!
+//! Every Lockbox account is a Program Derived Address. The seed schemas
+//! below mirror the `seeds = [...]` constraints in the program's account
+//! contexts.
+//!
+//! | Account              | Seeds                                                |
+//! |----------------------|------------------------------------------------------|
+//! | MasterLockbox        | `["master_lockbox", creator]`                        |
+//! | StorageChunk         | `["storage_chunk", master_lockbox, chunk_index_le]`  |
+//! | CategoryRegistry     | `["category_registry", master_lockbox]`              |
+//! | EntryNotes           | `["entry_notes", master_lockbox, entry_id_le]`       |
+//! | EntryProvenance      | `["entry_provenance", master_lockbox, entry_id_le]`  |
+//! | EntryCopies          | `["entry_copies", master_lockbox, entry_id_le]`      |
+//! | SecurityPolicy       | `["security_policy", master_lockbox]`                |
+//! | ContactBook          | `["contact_book", master_lockbox]`                   |
+//! | DenyList             | `["deny_list", master_lockbox]`                      |
+//! | TagRegistry          | `["tag_registry", master_lockbox]`                   |
+//! | SearchFilter         | `["search_filter", master_lockbox]`                  |
+//! | CategoryIndex        | `["category_index", master_lockbox]`                 |
+//! | ClientEntryIds       | `["client_entry_ids", master_lockbox]`               |
+//! | BillingReceipts      | `["billing_receipts", master_lockbox]`               |
+//! | VaultTransfer        | `["vault_transfer", master_lockbox]`                 |
+//! | ExportLog            | `["export_log", master_lockbox]`                     |
+//! | SharedVault          | `["shared_vault", master_lockbox, vault_id_le]`      |
+//! | SharedEntry          | `["shared_entry", shared_vault, entry_id_le]`        |
+//! | Invoice              | `["invoice", master_lockbox, invoice_id_le]`         |
+//! | RecoveryConfig       | `["recovery_config", owner]`                         |
+//! | GuardianAgreement    | `["guardian_agreement", recovery_config, guardian]`  |
+//! | RecoveryRequest      | `["recovery_request", owner, request_id_le]`         |
+//! | RecoveryConfigV2     | `["recovery_config_v2", owner]`                      |
+//! | RecoveryRequestV2    | `["recovery_request_v2", owner, request_id_le]`      |
+//! | EmergencyAccess      | `["emergency_access", owner]`                        |
+//! | EmergencyGrantRecord | `["emergency_grant", emergency_access, started_le]`  |
+//! | Inbox                | `["inbox", owner]`                                   |
+//! | Lockbox (v1)         | `["lockbox", owner]`                                 |
+//! | Treasury             | `["treasury"]`                                       |
+//! | ProgramConfig        | `["program_config"]`                                 |
+//! | RefundRecord         | `["refund_record", refund_id_le]`                    |
+//! | Alias                | `["alias", handle_hash]`                             |
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use lockbox::state::{
+    Alias, BillingReceipts, CategoryIndex, CategoryRegistry, ClientEntryIds, ContactBook, DenyList, EmergencyAccess, EmergencyGrantRecord, EntryNotes,
+    EntryCopies, EntryProvenance, ExportLog, GuardianAgreement, Inbox, Invoice, MasterLockbox, ProgramConfig, RefundRecord, RecoveryConfig, RecoveryConfigV2,
+    RecoveryRequest, RecoveryRequestV2, SearchFilter, SecurityPolicy, SharedEntry, SharedVault, StorageChunk, TagRegistry,
+    VaultTransfer, TOKEN_METADATA_PROGRAM_ID, TREASURY_SEED,
+};
+
+pub const RECOVERY_CONFIG_SEED: &[u8] = RecoveryConfig::SEEDS_PREFIX;
+pub const RECOVERY_REQUEST_SEED: &[u8] = RecoveryRequest::SEEDS_PREFIX;
+pub const RECOVERY_CONFIG_V2_SEED: &[u8] = RecoveryConfigV2::SEEDS_PREFIX;
+pub const RECOVERY_REQUEST_V2_SEED: &[u8] = RecoveryRequestV2::SEEDS_PREFIX;
+pub const EMERGENCY_ACCESS_SEED: &[u8] = EmergencyAccess::SEEDS_PREFIX;
+pub const LOCKBOX_V1_SEED: &[u8] = b"lockbox";
+
+/// Derive the master lockbox PDA for an owner
+///
+/// The address comes from the wallet that created the vault, so a vault
+/// bought with `accept_vault_transfer` is still derived from its seller's
+/// (or first owner's) wallet.
+pub fn master_lockbox(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive a storage chunk PDA from the master lockbox address
+pub fn storage_chunk(master_lockbox: &Pubkey, chunk_index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &chunk_index.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive a storage chunk PDA directly from the owner
+pub fn storage_chunk_for_owner(owner: &Pubkey, chunk_index: u16) -> (Pubkey, u8) {
+    storage_chunk(&master_lockbox(owner).0, chunk_index)
+}
+
+/// Derive the category registry PDA from the master lockbox address
+pub fn category_registry(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CategoryRegistry::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the security policy PDA from the master lockbox address
+pub fn security_policy(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SecurityPolicy::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the contact book PDA from the master lockbox address
+pub fn contact_book(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ContactBook::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the deny-list PDA from the master lockbox address
+pub fn deny_list(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DenyList::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the tag registry PDA from the master lockbox address
+pub fn tag_registry(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TagRegistry::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the search filter PDA from the master lockbox address
+pub fn search_filter(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SearchFilter::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the category index PDA from the master lockbox address
+pub fn category_index(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CategoryIndex::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the vault's client entry ID PDA
+pub fn client_entry_ids(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ClientEntryIds::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the billing receipts PDA from the master lockbox address
+pub fn billing_receipts(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BillingReceipts::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the export log PDA from the master lockbox address
+pub fn export_log(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ExportLog::SEEDS_PREFIX, master_lockbox.as_ref()], &lockbox::ID)
+}
+
+/// Derive the vault transfer PDA from the master lockbox address
+pub fn vault_transfer(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VaultTransfer::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive a shared vault PDA from the creating master lockbox's address
+pub fn shared_vault(master_lockbox: &Pubkey, vault_id: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SharedVault::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &vault_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive a shared entry PDA from the shared vault address
+pub fn shared_entry(shared_vault: &Pubkey, entry_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SharedEntry::SEEDS_PREFIX,
+            shared_vault.as_ref(),
+            &entry_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive an invoice PDA issued to a master lockbox
+pub fn invoice(master_lockbox: &Pubkey, invoice_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            Invoice::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &invoice_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the Metaplex metadata address of an NFT mint
+///
+/// Passed to token-gated shared vault instructions for a collection gate.
+pub fn token_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Derive a wallet's inbox PDA
+pub fn inbox(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Inbox::SEEDS_PREFIX, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive the notes PDA for an entry
+pub fn entry_notes(master_lockbox: &Pubkey, entry_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            EntryNotes::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &entry_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the provenance PDA for a copied entry
+pub fn entry_provenance(master_lockbox: &Pubkey, entry_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            EntryProvenance::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &entry_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the PDA listing an entry's copies in other vaults
+pub fn entry_copies(master_lockbox: &Pubkey, entry_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            EntryCopies::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &entry_id.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the V1 recovery config PDA
+pub fn recovery_config(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECOVERY_CONFIG_SEED, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive a guardian's agreement PDA from the recovery config address
+pub fn guardian_agreement(recovery_config: &Pubkey, guardian: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            GuardianAgreement::SEEDS_PREFIX,
+            recovery_config.as_ref(),
+            guardian.as_ref(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive a V1 recovery request PDA
+pub fn recovery_request(owner: &Pubkey, request_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECOVERY_REQUEST_SEED, owner.as_ref(), &request_id.to_le_bytes()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the V2 recovery config PDA
+pub fn recovery_config_v2(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECOVERY_CONFIG_V2_SEED, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive a V2 recovery request PDA
+///
+/// The program assigns `request_id = recovery_config.last_request_id + 1`,
+/// so callers should fetch the config first and pass the next ID.
+pub fn recovery_request_v2(owner: &Pubkey, request_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RECOVERY_REQUEST_V2_SEED, owner.as_ref(), &request_id.to_le_bytes()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the emergency access PDA
+pub fn emergency_access(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EMERGENCY_ACCESS_SEED, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive the grant record PDA for an emergency activation
+///
+/// `countdown_started` is the emergency access config's countdown start,
+/// so the record address is known before activation.
+pub fn emergency_grant_record(emergency_access: &Pubkey, countdown_started: i64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            EmergencyGrantRecord::SEEDS_PREFIX,
+            emergency_access.as_ref(),
+            &countdown_started.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the legacy V1 lockbox PDA
+pub fn lockbox_v1(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOCKBOX_V1_SEED, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive the treasury PDA that collects entry write fees
+pub fn treasury() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], &lockbox::ID)
+}
+
+/// Derive the program config PDA naming the billing admin
+pub fn program_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ProgramConfig::SEEDS_PREFIX], &lockbox::ID)
+}
+
+/// Derive a refund record PDA from its program-wide sequence number
+pub fn refund_record(refund_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RefundRecord::SEEDS_PREFIX, &refund_id.to_le_bytes()], &lockbox::ID)
+}
+
+/// Derive the program's ProgramData account (upgradeable loader)
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[lockbox::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// Derive the alias PDA for a handle hash (SHA-256 of the normalized handle)
+pub fn alias(handle_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Alias::SEEDS_PREFIX, handle_hash.as_ref()], &lockbox::ID)
+}
@@ -0,0 +1,412 @@
+//! # Setup Flows
+//!
+//! Social recovery and the dead man's switch each take several
+//! instructions to configure, and the program only rejects a bad setup
+//! once it is on-chain. `RecoverySetup` and `EmergencySetup` check a setup
+//! against the program's limits first (`validate` is a dry run), then
+//! produce the ordered transactions that configure it.
+//!
+//! ## Recovery
+//!
+//! 1. Split the master secret, one Shamir share per guardian
+//! 2. Commit to every share (`SHA256(share || guardian_pubkey)`)
+//! 3. `initialize_recovery_config_v2` + `add_guardians_batch`, packed into
+//!    as few transactions as fit
+//! 4. Deliver the shares off-chain, then check the fetched config with
+//!    `RecoveryPlan::verify` until enough guardians have accepted
+//!
+//! Every transaction is signed by the owner alone. Only the first one
+//! creates the config, so send them in order and resend the rest if a
+//! later one fails.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use lockbox::instructions::{
+    NewEmergencyContact, NewGuardian, MAX_EMERGENCY_CONTACT_BATCH, MAX_GUARDIAN_BATCH,
+};
+use lockbox::state::{
+    EmergencyAccess, EmergencyAccessLevel, RecoveryConfigV2, SubscriptionTier,
+    MAX_GUARDIANS, MAX_INACTIVITY_PERIOD, MAX_RECOVERY_DELAY, MIN_INACTIVITY_PERIOD,
+    MIN_RECOVERY_DELAY,
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+use crate::error::{ClientError, Result};
+use crate::instructions;
+use crate::shamir::{self, Share};
+
+/// Largest serialized transaction the cluster accepts
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Largest encrypted guardian nickname or contact name
+const MAX_NAME_SIZE: usize = 64;
+
+/// Largest encrypted emergency key
+const MAX_ENCRYPTED_KEY_SIZE: usize = 128;
+
+fn require_paid_tier(tier: SubscriptionTier) -> Result<()> {
+    if matches!(tier, SubscriptionTier::Premium | SubscriptionTier::Pro) {
+        Ok(())
+    } else {
+        Err(ClientError::InvalidSetup("feature requires a Premium or Pro subscription"))
+    }
+}
+
+fn has_duplicates(keys: &[Pubkey]) -> bool {
+    keys.iter().enumerate().any(|(i, key)| keys[i + 1..].contains(key))
+}
+
+// ============================================================================
+// Social Recovery
+// ============================================================================
+
+/// A guardian to invite
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardianInvite {
+    pub guardian_pubkey: Pubkey,
+    pub nickname_encrypted: Vec<u8>,
+}
+
+/// A recovery (V2) setup for one vault
+#[derive(Clone, Debug)]
+pub struct RecoverySetup {
+    pub owner: Pubkey,
+    /// Owner's current subscription tier
+    pub tier: SubscriptionTier,
+    /// Guardians needed to recover (M of N)
+    pub threshold: u8,
+    /// Seconds between initiating and completing a recovery
+    pub recovery_delay: i64,
+    pub guardians: Vec<GuardianInvite>,
+}
+
+/// The output of `RecoverySetup::build`
+#[derive(Clone, Debug)]
+pub struct RecoveryPlan {
+    /// Transactions to send, in order
+    pub transactions: Vec<Vec<Instruction>>,
+    /// Each guardian's share, to deliver off-chain (encrypted to the guardian)
+    pub shares: Vec<(Pubkey, Share)>,
+    pub threshold: u8,
+    pub master_secret_hash: [u8; 32],
+}
+
+/// How far a configured recovery has progressed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryStatus {
+    /// Fewer guardians have accepted than the threshold requires
+    AwaitingGuardians { accepted: usize, threshold: u8 },
+    /// Enough guardians have accepted for recovery to be initiated
+    Armed,
+}
+
+impl RecoverySetup {
+    /// Dry-run the setup against the program's constraints
+    ///
+    /// Checks the tier, threshold, delay, guardian count and nickname
+    /// sizes. The deny-list is not checked.
+    pub fn validate(&self) -> Result<()> {
+        require_paid_tier(self.tier)?;
+        if self.threshold < 2 {
+            return Err(ClientError::InvalidSetup("threshold must be at least 2"));
+        }
+        if self.threshold as usize > MAX_GUARDIANS {
+            return Err(ClientError::InvalidSetup("threshold exceeds the program maximum"));
+        }
+        if !(MIN_RECOVERY_DELAY..=MAX_RECOVERY_DELAY).contains(&self.recovery_delay) {
+            return Err(ClientError::InvalidSetup("recovery delay out of range"));
+        }
+        if self.guardians.len() < self.threshold as usize {
+            return Err(ClientError::InvalidSetup("fewer guardians than the threshold"));
+        }
+        if self.guardians.len() > self.tier.max_guardians() {
+            return Err(ClientError::InvalidSetup("too many guardians for the subscription tier"));
+        }
+        let keys: Vec<Pubkey> = self.guardians.iter().map(|g| g.guardian_pubkey).collect();
+        if has_duplicates(&keys) {
+            return Err(ClientError::InvalidSetup("duplicate guardian"));
+        }
+        if keys.contains(&self.owner) {
+            return Err(ClientError::InvalidSetup("owner cannot be their own guardian"));
+        }
+        if self.guardians.iter().any(|g| g.nickname_encrypted.len() > MAX_NAME_SIZE) {
+            return Err(ClientError::InvalidSetup("guardian nickname exceeds 64 bytes"));
+        }
+        Ok(())
+    }
+
+    /// Validate, split `master_secret` and build the setup transactions
+    pub fn build(&self, master_secret: &[u8; 32]) -> Result<RecoveryPlan> {
+        self.build_with_rng(master_secret, &mut OsRng)
+    }
+
+    /// `build` using the supplied RNG for the Shamir split
+    pub fn build_with_rng(
+        &self,
+        master_secret: &[u8; 32],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<RecoveryPlan> {
+        self.validate()?;
+
+        let shares = shamir::split_secret_with_rng(
+            master_secret,
+            self.threshold,
+            self.guardians.len() as u8,
+            rng,
+        )?;
+        let master_secret_hash = shamir::master_secret_hash(master_secret);
+
+        let new_guardians = self
+            .guardians
+            .iter()
+            .zip(&shares)
+            .map(|(guardian, share)| NewGuardian {
+                guardian_pubkey: guardian.guardian_pubkey,
+                share_index: share.index,
+                share_commitment: shamir::share_commitment(&share.data, &guardian.guardian_pubkey),
+                nickname_encrypted: guardian.nickname_encrypted.clone(),
+            })
+            .collect();
+
+        let owner = self.owner;
+        let transactions = pack_batches(
+            instructions::initialize_recovery_config_v2(
+                &owner,
+                self.threshold,
+                self.recovery_delay,
+                master_secret_hash,
+            ),
+            new_guardians,
+            MAX_GUARDIAN_BATCH,
+            |batch| instructions::add_guardians_batch(&owner, batch),
+        )?;
+
+        Ok(RecoveryPlan {
+            transactions,
+            shares: self
+                .guardians
+                .iter()
+                .map(|g| g.guardian_pubkey)
+                .zip(shares)
+                .collect(),
+            threshold: self.threshold,
+            master_secret_hash,
+        })
+    }
+}
+
+impl RecoveryPlan {
+    /// Check a fetched config against the plan and report whether it is armed
+    ///
+    /// # Errors
+    /// * `SetupMismatch` - The config's threshold, secret hash or guardians
+    ///   differ from the plan, e.g. because a transaction was not sent
+    pub fn verify(&self, config: &RecoveryConfigV2) -> Result<RecoveryStatus> {
+        if config.threshold != self.threshold {
+            return Err(ClientError::SetupMismatch("threshold differs"));
+        }
+        if config.master_secret_hash != self.master_secret_hash {
+            return Err(ClientError::SetupMismatch("master secret hash differs"));
+        }
+        if config.guardians.len() != self.shares.len() {
+            return Err(ClientError::SetupMismatch("guardian count differs"));
+        }
+        for (guardian, share) in &self.shares {
+            let on_chain = config
+                .guardians
+                .iter()
+                .find(|g| g.guardian_pubkey == *guardian)
+                .ok_or(ClientError::SetupMismatch("guardian missing"))?;
+            if on_chain.share_index != share.index
+                || !config.verify_share_commitment(guardian, &share.data)
+            {
+                return Err(ClientError::SetupMismatch("share commitment differs"));
+            }
+        }
+
+        Ok(if config.is_armed() {
+            RecoveryStatus::Armed
+        } else {
+            RecoveryStatus::AwaitingGuardians {
+                accepted: config.active_guardian_count(),
+                threshold: config.threshold,
+            }
+        })
+    }
+}
+
+// ============================================================================
+// Emergency Access
+// ============================================================================
+
+/// An emergency contact to invite
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmergencyContactInvite {
+    pub contact_pubkey: Pubkey,
+    pub contact_name_encrypted: Vec<u8>,
+    pub access_level: EmergencyAccessLevel,
+    /// Vault key encrypted to the contact
+    pub encrypted_key: Vec<u8>,
+}
+
+/// A dead man's switch setup for one vault
+#[derive(Clone, Debug)]
+pub struct EmergencySetup {
+    pub owner: Pubkey,
+    /// Owner's current subscription tier
+    pub tier: SubscriptionTier,
+    pub inactivity_period: i64,
+    pub grace_period: i64,
+    /// 0 = notify only when the countdown starts
+    pub notification_interval: i64,
+    pub contacts: Vec<EmergencyContactInvite>,
+}
+
+impl EmergencySetup {
+    /// Dry-run the setup against the program's constraints
+    ///
+    /// Checks the tier, periods, contact count and field sizes. The
+    /// deny-list is not checked.
+    pub fn validate(&self) -> Result<()> {
+        require_paid_tier(self.tier)?;
+        if !(MIN_INACTIVITY_PERIOD..=MAX_INACTIVITY_PERIOD).contains(&self.inactivity_period) {
+            return Err(ClientError::InvalidSetup("inactivity period out of range"));
+        }
+        if self.grace_period < MIN_RECOVERY_DELAY {
+            return Err(ClientError::InvalidSetup("grace period too short"));
+        }
+        if !EmergencyAccess::is_notification_interval_valid(
+            self.notification_interval,
+            self.grace_period,
+        ) {
+            return Err(ClientError::InvalidSetup("notification interval out of range"));
+        }
+        if self.contacts.is_empty() {
+            return Err(ClientError::InvalidSetup("at least one contact required"));
+        }
+        if self.contacts.len() > self.tier.max_emergency_contacts() {
+            return Err(ClientError::InvalidSetup("too many contacts for the subscription tier"));
+        }
+        let keys: Vec<Pubkey> = self.contacts.iter().map(|c| c.contact_pubkey).collect();
+        if has_duplicates(&keys) {
+            return Err(ClientError::InvalidSetup("duplicate contact"));
+        }
+        if keys.contains(&self.owner) {
+            return Err(ClientError::InvalidSetup("owner cannot be their own contact"));
+        }
+        if self.contacts.iter().any(|c| c.contact_name_encrypted.len() > MAX_NAME_SIZE) {
+            return Err(ClientError::InvalidSetup("contact name exceeds 64 bytes"));
+        }
+        if self.contacts.iter().any(|c| c.encrypted_key.len() > MAX_ENCRYPTED_KEY_SIZE) {
+            return Err(ClientError::InvalidSetup("encrypted key exceeds 128 bytes"));
+        }
+        Ok(())
+    }
+
+    /// Validate and build the setup transactions, in order
+    pub fn build(&self) -> Result<Vec<Vec<Instruction>>> {
+        self.validate()?;
+
+        let owner = self.owner;
+        let contacts = self
+            .contacts
+            .iter()
+            .map(|c| NewEmergencyContact {
+                contact_pubkey: c.contact_pubkey,
+                contact_name_encrypted: c.contact_name_encrypted.clone(),
+                access_level: c.access_level,
+                encrypted_key: c.encrypted_key.clone(),
+            })
+            .collect();
+
+        pack_batches(
+            instructions::initialize_emergency_access(
+                &owner,
+                self.inactivity_period,
+                self.grace_period,
+                self.notification_interval,
+            ),
+            contacts,
+            MAX_EMERGENCY_CONTACT_BATCH,
+            |batch| instructions::add_emergency_contacts_batch(&owner, batch),
+        )
+    }
+}
+
+// ============================================================================
+// Transaction Packing
+// ============================================================================
+
+fn compact_len(n: usize) -> usize {
+    match n {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Serialized size of a legacy transaction with one signer
+pub fn transaction_size(instructions: &[Instruction]) -> usize {
+    let mut keys: Vec<Pubkey> = Vec::new();
+    for ix in instructions {
+        for key in ix.accounts.iter().map(|meta| meta.pubkey).chain([ix.program_id]) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let instructions_size: usize = instructions
+        .iter()
+        .map(|ix| {
+            1 + compact_len(ix.accounts.len())
+                + ix.accounts.len()
+                + compact_len(ix.data.len())
+                + ix.data.len()
+        })
+        .sum();
+
+    compact_len(1) + 64 // signature
+        + 3 // message header
+        + compact_len(keys.len()) + 32 * keys.len()
+        + 32 // recent blockhash
+        + compact_len(instructions.len()) + instructions_size
+}
+
+/// Put `first` and batches of `items` into as few transactions as fit
+///
+/// Each transaction holds one batch instruction of at most `max_batch`
+/// items; the first also starts with `first`.
+fn pack_batches<T: Clone>(
+    first: Instruction,
+    items: Vec<T>,
+    max_batch: usize,
+    batch_ix: impl Fn(Vec<T>) -> Instruction,
+) -> Result<Vec<Vec<Instruction>>> {
+    let mut transactions = Vec::new();
+    let mut leading = Some(first);
+    let mut remaining = items.as_slice();
+
+    while !remaining.is_empty() {
+        let fits = |count: usize| {
+            let mut tx: Vec<Instruction> = leading.iter().cloned().collect();
+            tx.push(batch_ix(remaining[..count].to_vec()));
+            transaction_size(&tx) <= MAX_TRANSACTION_SIZE
+        };
+
+        let mut count = remaining.len().min(max_batch);
+        while count > 0 && !fits(count) {
+            count -= 1;
+        }
+        if count == 0 {
+            return Err(ClientError::InvalidSetup("entry too large for a transaction"));
+        }
+
+        let mut tx: Vec<Instruction> = leading.take().into_iter().collect();
+        tx.push(batch_ix(remaining[..count].to_vec()));
+        transactions.push(tx);
+        remaining = &remaining[count..];
+    }
+
+    Ok(transactions)
+}
@@ -0,0 +1,182 @@
+//! # Shamir Secret Sharing (GF(2^8))
+//!
+//! Byte-wise Shamir Secret Sharing over GF(2^8) with the AES polynomial
+//! `x^8 + x^4 + x^3 + x + 1` (0x11b), compatible with the web client's
+//! `shamir-secret-sharing.ts`.
+//!
+//! Share indices are 1-based: index 0 would evaluate the polynomial at the
+//! secret itself, which is why the program rejects `share_index == 0`.
+//!
+//! ## Commitments (Recovery V2)
+//!
+//! - `share_commitment = SHA256(share || guardian_pubkey)`
+//! - `master_secret_hash = SHA256(master_secret)`
+//! - `challenge_hash = SHA256(challenge_plaintext)`
+
+use anchor_lang::prelude::Pubkey;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ClientError, Result};
+
+/// A single Shamir share
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    /// Share index (1 to N) - MUST be non-zero
+    pub index: u8,
+
+    /// Share bytes (same length as the secret)
+    pub data: Vec<u8>,
+}
+
+/// Multiply in GF(2^8) without lookup tables (no secret-dependent branches)
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0u8;
+    for _ in 0..8 {
+        result ^= a & (b & 1).wrapping_neg();
+        let high_bit = (a >> 7) & 1;
+        a = (a << 1) ^ (0x1b & high_bit.wrapping_neg());
+        b >>= 1;
+    }
+    result
+}
+
+/// Multiplicative inverse via a^254 (a^255 = 1 for non-zero a)
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    gf_mul(a, gf_inv(b))
+}
+
+/// Evaluate a polynomial at `x` using Horner's method
+fn evaluate_polynomial(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+/// Split a secret into `total_shares` shares with an M-of-N threshold
+pub fn split_secret(secret: &[u8], threshold: u8, total_shares: u8) -> Result<Vec<Share>> {
+    split_secret_with_rng(secret, threshold, total_shares, &mut OsRng)
+}
+
+/// Split a secret using the supplied RNG for polynomial coefficients
+pub fn split_secret_with_rng(
+    secret: &[u8],
+    threshold: u8,
+    total_shares: u8,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<Share>> {
+    if threshold < 2 {
+        return Err(ClientError::InvalidShamirParameters("threshold must be at least 2"));
+    }
+    if total_shares < threshold {
+        return Err(ClientError::InvalidShamirParameters(
+            "total shares must be at least the threshold",
+        ));
+    }
+    if secret.is_empty() {
+        return Err(ClientError::InvalidShamirParameters("secret cannot be empty"));
+    }
+
+    let mut shares: Vec<Share> = (1..=total_shares)
+        .map(|index| Share {
+            index,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+
+        for share in shares.iter_mut() {
+            share.data.push(evaluate_polynomial(&coefficients, share.index));
+        }
+    }
+
+    coefficients.iter_mut().for_each(|c| *c = 0);
+    Ok(shares)
+}
+
+/// Reconstruct a secret from at least `threshold` shares
+///
+/// Passing fewer shares than the original threshold returns a wrong secret
+/// rather than an error; verify the result against `master_secret_hash`.
+pub fn reconstruct_secret(shares: &[Share]) -> Result<Vec<u8>> {
+    if shares.len() < 2 {
+        return Err(ClientError::InvalidShares("at least 2 shares required"));
+    }
+
+    let len = shares[0].data.len();
+    if shares.iter().any(|s| s.data.len() != len) {
+        return Err(ClientError::InvalidShares("shares have inconsistent lengths"));
+    }
+    if shares.iter().any(|s| s.index == 0) {
+        return Err(ClientError::InvalidShares("share index must be non-zero"));
+    }
+    for (i, a) in shares.iter().enumerate() {
+        if shares[i + 1..].iter().any(|b| b.index == a.index) {
+            return Err(ClientError::InvalidShares("duplicate share index"));
+        }
+    }
+
+    // Lagrange basis coefficients at x = 0 are the same for every byte
+    let basis: Vec<u8> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, si)| {
+            let (num, den) = shares
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold((1u8, 1u8), |(num, den), (_, sj)| {
+                    (gf_mul(num, sj.index), gf_mul(den, si.index ^ sj.index))
+                });
+            gf_div(num, den)
+        })
+        .collect();
+
+    Ok((0..len)
+        .map(|byte| {
+            shares
+                .iter()
+                .zip(&basis)
+                .fold(0u8, |acc, (share, &coeff)| acc ^ gf_mul(share.data[byte], coeff))
+        })
+        .collect())
+}
+
+/// Guardian share commitment: `SHA256(share || guardian_pubkey)`
+///
+/// Matches `RecoveryConfigV2::verify_share_commitment` on-chain.
+pub fn share_commitment(share: &[u8], guardian: &Pubkey) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(share);
+    hasher.update(guardian.as_ref());
+    hasher.finalize().into()
+}
+
+/// `SHA256(master_secret)` as stored in `RecoveryConfigV2::master_secret_hash`
+pub fn master_secret_hash(master_secret: &[u8; 32]) -> [u8; 32] {
+    Sha256::digest(master_secret).into()
+}
+
+/// `SHA256(challenge_plaintext)` as stored in `RecoveryChallenge::challenge_hash`
+pub fn challenge_hash(challenge_plaintext: &[u8; 32]) -> [u8; 32] {
+    Sha256::digest(challenge_plaintext).into()
+}
@@ -0,0 +1,83 @@
+//! # Account Sizes
+//!
+//! Exact account sizes and rent-exempt balances, computed with the same
+//! expressions as the program's `space = ...` and `realloc = ...`
+//! constraints. Use them to fund payers and budget transactions instead
+//! of guessing.
+//!
+//! Rent uses the default (mainnet) rent parameters; clusters with custom
+//! rent should ask the RPC node for `getMinimumBalanceForRentExemption`.
+
+use anchor_lang::prelude::Rent;
+use anchor_lang::Space;
+use lockbox::state::{
+    CategoryIndex, CategoryRegistry, EmergencyAccess, EmergencyGrantRecord, GuardianAgreement, MasterLockbox, RecoveryConfig,
+    RecoveryConfigV2, RecoveryRequest, RecoveryRequestV2, StorageChunk,
+};
+
+/// Master lockbox with `num_chunks` registered chunks and a settings blob
+pub const fn master_lockbox(num_chunks: usize, settings_len: usize) -> usize {
+    MasterLockbox::calculate_space(num_chunks) + settings_len
+}
+
+/// Storage chunk with `capacity` bytes of data (`initial_capacity`, or
+/// the capacity after `expand_chunk`)
+pub const fn storage_chunk(capacity: u32) -> usize {
+    StorageChunk::space(capacity)
+}
+
+/// Category registry
+pub const fn category_registry() -> usize {
+    8 + CategoryRegistry::INIT_SPACE
+}
+
+/// Category index holding `entries` entries
+pub const fn category_index(entries: usize) -> usize {
+    CategoryIndex::space_for(entries)
+}
+
+/// Legacy recovery config holding `guardians` guardians
+pub const fn recovery_config(guardians: usize) -> usize {
+    RecoveryConfig::space_for(guardians)
+}
+
+/// Guardian agreement of a legacy recovery config
+pub const fn guardian_agreement() -> usize {
+    8 + GuardianAgreement::INIT_SPACE
+}
+
+/// Legacy recovery request
+pub const fn recovery_request() -> usize {
+    8 + RecoveryRequest::INIT_SPACE
+}
+
+/// Recovery config (v2) holding `guardians` guardians
+pub const fn recovery_config_v2(guardians: usize) -> usize {
+    RecoveryConfigV2::space_for(guardians)
+}
+
+/// Recovery request (v2)
+pub const fn recovery_request_v2() -> usize {
+    8 + RecoveryRequestV2::INIT_SPACE
+}
+
+/// Emergency access config holding `contacts` contacts
+pub const fn emergency_access(contacts: usize) -> usize {
+    EmergencyAccess::space_for(contacts)
+}
+
+/// Emergency grant record
+pub const fn emergency_grant_record() -> usize {
+    8 + EmergencyGrantRecord::INIT_SPACE
+}
+
+/// Rent-exempt balance of an account of `space` bytes
+pub fn rent(space: usize) -> u64 {
+    Rent::default().minimum_balance(space)
+}
+
+/// Lamports `expand_chunk` charges to grow a chunk by `additional_size`
+pub fn chunk_expansion_rent(capacity: u32, additional_size: u32) -> u64 {
+    let new_capacity = capacity.saturating_add(additional_size);
+    rent(storage_chunk(new_capacity)).saturating_sub(rent(storage_chunk(capacity)))
+}
@@ -0,0 +1,593 @@
+//! Client SDK tests: crypto round-trips, Shamir reconstruction, and
+//! compatibility with the program's own account and commitment logic.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{AccountSerialize, Discriminator};
+use lockbox_client::lockbox::state::{
+    CooldownMode, DataEntryHeader, EmergencyAccessLevel, GuardianStatus, GuardianV2, MasterLockbox,
+    PasswordEntryType, RecoveryConfigV2, StorageChunk, StorageType, SubscriptionTier,
+    DEFAULT_RECOVERY_COOLDOWN, MAX_GUARDIANS,
+};
+use lockbox_client::export::ExportManifest;
+use lockbox_client::lockbox::instructions::NewPasswordEntry;
+use lockbox_client::setup::{
+    self, EmergencyContactInvite, EmergencySetup, GuardianInvite, RecoveryStatus, RecoverySetup,
+};
+use lockbox_client::{accounts, crypto, instructions, pda, shamir, space, ClientError};
+use std::collections::HashMap;
+
+fn test_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = (i as u8).wrapping_mul(7);
+    }
+    key
+}
+
+#[test]
+fn test_entry_encryption_round_trip() {
+    let key = test_key();
+    let payload = crypto::encrypt_entry(&key, b"hunter2").unwrap();
+
+    assert_eq!(payload.len(), crypto::HEADER_SIZE + crypto::NONCE_SIZE + 7 + crypto::TAG_SIZE);
+    assert!(payload.len() >= crypto::MIN_PAYLOAD_SIZE);
+    assert_eq!(payload[..crypto::HEADER_SIZE], crypto::PAYLOAD_HEADER);
+    assert_eq!(crypto::decrypt_entry(&key, &payload).unwrap(), b"hunter2");
+
+    // Entries stored before framing still decrypt
+    let legacy = &payload[crypto::HEADER_SIZE..];
+    assert_eq!(crypto::decrypt_entry(&key, legacy).unwrap(), b"hunter2");
+
+    // Tampering is detected by the Poly1305 tag
+    let mut tampered = payload.clone();
+    tampered[crypto::NONCE_SIZE] ^= 0x01;
+    assert!(matches!(
+        crypto::decrypt_entry(&key, &tampered),
+        Err(ClientError::Decryption)
+    ));
+
+    // Payloads shorter than nonce + tag are rejected up front
+    assert!(matches!(
+        crypto::decrypt_entry(&key, &payload[..39]),
+        Err(ClientError::PayloadTooShort(39, 40))
+    ));
+}
+
+#[test]
+fn test_key_derivation_domain_separation() {
+    let wallet = Pubkey::new_unique();
+    let signature = [9u8; 64];
+
+    let session = crypto::derive_session_key_deterministic(&wallet, &signature);
+    let search = crypto::derive_search_key(&wallet, &signature);
+    assert_ne!(session, search);
+
+    // Deterministic for the same inputs
+    assert_eq!(session, crypto::derive_session_key_deterministic(&wallet, &signature));
+
+    // Title hashes are case- and whitespace-insensitive
+    assert_eq!(
+        crypto::title_hash(&search, "  GitHub "),
+        crypto::title_hash(&search, "github")
+    );
+
+    // Search tokens are one per distinct lowercased word
+    let tokens = crypto::search_tokens(&search, "GitHub work-account, github");
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0], crypto::search_token(&search, "GITHUB"));
+}
+
+#[test]
+fn test_shamir_any_threshold_subset_reconstructs() {
+    let secret = test_key();
+    let shares = shamir::split_secret(&secret, 3, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+
+    for subset in [[0, 1, 2], [0, 2, 4], [1, 3, 4], [2, 3, 4]] {
+        let picked: Vec<_> = subset.iter().map(|&i| shares[i].clone()).collect();
+        assert_eq!(shamir::reconstruct_secret(&picked).unwrap(), secret);
+    }
+
+    // Below threshold yields a different secret, not an error
+    let too_few = vec![shares[0].clone(), shares[1].clone()];
+    assert_ne!(shamir::reconstruct_secret(&too_few).unwrap(), secret);
+
+    let duplicate = vec![shares[0].clone(), shares[0].clone()];
+    assert!(shamir::reconstruct_secret(&duplicate).is_err());
+}
+
+#[test]
+fn test_share_commitment_matches_program_verification() {
+    let guardian = Pubkey::new_unique();
+    let shares = shamir::split_secret(&test_key(), 2, 3).unwrap();
+    let commitment = shamir::share_commitment(&shares[0].data, &guardian);
+
+    let config = RecoveryConfigV2 {
+        version: 1,
+        owner: Pubkey::new_unique(),
+        threshold: 2,
+        total_guardians: 1,
+        guardians: vec![GuardianV2 {
+            guardian_pubkey: guardian,
+            share_index: shares[0].index,
+            share_commitment: commitment,
+            added_at: 0,
+            nickname_encrypted: vec![],
+            status: GuardianStatus::Active,
+        }],
+        recovery_delay: 0,
+        created_at: 0,
+        last_modified: 0,
+        last_request_id: 0,
+        master_secret_hash: shamir::master_secret_hash(&test_key()),
+        last_recovery_attempt: 0,
+        cooldown_mode: CooldownMode::Timestamp,
+        recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+        last_recovery_attempt_slot: 0,
+        last_recovery_attempt_epoch: 0,
+        bump: 0,
+    };
+
+    assert!(config.verify_share_commitment(&guardian, &shares[0].data));
+    assert!(!config.verify_share_commitment(&guardian, &shares[1].data));
+    assert_eq!(config.master_secret_hash, hash(&test_key()).to_bytes());
+}
+
+#[test]
+fn test_space_helpers_match_serialized_accounts() {
+    // Usable in const contexts, e.g. to size fixed buffers
+    const CHUNK_SPACE: usize = space::storage_chunk(1024);
+    assert_eq!(CHUNK_SPACE, StorageChunk::BASE_SPACE + 1024);
+    assert_eq!(space::master_lockbox(0, 0), MasterLockbox::INIT_SPACE);
+    assert_eq!(space::master_lockbox(2, 16), MasterLockbox::calculate_space(2) + 16);
+
+    // An empty chunk serializes to exactly the space before its data region
+    let mut data = Vec::new();
+    chunk_with(0, &[]).try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), space::storage_chunk(0));
+
+    // A recovery config with every guardian at its largest fills its space
+    let guardians = (1..=MAX_GUARDIANS as u8)
+        .map(|share_index| GuardianV2 {
+            guardian_pubkey: Pubkey::new_unique(),
+            share_index,
+            share_commitment: [0u8; 32],
+            added_at: 0,
+            nickname_encrypted: vec![0u8; 64],
+            status: GuardianStatus::Active,
+        })
+        .collect::<Vec<_>>();
+    let config = RecoveryConfigV2 {
+        version: 1,
+        owner: Pubkey::new_unique(),
+        threshold: 2,
+        total_guardians: guardians.len() as u8,
+        guardians,
+        recovery_delay: 0,
+        created_at: 0,
+        last_modified: 0,
+        last_request_id: 0,
+        master_secret_hash: [0u8; 32],
+        last_recovery_attempt: 0,
+        cooldown_mode: CooldownMode::Timestamp,
+        recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+        last_recovery_attempt_slot: 0,
+        last_recovery_attempt_epoch: 0,
+        bump: 0,
+    };
+    let mut data = Vec::new();
+    config.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), space::recovery_config_v2(MAX_GUARDIANS));
+    assert_eq!(space::recovery_config_v2(0), space::recovery_config_v2(MAX_GUARDIANS));
+
+    // Growing a chunk costs the rent difference between the two sizes
+    let grow = space::chunk_expansion_rent(1024, 1024);
+    assert!(grow > 0);
+    assert_eq!(
+        grow,
+        space::rent(space::storage_chunk(2048)) - space::rent(space::storage_chunk(1024))
+    );
+}
+
+#[test]
+fn test_instruction_builders_use_program_layout() {
+    let owner = Pubkey::new_unique();
+    let ix = instructions::store_password_entry(
+        &owner,
+        0,
+        vec![0u8; 40],
+        lockbox_client::lockbox::state::PasswordEntryType::Login,
+        0,
+        [0u8; 32],
+        None,
+    );
+
+    assert_eq!(ix.program_id, lockbox_client::PROGRAM_ID);
+    assert_eq!(&ix.data[..8], &hash(b"global:store_password_entry").to_bytes()[..8]);
+    assert_eq!(ix.accounts[0].pubkey, pda::master_lockbox(&owner).0);
+    assert_eq!(ix.accounts[1].pubkey, pda::storage_chunk_for_owner(&owner, 0).0);
+    assert!(ix.accounts[2].is_signer);
+    let master = pda::master_lockbox(&owner).0;
+    assert_eq!(ix.accounts[3].pubkey, pda::security_policy(&master).0);
+    assert_eq!(ix.accounts[4].pubkey, lockbox_client::PROGRAM_ID);
+
+    let ix = instructions::with_category_registry(ix, &owner);
+    let registry = pda::category_registry(&master).0;
+    assert_eq!(ix.accounts[4].pubkey, registry);
+    assert!(ix.accounts[4].is_writable);
+    assert_eq!(ix.accounts[5].pubkey, lockbox_client::PROGRAM_ID);
+
+    let ix = instructions::with_category_index(ix, &owner);
+    let category_index = pda::category_index(&master).0;
+    assert_eq!(ix.accounts[5].pubkey, category_index);
+    assert!(ix.accounts[5].is_writable);
+
+    // Entry write fees go to the treasury via the system program
+    assert_eq!(ix.accounts[6].pubkey, pda::treasury().0);
+    assert!(ix.accounts[6].is_writable);
+    assert_eq!(ix.accounts[8].pubkey, system_program::ID);
+
+    let payer = Pubkey::new_unique();
+    let ix = instructions::with_auto_expand(ix, &payer);
+    assert_eq!(ix.accounts[4].pubkey, registry);
+    assert_eq!(ix.accounts[7].pubkey, payer);
+    assert!(ix.accounts[7].is_signer && ix.accounts[7].is_writable);
+    assert_eq!(ix.accounts[8].pubkey, system_program::ID);
+    assert_eq!(ix.accounts[9].pubkey, lockbox_client::PROGRAM_ID);
+
+    // A client ID is reserved in the vault's client entry ID account
+    let client_id = lockbox_client::lockbox::state::MasterLockbox::CLIENT_ENTRY_ID_BIT | 7;
+    let ix = instructions::store_password_entry(
+        &owner,
+        0,
+        vec![0u8; 40],
+        lockbox_client::lockbox::state::PasswordEntryType::Login,
+        0,
+        [0u8; 32],
+        Some(client_id),
+    );
+    assert_eq!(ix.accounts[9].pubkey, pda::client_entry_ids(&master).0);
+    assert!(ix.accounts[9].is_writable);
+
+    // Optional placeholders are filled independently of each other
+    let cosigner = Pubkey::new_unique();
+    let ix = instructions::delete_password_entry_with_aliases(&owner, 0, 1, 0, &[2]);
+    let ix = instructions::with_category_index(instructions::with_delete_cosigner(ix, &cosigner), &owner);
+    assert_eq!(ix.accounts[4].pubkey, lockbox_client::PROGRAM_ID);
+    assert_eq!(ix.accounts[5].pubkey, category_index);
+    let ix = instructions::with_category_registry(ix, &owner);
+    assert_eq!(ix.accounts[4].pubkey, registry);
+    assert_eq!(ix.accounts[5].pubkey, category_index);
+    assert_eq!(ix.accounts[6].pubkey, pda::entry_copies(&master, 1).0);
+    assert!(ix.accounts[6].is_writable);
+    assert_eq!(ix.accounts[7].pubkey, cosigner);
+    assert!(ix.accounts[7].is_signer);
+    assert_eq!(ix.accounts[8].pubkey, pda::storage_chunk(&master, 2).0);
+
+    // Rebuilding indexes repairs the registry and category index when added
+    let device = Pubkey::new_unique();
+    let ix = instructions::rebuild_indexes(&owner, &[0, 1], Some(device));
+    let ix = instructions::with_category_index(ix, &owner);
+    assert_eq!(ix.accounts[1].pubkey, lockbox_client::PROGRAM_ID);
+    assert_eq!(ix.accounts[2].pubkey, category_index);
+    let ix = instructions::with_category_registry(ix, &owner);
+    assert_eq!(ix.accounts[1].pubkey, registry);
+    // The lease holder co-signs ahead of the chunks
+    assert_eq!(ix.accounts[5].pubkey, device);
+    assert!(ix.accounts[5].is_signer);
+    assert_eq!(ix.accounts[6].pubkey, pda::storage_chunk(&master, 0).0);
+    assert!(!ix.accounts[7].is_writable);
+}
+
+#[test]
+fn test_retrieve_builder_encodes_simulate_flag() {
+    let owner = Pubkey::new_unique();
+    let ix = instructions::retrieve_password_entry(&owner, 2, 9, true);
+    assert_eq!(&ix.data[..8], &hash(b"global:retrieve_password_entry").to_bytes()[..8]);
+    // chunk_index (u16), entry_id (u64), then the simulate flag
+    assert_eq!(ix.data.len(), 8 + 2 + 8 + 1);
+    assert_eq!(ix.data[18], 1);
+
+    let ix = instructions::retrieve_password_entry(&owner, 2, 9, false);
+    assert_eq!(ix.data[18], 0);
+}
+
+#[test]
+fn test_batch_store_builder_encodes_entries() {
+    let owner = Pubkey::new_unique();
+    let entries: Vec<NewPasswordEntry> = (0..3u8)
+        .map(|i| NewPasswordEntry {
+            encrypted_data: vec![i; 40],
+            entry_type: PasswordEntryType::Login,
+            category: 0,
+            title_hash: [i; 32],
+        })
+        .collect();
+    let ix = instructions::store_password_entries_batch(&owner, 1, entries);
+    assert_eq!(&ix.data[..8], &hash(b"global:store_password_entries_batch").to_bytes()[..8]);
+    // chunk_index (u16), then the vec length
+    assert_eq!(&ix.data[10..14], &3u32.to_le_bytes());
+
+    // Shares the store context, so the store helpers apply
+    let payer = Pubkey::new_unique();
+    let ix = instructions::with_auto_expand(ix, &payer);
+    let len = ix.accounts.len();
+    assert_eq!(ix.accounts[len - 3].pubkey, payer);
+}
+
+#[test]
+fn test_copy_entry_builder_links_both_vaults() {
+    let (source_owner, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = instructions::copy_entry_to_vault(&source_owner, 1, 7, &owner, 0, 42, vec![0u8; 40], [0u8; 32], 0);
+
+    let source = pda::master_lockbox(&source_owner).0;
+    let master = pda::master_lockbox(&owner).0;
+    assert_eq!(&ix.data[..8], &hash(b"global:copy_entry_to_vault").to_bytes()[..8]);
+    assert_eq!(ix.accounts[0].pubkey, source);
+    assert!(!ix.accounts[0].is_writable);
+    assert_eq!(ix.accounts[1].pubkey, pda::storage_chunk(&source, 1).0);
+
+    // The copy list hangs off the source entry
+    assert_eq!(ix.accounts[2].pubkey, pda::entry_copies(&source, 7).0);
+    assert!(ix.accounts[3].is_signer && ix.accounts[3].is_writable);
+    assert_eq!(ix.accounts[4].pubkey, master);
+    assert_eq!(ix.accounts[5].pubkey, pda::storage_chunk(&master, 0).0);
+
+    // The provenance record sits at the copy's future entry ID
+    assert_eq!(ix.accounts[6].pubkey, pda::entry_provenance(&master, 42).0);
+    assert!(ix.accounts[7].is_signer && ix.accounts[7].is_writable);
+}
+
+fn chunk_with(chunk_index: u16, entries: &[(u64, u8, u8)]) -> StorageChunk {
+    let mut chunk = StorageChunk {
+        version: 0,
+        master_lockbox: Pubkey::default(),
+        owner: Pubkey::default(),
+        chunk_index: 0,
+        max_capacity: 0,
+        current_size: 0,
+        data_type: StorageType::Passwords,
+        encrypted_data: Vec::new(),
+        entry_headers: Vec::new(),
+        entry_count: 0,
+        created_at: 0,
+        last_modified: 0,
+        layout_epoch: 0,
+        bump: 0,
+    };
+    chunk
+        .initialize(Pubkey::default(), Pubkey::default(), chunk_index, 1024, StorageType::Passwords, 255, 0)
+        .unwrap();
+    for &(entry_id, fill, flags) in entries {
+        let header = DataEntryHeader {
+            entry_id,
+            offset: chunk.current_size,
+            size: 40,
+            entry_type: PasswordEntryType::Login,
+            category: 0,
+            title_hash: [0u8; 32],
+            created_at: 0,
+            last_modified: 0,
+            access_count: 0,
+            flags,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        };
+        chunk.add_entry(header, vec![fill; 40], 0).unwrap();
+    }
+    chunk
+}
+
+#[test]
+fn test_export_manifest_hash_is_layout_independent() {
+    let master = Pubkey::new_unique();
+    let a = chunk_with(0, &[(3, 3, 0), (1, 1, 0)]);
+    let b = chunk_with(1, &[(2, 2, 0), (9, 9, DataEntryHeader::FLAG_ALIAS)]);
+
+    let manifest = ExportManifest::from_chunks(&master, &[a.clone(), b.clone()], 100).unwrap();
+    let reordered = ExportManifest::from_chunks(&master, &[b, a], 100).unwrap();
+    assert_eq!(manifest.hash(), reordered.hash());
+    // The alias is not exported
+    assert_eq!(manifest.entry_count(), 3);
+    assert_eq!(manifest.entries[0].entry_id, 1);
+
+    // Any change to a ciphertext changes the commitment
+    let tampered = chunk_with(0, &[(3, 4, 0), (1, 1, 0)]);
+    let other = ExportManifest::from_chunks(&master, &[tampered, chunk_with(1, &[(2, 2, 0)])], 100).unwrap();
+    assert_ne!(manifest.hash(), other.hash());
+
+    let owner = Pubkey::new_unique();
+    let ix = instructions::commit_export_manifest(&owner, &manifest);
+    assert_eq!(&ix.data[8..40], &manifest.hash());
+    assert_eq!(ix.accounts[1].pubkey, pda::export_log(&pda::master_lockbox(&owner).0).0);
+}
+
+#[test]
+fn test_fetch_decodes_and_validates_discriminator() {
+    let owner = Pubkey::new_unique();
+    let mut master = MasterLockbox {
+        version: 1,
+        owner,
+        total_entries: 0,
+        storage_chunks_count: 0,
+        subscription_tier: lockbox_client::lockbox::state::SubscriptionTier::Free,
+        last_accessed: 0,
+        subscription_expires: 0,
+        total_capacity: 0,
+        storage_used: 0,
+        storage_chunks: vec![],
+        encrypted_index: vec![],
+        next_entry_id: 1,
+        categories_count: 0,
+        created_at: 0,
+        recent_entries_enabled: false,
+        recent_entries: [0; lockbox_client::lockbox::state::MAX_RECENT_ENTRIES],
+        trash_retention: 0,
+        owner_kind: lockbox_client::lockbox::state::OwnerKind::Wallet,
+        billing_sequence: 0,
+        billing_period_start: 0,
+        billing_period_paid: 0,
+        bonus_capacity: 0,
+        bonus_capacity_expires: 0,
+        consecutive_renewals: 0,
+        subscription_status: lockbox_client::lockbox::state::SubscriptionStatus::Active,
+        dunning_window: lockbox_client::lockbox::state::DEFAULT_DUNNING_WINDOW,
+        deletion_requested_at: 0,
+        active_writer: None,
+        burst_window_start: 0,
+        burst_bytes: 0,
+        write_burst_limit: 0,
+        heat_map_period_start: 0,
+        heat_map_reads: [0; 16],
+        has_category_registry: false,
+        has_category_index: false,
+        creator: owner,
+        listed_for_transfer: false,
+        shared_storage_used: 0,
+        encrypted_settings: vec![],
+        bump: 255,
+    };
+    master.total_entries = 3;
+
+    let mut data = Vec::new();
+    master.try_serialize(&mut data).unwrap();
+    assert_eq!(&data[..8], &MasterLockbox::DISCRIMINATOR);
+
+    let mut store = HashMap::new();
+    store.insert(pda::master_lockbox(&owner).0, data.clone());
+
+    let fetched = accounts::fetch_master_lockbox(&store, &owner).unwrap().unwrap();
+    assert_eq!(fetched.owner, owner);
+    assert_eq!(fetched.total_entries, 3);
+
+    // Missing accounts are None, wrong account types fail to decode
+    assert!(accounts::fetch_master_lockbox(&store, &Pubkey::new_unique())
+        .unwrap()
+        .is_none());
+    data[0] ^= 0xff;
+    assert!(accounts::decode::<MasterLockbox>(&data).is_err());
+}
+
+
+#[test]
+fn test_recovery_setup_plans_and_verifies() {
+    let owner = Pubkey::new_unique();
+    let mut setup = RecoverySetup {
+        owner,
+        tier: SubscriptionTier::Pro,
+        threshold: 3,
+        recovery_delay: 2 * 24 * 60 * 60,
+        guardians: (0..12)
+            .map(|_| GuardianInvite {
+                guardian_pubkey: Pubkey::new_unique(),
+                nickname_encrypted: vec![7; 64],
+            })
+            .collect(),
+    };
+    let plan = setup.build(&test_key()).unwrap();
+
+    // Init goes first; every transaction fits and every guardian is added once
+    let init = instructions::initialize_recovery_config_v2(&owner, 3, setup.recovery_delay, plan.master_secret_hash);
+    assert_eq!(plan.transactions[0][0].data, init.data);
+    assert!(plan.transactions.len() > 1);
+    assert!(plan
+        .transactions
+        .iter()
+        .all(|tx| setup::transaction_size(tx) <= setup::MAX_TRANSACTION_SIZE));
+    assert_eq!(plan.shares.len(), 12);
+
+    // A config holding the plan's commitments verifies; it arms once guardians accept
+    let mut config = RecoveryConfigV2 {
+        version: 1,
+        owner,
+        threshold: 3,
+        total_guardians: 12,
+        guardians: plan
+            .shares
+            .iter()
+            .map(|(guardian, share)| GuardianV2 {
+                guardian_pubkey: *guardian,
+                share_index: share.index,
+                share_commitment: shamir::share_commitment(&share.data, guardian),
+                added_at: 0,
+                nickname_encrypted: vec![],
+                status: GuardianStatus::PendingAcceptance,
+            })
+            .collect(),
+        recovery_delay: setup.recovery_delay,
+        created_at: 0,
+        last_modified: 0,
+        last_request_id: 0,
+        master_secret_hash: plan.master_secret_hash,
+        last_recovery_attempt: 0,
+        cooldown_mode: CooldownMode::Timestamp,
+        recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+        last_recovery_attempt_slot: 0,
+        last_recovery_attempt_epoch: 0,
+        bump: 0,
+    };
+    assert_eq!(
+        plan.verify(&config).unwrap(),
+        RecoveryStatus::AwaitingGuardians { accepted: 0, threshold: 3 }
+    );
+    config.guardians.iter_mut().take(3).for_each(|g| g.status = GuardianStatus::Active);
+    assert_eq!(plan.verify(&config).unwrap(), RecoveryStatus::Armed);
+    config.guardians.pop();
+    assert!(matches!(plan.verify(&config), Err(ClientError::SetupMismatch(_))));
+
+    // Limits are checked before anything is built
+    setup.tier = SubscriptionTier::Premium;
+    assert!(matches!(setup.build(&test_key()), Err(ClientError::InvalidSetup(_))));
+    setup.tier = SubscriptionTier::Basic;
+    setup.guardians.truncate(5);
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+    setup.tier = SubscriptionTier::Premium;
+    setup.validate().unwrap();
+    setup.threshold = 6;
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+    setup.threshold = 3;
+    setup.guardians[1].guardian_pubkey = setup.guardians[0].guardian_pubkey;
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+}
+
+#[test]
+fn test_emergency_setup_validates_and_packs_contacts() {
+    let owner = Pubkey::new_unique();
+    let mut setup = EmergencySetup {
+        owner,
+        tier: SubscriptionTier::Pro,
+        inactivity_period: 90 * 24 * 60 * 60,
+        grace_period: 7 * 24 * 60 * 60,
+        notification_interval: 24 * 60 * 60,
+        contacts: (0..10)
+            .map(|_| EmergencyContactInvite {
+                contact_pubkey: Pubkey::new_unique(),
+                contact_name_encrypted: vec![1; 64],
+                access_level: EmergencyAccessLevel::ViewOnly,
+                encrypted_key: vec![2; 128],
+            })
+            .collect(),
+    };
+    let transactions = setup.build().unwrap();
+    assert_eq!(transactions[0].len(), 2);
+    assert!(transactions[1..].iter().all(|tx| tx.len() == 1));
+    assert!(transactions
+        .iter()
+        .all(|tx| setup::transaction_size(tx) <= setup::MAX_TRANSACTION_SIZE));
+
+    setup.tier = SubscriptionTier::Premium;
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+    setup.contacts.truncate(5);
+    setup.validate().unwrap();
+    setup.notification_interval = 60;
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+    setup.notification_interval = 0;
+    setup.contacts[0].encrypted_key.push(0);
+    assert!(matches!(setup.validate(), Err(ClientError::InvalidSetup(_))));
+}
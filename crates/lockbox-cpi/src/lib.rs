@@ -0,0 +1,68 @@
+//! # Lockbox CPI Interface
+//!
+//! Sanctioned composability surface for on-chain programs (DAO tooling,
+//! protocol treasuries, ...) that want to keep operational secrets in a
+//! Lockbox vault owned by one of their PDAs.
+//!
+//! ## What's exposed
+//!
+//! - **state**: Account structs (`MasterLockbox`, `StorageChunk`, ...) for
+//!   deserializing Lockbox accounts in your own instruction contexts
+//! - **cpi** (feature `cpi`, on by default): Anchor-generated CPI module
+//! - **pda**: Seed helpers for deriving Lockbox PDAs on-chain
+//! - **signed** (feature `cpi`): `invoke_signed` wrappers for vaults owned by
+//!   a caller PDA
+//!
+//! ## Example
+//!
+//! ```ignore
+//! let seeds: &[&[u8]] = &[b"vault_authority", &[ctx.bumps.vault_authority]];
+//! lockbox_cpi::signed::store_secret(
+//!     ctx.accounts.lockbox_program.to_account_info(),
+//!     lockbox_cpi::cpi::accounts::StorePasswordEntry {
+//!         master_lockbox: ctx.accounts.master_lockbox.to_account_info(),
+//!         storage_chunk: ctx.accounts.storage_chunk.to_account_info(),
+//!         owner: ctx.accounts.vault_authority.to_account_info(),
+//!         security_policy: ctx.accounts.security_policy.to_account_info(),
+//!         category_registry: None,
+//!         category_index: None,
+//!         payer: None,
+//!         system_program: None,
+//!     },
+//!     &[seeds],
+//!     0,
+//!     encrypted_data,
+//!     PasswordEntryType::ApiKey,
+//!     0,
+//!     title_hash,
+//!     None,
+//! )?;
+//! ```
+//!
+//! The owner PDA signs via `invoke_signed`, which satisfies the program's
+//! `owner: Signer` constraints. Create the vault with
+//! `signed::initialize_program_owned_lockbox` so it is recorded as
+//! `OwnerKind::ProgramDerived` and only accepts instructions arriving via CPI.
+//! Encryption stays off-chain: pass ciphertext produced by the client SDK,
+//! never plaintext.
+
+pub mod pda;
+
+#[cfg(feature = "cpi")]
+pub mod signed;
+
+/// Lockbox program ID
+pub use lockbox::ID;
+
+/// Account and enum types
+pub use lockbox::state;
+
+/// Program error codes (for matching CPI failures)
+pub use lockbox::errors::LockboxError;
+
+/// Anchor-generated CPI module (`cpi::store_password_entry`, `cpi::accounts::*`, ...)
+#[cfg(feature = "cpi")]
+pub use lockbox::cpi;
+
+/// Program marker type for `Program<'info, Lockbox>` in caller contexts
+pub use lockbox::program::Lockbox;
@@ -0,0 +1,41 @@
+//! # PDA Seeds
+//!
+//! On-chain friendly derivation helpers. `find_program_address` costs
+//! compute; callers that already store bumps should prefer
+//! `create_program_address` with the seeds below.
+
+use anchor_lang::prelude::Pubkey;
+use lockbox::state::{CategoryRegistry, MasterLockbox, SecurityPolicy, StorageChunk};
+
+/// Derive the master lockbox PDA for an owner (wallet or caller PDA)
+pub fn master_lockbox(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()], &lockbox::ID)
+}
+
+/// Derive a storage chunk PDA from the master lockbox address
+pub fn storage_chunk(master_lockbox: &Pubkey, chunk_index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.as_ref(),
+            &chunk_index.to_le_bytes(),
+        ],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the category registry PDA from the master lockbox address
+pub fn category_registry(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CategoryRegistry::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
+
+/// Derive the security policy PDA from the master lockbox address
+pub fn security_policy(master_lockbox: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SecurityPolicy::SEEDS_PREFIX, master_lockbox.as_ref()],
+        &lockbox::ID,
+    )
+}
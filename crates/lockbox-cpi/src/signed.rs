@@ -0,0 +1,151 @@
+//! # Signed CPI Wrappers
+//!
+//! Thin wrappers around the generated `cpi` functions for vaults owned by a
+//! caller PDA. Each wrapper checks the target program ID before invoking, so
+//! a caller cannot be tricked into signing for an impostor program.
+
+use anchor_lang::prelude::*;
+use lockbox::cpi::{self, accounts};
+use lockbox::state::{PasswordEntryType, StorageType};
+
+fn check_program(program: &AccountInfo) -> Result<()> {
+    require_keys_eq!(*program.key, lockbox::ID, ErrorCode::InvalidProgramId);
+    Ok(())
+}
+
+/// Create the master lockbox for a PDA owner
+///
+/// The owner PDA pays rent, so it must be system-owned and funded.
+pub fn initialize_master_lockbox<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::InitializeMasterLockbox<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::initialize_master_lockbox(CpiContext::new_with_signer(program, accounts, signer_seeds))
+}
+
+/// Create a master lockbox marked as owned by the calling program
+///
+/// `owner_seeds` are the same seeds (including bump) used in `signer_seeds`;
+/// Lockbox re-derives the PDA under `caller_program_id` before recording it.
+pub fn initialize_program_owned_lockbox<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::InitializeMasterLockbox<'info>,
+    signer_seeds: &[&[&[u8]]],
+    caller_program_id: Pubkey,
+    owner_seeds: Vec<Vec<u8>>,
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::initialize_program_owned_lockbox(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        caller_program_id,
+        owner_seeds,
+    )
+}
+
+/// Create a storage chunk for a PDA owner
+pub fn initialize_storage_chunk<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::InitializeStorageChunk<'info>,
+    signer_seeds: &[&[&[u8]]],
+    chunk_index: u16,
+    initial_capacity: u32,
+    data_type: StorageType,
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::initialize_storage_chunk(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        chunk_index,
+        initial_capacity,
+        data_type,
+    )
+}
+
+/// Store an encrypted secret
+#[allow(clippy::too_many_arguments)]
+pub fn store_secret<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::StorePasswordEntry<'info>,
+    signer_seeds: &[&[&[u8]]],
+    chunk_index: u16,
+    encrypted_data: Vec<u8>,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+    client_entry_id: Option<u64>,
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::store_password_entry(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        chunk_index,
+        encrypted_data,
+        entry_type,
+        category,
+        title_hash,
+        client_entry_id,
+    )
+}
+
+/// Retrieve an encrypted secret (returned via program return data)
+///
+/// With `simulate`, the read leaves access counts and timestamps untouched.
+pub fn retrieve_secret<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::RetrievePasswordEntry<'info>,
+    signer_seeds: &[&[&[u8]]],
+    chunk_index: u16,
+    entry_id: u64,
+    simulate: bool,
+) -> Result<Vec<u8>> {
+    check_program(&program)?;
+    let data = cpi::retrieve_password_entry(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        chunk_index,
+        entry_id,
+        simulate,
+    )?;
+    Ok(data.get())
+}
+
+/// Replace an encrypted secret
+///
+/// `expected_layout_epoch` is the chunk's `layout_epoch` as last read.
+pub fn update_secret<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::UpdatePasswordEntry<'info>,
+    signer_seeds: &[&[&[u8]]],
+    chunk_index: u16,
+    entry_id: u64,
+    new_encrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::update_password_entry(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        chunk_index,
+        entry_id,
+        new_encrypted_data,
+        expected_layout_epoch,
+    )
+}
+
+/// Delete an encrypted secret
+///
+/// `expected_layout_epoch` is the chunk's `layout_epoch` as last read.
+pub fn delete_secret<'info>(
+    program: AccountInfo<'info>,
+    accounts: accounts::DeletePasswordEntry<'info>,
+    signer_seeds: &[&[&[u8]]],
+    chunk_index: u16,
+    entry_id: u64,
+    expected_layout_epoch: u32,
+) -> Result<()> {
+    check_program(&program)?;
+    cpi::delete_password_entry(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        chunk_index,
+        entry_id,
+        expected_layout_epoch,
+    )
+}
@@ -0,0 +1,62 @@
+//! CPI interface tests: seed helpers must agree with the client SDK, and the
+//! signed wrappers must refuse to invoke anything but the Lockbox program.
+
+use anchor_lang::prelude::*;
+use lockbox_cpi::cpi::accounts::DeletePasswordEntry;
+
+#[test]
+fn test_pda_helpers_match_client() {
+    let owner = Pubkey::new_unique();
+    let master = lockbox_cpi::pda::master_lockbox(&owner);
+
+    assert_eq!(master, lockbox_client::pda::master_lockbox(&owner));
+    assert_eq!(
+        lockbox_cpi::pda::storage_chunk(&master.0, 3),
+        lockbox_client::pda::storage_chunk_for_owner(&owner, 3)
+    );
+    assert_eq!(
+        lockbox_cpi::pda::category_registry(&master.0),
+        lockbox_client::pda::category_registry(&master.0)
+    );
+    assert_eq!(
+        lockbox_cpi::pda::security_policy(&master.0),
+        lockbox_client::pda::security_policy(&master.0)
+    );
+}
+
+#[test]
+fn test_signed_wrapper_rejects_impostor_program() {
+    let impostor = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+    let system = anchor_lang::system_program::ID;
+    let mut lamports = [0u64; 4];
+    let mut data: [Vec<u8>; 4] = Default::default();
+    let [l0, l1, l2, l3] = &mut lamports;
+    let [d0, d1, d2, d3] = &mut data;
+
+    let program = AccountInfo::new(&impostor, false, false, l0, d0, &system, true, 0);
+    let master = AccountInfo::new(&key, false, true, l1, d1, &system, false, 0);
+    let chunk = AccountInfo::new(&key, false, true, l2, d2, &system, false, 0);
+    let owner = AccountInfo::new(&key, true, true, l3, d3, &system, false, 0);
+
+    let err = lockbox_cpi::signed::delete_secret(
+        program,
+        DeletePasswordEntry {
+            master_lockbox: master.clone(),
+            storage_chunk: chunk,
+            owner,
+            security_policy: master.clone(),
+            category_registry: None,
+            category_index: None,
+            entry_copies: master.clone(),
+            delete_cosigner: None,
+        },
+        &[],
+        0,
+        1,
+        0,
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ErrorCode::InvalidProgramId.into());
+}
@@ -0,0 +1,72 @@
+//! Account files for `solana-test-validator --account`
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::AccountSerialize;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::error::{FixtureError, Result};
+
+/// Lamports every fixture wallet starts with (100 SOL)
+pub const WALLET_LAMPORTS: u64 = 100_000_000_000;
+
+/// One preloaded account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountFixture {
+    pub address: Pubkey,
+    /// Owning program
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+impl AccountFixture {
+    /// A program account holding `account`, rent-exempt at `space` bytes
+    ///
+    /// `space` is the size the program would have allocated; it grows to
+    /// the serialized length if that is larger.
+    pub fn program_account<T: AccountSerialize>(address: Pubkey, account: &T, space: usize) -> Result<Self> {
+        let mut data = Vec::with_capacity(space);
+        account
+            .try_serialize(&mut data)
+            .map_err(|e| FixtureError::Serialize(e.to_string()))?;
+        if data.len() < space {
+            data.resize(space, 0);
+        }
+
+        Ok(AccountFixture {
+            address,
+            owner: lockbox_client::PROGRAM_ID,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+        })
+    }
+
+    /// A funded system account
+    pub fn wallet(address: Pubkey) -> Self {
+        AccountFixture {
+            address,
+            owner: system_program::ID,
+            lamports: WALLET_LAMPORTS,
+            data: Vec::new(),
+        }
+    }
+
+    /// `solana account --output json` representation
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.address.to_string(),
+            "account": {
+                "lamports": self.lamports,
+                "data": [STANDARD.encode(&self.data), "base64"],
+                "owner": self.owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": self.data.len(),
+            }
+        })
+    }
+}
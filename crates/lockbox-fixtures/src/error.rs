@@ -0,0 +1,23 @@
+use lockbox_client::ClientError;
+use thiserror::Error;
+
+/// Errors produced while building fixtures
+#[derive(Debug, Error)]
+pub enum FixtureError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("Program state rejected the fixture: {0}")]
+    Program(String),
+
+    #[error("Account serialization failed: {0}")]
+    Serialize(String),
+}
+
+impl From<anchor_lang::error::Error> for FixtureError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        FixtureError::Program(err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, FixtureError>;
@@ -0,0 +1,100 @@
+//! Deterministic fixture keypairs
+//!
+//! A keypair's seed is `SHA256("lockbox-fixtures-v1" || label)`, and its
+//! public key is derived the way Solana derives Ed25519 wallets.
+
+use anchor_lang::prelude::Pubkey;
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{impls, CryptoRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+
+/// Domain separator for every fixture seed
+pub const SEED_DOMAIN: &[u8] = b"lockbox-fixtures-v1";
+
+/// `SHA256(SEED_DOMAIN || label)`
+pub fn derive_seed(label: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SEED_DOMAIN);
+    hasher.update(label.as_bytes());
+    hasher.finalize().into()
+}
+
+/// A labelled Ed25519 keypair
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureKeypair {
+    pub label: String,
+    pub seed: [u8; 32],
+    pub pubkey: Pubkey,
+}
+
+impl FixtureKeypair {
+    /// Derive the keypair for `label`
+    pub fn derive(label: &str) -> Self {
+        let seed = derive_seed(label);
+        let hash = Sha512::digest(seed);
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&hash[..32]);
+        bytes[0] &= 248;
+        bytes[31] &= 127;
+        bytes[31] |= 64;
+        let point = &Scalar::from_bits(bytes) * &ED25519_BASEPOINT_TABLE;
+
+        FixtureKeypair {
+            label: label.to_string(),
+            seed,
+            pubkey: Pubkey::new_from_array(point.compress().to_bytes()),
+        }
+    }
+
+    /// The 64-byte `seed || pubkey` array the Solana CLI reads as a keypair file
+    pub fn to_json(&self) -> String {
+        let bytes: Vec<u8> = self.seed.iter().chain(self.pubkey.as_ref()).copied().collect();
+        serde_json::to_string(&bytes).expect("byte arrays always serialize")
+    }
+}
+
+/// SHA-256 counter-mode byte stream, for reproducible Shamir splits
+///
+/// Only for fixtures: the output is predictable from the label.
+pub struct FixtureRng {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl FixtureRng {
+    pub fn new(label: &str) -> Self {
+        FixtureRng {
+            seed: derive_seed(label),
+            counter: 0,
+        }
+    }
+}
+
+impl RngCore for FixtureRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(32) {
+            let mut hasher = Sha256::new();
+            hasher.update(self.seed);
+            hasher.update(self.counter.to_le_bytes());
+            self.counter += 1;
+            let block: [u8; 32] = hasher.finalize().into();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixtureRng {}
@@ -0,0 +1,29 @@
+//! # Lockbox Fixtures
+//!
+//! Deterministic sample vaults for frontend and SDK integration testing.
+//! Every key, secret and account is derived from a fixed label and the
+//! fixture timestamp, so two runs with the same `now` produce identical
+//! accounts and every team tests against the same vaults.
+//!
+//! Accounts are built from the program's own state types and written in
+//! `solana account --output json` format, which `solana-test-validator`
+//! preloads with `--account`. Nothing is sent as a transaction, so a
+//! localnet is ready as soon as the validator starts.
+//!
+//! ## Modules
+//!
+//! - **keys**: Deterministic Ed25519 keypairs in Solana CLI format
+//! - **account**: Account files for `solana-test-validator --account`
+//! - **vaults**: The sample vaults (every tier, recovery, emergency access,
+//!   a program-owned shared vault) and their manifest
+//!
+//! Fixture keys and secrets are public by construction. Never fund or use
+//! them outside a local validator.
+
+pub mod account;
+pub mod error;
+pub mod keys;
+pub mod vaults;
+
+pub use error::FixtureError;
+pub use vaults::{build_fixtures, FixtureSet};
@@ -0,0 +1,150 @@
+//! # lockbox-fixtures
+//!
+//! ```text
+//! lockbox-fixtures write [--out <DIR>] [--now <UNIX_TIMESTAMP>]
+//! lockbox-fixtures localnet [--program <PATH_TO_SO>] [--out <DIR>] [--ledger <DIR>] [--now <UNIX_TIMESTAMP>]
+//! ```
+//!
+//! `write` writes `manifest.json`, `keypairs/` and `accounts/` to `--out`
+//! (default `.lockbox-fixtures`). `localnet` writes them too, then runs
+//! `solana-test-validator --reset` with the program (default
+//! `target/deploy/lockbox.so`) deployed and every fixture account
+//! preloaded. `--now` defaults to the current time.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lockbox_fixtures::vaults::keypair_file;
+use lockbox_fixtures::{build_fixtures, FixtureSet};
+
+type CliResult<T> = std::result::Result<T, String>;
+
+/// Minimal `--flag value` parser
+struct Args {
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(raw: &[String]) -> CliResult<Self> {
+        let mut flags = Vec::new();
+        let mut iter = raw.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument: {arg}"))?;
+            let value = match iter.peek() {
+                Some(next) if !next.starts_with("--") => iter.next().cloned(),
+                _ => None,
+            };
+            flags.push((name.to_string(), value));
+        }
+        Ok(Self { flags })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, v)| v.as_deref())
+    }
+}
+
+fn now(args: &Args) -> CliResult<i64> {
+    match args.get("now") {
+        Some(value) => value.parse().map_err(|_| format!("invalid --now: {value}")),
+        None => Ok(SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default()),
+    }
+}
+
+fn write_file(path: &Path, contents: String) -> CliResult<()> {
+    fs::write(path, contents).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Write the manifest, keypairs and account files; returns the account files
+fn write_fixtures(set: &FixtureSet, out: &Path) -> CliResult<Vec<(String, PathBuf)>> {
+    let keypairs = out.join("keypairs");
+    let accounts = out.join("accounts");
+    for dir in [&keypairs, &accounts] {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+
+    for keypair in set.keypairs() {
+        write_file(&keypairs.join(keypair_file(keypair)), keypair.to_json())?;
+    }
+
+    let mut files = Vec::new();
+    for account in &set.accounts {
+        let address = account.address.to_string();
+        let path = accounts.join(format!("{address}.json"));
+        write_file(&path, account.to_json().to_string())?;
+        files.push((address, path));
+    }
+
+    let manifest = serde_json::to_string_pretty(&set.manifest()).map_err(|e| e.to_string())?;
+    write_file(&out.join("manifest.json"), manifest)?;
+
+    eprintln!(
+        "Wrote {} vaults, {} accounts and {} keypairs to {}",
+        set.vaults.len(),
+        set.accounts.len(),
+        set.keypairs().len(),
+        out.display()
+    );
+    Ok(files)
+}
+
+fn write(args: &Args) -> CliResult<()> {
+    let set = build_fixtures(now(args)?).map_err(|e| e.to_string())?;
+    write_fixtures(&set, Path::new(args.get("out").unwrap_or(".lockbox-fixtures")))?;
+    Ok(())
+}
+
+fn localnet(args: &Args) -> CliResult<()> {
+    let program = args.get("program").unwrap_or("target/deploy/lockbox.so");
+    if !Path::new(program).exists() {
+        return Err(format!("{program} not found; run `anchor build` or pass --program"));
+    }
+
+    let out = Path::new(args.get("out").unwrap_or(".lockbox-fixtures"));
+    let set = build_fixtures(now(args)?).map_err(|e| e.to_string())?;
+    let files = write_fixtures(&set, out)?;
+
+    let mut cmd = Command::new("solana-test-validator");
+    cmd.arg("--reset")
+        .args(["--ledger", args.get("ledger").unwrap_or(".lockbox-fixtures/ledger")])
+        .args(["--bpf-program", &lockbox_client::PROGRAM_ID.to_string(), program]);
+    for (address, path) in &files {
+        cmd.arg("--account").arg(address).arg(path);
+    }
+
+    let status = cmd
+        .status()
+        .map_err(|e| format!("failed to start solana-test-validator: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("solana-test-validator exited with {status}"))
+    }
+}
+
+fn main() {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let result = match raw.split_first() {
+        Some((command, rest)) => Args::parse(rest).and_then(|args| match command.as_str() {
+            "write" => write(&args),
+            "localnet" => localnet(&args),
+            other => Err(format!("unknown command: {other}")),
+        }),
+        None => Err("usage: lockbox-fixtures <write|localnet> [options]".to_string()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
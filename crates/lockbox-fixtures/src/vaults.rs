@@ -0,0 +1,485 @@
+//! The sample vaults
+//!
+//! | Vault     | Tier    | Extras                                              |
+//! |-----------|---------|-----------------------------------------------------|
+//! | `free`    | Free    | -                                                   |
+//! | `basic`   | Basic   | -                                                   |
+//! | `premium` | Premium | Recovery V2 (2 of 3, armed), emergency access (2)   |
+//! | `pro`     | Pro     | Recovery V2 (3 of 5, not armed), emergency access (3) |
+//! | `shared`  | Premium | Owned by a PDA of a fixture program (`lockbox-cpi`) |
+//!
+//! Every vault has one storage chunk holding the same sample entries,
+//! encrypted under the vault's fixture session key. Emergency contacts'
+//! encrypted keys are random placeholders, not real key envelopes.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorDeserialize;
+use lockbox_client::lockbox::state::{
+    DataEntryHeader, EmergencyAccess, EmergencyAccessLevel, EmergencyContact,
+    EmergencyContactStatus, EmergencyStatus, GuardianStatus, GuardianV2, MasterLockbox, OwnerKind,
+    PasswordEntryType, RecoveryConfigV2, StorageChunk, StorageChunkInfo, StorageType,
+    SubscriptionTier, DEFAULT_GRACE_PERIOD, DEFAULT_INACTIVITY_PERIOD, DEFAULT_INVITATION_TTL,
+    MIN_RECOVERY_DELAY,
+};
+use lockbox_client::shamir::{self, Share};
+use lockbox_client::{crypto, pda};
+use rand_core::RngCore;
+use serde_json::{json, Value};
+
+use crate::account::AccountFixture;
+use crate::error::Result;
+use crate::keys::{derive_seed, FixtureKeypair, FixtureRng};
+
+/// Seed of the shared vault's owner PDA under the fixture program
+pub const SHARED_VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+
+/// Entries stored in every vault: type, title, plaintext
+const SAMPLE_ENTRIES: &[(PasswordEntryType, &str, &str)] = &[
+    (
+        PasswordEntryType::Login,
+        "GitHub",
+        r#"{"title":"GitHub","username":"fixture","password":"correct-horse-battery"}"#,
+    ),
+    (
+        PasswordEntryType::SecureNote,
+        "Wi-Fi",
+        r#"{"title":"Wi-Fi","notes":"SSID lockbox-lab / pass hunter2"}"#,
+    ),
+    (
+        PasswordEntryType::ApiKey,
+        "Stripe",
+        r#"{"title":"Stripe","key":"sk_test_fixture"}"#,
+    ),
+];
+
+struct RecoverySpec {
+    threshold: u8,
+    guardians: u8,
+    accepted: u8,
+}
+
+struct VaultSpec {
+    name: &'static str,
+    tier: SubscriptionTier,
+    recovery: Option<RecoverySpec>,
+    emergency_contacts: u8,
+    program_owned: bool,
+}
+
+const VAULTS: &[VaultSpec] = &[
+    VaultSpec {
+        name: "free",
+        tier: SubscriptionTier::Free,
+        recovery: None,
+        emergency_contacts: 0,
+        program_owned: false,
+    },
+    VaultSpec {
+        name: "basic",
+        tier: SubscriptionTier::Basic,
+        recovery: None,
+        emergency_contacts: 0,
+        program_owned: false,
+    },
+    VaultSpec {
+        name: "premium",
+        tier: SubscriptionTier::Premium,
+        recovery: Some(RecoverySpec { threshold: 2, guardians: 3, accepted: 2 }),
+        emergency_contacts: 2,
+        program_owned: false,
+    },
+    VaultSpec {
+        name: "pro",
+        tier: SubscriptionTier::Pro,
+        recovery: Some(RecoverySpec { threshold: 3, guardians: 5, accepted: 1 }),
+        emergency_contacts: 3,
+        program_owned: false,
+    },
+    VaultSpec {
+        name: "shared",
+        tier: SubscriptionTier::Premium,
+        recovery: None,
+        emergency_contacts: 0,
+        program_owned: true,
+    },
+];
+
+/// A sample entry as stored
+#[derive(Clone, Debug)]
+pub struct SampleEntry {
+    pub entry_id: u64,
+    pub entry_type: PasswordEntryType,
+    pub title: &'static str,
+}
+
+/// A guardian with its Shamir share
+#[derive(Clone, Debug)]
+pub struct GuardianFixture {
+    pub keypair: FixtureKeypair,
+    pub share: Share,
+    /// Whether the guardian is `Active` rather than `PendingAcceptance`
+    pub accepted: bool,
+}
+
+/// A vault's Recovery V2 config
+#[derive(Clone, Debug)]
+pub struct RecoveryFixture {
+    pub threshold: u8,
+    pub master_secret: [u8; 32],
+    pub guardians: Vec<GuardianFixture>,
+}
+
+/// One sample vault
+#[derive(Clone, Debug)]
+pub struct VaultFixture {
+    pub name: &'static str,
+    pub tier: SubscriptionTier,
+    pub owner: Pubkey,
+    /// Signing keypair of a wallet owner (None for the program-owned vault)
+    pub owner_keypair: Option<FixtureKeypair>,
+    /// Program and seeds (bump included) of a PDA owner
+    pub owner_program: Option<(Pubkey, Vec<Vec<u8>>)>,
+    /// Key the sample entries are encrypted under
+    pub session_key: [u8; 32],
+    /// Key the sample entries' title hashes are computed with
+    pub search_key: [u8; 32],
+    pub entries: Vec<SampleEntry>,
+    pub recovery: Option<RecoveryFixture>,
+    /// Emergency contacts; the first has accepted
+    pub emergency_contacts: Vec<FixtureKeypair>,
+}
+
+/// Every sample vault and the accounts to preload
+#[derive(Clone, Debug)]
+pub struct FixtureSet {
+    /// Timestamp the accounts were created at
+    pub now: i64,
+    pub vaults: Vec<VaultFixture>,
+    pub accounts: Vec<AccountFixture>,
+}
+
+/// An account with every field zeroed, ready for its `initialize`
+fn zeroed<T: AnchorDeserialize>() -> T {
+    T::deserialize(&mut &vec![0u8; 8192][..]).expect("all-zero bytes decode to an empty account")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Build every sample vault as of `now`
+///
+/// Paid subscriptions run from `now`, so pass the localnet's current time.
+pub fn build_fixtures(now: i64) -> Result<FixtureSet> {
+    let mut set = FixtureSet {
+        now,
+        vaults: Vec::new(),
+        accounts: Vec::new(),
+    };
+    for spec in VAULTS {
+        build_vault(spec, now, &mut set)?;
+    }
+    Ok(set)
+}
+
+fn build_vault(spec: &VaultSpec, now: i64, set: &mut FixtureSet) -> Result<()> {
+    let name = spec.name;
+    let (owner, owner_keypair, owner_program) = if spec.program_owned {
+        let program_id = FixtureKeypair::derive(&format!("{name}/program")).pubkey;
+        let (authority, bump) =
+            Pubkey::find_program_address(&[SHARED_VAULT_AUTHORITY_SEED], &program_id);
+        let seeds = vec![SHARED_VAULT_AUTHORITY_SEED.to_vec(), vec![bump]];
+        (authority, None, Some((program_id, seeds)))
+    } else {
+        let keypair = FixtureKeypair::derive(&format!("{name}/owner"));
+        set.accounts.push(AccountFixture::wallet(keypair.pubkey));
+        (keypair.pubkey, Some(keypair), None)
+    };
+
+    let (master_address, master_bump) = pda::master_lockbox(&owner);
+    let mut master: MasterLockbox = zeroed();
+    master.initialize(owner, master_bump, now)?;
+    if let Some((program_id, _)) = &owner_program {
+        master.owner_kind = OwnerKind::ProgramDerived { program_id: *program_id };
+    }
+    if spec.tier != SubscriptionTier::Free {
+        master.upgrade_subscription(spec.tier, now)?;
+    }
+
+    let (chunk_address, chunk_bump) = pda::storage_chunk(&master_address, 0);
+    let capacity = StorageChunk::MIN_CHUNK_SIZE;
+    let mut chunk: StorageChunk = zeroed();
+    chunk.initialize(master_address, owner, 0, capacity, StorageType::Passwords, chunk_bump, now)?;
+    master.add_chunk(StorageChunkInfo {
+        chunk_address,
+        chunk_index: 0,
+        max_capacity: capacity,
+        size_used: 0,
+        data_type: StorageType::Passwords,
+        created_at: now,
+        last_modified: now,
+        layout_epoch: chunk.layout_epoch,
+    })?;
+
+    let session_key = derive_seed(&format!("{name}/session-key"));
+    let search_key = derive_seed(&format!("{name}/search-key"));
+    let mut nonces = FixtureRng::new(&format!("{name}/nonces"));
+    let mut entries = Vec::new();
+    for &(entry_type, title, plaintext) in SAMPLE_ENTRIES {
+        let payload = crypto::encrypt_entry_with_rng(&session_key, plaintext.as_bytes(), &mut nonces)?;
+        let entry_id = master.get_next_entry_id();
+        let header = DataEntryHeader {
+            entry_id,
+            offset: chunk.current_size,
+            size: payload.len() as u32,
+            entry_type,
+            category: 0,
+            title_hash: crypto::title_hash(&search_key, title),
+            created_at: now,
+            last_modified: now,
+            access_count: 0,
+            flags: 0,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        };
+        chunk.add_entry(header, payload, now)?;
+        master.update_chunk_usage(0, chunk.current_size)?;
+        master.increment_entries();
+        entries.push(SampleEntry { entry_id, entry_type, title });
+    }
+
+    let recovery = match &spec.recovery {
+        Some(recovery) => Some(build_recovery(name, owner, recovery, now, set)?),
+        None => None,
+    };
+    let emergency_contacts = if spec.emergency_contacts > 0 {
+        build_emergency(name, owner, spec.emergency_contacts, now, set)?
+    } else {
+        Vec::new()
+    };
+
+    let master_space = master.space_for_chunks(master.storage_chunks.len());
+    set.accounts.push(AccountFixture::program_account(master_address, &master, master_space)?);
+    set.accounts.push(AccountFixture::program_account(
+        chunk_address,
+        &chunk,
+        StorageChunk::space(capacity),
+    )?);
+
+    set.vaults.push(VaultFixture {
+        name,
+        tier: spec.tier,
+        owner,
+        owner_keypair,
+        owner_program,
+        session_key,
+        search_key,
+        entries,
+        recovery,
+        emergency_contacts,
+    });
+    Ok(())
+}
+
+fn build_recovery(
+    name: &str,
+    owner: Pubkey,
+    spec: &RecoverySpec,
+    now: i64,
+    set: &mut FixtureSet,
+) -> Result<RecoveryFixture> {
+    let master_secret = derive_seed(&format!("{name}/recovery-secret"));
+    let shares = shamir::split_secret_with_rng(
+        &master_secret,
+        spec.threshold,
+        spec.guardians,
+        &mut FixtureRng::new(&format!("{name}/shamir")),
+    )?;
+
+    let guardians: Vec<GuardianFixture> = shares
+        .into_iter()
+        .enumerate()
+        .map(|(i, share)| GuardianFixture {
+            keypair: FixtureKeypair::derive(&format!("{name}/guardian-{}", share.index)),
+            share,
+            accepted: i < spec.accepted as usize,
+        })
+        .collect();
+
+    let (config_address, bump) = pda::recovery_config_v2(&owner);
+    let mut config: RecoveryConfigV2 = zeroed();
+    config.version = RecoveryConfigV2::CURRENT_VERSION;
+    config.owner = owner;
+    config.threshold = spec.threshold;
+    config.recovery_delay = MIN_RECOVERY_DELAY;
+    config.created_at = now;
+    config.last_modified = now;
+    config.master_secret_hash = shamir::master_secret_hash(&master_secret);
+    config.bump = bump;
+    for guardian in &guardians {
+        let guardian_pubkey = guardian.keypair.pubkey;
+        config.guardians.push(GuardianV2 {
+            guardian_pubkey,
+            share_index: guardian.share.index,
+            share_commitment: shamir::share_commitment(&guardian.share.data, &guardian_pubkey),
+            added_at: now,
+            nickname_encrypted: Vec::new(),
+            status: if guardian.accepted {
+                GuardianStatus::Active
+            } else {
+                GuardianStatus::PendingAcceptance
+            },
+        });
+        set.accounts.push(AccountFixture::wallet(guardian_pubkey));
+    }
+    config.total_guardians = config.guardians.len() as u8;
+
+    set.accounts.push(AccountFixture::program_account(
+        config_address,
+        &config,
+        RecoveryConfigV2::space_for(config.guardians.len()),
+    )?);
+
+    Ok(RecoveryFixture {
+        threshold: spec.threshold,
+        master_secret,
+        guardians,
+    })
+}
+
+fn build_emergency(
+    name: &str,
+    owner: Pubkey,
+    count: u8,
+    now: i64,
+    set: &mut FixtureSet,
+) -> Result<Vec<FixtureKeypair>> {
+    let (access_address, bump) = pda::emergency_access(&owner);
+    let mut access: EmergencyAccess = zeroed();
+    access.version = EmergencyAccess::CURRENT_VERSION;
+    access.owner = owner;
+    access.inactivity_period = DEFAULT_INACTIVITY_PERIOD;
+    access.grace_period = DEFAULT_GRACE_PERIOD;
+    access.invitation_ttl = DEFAULT_INVITATION_TTL;
+    access.last_activity = now;
+    access.status = EmergencyStatus::Active;
+    access.created_at = now;
+    access.bump = bump;
+
+    let mut placeholder_keys = FixtureRng::new(&format!("{name}/emergency-keys"));
+    let contacts: Vec<FixtureKeypair> = (1..=count)
+        .map(|i| FixtureKeypair::derive(&format!("{name}/contact-{i}")))
+        .collect();
+    for (i, contact) in contacts.iter().enumerate() {
+        let mut encrypted_key = vec![0u8; 32 + 24 + 32 + 16];
+        placeholder_keys.fill_bytes(&mut encrypted_key);
+        access.emergency_contacts.push(EmergencyContact {
+            contact_pubkey: contact.pubkey,
+            contact_name_encrypted: Vec::new(),
+            access_level: EmergencyAccessLevel::ViewOnly,
+            encrypted_key,
+            added_at: now,
+            access_granted_at: None,
+            status: if i == 0 {
+                EmergencyContactStatus::Active
+            } else {
+                EmergencyContactStatus::PendingAcceptance
+            },
+            invited_at: now,
+            expires_at: now + DEFAULT_INVITATION_TTL,
+        });
+        set.accounts.push(AccountFixture::wallet(contact.pubkey));
+    }
+
+    set.accounts.push(AccountFixture::program_account(
+        access_address,
+        &access,
+        EmergencyAccess::space_for(access.emergency_contacts.len()),
+    )?);
+    Ok(contacts)
+}
+
+impl FixtureSet {
+    /// Look up a vault by name
+    pub fn vault(&self, name: &str) -> Option<&VaultFixture> {
+        self.vaults.iter().find(|v| v.name == name)
+    }
+
+    /// Every keypair the fixtures use, owners first
+    pub fn keypairs(&self) -> Vec<&FixtureKeypair> {
+        let owners = self.vaults.iter().filter_map(|v| v.owner_keypair.as_ref());
+        let guardians = self
+            .vaults
+            .iter()
+            .filter_map(|v| v.recovery.as_ref())
+            .flat_map(|r| r.guardians.iter().map(|g| &g.keypair));
+        let contacts = self.vaults.iter().flat_map(|v| v.emergency_contacts.iter());
+        owners.chain(guardians).chain(contacts).collect()
+    }
+
+    /// JSON description of every vault for test harnesses
+    ///
+    /// Keypairs are named by `keypair_file`; shares use the guardian
+    /// daemon's `INDEX:HEX` format.
+    pub fn manifest(&self) -> Value {
+        let vaults: Vec<Value> = self
+            .vaults
+            .iter()
+            .map(|vault| {
+                let master_lockbox = pda::master_lockbox(&vault.owner).0;
+                json!({
+                    "name": vault.name,
+                    "tier": format!("{:?}", vault.tier),
+                    "owner": vault.owner.to_string(),
+                    "owner_keypair": vault.owner_keypair.as_ref().map(keypair_file),
+                    "owner_program": vault.owner_program.as_ref().map(|(program_id, seeds)| json!({
+                        "program_id": program_id.to_string(),
+                        "seeds": seeds.iter().map(|s| hex(s)).collect::<Vec<_>>(),
+                    })),
+                    "master_lockbox": master_lockbox.to_string(),
+                    "storage_chunks": [pda::storage_chunk(&master_lockbox, 0).0.to_string()],
+                    "session_key": hex(&vault.session_key),
+                    "search_key": hex(&vault.search_key),
+                    "entries": vault.entries.iter().map(|e| json!({
+                        "entry_id": e.entry_id,
+                        "entry_type": format!("{:?}", e.entry_type),
+                        "title": e.title,
+                    })).collect::<Vec<_>>(),
+                    "recovery": vault.recovery.as_ref().map(|r| json!({
+                        "config": pda::recovery_config_v2(&vault.owner).0.to_string(),
+                        "threshold": r.threshold,
+                        "master_secret": hex(&r.master_secret),
+                        "guardians": r.guardians.iter().map(|g| json!({
+                            "pubkey": g.keypair.pubkey.to_string(),
+                            "keypair": keypair_file(&g.keypair),
+                            "share": format!("{}:{}", g.share.index, hex(&g.share.data)),
+                            "accepted": g.accepted,
+                        })).collect::<Vec<_>>(),
+                    })),
+                    "emergency_access": (!vault.emergency_contacts.is_empty()).then(|| json!({
+                        "config": pda::emergency_access(&vault.owner).0.to_string(),
+                        "contacts": vault.emergency_contacts.iter().map(|c| json!({
+                            "pubkey": c.pubkey.to_string(),
+                            "keypair": keypair_file(c),
+                        })).collect::<Vec<_>>(),
+                    })),
+                })
+            })
+            .collect();
+
+        json!({
+            "program_id": lockbox_client::PROGRAM_ID.to_string(),
+            "now": self.now,
+            "vaults": vaults,
+        })
+    }
+}
+
+/// File name a keypair is written to, relative to the `keypairs` directory
+pub fn keypair_file(keypair: &FixtureKeypair) -> String {
+    format!("{}.json", keypair.label.replace('/', "-"))
+}
@@ -0,0 +1,112 @@
+//! Fixture tests: determinism, decodable accounts and usable keys and shares.
+
+use anchor_lang::prelude::Pubkey;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use lockbox_client::lockbox::state::{
+    EmergencyAccess, MasterLockbox, OwnerKind, RecoveryConfigV2, StorageChunk, SubscriptionTier,
+};
+use lockbox_client::{accounts, crypto, pda, shamir};
+use lockbox_fixtures::build_fixtures;
+use lockbox_fixtures::keys::FixtureKeypair;
+use sha2::{Digest, Sha512};
+
+const NOW: i64 = 1_700_000_000;
+
+fn account_data(set: &lockbox_fixtures::FixtureSet, address: &Pubkey) -> Vec<u8> {
+    set.accounts
+        .iter()
+        .find(|a| &a.address == address)
+        .unwrap_or_else(|| panic!("no fixture account {address}"))
+        .data
+        .clone()
+}
+
+#[test]
+fn test_fixtures_are_deterministic() {
+    let first = build_fixtures(NOW).unwrap();
+    let second = build_fixtures(NOW).unwrap();
+    assert_eq!(first.accounts, second.accounts);
+    assert_eq!(first.manifest(), second.manifest());
+
+    // Every address is preloaded once
+    let mut addresses: Vec<_> = first.accounts.iter().map(|a| a.address).collect();
+    addresses.sort();
+    addresses.dedup();
+    assert_eq!(addresses.len(), first.accounts.len());
+
+    // Keypair files hold the seed and the matching Ed25519 public key
+    let keypair = FixtureKeypair::derive("premium/owner");
+    let hash = Sha512::digest(keypair.seed);
+    let mut scalar = [0u8; 32];
+    scalar.copy_from_slice(&hash[..32]);
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    let point = ED25519_BASEPOINT_POINT * Scalar::from_bits(scalar);
+    assert_eq!(keypair.pubkey.to_bytes(), point.compress().to_bytes());
+    let bytes: Vec<u8> = serde_json::from_str(&keypair.to_json()).unwrap();
+    assert_eq!(&bytes[32..], keypair.pubkey.as_ref());
+}
+
+#[test]
+fn test_vaults_decode_with_every_tier() {
+    let set = build_fixtures(NOW).unwrap();
+    let tiers: Vec<_> = set.vaults.iter().map(|v| v.tier).collect();
+    for tier in [SubscriptionTier::Free, SubscriptionTier::Basic, SubscriptionTier::Premium, SubscriptionTier::Pro] {
+        assert!(tiers.contains(&tier));
+    }
+
+    for vault in &set.vaults {
+        let master_address = pda::master_lockbox(&vault.owner).0;
+        let master: MasterLockbox = accounts::decode(&account_data(&set, &master_address)).unwrap();
+        assert_eq!(master.subscription_tier, vault.tier);
+        assert_eq!(master.total_entries, vault.entries.len() as u64);
+        assert!(master.is_storage_used_consistent());
+
+        let chunk: StorageChunk =
+            accounts::decode(&account_data(&set, &pda::storage_chunk(&master_address, 0).0)).unwrap();
+        for entry in &vault.entries {
+            let header = chunk.get_entry_header(entry.entry_id).unwrap();
+            assert_eq!(header.title_hash, crypto::title_hash(&vault.search_key, entry.title));
+            let payload = chunk.get_entry_data(entry.entry_id).unwrap();
+            assert!(crypto::decrypt_entry(&vault.session_key, &payload).is_ok());
+        }
+    }
+
+    // The shared vault is owned by a PDA of the fixture program
+    let shared = set.vault("shared").unwrap();
+    let (program_id, seeds) = shared.owner_program.clone().unwrap();
+    let seeds: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    assert_eq!(Pubkey::create_program_address(&seeds, &program_id).unwrap(), shared.owner);
+    let master: MasterLockbox =
+        accounts::decode(&account_data(&set, &pda::master_lockbox(&shared.owner).0)).unwrap();
+    assert_eq!(master.owner_kind, OwnerKind::ProgramDerived { program_id });
+}
+
+#[test]
+fn test_recovery_shares_match_commitments() {
+    let set = build_fixtures(NOW).unwrap();
+
+    for (name, armed) in [("premium", true), ("pro", false)] {
+        let vault = set.vault(name).unwrap();
+        let recovery = vault.recovery.as_ref().unwrap();
+        let config: RecoveryConfigV2 =
+            accounts::decode(&account_data(&set, &pda::recovery_config_v2(&vault.owner).0)).unwrap();
+        assert_eq!(config.is_armed(), armed);
+        assert_eq!(config.master_secret_hash, shamir::master_secret_hash(&recovery.master_secret));
+
+        for guardian in &recovery.guardians {
+            assert!(config.verify_share_commitment(&guardian.keypair.pubkey, &guardian.share.data));
+        }
+        let quorum: Vec<_> = recovery.guardians[..recovery.threshold as usize]
+            .iter()
+            .map(|g| g.share.clone())
+            .collect();
+        assert_eq!(shamir::reconstruct_secret(&quorum).unwrap(), recovery.master_secret);
+
+        let access: EmergencyAccess =
+            accounts::decode(&account_data(&set, &pda::emergency_access(&vault.owner).0)).unwrap();
+        assert_eq!(access.emergency_contacts.len(), vault.emergency_contacts.len());
+    }
+}
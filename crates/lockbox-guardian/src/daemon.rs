@@ -0,0 +1,207 @@
+//! # Guardian Daemon Core
+//!
+//! Transport-agnostic state machine: feed it program log lines and a clock,
+//! and it tells you what to do. Account reads go through
+//! [`AccountFetcher`], so the same logic runs against an RPC node, a test
+//! bank, or a snapshot.
+//!
+//! ## Lifecycle of a request
+//!
+//! 1. `RecoveryInitiated(V2)Event` observed → request re-read and verified
+//! 2. Delay not elapsed → request parked until `ready_at`
+//! 3. Ready → [`Action::Confirm`] (V2) or [`Action::Approve`] (V1)
+//! 4. Anything suspicious → [`Action::Reject`] for a human to review
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use lockbox_client::accounts::{self, AccountFetcher};
+use lockbox_client::instructions;
+
+use crate::error::Result;
+use crate::events::{parse_log_line, RecoveryInitiated, RecoveryVersion};
+use crate::verify::{verify_request_v1, verify_request_v2, GuardianPolicy, Rejection, Verdict};
+
+/// Something the operator (or the submitting transport) should do
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// V2: submit `confirm_participation`, then seal the share to `requester`
+    Confirm {
+        owner: Pubkey,
+        request_id: u64,
+        requester: Pubkey,
+        instruction: Instruction,
+    },
+
+    /// V1: submit `approve_recovery` with the guardian's decrypted share
+    ///
+    /// V1 shares live on-chain encrypted to the guardian, so the daemon
+    /// cannot build the instruction without the operator's decryption step;
+    /// use [`lockbox_client::instructions::approve_recovery`].
+    Approve {
+        owner: Pubkey,
+        request_id: u64,
+        requester: Pubkey,
+    },
+
+    /// Request parked until its recovery delay elapses
+    Scheduled {
+        owner: Pubkey,
+        request_id: u64,
+        ready_at: i64,
+    },
+
+    /// Request failed verification
+    Reject {
+        owner: Pubkey,
+        request_id: u64,
+        reason: Rejection,
+    },
+}
+
+/// Guardian daemon state
+pub struct Daemon {
+    /// This guardian's wallet
+    pub guardian: Pubkey,
+
+    /// Operator policy
+    pub policy: GuardianPolicy,
+
+    /// Verified requests waiting for their delay, keyed by (version, owner, request_id)
+    pending: BTreeMap<(RecoveryVersion, Pubkey, u64), RecoveryInitiated>,
+}
+
+impl Daemon {
+    pub fn new(guardian: Pubkey, policy: GuardianPolicy) -> Self {
+        Self {
+            guardian,
+            policy,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Number of requests waiting for their delay to elapse
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Process one program log line
+    pub fn handle_log_line(
+        &mut self,
+        fetcher: &impl AccountFetcher,
+        line: &str,
+        now: i64,
+    ) -> Result<Option<Action>> {
+        match parse_log_line(line) {
+            Some(event) => self.handle_event(fetcher, event, now).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Verify an observed recovery event against on-chain state
+    pub fn handle_event(
+        &mut self,
+        fetcher: &impl AccountFetcher,
+        event: RecoveryInitiated,
+        now: i64,
+    ) -> Result<Action> {
+        let key = (event.version, event.owner, event.request_id);
+        let verdict = self.verify(fetcher, &event, now)?;
+
+        let action = match verdict {
+            Verdict::Wait { ready_at } => {
+                self.pending.insert(key, event.clone());
+                Action::Scheduled {
+                    owner: event.owner,
+                    request_id: event.request_id,
+                    ready_at,
+                }
+            }
+            Verdict::Approve => {
+                self.pending.remove(&key);
+                self.ready_action(&event)
+            }
+            Verdict::Reject(reason) => {
+                self.pending.remove(&key);
+                Action::Reject {
+                    owner: event.owner,
+                    request_id: event.request_id,
+                    reason,
+                }
+            }
+        };
+
+        Ok(action)
+    }
+
+    /// Re-verify parked requests whose delay has elapsed
+    pub fn poll(&mut self, fetcher: &impl AccountFetcher, now: i64) -> Result<Vec<Action>> {
+        let due: Vec<RecoveryInitiated> = self
+            .pending
+            .values()
+            .filter(|event| event.ready_at <= now)
+            .cloned()
+            .collect();
+
+        due.into_iter()
+            .map(|event| self.handle_event(fetcher, event, now))
+            .collect()
+    }
+
+    fn verify(
+        &self,
+        fetcher: &impl AccountFetcher,
+        event: &RecoveryInitiated,
+        now: i64,
+    ) -> Result<Verdict> {
+        let reject = |reason| Ok(Verdict::Reject(reason));
+
+        match event.version {
+            RecoveryVersion::V2 => {
+                let config = accounts::fetch_recovery_config_v2(fetcher, &event.owner)?;
+                let request =
+                    accounts::fetch_recovery_request_v2(fetcher, &event.owner, event.request_id)?;
+                let (Some(config), Some(request)) = (config, request) else {
+                    return reject(Rejection::AccountMissing);
+                };
+                if request.requester != event.requester || request.request_id != event.request_id {
+                    return reject(Rejection::EventMismatch);
+                }
+                Ok(verify_request_v2(&self.policy, &self.guardian, &config, &request, now))
+            }
+            RecoveryVersion::V1 => {
+                let config = accounts::fetch_recovery_config(fetcher, &event.owner)?;
+                let request =
+                    accounts::fetch_recovery_request(fetcher, &event.owner, event.request_id)?;
+                let (Some(config), Some(request)) = (config, request) else {
+                    return reject(Rejection::AccountMissing);
+                };
+                if request.requester != event.requester || request.request_id != event.request_id {
+                    return reject(Rejection::EventMismatch);
+                }
+                Ok(verify_request_v1(&self.policy, &self.guardian, &config, &request, now))
+            }
+        }
+    }
+
+    fn ready_action(&self, event: &RecoveryInitiated) -> Action {
+        match event.version {
+            RecoveryVersion::V2 => Action::Confirm {
+                owner: event.owner,
+                request_id: event.request_id,
+                requester: event.requester,
+                instruction: instructions::confirm_participation(
+                    &event.owner,
+                    &self.guardian,
+                    event.request_id,
+                ),
+            },
+            RecoveryVersion::V1 => Action::Approve {
+                owner: event.owner,
+                request_id: event.request_id,
+                requester: event.requester,
+            },
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! # Off-Chain Share Delivery
+//!
+//! In the V2 flow guardians never put shares on-chain. Instead each guardian
+//! seals their share to the recovery *requester* and hands the envelope over
+//! any channel (email, chat, QR code). Only the requester's wallet can open it.
+//!
+//! ## Construction
+//!
+//! - The requester's Ed25519 wallet key is mapped to X25519 (birational map)
+//! - The guardian generates an ephemeral X25519 key and performs ECDH
+//! - Key: `HKDF-SHA256(ikm = shared, salt = ephemeral_pub || recipient_pub,
+//!   info = "lockbox-share-delivery-v1")`
+//! - Cipher: XChaCha20-Poly1305 with AAD `owner || request_id (LE) || share_index`
+//!
+//! Binding the AAD to the request means an envelope cannot be replayed into
+//! a different recovery.
+//!
+//! ## Envelope Format
+//!
+//! `[version (1) | ephemeral_pub (32) | share_index (1) | nonce (24) | ciphertext + tag]`
+
+use anchor_lang::prelude::Pubkey;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use curve25519_dalek::constants::X25519_BASEPOINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use hkdf::Hkdf;
+use lockbox_client::crypto::{NONCE_SIZE, TAG_SIZE};
+use lockbox_client::shamir::Share;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::error::{GuardianError, Result};
+
+/// Current envelope format version
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// HKDF info string for share delivery keys
+pub const DELIVERY_KEY_INFO: &[u8] = b"lockbox-share-delivery-v1";
+
+const HEADER_SIZE: usize = 1 + 32 + 1 + NONCE_SIZE;
+
+fn clamp(mut bytes: [u8; 32]) -> Scalar {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    Scalar::from_bits(bytes)
+}
+
+/// Map an Ed25519 wallet pubkey to its X25519 (Montgomery) form
+fn wallet_to_x25519(wallet: &Pubkey) -> Result<MontgomeryPoint> {
+    let point = CompressedEdwardsY(wallet.to_bytes())
+        .decompress()
+        .ok_or(GuardianError::InvalidPublicKey)?;
+    if point.is_small_order() {
+        return Err(GuardianError::InvalidPublicKey);
+    }
+    Ok(point.to_montgomery())
+}
+
+/// Map an Ed25519 secret seed (first 32 bytes of a Solana keypair) to X25519
+fn wallet_secret_to_x25519(seed: &[u8; 32]) -> Scalar {
+    let hash = Sha512::digest(seed);
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&hash[..32]);
+    clamp(bytes)
+}
+
+fn delivery_key(
+    shared: &MontgomeryPoint,
+    ephemeral: &MontgomeryPoint,
+    recipient: &MontgomeryPoint,
+) -> Result<[u8; 32]> {
+    if shared.as_bytes().iter().all(|&b| b == 0) {
+        return Err(GuardianError::WeakKeyExchange);
+    }
+
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral.as_bytes());
+    salt[32..].copy_from_slice(recipient.as_bytes());
+
+    let hk = Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes());
+    let mut key = [0u8; 32];
+    hk.expand(DELIVERY_KEY_INFO, &mut key)
+        .expect("valid HKDF output length");
+    Ok(key)
+}
+
+fn associated_data(owner: &Pubkey, request_id: u64, share_index: u8) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(32 + 8 + 1);
+    aad.extend_from_slice(owner.as_ref());
+    aad.extend_from_slice(&request_id.to_le_bytes());
+    aad.push(share_index);
+    aad
+}
+
+/// Seal a guardian's share to the recovery requester
+pub fn seal_share(
+    requester: &Pubkey,
+    owner: &Pubkey,
+    request_id: u64,
+    share: &Share,
+) -> Result<Vec<u8>> {
+    seal_share_with_rng(requester, owner, request_id, share, &mut OsRng)
+}
+
+/// Seal a share using the supplied RNG for the ephemeral key and nonce
+pub fn seal_share_with_rng(
+    requester: &Pubkey,
+    owner: &Pubkey,
+    request_id: u64,
+    share: &Share,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>> {
+    let recipient = wallet_to_x25519(requester)?;
+
+    let mut ephemeral_bytes = [0u8; 32];
+    rng.fill_bytes(&mut ephemeral_bytes);
+    let ephemeral_secret = clamp(ephemeral_bytes);
+    let ephemeral_pub = X25519_BASEPOINT * ephemeral_secret;
+
+    let key = delivery_key(&(recipient * ephemeral_secret), &ephemeral_pub, &recipient)?;
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.fill_bytes(&mut nonce);
+
+    let aad = associated_data(owner, request_id, share.index);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &share.data,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| GuardianError::Seal)?;
+
+    let mut envelope = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+    envelope.push(ENVELOPE_VERSION);
+    envelope.extend_from_slice(ephemeral_pub.as_bytes());
+    envelope.push(share.index);
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// Open a share envelope with the requester's wallet secret seed
+///
+/// `requester_seed` is the first 32 bytes of the requester's Solana keypair.
+pub fn open_share(
+    requester_seed: &[u8; 32],
+    owner: &Pubkey,
+    request_id: u64,
+    envelope: &[u8],
+) -> Result<Share> {
+    if envelope.len() < HEADER_SIZE + TAG_SIZE {
+        return Err(GuardianError::MalformedEnvelope);
+    }
+    if envelope[0] != ENVELOPE_VERSION {
+        return Err(GuardianError::UnsupportedEnvelopeVersion(envelope[0]));
+    }
+
+    let mut ephemeral_bytes = [0u8; 32];
+    ephemeral_bytes.copy_from_slice(&envelope[1..33]);
+    let ephemeral_pub = MontgomeryPoint(ephemeral_bytes);
+    let share_index = envelope[33];
+    let nonce = &envelope[34..HEADER_SIZE];
+    let ciphertext = &envelope[HEADER_SIZE..];
+
+    if share_index == 0 {
+        return Err(GuardianError::MalformedEnvelope);
+    }
+
+    let secret = wallet_secret_to_x25519(requester_seed);
+    let recipient = X25519_BASEPOINT * secret;
+    let key = delivery_key(&(ephemeral_pub * secret), &ephemeral_pub, &recipient)?;
+
+    let aad = associated_data(owner, request_id, share_index);
+    let data = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| GuardianError::Open)?;
+
+    Ok(Share {
+        index: share_index,
+        data,
+    })
+}
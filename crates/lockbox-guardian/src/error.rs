@@ -0,0 +1,29 @@
+use lockbox_client::ClientError;
+use thiserror::Error;
+
+/// Errors produced by the guardian daemon
+#[derive(Debug, Error)]
+pub enum GuardianError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("Invalid public key: not a valid Ed25519 point")]
+    InvalidPublicKey,
+
+    #[error("Share envelope is malformed")]
+    MalformedEnvelope,
+
+    #[error("Unsupported share envelope version: {0}")]
+    UnsupportedEnvelopeVersion(u8),
+
+    #[error("Share envelope encryption failed")]
+    Seal,
+
+    #[error("Share envelope decryption failed: wrong recipient or tampered envelope")]
+    Open,
+
+    #[error("Key exchange produced a degenerate shared secret")]
+    WeakKeyExchange,
+}
+
+pub type Result<T> = std::result::Result<T, GuardianError>;
@@ -0,0 +1,70 @@
+//! # Program Log Parsing
+//!
+//! Anchor emits events as `Program data: <base64>` log lines, where the
+//! payload is the 8-byte event discriminator followed by the Borsh-encoded
+//! event. The daemon only cares about recovery initiation.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lockbox_client::lockbox::instructions::{RecoveryInitiatedEvent, RecoveryInitiatedV2Event};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Which recovery flow a request belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RecoveryVersion {
+    /// Guardian approval submits the decrypted share on-chain
+    V1,
+
+    /// Guardian confirms on-chain and delivers the share off-chain
+    V2,
+}
+
+/// A `RecoveryInitiated(V2)Event` observed in program logs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryInitiated {
+    pub version: RecoveryVersion,
+    pub owner: Pubkey,
+    pub requester: Pubkey,
+    pub request_id: u64,
+    pub ready_at: i64,
+}
+
+fn decode_event<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let payload = data.strip_prefix(&T::DISCRIMINATOR[..])?;
+    T::try_from_slice(payload).ok()
+}
+
+/// Parse a single log line, returning the recovery event it carries (if any)
+///
+/// Accepts raw `Program data:` lines as well as lines prefixed by a log
+/// collector (e.g. `solana logs` indents each line).
+pub fn parse_log_line(line: &str) -> Option<RecoveryInitiated> {
+    let start = line.find(PROGRAM_DATA_PREFIX)? + PROGRAM_DATA_PREFIX.len();
+    let data = STANDARD.decode(line[start..].trim()).ok()?;
+
+    if let Some(e) = decode_event::<RecoveryInitiatedV2Event>(&data) {
+        return Some(RecoveryInitiated {
+            version: RecoveryVersion::V2,
+            owner: e.owner,
+            requester: e.requester,
+            request_id: e.request_id,
+            ready_at: e.ready_at,
+        });
+    }
+
+    decode_event::<RecoveryInitiatedEvent>(&data).map(|e| RecoveryInitiated {
+        version: RecoveryVersion::V1,
+        owner: e.owner,
+        requester: e.requester,
+        request_id: e.request_id,
+        ready_at: e.ready_at,
+    })
+}
+
+/// Parse every recovery event out of a transaction's log messages
+pub fn parse_logs<'a>(logs: impl IntoIterator<Item = &'a str>) -> Vec<RecoveryInitiated> {
+    logs.into_iter().filter_map(parse_log_line).collect()
+}
@@ -0,0 +1,30 @@
+//! # Lockbox Guardian Daemon
+//!
+//! Reference implementation of the off-chain half of social recovery.
+//! A guardian runs this next to their wallet to:
+//!
+//! - Watch program logs for `RecoveryInitiatedEvent` / `RecoveryInitiatedV2Event`
+//! - Re-read the request and config from chain and verify them
+//! - Wait out the recovery delay, then produce the `confirm_participation`
+//!   (V2) or `approve_recovery` (V1) step
+//! - Seal the guardian's Shamir share to the requester for off-chain delivery
+//!
+//! ## Modules
+//!
+//! - **events**: Decode Anchor `Program data:` log lines
+//! - **verify**: Request verification and operator policy
+//! - **daemon**: Event-driven state machine producing [`daemon::Action`]s
+//! - **delivery**: X25519 + XChaCha20-Poly1305 share envelopes
+//!
+//! The daemon never signs. Actions carry unsigned instructions so operators
+//! can route them through a hardware wallet or multisig.
+
+pub mod daemon;
+pub mod delivery;
+pub mod error;
+pub mod events;
+pub mod verify;
+
+pub use daemon::{Action, Daemon};
+pub use error::GuardianError;
+pub use verify::GuardianPolicy;
@@ -0,0 +1,268 @@
+//! # lockbox-guardian
+//!
+//! ```text
+//! solana logs <PROGRAM_ID> | lockbox-guardian watch --guardian <PUBKEY> [--owner <PUBKEY>]...
+//!                                                    [--url <RPC_URL>] [--strict-new-owner]
+//! lockbox-guardian seal-share --requester <PUBKEY> --owner <PUBKEY> --request-id <N> --share <INDEX:HEX>
+//! lockbox-guardian open-share --keypair <PATH> --owner <PUBKEY> --request-id <N> --envelope <BASE64>
+//! ```
+//!
+//! `watch` reads program logs on stdin and reads accounts through the
+//! `solana` CLI, so it works against any cluster the CLI is configured for.
+//! It prints one line per action; confirm instructions are printed unsigned
+//! for the operator to submit with their own signer.
+
+use std::io::{self, BufRead};
+use std::process::{self, Command};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lockbox_client::accounts::AccountFetcher;
+use lockbox_client::shamir::Share;
+use lockbox_client::ClientError;
+use lockbox_guardian::{delivery, Action, Daemon, GuardianPolicy};
+
+type CliResult<T> = std::result::Result<T, String>;
+
+/// Reads accounts via `solana account <ADDRESS> --output json`
+struct SolanaCliFetcher {
+    url: Option<String>,
+}
+
+impl AccountFetcher for SolanaCliFetcher {
+    fn fetch_account_data(&self, address: &Pubkey) -> lockbox_client::error::Result<Option<Vec<u8>>> {
+        let mut cmd = Command::new("solana");
+        cmd.args(["account", &address.to_string(), "--output", "json"]);
+        if let Some(url) = &self.url {
+            cmd.args(["--url", url]);
+        }
+
+        let output = cmd.output().map_err(|e| ClientError::Fetch(e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("AccountNotFound") || stderr.contains("not found") {
+                return Ok(None);
+            }
+            return Err(ClientError::Fetch(stderr.trim().to_string()));
+        }
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| ClientError::Fetch(e.to_string()))?;
+        let encoded = json["account"]["data"][0]
+            .as_str()
+            .ok_or_else(|| ClientError::Fetch("missing account data".to_string()))?;
+        STANDARD
+            .decode(encoded)
+            .map(Some)
+            .map_err(|e| ClientError::Fetch(e.to_string()))
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn parse_pubkey(value: &str) -> CliResult<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| format!("invalid pubkey: {value}"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(value: &str) -> CliResult<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return Err("hex string has odd length".to_string());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|_| "invalid hex".to_string()))
+        .collect()
+}
+
+/// Minimal `--flag value` parser; repeated flags accumulate
+struct Args {
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(raw: &[String]) -> CliResult<Self> {
+        let mut flags = Vec::new();
+        let mut iter = raw.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument: {arg}"))?;
+            let value = match iter.peek() {
+                Some(next) if !next.starts_with("--") => iter.next().cloned(),
+                _ => None,
+            };
+            flags.push((name.to_string(), value));
+        }
+        Ok(Self { flags })
+    }
+
+    fn all(&self, name: &str) -> Vec<&str> {
+        self.flags
+            .iter()
+            .filter(|(n, _)| n == name)
+            .filter_map(|(_, v)| v.as_deref())
+            .collect()
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.all(name).into_iter().next()
+    }
+
+    fn required(&self, name: &str) -> CliResult<&str> {
+        self.get(name).ok_or_else(|| format!("missing --{name}"))
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.flags.iter().any(|(n, _)| n == name)
+    }
+}
+
+fn print_action(action: &Action) {
+    match action {
+        Action::Confirm {
+            owner,
+            request_id,
+            requester,
+            instruction,
+        } => {
+            println!("CONFIRM owner={owner} request_id={request_id} requester={requester}");
+            println!("  program={}", instruction.program_id);
+            for meta in &instruction.accounts {
+                println!(
+                    "  account={} signer={} writable={}",
+                    meta.pubkey, meta.is_signer, meta.is_writable
+                );
+            }
+            println!("  data={}", STANDARD.encode(&instruction.data));
+            println!("  next: seal-share --requester {requester} --owner {owner} --request-id {request_id}");
+        }
+        Action::Approve {
+            owner,
+            request_id,
+            requester,
+        } => {
+            println!("APPROVE_V1 owner={owner} request_id={request_id} requester={requester}");
+        }
+        Action::Scheduled {
+            owner,
+            request_id,
+            ready_at,
+        } => {
+            println!("SCHEDULED owner={owner} request_id={request_id} ready_at={ready_at}");
+        }
+        Action::Reject {
+            owner,
+            request_id,
+            reason,
+        } => {
+            println!("REJECT owner={owner} request_id={request_id} reason={reason:?}");
+        }
+    }
+}
+
+fn watch(args: &Args) -> CliResult<()> {
+    let guardian = parse_pubkey(args.required("guardian")?)?;
+    let policy = GuardianPolicy {
+        watched_owners: args
+            .all("owner")
+            .into_iter()
+            .map(parse_pubkey)
+            .collect::<CliResult<_>>()?,
+        require_requester_as_new_owner: args.has("strict-new-owner"),
+    };
+    let fetcher = SolanaCliFetcher {
+        url: args.get("url").map(str::to_string),
+    };
+    let mut daemon = Daemon::new(guardian, policy);
+
+    eprintln!("Watching for recovery requests as guardian {guardian}");
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        match daemon.handle_log_line(&fetcher, &line, now()) {
+            Ok(Some(action)) => print_action(&action),
+            Ok(None) => {}
+            Err(e) => eprintln!("error: {e}"),
+        }
+        match daemon.poll(&fetcher, now()) {
+            Ok(actions) => actions.iter().for_each(print_action),
+            Err(e) => eprintln!("error: {e}"),
+        }
+    }
+    Ok(())
+}
+
+fn seal_share(args: &Args) -> CliResult<()> {
+    let requester = parse_pubkey(args.required("requester")?)?;
+    let owner = parse_pubkey(args.required("owner")?)?;
+    let request_id: u64 = args
+        .required("request-id")?
+        .parse()
+        .map_err(|_| "invalid --request-id".to_string())?;
+    let (index, data) = args
+        .required("share")?
+        .split_once(':')
+        .ok_or_else(|| "--share must be INDEX:HEX".to_string())?;
+    let share = Share {
+        index: index.parse().map_err(|_| "invalid share index".to_string())?,
+        data: from_hex(data)?,
+    };
+
+    let envelope = delivery::seal_share(&requester, &owner, request_id, &share)
+        .map_err(|e| e.to_string())?;
+    println!("{}", STANDARD.encode(envelope));
+    Ok(())
+}
+
+fn open_share(args: &Args) -> CliResult<()> {
+    let keypair_json =
+        std::fs::read_to_string(args.required("keypair")?).map_err(|e| e.to_string())?;
+    let keypair: Vec<u8> = serde_json::from_str(&keypair_json).map_err(|e| e.to_string())?;
+    let seed: [u8; 32] = keypair
+        .get(..32)
+        .and_then(|s| s.try_into().ok())
+        .ok_or_else(|| "keypair file must contain 64 bytes".to_string())?;
+
+    let owner = parse_pubkey(args.required("owner")?)?;
+    let request_id: u64 = args
+        .required("request-id")?
+        .parse()
+        .map_err(|_| "invalid --request-id".to_string())?;
+    let envelope = STANDARD
+        .decode(args.required("envelope")?)
+        .map_err(|e| e.to_string())?;
+
+    let share =
+        delivery::open_share(&seed, &owner, request_id, &envelope).map_err(|e| e.to_string())?;
+    println!("{}:{}", share.index, to_hex(&share.data));
+    Ok(())
+}
+
+fn main() {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let result = match raw.split_first() {
+        Some((command, rest)) => Args::parse(rest).and_then(|args| match command.as_str() {
+            "watch" => watch(&args),
+            "seal-share" => seal_share(&args),
+            "open-share" => open_share(&args),
+            other => Err(format!("unknown command: {other}")),
+        }),
+        None => Err("usage: lockbox-guardian <watch|seal-share|open-share> [options]".to_string()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,174 @@
+//! # Recovery Request Verification
+//!
+//! Before a guardian confirms anything, the daemon re-reads the request and
+//! config from chain and checks that the event it saw is genuine, still
+//! live, and aimed at a vault this guardian actually protects.
+//!
+//! Checks are deliberately conservative: any doubt is a rejection, and a
+//! rejected request needs a human to look at it.
+
+use std::collections::HashSet;
+
+use anchor_lang::prelude::Pubkey;
+use lockbox_client::lockbox::state::{
+    RecoveryConfig, RecoveryConfigV2, RecoveryRequest, RecoveryRequestV2, RecoveryStatus,
+};
+
+/// Size of the V2 encrypted challenge (24 nonce + 32 ciphertext + 16 tag)
+pub const CHALLENGE_SIZE: usize = 80;
+
+/// Operator policy for which requests the daemon may act on
+#[derive(Clone, Debug, Default)]
+pub struct GuardianPolicy {
+    /// Vault owners this guardian protects; empty means any vault that
+    /// lists this guardian
+    pub watched_owners: HashSet<Pubkey>,
+
+    /// Refuse requests that move the vault to a wallet other than the requester
+    pub require_requester_as_new_owner: bool,
+}
+
+impl GuardianPolicy {
+    fn watches(&self, owner: &Pubkey) -> bool {
+        self.watched_owners.is_empty() || self.watched_owners.contains(owner)
+    }
+}
+
+/// Why a recovery request was not approved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// Vault is not in the operator's watch list
+    OwnerNotWatched,
+
+    /// Request or config account does not exist (event spoofed or closed)
+    AccountMissing,
+
+    /// On-chain request does not match the observed event
+    EventMismatch,
+
+    /// This daemon's key is not an active guardian of the vault
+    NotAGuardian,
+
+    /// Requester is not an active guardian of the vault
+    RequesterNotGuardian,
+
+    /// Request is no longer pending (completed, cancelled, expired)
+    NotPending,
+
+    /// Request passed its expiration time
+    Expired,
+
+    /// Guardian already confirmed/approved this request
+    AlreadyConfirmed,
+
+    /// V2 challenge is not the expected 80-byte AEAD payload
+    MalformedChallenge,
+
+    /// Request hands the vault to a wallet other than the requester
+    UnexpectedNewOwner(Pubkey),
+}
+
+/// Result of verifying a live request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Request is valid but the recovery delay has not elapsed
+    Wait { ready_at: i64 },
+
+    /// Request is valid and ready for this guardian to act
+    Approve,
+
+    /// Request must not be acted on
+    Reject(Rejection),
+}
+
+/// Verify a V2 request against its config for `guardian`
+pub fn verify_request_v2(
+    policy: &GuardianPolicy,
+    guardian: &Pubkey,
+    config: &RecoveryConfigV2,
+    request: &RecoveryRequestV2,
+    now: i64,
+) -> Verdict {
+    if !policy.watches(&config.owner) {
+        return Verdict::Reject(Rejection::OwnerNotWatched);
+    }
+    if request.owner != config.owner {
+        return Verdict::Reject(Rejection::EventMismatch);
+    }
+    if !config.is_active_guardian(guardian) {
+        return Verdict::Reject(Rejection::NotAGuardian);
+    }
+    if !config.is_active_guardian(&request.requester) {
+        return Verdict::Reject(Rejection::RequesterNotGuardian);
+    }
+    if request.challenge.encrypted_challenge.len() != CHALLENGE_SIZE {
+        return Verdict::Reject(Rejection::MalformedChallenge);
+    }
+    if let Some(rejection) = check_new_owner(policy, &request.requester, request.new_owner) {
+        return Verdict::Reject(rejection);
+    }
+    if request.has_guardian_confirmed(guardian) {
+        return Verdict::Reject(Rejection::AlreadyConfirmed);
+    }
+
+    check_timing(&request.status, request.ready_at, request.expires_at, now)
+}
+
+/// Verify a V1 request against its config for `guardian`
+pub fn verify_request_v1(
+    policy: &GuardianPolicy,
+    guardian: &Pubkey,
+    config: &RecoveryConfig,
+    request: &RecoveryRequest,
+    now: i64,
+) -> Verdict {
+    if !policy.watches(&config.owner) {
+        return Verdict::Reject(Rejection::OwnerNotWatched);
+    }
+    if request.owner != config.owner {
+        return Verdict::Reject(Rejection::EventMismatch);
+    }
+    if !config.is_active_guardian(guardian) {
+        return Verdict::Reject(Rejection::NotAGuardian);
+    }
+    if !config.is_active_guardian(&request.requester) {
+        return Verdict::Reject(Rejection::RequesterNotGuardian);
+    }
+    if let Some(rejection) = check_new_owner(policy, &request.requester, request.new_owner) {
+        return Verdict::Reject(rejection);
+    }
+    if request.has_guardian_approved(guardian) {
+        return Verdict::Reject(Rejection::AlreadyConfirmed);
+    }
+
+    check_timing(&request.status, request.ready_at, request.expires_at, now)
+}
+
+fn check_new_owner(
+    policy: &GuardianPolicy,
+    requester: &Pubkey,
+    new_owner: Option<Pubkey>,
+) -> Option<Rejection> {
+    match new_owner {
+        Some(new_owner) if policy.require_requester_as_new_owner && new_owner != *requester => {
+            Some(Rejection::UnexpectedNewOwner(new_owner))
+        }
+        _ => None,
+    }
+}
+
+fn check_timing(status: &RecoveryStatus, ready_at: i64, expires_at: i64, now: i64) -> Verdict {
+    if !matches!(
+        status,
+        RecoveryStatus::Pending | RecoveryStatus::ReadyForReconstruction
+    ) {
+        return Verdict::Reject(Rejection::NotPending);
+    }
+    if now > expires_at {
+        return Verdict::Reject(Rejection::Expired);
+    }
+    if now < ready_at {
+        return Verdict::Wait { ready_at };
+    }
+    Verdict::Approve
+}
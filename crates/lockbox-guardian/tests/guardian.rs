@@ -0,0 +1,269 @@
+//! Guardian daemon tests: log parsing, request verification, and share delivery.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Event};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use lockbox_client::lockbox::instructions::RecoveryInitiatedV2Event;
+use lockbox_client::lockbox::state::{
+    CooldownMode, GuardianStatus, GuardianV2, RecoveryChallenge, RecoveryConfigV2, RecoveryRequestV2,
+    RecoveryStatus, DEFAULT_RECOVERY_COOLDOWN,
+};
+use lockbox_client::{pda, shamir};
+use lockbox_guardian::delivery::{open_share, seal_share};
+use lockbox_guardian::events::{parse_log_line, RecoveryVersion};
+use lockbox_guardian::verify::Rejection;
+use lockbox_guardian::{Action, Daemon, GuardianError, GuardianPolicy};
+use sha2::{Digest, Sha512};
+
+const DELAY: i64 = 86_400;
+
+/// Derive the Ed25519 wallet pubkey for a keypair seed (as Solana does)
+fn wallet_from_seed(seed: &[u8; 32]) -> Pubkey {
+    let hash = Sha512::digest(seed);
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&hash[..32]);
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    let point = ED25519_BASEPOINT_POINT * Scalar::from_bits(bytes);
+    Pubkey::new_from_array(point.compress().to_bytes())
+}
+
+fn guardian_entry(pubkey: Pubkey, index: u8) -> GuardianV2 {
+    GuardianV2 {
+        guardian_pubkey: pubkey,
+        share_index: index,
+        share_commitment: [0u8; 32],
+        added_at: 0,
+        nickname_encrypted: vec![],
+        status: GuardianStatus::Active,
+    }
+}
+
+struct Fixture {
+    owner: Pubkey,
+    guardian: Pubkey,
+    requester: Pubkey,
+    accounts: HashMap<Pubkey, Vec<u8>>,
+}
+
+fn fixture(requested_at: i64) -> Fixture {
+    let owner = Pubkey::new_unique();
+    let guardian = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+
+    let config = RecoveryConfigV2 {
+        version: 1,
+        owner,
+        threshold: 2,
+        total_guardians: 2,
+        guardians: vec![guardian_entry(guardian, 1), guardian_entry(requester, 2)],
+        recovery_delay: DELAY,
+        created_at: 0,
+        last_modified: 0,
+        last_request_id: 1,
+        master_secret_hash: [0u8; 32],
+        last_recovery_attempt: requested_at,
+        cooldown_mode: CooldownMode::Timestamp,
+        recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+        last_recovery_attempt_slot: 0,
+        last_recovery_attempt_epoch: 0,
+        bump: 0,
+    };
+    let request = RecoveryRequestV2 {
+        version: 1,
+        owner,
+        requester,
+        request_id: 1,
+        requested_at,
+        ready_at: requested_at + DELAY,
+        expires_at: requested_at + DELAY + 30 * 86_400,
+        challenge: RecoveryChallenge {
+            encrypted_challenge: vec![7u8; 80],
+            challenge_hash: [0u8; 32],
+            created_at: requested_at,
+        },
+        participating_guardians: vec![],
+        new_owner: None,
+        status: RecoveryStatus::Pending,
+        bump: 0,
+    };
+
+    let mut accounts = HashMap::new();
+    let mut data = Vec::new();
+    config.try_serialize(&mut data).unwrap();
+    accounts.insert(pda::recovery_config_v2(&owner).0, data);
+    let mut data = Vec::new();
+    request.try_serialize(&mut data).unwrap();
+    accounts.insert(pda::recovery_request_v2(&owner, 1).0, data);
+
+    Fixture {
+        owner,
+        guardian,
+        requester,
+        accounts,
+    }
+}
+
+fn log_line(owner: Pubkey, requester: Pubkey, requested_at: i64) -> String {
+    let event = RecoveryInitiatedV2Event {
+        owner,
+        requester,
+        request_id: 1,
+        ready_at: requested_at + DELAY,
+    };
+    format!("  Program data: {}", STANDARD.encode(event.data()))
+}
+
+#[test]
+fn test_parse_recovery_initiated_v2_log() {
+    let owner = Pubkey::new_unique();
+    let requester = Pubkey::new_unique();
+    let event = parse_log_line(&log_line(owner, requester, 100)).unwrap();
+
+    assert_eq!(event.version, RecoveryVersion::V2);
+    assert_eq!(event.owner, owner);
+    assert_eq!(event.requester, requester);
+    assert_eq!(event.ready_at, 100 + DELAY);
+
+    assert!(parse_log_line("Program log: Instruction: InitiateRecoveryV2").is_none());
+    assert!(parse_log_line("Program data: not-base64!").is_none());
+}
+
+#[test]
+fn test_daemon_waits_for_delay_then_confirms() {
+    let f = fixture(1_000);
+    let mut daemon = Daemon::new(f.guardian, GuardianPolicy::default());
+    let line = log_line(f.owner, f.requester, 1_000);
+
+    let action = daemon.handle_log_line(&f.accounts, &line, 1_500).unwrap().unwrap();
+    assert_eq!(
+        action,
+        Action::Scheduled {
+            owner: f.owner,
+            request_id: 1,
+            ready_at: 1_000 + DELAY
+        }
+    );
+    assert_eq!(daemon.pending_count(), 1);
+
+    // Nothing due before ready_at
+    assert!(daemon.poll(&f.accounts, 1_000 + DELAY - 1).unwrap().is_empty());
+
+    let actions = daemon.poll(&f.accounts, 1_000 + DELAY).unwrap();
+    assert_eq!(daemon.pending_count(), 0);
+    match &actions[..] {
+        [Action::Confirm {
+            requester,
+            instruction,
+            ..
+        }] => {
+            assert_eq!(*requester, f.requester);
+            assert_eq!(instruction.program_id, lockbox_client::PROGRAM_ID);
+            assert_eq!(instruction.accounts[2].pubkey, f.guardian);
+            assert!(instruction.accounts[2].is_signer);
+        }
+        other => panic!("unexpected actions: {other:?}"),
+    }
+}
+
+#[test]
+fn test_daemon_rejects_spoofed_and_foreign_requests() {
+    let f = fixture(1_000);
+    let now = 1_000 + DELAY;
+
+    // Event whose requester does not match the on-chain request
+    let mut daemon = Daemon::new(f.guardian, GuardianPolicy::default());
+    let spoofed = log_line(f.owner, Pubkey::new_unique(), 1_000);
+    assert!(matches!(
+        daemon.handle_log_line(&f.accounts, &spoofed, now).unwrap(),
+        Some(Action::Reject {
+            reason: Rejection::EventMismatch,
+            ..
+        })
+    ));
+
+    // Event for a vault with no recovery accounts
+    let missing = log_line(Pubkey::new_unique(), f.requester, 1_000);
+    assert!(matches!(
+        daemon.handle_log_line(&f.accounts, &missing, now).unwrap(),
+        Some(Action::Reject {
+            reason: Rejection::AccountMissing,
+            ..
+        })
+    ));
+
+    // Daemon key that is not a guardian of this vault
+    let mut stranger = Daemon::new(Pubkey::new_unique(), GuardianPolicy::default());
+    let line = log_line(f.owner, f.requester, 1_000);
+    assert!(matches!(
+        stranger.handle_log_line(&f.accounts, &line, now).unwrap(),
+        Some(Action::Reject {
+            reason: Rejection::NotAGuardian,
+            ..
+        })
+    ));
+
+    // Vault outside the operator's watch list
+    let policy = GuardianPolicy {
+        watched_owners: [Pubkey::new_unique()].into_iter().collect(),
+        ..GuardianPolicy::default()
+    };
+    let mut scoped = Daemon::new(f.guardian, policy);
+    assert!(matches!(
+        scoped.handle_log_line(&f.accounts, &line, now).unwrap(),
+        Some(Action::Reject {
+            reason: Rejection::OwnerNotWatched,
+            ..
+        })
+    ));
+
+    // Past expiration
+    assert!(matches!(
+        daemon
+            .handle_log_line(&f.accounts, &line, now + 31 * 86_400)
+            .unwrap(),
+        Some(Action::Reject {
+            reason: Rejection::Expired,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_share_delivery_round_trip() {
+    let requester_seed = [42u8; 32];
+    let requester = wallet_from_seed(&requester_seed);
+    let owner = Pubkey::new_unique();
+    let shares = shamir::split_secret(&[9u8; 32], 2, 3).unwrap();
+
+    let envelope = seal_share(&requester, &owner, 5, &shares[1]).unwrap();
+    let opened = open_share(&requester_seed, &owner, 5, &envelope).unwrap();
+    assert_eq!(opened, shares[1]);
+
+    // Envelope is bound to the recovery request
+    assert!(matches!(
+        open_share(&requester_seed, &owner, 6, &envelope),
+        Err(GuardianError::Open)
+    ));
+    assert!(matches!(
+        open_share(&requester_seed, &Pubkey::new_unique(), 5, &envelope),
+        Err(GuardianError::Open)
+    ));
+
+    // Only the requester's wallet can open it
+    assert!(matches!(
+        open_share(&[43u8; 32], &owner, 5, &envelope),
+        Err(GuardianError::Open)
+    ));
+
+    assert!(matches!(
+        open_share(&requester_seed, &owner, 5, &envelope[..20]),
+        Err(GuardianError::MalformedEnvelope)
+    ));
+}
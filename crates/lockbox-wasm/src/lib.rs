@@ -0,0 +1,275 @@
+//! # Lockbox WASM Bindings
+//!
+//! `wasm-bindgen` wrappers around `lockbox-client` so the web app and the
+//! browser extension run the exact same Rust code as the SDK tests, instead
+//! of a parallel TypeScript implementation of nonce layout and key derivation.
+//!
+//! ## Building
+//!
+//! ```text
+//! wasm-pack build crates/lockbox-wasm --target web
+//! ```
+//!
+//! ## Conventions
+//!
+//! - Pubkeys cross the boundary as base58 strings
+//! - Keys, payloads, and hashes cross as `Uint8Array`
+//! - Errors surface as JS `Error`s carrying the SDK error message
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use lockbox_client::lockbox::state::{PasswordEntryType, StorageType};
+use lockbox_client::{crypto, instructions};
+use wasm_bindgen::prelude::*;
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, JsError> {
+    Pubkey::from_str(value).map_err(|_| JsError::new(&format!("Invalid pubkey: {value}")))
+}
+
+fn key32(bytes: &[u8]) -> Result<[u8; 32], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new("Invalid key length (expected 32 bytes)"))
+}
+
+fn entry_type(value: u8) -> Result<PasswordEntryType, JsError> {
+    Ok(match value {
+        0 => PasswordEntryType::Login,
+        1 => PasswordEntryType::CreditCard,
+        2 => PasswordEntryType::SecureNote,
+        3 => PasswordEntryType::Identity,
+        4 => PasswordEntryType::ApiKey,
+        5 => PasswordEntryType::SshKey,
+        6 => PasswordEntryType::CryptoWallet,
+        7 => PasswordEntryType::Totp,
+        _ => return Err(JsError::new(&format!("Invalid entry type: {value}"))),
+    })
+}
+
+fn storage_type(value: u8) -> Result<StorageType, JsError> {
+    Ok(match value {
+        0 => StorageType::Passwords,
+        1 => StorageType::SharedItems,
+        2 => StorageType::SearchIndex,
+        3 => StorageType::AuditLogs,
+        4 => StorageType::ArchivedPasswords,
+        _ => return Err(JsError::new(&format!("Invalid storage type: {value}"))),
+    })
+}
+
+// ============================================================================
+// Crypto
+// ============================================================================
+
+/// HKDF session key from a wallet signature and explicit 32-byte salt
+#[wasm_bindgen(js_name = deriveSessionKey)]
+pub fn derive_session_key(wallet: &str, signature: &[u8], salt: &[u8]) -> Result<Vec<u8>, JsError> {
+    let salt = key32(salt)?;
+    Ok(crypto::derive_session_key(&parse_pubkey(wallet)?, signature, &salt).to_vec())
+}
+
+/// HKDF session key using the deterministic per-wallet salt
+#[wasm_bindgen(js_name = deriveSessionKeyDeterministic)]
+pub fn derive_session_key_deterministic(wallet: &str, signature: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(crypto::derive_session_key_deterministic(&parse_pubkey(wallet)?, signature).to_vec())
+}
+
+/// Blind-index key, domain-separated from the session key
+#[wasm_bindgen(js_name = deriveSearchKey)]
+pub fn derive_search_key(wallet: &str, signature: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(crypto::derive_search_key(&parse_pubkey(wallet)?, signature).to_vec())
+}
+
+/// Encrypt an entry: `nonce (24) || ciphertext || tag (16)`
+#[wasm_bindgen(js_name = encryptEntry)]
+pub fn encrypt_entry(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, JsError> {
+    crypto::encrypt_entry(&key32(key)?, plaintext).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Decrypt an entry payload produced by `encryptEntry` (or the SDK)
+#[wasm_bindgen(js_name = decryptEntry)]
+pub fn decrypt_entry(key: &[u8], payload: &[u8]) -> Result<Vec<u8>, JsError> {
+    crypto::decrypt_entry(&key32(key)?, payload).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// `HMAC-SHA256(search_key, token)`
+#[wasm_bindgen(js_name = blindIndex)]
+pub fn blind_index(search_key: &[u8], token: &str) -> Result<Vec<u8>, JsError> {
+    Ok(crypto::blind_index(&key32(search_key)?, token).to_vec())
+}
+
+/// Normalized title hash for `DataEntryHeader.title_hash`
+#[wasm_bindgen(js_name = titleHash)]
+pub fn title_hash(search_key: &[u8], title: &str) -> Result<Vec<u8>, JsError> {
+    Ok(crypto::title_hash(&key32(search_key)?, title).to_vec())
+}
+
+// ============================================================================
+// Instructions
+// ============================================================================
+
+/// Account meta for a built instruction
+#[wasm_bindgen(js_name = AccountMeta)]
+pub struct WasmAccountMeta {
+    #[wasm_bindgen(getter_with_clone)]
+    pub pubkey: String,
+
+    #[wasm_bindgen(js_name = isSigner)]
+    pub is_signer: bool,
+
+    #[wasm_bindgen(js_name = isWritable)]
+    pub is_writable: bool,
+}
+
+/// Program instruction ready to wrap in a `TransactionInstruction`
+#[wasm_bindgen(js_name = Instruction)]
+pub struct WasmInstruction {
+    inner: Instruction,
+}
+
+#[wasm_bindgen(js_class = Instruction)]
+impl WasmInstruction {
+    #[wasm_bindgen(getter, js_name = programId)]
+    pub fn program_id(&self) -> String {
+        self.inner.program_id.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn accounts(&self) -> Vec<WasmAccountMeta> {
+        self.inner
+            .accounts
+            .iter()
+            .map(|meta| WasmAccountMeta {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.inner.data.clone()
+    }
+}
+
+impl From<Instruction> for WasmInstruction {
+    fn from(inner: Instruction) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<WasmInstruction> for Instruction {
+    fn from(ix: WasmInstruction) -> Self {
+        ix.inner
+    }
+}
+
+#[wasm_bindgen(js_name = initializeMasterLockbox)]
+pub fn initialize_master_lockbox(owner: &str) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::initialize_master_lockbox(&parse_pubkey(owner)?).into())
+}
+
+#[wasm_bindgen(js_name = initializeStorageChunk)]
+pub fn initialize_storage_chunk(
+    owner: &str,
+    chunk_index: u16,
+    initial_capacity: u32,
+    data_type: u8,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::initialize_storage_chunk(
+        &parse_pubkey(owner)?,
+        chunk_index,
+        initial_capacity,
+        storage_type(data_type)?,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = storePasswordEntry)]
+pub fn store_password_entry(
+    owner: &str,
+    chunk_index: u16,
+    encrypted_data: Vec<u8>,
+    entry_type_value: u8,
+    category: u32,
+    title_hash: &[u8],
+    client_entry_id: Option<u64>,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::store_password_entry(
+        &parse_pubkey(owner)?,
+        chunk_index,
+        encrypted_data,
+        entry_type(entry_type_value)?,
+        category,
+        key32(title_hash)?,
+        client_entry_id,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = retrievePasswordEntry)]
+pub fn retrieve_password_entry(
+    owner: &str,
+    chunk_index: u16,
+    entry_id: u64,
+    simulate: bool,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::retrieve_password_entry(
+        &parse_pubkey(owner)?,
+        chunk_index,
+        entry_id,
+        simulate,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = updatePasswordEntry)]
+pub fn update_password_entry(
+    owner: &str,
+    chunk_index: u16,
+    entry_id: u64,
+    new_encrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::update_password_entry(
+        &parse_pubkey(owner)?,
+        chunk_index,
+        entry_id,
+        new_encrypted_data,
+        expected_layout_epoch,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = deletePasswordEntry)]
+pub fn delete_password_entry(
+    owner: &str,
+    chunk_index: u16,
+    entry_id: u64,
+    expected_layout_epoch: u32,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::delete_password_entry(
+        &parse_pubkey(owner)?,
+        chunk_index,
+        entry_id,
+        expected_layout_epoch,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = confirmParticipation)]
+pub fn confirm_participation(
+    owner: &str,
+    guardian: &str,
+    request_id: u64,
+) -> Result<WasmInstruction, JsError> {
+    Ok(instructions::confirm_participation(
+        &parse_pubkey(owner)?,
+        &parse_pubkey(guardian)?,
+        request_id,
+    )
+    .into())
+}
@@ -0,0 +1,65 @@
+//! Binding tests (native target): the exported functions must produce the
+//! same bytes as the SDK they wrap. Only success paths are exercised here;
+//! `JsError` construction requires a JS host.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use lockbox_client::lockbox::state::PasswordEntryType;
+use lockbox_client::{crypto, instructions};
+
+#[test]
+fn test_crypto_matches_sdk() {
+    let wallet = Pubkey::new_unique();
+    let signature = [3u8; 64];
+
+    let key = lockbox_wasm::derive_session_key_deterministic(&wallet.to_string(), &signature)
+        .unwrap_or_else(|_| unreachable!());
+    assert_eq!(
+        key,
+        crypto::derive_session_key_deterministic(&wallet, &signature)
+    );
+
+    let payload = lockbox_wasm::encrypt_entry(&key, b"secret").unwrap_or_else(|_| unreachable!());
+    let key: [u8; 32] = key.try_into().unwrap();
+    assert_eq!(crypto::decrypt_entry(&key, &payload).unwrap(), b"secret");
+
+    let search = crypto::derive_search_key(&wallet, &signature);
+    assert_eq!(
+        lockbox_wasm::title_hash(&search, " Bank ").unwrap_or_else(|_| unreachable!()),
+        crypto::title_hash(&search, "bank")
+    );
+}
+
+#[test]
+fn test_instruction_matches_sdk() {
+    let owner = Pubkey::new_unique();
+    let ix: Instruction = lockbox_wasm::store_password_entry(
+        &owner.to_string(),
+        1,
+        vec![0u8; 48],
+        PasswordEntryType::ApiKey as u8,
+        2,
+        &[5u8; 32],
+        Some(42),
+    )
+    .unwrap_or_else(|_| unreachable!())
+    .into();
+
+    let expected = instructions::store_password_entry(
+        &owner,
+        1,
+        vec![0u8; 48],
+        PasswordEntryType::ApiKey,
+        2,
+        [5u8; 32],
+        Some(42),
+    );
+    assert_eq!(ix, expected);
+
+    let wrapped = lockbox_wasm::delete_password_entry(&owner.to_string(), 0, 9, 0)
+        .unwrap_or_else(|_| unreachable!());
+    let accounts = wrapped.accounts();
+    assert_eq!(wrapped.program_id(), lockbox_client::PROGRAM_ID.to_string());
+    assert_eq!(accounts[2].pubkey, owner.to_string());
+    assert!(accounts[2].is_signer);
+}
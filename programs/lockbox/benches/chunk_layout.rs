@@ -0,0 +1,68 @@
+//! Chunk layout benchmarks
+//!
+//! Entry operations and account (de)serialization over the state structs,
+//! across chunk capacities and entry counts. `account_codec` compares
+//! decoding the whole chunk with decoding only its headers, the cost a
+//! header-split layout would pay instead.
+//!
+//! ```text
+//! cargo bench -p lockbox --bench chunk_layout
+//! ```
+
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use lockbox::state::StorageChunk;
+
+use common::Layout;
+
+fn bench_op(
+    c: &mut Criterion,
+    name: &str,
+    setup: impl Fn(Layout) -> StorageChunk,
+    op: impl Fn(&mut StorageChunk, Layout),
+) {
+    let mut group = c.benchmark_group(format!("chunk_ops/{name}"));
+    for layout in common::layouts() {
+        let chunk = setup(layout);
+        group.bench_with_input(BenchmarkId::from_parameter(layout.label()), &layout, |b, &layout| {
+            b.iter_batched(
+                || chunk.clone(),
+                |mut chunk| {
+                    op(&mut chunk, layout);
+                    chunk
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn chunk_ops(c: &mut Criterion) {
+    bench_op(c, "add", |layout| common::chunk_with(layout, layout.entries - 1), common::add_last);
+    bench_op(c, "update_in_place", common::filled_chunk, common::update_in_place);
+    bench_op(c, "update_grow", common::filled_chunk, common::update_grow);
+    bench_op(c, "delete", common::filled_chunk, |chunk, _| common::delete_first(chunk));
+}
+
+fn account_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("account_codec");
+    for layout in common::layouts() {
+        let chunk = common::filled_chunk(layout);
+        let account = common::account_data(&chunk);
+        let headers = chunk.entry_headers.try_to_vec().unwrap();
+
+        group.bench_with_input(BenchmarkId::new("full_chunk", layout.label()), &account, |b, data| {
+            b.iter(|| common::account_round_trip(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("headers_only", layout.label()), &headers, |b, data| {
+            b.iter(|| common::headers_round_trip(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, chunk_ops, account_codec);
+criterion_main!(benches);
@@ -0,0 +1,145 @@
+//! Chunk layout scenarios shared by the `chunk_layout` benchmark and the
+//! `chunk_layout_results` test
+//!
+//! Each layout is a chunk capacity and an entry count; entries are equal
+//! sized and fill half the capacity, so every operation has room to grow.
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use lockbox::state::{DataEntryHeader, PasswordEntryType, StorageChunk, StorageType};
+
+pub const NOW: i64 = 1_700_000_000;
+
+/// Chunk capacities benchmarked (bytes)
+pub const CAPACITIES: [u32; 3] = [1024, 4096, 10240];
+
+/// Entry counts benchmarked (at most 100 per chunk)
+pub const ENTRY_COUNTS: [usize; 3] = [10, 50, 100];
+
+/// Bytes an update grows an entry by
+pub const GROWTH: usize = 16;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub capacity: u32,
+    pub entries: usize,
+}
+
+impl Layout {
+    pub fn label(&self) -> String {
+        format!("{}B/{}", self.capacity, self.entries)
+    }
+
+    pub fn entry_size(&self) -> usize {
+        (self.capacity as usize / 2 / self.entries).max(1)
+    }
+}
+
+/// Every capacity and entry count combination
+pub fn layouts() -> Vec<Layout> {
+    CAPACITIES
+        .iter()
+        .flat_map(|&capacity| ENTRY_COUNTS.iter().map(move |&entries| Layout { capacity, entries }))
+        .collect()
+}
+
+pub fn header(entry_id: u64, offset: u32, size: u32) -> DataEntryHeader {
+    DataEntryHeader {
+        entry_id,
+        offset,
+        size,
+        entry_type: PasswordEntryType::Login,
+        category: 0,
+        title_hash: [entry_id as u8; 32],
+        created_at: NOW,
+        last_modified: NOW,
+        access_count: 0,
+        flags: 0,
+        next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+        next_offset: 0,
+        tag_mask: 0,
+        rotate_after: 0,
+        parent_entry: 0,
+        linked_entry_ids: Vec::new(),
+    }
+}
+
+/// A chunk holding `count` entries of the layout's size (IDs `1..=count`)
+pub fn chunk_with(layout: Layout, count: usize) -> StorageChunk {
+    let mut data = StorageChunk::DISCRIMINATOR.to_vec();
+    data.resize(StorageChunk::BASE_SPACE, 0);
+    let mut chunk = StorageChunk::try_deserialize(&mut &data[..]).unwrap();
+    chunk
+        .initialize(
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            0,
+            layout.capacity,
+            StorageType::Passwords,
+            255,
+            NOW,
+        )
+        .unwrap();
+
+    let size = layout.entry_size();
+    for entry_id in 1..=count as u64 {
+        let entry = header(entry_id, chunk.current_size, size as u32);
+        chunk.add_entry(entry, vec![entry_id as u8; size], NOW).unwrap();
+    }
+    chunk
+}
+
+/// A chunk filled to the layout's entry count
+pub fn filled_chunk(layout: Layout) -> StorageChunk {
+    chunk_with(layout, layout.entries)
+}
+
+/// Serialized account data of a chunk
+pub fn account_data(chunk: &StorageChunk) -> Vec<u8> {
+    let mut data = Vec::new();
+    chunk.try_serialize(&mut data).unwrap();
+    data
+}
+
+// ============================================================================
+// Operations
+// ============================================================================
+
+/// Append the layout's last entry to a chunk holding the others
+pub fn add_last(chunk: &mut StorageChunk, layout: Layout) {
+    let size = layout.entry_size();
+    let entry_id = layout.entries as u64;
+    let entry = header(entry_id, chunk.current_size, size as u32);
+    chunk.add_entry(entry, vec![entry_id as u8; size], NOW).unwrap();
+}
+
+/// Rewrite the middle entry in place
+pub fn update_in_place(chunk: &mut StorageChunk, layout: Layout) {
+    let entry_id = (layout.entries / 2 + 1) as u64;
+    chunk.update_entry(entry_id, vec![0xAA; layout.entry_size()], NOW).unwrap();
+}
+
+/// Grow the middle entry, shifting every entry after it
+pub fn update_grow(chunk: &mut StorageChunk, layout: Layout) {
+    let entry_id = (layout.entries / 2 + 1) as u64;
+    chunk.update_entry(entry_id, vec![0xAA; layout.entry_size() + GROWTH], NOW).unwrap();
+}
+
+/// Delete the first entry, shifting every other entry
+pub fn delete_first(chunk: &mut StorageChunk) {
+    chunk.delete_entry(1, NOW).unwrap();
+}
+
+/// Decode and re-encode the whole chunk account, as every instruction does
+pub fn account_round_trip(data: &[u8]) -> Vec<u8> {
+    let chunk = StorageChunk::try_deserialize(&mut &data[..]).unwrap();
+    account_data(&chunk)
+}
+
+/// Decode and re-encode only the entry headers (a header-split layout)
+pub fn headers_round_trip(headers: &[u8]) -> Vec<u8> {
+    let decoded = Vec::<DataEntryHeader>::try_from_slice(headers).unwrap();
+    decoded.try_to_vec().unwrap()
+}
@@ -0,0 +1,32 @@
+//! Entry operations on storage chunks
+//!
+//! Chunks built by the operations must keep their layout; chunks decoded
+//! from arbitrary account data only need to fail cleanly.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lockbox::state::StorageType;
+use lockbox_fuzz::chunk::{apply, check_layout, decode_chunk, new_chunk, ChunkInput};
+
+fuzz_target!(|input: ChunkInput| {
+    if let Some(raw) = &input.raw {
+        if let Some(mut chunk) = decode_chunk(raw) {
+            for op in &input.ops {
+                apply(&mut chunk, op);
+            }
+        }
+        return;
+    }
+
+    let data_type = if input.search_index {
+        StorageType::SearchIndex
+    } else {
+        StorageType::Passwords
+    };
+    let mut chunk = new_chunk(data_type, input.capacity);
+    for op in &input.ops {
+        apply(&mut chunk, op);
+        check_layout(&chunk);
+    }
+});
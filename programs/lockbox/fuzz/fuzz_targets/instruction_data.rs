@@ -0,0 +1,23 @@
+//! Arbitrary instruction data, with and without a valid discriminator
+//!
+//! No accounts are passed, so every call must fail, either deserializing
+//! the arguments or loading the first account.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lockbox_fuzz::{check_result, instruction_data, runtime};
+
+fn run(data: &[u8]) {
+    let result = lockbox::entry(&lockbox::ID, &[], data);
+    check_result(&result);
+    assert!(result.is_err(), "instruction succeeded without accounts");
+}
+
+fuzz_target!(|data: &[u8]| {
+    runtime::install_stubs();
+    run(data);
+    if let Some((selector, args)) = data.split_first() {
+        run(&instruction_data(*selector, args));
+    }
+});
@@ -0,0 +1,20 @@
+//! Instruction sequences against the fixture vaults
+//!
+//! Each instruction sees the account changes of the successful ones before
+//! it, so later instructions can reach state earlier ones created.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lockbox_fuzz::runtime::Harness;
+use lockbox_fuzz::FuzzInstruction;
+
+/// Most instructions run per input
+const MAX_SEQUENCE: usize = 8;
+
+fuzz_target!(|sequence: Vec<FuzzInstruction>| {
+    let mut harness = Harness::new();
+    for instruction in sequence.iter().take(MAX_SEQUENCE) {
+        let _ = harness.process(instruction);
+    }
+});
@@ -0,0 +1,154 @@
+//! Storage chunk operations for the `chunk_layout` target
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use arbitrary::Arbitrary;
+use lockbox::state::{DataEntryHeader, PasswordEntryType, StorageChunk, StorageType};
+
+use crate::NOW;
+
+/// One chunk operation, as the entry handlers perform it
+#[derive(Arbitrary, Clone, Debug)]
+pub enum ChunkOp {
+    Add { entry_id: u8, size: u16 },
+    Update { entry_id: u8, size: u16 },
+    Delete { entry_id: u8 },
+    Take { entry_id: u8 },
+    Read { entry_id: u8 },
+    Trash { entry_id: u8 },
+    Restore { entry_id: u8 },
+    SetSearchTokens { entry_id: u8, tokens: Vec<u8> },
+    Health,
+}
+
+/// A chunk to operate on and the operations
+#[derive(Arbitrary, Clone, Debug)]
+pub struct ChunkInput {
+    /// Start from a `SearchIndex` chunk instead of a `Passwords` one
+    pub search_index: bool,
+    pub capacity: u16,
+    /// Arbitrary account data to start from instead of a fresh chunk
+    pub raw: Option<Vec<u8>>,
+    pub ops: Vec<ChunkOp>,
+}
+
+/// Payload of `entry_id` at `size` bytes, so misplaced data is detectable
+fn payload(entry_id: u8, size: u16) -> Vec<u8> {
+    vec![entry_id; size as usize]
+}
+
+fn header(entry_id: u64, offset: u32, size: u32) -> DataEntryHeader {
+    DataEntryHeader {
+        entry_id,
+        offset,
+        size,
+        entry_type: PasswordEntryType::Login,
+        category: 0,
+        title_hash: [0u8; 32],
+        created_at: NOW,
+        last_modified: NOW,
+        access_count: 0,
+        flags: 0,
+        next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+        next_offset: 0,
+        tag_mask: 0,
+        rotate_after: 0,
+        parent_entry: 0,
+        linked_entry_ids: Vec::new(),
+    }
+}
+
+/// A fresh chunk, as `initialize_storage_chunk` leaves it
+pub fn new_chunk(data_type: StorageType, capacity: u16) -> StorageChunk {
+    let capacity = (capacity as u32).clamp(StorageChunk::MIN_CHUNK_SIZE, StorageChunk::MAX_CHUNK_SIZE);
+    let mut data = StorageChunk::DISCRIMINATOR.to_vec();
+    data.resize(StorageChunk::BASE_SPACE, 0);
+    let mut chunk = StorageChunk::try_deserialize(&mut &data[..]).expect("zeroed chunk decodes");
+    chunk
+        .initialize(
+            Default::default(),
+            Default::default(),
+            0,
+            capacity,
+            data_type,
+            255,
+            NOW,
+        )
+        .expect("chunk initializes");
+    chunk
+}
+
+/// Decode arbitrary bytes as a storage chunk account
+pub fn decode_chunk(raw: &[u8]) -> Option<StorageChunk> {
+    let mut data = StorageChunk::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(raw);
+    StorageChunk::try_deserialize(&mut &data[..]).ok()
+}
+
+/// Apply one operation; errors are expected and ignored
+pub fn apply(chunk: &mut StorageChunk, op: &ChunkOp) {
+    match *op {
+        ChunkOp::Add { entry_id, size } => {
+            let entry = header(entry_id as u64, chunk.current_size, size as u32);
+            let _ = chunk.add_entry(entry, payload(entry_id, size), NOW);
+        }
+        ChunkOp::Update { entry_id, size } => {
+            let _ = chunk.update_entry(entry_id as u64, payload(entry_id, size), NOW);
+        }
+        ChunkOp::Delete { entry_id } => {
+            let _ = chunk.delete_entry(entry_id as u64, NOW);
+        }
+        ChunkOp::Take { entry_id } => {
+            let _ = chunk.take_entry(entry_id as u64, NOW);
+        }
+        ChunkOp::Read { entry_id } => {
+            let _ = chunk.get_entry_data(entry_id as u64);
+            let _ = chunk.get_alias_target(entry_id as u64);
+        }
+        ChunkOp::Trash { entry_id } => {
+            if let Ok(entry) = chunk.get_entry_header_mut(entry_id as u64) {
+                entry.set_trashed(true);
+            }
+        }
+        ChunkOp::Restore { entry_id } => {
+            let _ = chunk.restore_trashed(entry_id as u64, NOW, i64::MAX);
+        }
+        ChunkOp::SetSearchTokens { entry_id, ref tokens } => {
+            let tokens: Vec<[u8; 32]> = tokens.iter().map(|t| [*t; 32]).collect();
+            let _ = chunk.set_search_tokens(entry_id as u64, &tokens, NOW);
+        }
+        ChunkOp::Health => {
+            let _ = chunk.health();
+            let _ = chunk.expired_trash(NOW, 0);
+            let _ = chunk.rotation_due(NOW);
+        }
+    }
+}
+
+/// Assert the layout the entry handlers maintain
+///
+/// Entries are packed back to back in header order, each holding its own
+/// payload; a search index chunk holds whole postings and no headers.
+pub fn check_layout(chunk: &StorageChunk) {
+    assert_eq!(chunk.current_size as usize, chunk.encrypted_data.len(), "current_size drifted");
+    assert!(chunk.current_size <= chunk.max_capacity, "chunk over capacity");
+    assert!(chunk.is_entry_count_consistent(), "entry_count drifted");
+
+    if chunk.data_type == StorageType::SearchIndex {
+        assert!(chunk.entry_headers.is_empty(), "entry in a search index chunk");
+        assert_eq!(chunk.encrypted_data.len() % lockbox::state::SEARCH_POSTING_SIZE, 0);
+        return;
+    }
+
+    let mut offset = 0u32;
+    for entry in &chunk.entry_headers {
+        assert_eq!(entry.offset, offset, "entry {} misplaced", entry.entry_id);
+        let data = chunk.get_entry_data(entry.entry_id).expect("entry data in bounds");
+        assert!(
+            data.iter().all(|byte| *byte as u64 == entry.entry_id),
+            "entry {} holds another entry's data",
+            entry.entry_id
+        );
+        offset += entry.size;
+    }
+    assert_eq!(offset, chunk.current_size, "entries do not cover the data");
+}
@@ -0,0 +1,135 @@
+//! # Lockbox Fuzzing Harness
+//!
+//! Shared code for the cargo-fuzz targets in `fuzz_targets/`:
+//!
+//! - `instruction_data` - arbitrary bytes through `lockbox::entry` with no
+//!   accounts, exercising every instruction's Borsh deserialization
+//! - `instruction_handlers` - instruction sequences with arbitrary arguments
+//!   and accounts, run against the `lockbox-fixtures` vaults
+//! - `chunk_layout` - entry operations on well-formed and arbitrary storage
+//!   chunks, exercising the offset and length arithmetic
+//!
+//! Handlers run in-process, the way `solana-program-test` runs a builtin:
+//! the accounts are serialized into a loader input buffer, the syscalls the
+//! program uses (clock, rent, logging, system program CPI) are stubbed, and
+//! `lockbox::entry` is called directly. Every target asserts that the
+//! program never panics and only fails with typed errors.
+//!
+//! ```text
+//! cd programs/lockbox/fuzz
+//! cargo +nightly fuzz run instruction_handlers
+//! ```
+
+use std::sync::OnceLock;
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use arbitrary::Arbitrary;
+use sha2::{Digest, Sha256};
+
+pub mod chunk;
+pub mod runtime;
+
+/// Cluster time the harness runs at
+pub const NOW: i64 = 1_700_000_000;
+
+/// Slot the harness runs at
+pub const SLOT: u64 = 250_000_000;
+
+/// Most accounts one fuzzed instruction passes
+pub const MAX_ACCOUNTS: usize = 32;
+
+/// A program instruction and its Anchor discriminator
+#[derive(Clone, Debug)]
+pub struct InstructionInfo {
+    pub name: String,
+    pub discriminator: [u8; 8],
+}
+
+/// Every instruction of the program, in `lib.rs` order
+///
+/// Read from the program source so new instructions are fuzzed without
+/// touching the harness.
+pub fn instructions() -> &'static [InstructionInfo] {
+    static INSTRUCTIONS: OnceLock<Vec<InstructionInfo>> = OnceLock::new();
+    INSTRUCTIONS.get_or_init(|| {
+        include_str!("../../src/lib.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("    pub fn "))
+            .filter_map(|rest| rest.split(['(', '<']).next())
+            .map(|name| {
+                let digest = Sha256::digest(format!("global:{name}"));
+                let mut discriminator = [0u8; 8];
+                discriminator.copy_from_slice(&digest[..8]);
+                InstructionInfo {
+                    name: name.to_string(),
+                    discriminator,
+                }
+            })
+            .collect()
+    })
+}
+
+/// Instruction data for the instruction picked by `selector`
+pub fn instruction_data(selector: u8, args: &[u8]) -> Vec<u8> {
+    let instructions = instructions();
+    let info = &instructions[selector as usize % instructions.len()];
+    let mut data = info.discriminator.to_vec();
+    data.extend_from_slice(args);
+    data
+}
+
+/// Number of `LockboxError` variants (codes `6000..6000 + n`)
+fn lockbox_error_count() -> u32 {
+    static COUNT: OnceLock<u32> = OnceLock::new();
+    *COUNT.get_or_init(|| {
+        let source = include_str!("../../src/errors.rs");
+        let body = source
+            .split("pub enum LockboxError {")
+            .nth(1)
+            .and_then(|rest| rest.split("\n}").next())
+            .expect("LockboxError enum in errors.rs");
+        body.lines()
+            .map(str::trim)
+            .filter(|line| line.ends_with(',') && line.starts_with(|c: char| c.is_ascii_uppercase()))
+            .count() as u32
+    })
+}
+
+/// Assert that an instruction failed, if at all, with a typed error
+///
+/// Custom codes must be Anchor framework errors or `LockboxError`s, and an
+/// `InvariantViolation` (the harness builds with `invariant-checks`) is
+/// always a bug.
+pub fn check_result(result: &ProgramResult) {
+    let Err(err) = result else { return };
+    if let ProgramError::Custom(code) = err {
+        let framework = (100..ERROR_CODE_OFFSET).contains(code);
+        let program = (ERROR_CODE_OFFSET..ERROR_CODE_OFFSET + lockbox_error_count()).contains(code);
+        assert!(framework || program, "untyped error code {code}");
+
+        let invariant_violation: u32 = lockbox::errors::LockboxError::InvariantViolation.into();
+        assert_ne!(*code, invariant_violation, "accounting invariant violated");
+    }
+}
+
+/// One account passed to a fuzzed instruction
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub struct FuzzAccountMeta {
+    /// Index into the harness account pool (modulo its size)
+    pub index: u8,
+    /// Only honoured for wallets the fixtures hold keys for
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// One fuzzed instruction
+#[derive(Arbitrary, Clone, Debug)]
+pub struct FuzzInstruction {
+    /// Index into [`instructions`] (modulo its length)
+    pub instruction: u8,
+    pub accounts: Vec<FuzzAccountMeta>,
+    /// Borsh-encoded arguments, after the discriminator
+    pub args: Vec<u8>,
+}
@@ -0,0 +1,381 @@
+//! In-process runtime
+//!
+//! Stubs the syscalls the program uses, serializes accounts into the
+//! loader's input format (so `realloc` and `assign` work on real buffers)
+//! and runs instructions against a pool of fixture accounts. Like the
+//! runtime, a failed instruction leaves the pool untouched.
+
+use std::sync::{Once, OnceLock};
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::entrypoint::{
+    self, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS,
+};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::system_instruction::SystemInstruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use lockbox_client::pda;
+use lockbox_fixtures::account::AccountFixture;
+use lockbox_fixtures::build_fixtures;
+use lockbox_fixtures::keys::FixtureKeypair;
+
+use crate::{check_result, instruction_data, FuzzInstruction, MAX_ACCOUNTS, NOW, SLOT};
+
+const NATIVE_LOADER: &str = "NativeLoader1111111111111111111111111111111";
+
+/// The cluster clock at [`NOW`]
+pub fn clock() -> Clock {
+    Clock {
+        slot: SLOT,
+        epoch_start_timestamp: NOW,
+        epoch: SLOT / 432_000,
+        leader_schedule_epoch: SLOT / 432_000 + 1,
+        unix_timestamp: NOW,
+    }
+}
+
+// ============================================================================
+// Syscall Stubs
+// ============================================================================
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Clock) = clock() };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        invoke_system_program(instruction, account_infos)
+    }
+}
+
+/// Install the syscall stubs (once per process)
+pub fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+fn require_writable(account: &AccountInfo) -> ProgramResult {
+    if account.is_writable {
+        Ok(())
+    } else {
+        // The runtime's ReadonlyLamportChange / ReadonlyDataModified
+        Err(ProgramError::InvalidArgument)
+    }
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    require_writable(from)?;
+    require_writable(to)?;
+    let debited = from.lamports().checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = debited;
+    let credited = to.lamports().checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    require_writable(account)?;
+    if !account.data_is_empty() || *account.owner != system_program::ID {
+        // SystemError::AccountAlreadyInUse
+        return Err(ProgramError::Custom(0));
+    }
+    account.realloc(space as usize, true)
+}
+
+/// The system program instructions the lockbox program invokes
+fn invoke_system_program(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    if instruction.program_id != system_program::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let account = |index: usize| {
+        let key = instruction
+            .accounts
+            .get(index)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?
+            .pubkey;
+        account_infos
+            .iter()
+            .find(|info| *info.key == key)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    };
+
+    let system_instruction: SystemInstruction =
+        bincode::deserialize(&instruction.data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match system_instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => {
+            let to = account(1)?;
+            allocate(to, space)?;
+            transfer(account(0)?, to, lamports)?;
+            to.assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => transfer(account(0)?, account(1)?, lamports)?,
+        SystemInstruction::Allocate { space } => allocate(account(0)?, space)?,
+        SystemInstruction::Assign { owner } => {
+            let to = account(0)?;
+            require_writable(to)?;
+            to.assign(&owner);
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Account Pool
+// ============================================================================
+
+/// An account instructions can be given
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolAccount {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub executable: bool,
+    /// The fixtures hold this wallet's key, so it may sign
+    pub signer: bool,
+}
+
+impl PoolAccount {
+    fn from_fixture(fixture: &AccountFixture, signer: bool) -> Self {
+        Self {
+            address: fixture.address,
+            owner: fixture.owner,
+            lamports: fixture.lamports,
+            data: fixture.data.clone(),
+            executable: false,
+            signer,
+        }
+    }
+
+    fn empty(address: Pubkey) -> Self {
+        Self {
+            address,
+            owner: system_program::ID,
+            lamports: 0,
+            data: Vec::new(),
+            executable: false,
+            signer: false,
+        }
+    }
+
+    fn program(address: Pubkey, loader: Pubkey) -> Self {
+        Self {
+            owner: loader,
+            lamports: 1,
+            executable: true,
+            ..Self::empty(address)
+        }
+    }
+
+    fn sysvar<T: serde::Serialize>(address: Pubkey, value: &T) -> Self {
+        let data = bincode::serialize(value).expect("sysvar serializes");
+        Self {
+            owner: sysvar::ID,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            ..Self::empty(address)
+        }
+    }
+}
+
+/// Unused PDAs of a vault, for instructions that create them
+fn empty_pdas(owner: &Pubkey) -> Vec<Pubkey> {
+    let master = pda::master_lockbox(owner).0;
+    let mut addresses = vec![
+        master,
+        pda::category_registry(&master).0,
+        pda::security_policy(&master).0,
+        pda::contact_book(&master).0,
+        pda::deny_list(&master).0,
+        pda::tag_registry(&master).0,
+        pda::search_filter(&master).0,
+        pda::category_index(&master).0,
+        pda::billing_receipts(&master).0,
+        pda::export_log(&master).0,
+        pda::vault_transfer(&master).0,
+        pda::entry_notes(&master, 1).0,
+        pda::entry_provenance(&master, 1).0,
+        pda::entry_copies(&master, 1).0,
+        pda::inbox(owner).0,
+        pda::recovery_config_v2(owner).0,
+        pda::emergency_access(owner).0,
+    ];
+    addresses.extend((0..4).map(|index| pda::storage_chunk(&master, index).0));
+    addresses
+}
+
+/// The fixture vaults, a vault-less wallet, the PDAs they may create, the
+/// programs and the sysvars
+pub fn pool() -> &'static [PoolAccount] {
+    static POOL: OnceLock<Vec<PoolAccount>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let set = build_fixtures(NOW).expect("fixtures build");
+        let signers: Vec<Pubkey> = set.keypairs().iter().map(|keypair| keypair.pubkey).collect();
+        let newcomer = FixtureKeypair::derive("fuzz/newcomer").pubkey;
+
+        let mut accounts: Vec<PoolAccount> = set
+            .accounts
+            .iter()
+            .map(|fixture| PoolAccount::from_fixture(fixture, signers.contains(&fixture.address)))
+            .collect();
+        accounts.push(PoolAccount::from_fixture(&AccountFixture::wallet(newcomer), true));
+
+        let owners = set.vaults.iter().map(|vault| vault.owner).chain([newcomer]);
+        let addresses = owners
+            .flat_map(|owner| empty_pdas(&owner))
+            .chain([pda::treasury().0]);
+        for address in addresses {
+            if !accounts.iter().any(|account| account.address == address) {
+                accounts.push(PoolAccount::empty(address));
+            }
+        }
+
+        accounts.push(PoolAccount::program(system_program::ID, NATIVE_LOADER.parse().unwrap()));
+        accounts.push(PoolAccount::program(lockbox::ID, bpf_loader_upgradeable::ID));
+        accounts.push(PoolAccount::sysvar(sysvar::clock::ID, &clock()));
+        accounts.push(PoolAccount::sysvar(sysvar::rent::ID, &Rent::default()));
+        accounts
+    })
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+/// `(pool index, is_signer, is_writable)` of each instruction account
+type Metas = [(usize, bool, bool)];
+
+/// Serialize accounts and data into the loader's (aligned) input format
+///
+/// Backed by `u64`s so `entrypoint::deserialize` reads aligned words.
+fn serialize_input(metas: &Metas, pool: &[PoolAccount], data: &[u8]) -> Vec<u64> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+    for (position, &(index, is_signer, is_writable)) in metas.iter().enumerate() {
+        if let Some(first) = metas[..position].iter().position(|meta| meta.0 == index) {
+            bytes.push(first as u8);
+            bytes.extend_from_slice(&[0u8; 7]);
+            continue;
+        }
+
+        let account = &pool[index];
+        bytes.extend_from_slice(&[NON_DUP_MARKER, is_signer as u8, is_writable as u8, account.executable as u8]);
+        bytes.extend_from_slice(&[0u8; 4]); // original data length, set by `deserialize`
+        bytes.extend_from_slice(account.address.as_ref());
+        bytes.extend_from_slice(account.owner.as_ref());
+        bytes.extend_from_slice(&account.lamports.to_le_bytes());
+        bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&account.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+        bytes.extend_from_slice(&u64::MAX.to_le_bytes()); // rent epoch
+    }
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(lockbox::ID.as_ref());
+
+    let mut words = vec![0u64; bytes.len().div_ceil(8)];
+    // SAFETY: `words` holds at least `bytes.len()` bytes
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), words.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    words
+}
+
+/// Runs instructions against a private copy of the account pool
+pub struct Harness {
+    pub accounts: Vec<PoolAccount>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        install_stubs();
+        Self {
+            accounts: pool().to_vec(),
+        }
+    }
+
+    /// Run one instruction and commit its account changes if it succeeds
+    ///
+    /// Panics if the program panics, fails with an untyped error, or
+    /// breaks a rule the runtime enforces: lamports must balance and
+    /// read-only accounts must not change.
+    pub fn process(&mut self, instruction: &FuzzInstruction) -> ProgramResult {
+        let metas: Vec<(usize, bool, bool)> = instruction
+            .accounts
+            .iter()
+            .take(MAX_ACCOUNTS)
+            .map(|meta| {
+                let index = meta.index as usize % self.accounts.len();
+                (index, meta.is_signer && self.accounts[index].signer, meta.is_writable)
+            })
+            .collect();
+        let data = instruction_data(instruction.instruction, &instruction.args);
+
+        let mut input = serialize_input(&metas, &self.accounts, &data);
+        // SAFETY: `input` is a well-formed loader buffer that outlives `infos`
+        let (program_id, infos, data) = unsafe { entrypoint::deserialize(input.as_mut_ptr() as *mut u8) };
+        let result = lockbox::entry(program_id, &infos, data);
+        check_result(&result);
+        result?;
+
+        let mut changed: Vec<(usize, PoolAccount)> = Vec::new();
+        for (info, &(index, _, _)) in infos.iter().zip(&metas) {
+            if changed.iter().any(|(i, _)| *i == index) {
+                continue;
+            }
+            let before = &self.accounts[index];
+            let after = PoolAccount {
+                owner: *info.owner,
+                lamports: info.lamports(),
+                data: info.data.borrow().to_vec(),
+                ..before.clone()
+            };
+            if after != *before {
+                assert!(info.is_writable, "read-only account {} changed", before.address);
+            }
+            changed.push((index, after));
+        }
+
+        let before: u128 = changed.iter().map(|(i, _)| self.accounts[*i].lamports as u128).sum();
+        let after: u128 = changed.iter().map(|(_, account)| account.lamports as u128).sum();
+        assert_eq!(before, after, "lamports not balanced");
+
+        for (index, account) in changed {
+            self.accounts[index] = account;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,54 @@
 use anchor_lang::prelude::*;
 
+/// Quantity a failed check compared, reported in `ErrorContextEvent`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorField {
+    /// Write time (`actual`) against the earliest allowed write (`limit`)
+    WriteTimestamp,
+    /// Ciphertext bytes (`actual`) against the minimum AEAD payload (`limit`)
+    DataSize,
+    /// Current time (`actual`) against the subscription expiry (`limit`)
+    SubscriptionExpiry,
+    /// Vault bytes after the write (`actual`) against the capacity limit (`limit`)
+    StorageCapacity,
+    /// Bytes written to the chunk (`actual`) against the space left (`limit`)
+    ChunkCapacity,
+    /// Settings blob bytes (`actual`) against the tier limit (`limit`)
+    SettingsSize,
+    /// Entry ciphertext bytes (`actual`) against the tier limit (`limit`)
+    EntrySize,
+}
+
+/// Structured context for a failed check
+///
+/// Emitted just before the instruction fails, so it shows up in the
+/// simulation or transaction logs next to the numeric error code. Clients
+/// can then render a localized message from `code`, `field`, `limit` and
+/// `actual` instead of the English `#[msg]` text.
+#[event]
+pub struct ErrorContextEvent {
+    /// Anchor error code (`6000 + LockboxError` discriminant)
+    pub code: u32,
+    pub field: ErrorField,
+    pub limit: i64,
+    pub actual: i64,
+}
+
+/// Like `require!`, but emits an `ErrorContextEvent` when `condition` fails
+pub fn require_with_context(
+    condition: bool,
+    error: LockboxError,
+    field: ErrorField,
+    limit: i64,
+    actual: i64,
+) -> Result<()> {
+    if condition {
+        return Ok(());
+    }
+    emit!(ErrorContextEvent { code: error.into(), field, limit, actual });
+    Err(error.into())
+}
+
 #[error_code]
 pub enum LockboxError {
     #[msg("Maximum number of storage chunks reached")]
@@ -90,7 +139,7 @@ pub enum LockboxError {
     #[msg("Invalid recovery delay (must be between 1 and 30 days)")]
     InvalidRecoveryDelay,
 
-    #[msg("Maximum number of guardians reached (10)")]
+    #[msg("Maximum number of guardians reached for this tier")]
     TooManyGuardians,
 
     #[msg("Guardian already exists")]
@@ -130,7 +179,7 @@ pub enum LockboxError {
     #[msg("Invalid grace period (must be >= 1 day)")]
     InvalidGracePeriod,
 
-    #[msg("Maximum number of emergency contacts reached (5)")]
+    #[msg("Maximum number of emergency contacts reached for this tier")]
     TooManyContacts,
 
     #[msg("Emergency contact already exists")]
@@ -195,4 +244,461 @@ pub enum LockboxError {
     // SECURITY FIX (Phase 3): Rate limiting
     #[msg("Recovery rate limit exceeded: please wait before initiating another recovery")]
     RecoveryRateLimitExceeded,
+
+    // Program-owned vaults
+    #[msg("Owner seeds do not derive the signing PDA for the given program")]
+    InvalidOwnerSeeds,
+
+    // Account migrations
+    #[msg("Account is not a migratable Lockbox account")]
+    UnknownAccountType,
+
+    #[msg("Account is already at the current schema version")]
+    AccountAlreadyMigrated,
+
+    // Client-assigned entry IDs
+    #[msg("Client entry ID must have the high bit set")]
+    InvalidEntryId,
+
+    #[msg("Entry ID is already in use in this vault")]
+    DuplicateEntryId,
+
+    // Entry aliases
+    #[msg("Entry is not an alias")]
+    EntryNotAlias,
+
+    #[msg("Operation not supported on alias entries")]
+    EntryIsAlias,
+
+    #[msg("Alias chunk must be a separate, writable chunk of this lockbox")]
+    InvalidAliasTarget,
+
+    // Entry notes
+    #[msg("Entry already has the maximum number of notes")]
+    EntryNotesFull,
+
+    // Cold storage
+    #[msg("Chunk has the wrong storage type for this operation")]
+    InvalidChunkType,
+
+    // Trash
+    #[msg("Trash retention must be between 1 and 365 days")]
+    InvalidTrashRetention,
+
+    #[msg("Entry is in the trash")]
+    EntryTrashed,
+
+    // Chunk shrinking
+    #[msg("Chunk cannot shrink below its stored data or the minimum chunk size")]
+    ShrinkBelowContents,
+
+    // Multi-chunk instructions
+    #[msg("Additional chunk must be a writable, registered chunk of this lockbox")]
+    InvalidChunkAccount,
+
+    // CPI guard
+    #[msg("Instruction must be invoked directly, not via another program")]
+    CpiNotAllowed,
+
+    // Security policy
+    #[msg("Ciphertext size must be padded to the policy block size")]
+    UnpaddedCiphertext,
+
+    #[msg("Security policy requires the delete co-signer")]
+    CosignerRequired,
+
+    #[msg("Security policy read cooldown has not elapsed")]
+    ReadCooldownActive,
+
+    #[msg("Unknown privacy flags")]
+    InvalidPrivacyFlags,
+
+    // Guardian agreements
+    #[msg("Agreement hash does not match the owner's recorded terms")]
+    AgreementHashMismatch,
+
+    // Emergency contact notifications
+    #[msg("Notification interval must be 0 or between 1 hour and the grace period")]
+    InvalidNotificationInterval,
+
+    #[msg("Emergency contacts are not due another notification")]
+    NotificationNotDue,
+
+    // Account migrations (v2)
+    #[msg("Account must be migrated to the previous schema version first")]
+    MigrationOutOfOrder,
+
+    // Emergency flow testing
+    #[msg("Emergency flow can only be tested while no countdown is running")]
+    EmergencyFlowTestUnavailable,
+
+    // Tier-dependent guardian limits
+    #[msg("Recovery request already holds the maximum number of guardian approvals")]
+    RecoveryApprovalsFull,
+
+    // Closing recovery and emergency configs
+    #[msg("Emergency countdown is running - cancel it before closing")]
+    EmergencyCountdownActive,
+
+    // Closing the master lockbox
+    #[msg("Every chunk and auxiliary account must be passed when closing the master lockbox")]
+    AuxiliaryAccountMissing,
+
+    #[msg("Close chunks, registry, policy, recovery and emergency accounts before the master lockbox")]
+    AuxiliaryAccountOpen,
+
+    // Closing storage chunks
+    #[msg("Chunk still holds entries - pass allow_non_empty to destroy them")]
+    ChunkNotEmpty,
+
+    // Recovery account binding
+    #[msg("Recovery request does not belong to this recovery configuration")]
+    RecoveryRequestMismatch,
+
+    // Entry write fees
+    #[msg("Entry write fee is due - pass the treasury and system program")]
+    EntryFeeAccountsMissing,
+
+    // Accounting invariants (invariant-checks feature)
+    #[msg("Lockbox accounting invariant violated - see InvariantViolationEvent")]
+    InvariantViolation,
+
+    // Encrypted settings
+    #[msg("Settings blob exceeds the subscription tier's limit")]
+    SettingsTooLarge,
+
+    // Contact book
+    #[msg("Contact book is full for this subscription tier")]
+    ContactBookFull,
+
+    #[msg("Contact is already in the contact book")]
+    ContactBookEntryExists,
+
+    #[msg("Contact not found in the contact book")]
+    ContactBookEntryNotFound,
+
+    // Inbox
+    #[msg("Inbox has no such item")]
+    InboxItemNotFound,
+
+    // Invitations
+    #[msg("Guardian invitation has expired; ask the owner to resend it")]
+    GuardianInvitationExpired,
+
+    #[msg("Invitation TTL must be between 1 and 90 days")]
+    InvalidInvitationTtl,
+
+    #[msg("Emergency contact invitation has expired; ask the owner to resend it")]
+    ContactInvitationExpired,
+
+    // Recovery Arming
+    #[msg("Recovery is not armed: fewer guardians have accepted than the threshold requires")]
+    RecoveryNotArmed,
+
+    // Recovery Pause
+    #[msg("Recovery is disabled by the owner")]
+    RecoveryDisabled,
+
+    // Owner Activity
+    #[msg("The owner used the vault too recently for recovery to start")]
+    OwnerRecentlyActive,
+
+    #[msg("Owner activity window must be 0 or between 1 and 365 days")]
+    InvalidActivityWindow,
+
+    // Entry Copy
+    #[msg("Source and target vault must be different")]
+    CopyTargetIsSource,
+
+    // Billing
+    #[msg("Billing period has not ended or the vault is not on a paid tier")]
+    BillingSnapshotNotDue,
+
+    // Capacity Add-ons
+    #[msg("Capacity add-on must be between 1 and 20 packs")]
+    InvalidCapacityAddon,
+
+    // Dunning
+    #[msg("Subscription has not expired, or its dunning window is still open")]
+    SubscriptionTransitionNotDue,
+
+    #[msg("Dunning window must be at most 30 days")]
+    InvalidDunningWindow,
+
+    // Vault Deletion
+    #[msg("Vault deletion has already been requested")]
+    VaultDeletionAlreadyRequested,
+
+    #[msg("Vault deletion has not been requested")]
+    VaultDeletionNotRequested,
+
+    #[msg("Request vault deletion and wait out the delay before destroying data")]
+    VaultDeletionPending,
+
+    // Export Consent
+    #[msg("Export consent must name existing emergency contacts, once each")]
+    InvalidExportConsent,
+
+    // Entry Size
+    #[msg("Encrypted entry exceeds the subscription tier's maximum entry size")]
+    EntryTooLarge,
+
+    // Chunk Layout
+    #[msg("Chunk layout changed since it was read; reload the chunk and retry")]
+    StaleChunkLayout,
+
+    // Writer Lease
+    #[msg("Another device holds the writer lease; wait for it to expire")]
+    WriterLeaseHeld,
+
+    #[msg("Writer lease duration must be between 1 second and 10 minutes")]
+    InvalidWriterLeaseDuration,
+
+    #[msg("This device does not hold the writer lease")]
+    WriterLeaseNotHeld,
+
+    // Vault Transfer
+    #[msg("Vault transfer must name another buyer and be listed for at most 30 days")]
+    InvalidVaultTransfer,
+
+    #[msg("Vault transfer is not open to this buyer, or the listing has expired")]
+    VaultTransferNotOpen,
+
+    #[msg("Vault transfer price differs from the price the buyer agreed to")]
+    VaultTransferPriceMismatch,
+
+    #[msg("Vault transfer has already been accepted")]
+    VaultTransferAlreadyAccepted,
+
+    #[msg("Vault transfer has not been accepted yet")]
+    VaultTransferNotAccepted,
+
+    #[msg("Pass every storage chunk of the vault being transferred")]
+    VaultTransferChunksMissing,
+
+    #[msg("Entries written under the previous owner's key remain; re-key them first")]
+    VaultRekeyIncomplete,
+
+    // Export Manifest
+    #[msg("Unsupported export manifest format version")]
+    UnsupportedExportManifest,
+
+    // Batch Writes
+    #[msg("Batch must hold between 1 and 16 entries")]
+    InvalidBatchSize,
+
+    // Payload Framing
+    #[msg("Encrypted payload lacks a valid framing header (version, nonce length, suite)")]
+    MalformedPayload,
+
+    // Write Burst
+    #[msg("Too many bytes written in this slot window; retry shortly")]
+    WriteBurstLimited,
+
+    #[msg("Write burst limit must be 0 (tier default) or between the tier's maximum entry size and its burst limit")]
+    InvalidWriteBurstLimit,
+
+    // Cross-Chunk Entries
+    #[msg("Entry spans several chunks; use the spanning entry instructions")]
+    EntrySpansChunks,
+
+    #[msg("A segment of the spanning entry is missing or out of order")]
+    EntrySegmentMissing,
+
+    // Entry Copies
+    #[msg("Entry already has the maximum number of fresh copies")]
+    TooManyEntryCopies,
+
+    // Trash Restore
+    #[msg("Entry is not in the trash")]
+    EntryNotTrashed,
+
+    #[msg("Trash retention has elapsed; the entry can only be purged")]
+    TrashRetentionElapsed,
+
+    // Deny-List
+    #[msg("Key is on the vault's deny-list")]
+    KeyDenied,
+
+    #[msg("Key is already on the deny-list")]
+    KeyAlreadyDenied,
+
+    #[msg("Key is not on the deny-list")]
+    KeyNotDenied,
+
+    #[msg("Deny-list is full")]
+    DenyListFull,
+
+    // Entry Flags
+    #[msg("Only the favorite and archived flags can be set directly")]
+    InvalidEntryFlags,
+
+    // Entry Tags
+    #[msg("Tag hash must not be all zeroes")]
+    InvalidTagHash,
+
+    #[msg("Tag is not registered")]
+    TagNotFound,
+
+    #[msg("Every tag slot is in use; delete a tag first")]
+    TagRegistryFull,
+
+    #[msg("Pass every storage chunk of the vault to delete a tag")]
+    TagChunksMissing,
+
+    #[msg("Delete every tag before closing the tag registry")]
+    TagsStillRegistered,
+
+    // Rotation Tracking
+    #[msg("Rotation date must be a Unix timestamp, or 0 to clear it")]
+    InvalidRotationDate,
+
+    // TOTP Entries
+    #[msg("TOTP payload must hold a 16 to 128-byte seed block")]
+    InvalidTotpPayload,
+
+    #[msg("Entry is not a TOTP entry")]
+    NotTotpEntry,
+
+    #[msg("TOTP entries can only be attached to a live login entry")]
+    InvalidTotpParent,
+
+    // Entry Links
+    #[msg("Only two different live, non-alias entries can be linked")]
+    InvalidEntryLink,
+
+    #[msg("Entries are already linked")]
+    EntryAlreadyLinked,
+
+    #[msg("Entry has too many links")]
+    TooManyEntryLinks,
+
+    #[msg("Entries are not linked")]
+    EntryLinkNotFound,
+
+    // Search Index
+    #[msg("Search index chunks hold index postings, not entries")]
+    SearchIndexChunk,
+
+    #[msg("Chunk is not a search index chunk")]
+    NotSearchIndexChunk,
+
+    #[msg("Too many search tokens for one entry")]
+    TooManySearchTokens,
+
+    #[msg("Search tokens cannot be all zeroes")]
+    InvalidSearchToken,
+
+    // Title Lookup
+    #[msg("Title hash cannot be all zeroes")]
+    InvalidTitleHash,
+
+    // Category Index
+    #[msg("Category index is full")]
+    CategoryIndexFull,
+
+    #[msg("System program is required to grow the category index")]
+    CategoryIndexAccountsMissing,
+
+    #[msg("Every registered chunk must be passed to rebuild the category index")]
+    CategoryIndexChunksMissing,
+
+    // Timekeeping
+    #[msg("Cluster clock returned an implausible timestamp")]
+    InvalidClockTimestamp,
+
+    #[msg("Recorded timestamp is ahead of the cluster clock by more than the allowed drift")]
+    TimestampInFuture,
+
+    // Recovery Cooldown
+    #[msg("Recovery cooldown must be at least 1 and at most about a week in the chosen units")]
+    InvalidRecoveryCooldown,
+
+    // Index Rebuild
+    #[msg("Every registered chunk must be passed to rebuild the vault's indexes")]
+    IndexRebuildChunksMissing,
+
+    // Category Accounts
+    #[msg("The vault has a category registry; pass it to keep category counts in sync")]
+    CategoryRegistryRequired,
+
+    #[msg("The vault has a category index; pass it to keep the index in sync")]
+    CategoryIndexRequired,
+
+    // Vault Transfer Listing
+    #[msg("Vault is listed for transfer; cancel the listing to change its entries or delete it")]
+    VaultListedForTransfer,
+
+    // Shared Vaults
+    #[msg("Shared vault has the maximum number of members")]
+    SharedVaultFull,
+
+    #[msg("Wallet is already a member of the shared vault")]
+    SharedVaultMemberExists,
+
+    #[msg("Wallet is not a member of the shared vault")]
+    SharedVaultMemberNotFound,
+
+    #[msg("Member's role does not allow this action in the shared vault")]
+    SharedVaultAccessDenied,
+
+    #[msg("Shared vault still has entries")]
+    SharedVaultNotEmpty,
+
+    #[msg("Vault's shared vaults still hold entries; delete them before closing the vault")]
+    SharedStorageInUse,
+
+    // Shared Entry Checkout
+    #[msg("Entry is checked out by another member")]
+    EntryCheckedOut,
+
+    #[msg("Checkout duration must be between 1 second and 1 hour")]
+    InvalidEntryCheckoutDuration,
+
+    #[msg("Member does not hold a live checkout of this entry")]
+    EntryNotCheckedOut,
+
+    // Token-Gated Shared Vaults
+    #[msg("Shared vault does not allow token-gated joins")]
+    SharedVaultNotGated,
+
+    #[msg("Wallet does not hold the token required by the shared vault")]
+    TokenGateNotSatisfied,
+
+    // Offboarding Rotation
+    #[msg("Account is not a writable entry of this shared vault, or appears twice")]
+    InvalidSharedEntryAccount,
+
+    #[msg("Every entry of the shared vault must be passed to flag them for rotation")]
+    SharedEntriesMissing,
+
+    // Organization Invoices
+    #[msg("Invoice must be for a paid tier, with a nonzero amount and a period of at most 3 years")]
+    InvalidInvoice,
+
+    #[msg("Invoice has already been paid")]
+    InvoiceNotOpen,
+
+    #[msg("Token program must be SPL Token or Token-2022")]
+    InvalidTokenProgram,
+
+    // Refunds
+    #[msg("Refund must be nonzero and paid to the vault's owner")]
+    InvalidRefund,
+
+    #[msg("Treasury cannot cover the refund and stay rent exempt")]
+    InsufficientTreasuryFunds,
+
+    // Client Entry IDs
+    #[msg("Client entry IDs need the vault's client entry ID account")]
+    ClientEntryIdsRequired,
+
+    #[msg("Vault has reserved the maximum number of client entry IDs")]
+    ClientEntryIdsFull,
+
+    #[msg("System program is required to grow the client entry ID account")]
+    ClientEntryIdsAccountsMissing,
+
+    #[msg("Every registered chunk must be passed to create the client entry ID account")]
+    ClientEntryIdsChunksMissing,
 }
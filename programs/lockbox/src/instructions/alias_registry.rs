@@ -0,0 +1,172 @@
+/**
+ * Handle Alias Registry
+ *
+ * Opt-in global handles ("@alice") that resolve on-chain to a vault owner,
+ * so users can reference each other without pasting addresses. The handle
+ * itself never appears on-chain; the `Alias` PDA is keyed by its hash.
+ *
+ * Security:
+ * - Registration costs `ALIAS_REGISTRATION_FEE_LAMPORTS` (paid to the
+ *   treasury) on top of rent, which makes bulk squatting expensive
+ * - Handles can only point at owners with a master lockbox
+ * - Only the current holder can transfer or release a handle
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Alias, MasterLockbox, ALIAS_REGISTRATION_FEE_LAMPORTS, TREASURY_SEED};
+use crate::errors::LockboxError;
+
+/// Claim a handle for the signer's vault
+#[derive(Accounts)]
+#[instruction(handle_hash: [u8; 32])]
+pub struct RegisterAlias<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Fails with "already in use" if the handle is taken
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Alias::INIT_SPACE,
+        seeds = [Alias::SEEDS_PREFIX, handle_hash.as_ref()],
+        bump
+    )]
+    pub alias: Account<'info, Alias>,
+
+    /// CHECK: Treasury PDA (system account) receiving the registration fee
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_alias_handler(ctx: Context<RegisterAlias>, handle_hash: [u8; 32]) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            },
+        ),
+        ALIAS_REGISTRATION_FEE_LAMPORTS,
+    )?;
+
+    let alias = &mut ctx.accounts.alias;
+    alias.version = Alias::CURRENT_VERSION;
+    alias.handle_hash = handle_hash;
+    alias.owner = ctx.accounts.owner.key();
+    alias.registered_at = current_timestamp;
+    alias.bump = ctx.bumps.alias;
+
+    emit!(AliasRegisteredEvent {
+        handle_hash,
+        owner: alias.owner,
+        fee: ALIAS_REGISTRATION_FEE_LAMPORTS,
+        registered_at: current_timestamp,
+    });
+
+    msg!("Alias registered for {}", alias.owner);
+    Ok(())
+}
+
+/// Hand a handle to another vault owner
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferAlias<'info> {
+    #[account(
+        mut,
+        seeds = [Alias::SEEDS_PREFIX, alias.handle_hash.as_ref()],
+        bump = alias.bump,
+        constraint = alias.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub alias: Account<'info, Alias>,
+
+    /// Recipient's vault; handles only ever resolve to existing vaults
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, new_master_lockbox.creator.as_ref()],
+        bump = new_master_lockbox.bump,
+        constraint = new_master_lockbox.owner == new_owner @ LockboxError::Unauthorized
+    )]
+    pub new_master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn transfer_alias_handler(ctx: Context<TransferAlias>, new_owner: Pubkey) -> Result<()> {
+    let alias = &mut ctx.accounts.alias;
+    let previous_owner = alias.owner;
+
+    alias.owner = new_owner;
+    alias.registered_at = Clock::get()?.unix_timestamp;
+
+    emit!(AliasTransferredEvent {
+        handle_hash: alias.handle_hash,
+        previous_owner,
+        new_owner,
+    });
+
+    msg!("Alias transferred from {} to {}", previous_owner, new_owner);
+    Ok(())
+}
+
+/// Give up a handle and reclaim its rent
+#[derive(Accounts)]
+pub struct ReleaseAlias<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [Alias::SEEDS_PREFIX, alias.handle_hash.as_ref()],
+        bump = alias.bump,
+        constraint = alias.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub alias: Account<'info, Alias>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn release_alias_handler(ctx: Context<ReleaseAlias>) -> Result<()> {
+    emit!(AliasReleasedEvent {
+        handle_hash: ctx.accounts.alias.handle_hash,
+        owner: ctx.accounts.owner.key(),
+    });
+
+    msg!("Alias released");
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct AliasRegisteredEvent {
+    pub handle_hash: [u8; 32],
+    pub owner: Pubkey,
+    pub fee: u64,
+    pub registered_at: i64,
+}
+
+#[event]
+pub struct AliasTransferredEvent {
+    pub handle_hash: [u8; 32],
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct AliasReleasedEvent {
+    pub handle_hash: [u8; 32],
+    pub owner: Pubkey,
+}
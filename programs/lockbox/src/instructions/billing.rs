@@ -0,0 +1,128 @@
+/**
+ * Billing Snapshots
+ *
+ * Paid vaults accumulate the subscription payments made in each 30-day
+ * billing period on the master lockbox. Once a period ends, anyone may
+ * crank `emit_billing_snapshot` to close it: the program emits a
+ * `BillingSnapshotEvent` with a per-vault sequence number and, if the owner
+ * created one, appends the receipt to their `BillingReceipts` account.
+ * Together these give businesses a verifiable expense record for the
+ * subscription.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{check_recorded_timestamp, cluster_timestamp, BillingReceipts, MasterLockbox, SubscriptionTier};
+use crate::errors::LockboxError;
+
+/// Create the vault's billing receipts account
+#[derive(Accounts)]
+pub struct InitializeBillingReceipts<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + BillingReceipts::INIT_SPACE,
+        seeds = [BillingReceipts::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub billing_receipts: Account<'info, BillingReceipts>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_billing_receipts_handler(ctx: Context<InitializeBillingReceipts>) -> Result<()> {
+    let billing_receipts = &mut ctx.accounts.billing_receipts;
+    billing_receipts.version = BillingReceipts::CURRENT_VERSION;
+    billing_receipts.master_lockbox = ctx.accounts.master_lockbox.key();
+    billing_receipts.owner = ctx.accounts.owner.key();
+    billing_receipts.receipts = Vec::new();
+    billing_receipts.bump = ctx.bumps.billing_receipts;
+
+    msg!("Billing receipts initialized for {}", billing_receipts.owner);
+    Ok(())
+}
+
+/// Close a vault's billing period (permissionless crank)
+#[derive(Accounts)]
+pub struct EmitBillingSnapshot<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Optional: also records the receipt in account state
+    #[account(
+        mut,
+        seeds = [BillingReceipts::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = billing_receipts.bump
+    )]
+    pub billing_receipts: Option<Account<'info, BillingReceipts>>,
+}
+
+/// Close the billing period once `BILLING_PERIOD` has passed
+///
+/// The next period starts when the snapshot is taken, so a late crank never
+/// produces a catch-up burst of empty snapshots.
+///
+/// # Errors
+/// * `BillingSnapshotNotDue` - Free vault, or the period has not ended
+/// * `TimestampInFuture` - The period start is ahead of the cluster clock
+pub fn emit_billing_snapshot_handler(ctx: Context<EmitBillingSnapshot>) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = cluster_timestamp()?;
+    check_recorded_timestamp(master_lockbox.billing_period_start, current_timestamp)?;
+
+    require!(
+        master_lockbox.is_billing_snapshot_due(current_timestamp),
+        LockboxError::BillingSnapshotNotDue
+    );
+
+    let receipt = master_lockbox.close_billing_period(current_timestamp);
+    if let Some(billing_receipts) = ctx.accounts.billing_receipts.as_mut() {
+        billing_receipts.record(receipt);
+    }
+
+    emit!(BillingSnapshotEvent {
+        owner: master_lockbox.owner,
+        sequence: receipt.sequence,
+        tier: receipt.tier,
+        period_start: receipt.period_start,
+        period_end: receipt.period_end,
+        amount_paid: receipt.amount_paid,
+        storage_used: receipt.storage_used,
+    });
+
+    msg!(
+        "Billing snapshot {} for {}: {} lamports paid",
+        receipt.sequence,
+        master_lockbox.owner,
+        receipt.amount_paid
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct BillingSnapshotEvent {
+    pub owner: Pubkey,
+    pub sequence: u64,
+    pub tier: SubscriptionTier,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub amount_paid: u64,
+    pub storage_used: u64,
+}
@@ -0,0 +1,244 @@
+/**
+ * Category Index Instructions
+ *
+ * The vault's `CategoryIndex` maps each category ID to the IDs of the
+ * entries filed under it, so clients can list a category without fetching
+ * and scanning every chunk's headers.
+ *
+ * Like the category registry, the index is an account of the entry
+ * instructions that add or remove entries (`store_password_entry`,
+ * `store_password_entries_batch`, `store_spanning_entry`,
+ * `delete_password_entry`, `delete_spanning_entry` and
+ * `purge_expired_trash`), which keep it current. Once the index exists they
+ * fail without it, so it cannot silently drift. Updates never change an
+ * entry's category, so they leave it alone. Stores grow the account as
+ * needed, with the owner paying the rent.
+ *
+ * `rebuild_category_index` recomputes the index from every chunk of the
+ * vault, to backfill a new index.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::errors::LockboxError;
+use crate::instructions::chunk_set::ChunkSet;
+use crate::state::{CategoryIndex, CategoryIndexEntry, MasterLockbox, StorageChunk, MAX_CATEGORY_INDEX_ENTRIES};
+
+/// Most entry IDs `list_category_entries` returns (fits the 1 KB return data limit)
+pub const MAX_CATEGORY_RESULTS: usize = 127;
+
+/// Grow the index account to fit its entries, topping up rent from `payer`
+///
+/// # Errors
+/// * `CategoryIndexAccountsMissing` - Growth is needed but no system
+///   program was passed
+pub fn fit_category_index<'info>(
+    category_index: &Account<'info, CategoryIndex>,
+    payer: AccountInfo<'info>,
+    system_program: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let needed = CategoryIndex::space_for(category_index.entries.len());
+    let info = category_index.to_account_info();
+    if needed <= info.data_len() {
+        return Ok(());
+    }
+    let system_program = system_program.ok_or(LockboxError::CategoryIndexAccountsMissing)?;
+
+    let additional_rent = Rent::get()?
+        .minimum_balance(needed)
+        .saturating_sub(info.lamports());
+    if additional_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program, Transfer { from: payer, to: info.clone() }),
+            additional_rent,
+        )?;
+    }
+    info.realloc(needed, false)?;
+    Ok(())
+}
+
+/// Index entries of `chunks` by category
+///
+/// Aliases and continuation segments are left out, as on the write path.
+pub fn category_index_entries(chunks: &[StorageChunk]) -> Vec<CategoryIndexEntry> {
+    let mut entries: Vec<CategoryIndexEntry> = chunks
+        .iter()
+        .flat_map(|chunk| &chunk.entry_headers)
+        .filter(|header| !header.is_alias() && !header.is_continuation())
+        .map(|header| CategoryIndexEntry {
+            category: header.category,
+            entry_id: header.entry_id,
+        })
+        .collect();
+    entries.sort_unstable_by_key(|e| (e.category, e.entry_id));
+    entries.dedup();
+    entries
+}
+
+/// Create the vault's category index
+#[derive(Accounts)]
+pub struct InitializeCategoryIndex<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = CategoryIndex::space_for(0),
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an empty category index
+///
+/// Entries stored before the index existed are not in it; follow up with
+/// `rebuild_category_index`.
+pub fn initialize_category_index_handler(ctx: Context<InitializeCategoryIndex>) -> Result<()> {
+    let category_index = &mut ctx.accounts.category_index;
+    category_index.version = CategoryIndex::CURRENT_VERSION;
+    category_index.master_lockbox = ctx.accounts.master_lockbox.key();
+    category_index.entries = Vec::new();
+    category_index.bump = ctx.bumps.category_index;
+    ctx.accounts.master_lockbox.has_category_index = true;
+
+    msg!("Category index initialized");
+    Ok(())
+}
+
+/// Recompute the category index from the vault's chunks
+#[derive(Accounts)]
+pub struct RebuildCategoryIndex<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace the index with the entries of every chunk of the vault
+///
+/// Every registered chunk must be passed as a read-only remaining account.
+///
+/// # Errors
+/// * `CategoryIndexChunksMissing` - Not every registered chunk was passed
+/// * `InvalidChunkAccount` - A remaining account is not a registered chunk
+///   of this lockbox, or appears twice
+/// * `CategoryIndexFull` - The vault has more than `MAX_CATEGORY_INDEX_ENTRIES` entries
+pub fn rebuild_category_index_handler(ctx: Context<RebuildCategoryIndex>) -> Result<()> {
+    let chunks = ChunkSet::load_read_only(ctx.remaining_accounts, &ctx.accounts.master_lockbox)?;
+    require!(
+        chunks.len() == ctx.accounts.master_lockbox.storage_chunks.len(),
+        LockboxError::CategoryIndexChunksMissing
+    );
+
+    let entries = category_index_entries(chunks.chunks());
+    require!(
+        entries.len() <= MAX_CATEGORY_INDEX_ENTRIES,
+        LockboxError::CategoryIndexFull
+    );
+    let category_index = &mut ctx.accounts.category_index;
+    category_index.entries = entries;
+    fit_category_index(
+        category_index,
+        ctx.accounts.owner.to_account_info(),
+        Some(ctx.accounts.system_program.to_account_info()),
+    )?;
+
+    emit!(CategoryIndexRebuiltEvent {
+        master_lockbox: category_index.master_lockbox,
+        entries: category_index.entries.len() as u32,
+    });
+
+    msg!("Category index rebuilt with {} entries", category_index.entries.len());
+    Ok(())
+}
+
+/// List the entries of one category
+#[derive(Accounts)]
+pub struct QueryCategoryIndex<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+}
+
+/// Return the IDs of the entries filed under `category`, ascending
+///
+/// At most `MAX_CATEGORY_RESULTS` IDs are returned; clients needing every
+/// entry of a larger category read the index account directly.
+pub fn list_category_entries_handler(ctx: Context<QueryCategoryIndex>, category: u32) -> Result<Vec<u64>> {
+    let mut entry_ids = ctx.accounts.category_index.entries_in(category);
+    entry_ids.truncate(MAX_CATEGORY_RESULTS);
+    Ok(entry_ids)
+}
+
+/// Close the vault's category index
+#[derive(Accounts)]
+pub struct CloseCategoryIndex<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_category_index_handler(ctx: Context<CloseCategoryIndex>) -> Result<()> {
+    ctx.accounts.master_lockbox.has_category_index = false;
+    msg!("Category index closed - rent reclaimed");
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct CategoryIndexRebuiltEvent {
+    pub master_lockbox: Pubkey,
+    pub entries: u32,
+}
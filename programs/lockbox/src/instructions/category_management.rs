@@ -6,9 +6,9 @@ use crate::state::{MasterLockbox, CategoryRegistry, Category};
 pub struct InitializeCategoryRegistry<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -29,7 +29,7 @@ pub struct InitializeCategoryRegistry<'info> {
 
 pub fn initialize_category_registry_handler(ctx: Context<InitializeCategoryRegistry>) -> Result<()> {
     let category_registry = &mut ctx.accounts.category_registry;
-    let master_lockbox = &ctx.accounts.master_lockbox;
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
     // Verify subscription tier supports categories (Basic and above)
@@ -38,12 +38,14 @@ pub fn initialize_category_registry_handler(ctx: Context<InitializeCategoryRegis
         crate::errors::LockboxError::SubscriptionExpired // Could add FeatureNotAvailable error
     );
 
+    category_registry.version = CategoryRegistry::CURRENT_VERSION;
     category_registry.owner = master_lockbox.owner;
     category_registry.master_lockbox = master_lockbox.key();
     category_registry.categories = Vec::new();
     category_registry.next_category_id = 0;
     category_registry.created_at = current_timestamp;
     category_registry.bump = ctx.bumps.category_registry;
+    master_lockbox.has_category_registry = true;
 
     msg!("Category registry initialized");
 
@@ -55,9 +57,9 @@ pub fn initialize_category_registry_handler(ctx: Context<InitializeCategoryRegis
 pub struct CreateCategory<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -124,8 +126,9 @@ pub fn create_category_handler(
     // Add to registry
     category_registry.add_category(category)?;
 
-    // Update master lockbox
+    // Update master lockbox (flagging registries created before the flag)
     master_lockbox.categories_count += 1;
+    master_lockbox.has_category_registry = true;
     master_lockbox.touch(current_timestamp);
 
     msg!("Category {} created", category_id);
@@ -138,9 +141,9 @@ pub fn create_category_handler(
 pub struct UpdateCategory<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -214,9 +217,9 @@ pub fn update_category_handler(
 pub struct DeleteCategory<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -263,3 +266,56 @@ pub fn delete_category_handler(
 
     Ok(())
 }
+
+/// Emit per-category usage for a vault (permissionless, read-only)
+#[derive(Accounts)]
+pub struct EmitCategorySummary<'info> {
+    #[account(
+        seeds = [CategoryRegistry::SEEDS_PREFIX, category_registry.master_lockbox.as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Account<'info, CategoryRegistry>,
+}
+
+/// Emit a `CategorySummaryEvent` with entry and byte counts per category
+///
+/// Lets clients show "Work uses 40KB" without downloading every chunk.
+pub fn emit_category_summary_handler(ctx: Context<EmitCategorySummary>) -> Result<()> {
+    let registry = &ctx.accounts.category_registry;
+
+    let categories: Vec<CategoryUsage> = registry.categories
+        .iter()
+        .map(|c| CategoryUsage {
+            id: c.id,
+            entry_count: c.entry_count,
+            bytes_used: c.bytes_used,
+        })
+        .collect();
+
+    emit!(CategorySummaryEvent {
+        owner: registry.owner,
+        total_bytes: categories.iter().map(|c| c.bytes_used).sum(),
+        categories,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Usage counters for a single category
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CategoryUsage {
+    pub id: u8,
+    pub entry_count: u32,
+    pub bytes_used: u64,
+}
+
+#[event]
+pub struct CategorySummaryEvent {
+    pub owner: Pubkey,
+    pub total_bytes: u64,
+    pub categories: Vec<CategoryUsage>,
+}
@@ -1,10 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::state::{MasterLockbox, StorageChunk};
+use crate::state::{AliasTarget, MasterLockbox, StorageChunk, StorageType};
 use crate::errors::LockboxError;
 
 /// Maximum realloc increment per call (10KB)
 /// This prevents excessive single reallocations and manages rent requirements
-const MAX_REALLOC_INCREMENT: u32 = 10240;
+pub const MAX_REALLOC_INCREMENT: u32 = 10240;
 
 /// Expand an existing storage chunk
 ///
@@ -25,14 +25,37 @@ const MAX_REALLOC_INCREMENT: u32 = 10240;
 /// * `ReallocTooLarge` - Trying to expand by more than 10KB in one call
 /// * `ChunkNotFound` - Referenced chunk not found in master lockbox
 /// * `Unauthorized` - Caller doesn't own the lockbox
+/// * `WriterLeaseHeld` - Another device holds the writer lease
 pub fn expand_chunk_handler(
     ctx: Context<ExpandChunk>,
     additional_size: u32,
 ) -> Result<()> {
-    let chunk = &mut ctx.accounts.storage_chunk;
-    let master = &mut ctx.accounts.master_lockbox;
     let clock = Clock::get()?;
 
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    ctx.accounts.master_lockbox.check_writer(device.as_ref(), clock.unix_timestamp)?;
+
+    grow_chunk(
+        &mut ctx.accounts.storage_chunk,
+        &mut ctx.accounts.master_lockbox,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        additional_size,
+        clock.unix_timestamp,
+    )
+}
+
+/// Realloc a chunk by `additional_size` bytes, charging `payer` the rent
+///
+/// Shared by `expand_chunk` and the auto-expanding store path.
+pub(crate) fn grow_chunk<'info>(
+    chunk: &mut Account<'info, StorageChunk>,
+    master: &mut MasterLockbox,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    additional_size: u32,
+    current_timestamp: i64,
+) -> Result<()> {
     // Validate expansion doesn't exceed limits
     let new_capacity = chunk.max_capacity
         .checked_add(additional_size)
@@ -61,7 +84,7 @@ pub fn expand_chunk_handler(
     // Transfer additional rent from user if needed
     if additional_rent > 0 {
         let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-            ctx.accounts.payer.key,
+            payer.key,
             chunk.to_account_info().key,
             additional_rent,
         );
@@ -69,9 +92,9 @@ pub fn expand_chunk_handler(
         anchor_lang::solana_program::program::invoke(
             &transfer_ix,
             &[
-                ctx.accounts.payer.to_account_info(),
+                payer,
                 chunk.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
+                system_program,
             ],
         )?;
     }
@@ -79,7 +102,7 @@ pub fn expand_chunk_handler(
     // Perform reallocation
     chunk.to_account_info().realloc(new_len, false)?;
     chunk.max_capacity = new_capacity;
-    chunk.last_modified = clock.unix_timestamp;
+    chunk.last_modified = current_timestamp;
 
     // Update master lockbox tracking
     let chunk_info = master.storage_chunks
@@ -88,7 +111,8 @@ pub fn expand_chunk_handler(
         .ok_or(LockboxError::ChunkNotFound)?;
 
     chunk_info.max_capacity = new_capacity;
-    chunk_info.last_modified = clock.unix_timestamp;
+    chunk_info.last_modified = current_timestamp;
+    master.bump_chunk_layout(chunk)?;
 
     master.total_capacity = master.total_capacity
         .checked_add(additional_size as u64)
@@ -105,9 +129,9 @@ pub struct ExpandChunk<'info> {
     /// Master lockbox that owns the chunk
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        has_one = owner @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -134,4 +158,312 @@ pub struct ExpandChunk<'info> {
 
     /// System program for rent transfers
     pub system_program: Program<'info, System>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Shrink a chunk and refund the freed rent to the owner
+///
+/// Pairs with compaction: after entries are deleted, purged or moved to a
+/// cold chunk, unused capacity can be released. Lamports above the new
+/// rent-exempt minimum go back to the owner.
+///
+/// # Errors
+/// * `ShrinkBelowContents` - New capacity would not hold the chunk's data,
+///   or would drop below `MIN_CHUNK_SIZE`
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+pub fn shrink_chunk_handler(
+    ctx: Context<ShrinkChunk>,
+    _chunk_index: u16,
+    reduce_by: u32,
+) -> Result<()> {
+    let chunk = &mut ctx.accounts.storage_chunk;
+    let master = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    master.check_writer(device.as_ref(), current_timestamp)?;
+
+    let new_capacity = chunk.max_capacity
+        .checked_sub(reduce_by)
+        .filter(|c| *c >= StorageChunk::MIN_CHUNK_SIZE && *c >= chunk.current_size)
+        .ok_or(LockboxError::ShrinkBelowContents)?;
+
+    // Headers share the account with entry data, so check the serialized
+    // size rather than `current_size` alone
+    let old_len = chunk.to_account_info().data_len();
+    let new_len = old_len.saturating_sub(reduce_by as usize);
+    let serialized_len = 8 + chunk.try_to_vec()?.len();
+    require!(
+        reduce_by > 0 && new_len >= serialized_len,
+        LockboxError::ShrinkBelowContents
+    );
+
+    chunk.to_account_info().realloc(new_len, false)?;
+    chunk.max_capacity = new_capacity;
+    chunk.last_modified = current_timestamp;
+
+    // Refund everything above the new rent-exempt minimum
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(new_len);
+    let chunk_info = chunk.to_account_info();
+    let lamports_reclaimed = chunk_info.lamports().saturating_sub(rent_exempt_minimum);
+    if lamports_reclaimed > 0 {
+        **chunk_info.try_borrow_mut_lamports()? -= lamports_reclaimed;
+        **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += lamports_reclaimed;
+    }
+
+    let registered = master.storage_chunks
+        .iter_mut()
+        .find(|c| c.chunk_index == chunk.chunk_index)
+        .ok_or(LockboxError::ChunkNotFound)?;
+    registered.max_capacity = new_capacity;
+    registered.last_modified = current_timestamp;
+    master.bump_chunk_layout(chunk)?;
+    master.total_capacity = master.total_capacity.saturating_sub(reduce_by as u64);
+    master.touch(current_timestamp);
+
+    emit!(RentReclaimedEvent {
+        owner: ctx.accounts.owner.key(),
+        account: chunk.key(),
+        old_len: old_len as u32,
+        new_len: new_len as u32,
+        lamports_reclaimed,
+        timestamp: current_timestamp,
+    });
+
+    msg!("Shrunk chunk {} by {} bytes, reclaimed {} lamports",
+        chunk.chunk_index, reduce_by, lamports_reclaimed);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct ShrinkChunk<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::ChunkNotFound,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// Owner wallet (receives the reclaimed rent)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Move archived entries from a hot chunk into a cold archive chunk
+///
+/// Keeps hot chunks small and cheap to fetch. Entries keep their IDs;
+/// aliases in the two chunks involved are re-pointed at the new location.
+/// Moves at most `max_entries` per call and stops early when the cold chunk
+/// is full, so it can be cranked repeatedly.
+///
+/// # Errors
+/// * `InvalidChunkType` - Cold chunk is not `ArchivedPasswords`, or source is
+/// * `InvalidChunkIndex` - Source and cold chunk are the same
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+pub fn migrate_archived_entries_handler(
+    ctx: Context<MigrateArchivedEntries>,
+    _source_chunk_index: u16,
+    _cold_chunk_index: u16,
+    max_entries: u8,
+) -> Result<()> {
+    let master = &mut ctx.accounts.master_lockbox;
+    let source = &mut ctx.accounts.source_chunk;
+    let cold = &mut ctx.accounts.cold_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    master.check_writer(device.as_ref(), current_timestamp)?;
+
+    require!(
+        source.chunk_index != cold.chunk_index,
+        LockboxError::InvalidChunkIndex
+    );
+    require!(
+        cold.data_type == StorageType::ArchivedPasswords
+            && source.data_type != StorageType::ArchivedPasswords,
+        LockboxError::InvalidChunkType
+    );
+
+    let archived: Vec<u64> = source.entry_headers
+        .iter()
+        .filter(|h| h.is_archived() && !h.is_alias())
+        .map(|h| h.entry_id)
+        .take(max_entries as usize)
+        .collect();
+
+    let mut moved = 0u32;
+    for entry_id in archived {
+        let size = source.get_entry_header(entry_id)?.size;
+        if !cold.can_fit(size) || cold.entry_headers.len() >= 100 {
+            break;
+        }
+
+        let (mut header, data) = source.take_entry(entry_id, current_timestamp)?;
+        header.offset = cold.current_size;
+        cold.add_entry(header, data, current_timestamp)?;
+
+        let from = AliasTarget { chunk_index: source.chunk_index, entry_id };
+        let to = AliasTarget { chunk_index: cold.chunk_index, entry_id };
+        source.retarget_aliases(from, to, current_timestamp)?;
+        cold.retarget_aliases(from, to, current_timestamp)?;
+
+        moved += 1;
+    }
+
+    master.update_chunk_usage(source.chunk_index, source.current_size)?;
+    master.update_chunk_usage(cold.chunk_index, cold.current_size)?;
+    if moved > 0 {
+        master.bump_chunk_layout(source)?;
+        master.bump_chunk_layout(cold)?;
+    }
+    master.touch(current_timestamp);
+
+    msg!(
+        "Moved {} archived entries from chunk {} to cold chunk {}",
+        moved,
+        source.chunk_index,
+        cold.chunk_index
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(source_chunk_index: u16, cold_chunk_index: u16)]
+pub struct MigrateArchivedEntries<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Hot chunk holding archived entries
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &source_chunk_index.to_le_bytes()
+        ],
+        bump = source_chunk.bump,
+        constraint = source_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub source_chunk: Account<'info, StorageChunk>,
+
+    /// Cold chunk created with `StorageType::ArchivedPasswords`
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &cold_chunk_index.to_le_bytes()
+        ],
+        bump = cold_chunk.bump,
+        constraint = cold_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub cold_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Emit a health report for a chunk (permissionless, read-only)
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct EmitChunkReport<'info> {
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            storage_chunk.master_lockbox.as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+}
+
+/// Emit a `ChunkReportEvent` describing a chunk's space and rent status
+///
+/// Maintenance UIs use it to suggest compaction (high fragmentation or
+/// trashed bytes), expansion (little slack) or closing (empty chunk).
+pub fn emit_chunk_report_handler(ctx: Context<EmitChunkReport>, chunk_index: u16) -> Result<()> {
+    let chunk = &ctx.accounts.storage_chunk;
+    let info = chunk.to_account_info();
+    let health = chunk.health();
+
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(info.data_len());
+    let lamports = info.lamports();
+
+    emit!(ChunkReportEvent {
+        master_lockbox: chunk.master_lockbox,
+        chunk_index,
+        entry_count: chunk.entry_count,
+        max_capacity: chunk.max_capacity,
+        current_size: chunk.current_size,
+        slack_bytes: health.slack_bytes,
+        largest_free_block: health.largest_free_block,
+        fragmentation_bps: health.fragmentation_bps,
+        trashed_bytes: health.trashed_bytes,
+        lamports,
+        rent_exempt_minimum,
+        rent_exempt: lamports >= rent_exempt_minimum,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Rent released by shrinking an account; sum these to track rent saved
+#[event]
+pub struct RentReclaimedEvent {
+    pub owner: Pubkey,
+    pub account: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub lamports_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChunkReportEvent {
+    pub master_lockbox: Pubkey,
+    pub chunk_index: u16,
+    pub entry_count: u16,
+    pub max_capacity: u32,
+    pub current_size: u32,
+    pub slack_bytes: u32,
+    pub largest_free_block: u32,
+    pub fragmentation_bps: u16,
+    pub trashed_bytes: u32,
+    pub lamports: u64,
+    pub rent_exempt_minimum: u64,
+    pub rent_exempt: bool,
 }
@@ -0,0 +1,125 @@
+/**
+ * Multi-Chunk Access
+ *
+ * Anchor contexts name a fixed set of accounts, so instructions that touch
+ * a variable number of chunks (alias cascades, moves, batch operations)
+ * take the extra chunks as writable remaining accounts. `ChunkSet` loads
+ * and validates them once and writes them back when the handler is done.
+ *
+ * Security:
+ * - Every chunk must be owned by this program and writable (unless loaded
+ *   read-only for a view)
+ * - Every chunk must be registered in the master lockbox's chunk list
+ *   under the same address, index and layout epoch
+ * - The context's own chunk(s) and duplicates are rejected, so no chunk is
+ *   deserialized twice and written back over itself
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{MasterLockbox, StorageChunk};
+
+/// Storage chunks loaded from remaining accounts
+pub struct ChunkSet<'a, 'info> {
+    infos: Vec<&'a AccountInfo<'info>>,
+    chunks: Vec<StorageChunk>,
+}
+
+impl<'a, 'info> ChunkSet<'a, 'info> {
+    /// Load and validate `accounts` as chunks of `master_lockbox`
+    ///
+    /// `exclude` lists chunk addresses already present in the context.
+    ///
+    /// # Errors
+    /// * `InvalidChunkAccount` - Account is not a writable, registered chunk
+    ///   of this lockbox, is excluded, or appears twice
+    /// * `StaleChunkLayout` - Chunk and its registration disagree on the
+    ///   layout epoch
+    pub fn load(
+        accounts: &'a [AccountInfo<'info>],
+        master_lockbox: &Account<MasterLockbox>,
+        exclude: &[Pubkey],
+    ) -> Result<Self> {
+        Self::load_checked(accounts, master_lockbox, exclude, true)
+    }
+
+    /// Load and validate `accounts` for reading only
+    ///
+    /// Same checks as [`ChunkSet::load`] except that chunks may be passed
+    /// read-only; the set must not be committed.
+    pub fn load_read_only(
+        accounts: &'a [AccountInfo<'info>],
+        master_lockbox: &Account<MasterLockbox>,
+    ) -> Result<Self> {
+        Self::load_checked(accounts, master_lockbox, &[], false)
+    }
+
+    fn load_checked(
+        accounts: &'a [AccountInfo<'info>],
+        master_lockbox: &Account<MasterLockbox>,
+        exclude: &[Pubkey],
+        writable: bool,
+    ) -> Result<Self> {
+        let mut infos: Vec<&'a AccountInfo<'info>> = Vec::with_capacity(accounts.len());
+        let mut chunks = Vec::with_capacity(accounts.len());
+
+        for info in accounts {
+            require!(
+                info.owner == &crate::ID
+                    && (info.is_writable || !writable)
+                    && !exclude.contains(info.key)
+                    && !infos.iter().any(|seen| seen.key == info.key),
+                LockboxError::InvalidChunkAccount
+            );
+
+            let chunk = StorageChunk::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            let registered = master_lockbox.storage_chunks.iter().any(|c| {
+                c.chunk_address == info.key() && c.chunk_index == chunk.chunk_index
+            });
+            require!(
+                chunk.master_lockbox == master_lockbox.key() && registered,
+                LockboxError::InvalidChunkAccount
+            );
+            master_lockbox.check_chunk_layout(&chunk)?;
+
+            infos.push(info);
+            chunks.push(chunk);
+        }
+
+        Ok(Self { infos, chunks })
+    }
+
+    /// Number of loaded chunks
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Whether no chunks were passed
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Chunk with the given index, if it was passed
+    pub fn get_mut(&mut self, chunk_index: u16) -> Option<&mut StorageChunk> {
+        self.chunks.iter_mut().find(|chunk| chunk.chunk_index == chunk_index)
+    }
+
+    /// All loaded chunks, in the order passed
+    pub fn chunks(&self) -> &[StorageChunk] {
+        &self.chunks
+    }
+
+    /// All loaded chunks
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, StorageChunk> {
+        self.chunks.iter_mut()
+    }
+
+    /// Write every chunk back and sync its usage into the master lockbox
+    pub fn commit(self, master_lockbox: &mut MasterLockbox) -> Result<()> {
+        for (info, chunk) in self.infos.into_iter().zip(self.chunks) {
+            master_lockbox.update_chunk_usage(chunk.chunk_index, chunk.current_size)?;
+            chunk.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+/**
+ * Client Entry IDs
+ *
+ * Sync engines store entries under stable IDs they generate themselves
+ * (high bit set, see `MasterLockbox::is_client_entry_id`). A chunk rejects
+ * an ID it already holds, but the category index, title lookup, entry
+ * links, moves and trash restores all key entries by ID alone, so an ID
+ * must be unique across the whole vault.
+ *
+ * The vault's `ClientEntryIds` account records every client ID in use.
+ * `store_password_entry` requires it whenever a `client_entry_id` is
+ * passed and reserves the ID there, growing the account as needed with the
+ * owner paying the rent. `initialize_client_entry_ids` seeds it from the
+ * client IDs already stored in the vault's chunks.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::errors::LockboxError;
+use crate::instructions::chunk_set::ChunkSet;
+use crate::state::{ClientEntryIds, MasterLockbox, StorageChunk, MAX_CLIENT_ENTRY_IDS};
+
+/// Grow the account to fit its IDs, topping up rent from `payer`
+///
+/// # Errors
+/// * `ClientEntryIdsAccountsMissing` - Growth is needed but no system
+///   program was passed
+pub fn fit_client_entry_ids<'info>(
+    client_entry_ids: &Account<'info, ClientEntryIds>,
+    payer: AccountInfo<'info>,
+    system_program: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let needed = ClientEntryIds::space_for(client_entry_ids.ids.len());
+    let info = client_entry_ids.to_account_info();
+    if needed <= info.data_len() {
+        return Ok(());
+    }
+    let system_program = system_program.ok_or(LockboxError::ClientEntryIdsAccountsMissing)?;
+
+    let additional_rent = Rent::get()?
+        .minimum_balance(needed)
+        .saturating_sub(info.lamports());
+    if additional_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program, Transfer { from: payer, to: info.clone() }),
+            additional_rent,
+        )?;
+    }
+    info.realloc(needed, false)?;
+    Ok(())
+}
+
+/// Client IDs of the entries stored in `chunks`, ascending
+///
+/// Continuation segments share their entry's ID and are skipped.
+///
+/// # Errors
+/// * `DuplicateEntryId` - Two chunks hold entries with the same client ID
+/// * `ClientEntryIdsFull` - More than `MAX_CLIENT_ENTRY_IDS` client IDs
+pub fn client_entry_ids_in(chunks: &[StorageChunk]) -> Result<Vec<u64>> {
+    let mut ids: Vec<u64> = chunks
+        .iter()
+        .flat_map(|chunk| &chunk.entry_headers)
+        .filter(|header| !header.is_continuation())
+        .map(|header| header.entry_id)
+        .filter(|id| MasterLockbox::is_client_entry_id(*id))
+        .collect();
+    ids.sort_unstable();
+
+    require!(
+        ids.windows(2).all(|pair| pair[0] != pair[1]),
+        LockboxError::DuplicateEntryId
+    );
+    require!(ids.len() <= MAX_CLIENT_ENTRY_IDS, LockboxError::ClientEntryIdsFull);
+    Ok(ids)
+}
+
+/// Create the vault's client entry ID account
+#[derive(Accounts)]
+pub struct InitializeClientEntryIds<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ClientEntryIds::space_for(0),
+        seeds = [ClientEntryIds::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub client_entry_ids: Account<'info, ClientEntryIds>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the account, reserving the client IDs already in the vault
+///
+/// Every registered chunk must be passed as a read-only remaining account.
+///
+/// # Errors
+/// * `ClientEntryIdsChunksMissing` - Not every registered chunk was passed
+/// * `InvalidChunkAccount` - A remaining account is not a registered chunk
+///   of this lockbox, or appears twice
+/// * `DuplicateEntryId` - The vault already holds a client ID twice
+pub fn initialize_client_entry_ids_handler(ctx: Context<InitializeClientEntryIds>) -> Result<()> {
+    let chunks = ChunkSet::load_read_only(ctx.remaining_accounts, &ctx.accounts.master_lockbox)?;
+    require!(
+        chunks.len() == ctx.accounts.master_lockbox.storage_chunks.len(),
+        LockboxError::ClientEntryIdsChunksMissing
+    );
+
+    let client_entry_ids = &mut ctx.accounts.client_entry_ids;
+    client_entry_ids.version = ClientEntryIds::CURRENT_VERSION;
+    client_entry_ids.master_lockbox = ctx.accounts.master_lockbox.key();
+    client_entry_ids.ids = client_entry_ids_in(chunks.chunks())?;
+    client_entry_ids.bump = ctx.bumps.client_entry_ids;
+    fit_client_entry_ids(
+        client_entry_ids,
+        ctx.accounts.owner.to_account_info(),
+        Some(ctx.accounts.system_program.to_account_info()),
+    )?;
+
+    msg!("Client entry IDs initialized with {} IDs", client_entry_ids.ids.len());
+    Ok(())
+}
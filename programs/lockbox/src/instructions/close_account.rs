@@ -4,65 +4,224 @@
  * Provides functionality for permanently closing Master Lockbox accounts
  * and reclaiming rent. This is a destructive operation that cannot be undone.
  *
+ * Deleting a vault takes two steps: `request_vault_deletion` sets a
+ * tombstone, and only after `VAULT_DELETION_DELAY` may non-empty chunks be
+ * destroyed and `finalize_vault_deletion` close the master lockbox. The
+ * owner can cancel in between, so one phished or mistaken signature cannot
+ * wipe the vault.
+ *
  * Security:
  * - Only the account owner can close their account
  * - Rent is returned to the owner's wallet
  * - All data is permanently deleted
+ * - The master lockbox closes last: chunks, the category registry, the
+ *   security policy, the deny-list, recovery configs and emergency access must be closed
+ *   first so no account is stranded without its master
  */
 
 use anchor_lang::prelude::*;
 use crate::state::master_lockbox::MasterLockbox;
 use crate::state::storage_chunk::StorageChunk;
+use crate::state::{
+    CategoryIndex, CategoryRegistry, ContactBook, DenyList, EmergencyAccess, RecoveryConfig, RecoveryConfigV2,
+    SearchFilter, SecurityPolicy, TagRegistry, VAULT_DELETION_DELAY,
+};
 use crate::errors::LockboxError;
 
 /**
- * Close Master Lockbox Account
+ * Request Vault Deletion
+ *
+ * Sets the deletion tombstone. Nothing is deleted yet; the owner has
+ * `VAULT_DELETION_DELAY` to notice and cancel.
+ *
+ * # Returns
+ * - `Ok(())` on success
+ * - `Err(LockboxError::VaultDeletionAlreadyRequested)` if a tombstone is set
+ */
+pub fn request_vault_deletion_handler(ctx: Context<VaultDeletion>) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        !master_lockbox.is_deletion_requested(),
+        LockboxError::VaultDeletionAlreadyRequested
+    );
+
+    master_lockbox.deletion_requested_at = current_timestamp;
+    master_lockbox.touch(current_timestamp);
+
+    emit!(VaultDeletionRequestedEvent {
+        owner: master_lockbox.owner,
+        requested_at: current_timestamp,
+        finalize_after: current_timestamp + VAULT_DELETION_DELAY,
+    });
+
+    msg!("Vault deletion requested - final after {}", current_timestamp + VAULT_DELETION_DELAY);
+    Ok(())
+}
+
+/**
+ * Cancel Vault Deletion
+ *
+ * Clears the deletion tombstone.
+ *
+ * # Returns
+ * - `Ok(())` on success
+ * - `Err(LockboxError::VaultDeletionNotRequested)` if no tombstone is set
+ */
+pub fn cancel_vault_deletion_handler(ctx: Context<VaultDeletion>) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        master_lockbox.is_deletion_requested(),
+        LockboxError::VaultDeletionNotRequested
+    );
+
+    master_lockbox.deletion_requested_at = 0;
+    master_lockbox.touch(current_timestamp);
+
+    emit!(VaultDeletionCanceledEvent {
+        owner: master_lockbox.owner,
+        canceled_at: current_timestamp,
+    });
+
+    msg!("Vault deletion canceled");
+    Ok(())
+}
+
+/**
+ * Account validation for request_vault_deletion and cancel_vault_deletion
+ */
+#[derive(Accounts)]
+pub struct VaultDeletion<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+/**
+ * Finalize Vault Deletion
  *
  * Permanently closes the user's Master Lockbox account and returns
  * all rent to the owner's wallet. This is irreversible.
  *
  * # Security Checks
  * - Verifies the signer is the account owner
+ * - Verifies the deletion tombstone has waited out `VAULT_DELETION_DELAY`
+ * - Verifies every auxiliary account (see `auxiliary_accounts`) is passed in
+ *   remaining accounts and already closed
+ * - Verifies no shared vault created by this vault still holds entries
  * - Transfers all lamports (rent) back to owner
  * - Closes the account (marks for garbage collection)
  *
  * # Returns
  * - `Ok(())` on successful closure
  * - `Err(LockboxError::Unauthorized)` if signer is not owner
+ * - `Err(LockboxError::VaultDeletionPending)` if deletion was not requested
+ *   or the delay has not passed
+ * - `Err(LockboxError::SharedStorageInUse)` if shared vaults still hold entries
+ * - `Err(LockboxError::AuxiliaryAccountMissing)` if an auxiliary account was not passed
+ * - `Err(LockboxError::AuxiliaryAccountOpen)` if an auxiliary account still exists
  */
-pub fn close_master_lockbox_handler(ctx: Context<CloseMasterLockbox>) -> Result<()> {
+pub fn finalize_vault_deletion_handler(ctx: Context<FinalizeVaultDeletion>) -> Result<()> {
     // Verify ownership (already enforced by constraint, but explicit check for clarity)
     let master = &ctx.accounts.master_lockbox;
     require!(
         master.owner == ctx.accounts.owner.key(),
         LockboxError::Unauthorized
     );
+    require!(
+        master.is_deletion_ready(Clock::get()?.unix_timestamp),
+        LockboxError::VaultDeletionPending
+    );
+    require!(master.shared_storage_used == 0, LockboxError::SharedStorageInUse);
+
+    let expected = auxiliary_accounts(master, ctx.accounts.owner.key);
+    ensure_accounts_closed(&expected, ctx.remaining_accounts)?;
 
     msg!("Master Lockbox closed successfully - rent reclaimed");
     Ok(())
 }
 
 /**
- * Account validation for close_master_lockbox instruction
+ * Accounts that belong to a master lockbox and must be closed before it
+ *
+ * Registered storage chunks, the category registry, the security policy,
+ * the contact book, the deny-list, the tag registry, the search filter, the category index, both recovery configs and the emergency access config. Entry notes are
+ * keyed by entry ID and cannot be enumerated here; close them together
+ * with their entries.
+ */
+pub fn auxiliary_accounts(master_lockbox: &Account<MasterLockbox>, owner: &Pubkey) -> Vec<Pubkey> {
+    let master = master_lockbox.key();
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+
+    let mut accounts: Vec<Pubkey> = master_lockbox
+        .storage_chunks
+        .iter()
+        .map(|chunk| chunk.chunk_address)
+        .collect();
+    accounts.extend([
+        pda(&[CategoryRegistry::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[SecurityPolicy::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[ContactBook::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[DenyList::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[TagRegistry::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[SearchFilter::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[CategoryIndex::SEEDS_PREFIX, master.as_ref()]),
+        pda(&[RecoveryConfig::SEEDS_PREFIX, owner.as_ref()]),
+        pda(&[RecoveryConfigV2::SEEDS_PREFIX, owner.as_ref()]),
+        pda(&[EmergencyAccess::SEEDS_PREFIX, owner.as_ref()]),
+    ]);
+    accounts
+}
+
+/**
+ * Check that every expected account is present and no longer a live
+ * program account
+ */
+pub fn ensure_accounts_closed(expected: &[Pubkey], accounts: &[AccountInfo]) -> Result<()> {
+    for address in expected {
+        let info = accounts
+            .iter()
+            .find(|info| info.key == address)
+            .ok_or(LockboxError::AuxiliaryAccountMissing)?;
+        require!(
+            info.owner != &crate::ID || info.lamports() == 0,
+            LockboxError::AuxiliaryAccountOpen
+        );
+    }
+    Ok(())
+}
+
+/**
+ * Account validation for finalize_vault_deletion instruction
  *
  * Uses Anchor's `close` constraint to automatically transfer rent
  * and mark the account for garbage collection.
  */
 #[derive(Accounts)]
-pub struct CloseMasterLockbox<'info> {
+pub struct FinalizeVaultDeletion<'info> {
     /// The Master Lockbox PDA to close
     /// Rent will be returned to the owner
     #[account(
         mut,
         close = owner,
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
     /// The owner/signer who is closing the account
-    /// Must be the original creator of the Master Lockbox
+    /// Must be the current owner of the Master Lockbox
     /// Receives all rent lamports
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -73,23 +232,95 @@ pub struct CloseMasterLockbox<'info> {
  *
  * Closes an individual storage chunk and returns rent to the owner.
  * Chunks should be closed before closing the Master Lockbox for maximum
- * rent recovery.
+ * rent recovery. The chunk is removed from the master lockbox's chunk list
+ * and its entries, bytes and capacity are released from the lockbox totals.
+ * Closing the highest-indexed chunk makes its index the next to allocate.
  *
  * # Arguments
  * - `chunk_index`: Index of the chunk to close
+ * - `allow_non_empty`: Confirms that entries still in the chunk may be destroyed
+ *
+ * Destroyed entries are released from the category registry and index like
+ * deleted ones; once the vault has either, it must be passed.
  *
  * # Returns
  * - `Ok(())` on successful closure
  * - `Err(LockboxError::Unauthorized)` if signer is not owner
+ * - `Err(LockboxError::ChunkNotEmpty)` if the chunk holds entries and
+ *   `allow_non_empty` is false
+ * - `Err(LockboxError::VaultDeletionPending)` if the chunk holds entries and
+ *   vault deletion is not ready to finalize
+ * - `Err(LockboxError::WriterLeaseHeld)` if another device holds the writer lease
  */
 pub fn close_storage_chunk_handler(
-    _ctx: Context<CloseStorageChunk>,
-    _chunk_index: u16,
+    ctx: Context<CloseStorageChunk>,
+    chunk_index: u16,
+    allow_non_empty: bool,
 ) -> Result<()> {
-    msg!("Storage chunk closed successfully - rent reclaimed");
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &ctx.accounts.storage_chunk;
+
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    master_lockbox.check_writer(device.as_ref(), current_timestamp)?;
+
+    require!(
+        storage_chunk.entry_count == 0 || allow_non_empty,
+        LockboxError::ChunkNotEmpty
+    );
+    // Destroying entries waits out the vault deletion delay
+    require!(
+        storage_chunk.entry_count == 0 || master_lockbox.is_deletion_ready(current_timestamp),
+        LockboxError::VaultDeletionPending
+    );
+
+    release_chunk_entries(
+        master_lockbox,
+        storage_chunk,
+        ctx.accounts.category_registry.as_deref_mut(),
+        ctx.accounts.category_index.as_deref_mut(),
+    );
+    master_lockbox.remove_chunk(chunk_index)?;
+    master_lockbox.touch(current_timestamp);
+
+    msg!(
+        "Storage chunk {} closed ({} entries destroyed) - rent reclaimed",
+        chunk_index,
+        storage_chunk.entry_count
+    );
     Ok(())
 }
 
+/// Release the entries of a chunk about to be destroyed from the vault's
+/// counters, as deleting each of them would
+///
+/// Continuation segments are not entries but their bytes count toward
+/// their category; aliases are not in the registry or index.
+pub fn release_chunk_entries(
+    master_lockbox: &mut MasterLockbox,
+    storage_chunk: &StorageChunk,
+    mut category_registry: Option<&mut CategoryRegistry>,
+    mut category_index: Option<&mut CategoryIndex>,
+) {
+    for header in &storage_chunk.entry_headers {
+        let is_entry = !header.is_continuation();
+        if is_entry {
+            master_lockbox.forget_recent_entry(header.entry_id);
+            master_lockbox.decrement_entries();
+        }
+        if header.is_alias() {
+            continue;
+        }
+        if let Some(registry) = category_registry.as_deref_mut() {
+            registry.record_usage(header.category, -(is_entry as i32), -(header.size as i64));
+        }
+        if let Some(index) = category_index.as_deref_mut().filter(|_| is_entry) {
+            index.remove(header.category, header.entry_id);
+        }
+    }
+}
+
 /**
  * Account validation for close_storage_chunk instruction
  */
@@ -101,7 +332,7 @@ pub struct CloseStorageChunk<'info> {
         mut,
         close = owner,
         seeds = [
-            b"storage_chunk",
+            StorageChunk::SEEDS_PREFIX,
             master_lockbox.key().as_ref(),
             &chunk_index.to_le_bytes()
         ],
@@ -110,10 +341,20 @@ pub struct CloseStorageChunk<'info> {
     pub storage_chunk: Account<'info, StorageChunk>,
 
     /// The Master Lockbox (for ownership verification)
+    /// Shrinks by one chunk entry as the chunk is unregistered
     #[account(
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ LockboxError::CategoryIndexRequired,
+        realloc = master_lockbox.space_for_chunks(master_lockbox.storage_chunks.len().saturating_sub(1)),
+        realloc::payer = owner,
+        realloc::zero = false,
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -121,6 +362,29 @@ pub struct CloseStorageChunk<'info> {
     /// Receives all rent lamports
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    /// Releases destroyed entries' category counts; required once the vault
+    /// has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Removes destroyed entries from the category index; required once the
+    /// vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
 }
 
 /**
@@ -134,6 +398,8 @@ pub struct CloseStorageChunk<'info> {
  * - Uses AccountInfo instead of Account<StorageChunk> to bypass discriminator validation
  * - Still validates PDA derivation and ownership
  * - Can only be called by the master lockbox owner
+ * - Data is zeroed and the account is handed back to the system program,
+ *   so the shell cannot be revived by refunding it within the transaction
  *
  * # Arguments
  * - `chunk_index`: Index of the chunk to force close
@@ -145,15 +411,22 @@ pub fn force_close_orphaned_chunk_handler(
     ctx: Context<ForceCloseOrphanedChunk>,
     _chunk_index: u16,
 ) -> Result<()> {
-    // Transfer all lamports from chunk to owner
     let chunk_account = &ctx.accounts.storage_chunk;
     let owner_account = &ctx.accounts.owner;
 
+    // Wipe the contents before giving up ownership
+    chunk_account.try_borrow_mut_data()?.fill(0);
+
+    // Transfer all lamports from chunk to owner
     let rent_lamports = chunk_account.lamports();
 
     **chunk_account.try_borrow_mut_lamports()? -= rent_lamports;
     **owner_account.try_borrow_mut_lamports()? += rent_lamports;
 
+    // Same teardown as Anchor's `close`: system-owned and empty
+    chunk_account.assign(&anchor_lang::system_program::ID);
+    chunk_account.realloc(0, false)?;
+
     msg!("Orphaned storage chunk force-closed - {} lamports reclaimed", rent_lamports);
     Ok(())
 }
@@ -168,11 +441,12 @@ pub fn force_close_orphaned_chunk_handler(
 #[instruction(chunk_index: u16)]
 pub struct ForceCloseOrphanedChunk<'info> {
     /// The Storage Chunk PDA to force close (uses AccountInfo to bypass validation)
-    /// CHECK: PDA derivation is validated, but account structure is not
+    /// CHECK: PDA derivation and program ownership are validated, but account structure is not
     #[account(
         mut,
+        owner = crate::ID,
         seeds = [
-            b"storage_chunk",
+            StorageChunk::SEEDS_PREFIX,
             master_lockbox.key().as_ref(),
             &chunk_index.to_le_bytes()
         ],
@@ -182,9 +456,9 @@ pub struct ForceCloseOrphanedChunk<'info> {
 
     /// The Master Lockbox (for ownership verification)
     #[account(
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -193,3 +467,146 @@ pub struct ForceCloseOrphanedChunk<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 }
+
+/**
+ * Detect Orphaned Storage Chunks
+ *
+ * Compares candidate accounts (passed as remaining accounts) against the
+ * master lockbox's chunk list and emits one `OrphanDetectedEvent` per
+ * discrepancy. Read-only and permissionless; pair with
+ * `force_close_orphaned_chunk` to clean up what it finds.
+ *
+ * # Returns
+ * - `Ok(())` always; discrepancies are reported through events
+ */
+pub fn detect_orphans_handler(ctx: Context<DetectOrphans>) -> Result<()> {
+    let master_lockbox = &ctx.accounts.master_lockbox;
+    let orphans = find_orphans(master_lockbox, &master_lockbox.key(), ctx.remaining_accounts);
+
+    for orphan in &orphans {
+        emit!(OrphanDetectedEvent {
+            master_lockbox: master_lockbox.key(),
+            account: orphan.account,
+            chunk_index: orphan.chunk_index,
+            kind: orphan.kind,
+        });
+    }
+
+    msg!(
+        "Orphan sweep: {} candidates, {} discrepancies",
+        ctx.remaining_accounts.len(),
+        orphans.len()
+    );
+    Ok(())
+}
+
+/// A chunk account that disagrees with the master lockbox's chunk list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrphanReport {
+    pub account: Pubkey,
+    pub chunk_index: u16,
+    pub kind: OrphanKind,
+}
+
+/**
+ * Classify candidate accounts against the chunk list
+ *
+ * A live candidate is reported as `UnregisteredChunk` only if it decodes as
+ * a chunk of this master lockbox at its canonical PDA; undecodable or
+ * foreign accounts are ignored. A registered chunk address is reported as
+ * `MissingChunk` if the candidate passed for it holds no program account.
+ */
+pub fn find_orphans(
+    master_lockbox: &MasterLockbox,
+    master_key: &Pubkey,
+    candidates: &[AccountInfo],
+) -> Vec<OrphanReport> {
+    let mut orphans = Vec::new();
+
+    for info in candidates {
+        let registered = master_lockbox
+            .storage_chunks
+            .iter()
+            .find(|chunk| chunk.chunk_address == info.key());
+        let is_live = info.owner == &crate::ID && info.lamports() > 0 && !info.data_is_empty();
+
+        match (registered, is_live) {
+            (Some(chunk), false) => orphans.push(OrphanReport {
+                account: info.key(),
+                chunk_index: chunk.chunk_index,
+                kind: OrphanKind::MissingChunk,
+            }),
+            (None, true) => {
+                let Ok(data) = info.try_borrow_data() else { continue };
+                let Ok(chunk) = StorageChunk::try_deserialize(&mut &data[..]) else { continue };
+                let canonical = Pubkey::create_program_address(
+                    &[
+                        StorageChunk::SEEDS_PREFIX,
+                        master_key.as_ref(),
+                        &chunk.chunk_index.to_le_bytes(),
+                        &[chunk.bump],
+                    ],
+                    &crate::ID,
+                );
+                if chunk.master_lockbox == *master_key && canonical == Ok(info.key()) {
+                    orphans.push(OrphanReport {
+                        account: info.key(),
+                        chunk_index: chunk.chunk_index,
+                        kind: OrphanKind::UnregisteredChunk,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    orphans
+}
+
+/**
+ * Account validation for detect_orphans instruction
+ */
+#[derive(Accounts)]
+pub struct DetectOrphans<'info> {
+    /// The Master Lockbox whose chunk list is checked
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Kind of chunk list discrepancy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrphanKind {
+    /// Chunk account exists but is not in `storage_chunks`
+    UnregisteredChunk,
+
+    /// `storage_chunks` lists an address with no chunk account
+    MissingChunk,
+}
+
+#[event]
+pub struct OrphanDetectedEvent {
+    pub master_lockbox: Pubkey,
+    pub account: Pubkey,
+    pub chunk_index: u16,
+    pub kind: OrphanKind,
+}
+
+#[event]
+pub struct VaultDeletionRequestedEvent {
+    pub owner: Pubkey,
+    pub requested_at: i64,
+    pub finalize_after: i64,
+}
+
+#[event]
+pub struct VaultDeletionCanceledEvent {
+    pub owner: Pubkey,
+    pub canceled_at: i64,
+}
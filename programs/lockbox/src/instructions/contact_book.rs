@@ -0,0 +1,210 @@
+/**
+ * Contact Book Instructions
+ *
+ * CRUD over the owner's `ContactBook` so sharing and one-time-secret flows
+ * can offer saved recipients instead of asking for a key every time. The
+ * book is resized by one `Contact` per add/remove; the owner pays (and is
+ * refunded) the difference in rent.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{Contact, ContactBook, DenyList, MasterLockbox};
+use crate::errors::LockboxError;
+use super::deny_list::check_not_denied;
+use super::password_entry::MIN_AEAD_SIZE;
+
+/// Create an empty contact book
+#[derive(Accounts)]
+pub struct InitializeContactBook<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ContactBook::space_for(0),
+        seeds = [ContactBook::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub contact_book: Account<'info, ContactBook>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_contact_book_handler(ctx: Context<InitializeContactBook>) -> Result<()> {
+    let contact_book = &mut ctx.accounts.contact_book;
+    contact_book.version = ContactBook::CURRENT_VERSION;
+    contact_book.owner = ctx.accounts.owner.key();
+    contact_book.master_lockbox = ctx.accounts.master_lockbox.key();
+    contact_book.contacts = Vec::new();
+    contact_book.created_at = Clock::get()?.unix_timestamp;
+    contact_book.bump = ctx.bumps.contact_book;
+
+    msg!("Contact book initialized");
+    Ok(())
+}
+
+/// Add a contact (grows the book by one entry)
+#[derive(Accounts)]
+pub struct AddContact<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [ContactBook::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = contact_book.bump,
+        realloc = ContactBook::space_for(contact_book.contacts.len() + 1),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub contact_book: Account<'info, ContactBook>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
+}
+
+/// Add `contact` under an encrypted label
+///
+/// # Errors
+/// * `InvalidDataSize` - Label is not a valid AEAD payload or exceeds 64 bytes
+/// * `ContactBookFull` - The tier's contact limit is reached
+/// * `ContactBookEntryExists` - The key is already in the book
+/// * `KeyDenied` - The key is on the vault's deny-list
+pub fn add_contact_handler(
+    ctx: Context<AddContact>,
+    contact: Pubkey,
+    label_encrypted: Vec<u8>,
+) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    require!(label_encrypted.len() >= MIN_AEAD_SIZE, LockboxError::InvalidDataSize);
+    check_not_denied(&ctx.accounts.deny_list, &contact)?;
+
+    let max_contacts = ctx
+        .accounts
+        .master_lockbox
+        .effective_tier(current_timestamp)
+        .max_contacts();
+    ctx.accounts
+        .contact_book
+        .add_contact(contact, label_encrypted, max_contacts, current_timestamp)?;
+
+    msg!("Contact {} added", contact);
+    Ok(())
+}
+
+/// Relabel a contact
+#[derive(Accounts)]
+pub struct UpdateContact<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [ContactBook::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = contact_book.bump
+    )]
+    pub contact_book: Account<'info, ContactBook>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn update_contact_handler(
+    ctx: Context<UpdateContact>,
+    contact: Pubkey,
+    label_encrypted: Vec<u8>,
+) -> Result<()> {
+    require!(
+        (MIN_AEAD_SIZE..=Contact::MAX_LABEL_SIZE).contains(&label_encrypted.len()),
+        LockboxError::InvalidDataSize
+    );
+
+    let entry = ctx.accounts.contact_book.get_contact_mut(&contact)?;
+    entry.label_encrypted = label_encrypted;
+    entry.last_modified = Clock::get()?.unix_timestamp;
+
+    msg!("Contact {} updated", contact);
+    Ok(())
+}
+
+/// Remove a contact (shrinks the book by one entry)
+#[derive(Accounts)]
+pub struct RemoveContact<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [ContactBook::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = contact_book.bump,
+        realloc = ContactBook::space_for(contact_book.contacts.len().saturating_sub(1)),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub contact_book: Account<'info, ContactBook>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn remove_contact_handler(ctx: Context<RemoveContact>, contact: Pubkey) -> Result<()> {
+    ctx.accounts.contact_book.remove_contact(&contact)?;
+
+    msg!("Contact {} removed", contact);
+    Ok(())
+}
+
+/// Close the contact book and reclaim its rent
+#[derive(Accounts)]
+pub struct CloseContactBook<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [ContactBook::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = contact_book.bump
+    )]
+    pub contact_book: Account<'info, ContactBook>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_contact_book_handler(_ctx: Context<CloseContactBook>) -> Result<()> {
+    msg!("Contact book closed - rent reclaimed");
+    Ok(())
+}
@@ -0,0 +1,155 @@
+/**
+ * Deny-List Instructions
+ *
+ * The owner keeps a `DenyList` of keys reported stolen or otherwise
+ * untrusted. `deny_key` creates the list on first use; every instruction
+ * that hands a key access to the vault (guardians, emergency contacts,
+ * contact book recipients, entry copies) rejects keys on it with
+ * `KeyDenied`.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{DenyList, MasterLockbox};
+use crate::errors::LockboxError;
+
+/// Reject `key` if it is on the vault's deny-list
+///
+/// `deny_list` is the vault's `DenyList` PDA, which may not exist.
+pub fn check_not_denied(deny_list: &AccountInfo, key: &Pubkey) -> Result<()> {
+    if let Some(list) = DenyList::load(deny_list)? {
+        require!(!list.is_denied(key), LockboxError::KeyDenied);
+    }
+    Ok(())
+}
+
+/// Add a key to the deny-list, creating it if needed
+#[derive(Accounts)]
+pub struct DenyKey<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + DenyList::INIT_SPACE,
+        seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub deny_list: Account<'info, DenyList>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Deny `key`
+///
+/// # Errors
+/// * `KeyAlreadyDenied` - Key is already on the list
+/// * `DenyListFull` - `MAX_DENIED_KEYS` keys are already denied
+pub fn deny_key_handler(ctx: Context<DenyKey>, key: Pubkey) -> Result<()> {
+    let deny_list = &mut ctx.accounts.deny_list;
+    if deny_list.version == 0 {
+        deny_list.version = DenyList::CURRENT_VERSION;
+        deny_list.master_lockbox = ctx.accounts.master_lockbox.key();
+        deny_list.bump = ctx.bumps.deny_list;
+    }
+    deny_list.deny(key)?;
+
+    emit!(KeyDeniedEvent {
+        master_lockbox: deny_list.master_lockbox,
+        key,
+        denied_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Key {} denied", key);
+    Ok(())
+}
+
+/// Remove a key from the deny-list
+#[derive(Accounts)]
+pub struct AllowKey<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = deny_list.bump
+    )]
+    pub deny_list: Account<'info, DenyList>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Allow `key` again
+///
+/// # Errors
+/// * `KeyNotDenied` - Key is not on the list
+pub fn allow_key_handler(ctx: Context<AllowKey>, key: Pubkey) -> Result<()> {
+    let deny_list = &mut ctx.accounts.deny_list;
+    deny_list.allow(&key)?;
+
+    emit!(KeyAllowedEvent {
+        master_lockbox: deny_list.master_lockbox,
+        key,
+        allowed_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Key {} allowed", key);
+    Ok(())
+}
+
+/// Close the deny-list and reclaim its rent
+#[derive(Accounts)]
+pub struct CloseDenyList<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = deny_list.bump
+    )]
+    pub deny_list: Account<'info, DenyList>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_deny_list_handler(_ctx: Context<CloseDenyList>) -> Result<()> {
+    msg!("Deny-list closed - rent reclaimed");
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct KeyDeniedEvent {
+    pub master_lockbox: Pubkey,
+    pub key: Pubkey,
+    pub denied_at: i64,
+}
+
+#[event]
+pub struct KeyAllowedEvent {
+    pub master_lockbox: Pubkey,
+    pub key: Pubkey,
+    pub allowed_at: i64,
+}
@@ -9,8 +9,13 @@
 //! ### Setup Phase
 //! 1. `initialize_emergency_access` - Owner creates emergency access config
 //! 2. `add_emergency_contact` - Owner adds emergency contacts with access levels
+//!    (`add_emergency_contacts_batch` adds several at once, all or nothing)
 //! 3. `accept_emergency_contact` - Contact accepts their role
 //! 4. `remove_emergency_contact` - Owner removes a contact
+//! 5. `resend_emergency_contact_invitation` - Owner restarts a pending contact's acceptance window
+//! 6. `expire_pending_contacts` - Anyone removes contacts whose invitations lapsed
+//! 7. `set_export_consent` / `revoke_export_consent` - Owner signs or withdraws
+//!    standing consent to release data to FullAccess-level contacts
 //!
 //! ### Activity Tracking
 //! 1. `record_activity` - Called on every password operation (extends countdown)
@@ -18,12 +23,25 @@
 //!
 //! ### Emergency Activation
 //! 1. `check_and_start_countdown` - Cron job checks inactivity
-//! 2. `activate_emergency_access` - After grace period, grant access
-//! 3. `cancel_emergency_countdown` - Owner cancels countdown
+//! 2. `notify_emergency_contacts` - Cron job re-notifies contacts during the countdown
+//! 3. `activate_emergency_access` - After grace period, grant access
+//! 4. `cancel_emergency_countdown` - Owner cancels countdown
+//!
+//! ### Teardown
+//! 1. `close_emergency_access` - Owner closes the config when no countdown is running
+//!
+//! ### Testing
+//! 1. `test_emergency_flow` - Owner simulates countdown and activation; every
+//!    event is emitted with `test_mode = true` and no access is granted
 
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
+use super::deny_list::check_not_denied;
+use super::inbox::deliver_to_inbox;
+
+/// Most contacts `add_emergency_contacts_batch` adds in one instruction
+pub const MAX_EMERGENCY_CONTACT_BATCH: usize = PRO_MAX_EMERGENCY_CONTACTS;
 
 /// Initialize emergency access configuration
 ///
@@ -33,14 +51,17 @@ use crate::errors::*;
 /// # Arguments
 /// * `inactivity_period` - Time in seconds before countdown starts (e.g., 90 days)
 /// * `grace_period` - Time after countdown to grant access (e.g., 7 days)
+/// * `notification_interval` - Time between contact notifications during the
+///   countdown (0 = notify only when the countdown starts)
 pub fn initialize_emergency_access_handler(
     ctx: Context<InitializeEmergencyAccess>,
     inactivity_period: i64,
     grace_period: i64,
+    notification_interval: i64,
 ) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
     let master_lockbox = &ctx.accounts.master_lockbox;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify subscription tier (Premium or Pro required)
     require!(
@@ -63,15 +84,26 @@ pub fn initialize_emergency_access_handler(
         LockboxError::InvalidGracePeriod
     );
 
+    // Validate notification interval
+    require!(
+        EmergencyAccess::is_notification_interval_valid(notification_interval, grace_period),
+        LockboxError::InvalidNotificationInterval
+    );
+
     // Initialize emergency access
     emergency_access.owner = ctx.accounts.owner.key();
     emergency_access.emergency_contacts = Vec::new();
     emergency_access.inactivity_period = inactivity_period;
     emergency_access.grace_period = grace_period;
-    emergency_access.last_activity = clock.unix_timestamp;
+    emergency_access.notification_interval = notification_interval;
+    emergency_access.invitation_ttl = DEFAULT_INVITATION_TTL;
+    emergency_access.last_activity = now;
     emergency_access.countdown_started = None;
+    emergency_access.last_notified_at = 0;
     emergency_access.status = EmergencyStatus::Active;
-    emergency_access.created_at = clock.unix_timestamp;
+    emergency_access.created_at = now;
+    emergency_access.export_consent = None;
+    emergency_access.version = EmergencyAccess::CURRENT_VERSION;
     emergency_access.bump = ctx.bumps.emergency_access;
 
     msg!(
@@ -92,6 +124,9 @@ pub fn initialize_emergency_access_handler(
 /// * `contact_name_encrypted` - Encrypted contact name
 /// * `access_level` - Access level granted to contact
 /// * `encrypted_key` - Vault key encrypted with contact's pubkey
+///
+/// Lapsed invitations are expired first so they don't count toward the
+/// tier's contact limit.
 pub fn add_emergency_contact_handler(
     ctx: Context<AddEmergencyContact>,
     contact_pubkey: Pubkey,
@@ -100,7 +135,7 @@ pub fn add_emergency_contact_handler(
     encrypted_key: Vec<u8>,
 ) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -108,47 +143,106 @@ pub fn add_emergency_contact_handler(
         LockboxError::Unauthorized
     );
 
-    // Check maximum contacts
-    require!(
-        emergency_access.emergency_contacts.len() < MAX_EMERGENCY_CONTACTS,
-        LockboxError::TooManyContacts
-    );
+    let expired = emergency_access.expire_pending_contacts(now);
+    emit_expired_invitations(emergency_access.owner, &expired);
+
+    check_not_denied(&ctx.accounts.deny_list, &contact_pubkey)?;
+
+    // Tier limit, unique key and field sizes
+    let expires_at = now + emergency_access.invitation_ttl;
+    emergency_access.add_contact(
+        EmergencyContact {
+            contact_pubkey,
+            contact_name_encrypted,
+            access_level,
+            encrypted_key,
+            added_at: now,
+            access_granted_at: None,
+            status: EmergencyContactStatus::PendingAcceptance,
+            invited_at: now,
+            expires_at,
+        },
+        ctx.accounts.master_lockbox.subscription_tier.max_emergency_contacts(),
+    )?;
+
+    let access_key = emergency_access.key();
+    if let Some(inbox) = ctx.accounts.contact_inbox.as_mut() {
+        deliver_to_inbox(
+            inbox,
+            InboxItemKind::EmergencyContact,
+            ctx.accounts.owner.key(),
+            access_key,
+            now,
+        );
+    }
 
-    // Check contact doesn't already exist
-    require!(
-        !emergency_access
-            .emergency_contacts
-            .iter()
-            .any(|c| c.contact_pubkey == contact_pubkey),
-        LockboxError::ContactAlreadyExists
+    msg!(
+        "Emergency contact added: pubkey={}, level={:?}",
+        contact_pubkey,
+        access_level
     );
 
-    // Validate sizes
-    require!(
-        contact_name_encrypted.len() <= 64,
-        LockboxError::InvalidNicknameSize
-    );
+    Ok(())
+}
+
+/// One contact of an `add_emergency_contacts_batch` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NewEmergencyContact {
+    pub contact_pubkey: Pubkey,
+    pub contact_name_encrypted: Vec<u8>,
+    pub access_level: EmergencyAccessLevel,
+    pub encrypted_key: Vec<u8>,
+}
+
+/// Add several emergency contacts in one instruction
+///
+/// Either every contact is added or none is, so a setup sent together with
+/// `initialize_emergency_access` cannot leave a half-configured dead man's
+/// switch behind. The batch must fit the tier's contact limit together with
+/// the contacts already present; lapsed invitations are expired first.
+/// Contacts' inboxes are not notified.
+///
+/// # Errors
+/// * `InvalidBatchSize` - Batch is empty or exceeds `MAX_EMERGENCY_CONTACT_BATCH`
+/// * `KeyDenied` - A contact is on the vault's deny-list
+/// * Any error of `EmergencyAccess::add_contact`, for the first contact that
+///   fails
+pub fn add_emergency_contacts_batch_handler(
+    ctx: Context<AddEmergencyContactsBatch>,
+    contacts: Vec<NewEmergencyContact>,
+) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    let max_contacts = ctx.accounts.master_lockbox.subscription_tier.max_emergency_contacts();
+    let now = cluster_timestamp()?;
+
     require!(
-        encrypted_key.len() <= 128,
-        LockboxError::InvalidKeySize
+        (1..=MAX_EMERGENCY_CONTACT_BATCH).contains(&contacts.len()),
+        LockboxError::InvalidBatchSize
     );
 
-    // Add contact
-    emergency_access.emergency_contacts.push(EmergencyContact {
-        contact_pubkey,
-        contact_name_encrypted,
-        access_level,
-        encrypted_key,
-        added_at: clock.unix_timestamp,
-        access_granted_at: None,
-        status: EmergencyContactStatus::PendingAcceptance,
-    });
+    let expired = emergency_access.expire_pending_contacts(now);
+    emit_expired_invitations(emergency_access.owner, &expired);
+
+    let expires_at = now + emergency_access.invitation_ttl;
+    for contact in contacts {
+        check_not_denied(&ctx.accounts.deny_list, &contact.contact_pubkey)?;
+        emergency_access.add_contact(
+            EmergencyContact {
+                contact_pubkey: contact.contact_pubkey,
+                contact_name_encrypted: contact.contact_name_encrypted,
+                access_level: contact.access_level,
+                encrypted_key: contact.encrypted_key,
+                added_at: now,
+                access_granted_at: None,
+                status: EmergencyContactStatus::PendingAcceptance,
+                invited_at: now,
+                expires_at,
+            },
+            max_contacts,
+        )?;
+    }
 
-    msg!(
-        "Emergency contact added: pubkey={}, level={:?}",
-        contact_pubkey,
-        access_level
-    );
+    msg!("{} emergency contacts configured", emergency_access.emergency_contacts.len());
 
     Ok(())
 }
@@ -159,6 +253,7 @@ pub fn add_emergency_contact_handler(
 pub fn accept_emergency_contact_handler(ctx: Context<AcceptEmergencyContact>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
     let contact_pubkey = ctx.accounts.contact.key();
+    let now = cluster_timestamp()?;
 
     // Find contact
     let contact = emergency_access
@@ -172,6 +267,10 @@ pub fn accept_emergency_contact_handler(ctx: Context<AcceptEmergencyContact>) ->
         contact.status == EmergencyContactStatus::PendingAcceptance,
         LockboxError::ContactAlreadyAccepted
     );
+    require!(
+        !contact.is_invitation_expired(now),
+        LockboxError::ContactInvitationExpired
+    );
 
     // Activate contact
     contact.status = EmergencyContactStatus::Active;
@@ -208,20 +307,191 @@ pub fn remove_emergency_contact_handler(
 
     emergency_access.emergency_contacts.remove(contact_index);
 
+    // Re-adding the wallet later must not revive its consent
+    if let Some(consent) = emergency_access.export_consent.as_mut() {
+        consent.contacts.retain(|c| c != &contact_pubkey);
+    }
+
     msg!("Emergency contact removed: pubkey={}", contact_pubkey);
 
     Ok(())
 }
 
+/// Resend a pending emergency contact's invitation
+///
+/// Restarts the acceptance window from now using the config's current
+/// invitation TTL. Also revives a lapsed invitation that no one has
+/// expired yet. Optionally delivers the designation to the contact's inbox
+/// again.
+///
+/// # Arguments
+/// * `contact_pubkey` - Pending contact to re-invite
+pub fn resend_emergency_contact_invitation_handler(
+    ctx: Context<ResendEmergencyContactInvitation>,
+    contact_pubkey: Pubkey,
+) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    let now = cluster_timestamp()?;
+    let invitation_ttl = emergency_access.invitation_ttl;
+
+    let contact = emergency_access
+        .emergency_contacts
+        .iter_mut()
+        .find(|c| c.contact_pubkey == contact_pubkey)
+        .ok_or(LockboxError::ContactNotFound)?;
+    require!(
+        contact.status == EmergencyContactStatus::PendingAcceptance,
+        LockboxError::ContactAlreadyAccepted
+    );
+
+    contact.invited_at = now;
+    contact.expires_at = now + invitation_ttl;
+    let expires_at = contact.expires_at;
+
+    let access_key = emergency_access.key();
+    if let Some(inbox) = ctx.accounts.contact_inbox.as_mut() {
+        deliver_to_inbox(
+            inbox,
+            InboxItemKind::EmergencyContact,
+            ctx.accounts.owner.key(),
+            access_key,
+            now,
+        );
+    }
+
+    emit!(EmergencyContactInvitationResentEvent {
+        owner: ctx.accounts.owner.key(),
+        contact: contact_pubkey,
+        expires_at,
+    });
+
+    msg!("Emergency contact invitation resent: pubkey={}, expires_at={}", contact_pubkey, expires_at);
+
+    Ok(())
+}
+
+/// Remove contacts whose invitations lapsed (permissionless crank)
+///
+/// Emits one `EmergencyContactInvitationExpiredEvent` per removed contact.
+/// `add_emergency_contact` runs the same cleanup, so this is only needed to
+/// tidy a config the owner isn't otherwise touching.
+pub fn expire_pending_contacts_handler(ctx: Context<ExpirePendingContacts>) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    let now = cluster_timestamp()?;
+
+    let expired = emergency_access.expire_pending_contacts(now);
+    emit_expired_invitations(emergency_access.owner, &expired);
+
+    msg!("Expired {} pending emergency contacts", expired.len());
+
+    Ok(())
+}
+
+/// Set how long future emergency contact invitations stay open
+///
+/// Applies to invitations added or resent afterwards; open invitations
+/// keep their current expiry.
+///
+/// # Arguments
+/// * `invitation_ttl` - Seconds, between `MIN_INVITATION_TTL` and `MAX_INVITATION_TTL`
+pub fn set_emergency_invitation_ttl_handler(
+    ctx: Context<SetEmergencyInvitationTtl>,
+    invitation_ttl: i64,
+) -> Result<()> {
+    require!(
+        (MIN_INVITATION_TTL..=MAX_INVITATION_TTL).contains(&invitation_ttl),
+        LockboxError::InvalidInvitationTtl
+    );
+
+    ctx.accounts.emergency_access.invitation_ttl = invitation_ttl;
+
+    msg!("Emergency contact invitation TTL set to {}s", invitation_ttl);
+
+    Ok(())
+}
+
+/// Sign standing consent to export vault data on activation
+///
+/// FullAccess and TransferOwnership contacts are only granted access by
+/// `activate_emergency_access` if they are named here; ViewOnly contacts
+/// need no consent. Replaces any earlier consent.
+///
+/// # Arguments
+/// * `contacts` - Existing emergency contacts the consent covers (no duplicates)
+/// * `scope_hash` - SHA-256 of the client-defined export scope
+/// * `conditions_hash` - SHA-256 of the conditions document
+pub fn set_export_consent_handler(
+    ctx: Context<SetExportConsent>,
+    contacts: Vec<Pubkey>,
+    scope_hash: [u8; 32],
+    conditions_hash: [u8; 32],
+) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    let now = cluster_timestamp()?;
+
+    require!(!contacts.is_empty(), LockboxError::InvalidExportConsent);
+    for (i, contact) in contacts.iter().enumerate() {
+        require!(
+            emergency_access.get_contact(contact).is_some() && !contacts[..i].contains(contact),
+            LockboxError::InvalidExportConsent
+        );
+    }
+
+    emit!(ExportConsentSetEvent {
+        owner: emergency_access.owner,
+        contacts: contacts.clone(),
+        scope_hash,
+        conditions_hash,
+        signed_at: now,
+    });
+
+    emergency_access.export_consent = Some(ExportConsent {
+        contacts,
+        scope_hash,
+        conditions_hash,
+        signed_at: now,
+    });
+
+    msg!("Export consent signed");
+
+    Ok(())
+}
+
+/// Withdraw export consent; FullAccess-level contacts are no longer granted
+pub fn revoke_export_consent_handler(ctx: Context<SetExportConsent>) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    emergency_access.export_consent = None;
+
+    emit!(ExportConsentRevokedEvent {
+        owner: emergency_access.owner,
+        revoked_at: cluster_timestamp()?,
+    });
+
+    msg!("Export consent revoked");
+
+    Ok(())
+}
+
+fn emit_expired_invitations(owner: Pubkey, expired: &[EmergencyContact]) {
+    for contact in expired {
+        emit!(EmergencyContactInvitationExpiredEvent {
+            owner,
+            contact: contact.contact_pubkey,
+            invited_at: contact.invited_at,
+            expired_at: contact.expires_at,
+        });
+    }
+}
+
 /// Record activity (called on password operations)
 ///
 /// This instruction should be called as part of password store/retrieve/update
 /// operations to track user activity and reset the countdown.
 pub fn record_activity_handler(ctx: Context<RecordActivity>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
-    emergency_access.record_activity(clock.unix_timestamp);
+    emergency_access.record_activity(now);
 
     msg!("Activity recorded: countdown reset");
 
@@ -234,7 +504,7 @@ pub fn record_activity_handler(ctx: Context<RecordActivity>) -> Result<()> {
 /// This is useful as a "I'm alive" button in the UI.
 pub fn manual_activity_ping_handler(ctx: Context<ManualActivityPing>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -242,7 +512,7 @@ pub fn manual_activity_ping_handler(ctx: Context<ManualActivityPing>) -> Result<
         LockboxError::Unauthorized
     );
 
-    emergency_access.record_activity(clock.unix_timestamp);
+    emergency_access.record_activity(now);
 
     msg!("Manual activity ping: countdown reset");
 
@@ -255,55 +525,208 @@ pub fn manual_activity_ping_handler(ctx: Context<ManualActivityPing>) -> Result<
 /// Anyone can call this (designed for cron bots).
 pub fn check_and_start_countdown_handler(ctx: Context<CheckAndStartCountdown>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
+    check_recorded_timestamp(emergency_access.last_activity, now)?;
 
     // Check if countdown should start
-    if emergency_access.should_start_countdown(clock.unix_timestamp) {
-        emergency_access.start_countdown(clock.unix_timestamp);
+    if emergency_access.should_start_countdown(now) {
+        emergency_access.start_countdown(now);
 
         msg!(
             "Emergency countdown started: grace_period_ends={}",
-            clock.unix_timestamp + emergency_access.grace_period
+            now + emergency_access.grace_period
         );
 
         // Emit event for notifications
         emit!(EmergencyCountdownStartedEvent {
             owner: emergency_access.owner,
-            countdown_started: clock.unix_timestamp,
-            grace_period_ends: clock.unix_timestamp + emergency_access.grace_period,
+            countdown_started: now,
+            grace_period_ends: now + emergency_access.grace_period,
+            test_mode: false,
         });
+        emit_pending_notifications(emergency_access, now, false);
     }
 
     Ok(())
 }
 
+/// Notify emergency contacts during the countdown
+///
+/// Cron job instruction that emits a `PendingEmergencyAccessEvent` to every
+/// active contact once per `notification_interval` while the countdown runs.
+/// Anyone can call this (designed for cron bots).
+pub fn notify_emergency_contacts_handler(ctx: Context<NotifyEmergencyContacts>) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+    let now = cluster_timestamp()?;
+
+    require!(
+        emergency_access.should_notify_contacts(now),
+        LockboxError::NotificationNotDue
+    );
+
+    emergency_access.last_notified_at = now;
+    emit_pending_notifications(emergency_access, now, false);
+
+    msg!(
+        "Emergency contacts notified: {} contacts",
+        emergency_access.active_contact_count()
+    );
+
+    Ok(())
+}
+
+/// Change the countdown notification interval
+///
+/// # Arguments
+/// * `notification_interval` - Seconds between notifications (0 = off)
+pub fn set_notification_interval_handler(
+    ctx: Context<SetNotificationInterval>,
+    notification_interval: i64,
+) -> Result<()> {
+    let emergency_access = &mut ctx.accounts.emergency_access;
+
+    require!(
+        EmergencyAccess::is_notification_interval_valid(
+            notification_interval,
+            emergency_access.grace_period
+        ),
+        LockboxError::InvalidNotificationInterval
+    );
+
+    emergency_access.notification_interval = notification_interval;
+
+    msg!("Emergency notification interval set to {}s", notification_interval);
+
+    Ok(())
+}
+
+/// Emit one `PendingEmergencyAccessEvent` per active contact
+fn emit_pending_notifications(emergency_access: &EmergencyAccess, current_time: i64, test_mode: bool) {
+    let countdown_started = emergency_access.countdown_started.unwrap_or(current_time);
+    for contact in emergency_access.active_contacts() {
+        emit!(PendingEmergencyAccessEvent {
+            owner: emergency_access.owner,
+            contact: contact.contact_pubkey,
+            access_level: contact.access_level,
+            countdown_started,
+            grace_period_ends: countdown_started + emergency_access.grace_period,
+            notified_at: current_time,
+            test_mode,
+        });
+    }
+}
+
+/// Emit one `EmergencyGrantEvent` per grant, then `EmergencyAccessActivatedEvent`
+fn emit_activation(
+    owner: Pubkey,
+    grant_record: Pubkey,
+    grants: &[EmergencyGrant],
+    activated_at: i64,
+    test_mode: bool,
+) {
+    for grant in grants {
+        emit!(EmergencyGrantEvent {
+            owner,
+            grant_record,
+            contact: grant.contact,
+            access_level: grant.access_level,
+            key_hash: grant.key_hash,
+            granted_at: activated_at,
+            test_mode,
+        });
+    }
+
+    emit!(EmergencyAccessActivatedEvent {
+        owner,
+        contacts_count: grants.len() as u8,
+        activated_at,
+        test_mode,
+    });
+}
+
 /// Activate emergency access
 ///
 /// After grace period elapses, grant access to all active emergency contacts.
-/// Anyone can call this (designed for cron bots).
+/// Anyone can call this (designed for cron bots); the caller pays for the
+/// `EmergencyGrantRecord`, and one `EmergencyGrantEvent` is emitted per grant.
 pub fn activate_emergency_access_handler(ctx: Context<ActivateEmergencyAccess>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
+    check_recorded_timestamp(emergency_access.countdown_started.unwrap_or_default(), now)?;
 
     // Verify grace period has elapsed
     require!(
-        emergency_access.should_activate_emergency(clock.unix_timestamp),
+        emergency_access.should_activate_emergency(now),
         LockboxError::GracePeriodNotElapsed
     );
 
-    emergency_access.activate_emergency(clock.unix_timestamp);
+    let countdown_started = emergency_access.countdown_started.unwrap_or_default();
+    let grants = emergency_access.activate_emergency(now);
+
+    let grant_record = &mut ctx.accounts.grant_record;
+    emit_activation(
+        emergency_access.owner,
+        grant_record.key(),
+        &grants,
+        now,
+        false,
+    );
 
     msg!(
         "Emergency access activated: {} contacts granted access",
-        emergency_access.active_contact_count()
+        grants.len()
     );
 
-    // Emit event
-    emit!(EmergencyAccessActivatedEvent {
-        owner: emergency_access.owner,
-        contacts_count: emergency_access.active_contact_count() as u8,
-        activated_at: clock.unix_timestamp,
+    grant_record.version = EmergencyGrantRecord::CURRENT_VERSION;
+    grant_record.owner = emergency_access.owner;
+    grant_record.emergency_access = emergency_access.key();
+    grant_record.countdown_started = countdown_started;
+    grant_record.activated_at = now;
+    grant_record.grants = grants;
+    grant_record.bump = ctx.bumps.grant_record;
+
+    Ok(())
+}
+
+/// Rehearse the emergency flow without granting access
+///
+/// Simulates a countdown and an immediate activation against a copy of the
+/// configuration and emits the same events as the real flow, each flagged
+/// `test_mode = true`, so the owner can confirm contacts are notified. The
+/// account is not modified and no `EmergencyGrantRecord` is written (the
+/// grant events carry a default record address).
+///
+/// Only allowed while no real countdown is running.
+pub fn test_emergency_flow_handler(ctx: Context<TestEmergencyFlow>) -> Result<()> {
+    let emergency_access = &ctx.accounts.emergency_access;
+    let now = cluster_timestamp()?;
+
+    require!(
+        emergency_access.status == EmergencyStatus::Active,
+        LockboxError::EmergencyFlowTestUnavailable
+    );
+
+    let mut simulated = emergency_access.clone().into_inner();
+    simulated.start_countdown(now);
+
+    emit!(EmergencyCountdownStartedEvent {
+        owner: simulated.owner,
+        countdown_started: now,
+        grace_period_ends: now + simulated.grace_period,
+        test_mode: true,
     });
+    emit_pending_notifications(&simulated, now, true);
+
+    let grants = simulated.activate_emergency(now);
+    emit_activation(
+        simulated.owner,
+        Pubkey::default(),
+        &grants,
+        now,
+        true,
+    );
+
+    msg!("Emergency flow test: {} contacts would be granted access", grants.len());
 
     Ok(())
 }
@@ -314,7 +737,7 @@ pub fn activate_emergency_access_handler(ctx: Context<ActivateEmergencyAccess>)
 /// returns and wants to stop the emergency access process.
 pub fn cancel_emergency_countdown_handler(ctx: Context<CancelEmergencyCountdown>) -> Result<()> {
     let emergency_access = &mut ctx.accounts.emergency_access;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -328,13 +751,28 @@ pub fn cancel_emergency_countdown_handler(ctx: Context<CancelEmergencyCountdown>
         LockboxError::NoActiveCountdown
     );
 
-    emergency_access.cancel_countdown(clock.unix_timestamp);
+    emergency_access.cancel_countdown(now);
 
     msg!("Emergency countdown cancelled");
 
     Ok(())
 }
 
+/// Close the emergency access configuration and reclaim its rent
+///
+/// Blocked while a countdown is running; the owner can cancel it first.
+/// Grant records from earlier activations are separate accounts and remain.
+pub fn close_emergency_access_handler(ctx: Context<CloseEmergencyAccess>) -> Result<()> {
+    require!(
+        ctx.accounts.emergency_access.status != EmergencyStatus::CountdownStarted,
+        LockboxError::EmergencyCountdownActive
+    );
+
+    msg!("Emergency access closed - rent reclaimed");
+
+    Ok(())
+}
+
 // ============================================================================
 // Account Validation Contexts
 // ============================================================================
@@ -345,15 +783,15 @@ pub struct InitializeEmergencyAccess<'info> {
         init,
         payer = owner,
         space = 8 + EmergencyAccess::INIT_SPACE,
-        seeds = [b"emergency_access", owner.key().as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
         bump
     )]
     pub emergency_access: Account<'info, EmergencyAccess>,
 
     #[account(
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -364,16 +802,77 @@ pub struct InitializeEmergencyAccess<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(contact_pubkey: Pubkey)]
 pub struct AddEmergencyContact<'info> {
     #[account(
         mut,
-        seeds = [b"emergency_access", owner.key().as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
         bump = emergency_access.bump,
-        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized,
+        realloc = EmergencyAccess::space_for(emergency_access.emergency_contacts.len() + 1),
+        realloc::payer = owner,
+        realloc::zero = false
     )]
     pub emergency_access: Account<'info, EmergencyAccess>,
 
+    /// Owner's vault (read-only, for the tier's contact limit)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional: the contact's inbox, notified of the designation
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, contact_pubkey.as_ref()],
+        bump = contact_inbox.bump
+    )]
+    pub contact_inbox: Option<Account<'info, Inbox>>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(contacts: Vec<NewEmergencyContact>)]
+pub struct AddEmergencyContactsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized,
+        realloc = EmergencyAccess::space_for(emergency_access.emergency_contacts.len() + contacts.len()),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    /// Owner's vault (read-only, for the tier's contact limit)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -388,7 +887,65 @@ pub struct AcceptEmergencyContact<'info> {
 pub struct RemoveEmergencyContact<'info> {
     #[account(
         mut,
-        seeds = [b"emergency_access", owner.key().as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(contact_pubkey: Pubkey)]
+pub struct ResendEmergencyContactInvitation<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
+
+    /// Optional: the contact's inbox, notified of the designation again
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, contact_pubkey.as_ref()],
+        bump = contact_inbox.bump
+    )]
+    pub contact_inbox: Option<Account<'info, Inbox>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpirePendingContacts<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, emergency_access.owner.as_ref()],
+        bump = emergency_access.bump
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+}
+
+#[derive(Accounts)]
+pub struct SetEmergencyInvitationTtl<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetExportConsent<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
         bump = emergency_access.bump,
         constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
     )]
@@ -401,7 +958,7 @@ pub struct RemoveEmergencyContact<'info> {
 pub struct RecordActivity<'info> {
     #[account(
         mut,
-        seeds = [b"emergency_access", emergency_access.owner.as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, emergency_access.owner.as_ref()],
         bump = emergency_access.bump
     )]
     pub emergency_access: Account<'info, EmergencyAccess>,
@@ -411,7 +968,7 @@ pub struct RecordActivity<'info> {
 pub struct ManualActivityPing<'info> {
     #[account(
         mut,
-        seeds = [b"emergency_access", owner.key().as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
         bump = emergency_access.bump,
         constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
     )]
@@ -426,22 +983,100 @@ pub struct CheckAndStartCountdown<'info> {
     pub emergency_access: Account<'info, EmergencyAccess>,
 }
 
+#[derive(Accounts)]
+pub struct NotifyEmergencyContacts<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, emergency_access.owner.as_ref()],
+        bump = emergency_access.bump
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+}
+
+#[derive(Accounts)]
+pub struct SetNotificationInterval<'info> {
+    #[account(
+        mut,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ActivateEmergencyAccess<'info> {
     #[account(mut)]
     pub emergency_access: Account<'info, EmergencyAccess>,
+
+    /// Owner's vault (read-only, for the CPI guard)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == emergency_access.owner @ LockboxError::Unauthorized,
+        constraint = master_lockbox.allows_sensitive_invocation() @ LockboxError::CpiNotAllowed
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Immutable record of this activation's grants
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + EmergencyGrantRecord::INIT_SPACE,
+        seeds = [
+            EmergencyGrantRecord::SEEDS_PREFIX,
+            emergency_access.key().as_ref(),
+            &emergency_access.countdown_started.unwrap_or_default().to_le_bytes()
+        ],
+        bump
+    )]
+    pub grant_record: Account<'info, EmergencyGrantRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TestEmergencyFlow<'info> {
+    #[account(
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct CancelEmergencyCountdown<'info> {
     #[account(
         mut,
-        seeds = [b"emergency_access", owner.key().as_ref()],
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = emergency_access.bump,
+        constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub emergency_access: Account<'info, EmergencyAccess>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEmergencyAccess<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [EmergencyAccess::SEEDS_PREFIX, owner.key().as_ref()],
         bump = emergency_access.bump,
         constraint = emergency_access.owner == owner.key() @ LockboxError::Unauthorized
     )]
     pub emergency_access: Account<'info, EmergencyAccess>,
 
+    #[account(mut)]
     pub owner: Signer<'info>,
 }
 
@@ -454,6 +1089,20 @@ pub struct EmergencyCountdownStartedEvent {
     pub owner: Pubkey,
     pub countdown_started: i64,
     pub grace_period_ends: i64,
+    /// Emitted by `test_emergency_flow`; no access was granted
+    pub test_mode: bool,
+}
+
+#[event]
+pub struct PendingEmergencyAccessEvent {
+    pub owner: Pubkey,
+    pub contact: Pubkey,
+    pub access_level: EmergencyAccessLevel,
+    pub countdown_started: i64,
+    pub grace_period_ends: i64,
+    pub notified_at: i64,
+    /// Emitted by `test_emergency_flow`; no access was granted
+    pub test_mode: bool,
 }
 
 #[event]
@@ -461,4 +1110,48 @@ pub struct EmergencyAccessActivatedEvent {
     pub owner: Pubkey,
     pub contacts_count: u8,
     pub activated_at: i64,
+    /// Emitted by `test_emergency_flow`; no access was granted
+    pub test_mode: bool,
+}
+
+#[event]
+pub struct EmergencyGrantEvent {
+    pub owner: Pubkey,
+    pub grant_record: Pubkey,
+    pub contact: Pubkey,
+    pub access_level: EmergencyAccessLevel,
+    pub key_hash: [u8; 32],
+    pub granted_at: i64,
+    /// Emitted by `test_emergency_flow`; no access was granted
+    pub test_mode: bool,
+}
+
+#[event]
+pub struct EmergencyContactInvitationResentEvent {
+    pub owner: Pubkey,
+    pub contact: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct EmergencyContactInvitationExpiredEvent {
+    pub owner: Pubkey,
+    pub contact: Pubkey,
+    pub invited_at: i64,
+    pub expired_at: i64,
+}
+
+#[event]
+pub struct ExportConsentSetEvent {
+    pub owner: Pubkey,
+    pub contacts: Vec<Pubkey>,
+    pub scope_hash: [u8; 32],
+    pub conditions_hash: [u8; 32],
+    pub signed_at: i64,
+}
+
+#[event]
+pub struct ExportConsentRevokedEvent {
+    pub owner: Pubkey,
+    pub revoked_at: i64,
 }
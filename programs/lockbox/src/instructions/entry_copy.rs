@@ -0,0 +1,333 @@
+/**
+ * Cross-Vault Entry Copy
+ *
+ * `copy_entry_to_vault` stores a client-re-encrypted copy of an entry in
+ * another owner's vault (e.g. moving a credential from a personal wallet's
+ * vault to a work wallet's) and records an `EntryProvenance` link back to
+ * the source. Both owners sign: the source owner vouches for the
+ * provenance, the target owner for the write.
+ *
+ * The program cannot check that the ciphertext matches the source entry;
+ * only the client holding both vault keys can re-encrypt it.
+ *
+ * The source side keeps an `EntryCopies` list of back-references. Updating
+ * the source entry flags its copies stale and deleting it revokes them,
+ * both announced with `EntryCopiesChangedEvent` for the target owners'
+ * clients to act on. Trashing does not cascade (the entry can still be
+ * restored), and neither does a trash purge: delete the entry outright to
+ * revoke its copies.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{
+    DataEntryHeader, DenyList, EntryCopies, EntryCopy, EntryProvenance, MasterLockbox, SecurityPolicy, StorageChunk, TREASURY_SEED,
+};
+use super::deny_list::check_not_denied;
+use super::invariants::enforce_invariants;
+use super::password_entry::{
+    check_aead_size, check_chunk_capacity, check_payload_framing, check_storage_capacity,
+    check_subscription_active, check_write_rate,
+};
+use super::subscription::{charge_entry_write_fee, EntryWriteFeeEvent};
+
+/// Copy an entry into another vault
+#[derive(Accounts)]
+#[instruction(source_chunk_index: u16, chunk_index: u16, source_entry_id: u64)]
+pub struct CopyEntryToVault<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, source_master_lockbox.creator.as_ref()],
+        bump = source_master_lockbox.bump,
+        constraint = source_master_lockbox.is_authorized_owner(&source_owner) @ LockboxError::Unauthorized
+    )]
+    pub source_master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Chunk holding the source entry (read-only)
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            source_master_lockbox.key().as_ref(),
+            &source_chunk_index.to_le_bytes()
+        ],
+        bump = source_chunk.bump,
+        constraint = source_chunk.master_lockbox == source_master_lockbox.key() @ LockboxError::Unauthorized
+    )]
+    pub source_chunk: Account<'info, StorageChunk>,
+
+    /// Back-references from the source entry, created on its first copy
+    #[account(
+        init_if_needed,
+        payer = source_owner,
+        space = 8 + EntryCopies::INIT_SPACE,
+        seeds = [
+            EntryCopies::SEEDS_PREFIX,
+            source_master_lockbox.key().as_ref(),
+            &source_entry_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub entry_copies: Account<'info, EntryCopies>,
+
+    /// Owner of the source vault; pays for the copy list
+    #[account(mut)]
+    pub source_owner: Signer<'info>,
+
+    /// Vault receiving the copy
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + EntryProvenance::INIT_SPACE,
+        seeds = [
+            EntryProvenance::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &master_lockbox.next_entry_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub entry_provenance: Account<'info, EntryProvenance>,
+
+    /// Owner of the receiving vault; pays for the provenance record
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury PDA (system account); required when the tier's
+    /// entry write fee is non-zero
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The source vault's deny-list, which may not exist; the
+    /// address is fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, source_master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
+}
+
+/// Store a re-encrypted copy of an entry in the owner's vault
+///
+/// The copy gets the next counter ID in the receiving vault, keeps the
+/// source's entry type, and is filed as uncategorized under the new
+/// `title_hash` (both are keyed per vault). Shared vaults are not yet a
+/// supported target.
+///
+/// # Errors
+/// * `CopyTargetIsSource` - Source and receiving vault are the same
+/// * `KeyDenied` - The receiving owner is on the source vault's deny-list
+/// * `EntryNotFound` - Entry is not in the source chunk
+/// * `EntryIsAlias` - Copy the alias target instead
+/// * `InvalidDataSize` - Ciphertext is not a valid AEAD payload
+/// * `MalformedPayload` - Ciphertext lacks the payload framing header
+/// * `EntryTooLarge` - Ciphertext exceeds the tier's entry size limit
+/// * `TooManyEntryCopies` - Source entry already has `MAX_ENTRY_COPIES`
+///   fresh copies
+/// * `SubscriptionExpired` / `InsufficientStorageCapacity` /
+///   `InsufficientChunkCapacity` - As for `store_password_entry`
+/// * `StaleChunkLayout` - The source chunk changed since the client read
+///   the entry from it
+pub fn copy_entry_to_vault_handler(
+    ctx: Context<CopyEntryToVault>,
+    _source_chunk_index: u16,
+    _chunk_index: u16,
+    source_entry_id: u64,
+    reencrypted_data: Vec<u8>,
+    title_hash: [u8; 32],
+    expected_layout_epoch: u32,
+) -> Result<()> {
+    let source_vault = ctx.accounts.source_master_lockbox.key();
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(source_vault != master_lockbox.key(), LockboxError::CopyTargetIsSource);
+    check_not_denied(&ctx.accounts.deny_list, &ctx.accounts.owner.key())?;
+
+    // SECURITY: Rate limiting
+    check_write_rate(master_lockbox, current_timestamp)?;
+    master_lockbox.check_chunk_layout(storage_chunk)?;
+    ctx.accounts.source_chunk.check_layout_epoch(expected_layout_epoch)?;
+
+    let source = ctx.accounts.source_chunk.get_entry_header(source_entry_id)?;
+    require!(!source.is_alias(), LockboxError::EntryIsAlias);
+    require!(!source.spans_chunks(), LockboxError::EntrySpansChunks);
+    let entry_type = source.entry_type;
+
+    check_aead_size(reencrypted_data.len())?;
+    check_payload_framing(&reencrypted_data)?;
+    master_lockbox.check_entry_size(reencrypted_data.len(), current_timestamp)?;
+    if let Some(policy) = SecurityPolicy::load(&ctx.accounts.security_policy)? {
+        policy.check_size(reencrypted_data.len())?;
+    }
+
+    check_subscription_active(master_lockbox, current_timestamp)?;
+    check_storage_capacity(master_lockbox, reencrypted_data.len() as u64, current_timestamp)?;
+    master_lockbox.record_write_burst(reencrypted_data.len(), Clock::get()?.slot)?;
+    check_chunk_capacity(storage_chunk, reencrypted_data.len() as u32)?;
+
+    // Matches the provenance PDA seed
+    let entry_id = master_lockbox.get_next_entry_id();
+    let size = reencrypted_data.len() as u32;
+    let entry_header = DataEntryHeader {
+        entry_id,
+        offset: storage_chunk.current_size,
+        size,
+        entry_type,
+        category: 0,
+        title_hash,
+        created_at: current_timestamp,
+        last_modified: current_timestamp,
+        access_count: 0,
+        flags: 0,
+        next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+        next_offset: 0,
+        tag_mask: 0,
+        rotate_after: 0,
+        parent_entry: 0,
+        linked_entry_ids: Vec::new(),
+    };
+
+    // Spam-prevention fee (waived on paid tiers)
+    let fee = charge_entry_write_fee(
+        master_lockbox.subscription_tier,
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+        Some(ctx.accounts.system_program.to_account_info()),
+    )?;
+    if fee > 0 {
+        emit!(EntryWriteFeeEvent { owner: master_lockbox.owner, entry_id, amount: fee });
+    }
+
+    storage_chunk.add_entry(entry_header, reencrypted_data, current_timestamp)?;
+
+    master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
+    master_lockbox.increment_entries();
+    master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    let provenance = &mut ctx.accounts.entry_provenance;
+    provenance.version = EntryProvenance::CURRENT_VERSION;
+    provenance.master_lockbox = master_lockbox.key();
+    provenance.owner = ctx.accounts.owner.key();
+    provenance.entry_id = entry_id;
+    provenance.source_vault = source_vault;
+    provenance.source_chunk_index = ctx.accounts.source_chunk.chunk_index;
+    provenance.source_entry_id = source_entry_id;
+    provenance.copied_at = current_timestamp;
+    provenance.bump = ctx.bumps.entry_provenance;
+
+    let copies = &mut ctx.accounts.entry_copies;
+    if copies.version == 0 {
+        copies.version = EntryCopies::CURRENT_VERSION;
+        copies.master_lockbox = source_vault;
+        copies.entry_id = source_entry_id;
+        copies.bump = ctx.bumps.entry_copies;
+    }
+    copies.record(EntryCopy {
+        target_vault: master_lockbox.key(),
+        entry_id,
+        copied_at: current_timestamp,
+        stale: false,
+    })?;
+
+    emit!(EntryCopiedEvent {
+        source_vault,
+        source_entry_id,
+        target_vault: master_lockbox.key(),
+        entry_id,
+        size,
+        copied_at: current_timestamp,
+    });
+
+    msg!("Entry {} copied from {} as entry {}", source_entry_id, source_vault, entry_id);
+
+    Ok(())
+}
+
+/// Flag or revoke the copies of an entry that was just updated or deleted
+///
+/// `entry_copies` is the source entry's `EntryCopies` PDA, which may not
+/// exist. On delete (`revoke`) the account is closed and its rent returned
+/// to `owner`.
+pub fn cascade_to_copies<'info>(
+    entry_copies: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    source_vault: Pubkey,
+    source_entry_id: u64,
+    revoke: bool,
+    now: i64,
+) -> Result<()> {
+    let Some(mut list) = EntryCopies::load(entry_copies)? else {
+        return Ok(());
+    };
+
+    let copies = if revoke {
+        let copies = list.copies;
+        entry_copies.try_borrow_mut_data()?.fill(0);
+        let lamports = entry_copies.lamports();
+        **entry_copies.try_borrow_mut_lamports()? -= lamports;
+        **owner.try_borrow_mut_lamports()? += lamports;
+        entry_copies.assign(&anchor_lang::system_program::ID);
+        entry_copies.realloc(0, false)?;
+        copies
+    } else {
+        let flagged = list.mark_stale();
+        list.try_serialize(&mut &mut entry_copies.try_borrow_mut_data()?[..])?;
+        flagged
+    };
+
+    if !copies.is_empty() {
+        emit!(EntryCopiesChangedEvent { source_vault, source_entry_id, copies, revoked: revoke, at: now });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct EntryCopiedEvent {
+    pub source_vault: Pubkey,
+    pub source_entry_id: u64,
+    pub target_vault: Pubkey,
+    pub entry_id: u64,
+    pub size: u32,
+    pub copied_at: i64,
+}
+
+/// Copies of an entry went stale (source updated) or were revoked
+/// (source deleted)
+#[event]
+pub struct EntryCopiesChangedEvent {
+    pub source_vault: Pubkey,
+    pub source_entry_id: u64,
+    pub copies: Vec<EntryCopy>,
+    pub revoked: bool,
+    pub at: i64,
+}
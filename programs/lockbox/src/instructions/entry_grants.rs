@@ -0,0 +1,112 @@
+/**
+ * Entry Grant Listing
+ *
+ * `list_entry_grants` answers "who besides the owner can currently see this
+ * credential?" in one simulated call. It is computed from the records each
+ * grant and revoke instruction already maintains, so it cannot drift from
+ * them:
+ *
+ * - `EntryCopies`: cross-vault copies (`copy_entry_to_vault`, flagged stale
+ *   by `update_password_entry`, revoked by `delete_password_entry`)
+ * - `EmergencyAccess`: emergency contacts (`add_emergency_contact`,
+ *   `remove_emergency_contact`, `activate_emergency_access`, ...)
+ *
+ * Emergency contacts are vault-wide; a ViewOnly contact's scope is defined
+ * client-side and may not include this entry.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{
+    EmergencyAccess, EmergencyAccessLevel, EmergencyContactStatus, EntryCopies, MasterLockbox,
+    StorageChunk,
+};
+
+/// One party who can see an entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntryGrant {
+    /// A copy in another vault; `stale` once the source has changed
+    Copy { target_vault: Pubkey, entry_id: u64, stale: bool },
+    /// An accepted emergency contact, who gains access on activation
+    EmergencyContact { contact: Pubkey, access_level: EmergencyAccessLevel },
+    /// An emergency contact whose access has been granted
+    EmergencyAccessGranted { contact: Pubkey, access_level: EmergencyAccessLevel },
+}
+
+/// Every live grant on an entry, copies first
+///
+/// Pending, revoked and lapsed emergency contacts are left out. At the
+/// maximum of 16 copies and 10 contacts the list serializes to 1016 bytes,
+/// inside the 1024-byte return data limit.
+pub fn entry_grants(copies: Option<&EntryCopies>, emergency: Option<&EmergencyAccess>) -> Vec<EntryGrant> {
+    let copy_grants = copies.into_iter().flat_map(|list| &list.copies).map(|copy| EntryGrant::Copy {
+        target_vault: copy.target_vault,
+        entry_id: copy.entry_id,
+        stale: copy.stale,
+    });
+    let contact_grants = emergency
+        .into_iter()
+        .flat_map(|config| &config.emergency_contacts)
+        .filter_map(|contact| match contact.status {
+            EmergencyContactStatus::Active => Some(EntryGrant::EmergencyContact {
+                contact: contact.contact_pubkey,
+                access_level: contact.access_level,
+            }),
+            EmergencyContactStatus::AccessGranted => Some(EntryGrant::EmergencyAccessGranted {
+                contact: contact.contact_pubkey,
+                access_level: contact.access_level,
+            }),
+            EmergencyContactStatus::PendingAcceptance | EmergencyContactStatus::Revoked => None,
+        });
+    copy_grants.chain(contact_grants).collect()
+}
+
+/// List the grants on an entry (read-only)
+#[derive(Accounts)]
+#[instruction(chunk_index: u16, entry_id: u64)]
+pub struct ListEntryGrants<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// CHECK: The entry's copy list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `EntryCopies::load`
+    #[account(
+        seeds = [EntryCopies::SEEDS_PREFIX, master_lockbox.key().as_ref(), &entry_id.to_le_bytes()],
+        bump
+    )]
+    pub entry_copies: UncheckedAccount<'info>,
+
+    /// CHECK: Emergency access PDA, which may not exist; the address is
+    /// fixed by seeds and the data is read with `EmergencyAccess::load`
+    #[account(seeds = [EmergencyAccess::SEEDS_PREFIX, master_lockbox.owner.as_ref()], bump)]
+    pub emergency_access: UncheckedAccount<'info>,
+}
+
+/// Return the entry's grants via return data
+///
+/// # Errors
+/// * `EntryNotFound` - Entry is not in the chunk
+pub fn list_entry_grants_handler(
+    ctx: Context<ListEntryGrants>,
+    _chunk_index: u16,
+    entry_id: u64,
+) -> Result<Vec<EntryGrant>> {
+    ctx.accounts.storage_chunk.get_entry_header(entry_id)?;
+
+    let copies = EntryCopies::load(&ctx.accounts.entry_copies)?;
+    let emergency = EmergencyAccess::load(&ctx.accounts.emergency_access)?;
+    Ok(entry_grants(copies.as_ref(), emergency.as_ref()))
+}
@@ -0,0 +1,222 @@
+/**
+ * Entry Link Instructions
+ *
+ * Related entries - a login, its recovery codes, its API key - are grouped
+ * by linking them. A link is recorded in both entries' `linked_entry_ids`
+ * (up to `MAX_ENTRY_LINKS` each), so either entry's header names the other
+ * without a scan of the vault.
+ *
+ * Deleting an entry leaves its ID in the lists of the entries it was linked
+ * to; clients treat IDs that no longer resolve as unlinked, and
+ * `unlink_entries` clears them.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::{DataEntryHeader, MasterLockbox, StorageChunk};
+use crate::errors::LockboxError;
+
+/// Link or unlink two entries
+///
+/// `linked_chunk` holds the second entry; omit it when both entries are in
+/// `storage_chunk`.
+#[derive(Accounts)]
+#[instruction(chunk_index: u16, entry_id: u64, linked_chunk_index: u16)]
+pub struct LinkEntries<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &linked_chunk_index.to_le_bytes()
+        ],
+        bump = linked_chunk.bump,
+        constraint = linked_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = linked_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub linked_chunk: Option<Account<'info, StorageChunk>>,
+
+    /// Pays for the extra header bytes of a new link
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+fn check_linkable(header: &DataEntryHeader) -> Result<()> {
+    require!(
+        !header.is_continuation() && !header.is_alias() && !header.is_trashed(),
+        LockboxError::InvalidEntryLink
+    );
+    Ok(())
+}
+
+/// Grow a chunk account to fit its headers, topping up rent from `payer`
+///
+/// Chunk space is sized for ciphertext; a new link adds 8 bytes to a header.
+fn fit_chunk<'info>(
+    chunk: &Account<'info, StorageChunk>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let needed = 8 + chunk.try_to_vec()?.len();
+    let info = chunk.to_account_info();
+    if needed <= info.data_len() {
+        return Ok(());
+    }
+
+    let additional_rent = Rent::get()?
+        .minimum_balance(needed)
+        .saturating_sub(info.lamports());
+    if additional_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            additional_rent,
+        )?;
+    }
+    info.realloc(needed, false)?;
+    Ok(())
+}
+
+/// Link two entries
+///
+/// # Errors
+/// * `InvalidEntryLink` - Same entry twice, an alias, a trashed entry or a
+///   continuation segment; or `linked_chunk` passed for a same-chunk link
+/// * `EntryAlreadyLinked` - The entries are already linked
+/// * `TooManyEntryLinks` - Either entry already has `MAX_ENTRY_LINKS` links
+/// * `EntryNotFound` - Either entry is not in its chunk
+pub fn link_entries_handler(
+    ctx: Context<LinkEntries>,
+    chunk_index: u16,
+    entry_id: u64,
+    linked_chunk_index: u16,
+    linked_entry_id: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    match accounts.linked_chunk.as_mut() {
+        Some(linked_chunk) => {
+            require!(linked_chunk_index != chunk_index, LockboxError::InvalidEntryLink);
+            let linked = linked_chunk.get_entry_header_mut(linked_entry_id)?;
+            check_linkable(linked)?;
+            linked.add_link(entry_id)?;
+            linked_chunk.last_modified = current_timestamp;
+            fit_chunk(linked_chunk, &accounts.owner, &accounts.system_program)?;
+        }
+        None => {
+            require!(linked_chunk_index == chunk_index, LockboxError::InvalidEntryLink);
+            let linked = accounts.storage_chunk.get_entry_header_mut(linked_entry_id)?;
+            check_linkable(linked)?;
+            linked.add_link(entry_id)?;
+        }
+    }
+
+    let storage_chunk = &mut accounts.storage_chunk;
+    let header = storage_chunk.get_entry_header_mut(entry_id)?;
+    check_linkable(header)?;
+    header.add_link(linked_entry_id)?;
+    storage_chunk.last_modified = current_timestamp;
+    fit_chunk(storage_chunk, &accounts.owner, &accounts.system_program)?;
+
+    emit!(EntriesLinkedEvent {
+        master_lockbox: accounts.master_lockbox.key(),
+        entry_id,
+        linked_entry_id,
+        linked: true,
+    });
+
+    msg!("Password entries {} and {} linked", entry_id, linked_entry_id);
+    Ok(())
+}
+
+/// Unlink two entries
+///
+/// If the linked entry no longer exists, only `entry_id`'s side is cleared.
+///
+/// # Errors
+/// * `EntryLinkNotFound` - Neither entry records the link
+/// * `InvalidEntryLink` - `linked_chunk` passed for a same-chunk link, or
+///   missing for a cross-chunk one
+/// * `EntryNotFound` - `entry_id` is not in its chunk
+pub fn unlink_entries_handler(
+    ctx: Context<LinkEntries>,
+    chunk_index: u16,
+    entry_id: u64,
+    linked_chunk_index: u16,
+    linked_entry_id: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let linked_chunk = match accounts.linked_chunk.as_mut() {
+        Some(linked_chunk) => {
+            require!(linked_chunk_index != chunk_index, LockboxError::InvalidEntryLink);
+            linked_chunk
+        }
+        None => {
+            require!(linked_chunk_index == chunk_index, LockboxError::InvalidEntryLink);
+            &mut accounts.storage_chunk
+        }
+    };
+    let mut removed = false;
+    if let Ok(linked) = linked_chunk.get_entry_header_mut(linked_entry_id) {
+        removed |= linked.remove_link(entry_id);
+        linked_chunk.last_modified = current_timestamp;
+    }
+
+    let storage_chunk = &mut accounts.storage_chunk;
+    removed |= storage_chunk.get_entry_header_mut(entry_id)?.remove_link(linked_entry_id);
+    require!(removed, LockboxError::EntryLinkNotFound);
+    storage_chunk.last_modified = current_timestamp;
+
+    emit!(EntriesLinkedEvent {
+        master_lockbox: accounts.master_lockbox.key(),
+        entry_id,
+        linked_entry_id,
+        linked: false,
+    });
+
+    msg!("Password entries {} and {} unlinked", entry_id, linked_entry_id);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct EntriesLinkedEvent {
+    pub master_lockbox: Pubkey,
+    pub entry_id: u64,
+    pub linked_entry_id: u64,
+    /// False when the entries were unlinked
+    pub linked: bool,
+}
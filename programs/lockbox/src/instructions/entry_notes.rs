@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use crate::state::{EntryNotes, MasterLockbox, StorageChunk};
+
+/// Append an encrypted note to an entry
+#[derive(Accounts)]
+#[instruction(chunk_index: u16, entry_id: u64)]
+pub struct AppendEntryNote<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Chunk holding the annotated entry (read-only)
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + EntryNotes::INIT_SPACE,
+        seeds = [
+            EntryNotes::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &entry_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub entry_notes: Account<'info, EntryNotes>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Append an encrypted note without rewriting the entry ciphertext
+///
+/// Notes live in a per-entry `EntryNotes` PDA created on first use and are
+/// capped at `EntryNotes::MAX_NOTES` per entry.
+///
+/// # Errors
+/// * `EntryNotFound` - Entry is not in the given chunk
+/// * `EntryIsAlias` - Notes belong on the alias target
+/// * `InvalidDataSize` - Note is not a valid AEAD payload or exceeds 128 bytes
+/// * `EntryNotesFull` - Entry already has the maximum number of notes
+pub fn append_entry_note_handler(
+    ctx: Context<AppendEntryNote>,
+    _chunk_index: u16,
+    entry_id: u64,
+    encrypted_note: Vec<u8>,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let entry_notes = &mut ctx.accounts.entry_notes;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // SECURITY: Rate limiting
+    require!(
+        master_lockbox.check_rate_limit(current_timestamp, 1),
+        crate::errors::LockboxError::RateLimitExceeded
+    );
+
+    // SECURITY: Validate AEAD ciphertext format (24-byte nonce + 16-byte tag)
+    const MIN_AEAD_SIZE: usize = 40;
+    require!(
+        encrypted_note.len() >= MIN_AEAD_SIZE,
+        crate::errors::LockboxError::InvalidDataSize
+    );
+
+    // Check subscription is active
+    require!(
+        master_lockbox.is_subscription_active(current_timestamp),
+        crate::errors::LockboxError::SubscriptionExpired
+    );
+
+    let header = ctx.accounts.storage_chunk.get_entry_header(entry_id)?;
+    require!(!header.is_alias(), crate::errors::LockboxError::EntryIsAlias);
+
+    // First note for this entry: initialize the side account
+    if entry_notes.version == 0 {
+        entry_notes.version = EntryNotes::CURRENT_VERSION;
+        entry_notes.master_lockbox = master_lockbox.key();
+        entry_notes.owner = ctx.accounts.owner.key();
+        entry_notes.entry_id = entry_id;
+        entry_notes.notes = Vec::new();
+        entry_notes.bump = ctx.bumps.entry_notes;
+    }
+
+    entry_notes.append(encrypted_note, current_timestamp)?;
+    master_lockbox.touch(current_timestamp);
+
+    msg!(
+        "Note appended to entry {} ({}/{})",
+        entry_id,
+        entry_notes.notes.len(),
+        EntryNotes::MAX_NOTES
+    );
+
+    Ok(())
+}
+
+/// Close an entry's notes account
+#[derive(Accounts)]
+#[instruction(entry_id: u64)]
+pub struct CloseEntryNotes<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            EntryNotes::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &entry_id.to_le_bytes()
+        ],
+        bump = entry_notes.bump,
+        constraint = entry_notes.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub entry_notes: Account<'info, EntryNotes>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Delete all notes for an entry and reclaim rent
+///
+/// Call after deleting the entry, or to clear its notes.
+pub fn close_entry_notes_handler(ctx: Context<CloseEntryNotes>, entry_id: u64) -> Result<()> {
+    msg!(
+        "Closed {} notes for entry {}",
+        ctx.accounts.entry_notes.notes.len(),
+        entry_id
+    );
+    Ok(())
+}
@@ -0,0 +1,267 @@
+/**
+ * Entry Tag Instructions
+ *
+ * Entries carry up to `MAX_TAGS` blind tags on top of their single
+ * category. The vault's `TagRegistry` maps each client-computed tag hash
+ * (`HMAC(tag_key, tag_name)`) to a slot, and an entry header's `tag_mask`
+ * has the bit of every slot it is tagged with. Tagging creates the registry
+ * and registers the tag on first use.
+ *
+ * Tags live on an entry's head segment. Deleting a tag clears its bit from
+ * every entry, so it needs every chunk of the vault as remaining accounts;
+ * untagging the last entry leaves the tag registered.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::instructions::chunk_set::ChunkSet;
+use crate::state::{MasterLockbox, StorageChunk, TagRegistry};
+
+/// Add or remove a tag on one entry
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct TagEntry<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + TagRegistry::INIT_SPACE,
+        seeds = [TagRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub tag_registry: Account<'info, TagRegistry>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Tag an entry, registering the tag if it is new
+///
+/// # Errors
+/// * `EntryNotFound` - Entry's head segment is not in the chunk
+/// * `EntryTrashed` - Entry is in the trash
+/// * `InvalidTagHash` - Tag hash is all zeroes
+/// * `TagRegistryFull` - Tag is new and every slot is in use
+pub fn tag_entry_handler(
+    ctx: Context<TagEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+    tag_hash: [u8; 32],
+) -> Result<()> {
+    let tag_registry = &mut ctx.accounts.tag_registry;
+    if tag_registry.version == 0 {
+        tag_registry.version = TagRegistry::CURRENT_VERSION;
+        tag_registry.master_lockbox = ctx.accounts.master_lockbox.key();
+        tag_registry.bump = ctx.bumps.tag_registry;
+    }
+    let mask = tag_registry.register(tag_hash)?;
+
+    let header = ctx.accounts.storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.is_continuation(), LockboxError::EntryNotFound);
+    require!(!header.is_trashed(), LockboxError::EntryTrashed);
+    header.tag_mask |= mask;
+
+    emit!(EntryTagsChangedEvent {
+        master_lockbox: tag_registry.master_lockbox,
+        entry_id,
+        tag_hash,
+        tagged: true,
+    });
+
+    msg!("Password entry {} tagged", entry_id);
+    Ok(())
+}
+
+/// Remove a tag from an entry
+///
+/// # Errors
+/// * `TagNotFound` - Tag is not registered
+/// * `EntryNotFound` - Entry's head segment is not in the chunk
+pub fn untag_entry_handler(
+    ctx: Context<TagEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+    tag_hash: [u8; 32],
+) -> Result<()> {
+    let mask = ctx.accounts.tag_registry.mask_of(&tag_hash)?;
+
+    let header = ctx.accounts.storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.is_continuation(), LockboxError::EntryNotFound);
+    header.tag_mask &= !mask;
+
+    emit!(EntryTagsChangedEvent {
+        master_lockbox: ctx.accounts.master_lockbox.key(),
+        entry_id,
+        tag_hash,
+        tagged: false,
+    });
+
+    msg!("Password entry {} untagged", entry_id);
+    Ok(())
+}
+
+/// Delete a tag from the registry and every entry
+///
+/// Every chunk of the vault is passed as a writable remaining account.
+#[derive(Accounts)]
+pub struct DeleteTag<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [TagRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = tag_registry.bump
+    )]
+    pub tag_registry: Account<'info, TagRegistry>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Free the tag's slot after clearing it from every entry
+///
+/// # Errors
+/// * `TagNotFound` - Tag is not registered
+/// * `TagChunksMissing` - Not every chunk of the vault was passed
+/// * `InvalidChunkAccount` - A remaining account is not a chunk of the vault
+pub fn delete_tag_handler(ctx: Context<DeleteTag>, tag_hash: [u8; 32]) -> Result<()> {
+    let mask = ctx.accounts.tag_registry.unregister(&tag_hash)?;
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, master_lockbox, &[])?;
+    require!(
+        chunks.len() == master_lockbox.storage_chunks.len(),
+        LockboxError::TagChunksMissing
+    );
+    let cleared: u32 = chunks.iter_mut().map(|chunk| chunk.clear_tag(mask)).sum();
+    chunks.commit(master_lockbox)?;
+
+    emit!(TagDeletedEvent {
+        master_lockbox: master_lockbox.key(),
+        tag_hash,
+        entries_cleared: cleared,
+    });
+
+    msg!("Tag deleted from {} entries", cleared);
+    Ok(())
+}
+
+/// List the entries of one chunk carrying a tag
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct ListTaggedEntries<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    #[account(
+        seeds = [TagRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = tag_registry.bump
+    )]
+    pub tag_registry: Account<'info, TagRegistry>,
+}
+
+/// Return the IDs of the chunk's live entries carrying `tag_hash`
+///
+/// A chunk holds at most 100 entries, so the result always fits in return
+/// data.
+///
+/// # Errors
+/// * `TagNotFound` - Tag is not registered
+pub fn list_tagged_entries_handler(
+    ctx: Context<ListTaggedEntries>,
+    _chunk_index: u16,
+    tag_hash: [u8; 32],
+) -> Result<Vec<u64>> {
+    let mask = ctx.accounts.tag_registry.mask_of(&tag_hash)?;
+    Ok(ctx.accounts.storage_chunk.entries_with_tag(mask))
+}
+
+/// Close the tag registry and reclaim its rent
+///
+/// Every tag must be deleted first, which also clears it from the entries,
+/// so a registry created later never meets stale tag bits.
+#[derive(Accounts)]
+pub struct CloseTagRegistry<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [TagRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = tag_registry.bump,
+        constraint = tag_registry.tags.iter().all(|tag| *tag == [0u8; 32]) @ LockboxError::TagsStillRegistered
+    )]
+    pub tag_registry: Account<'info, TagRegistry>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_tag_registry_handler(_ctx: Context<CloseTagRegistry>) -> Result<()> {
+    msg!("Tag registry closed - rent reclaimed");
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct EntryTagsChangedEvent {
+    pub master_lockbox: Pubkey,
+    pub entry_id: u64,
+    pub tag_hash: [u8; 32],
+    pub tagged: bool,
+}
+
+#[event]
+pub struct TagDeletedEvent {
+    pub master_lockbox: Pubkey,
+    pub tag_hash: [u8; 32],
+    pub entries_cleared: u32,
+}
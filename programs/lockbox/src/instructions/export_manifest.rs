@@ -0,0 +1,106 @@
+/**
+ * Export Manifest Commitments
+ *
+ * Before leaving for another password manager, a client builds an export
+ * manifest from the vault's chunks (format version, entry count, and each
+ * entry's ID, type, size and ciphertext hash; never plaintext) and commits
+ * its SHA-256 with `commit_export_manifest`. The manifest itself stays with
+ * the user; re-hashing it later and matching the on-chain commitment proves
+ * what was exported and when. `lockbox_client::export` defines the
+ * encoding.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{ExportCommitment, ExportLog, MasterLockbox, EXPORT_MANIFEST_VERSION};
+
+/// Commit an export manifest hash
+#[derive(Accounts)]
+pub struct CommitExportManifest<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ExportLog::INIT_SPACE,
+        seeds = [ExportLog::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub export_log: Account<'info, ExportLog>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Record `manifest_hash` in the vault's export log
+///
+/// The log is created on first use. The program cannot see the manifest, so
+/// `entry_count` is recorded as the client reports it.
+///
+/// # Errors
+/// * `UnsupportedExportManifest` - `format_version` is not
+///   `EXPORT_MANIFEST_VERSION`
+pub fn commit_export_manifest_handler(
+    ctx: Context<CommitExportManifest>,
+    manifest_hash: [u8; 32],
+    format_version: u8,
+    entry_count: u32,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let export_log = &mut ctx.accounts.export_log;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        format_version == EXPORT_MANIFEST_VERSION,
+        LockboxError::UnsupportedExportManifest
+    );
+
+    // First commitment for this vault: initialize the log
+    if export_log.version == 0 {
+        export_log.version = ExportLog::CURRENT_VERSION;
+        export_log.master_lockbox = master_lockbox.key();
+        export_log.owner = ctx.accounts.owner.key();
+        export_log.commitments = Vec::new();
+        export_log.bump = ctx.bumps.export_log;
+    }
+
+    export_log.record(ExportCommitment {
+        manifest_hash,
+        format_version,
+        entry_count,
+        committed_at: current_timestamp,
+    });
+    master_lockbox.touch(current_timestamp);
+
+    emit!(ExportManifestCommittedEvent {
+        owner: master_lockbox.owner,
+        manifest_hash,
+        format_version,
+        entry_count,
+        committed_at: current_timestamp,
+    });
+
+    msg!("Export manifest committed ({} entries)", entry_count);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct ExportManifestCommittedEvent {
+    pub owner: Pubkey,
+    pub manifest_hash: [u8; 32],
+    pub format_version: u8,
+    pub entry_count: u32,
+    pub committed_at: i64,
+}
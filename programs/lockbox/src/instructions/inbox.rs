@@ -0,0 +1,153 @@
+/**
+ * Inbox Instructions
+ *
+ * Gives recipients an on-chain way to discover what was addressed to them.
+ * Instructions that create an offer for another wallet (adding a guardian
+ * or emergency contact) take the recipient's inbox as an optional account
+ * and deliver a pointer to it. The recipient accepts or dismisses items;
+ * accepting only clears the pointer, so clients pair it with the offer's
+ * own accept instruction (e.g. `accept_guardianship`) in one transaction.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{Inbox, InboxItem, InboxItemKind};
+use crate::errors::LockboxError;
+
+/// Deliver a pointer to `inbox`, emitting `InboxItemDeliveredEvent` on success
+pub(crate) fn deliver_to_inbox(
+    inbox: &mut Inbox,
+    kind: InboxItemKind,
+    sender: Pubkey,
+    account: Pubkey,
+    current_timestamp: i64,
+) {
+    let item = InboxItem { kind, sender, account, received_at: current_timestamp };
+    if inbox.deliver(item) {
+        emit!(InboxItemDeliveredEvent { recipient: inbox.owner, kind, sender, account });
+    } else {
+        msg!("Inbox {} full or already notified; item not delivered", inbox.owner);
+    }
+}
+
+/// Create the signer's inbox
+#[derive(Accounts)]
+pub struct InitializeInbox<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Inbox::INIT_SPACE,
+        seeds = [Inbox::SEEDS_PREFIX, owner.key().as_ref()],
+        bump
+    )]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_inbox_handler(ctx: Context<InitializeInbox>) -> Result<()> {
+    let inbox = &mut ctx.accounts.inbox;
+    inbox.version = Inbox::CURRENT_VERSION;
+    inbox.owner = ctx.accounts.owner.key();
+    inbox.items = Vec::new();
+    inbox.bump = ctx.bumps.inbox;
+
+    msg!("Inbox initialized for {}", inbox.owner);
+    Ok(())
+}
+
+/// Accept or dismiss an inbox item
+#[derive(Accounts)]
+pub struct ResolveInboxItem<'info> {
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = inbox.bump,
+        constraint = inbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub inbox: Account<'info, Inbox>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn accept_inbox_item_handler(
+    ctx: Context<ResolveInboxItem>,
+    kind: InboxItemKind,
+    account: Pubkey,
+) -> Result<()> {
+    let item = ctx.accounts.inbox.take(kind, &account)?;
+
+    emit!(InboxItemResolvedEvent {
+        recipient: ctx.accounts.owner.key(),
+        kind,
+        sender: item.sender,
+        account,
+        accepted: true,
+    });
+
+    msg!("Inbox item {:?} accepted", kind);
+    Ok(())
+}
+
+pub fn dismiss_inbox_item_handler(
+    ctx: Context<ResolveInboxItem>,
+    kind: InboxItemKind,
+    account: Pubkey,
+) -> Result<()> {
+    let item = ctx.accounts.inbox.take(kind, &account)?;
+
+    emit!(InboxItemResolvedEvent {
+        recipient: ctx.accounts.owner.key(),
+        kind,
+        sender: item.sender,
+        account,
+        accepted: false,
+    });
+
+    msg!("Inbox item {:?} dismissed", kind);
+    Ok(())
+}
+
+/// Close the signer's inbox and reclaim its rent
+#[derive(Accounts)]
+pub struct CloseInbox<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [Inbox::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = inbox.bump,
+        constraint = inbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_inbox_handler(_ctx: Context<CloseInbox>) -> Result<()> {
+    msg!("Inbox closed - rent reclaimed");
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct InboxItemDeliveredEvent {
+    pub recipient: Pubkey,
+    pub kind: InboxItemKind,
+    pub sender: Pubkey,
+    pub account: Pubkey,
+}
+
+#[event]
+pub struct InboxItemResolvedEvent {
+    pub recipient: Pubkey,
+    pub kind: InboxItemKind,
+    pub sender: Pubkey,
+    pub account: Pubkey,
+    pub accepted: bool,
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::{MasterLockbox, StorageChunk, StorageChunkInfo, StorageType};
+use crate::errors::LockboxError;
+use crate::state::{MasterLockbox, OwnerKind, StorageChunk, StorageChunkInfo, StorageType};
 
 /// Initialize a new master lockbox account for the user
 #[derive(Accounts)]
@@ -33,6 +34,35 @@ pub fn handler(ctx: Context<InitializeMasterLockbox>) -> Result<()> {
     Ok(())
 }
 
+/// Initialize a master lockbox owned by another program's PDA
+///
+/// `owner_seeds` (including the bump) must derive `owner` under `program_id`.
+/// Only `program_id` can produce that PDA's signature, so every later
+/// instruction signed by `owner` is known to come from that program.
+pub fn initialize_program_owned_handler(
+    ctx: Context<InitializeMasterLockbox>,
+    program_id: Pubkey,
+    owner_seeds: Vec<Vec<u8>>,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+
+    let seeds: Vec<&[u8]> = owner_seeds.iter().map(|s| s.as_slice()).collect();
+    let derived = Pubkey::create_program_address(&seeds, &program_id)
+        .map_err(|_| LockboxError::InvalidOwnerSeeds)?;
+    require_keys_eq!(derived, owner, LockboxError::InvalidOwnerSeeds);
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let bump = ctx.bumps.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    master_lockbox.initialize(owner, bump, current_timestamp)?;
+    master_lockbox.owner_kind = OwnerKind::ProgramDerived { program_id };
+
+    msg!("Program-owned lockbox initialized: owner={}, program={}", owner, program_id);
+
+    Ok(())
+}
+
 /// Initialize a new storage chunk
 #[derive(Accounts)]
 #[instruction(chunk_index: u16, initial_capacity: u32)]
@@ -41,10 +71,10 @@ pub struct InitializeStorageChunk<'info> {
     /// Uses realloc to dynamically grow the account as more chunks are added
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized,
-        realloc = MasterLockbox::calculate_space(master_lockbox.storage_chunks.len() + 1),
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        realloc = master_lockbox.space_for_chunks(master_lockbox.storage_chunks.len() + 1),
         realloc::payer = owner,
         realloc::zero = false,
     )]
@@ -53,7 +83,7 @@ pub struct InitializeStorageChunk<'info> {
     #[account(
         init,
         payer = owner,
-        space = StorageChunk::BASE_SPACE + initial_capacity as usize,
+        space = StorageChunk::space(initial_capacity),
         seeds = [
             StorageChunk::SEEDS_PREFIX,
             master_lockbox.key().as_ref(),
@@ -81,15 +111,15 @@ pub fn initialize_storage_chunk_handler(
     let bump = ctx.bumps.storage_chunk;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
-    // Validate chunk index matches expected sequence
+    // Validate chunk index is the next in sequence or a closed chunk's slot
     require!(
-        chunk_index == master_lockbox.storage_chunks_count,
+        chunk_index <= master_lockbox.storage_chunks_count,
         crate::errors::LockboxError::InvalidChunkIndex
     );
 
     // Check for duplicate chunk (shouldn't happen with PDA, but defensive check)
     require!(
-        !master_lockbox.storage_chunks.iter().any(|c| c.chunk_index == chunk_index),
+        master_lockbox.is_chunk_index_available(chunk_index),
         crate::errors::LockboxError::DuplicateChunk
     );
 
@@ -107,11 +137,14 @@ pub fn initialize_storage_chunk_handler(
     let new_total_capacity = master_lockbox.total_capacity
         .checked_add(initial_capacity as u64)
         .ok_or(crate::errors::LockboxError::InvalidDataSize)?;
-    let max_capacity = master_lockbox.subscription_tier.max_capacity();
-    require!(
+    let max_capacity = master_lockbox.capacity_limit(current_timestamp);
+    crate::errors::require_with_context(
         new_total_capacity <= max_capacity,
-        crate::errors::LockboxError::InsufficientStorageCapacity
-    );
+        crate::errors::LockboxError::InsufficientStorageCapacity,
+        crate::errors::ErrorField::StorageCapacity,
+        max_capacity as i64,
+        new_total_capacity as i64,
+    )?;
 
     // Initialize chunk
     storage_chunk.initialize(
@@ -133,6 +166,7 @@ pub fn initialize_storage_chunk_handler(
         data_type,
         created_at: current_timestamp,
         last_modified: current_timestamp,
+        layout_epoch: storage_chunk.layout_epoch,
     };
 
     master_lockbox.add_chunk(chunk_info)?;
@@ -0,0 +1,71 @@
+/**
+ * Accounting Invariants
+ *
+ * Entry handlers keep redundant counters in sync by hand: each chunk's
+ * `entry_count` mirrors its header list, and the master lockbox's
+ * `storage_used` mirrors its chunks' `size_used`. With the
+ * `invariant-checks` feature, mutating handlers re-check these before
+ * returning, so a bookkeeping bug fails the transaction that introduced it
+ * instead of surfacing much later.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{MasterLockbox, StorageChunk};
+
+/// First broken invariant among the given accounts, if any
+pub fn find_violation(master_lockbox: &MasterLockbox, chunks: &[&StorageChunk]) -> Option<Invariant> {
+    if let Some(chunk) = chunks.iter().find(|chunk| !chunk.is_entry_count_consistent()) {
+        return Some(Invariant::ChunkEntryCount { chunk_index: chunk.chunk_index });
+    }
+    if !master_lockbox.is_storage_used_consistent() {
+        return Some(Invariant::StorageUsed);
+    }
+    None
+}
+
+/// Fail with `InvariantViolation` if the accounts' accounting is broken
+///
+/// No-op unless the program is built with `invariant-checks`.
+///
+/// # Errors
+/// * `InvariantViolation` - An invariant does not hold; an
+///   `InvariantViolationEvent` names it
+pub fn enforce_invariants(
+    master_key: Pubkey,
+    master_lockbox: &MasterLockbox,
+    chunks: &[&StorageChunk],
+) -> Result<()> {
+    if !cfg!(feature = "invariant-checks") {
+        return Ok(());
+    }
+
+    if let Some(invariant) = find_violation(master_lockbox, chunks) {
+        emit!(InvariantViolationEvent {
+            master_lockbox: master_key,
+            invariant,
+        });
+        return err!(LockboxError::InvariantViolation);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Accounting invariant checked by `enforce_invariants`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Invariant {
+    /// `entry_count` differs from the number of entry headers
+    ChunkEntryCount { chunk_index: u16 },
+
+    /// `storage_used` differs from the sum of the chunks' `size_used`
+    StorageUsed,
+}
+
+#[event]
+pub struct InvariantViolationEvent {
+    pub master_lockbox: Pubkey,
+    pub invariant: Invariant,
+}
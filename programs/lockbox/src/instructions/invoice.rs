@@ -0,0 +1,286 @@
+/**
+ * Organization Invoices
+ *
+ * Enterprise procurement pays against an invoice, from an organization's
+ * token account, rather than upgrading from a wallet. The program admin
+ * issues an `Invoice` to a vault with `issue_invoice`. Anyone who controls
+ * a token account in the invoice currency (the receiver's mint, typically
+ * a stablecoin) can settle it with `pay_invoice`: the amount moves to the
+ * configured receiver and the vault's subscription is extended in the same
+ * instruction. An open invoice can be withdrawn with `void_invoice`.
+ *
+ * Invoice payments are in tokens, so they are not added to the vault's
+ * lamport `billing_period_paid`; the paid `Invoice` account and
+ * `InvoicePaidEvent` are the settlement record.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use crate::errors::LockboxError;
+use crate::state::{
+    cluster_timestamp, Invoice, InvoiceStatus, MasterLockbox, ProgramConfig, SubscriptionStatus,
+    SubscriptionTier, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
+};
+use super::subscription::SubscriptionRestoredEvent;
+
+/// SPL Token `Transfer` instruction tag (shared by Token-2022)
+const TOKEN_TRANSFER_TAG: u8 = 3;
+
+/// Issue an invoice to a vault (admin only)
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct IssueInvoice<'info> {
+    #[account(
+        seeds = [ProgramConfig::SEEDS_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ LockboxError::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Invoice::INIT_SPACE,
+        seeds = [Invoice::SEEDS_PREFIX, master_lockbox.key().as_ref(), &invoice_id.to_le_bytes()],
+        bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Issue an invoice for `period` seconds of `tier`
+///
+/// # Arguments
+/// * `invoice_id` - Admin-chosen number, unique per vault
+/// * `tier` - Tier paid for; the vault's current tier or a higher one
+/// * `amount` - Amount due, in base units of the invoice receiver's mint
+/// * `period` - Seconds of subscription bought, up to `MAX_INVOICE_PERIOD`
+/// * `memo_hash` - Hash of the off-chain invoice memo
+///
+/// # Errors
+/// * `Unauthorized` - Signer is not the program admin
+/// * `InvalidInvoice` - Free tier, zero amount, or period out of range
+pub fn issue_invoice_handler(
+    ctx: Context<IssueInvoice>,
+    invoice_id: u64,
+    tier: SubscriptionTier,
+    amount: u64,
+    period: i64,
+    memo_hash: [u8; 32],
+) -> Result<()> {
+    Invoice::check_terms(tier, amount, period)?;
+
+    let invoice = &mut ctx.accounts.invoice;
+    invoice.version = Invoice::CURRENT_VERSION;
+    invoice.master_lockbox = ctx.accounts.master_lockbox.key();
+    invoice.invoice_id = invoice_id;
+    invoice.issued_by = ctx.accounts.admin.key();
+    invoice.tier = tier;
+    invoice.amount = amount;
+    invoice.period = period;
+    invoice.memo_hash = memo_hash;
+    invoice.status = InvoiceStatus::Open;
+    invoice.issued_at = cluster_timestamp()?;
+    invoice.paid_at = 0;
+    invoice.bump = ctx.bumps.invoice;
+
+    emit!(InvoiceIssuedEvent {
+        master_lockbox: invoice.master_lockbox,
+        invoice_id,
+        tier,
+        amount,
+        period,
+        memo_hash,
+    });
+
+    msg!("Invoice {} issued to {} for {} units", invoice_id, invoice.master_lockbox, amount);
+    Ok(())
+}
+
+/// Settle an invoice from a token account
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct PayInvoice<'info> {
+    #[account(seeds = [ProgramConfig::SEEDS_PREFIX], bump = program_config.bump)]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [Invoice::SEEDS_PREFIX, master_lockbox.key().as_ref(), &invoice_id.to_le_bytes()],
+        bump = invoice.bump
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    /// CHECK: Organization's token account paying the invoice; the token
+    /// program checks its mint and that `authority` may spend from it
+    #[account(mut)]
+    pub source_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Token account receiving invoice payments, pinned by the config
+    #[account(mut, address = program_config.invoice_receiver)]
+    pub invoice_receiver: UncheckedAccount<'info>,
+
+    /// Owner or delegate of `source_token_account`
+    pub authority: Signer<'info>,
+
+    /// CHECK: SPL Token or Token-2022, matching the token accounts
+    #[account(
+        constraint = token_program.key() == SPL_TOKEN_PROGRAM_ID
+            || token_program.key() == SPL_TOKEN_2022_PROGRAM_ID @ LockboxError::InvalidTokenProgram
+    )]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+/// Pay the invoice and extend the vault's subscription
+///
+/// Anyone may pay; the vault owner need not sign. A payment for the
+/// vault's current tier extends it by the invoice's period, from the
+/// current expiry if still paid up; a payment for a higher tier upgrades
+/// the vault for the period starting now.
+///
+/// # Errors
+/// * `InvoiceNotOpen` - Already paid
+/// * `InvalidTierUpgrade` - The vault has moved above the invoice's tier
+/// * `InvalidTokenProgram` - Token program is not SPL Token or Token-2022
+pub fn pay_invoice_handler(ctx: Context<PayInvoice>, invoice_id: u64) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let invoice = &mut ctx.accounts.invoice;
+    let was_past_due = master_lockbox.subscription_status == SubscriptionStatus::PastDue;
+    invoice.settle(master_lockbox, current_timestamp)?;
+
+    let mut data = Vec::with_capacity(9);
+    data.push(TOKEN_TRANSFER_TAG);
+    data.extend_from_slice(&invoice.amount.to_le_bytes());
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.token_program.key(),
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.source_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.invoice_receiver.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+            ],
+            data,
+        },
+        &[
+            ctx.accounts.source_token_account.to_account_info(),
+            ctx.accounts.invoice_receiver.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+    )?;
+
+    master_lockbox.touch(current_timestamp);
+
+    if was_past_due {
+        emit!(SubscriptionRestoredEvent {
+            owner: master_lockbox.owner,
+            tier: master_lockbox.subscription_tier,
+            expires: master_lockbox.subscription_expires,
+        });
+    }
+
+    emit!(InvoicePaidEvent {
+        master_lockbox: invoice.master_lockbox,
+        invoice_id,
+        payer: ctx.accounts.authority.key(),
+        amount: invoice.amount,
+        tier: master_lockbox.subscription_tier,
+        subscription_expires: master_lockbox.subscription_expires,
+    });
+
+    msg!(
+        "Invoice {} paid: {:?} until {}",
+        invoice_id,
+        master_lockbox.subscription_tier,
+        master_lockbox.subscription_expires
+    );
+    Ok(())
+}
+
+/// Withdraw an open invoice (admin only)
+#[derive(Accounts)]
+#[instruction(invoice_id: u64)]
+pub struct VoidInvoice<'info> {
+    #[account(
+        seeds = [ProgramConfig::SEEDS_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ LockboxError::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [Invoice::SEEDS_PREFIX, invoice.master_lockbox.as_ref(), &invoice_id.to_le_bytes()],
+        bump = invoice.bump,
+        constraint = invoice.status == InvoiceStatus::Open @ LockboxError::InvoiceNotOpen
+    )]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// Close an unpaid invoice, returning its rent to the admin
+///
+/// # Errors
+/// * `Unauthorized` - Signer is not the program admin
+/// * `InvoiceNotOpen` - Already paid; paid invoices are kept as records
+pub fn void_invoice_handler(ctx: Context<VoidInvoice>, invoice_id: u64) -> Result<()> {
+    emit!(InvoiceVoidedEvent {
+        master_lockbox: ctx.accounts.invoice.master_lockbox,
+        invoice_id,
+    });
+
+    msg!("Invoice {} voided", invoice_id);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct InvoiceIssuedEvent {
+    pub master_lockbox: Pubkey,
+    pub invoice_id: u64,
+    pub tier: SubscriptionTier,
+    pub amount: u64,
+    pub period: i64,
+    pub memo_hash: [u8; 32],
+}
+
+#[event]
+pub struct InvoicePaidEvent {
+    pub master_lockbox: Pubkey,
+    pub invoice_id: u64,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub tier: SubscriptionTier,
+    pub subscription_expires: i64,
+}
+
+#[event]
+pub struct InvoiceVoidedEvent {
+    pub master_lockbox: Pubkey,
+    pub invoice_id: u64,
+}
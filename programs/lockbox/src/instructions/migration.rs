@@ -0,0 +1,1031 @@
+/**
+ * Account Schema Migrations
+ *
+ * Every program-owned account carries a `version` byte directly after the
+ * Anchor discriminator. Accounts created before versioning was introduced
+ * (version 0) have no such byte and must be rewritten with
+ * `migrate_account_v1` before the program can deserialize them again.
+ *
+ * Future layout changes follow the same pattern: bump `CURRENT_VERSION` on
+ * the affected account, keep the previous layout as a private `...Vn`
+ * struct here, and add a `migrate_account_vN` instruction.
+ *
+ * Version 2 so far changes EmergencyAccess (countdown notifications),
+ * RecoveryConfig (guardian invitation expiry and acceptance records),
+ * RecoveryConfigV2 (recovery cooldown modes), MasterLockbox (billing
+ * periods, capacity add-ons, loyalty, dunning, the deletion tombstone, the
+ * writer lease, the category registry and index flags, the creator, the
+ * transfer listing flag, shared vault usage and per-chunk layout epochs) and StorageChunk
+ * (layout epoch); version 3 changes EmergencyAccess (contact invitation
+ * expiry and export consent) and StorageChunk (entry segment links);
+ * version 4 changes StorageChunk (entry tag masks, rotation dates, TOTP
+ * parents and entry links).
+ *
+ * Security:
+ * - Only the owner recorded in the account may migrate it
+ * - Migration is a pure layout rewrite: no field values change
+ * - The owner pays any additional rent for the larger layout
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::errors::LockboxError;
+use crate::state::{
+    Category, CategoryRegistry, CooldownMode, DataEntryHeader, EmergencyAccess, EmergencyAccessLevel, EmergencyContact, EmergencyContactStatus,
+    EmergencyStatus, Guardian, GuardianStatus, GuardianV2,
+    MasterLockbox, OwnerKind, PasswordEntryType, RecoveryConfig, RecoveryConfigV2, RecoveryRequest, RecoveryRequestV2, StorageChunk,
+    StorageChunkInfo, StorageType, SubscriptionStatus, SubscriptionTier, DEFAULT_DUNNING_WINDOW, DEFAULT_INVITATION_TTL,
+    DEFAULT_RECOVERY_COOLDOWN, DEFAULT_TRASH_RETENTION, HEAT_MAP_BUCKETS, MAX_RECENT_ENTRIES,
+};
+
+/// Offset of the first field after the discriminator
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// StorageChunkInfo layout before layout epochs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+struct StorageChunkInfoV1 {
+    chunk_address: Pubkey,
+    chunk_index: u16,
+    max_capacity: u32,
+    size_used: u32,
+    data_type: StorageType,
+    created_at: i64,
+    last_modified: i64,
+}
+
+impl From<StorageChunkInfoV1> for StorageChunkInfo {
+    fn from(v1: StorageChunkInfoV1) -> Self {
+        Self {
+            chunk_address: v1.chunk_address,
+            chunk_index: v1.chunk_index,
+            max_capacity: v1.max_capacity,
+            size_used: v1.size_used,
+            data_type: v1.data_type,
+            created_at: v1.created_at,
+            last_modified: v1.last_modified,
+            layout_epoch: 0,
+        }
+    }
+}
+
+/// MasterLockbox layout before versioning (no `recent_entries`, `owner_kind`)
+#[derive(AnchorDeserialize)]
+struct MasterLockboxV0 {
+    owner: Pubkey,
+    total_entries: u64,
+    storage_chunks_count: u16,
+    subscription_tier: SubscriptionTier,
+    last_accessed: i64,
+    subscription_expires: i64,
+    total_capacity: u64,
+    storage_used: u64,
+    storage_chunks: Vec<StorageChunkInfoV1>,
+    encrypted_index: Vec<u8>,
+    next_entry_id: u64,
+    categories_count: u32,
+    created_at: i64,
+    bump: u8,
+}
+
+impl From<MasterLockboxV0> for MasterLockboxV1 {
+    fn from(v0: MasterLockboxV0) -> Self {
+        Self {
+            version: 1,
+            owner: v0.owner,
+            total_entries: v0.total_entries,
+            storage_chunks_count: v0.storage_chunks_count,
+            subscription_tier: v0.subscription_tier,
+            last_accessed: v0.last_accessed,
+            subscription_expires: v0.subscription_expires,
+            total_capacity: v0.total_capacity,
+            storage_used: v0.storage_used,
+            storage_chunks: v0.storage_chunks,
+            encrypted_index: v0.encrypted_index,
+            next_entry_id: v0.next_entry_id,
+            categories_count: v0.categories_count,
+            created_at: v0.created_at,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: DEFAULT_TRASH_RETENTION,
+            owner_kind: OwnerKind::Wallet,
+            encrypted_settings: Vec::new(),
+            bump: v0.bump,
+        }
+    }
+}
+
+/// MasterLockbox layout before billing periods
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct MasterLockboxV1 {
+    version: u8,
+    owner: Pubkey,
+    total_entries: u64,
+    storage_chunks_count: u16,
+    subscription_tier: SubscriptionTier,
+    last_accessed: i64,
+    subscription_expires: i64,
+    total_capacity: u64,
+    storage_used: u64,
+    storage_chunks: Vec<StorageChunkInfoV1>,
+    encrypted_index: Vec<u8>,
+    next_entry_id: u64,
+    categories_count: u32,
+    created_at: i64,
+    recent_entries_enabled: bool,
+    recent_entries: [u64; MAX_RECENT_ENTRIES],
+    trash_retention: i64,
+    owner_kind: OwnerKind,
+    encrypted_settings: Vec<u8>,
+    bump: u8,
+}
+
+impl From<MasterLockboxV1> for MasterLockbox {
+    fn from(v1: MasterLockboxV1) -> Self {
+        Self {
+            version: 2,
+            owner: v1.owner,
+            total_entries: v1.total_entries,
+            storage_chunks_count: v1.storage_chunks_count,
+            subscription_tier: v1.subscription_tier,
+            last_accessed: v1.last_accessed,
+            subscription_expires: v1.subscription_expires,
+            total_capacity: v1.total_capacity,
+            storage_used: v1.storage_used,
+            storage_chunks: v1.storage_chunks.into_iter().map(StorageChunkInfo::from).collect(),
+            encrypted_index: v1.encrypted_index,
+            next_entry_id: v1.next_entry_id,
+            categories_count: v1.categories_count,
+            created_at: v1.created_at,
+            recent_entries_enabled: v1.recent_entries_enabled,
+            recent_entries: v1.recent_entries,
+            trash_retention: v1.trash_retention,
+            owner_kind: v1.owner_kind,
+            // Payments made before the migration were never recorded
+            billing_sequence: 0,
+            billing_period_start: v1.created_at,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; HEAT_MAP_BUCKETS],
+            // Categories can only be created in a registry
+            has_category_registry: v1.categories_count > 0,
+            has_category_index: false,
+            // Recovery overwrote `owner` without moving the PDA, leaving
+            // such vaults unreachable; every vault that can still be
+            // migrated was created by its owner
+            creator: v1.owner,
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: v1.encrypted_settings,
+            bump: v1.bump,
+        }
+    }
+}
+
+/// DataEntryHeader layout before entries could span chunks
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct DataEntryHeaderV2 {
+    entry_id: u64,
+    offset: u32,
+    size: u32,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+    created_at: i64,
+    last_modified: i64,
+    access_count: u32,
+    flags: u8,
+}
+
+impl From<DataEntryHeaderV2> for DataEntryHeaderV3 {
+    fn from(v2: DataEntryHeaderV2) -> Self {
+        Self {
+            entry_id: v2.entry_id,
+            offset: v2.offset,
+            size: v2.size,
+            entry_type: v2.entry_type,
+            category: v2.category,
+            title_hash: v2.title_hash,
+            created_at: v2.created_at,
+            last_modified: v2.last_modified,
+            access_count: v2.access_count,
+            flags: v2.flags,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+        }
+    }
+}
+
+/// DataEntryHeader layout before entry tags, rotation dates, TOTP parents
+/// and entry links
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct DataEntryHeaderV3 {
+    entry_id: u64,
+    offset: u32,
+    size: u32,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+    created_at: i64,
+    last_modified: i64,
+    access_count: u32,
+    flags: u8,
+    next_chunk: u16,
+    next_offset: u32,
+}
+
+impl From<DataEntryHeaderV3> for DataEntryHeader {
+    fn from(v3: DataEntryHeaderV3) -> Self {
+        Self {
+            entry_id: v3.entry_id,
+            offset: v3.offset,
+            size: v3.size,
+            entry_type: v3.entry_type,
+            category: v3.category,
+            title_hash: v3.title_hash,
+            created_at: v3.created_at,
+            last_modified: v3.last_modified,
+            access_count: v3.access_count,
+            flags: v3.flags,
+            next_chunk: v3.next_chunk,
+            next_offset: v3.next_offset,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        }
+    }
+}
+
+/// StorageChunk layout before layout epochs
+#[derive(AnchorDeserialize)]
+struct StorageChunkV1 {
+    // Checked by `check_schema_version` before the body is decoded
+    #[allow(dead_code)]
+    version: u8,
+    master_lockbox: Pubkey,
+    owner: Pubkey,
+    chunk_index: u16,
+    max_capacity: u32,
+    current_size: u32,
+    data_type: StorageType,
+    encrypted_data: Vec<u8>,
+    entry_headers: Vec<DataEntryHeaderV2>,
+    entry_count: u16,
+    created_at: i64,
+    last_modified: i64,
+    bump: u8,
+}
+
+/// StorageChunk layout before entries could span chunks
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct StorageChunkV2 {
+    version: u8,
+    master_lockbox: Pubkey,
+    owner: Pubkey,
+    chunk_index: u16,
+    max_capacity: u32,
+    current_size: u32,
+    data_type: StorageType,
+    encrypted_data: Vec<u8>,
+    entry_headers: Vec<DataEntryHeaderV2>,
+    entry_count: u16,
+    created_at: i64,
+    last_modified: i64,
+    layout_epoch: u32,
+    bump: u8,
+}
+
+impl From<StorageChunkV1> for StorageChunkV2 {
+    fn from(v1: StorageChunkV1) -> Self {
+        Self {
+            version: 2,
+            master_lockbox: v1.master_lockbox,
+            owner: v1.owner,
+            chunk_index: v1.chunk_index,
+            max_capacity: v1.max_capacity,
+            current_size: v1.current_size,
+            data_type: v1.data_type,
+            encrypted_data: v1.encrypted_data,
+            entry_headers: v1.entry_headers,
+            entry_count: v1.entry_count,
+            created_at: v1.created_at,
+            last_modified: v1.last_modified,
+            // Matches the epoch the MasterLockbox v2 rewrite gives its info
+            layout_epoch: 0,
+            bump: v1.bump,
+        }
+    }
+}
+
+/// StorageChunk layout before entry tags, rotation dates and TOTP parents
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct StorageChunkV3 {
+    version: u8,
+    master_lockbox: Pubkey,
+    owner: Pubkey,
+    chunk_index: u16,
+    max_capacity: u32,
+    current_size: u32,
+    data_type: StorageType,
+    encrypted_data: Vec<u8>,
+    entry_headers: Vec<DataEntryHeaderV3>,
+    entry_count: u16,
+    created_at: i64,
+    last_modified: i64,
+    layout_epoch: u32,
+    bump: u8,
+}
+
+impl From<StorageChunkV2> for StorageChunkV3 {
+    fn from(v2: StorageChunkV2) -> Self {
+        Self {
+            version: 3,
+            master_lockbox: v2.master_lockbox,
+            owner: v2.owner,
+            chunk_index: v2.chunk_index,
+            max_capacity: v2.max_capacity,
+            current_size: v2.current_size,
+            data_type: v2.data_type,
+            encrypted_data: v2.encrypted_data,
+            entry_headers: v2.entry_headers.into_iter().map(DataEntryHeaderV3::from).collect(),
+            entry_count: v2.entry_count,
+            created_at: v2.created_at,
+            last_modified: v2.last_modified,
+            layout_epoch: v2.layout_epoch,
+            bump: v2.bump,
+        }
+    }
+}
+
+impl From<StorageChunkV3> for StorageChunk {
+    fn from(v3: StorageChunkV3) -> Self {
+        Self {
+            version: 4,
+            master_lockbox: v3.master_lockbox,
+            owner: v3.owner,
+            chunk_index: v3.chunk_index,
+            max_capacity: v3.max_capacity,
+            current_size: v3.current_size,
+            data_type: v3.data_type,
+            encrypted_data: v3.encrypted_data,
+            entry_headers: v3.entry_headers.into_iter().map(DataEntryHeader::from).collect(),
+            entry_count: v3.entry_count,
+            created_at: v3.created_at,
+            last_modified: v3.last_modified,
+            layout_epoch: v3.layout_epoch,
+            bump: v3.bump,
+        }
+    }
+}
+
+/// Category layout before per-category byte tracking
+#[derive(AnchorDeserialize)]
+struct CategoryV0 {
+    id: u8,
+    name_encrypted: Vec<u8>,
+    icon: u8,
+    color: u8,
+    parent_id: Option<u8>,
+    entry_count: u32,
+    created_at: i64,
+    last_modified: i64,
+    flags: u8,
+}
+
+/// CategoryRegistry layout before versioning
+#[derive(AnchorDeserialize)]
+struct CategoryRegistryV0 {
+    owner: Pubkey,
+    master_lockbox: Pubkey,
+    categories: Vec<CategoryV0>,
+    next_category_id: u8,
+    created_at: i64,
+    bump: u8,
+}
+
+impl From<CategoryRegistryV0> for CategoryRegistry {
+    fn from(v0: CategoryRegistryV0) -> Self {
+        Self {
+            version: 1,
+            owner: v0.owner,
+            master_lockbox: v0.master_lockbox,
+            categories: v0
+                .categories
+                .into_iter()
+                .map(|c| Category {
+                    id: c.id,
+                    name_encrypted: c.name_encrypted,
+                    icon: c.icon,
+                    color: c.color,
+                    parent_id: c.parent_id,
+                    entry_count: c.entry_count,
+                    bytes_used: 0,
+                    created_at: c.created_at,
+                    last_modified: c.last_modified,
+                    flags: c.flags,
+                })
+                .collect(),
+            next_category_id: v0.next_category_id,
+            created_at: v0.created_at,
+            bump: v0.bump,
+        }
+    }
+}
+
+/// EmergencyContact layout before invitation expiry
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct EmergencyContactV2 {
+    contact_pubkey: Pubkey,
+    contact_name_encrypted: Vec<u8>,
+    access_level: EmergencyAccessLevel,
+    encrypted_key: Vec<u8>,
+    added_at: i64,
+    access_granted_at: Option<i64>,
+    status: EmergencyContactStatus,
+}
+
+/// EmergencyAccess layout before countdown notifications
+#[derive(AnchorDeserialize)]
+struct EmergencyAccessV1 {
+    // Checked by `check_schema_version` before the body is decoded
+    #[allow(dead_code)]
+    version: u8,
+    owner: Pubkey,
+    emergency_contacts: Vec<EmergencyContactV2>,
+    inactivity_period: i64,
+    grace_period: i64,
+    last_activity: i64,
+    countdown_started: Option<i64>,
+    status: EmergencyStatus,
+    created_at: i64,
+    bump: u8,
+}
+
+impl From<EmergencyAccessV1> for EmergencyAccessV2 {
+    fn from(v1: EmergencyAccessV1) -> Self {
+        Self {
+            version: 2,
+            owner: v1.owner,
+            emergency_contacts: v1.emergency_contacts,
+            inactivity_period: v1.inactivity_period,
+            grace_period: v1.grace_period,
+            notification_interval: 0,
+            last_activity: v1.last_activity,
+            countdown_started: v1.countdown_started,
+            last_notified_at: v1.countdown_started.unwrap_or_default(),
+            status: v1.status,
+            created_at: v1.created_at,
+            bump: v1.bump,
+        }
+    }
+}
+
+/// EmergencyAccess layout before contact invitation expiry and export consent
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct EmergencyAccessV2 {
+    version: u8,
+    owner: Pubkey,
+    emergency_contacts: Vec<EmergencyContactV2>,
+    inactivity_period: i64,
+    grace_period: i64,
+    notification_interval: i64,
+    last_activity: i64,
+    countdown_started: Option<i64>,
+    last_notified_at: i64,
+    status: EmergencyStatus,
+    created_at: i64,
+    bump: u8,
+}
+
+impl From<EmergencyAccessV2> for EmergencyAccess {
+    fn from(v2: EmergencyAccessV2) -> Self {
+        Self {
+            version: 3,
+            owner: v2.owner,
+            // Existing invitations count as sent when the contact was added
+            emergency_contacts: v2
+                .emergency_contacts
+                .into_iter()
+                .map(|c| EmergencyContact {
+                    contact_pubkey: c.contact_pubkey,
+                    contact_name_encrypted: c.contact_name_encrypted,
+                    access_level: c.access_level,
+                    encrypted_key: c.encrypted_key,
+                    added_at: c.added_at,
+                    access_granted_at: c.access_granted_at,
+                    status: c.status,
+                    invited_at: c.added_at,
+                    expires_at: c.added_at + DEFAULT_INVITATION_TTL,
+                })
+                .collect(),
+            inactivity_period: v2.inactivity_period,
+            grace_period: v2.grace_period,
+            notification_interval: v2.notification_interval,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            last_activity: v2.last_activity,
+            countdown_started: v2.countdown_started,
+            last_notified_at: v2.last_notified_at,
+            status: v2.status,
+            created_at: v2.created_at,
+            export_consent: None,
+            bump: v2.bump,
+        }
+    }
+}
+
+/// Guardian layout before invitation expiry
+#[derive(AnchorDeserialize)]
+struct GuardianV1 {
+    guardian_pubkey: Pubkey,
+    share_index: u8,
+    encrypted_share: Vec<u8>,
+    added_at: i64,
+    nickname_encrypted: Vec<u8>,
+    status: GuardianStatus,
+}
+
+/// RecoveryConfig layout before invitation expiry
+#[derive(AnchorDeserialize)]
+struct RecoveryConfigV1 {
+    // Checked by `check_schema_version` before the body is decoded
+    #[allow(dead_code)]
+    version: u8,
+    owner: Pubkey,
+    threshold: u8,
+    total_guardians: u8,
+    guardians: Vec<GuardianV1>,
+    recovery_delay: i64,
+    created_at: i64,
+    last_modified: i64,
+    last_request_id: u64,
+    bump: u8,
+}
+
+impl From<RecoveryConfigV1> for RecoveryConfig {
+    fn from(v1: RecoveryConfigV1) -> Self {
+        Self {
+            version: 2,
+            owner: v1.owner,
+            threshold: v1.threshold,
+            total_guardians: v1.total_guardians,
+            // Existing invitations count as sent when the guardian was added
+            guardians: v1
+                .guardians
+                .into_iter()
+                .map(|g| Guardian {
+                    guardian_pubkey: g.guardian_pubkey,
+                    share_index: g.share_index,
+                    encrypted_share: g.encrypted_share,
+                    added_at: g.added_at,
+                    nickname_encrypted: g.nickname_encrypted,
+                    status: g.status,
+                    invited_at: g.added_at,
+                    expires_at: g.added_at + DEFAULT_INVITATION_TTL,
+                    // When earlier guardians accepted was never recorded
+                    accepted_at: None,
+                    acceptance_hash: [0u8; 32],
+                })
+                .collect(),
+            recovery_delay: v1.recovery_delay,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            recovery_disabled: false,
+            owner_activity_window: 0,
+            created_at: v1.created_at,
+            last_modified: v1.last_modified,
+            last_request_id: v1.last_request_id,
+            bump: v1.bump,
+        }
+    }
+}
+
+/// RecoveryConfigV2 layout before recovery cooldown modes
+#[derive(AnchorDeserialize)]
+struct RecoveryConfigV2V1 {
+    // Checked by `check_schema_version` before the body is decoded
+    #[allow(dead_code)]
+    version: u8,
+    owner: Pubkey,
+    threshold: u8,
+    total_guardians: u8,
+    guardians: Vec<GuardianV2>,
+    recovery_delay: i64,
+    created_at: i64,
+    last_modified: i64,
+    last_request_id: u64,
+    master_secret_hash: [u8; 32],
+    last_recovery_attempt: i64,
+    bump: u8,
+}
+
+impl From<RecoveryConfigV2V1> for RecoveryConfigV2 {
+    fn from(v1: RecoveryConfigV2V1) -> Self {
+        Self {
+            version: 2,
+            owner: v1.owner,
+            threshold: v1.threshold,
+            total_guardians: v1.total_guardians,
+            guardians: v1.guardians,
+            recovery_delay: v1.recovery_delay,
+            created_at: v1.created_at,
+            last_modified: v1.last_modified,
+            last_request_id: v1.last_request_id,
+            master_secret_hash: v1.master_secret_hash,
+            last_recovery_attempt: v1.last_recovery_attempt,
+            // The cooldown every config had until now
+            cooldown_mode: CooldownMode::Timestamp,
+            recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+            // Earlier attempts were only recorded by timestamp
+            last_recovery_attempt_slot: 0,
+            last_recovery_attempt_epoch: 0,
+            bump: v1.bump,
+        }
+    }
+}
+
+/// MasterLockbox base space in the v0 layout (without storage chunks)
+pub const MASTER_LOCKBOX_V0_BASE_SPACE: usize =
+    8 + 32 + 8 + 2 + 1 + 8 + 8 + 8 + 8 + 4 + 4 + 8 + 4 + 8 + 1;
+
+/// Bytes the v2 MasterLockbox layout adds over v1 (billing period,
+/// capacity add-on, loyalty, dunning, deletion tombstone, writer lease, write
+/// burst, heat map, category registry and category index flags, creator,
+/// transfer listing and shared storage fields)
+const MASTER_LOCKBOX_V2_GROWTH: usize =
+    8 + 8 + 8 + 8 + 8 + 4 + 1 + 8 + 8 + 41 + 8 + 4 + 4 + 8 + 2 * HEAT_MAP_BUCKETS + 1 + 1 + 32 + 1 + 8;
+
+/// Bytes the v2 layout adds to each StorageChunkInfo and StorageChunk
+/// (layout epoch)
+const CHUNK_LAYOUT_V2_GROWTH: usize = 4;
+
+/// Bytes the v3 layout adds to each DataEntryHeader (segment links)
+const ENTRY_HEADER_V3_GROWTH: usize = 2 + 4;
+
+/// Bytes the v4 layout adds to each DataEntryHeader (tag mask, rotation
+/// date, TOTP parent, empty link list)
+const ENTRY_HEADER_V4_GROWTH: usize = 2 + 8 + 8 + 4;
+
+/// MasterLockbox base space in the v1 layout (without storage chunks)
+pub const MASTER_LOCKBOX_V1_BASE_SPACE: usize =
+    MasterLockbox::INIT_SPACE - MASTER_LOCKBOX_V2_GROWTH;
+
+/// Bytes the v1 MasterLockbox layout adds over v0
+const MASTER_LOCKBOX_V1_GROWTH: usize =
+    MASTER_LOCKBOX_V1_BASE_SPACE - MASTER_LOCKBOX_V0_BASE_SPACE;
+
+/// Rewrite unversioned account data in the v1 layout
+///
+/// `owner` must match the owner recorded in the account. Returns the new
+/// account data, sized so that the manual space calculations still hold.
+///
+/// # Errors
+/// * `UnknownAccountType` - Discriminator is not a versioned Lockbox account
+/// * `AccountAlreadyMigrated` - Account is already in the v1 layout
+/// * `Unauthorized` - `owner` is not the account's recorded owner
+pub fn upgrade_to_v1(data: &[u8], owner: &Pubkey) -> Result<Vec<u8>> {
+    require!(data.len() >= DISCRIMINATOR_LEN, LockboxError::UnknownAccountType);
+    let (discriminator, body) = data.split_at(DISCRIMINATOR_LEN);
+
+    let is_master = discriminator == MasterLockbox::DISCRIMINATOR;
+    let is_chunk = discriminator == StorageChunk::DISCRIMINATOR;
+    let is_registry = discriminator == CategoryRegistry::DISCRIMINATOR;
+    let is_known = is_master
+        || is_chunk
+        || is_registry
+        || discriminator == RecoveryConfig::DISCRIMINATOR
+        || discriminator == RecoveryRequest::DISCRIMINATOR
+        || discriminator == RecoveryConfigV2::DISCRIMINATOR
+        || discriminator == RecoveryRequestV2::DISCRIMINATOR
+        || discriminator == EmergencyAccess::DISCRIMINATOR;
+    require!(is_known, LockboxError::UnknownAccountType);
+
+    // StorageChunk records its master lockbox ahead of the owner
+    let owner_offset = if is_chunk { 32 } else { 0 };
+    let owner_at = |offset: usize| body.get(offset..offset + 32) == Some(owner.as_ref());
+
+    if !owner_at(owner_offset) {
+        // A v1 account has the owner shifted by the version byte
+        if matches!(body.first(), Some(1..)) && owner_at(owner_offset + 1) {
+            return err!(LockboxError::AccountAlreadyMigrated);
+        }
+        return err!(LockboxError::Unauthorized);
+    }
+
+    let mut new_data = Vec::with_capacity(data.len() + MASTER_LOCKBOX_V1_GROWTH);
+    if is_master {
+        let legacy = MasterLockboxV0::deserialize(&mut &body[..])?;
+        new_data.extend_from_slice(discriminator);
+        MasterLockboxV1::from(legacy).serialize(&mut new_data)?;
+        new_data.resize(data.len() + MASTER_LOCKBOX_V1_GROWTH, 0);
+    } else if is_registry {
+        // Registries are allocated at full size; grow to the v1 full size
+        let legacy = CategoryRegistryV0::deserialize(&mut &body[..])?;
+        CategoryRegistry::from(legacy).try_serialize(&mut new_data)?;
+        new_data.resize(8 + CategoryRegistry::INIT_SPACE, 0);
+    } else {
+        // Every other account only gains the leading version byte
+        new_data.extend_from_slice(discriminator);
+        new_data.push(1);
+        new_data.extend_from_slice(body);
+    }
+
+    Ok(new_data)
+}
+
+/// Rewrite schema v1 account data in the v2 layout
+///
+/// Only EmergencyAccess, RecoveryConfig, RecoveryConfigV2, MasterLockbox
+/// and StorageChunk have a v2 layout. `owner` must match the owner recorded
+/// in the account.
+///
+/// # Errors
+/// * `UnknownAccountType` - Not an EmergencyAccess, RecoveryConfig,
+///   RecoveryConfigV2, MasterLockbox or StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already in the v2 layout
+/// * `MigrationOutOfOrder` - Account still needs `migrate_account_v1`
+/// * `Unauthorized` - `owner` is not the account's recorded owner
+pub fn upgrade_to_v2(data: &[u8], owner: &Pubkey) -> Result<Vec<u8>> {
+    require!(data.len() >= DISCRIMINATOR_LEN, LockboxError::UnknownAccountType);
+    let (discriminator, body) = data.split_at(DISCRIMINATOR_LEN);
+    let is_recovery_config = discriminator == RecoveryConfig::DISCRIMINATOR;
+    let is_recovery_config_v2 = discriminator == RecoveryConfigV2::DISCRIMINATOR;
+    let is_master = discriminator == MasterLockbox::DISCRIMINATOR;
+    let is_chunk = discriminator == StorageChunk::DISCRIMINATOR;
+    require!(
+        is_recovery_config
+            || is_recovery_config_v2
+            || is_master
+            || is_chunk
+            || discriminator == EmergencyAccess::DISCRIMINATOR,
+        LockboxError::UnknownAccountType
+    );
+    // StorageChunk records its master lockbox ahead of the owner
+    let owner_offset = if is_chunk { 32 } else { 0 };
+    check_schema_version(body, owner, owner_offset, 1)?;
+
+    let mut new_data = Vec::new();
+    if is_master {
+        // Keep the chunk and settings space the account already had
+        let legacy = MasterLockboxV1::deserialize(&mut &body[..])?;
+        let chunk_growth = legacy.storage_chunks.len() * CHUNK_LAYOUT_V2_GROWTH;
+        MasterLockbox::from(legacy).try_serialize(&mut new_data)?;
+        new_data.resize(data.len() + MASTER_LOCKBOX_V2_GROWTH + chunk_growth, 0);
+    } else if is_chunk {
+        // Keep the data capacity the chunk already had
+        let legacy = StorageChunkV1::deserialize(&mut &body[..])?;
+        new_data.extend_from_slice(discriminator);
+        StorageChunkV2::from(legacy).serialize(&mut new_data)?;
+        new_data.resize(data.len() + CHUNK_LAYOUT_V2_GROWTH, 0);
+    } else if is_recovery_config {
+        // Recovery configs are sized for their guardian count
+        let legacy = RecoveryConfigV1::deserialize(&mut &body[..])?;
+        let space = RecoveryConfig::space_for(legacy.guardians.len());
+        RecoveryConfig::from(legacy).try_serialize(&mut new_data)?;
+        new_data.resize(space, 0);
+    } else if is_recovery_config_v2 {
+        // Likewise sized for the guardian count
+        let legacy = RecoveryConfigV2V1::deserialize(&mut &body[..])?;
+        let space = RecoveryConfigV2::space_for(legacy.guardians.len());
+        RecoveryConfigV2::from(legacy).try_serialize(&mut new_data)?;
+        new_data.resize(space, 0);
+    } else {
+        // Sized for the current layout so the v3 rewrite needs no more rent
+        let legacy = EmergencyAccessV1::deserialize(&mut &body[..])?;
+        let space = EmergencyAccess::space_for(legacy.emergency_contacts.len());
+        new_data.extend_from_slice(discriminator);
+        EmergencyAccessV2::from(legacy).serialize(&mut new_data)?;
+        new_data.resize(space, 0);
+    }
+
+    Ok(new_data)
+}
+
+/// Rewrite schema v2 account data in the v3 layout
+///
+/// Only EmergencyAccess and StorageChunk have a v3 layout. `owner` must
+/// match the owner recorded in the account.
+///
+/// # Errors
+/// * `UnknownAccountType` - Not an EmergencyAccess or StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already in the v3 layout
+/// * `MigrationOutOfOrder` - Account still needs an earlier migration
+/// * `Unauthorized` - `owner` is not the account's recorded owner
+pub fn upgrade_to_v3(data: &[u8], owner: &Pubkey) -> Result<Vec<u8>> {
+    require!(data.len() >= DISCRIMINATOR_LEN, LockboxError::UnknownAccountType);
+    let (discriminator, body) = data.split_at(DISCRIMINATOR_LEN);
+    let is_chunk = discriminator == StorageChunk::DISCRIMINATOR;
+    require!(
+        is_chunk || discriminator == EmergencyAccess::DISCRIMINATOR,
+        LockboxError::UnknownAccountType
+    );
+    // StorageChunk records its master lockbox ahead of the owner
+    let owner_offset = if is_chunk { 32 } else { 0 };
+    check_schema_version(body, owner, owner_offset, 2)?;
+
+    if is_chunk {
+        // Keep the data capacity the chunk already had
+        let legacy = StorageChunkV2::deserialize(&mut &body[..])?;
+        let header_growth = legacy.entry_headers.len() * ENTRY_HEADER_V3_GROWTH;
+        let mut new_data = Vec::with_capacity(data.len() + header_growth);
+        new_data.extend_from_slice(discriminator);
+        StorageChunkV3::from(legacy).serialize(&mut new_data)?;
+        new_data.resize(data.len() + header_growth, 0);
+        return Ok(new_data);
+    }
+
+    // Emergency access accounts are sized for their contact count
+    let legacy = EmergencyAccessV2::deserialize(&mut &body[..])?;
+    let space = EmergencyAccess::space_for(legacy.emergency_contacts.len());
+    let mut new_data = Vec::with_capacity(space);
+    EmergencyAccess::from(legacy).try_serialize(&mut new_data)?;
+    new_data.resize(space, 0);
+
+    Ok(new_data)
+}
+
+/// Rewrite schema v3 account data in the v4 layout
+///
+/// Only StorageChunk has a v4 layout. `owner` must match the owner recorded
+/// in the account.
+///
+/// # Errors
+/// * `UnknownAccountType` - Not a StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already in the v4 layout
+/// * `MigrationOutOfOrder` - Account still needs an earlier migration
+/// * `Unauthorized` - `owner` is not the account's recorded owner
+pub fn upgrade_to_v4(data: &[u8], owner: &Pubkey) -> Result<Vec<u8>> {
+    require!(data.len() >= DISCRIMINATOR_LEN, LockboxError::UnknownAccountType);
+    let (discriminator, body) = data.split_at(DISCRIMINATOR_LEN);
+    require!(discriminator == StorageChunk::DISCRIMINATOR, LockboxError::UnknownAccountType);
+    // StorageChunk records its master lockbox ahead of the owner
+    check_schema_version(body, owner, 32, 3)?;
+
+    // Keep the data capacity the chunk already had
+    let legacy = StorageChunkV3::deserialize(&mut &body[..])?;
+    let header_growth = legacy.entry_headers.len() * ENTRY_HEADER_V4_GROWTH;
+    let mut new_data = Vec::with_capacity(data.len() + header_growth);
+    StorageChunk::from(legacy).try_serialize(&mut new_data)?;
+    new_data.resize(data.len() + header_growth, 0);
+
+    Ok(new_data)
+}
+
+/// Check that a versioned account body (owner `owner_offset` bytes after the
+/// version byte) is owned by `owner` and at schema version `from`
+fn check_schema_version(body: &[u8], owner: &Pubkey, owner_offset: usize, from: u8) -> Result<()> {
+    let owner_at = |offset: usize| body.get(offset..offset + 32) == Some(owner.as_ref());
+    if !owner_at(owner_offset + 1) {
+        // An unversioned account has no version byte ahead of the owner
+        if owner_at(owner_offset) {
+            return err!(LockboxError::MigrationOutOfOrder);
+        }
+        return err!(LockboxError::Unauthorized);
+    }
+    require!(body[0] <= from, LockboxError::AccountAlreadyMigrated);
+    require!(body[0] == from, LockboxError::MigrationOutOfOrder);
+    Ok(())
+}
+
+/// Migrate an unversioned account to schema version 1
+///
+/// Accepts MasterLockbox, StorageChunk, CategoryRegistry, RecoveryConfig,
+/// RecoveryRequest, RecoveryConfigV2, RecoveryRequestV2 and EmergencyAccess
+/// accounts.
+///
+/// # Errors
+/// * `UnknownAccountType` - Not a migratable Lockbox account
+/// * `AccountAlreadyMigrated` - Account is already at version 1
+/// * `Unauthorized` - Signer is not the account's recorded owner
+pub fn migrate_account_v1_handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let new_data = {
+        let data = ctx.accounts.account.try_borrow_data()?;
+        upgrade_to_v1(&data, ctx.accounts.owner.key)?
+    };
+    rewrite_account(&ctx, new_data, 0, 1)
+}
+
+/// Migrate a schema v1 EmergencyAccess, RecoveryConfig, RecoveryConfigV2,
+/// MasterLockbox or StorageChunk account to schema version 2
+///
+/// # Errors
+/// * `UnknownAccountType` - Not an EmergencyAccess, RecoveryConfig,
+///   RecoveryConfigV2, MasterLockbox or StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already at version 2
+/// * `MigrationOutOfOrder` - Account must be migrated to version 1 first
+/// * `Unauthorized` - Signer is not the account's recorded owner
+pub fn migrate_account_v2_handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let new_data = {
+        let data = ctx.accounts.account.try_borrow_data()?;
+        upgrade_to_v2(&data, ctx.accounts.owner.key)?
+    };
+    rewrite_account(&ctx, new_data, 1, 2)
+}
+
+/// Migrate a schema v2 EmergencyAccess or StorageChunk account to schema
+/// version 3
+///
+/// # Errors
+/// * `UnknownAccountType` - Not an EmergencyAccess or StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already at version 3
+/// * `MigrationOutOfOrder` - Account must be migrated to version 2 first
+/// * `Unauthorized` - Signer is not the account's recorded owner
+pub fn migrate_account_v3_handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let new_data = {
+        let data = ctx.accounts.account.try_borrow_data()?;
+        upgrade_to_v3(&data, ctx.accounts.owner.key)?
+    };
+    rewrite_account(&ctx, new_data, 2, 3)
+}
+
+/// Migrate a schema v3 StorageChunk account to schema version 4
+///
+/// # Errors
+/// * `UnknownAccountType` - Not a StorageChunk account
+/// * `AccountAlreadyMigrated` - Account is already at version 4
+/// * `MigrationOutOfOrder` - Account must be migrated to version 3 first
+/// * `Unauthorized` - Signer is not the account's recorded owner
+pub fn migrate_account_v4_handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let new_data = {
+        let data = ctx.accounts.account.try_borrow_data()?;
+        upgrade_to_v4(&data, ctx.accounts.owner.key)?
+    };
+    rewrite_account(&ctx, new_data, 3, 4)
+}
+
+/// Replace the account's data, topping up rent for a larger layout
+fn rewrite_account(
+    ctx: &Context<MigrateAccount>,
+    new_data: Vec<u8>,
+    from_version: u8,
+    to_version: u8,
+) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+
+    // Top up rent for the larger layout
+    let rent = Rent::get()?;
+    let additional_rent = rent
+        .minimum_balance(new_data.len())
+        .saturating_sub(account.lamports());
+
+    if additional_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            additional_rent,
+        )?;
+    }
+
+    account.realloc(new_data.len(), false)?;
+    account.try_borrow_mut_data()?.copy_from_slice(&new_data);
+
+    emit!(AccountMigratedEvent {
+        account: account.key(),
+        owner: ctx.accounts.owner.key(),
+        from_version,
+        to_version,
+    });
+
+    msg!("Account {} migrated to schema version {}", account.key(), to_version);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Legacy layout cannot be deserialized; ownership by this
+    /// program is enforced here and the discriminator and recorded owner are
+    /// checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub owner: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
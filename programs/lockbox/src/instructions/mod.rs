@@ -1,19 +1,85 @@
 pub mod initialize;
 pub mod password_entry;
+pub mod entry_notes;
+pub mod trash;
+pub mod settings;
+pub mod security_policy;
 pub mod subscription;
+pub mod billing;
 pub mod chunk_management;
+pub mod chunk_set;
+pub mod writer_lease;
+pub mod invariants;
+pub mod preflight;
 pub mod category_management;
 pub mod close_account;
 pub mod recovery_management;
 pub mod recovery_management_v2;
 pub mod emergency_access_management;
+pub mod migration;
+pub mod alias_registry;
+pub mod contact_book;
+pub mod inbox;
+pub mod entry_copy;
+pub mod vault_transfer;
+pub mod export_manifest;
+pub mod spanning_entry;
+pub mod entry_grants;
+pub mod deny_list;
+pub mod entry_tags;
+pub mod rotation;
+pub mod totp;
+pub mod entry_links;
+pub mod search_index;
+pub mod search_filter;
+pub mod title_lookup;
+pub mod category_index;
+pub mod rebuild_indexes;
+pub mod shared_vault;
+pub mod program_config;
+pub mod invoice;
+pub mod refund;
+pub mod client_entry_ids;
 
 pub use initialize::*;
 pub use password_entry::*;
+pub use entry_notes::*;
+pub use trash::*;
+pub use settings::*;
+pub use security_policy::*;
 pub use subscription::*;
+pub use billing::*;
 pub use chunk_management::*;
+pub use chunk_set::*;
+pub use writer_lease::*;
+pub use invariants::*;
+pub use preflight::*;
 pub use category_management::*;
 pub use close_account::*;
 pub use recovery_management::*;
 pub use recovery_management_v2::*;
 pub use emergency_access_management::*;
+pub use migration::*;
+pub use alias_registry::*;
+pub use contact_book::*;
+pub use inbox::*;
+pub use entry_copy::*;
+pub use vault_transfer::*;
+pub use export_manifest::*;
+pub use spanning_entry::*;
+pub use entry_grants::*;
+pub use deny_list::*;
+pub use entry_tags::*;
+pub use rotation::*;
+pub use totp::*;
+pub use entry_links::*;
+pub use search_index::*;
+pub use search_filter::*;
+pub use title_lookup::*;
+pub use category_index::*;
+pub use rebuild_indexes::*;
+pub use shared_vault::*;
+pub use program_config::*;
+pub use invoice::*;
+pub use refund::*;
+pub use client_entry_ids::*;
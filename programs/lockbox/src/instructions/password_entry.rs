@@ -1,5 +1,130 @@
 use anchor_lang::prelude::*;
-use crate::state::{MasterLockbox, StorageChunk, DataEntryHeader, PasswordEntryType};
+use crate::errors::{require_with_context, ErrorField};
+use crate::state::{
+    MasterLockbox, StorageChunk, DataEntryHeader, PasswordEntryType, AliasTarget, CategoryIndex,
+    CategoryRegistry, ClientEntryIds, SecurityPolicy, EntryCopies, TREASURY_SEED,
+};
+use super::chunk_management::grow_chunk;
+use super::category_index::fit_category_index;
+use super::chunk_set::ChunkSet;
+use super::client_entry_ids::fit_client_entry_ids;
+use super::entry_copy::cascade_to_copies;
+use super::invariants::enforce_invariants;
+use super::security_policy::record_heat_map_read;
+use super::subscription::{charge_entry_write_fee, EntryWriteFeeEvent};
+
+/// Largest shortfall `store_password_entry` will cover by expanding the chunk
+pub const AUTO_EXPAND_MAX_MARGIN: u32 = 2048;
+
+/// Smallest valid XChaCha20-Poly1305 payload: 24-byte nonce + 16-byte tag
+pub const MIN_AEAD_SIZE: usize = 40;
+
+/// Most entries `store_password_entries_batch` writes in one instruction
+pub const MAX_STORE_BATCH: usize = 16;
+
+/// Entry payload framing version
+pub const PAYLOAD_FRAMING_VERSION: u8 = 1;
+
+/// Framing suite ID for XChaCha20-Poly1305 (24-byte nonce, 16-byte tag)
+pub const PAYLOAD_SUITE_XCHACHA20_POLY1305: u8 = 1;
+
+/// Framing header: version, nonce length, suite ID
+pub const PAYLOAD_HEADER_SIZE: usize = 3;
+
+/// Smallest TOTP payload: framing and AEAD overhead around a 16-byte
+/// (128-bit, RFC 4226 minimum) seed
+pub const TOTP_MIN_PAYLOAD_SIZE: usize = PAYLOAD_HEADER_SIZE + MIN_AEAD_SIZE + 16;
+
+/// Largest TOTP payload: room for a 64-byte (SHA-512) seed plus its
+/// algorithm, digits, period and issuer parameters
+pub const TOTP_MAX_PAYLOAD_SIZE: usize = PAYLOAD_HEADER_SIZE + MIN_AEAD_SIZE + 128;
+
+/// Fail with `InvalidTotpPayload` unless a TOTP payload fits a standard seed
+pub fn check_entry_type_size(entry_type: PasswordEntryType, size: usize) -> Result<()> {
+    if entry_type == PasswordEntryType::Totp {
+        require!(
+            (TOTP_MIN_PAYLOAD_SIZE..=TOTP_MAX_PAYLOAD_SIZE).contains(&size),
+            crate::errors::LockboxError::InvalidTotpPayload
+        );
+    }
+    Ok(())
+}
+
+/// Fail with `RateLimitExceeded` (and its context) inside the write interval
+pub fn check_write_rate(master_lockbox: &MasterLockbox, current_timestamp: i64) -> Result<()> {
+    require_with_context(
+        master_lockbox.check_rate_limit(current_timestamp, 1),
+        crate::errors::LockboxError::RateLimitExceeded,
+        ErrorField::WriteTimestamp,
+        master_lockbox.last_accessed + 1,
+        current_timestamp,
+    )
+}
+
+/// Fail with `InvalidDataSize` (and its context) below `MIN_AEAD_SIZE`
+pub fn check_aead_size(size: usize) -> Result<()> {
+    require_with_context(
+        size >= MIN_AEAD_SIZE,
+        crate::errors::LockboxError::InvalidDataSize,
+        ErrorField::DataSize,
+        MIN_AEAD_SIZE as i64,
+        size as i64,
+    )
+}
+
+/// Fail with `MalformedPayload` unless an entry payload carries a valid
+/// framing header
+///
+/// Entries are `[version | nonce length | suite ID | nonce | ciphertext |
+/// tag]`. Checking the header at write time turns a client bug into an
+/// immediate error instead of an entry that fails to decrypt months later.
+pub fn check_payload_framing(payload: &[u8]) -> Result<()> {
+    let framed = match payload {
+        [PAYLOAD_FRAMING_VERSION, nonce_len, PAYLOAD_SUITE_XCHACHA20_POLY1305, ..] => {
+            *nonce_len == 24 && payload.len() >= PAYLOAD_HEADER_SIZE + MIN_AEAD_SIZE
+        }
+        _ => false,
+    };
+    require!(framed, crate::errors::LockboxError::MalformedPayload);
+    Ok(())
+}
+
+/// Fail with `SubscriptionExpired` (and its context) once a paid tier lapses
+pub fn check_subscription_active(master_lockbox: &MasterLockbox, current_timestamp: i64) -> Result<()> {
+    require_with_context(
+        master_lockbox.is_subscription_active(current_timestamp),
+        crate::errors::LockboxError::SubscriptionExpired,
+        ErrorField::SubscriptionExpiry,
+        master_lockbox.subscription_expires,
+        current_timestamp,
+    )
+}
+
+/// Fail with `InsufficientStorageCapacity` (and its context) past the capacity limit
+pub fn check_storage_capacity(
+    master_lockbox: &MasterLockbox,
+    additional_bytes: u64,
+    current_timestamp: i64,
+) -> Result<()> {
+    require_with_context(
+        master_lockbox.has_capacity(additional_bytes, current_timestamp),
+        crate::errors::LockboxError::InsufficientStorageCapacity,
+        ErrorField::StorageCapacity,
+        master_lockbox.capacity_limit(current_timestamp) as i64,
+        master_lockbox.total_storage_used().saturating_add(additional_bytes) as i64,
+    )
+}
+
+/// Fail with `InsufficientChunkCapacity` (and its context) when `size` won't fit
+pub fn check_chunk_capacity(storage_chunk: &StorageChunk, size: u32) -> Result<()> {
+    require_with_context(
+        storage_chunk.can_fit(size),
+        crate::errors::LockboxError::InsufficientChunkCapacity,
+        ErrorField::ChunkCapacity,
+        storage_chunk.available_space() as i64,
+        size as i64,
+    )
+}
 
 /// Store a new password entry
 #[derive(Accounts)]
@@ -7,9 +132,14 @@ use crate::state::{MasterLockbox, StorageChunk, DataEntryHeader, PasswordEntryTy
 pub struct StorePasswordEntry<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ crate::errors::LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ crate::errors::LockboxError::CategoryIndexRequired
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -28,6 +158,49 @@ pub struct StorePasswordEntry<'info> {
 
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Keeps the category index in sync; required once the vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    /// CHECK: Treasury PDA (system account); required when the tier's
+    /// entry write fee is non-zero
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Optional: pays rent to expand the chunk when it is slightly too small
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
+
+    /// Required together with `payer` or `treasury`
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Reserves `client_entry_id` across the vault; required when one is
+    /// passed
+    #[account(
+        mut,
+        seeds = [ClientEntryIds::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = client_entry_ids.bump
+    )]
+    pub client_entry_ids: Option<Account<'info, ClientEntryIds>>,
 }
 
 pub fn store_password_entry_handler(
@@ -37,6 +210,7 @@ pub fn store_password_entry_handler(
     entry_type: PasswordEntryType,
     category: u32,
     title_hash: [u8; 32],
+    client_entry_id: Option<u64>,
 ) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
     let storage_chunk = &mut ctx.accounts.storage_chunk;
@@ -44,42 +218,72 @@ pub fn store_password_entry_handler(
 
     // SECURITY: Rate limiting (prevent DoS attacks)
     // Minimum 1 second between write operations
-    require!(
-        master_lockbox.check_rate_limit(current_timestamp, 1),
-        crate::errors::LockboxError::RateLimitExceeded
-    );
+    check_write_rate(master_lockbox, current_timestamp)?;
+    master_lockbox.check_chunk_layout(storage_chunk)?;
 
     // SECURITY: Validate AEAD ciphertext format
     // XChaCha20-Poly1305 (NaCl secretbox) format:
     // - First 24 bytes: nonce
     // - Remaining bytes: ciphertext + 16-byte Poly1305 tag
-    // Minimum valid size: 24 (nonce) + 16 (tag) = 40 bytes
-    const MIN_AEAD_SIZE: usize = 40;
-    require!(
-        encrypted_data.len() >= MIN_AEAD_SIZE,
-        crate::errors::LockboxError::InvalidDataSize
-    );
+    check_aead_size(encrypted_data.len())?;
+    check_payload_framing(&encrypted_data)?;
+    check_entry_type_size(entry_type, encrypted_data.len())?;
+    master_lockbox.check_entry_size(encrypted_data.len(), current_timestamp)?;
+
+    if let Some(policy) = SecurityPolicy::load(&ctx.accounts.security_policy)? {
+        policy.check_size(encrypted_data.len())?;
+    }
 
     // Check subscription is active
-    require!(
-        master_lockbox.is_subscription_active(current_timestamp),
-        crate::errors::LockboxError::SubscriptionExpired
-    );
+    check_subscription_active(master_lockbox, current_timestamp)?;
 
     // Check capacity
     let data_size = encrypted_data.len() as u64;
-    require!(
-        master_lockbox.has_capacity(data_size),
-        crate::errors::LockboxError::InsufficientStorageCapacity
-    );
+    check_storage_capacity(master_lockbox, data_size, current_timestamp)?;
+    master_lockbox.record_write_burst(encrypted_data.len(), Clock::get()?.slot)?;
 
-    require!(
-        storage_chunk.can_fit(encrypted_data.len() as u32),
-        crate::errors::LockboxError::InsufficientChunkCapacity
-    );
+    // Opt-in: cover a small shortfall by expanding the chunk in this
+    // instruction, so another device can't fill it between expand and store
+    let shortfall = (encrypted_data.len() as u32).saturating_sub(storage_chunk.available_space());
+    if let (Some(payer), Some(system_program)) = (&ctx.accounts.payer, &ctx.accounts.system_program) {
+        if shortfall > 0 && shortfall <= AUTO_EXPAND_MAX_MARGIN {
+            grow_chunk(
+                storage_chunk,
+                master_lockbox,
+                payer.to_account_info(),
+                system_program.to_account_info(),
+                shortfall,
+                current_timestamp,
+            )?;
+        }
+    }
 
-    // Get next entry ID
-    let entry_id = master_lockbox.get_next_entry_id();
+    check_chunk_capacity(storage_chunk, encrypted_data.len() as u32)?;
+
+    // Use the client's stable ID (sync engines) or assign the next counter ID.
+    // Client IDs live in the high-bit range so they never collide with the counter,
+    // and are reserved in the vault's ClientEntryIds so no other chunk reuses them.
+    let entry_id = match client_entry_id {
+        Some(id) => {
+            require!(
+                MasterLockbox::is_client_entry_id(id),
+                crate::errors::LockboxError::InvalidEntryId
+            );
+            let client_entry_ids = ctx
+                .accounts
+                .client_entry_ids
+                .as_mut()
+                .ok_or(crate::errors::LockboxError::ClientEntryIdsRequired)?;
+            client_entry_ids.reserve(id)?;
+            fit_client_entry_ids(
+                client_entry_ids,
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+            )?;
+            id
+        }
+        None => master_lockbox.get_next_entry_id(),
+    };
 
     // Create entry header
     let entry_header = DataEntryHeader {
@@ -93,30 +297,191 @@ pub fn store_password_entry_handler(
         last_modified: current_timestamp,
         access_count: 0,
         flags: 0,
+        next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+        next_offset: 0,
+        tag_mask: 0,
+        rotate_after: 0,
+        parent_entry: 0,
+        linked_entry_ids: Vec::new(),
     };
 
+    // Spam-prevention fee (waived on paid tiers)
+    let fee = charge_entry_write_fee(
+        master_lockbox.subscription_tier,
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+        ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+    )?;
+    if fee > 0 {
+        emit!(EntryWriteFeeEvent { owner: master_lockbox.owner, entry_id, amount: fee });
+    }
+
     // Add entry to chunk
     storage_chunk.add_entry(entry_header, encrypted_data, current_timestamp)?;
 
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        registry.record_usage(category, 1, data_size as i64);
+    }
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        category_index.insert(category, entry_id)?;
+        fit_category_index(
+            category_index,
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+        )?;
+    }
+
     // Update master lockbox
     master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
     master_lockbox.increment_entries();
     master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
 
     msg!("Password entry {} stored successfully", entry_id);
 
     Ok(())
 }
 
+/// One entry of a `store_password_entries_batch` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NewPasswordEntry {
+    pub encrypted_data: Vec<u8>,
+    pub entry_type: PasswordEntryType,
+    pub category: u32,
+    pub title_hash: [u8; 32],
+}
+
+/// Store several entries in one chunk (e.g. an import from another manager)
+///
+/// Takes the same accounts as `store_password_entry`. Every entry is
+/// validated before any is written, entries get consecutive counter IDs,
+/// and the batch counts as a single write for rate limiting. With `payer`
+/// set, the chunk grows by up to `AUTO_EXPAND_MAX_MARGIN` to fit the batch.
+///
+/// # Errors
+/// * `InvalidBatchSize` - Batch is empty or exceeds `MAX_STORE_BATCH`
+/// * Any error of `store_password_entry`, for the first entry that fails
+pub fn store_password_entries_batch_handler(
+    ctx: Context<StorePasswordEntry>,
+    _chunk_index: u16,
+    entries: Vec<NewPasswordEntry>,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        (1..=MAX_STORE_BATCH).contains(&entries.len()),
+        crate::errors::LockboxError::InvalidBatchSize
+    );
+
+    // SECURITY: Rate limiting (the whole batch is one write)
+    check_write_rate(master_lockbox, current_timestamp)?;
+    master_lockbox.check_chunk_layout(storage_chunk)?;
+
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+    for entry in &entries {
+        check_aead_size(entry.encrypted_data.len())?;
+        check_payload_framing(&entry.encrypted_data)?;
+        check_entry_type_size(entry.entry_type, entry.encrypted_data.len())?;
+        master_lockbox.check_entry_size(entry.encrypted_data.len(), current_timestamp)?;
+        if let Some(policy) = &policy {
+            policy.check_size(entry.encrypted_data.len())?;
+        }
+    }
+
+    check_subscription_active(master_lockbox, current_timestamp)?;
+
+    let total_size: u32 = entries.iter().map(|e| e.encrypted_data.len() as u32).sum();
+    check_storage_capacity(master_lockbox, total_size as u64, current_timestamp)?;
+    master_lockbox.record_write_burst(total_size as usize, Clock::get()?.slot)?;
+
+    let shortfall = total_size.saturating_sub(storage_chunk.available_space());
+    if let (Some(payer), Some(system_program)) = (&ctx.accounts.payer, &ctx.accounts.system_program) {
+        if shortfall > 0 && shortfall <= AUTO_EXPAND_MAX_MARGIN {
+            grow_chunk(
+                storage_chunk,
+                master_lockbox,
+                payer.to_account_info(),
+                system_program.to_account_info(),
+                shortfall,
+                current_timestamp,
+            )?;
+        }
+    }
+
+    check_chunk_capacity(storage_chunk, total_size)?;
+
+    let count = entries.len();
+    for entry in entries {
+        let entry_id = master_lockbox.get_next_entry_id();
+        let size = entry.encrypted_data.len() as u32;
+        let entry_header = DataEntryHeader {
+            entry_id,
+            offset: storage_chunk.current_size,
+            size,
+            entry_type: entry.entry_type,
+            category: entry.category,
+            title_hash: entry.title_hash,
+            created_at: current_timestamp,
+            last_modified: current_timestamp,
+            access_count: 0,
+            flags: 0,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        };
+
+        // Spam-prevention fee, per entry (waived on paid tiers)
+        let fee = charge_entry_write_fee(
+            master_lockbox.subscription_tier,
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+            ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+        )?;
+        if fee > 0 {
+            emit!(EntryWriteFeeEvent { owner: master_lockbox.owner, entry_id, amount: fee });
+        }
+
+        storage_chunk.add_entry(entry_header, entry.encrypted_data, current_timestamp)?;
+
+        if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+            registry.record_usage(entry.category, 1, size as i64);
+        }
+        if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+            category_index.insert(entry.category, entry_id)?;
+        }
+        master_lockbox.increment_entries();
+    }
+    if let Some(category_index) = ctx.accounts.category_index.as_ref() {
+        fit_category_index(
+            category_index,
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+        )?;
+    }
+
+    master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
+    master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    msg!("{} password entries stored", count);
+
+    Ok(())
+}
+
 /// Retrieve a password entry
 #[derive(Accounts)]
 #[instruction(chunk_index: u16, entry_id: u64)]
 pub struct RetrievePasswordEntry<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -134,12 +499,23 @@ pub struct RetrievePasswordEntry<'info> {
     pub storage_chunk: Account<'info, StorageChunk>,
 
     pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
 }
 
+/// Return an entry's ciphertext
+///
+/// With `simulate`, the read is a pure view: the same checks run, but the
+/// access count, recently-used list and timestamps are left alone, so an
+/// RPC simulation neither starts a read cooldown nor skews analytics.
 pub fn retrieve_password_entry_handler(
     ctx: Context<RetrievePasswordEntry>,
     _chunk_index: u16,
     entry_id: u64,
+    simulate: bool,
 ) -> Result<Vec<u8>> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
     let storage_chunk = &mut ctx.accounts.storage_chunk;
@@ -151,12 +527,41 @@ pub fn retrieve_password_entry_handler(
         crate::errors::LockboxError::SubscriptionExpired
     );
 
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+    if let Some(policy) = &policy {
+        require!(
+            master_lockbox.check_rate_limit(current_timestamp, policy.read_cooldown as i64),
+            crate::errors::LockboxError::ReadCooldownActive
+        );
+    }
+    let has_flag = |flag| policy.as_ref().is_some_and(|p| p.has_privacy_flag(flag));
+
+    // Spanning entries are reassembled by `retrieve_spanning_entry`
+    require!(
+        !storage_chunk.get_entry_header(entry_id)?.spans_chunks(),
+        crate::errors::LockboxError::EntrySpansChunks
+    );
+
     // Get entry data
     let data = storage_chunk.get_entry_data(entry_id)?;
 
+    if simulate {
+        return Ok(data);
+    }
+
     // Update access count
-    let header = storage_chunk.get_entry_header_mut(entry_id)?;
-    header.access_count += 1;
+    if !has_flag(SecurityPolicy::PRIVACY_NO_ACCESS_COUNT) {
+        let header = storage_chunk.get_entry_header_mut(entry_id)?;
+        header.access_count += 1;
+    }
+
+    // Recently-used list (no-op unless the owner opted in)
+    if !has_flag(SecurityPolicy::PRIVACY_NO_RECENT_ENTRIES) {
+        master_lockbox.record_recent_entry(entry_id);
+    }
+
+    let category = storage_chunk.get_entry_header(entry_id)?.category;
+    record_heat_map_read(master_lockbox, policy.as_ref(), category, current_timestamp);
 
     // Update timestamps
     storage_chunk.last_modified = current_timestamp;
@@ -173,9 +578,12 @@ pub fn retrieve_password_entry_handler(
 pub struct UpdatePasswordEntry<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ crate::errors::LockboxError::CategoryRegistryRequired
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -194,6 +602,37 @@ pub struct UpdatePasswordEntry<'info> {
 
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// CHECK: The entry's copy list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `EntryCopies::load`
+    #[account(
+        mut,
+        seeds = [EntryCopies::SEEDS_PREFIX, master_lockbox.key().as_ref(), &entry_id.to_le_bytes()],
+        bump
+    )]
+    pub entry_copies: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury PDA (system account); required when the tier's
+    /// entry write fee is non-zero
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Required together with `treasury`
+    pub system_program: Option<Program<'info, System>>,
 }
 
 pub fn update_password_entry_handler(
@@ -201,36 +640,72 @@ pub fn update_password_entry_handler(
     _chunk_index: u16,
     entry_id: u64,
     new_encrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
 ) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
     let storage_chunk = &mut ctx.accounts.storage_chunk;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
     // SECURITY: Rate limiting
-    require!(
-        master_lockbox.check_rate_limit(current_timestamp, 1),
-        crate::errors::LockboxError::RateLimitExceeded
-    );
+    check_write_rate(master_lockbox, current_timestamp)?;
+    master_lockbox.check_chunk_layout(storage_chunk)?;
+    storage_chunk.check_layout_epoch(expected_layout_epoch)?;
 
     // SECURITY: Validate AEAD ciphertext format
-    const MIN_AEAD_SIZE: usize = 40;
-    require!(
-        new_encrypted_data.len() >= MIN_AEAD_SIZE,
-        crate::errors::LockboxError::InvalidDataSize
-    );
+    check_aead_size(new_encrypted_data.len())?;
+    check_payload_framing(&new_encrypted_data)?;
+    master_lockbox.check_entry_size(new_encrypted_data.len(), current_timestamp)?;
+
+    if let Some(policy) = SecurityPolicy::load(&ctx.accounts.security_policy)? {
+        policy.check_size(new_encrypted_data.len())?;
+    }
 
     // Check subscription is active
-    require!(
-        master_lockbox.is_subscription_active(current_timestamp),
-        crate::errors::LockboxError::SubscriptionExpired
-    );
+    check_subscription_active(master_lockbox, current_timestamp)?;
+    master_lockbox.record_write_burst(new_encrypted_data.len(), Clock::get()?.slot)?;
+
+    // Aliases hold a pointer, not ciphertext; update the target instead.
+    // Trashed entries are frozen so edits cannot reset their purge timer.
+    let header = storage_chunk.get_entry_header(entry_id)?;
+    require!(!header.is_alias(), crate::errors::LockboxError::EntryIsAlias);
+    require!(!header.is_trashed(), crate::errors::LockboxError::EntryTrashed);
+    require!(!header.spans_chunks(), crate::errors::LockboxError::EntrySpansChunks);
+    check_entry_type_size(header.entry_type, new_encrypted_data.len())?;
+    let (category, old_size) = (header.category, header.size);
+    let new_size = new_encrypted_data.len() as i64;
+
+    // Spam-prevention fee (waived on paid tiers)
+    let fee = charge_entry_write_fee(
+        master_lockbox.subscription_tier,
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+        ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+    )?;
+    if fee > 0 {
+        emit!(EntryWriteFeeEvent { owner: master_lockbox.owner, entry_id, amount: fee });
+    }
 
     // Update entry
     storage_chunk.update_entry(entry_id, new_encrypted_data, current_timestamp)?;
 
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        registry.record_usage(category, 0, new_size - old_size as i64);
+    }
+
     // Update master lockbox
     master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
     master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    // Copies in other vaults no longer match
+    cascade_to_copies(
+        &ctx.accounts.entry_copies,
+        &ctx.accounts.owner,
+        master_lockbox.key(),
+        entry_id,
+        false,
+        current_timestamp,
+    )?;
 
     msg!("Password entry {} updated", entry_id);
 
@@ -243,9 +718,14 @@ pub fn update_password_entry_handler(
 pub struct DeletePasswordEntry<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ crate::errors::LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ crate::errors::LockboxError::CategoryIndexRequired
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -264,12 +744,47 @@ pub struct DeletePasswordEntry<'info> {
 
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Keeps the category index in sync; required once the vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    /// CHECK: The entry's copy list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `EntryCopies::load`
+    #[account(
+        mut,
+        seeds = [EntryCopies::SEEDS_PREFIX, master_lockbox.key().as_ref(), &entry_id.to_le_bytes()],
+        bump
+    )]
+    pub entry_copies: UncheckedAccount<'info>,
+
+    /// Required when the security policy names a delete co-signer
+    pub delete_cosigner: Option<Signer<'info>>,
 }
 
 pub fn delete_password_entry_handler(
     ctx: Context<DeletePasswordEntry>,
     _chunk_index: u16,
     entry_id: u64,
+    expected_layout_epoch: u32,
 ) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
     let storage_chunk = &mut ctx.accounts.storage_chunk;
@@ -280,6 +795,8 @@ pub fn delete_password_entry_handler(
         master_lockbox.check_rate_limit(current_timestamp, 1),
         crate::errors::LockboxError::RateLimitExceeded
     );
+    master_lockbox.check_chunk_layout(storage_chunk)?;
+    storage_chunk.check_layout_epoch(expected_layout_epoch)?;
 
     // Check subscription is active
     require!(
@@ -287,15 +804,426 @@ pub fn delete_password_entry_handler(
         crate::errors::LockboxError::SubscriptionExpired
     );
 
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+    if let Some(cosigner) = policy.and_then(|p| p.delete_cosigner) {
+        require!(
+            ctx.accounts.delete_cosigner.as_ref().is_some_and(|s| s.key() == cosigner),
+            crate::errors::LockboxError::CosignerRequired
+        );
+    }
+
+    // Deleting an alias only removes the pointer. Deleting a real entry also
+    // removes its aliases in this chunk and in any chunks passed as remaining
+    // accounts; aliases in chunks not passed are left dangling.
+    let header = storage_chunk.get_entry_header(entry_id)?;
+    require!(!header.spans_chunks(), crate::errors::LockboxError::EntrySpansChunks);
+    let (is_alias, category, size) = (header.is_alias(), header.category, header.size);
+
     // Delete entry
     storage_chunk.delete_entry(entry_id, current_timestamp)?;
 
+    // Aliases are not counted against their category
+    if let Some(registry) = ctx.accounts.category_registry.as_mut().filter(|_| !is_alias) {
+        registry.record_usage(category, -1, -(size as i64));
+    }
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        category_index.remove(category, entry_id);
+    }
+    master_lockbox.decrement_entries();
+    master_lockbox.forget_recent_entry(entry_id);
+
+    let mut aliases_removed = 0;
+    if !is_alias {
+        let target = AliasTarget {
+            chunk_index: storage_chunk.chunk_index,
+            entry_id,
+        };
+        aliases_removed += storage_chunk.delete_aliases_of(target, current_timestamp)?;
+        let mut chunks = ChunkSet::load(ctx.remaining_accounts, master_lockbox, &[storage_chunk.key()])?;
+        for chunk in chunks.iter_mut() {
+            aliases_removed += chunk.delete_aliases_of(target, current_timestamp)?;
+        }
+        chunks.commit(master_lockbox)?;
+        for _ in 0..aliases_removed {
+            master_lockbox.decrement_entries();
+        }
+    }
+
     // Update master lockbox
     master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
-    master_lockbox.decrement_entries();
     master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    if !is_alias {
+        cascade_to_copies(
+            &ctx.accounts.entry_copies,
+            &ctx.accounts.owner,
+            master_lockbox.key(),
+            entry_id,
+            true,
+            current_timestamp,
+        )?;
+    }
+
+    msg!("Password entry {} deleted ({} aliases removed)", entry_id, aliases_removed);
+
+    Ok(())
+}
 
-    msg!("Password entry {} deleted", entry_id);
+/// Move an entry to another chunk
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct MovePasswordEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Chunk currently holding the entry
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Move an entry into another chunk, keeping its ID
+///
+/// The destination chunk and any chunks holding aliases of the entry are
+/// passed as writable remaining accounts; aliases in the source chunk and
+/// in every passed chunk are re-pointed at the new location. Both chunks'
+/// layout epochs are bumped, so operations built against either before the
+/// move fail with `StaleChunkLayout`.
+///
+/// # Errors
+/// * `InvalidChunkIndex` - Destination is the source chunk
+/// * `InvalidChunkAccount` - Destination was not passed, or a passed
+///   account is not a chunk of this lockbox
+/// * `EntryIsAlias` - Aliases are not moved; recreate them instead
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+/// * `StaleChunkLayout` - The source chunk changed since the client read it
+pub fn move_password_entry_handler(
+    ctx: Context<MovePasswordEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+    dest_chunk_index: u16,
+    expected_layout_epoch: u32,
+) -> Result<()> {
+    let source = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    ctx.accounts.master_lockbox.check_writer(device.as_ref(), current_timestamp)?;
+
+    // SECURITY: Rate limiting
+    require!(
+        ctx.accounts.master_lockbox.check_rate_limit(current_timestamp, 1),
+        crate::errors::LockboxError::RateLimitExceeded
+    );
+    ctx.accounts.master_lockbox.check_chunk_layout(source)?;
+    source.check_layout_epoch(expected_layout_epoch)?;
+
+    require!(
+        dest_chunk_index != source.chunk_index,
+        crate::errors::LockboxError::InvalidChunkIndex
+    );
+    let header = source.get_entry_header(entry_id)?;
+    require!(!header.is_alias(), crate::errors::LockboxError::EntryIsAlias);
+    require!(!header.spans_chunks(), crate::errors::LockboxError::EntrySpansChunks);
+
+    let mut chunks = ChunkSet::load(
+        ctx.remaining_accounts,
+        &ctx.accounts.master_lockbox,
+        &[source.key()],
+    )?;
+    let dest = chunks
+        .get_mut(dest_chunk_index)
+        .ok_or(crate::errors::LockboxError::InvalidChunkAccount)?;
+
+    let (mut header, data) = source.take_entry(entry_id, current_timestamp)?;
+    header.offset = dest.current_size;
+    dest.add_entry(header, data, current_timestamp)?;
+
+    let from = AliasTarget { chunk_index: source.chunk_index, entry_id };
+    let to = AliasTarget { chunk_index: dest_chunk_index, entry_id };
+    source.retarget_aliases(from, to, current_timestamp)?;
+    for chunk in chunks.iter_mut() {
+        chunk.retarget_aliases(from, to, current_timestamp)?;
+    }
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    master_lockbox.bump_chunk_layout(source)?;
+    let dest = chunks
+        .get_mut(dest_chunk_index)
+        .ok_or(crate::errors::LockboxError::InvalidChunkAccount)?;
+    master_lockbox.bump_chunk_layout(dest)?;
+    master_lockbox.update_chunk_usage(source.chunk_index, source.current_size)?;
+    chunks.commit(master_lockbox)?;
+    master_lockbox.touch(current_timestamp);
+
+    msg!(
+        "Password entry {} moved from chunk {} to chunk {}",
+        entry_id,
+        source.chunk_index,
+        dest_chunk_index
+    );
+
+    Ok(())
+}
+
+/// Mark or unmark an entry as archived
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct SetEntryArchived<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Set the archived flag (archived entries can be moved to cold chunks)
+pub fn set_entry_archived_handler(
+    ctx: Context<SetEntryArchived>,
+    _chunk_index: u16,
+    entry_id: u64,
+    archived: bool,
+) -> Result<()> {
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // Archived entries may be migrated to another chunk whole
+    let header = storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.spans_chunks(), crate::errors::LockboxError::EntrySpansChunks);
+    header.set_archived(archived);
+    header.last_modified = current_timestamp;
+    storage_chunk.last_modified = current_timestamp;
+
+    msg!("Password entry {} archived: {}", entry_id, archived);
+    Ok(())
+}
+
+/// Set an entry's favorite and archived flags in one call
+///
+/// `flags` replaces the entry's `USER_FLAGS` bits; alias, trash and
+/// continuation flags are left alone. The entry's `last_modified` is not
+/// touched, since it tracks ciphertext writes (and the trash time).
+///
+/// # Errors
+/// * `InvalidEntryFlags` - `flags` has bits outside `USER_FLAGS`
+/// * `EntryTrashed` - Restore the entry first
+/// * `EntrySpansChunks` - Entries spanning chunks cannot be archived
+pub fn set_entry_flags_handler(
+    ctx: Context<SetEntryArchived>,
+    _chunk_index: u16,
+    entry_id: u64,
+    flags: u8,
+) -> Result<()> {
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let header = storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.is_trashed(), crate::errors::LockboxError::EntryTrashed);
+    require!(
+        flags & DataEntryHeader::FLAG_ARCHIVED == 0 || !header.spans_chunks(),
+        crate::errors::LockboxError::EntrySpansChunks
+    );
+    header.set_user_flags(flags)?;
+    storage_chunk.last_modified = current_timestamp;
+
+    msg!("Password entry {} flags set to {:#04x}", entry_id, flags);
+    Ok(())
+}
+
+/// Enable or disable the recently-used entry list
+#[derive(Accounts)]
+pub struct SetRecentEntriesTracking<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Opt in or out of recording retrievals on-chain
+///
+/// Retrieval history is visible to anyone reading the account, so it is off
+/// by default. Disabling clears the existing list.
+pub fn set_recent_entries_tracking_handler(
+    ctx: Context<SetRecentEntriesTracking>,
+    enabled: bool,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+
+    master_lockbox.recent_entries_enabled = enabled;
+    if !enabled {
+        master_lockbox.recent_entries = [0; crate::state::MAX_RECENT_ENTRIES];
+    }
+
+    msg!("Recent entries tracking {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Create an alias of an existing entry
+#[derive(Accounts)]
+#[instruction(chunk_index: u16, target_chunk_index: u16)]
+pub struct CreateEntryAlias<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ crate::errors::LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Chunk receiving the alias header
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// Chunk holding the target entry (may be the same as `storage_chunk`)
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &target_chunk_index.to_le_bytes()
+        ],
+        bump = target_chunk.bump
+    )]
+    pub target_chunk: Account<'info, StorageChunk>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn create_entry_alias_handler(
+    ctx: Context<CreateEntryAlias>,
+    _chunk_index: u16,
+    target_chunk_index: u16,
+    target_entry_id: u64,
+    category: u32,
+    title_hash: [u8; 32],
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // SECURITY: Rate limiting
+    require!(
+        master_lockbox.check_rate_limit(current_timestamp, 1),
+        crate::errors::LockboxError::RateLimitExceeded
+    );
+
+    // Check subscription is active
+    require!(
+        master_lockbox.is_subscription_active(current_timestamp),
+        crate::errors::LockboxError::SubscriptionExpired
+    );
+
+    // Aliases always point at real entries (no chains)
+    let target_header = ctx.accounts.target_chunk.get_entry_header(target_entry_id)?;
+    require!(!target_header.is_alias(), crate::errors::LockboxError::EntryIsAlias);
+    require!(!target_header.spans_chunks(), crate::errors::LockboxError::EntrySpansChunks);
+    let entry_type = target_header.entry_type;
+
+    let payload = AliasTarget {
+        chunk_index: target_chunk_index,
+        entry_id: target_entry_id,
+    }
+    .try_to_vec()?;
+
+    // Check capacity
+    require!(
+        master_lockbox.has_capacity(payload.len() as u64, current_timestamp),
+        crate::errors::LockboxError::InsufficientStorageCapacity
+    );
+
+    require!(
+        storage_chunk.can_fit(payload.len() as u32),
+        crate::errors::LockboxError::InsufficientChunkCapacity
+    );
+
+    let entry_id = master_lockbox.get_next_entry_id();
+
+    let entry_header = DataEntryHeader {
+        entry_id,
+        offset: storage_chunk.current_size,
+        size: payload.len() as u32,
+        entry_type,
+        category,
+        title_hash,
+        created_at: current_timestamp,
+        last_modified: current_timestamp,
+        access_count: 0,
+        flags: DataEntryHeader::FLAG_ALIAS,
+        next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+        next_offset: 0,
+        tag_mask: 0,
+        rotate_after: 0,
+        parent_entry: 0,
+        linked_entry_ids: Vec::new(),
+    };
+
+    storage_chunk.add_entry(entry_header, payload, current_timestamp)?;
+
+    // Update master lockbox
+    master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
+    master_lockbox.increment_entries();
+    master_lockbox.touch(current_timestamp);
+
+    msg!(
+        "Alias {} created for entry {} in chunk {}",
+        entry_id,
+        target_entry_id,
+        target_chunk_index
+    );
 
     Ok(())
 }
@@ -0,0 +1,193 @@
+/**
+ * Pre-flight Checks
+ *
+ * `can_store` and `can_expand` run the same validation as
+ * `store_password_entry` and `expand_chunk` without writing anything, and
+ * return a `PreflightResult` via return data. Clients simulate them before
+ * asking for a signature so the UI can explain exactly why an operation
+ * would fail (and by how much) instead of surfacing a bare error code.
+ *
+ * Checks run in the same order as the real handlers, so the first failure
+ * reported is the one the transaction would hit.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{MasterLockbox, SecurityPolicy, StorageChunk};
+use super::chunk_management::MAX_REALLOC_INCREMENT;
+use super::password_entry::{AUTO_EXPAND_MAX_MARGIN, MIN_AEAD_SIZE};
+
+/// Outcome of a pre-flight check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PreflightResult {
+    /// The operation would succeed; `fee_lamports` is the entry write fee
+    /// for stores, or the additional rent for expansions
+    Ok { fee_lamports: u64 },
+    /// Payload is smaller than a nonce plus authentication tag
+    InvalidDataSize,
+    /// The security policy requires sizes padded to `PADDING_BLOCK`
+    UnpaddedCiphertext,
+    /// Writes are rate limited until `retry_at`
+    RateLimited { retry_at: i64 },
+    /// The paid subscription lapsed at `expired_at`
+    SubscriptionExpired { expired_at: i64 },
+    /// The tier's total capacity has `available` bytes left
+    InsufficientStorageCapacity { available: u64 },
+    /// The chunk has `available` bytes left; `auto_expandable` means a store
+    /// with a payer would expand the chunk to cover the shortfall
+    InsufficientChunkCapacity { available: u32, auto_expandable: bool },
+    /// Expansion would exceed `MAX_CHUNK_SIZE` by more than `max_additional`
+    ChunkTooLarge { max_additional: u32 },
+    /// Expansion must be between 1 and `max_increment` bytes
+    ReallocTooLarge { max_increment: u32 },
+    /// The tier allows entries of at most `max_size` bytes
+    EntryTooLarge { max_size: u32 },
+}
+
+/// Check whether storing `size` bytes in `chunk` would succeed
+pub fn preflight_store(
+    master: &MasterLockbox,
+    chunk: &StorageChunk,
+    policy: Option<&SecurityPolicy>,
+    size: u32,
+    current_timestamp: i64,
+) -> PreflightResult {
+    if !master.check_rate_limit(current_timestamp, 1) {
+        return PreflightResult::RateLimited { retry_at: master.last_accessed + 1 };
+    }
+
+    if (size as usize) < MIN_AEAD_SIZE {
+        return PreflightResult::InvalidDataSize;
+    }
+
+    let max_size = master.effective_tier(current_timestamp).max_entry_size();
+    if size as usize > max_size {
+        return PreflightResult::EntryTooLarge { max_size: max_size as u32 };
+    }
+
+    if policy.is_some_and(|p| p.check_size(size as usize).is_err()) {
+        return PreflightResult::UnpaddedCiphertext;
+    }
+
+    if !master.is_subscription_active(current_timestamp) {
+        return PreflightResult::SubscriptionExpired { expired_at: master.subscription_expires };
+    }
+
+    if !master.has_capacity(size as u64, current_timestamp) {
+        let available = master
+            .capacity_limit(current_timestamp)
+            .saturating_sub(master.total_storage_used());
+        return PreflightResult::InsufficientStorageCapacity { available };
+    }
+
+    if !chunk.can_fit(size) {
+        let shortfall = size - chunk.available_space();
+        let auto_expandable = shortfall <= AUTO_EXPAND_MAX_MARGIN
+            && preflight_expand(chunk, shortfall, 0) == PreflightResult::Ok { fee_lamports: 0 };
+        return PreflightResult::InsufficientChunkCapacity {
+            available: chunk.available_space(),
+            auto_expandable,
+        };
+    }
+
+    PreflightResult::Ok { fee_lamports: master.subscription_tier.entry_write_fee() }
+}
+
+/// Check whether expanding `chunk` by `additional_size` bytes would succeed
+///
+/// `additional_rent` is passed through as the fee on success.
+pub fn preflight_expand(
+    chunk: &StorageChunk,
+    additional_size: u32,
+    additional_rent: u64,
+) -> PreflightResult {
+    let max_additional = StorageChunk::MAX_CHUNK_SIZE.saturating_sub(chunk.max_capacity);
+    if additional_size > max_additional {
+        return PreflightResult::ChunkTooLarge { max_additional };
+    }
+
+    if additional_size == 0 || additional_size > MAX_REALLOC_INCREMENT {
+        return PreflightResult::ReallocTooLarge { max_increment: MAX_REALLOC_INCREMENT };
+    }
+
+    PreflightResult::Ok { fee_lamports: additional_rent }
+}
+
+/// Simulate a store (read-only, no signer required)
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct CanStore<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+}
+
+pub fn can_store_handler(
+    ctx: Context<CanStore>,
+    _chunk_index: u16,
+    size: u32,
+) -> Result<PreflightResult> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+
+    Ok(preflight_store(
+        &ctx.accounts.master_lockbox,
+        &ctx.accounts.storage_chunk,
+        policy.as_ref(),
+        size,
+        current_timestamp,
+    ))
+}
+
+/// Simulate a chunk expansion (read-only, no signer required)
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct CanExpand<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+}
+
+pub fn can_expand_handler(
+    ctx: Context<CanExpand>,
+    _chunk_index: u16,
+    additional_size: u32,
+) -> Result<PreflightResult> {
+    let current_len = ctx.accounts.storage_chunk.to_account_info().data_len();
+    let rent = Rent::get()?;
+    let additional_rent = rent
+        .minimum_balance(current_len + additional_size as usize)
+        .saturating_sub(rent.minimum_balance(current_len));
+
+    Ok(preflight_expand(&ctx.accounts.storage_chunk, additional_size, additional_rent))
+}
@@ -0,0 +1,109 @@
+/**
+ * Program Config
+ *
+ * Billing operations that act on other users' vaults (issuing invoices,
+ * refunds) need a program-level admin. The `ProgramConfig` PDA records it.
+ * Only the program's upgrade authority can create the config, so the admin
+ * can't be claimed by whoever calls first after a deploy; from then on the
+ * admin alone can hand the role over or change the invoice receiver.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::program::Lockbox;
+use crate::state::ProgramConfig;
+
+/// Create the program config (upgrade authority only)
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [ProgramConfig::SEEDS_PREFIX],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ LockboxError::Unauthorized)]
+    pub program: Program<'info, Lockbox>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ LockboxError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the program config
+///
+/// # Arguments
+/// * `admin` - Wallet that will administer billing
+/// * `invoice_receiver` - SPL token account invoice payments go to
+///
+/// # Errors
+/// * `Unauthorized` - Signer is not the program's upgrade authority
+pub fn initialize_program_config_handler(
+    ctx: Context<InitializeProgramConfig>,
+    admin: Pubkey,
+    invoice_receiver: Pubkey,
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.version = ProgramConfig::CURRENT_VERSION;
+    program_config.admin = admin;
+    program_config.invoice_receiver = invoice_receiver;
+    program_config.refund_count = 0;
+    program_config.bump = ctx.bumps.program_config;
+
+    emit!(ProgramConfigUpdatedEvent { admin, invoice_receiver });
+
+    msg!("Program config initialized (admin: {})", admin);
+    Ok(())
+}
+
+/// Change the program config (admin only)
+#[derive(Accounts)]
+pub struct SetProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [ProgramConfig::SEEDS_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ LockboxError::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Hand the admin role over and/or change the invoice receiver
+///
+/// Open invoices are paid to the receiver configured at payment time.
+///
+/// # Errors
+/// * `Unauthorized` - Signer is not the current admin
+pub fn set_program_config_handler(
+    ctx: Context<SetProgramConfig>,
+    admin: Pubkey,
+    invoice_receiver: Pubkey,
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.admin = admin;
+    program_config.invoice_receiver = invoice_receiver;
+
+    emit!(ProgramConfigUpdatedEvent { admin, invoice_receiver });
+
+    msg!("Program config updated (admin: {})", admin);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct ProgramConfigUpdatedEvent {
+    pub admin: Pubkey,
+    pub invoice_receiver: Pubkey,
+}
@@ -0,0 +1,201 @@
+/**
+ * Index Rebuild
+ *
+ * The master lockbox, the category registry and the category index all
+ * carry counters that entry handlers keep in sync by hand. If one drifts
+ * from the chunks (a bookkeeping bug, or a write that left an optional
+ * account out), `rebuild_indexes` recomputes them from every chunk of the
+ * vault:
+ *
+ * - Master lockbox: each chunk's recorded usage and capacity,
+ *   `storage_used`, `total_capacity`, `storage_chunks_count` and
+ *   `total_entries`; `next_entry_id` is raised past every program-assigned
+ *   ID so it can never be reissued
+ * - Category registry (if passed): each category's entry and byte counts,
+ *   and the master lockbox's `categories_count`
+ * - Category index (if passed): replaced as by `rebuild_category_index`
+ *
+ * The encrypted index blob and search index postings are written by the
+ * client and cannot be recomputed on-chain; a search index chunk's usage
+ * is still counted like any other chunk's.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::instructions::category_index::{category_index_entries, fit_category_index};
+use crate::instructions::chunk_set::ChunkSet;
+use crate::instructions::invariants::enforce_invariants;
+use crate::state::{
+    CategoryIndex, CategoryRegistry, MasterLockbox, StorageChunk, MAX_CATEGORY_INDEX_ENTRIES,
+};
+
+/// Recompute the master lockbox's usage and entry counters from `chunks`
+///
+/// `chunks` should be every registered chunk; chunks that are not
+/// registered are ignored. Aliases and trashed entries count toward
+/// `total_entries` (as on the write paths); continuation segments do not.
+pub fn rebuild_master_aggregates(master_lockbox: &mut MasterLockbox, chunks: &[StorageChunk]) {
+    for chunk in chunks {
+        if let Some(info) = master_lockbox
+            .storage_chunks
+            .iter_mut()
+            .find(|c| c.chunk_index == chunk.chunk_index)
+        {
+            info.size_used = chunk.current_size;
+            info.max_capacity = chunk.max_capacity;
+        }
+    }
+
+    master_lockbox.storage_used = master_lockbox.storage_chunks.iter().map(|c| c.size_used as u64).sum();
+    master_lockbox.total_capacity = master_lockbox.storage_chunks.iter().map(|c| c.max_capacity as u64).sum();
+    master_lockbox.storage_chunks_count = master_lockbox
+        .storage_chunks
+        .iter()
+        .map(|c| c.chunk_index + 1)
+        .max()
+        .unwrap_or(0);
+
+    let headers = || chunks.iter().flat_map(|chunk| &chunk.entry_headers);
+    master_lockbox.total_entries = headers().filter(|header| !header.is_continuation()).count() as u64;
+
+    if let Some(highest) = headers()
+        .map(|header| header.entry_id)
+        .filter(|id| !MasterLockbox::is_client_entry_id(*id))
+        .max()
+    {
+        master_lockbox.next_entry_id = master_lockbox.next_entry_id.max(highest.saturating_add(1));
+    }
+}
+
+/// Recount every category's entries and bytes from `chunks`
+///
+/// Aliases are not counted. A spanning entry counts once, with the bytes
+/// of all its segments.
+pub fn recount_categories(registry: &mut CategoryRegistry, chunks: &[StorageChunk]) {
+    for category in registry.categories.iter_mut() {
+        category.entry_count = 0;
+        category.bytes_used = 0;
+    }
+
+    for header in chunks
+        .iter()
+        .flat_map(|chunk| &chunk.entry_headers)
+        .filter(|header| !header.is_alias())
+    {
+        let entries = if header.is_continuation() { 0 } else { 1 };
+        registry.record_usage(header.category, entries, header.size as i64);
+    }
+}
+
+/// Recompute the vault's aggregates from its chunks
+#[derive(Accounts)]
+pub struct RebuildIndexes<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Optional: per-category entry and byte counts are recounted
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Optional: the category index is rebuilt
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Recompute the master lockbox's counters, and the category registry and
+/// index when passed, from every chunk of the vault
+///
+/// Every registered chunk must be passed as a read-only remaining account.
+///
+/// # Errors
+/// * `IndexRebuildChunksMissing` - Not every registered chunk was passed
+/// * `InvalidChunkAccount` - A remaining account is not a registered chunk
+///   of this lockbox, or appears twice
+/// * `CategoryIndexFull` - The index is passed and the vault has more than
+///   `MAX_CATEGORY_INDEX_ENTRIES` entries
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+pub fn rebuild_indexes_handler(ctx: Context<RebuildIndexes>) -> Result<()> {
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    ctx.accounts
+        .master_lockbox
+        .check_writer(device.as_ref(), Clock::get()?.unix_timestamp)?;
+
+    let chunks = ChunkSet::load_read_only(ctx.remaining_accounts, &ctx.accounts.master_lockbox)?;
+    require!(
+        chunks.len() == ctx.accounts.master_lockbox.storage_chunks.len(),
+        LockboxError::IndexRebuildChunksMissing
+    );
+
+    let master_key = ctx.accounts.master_lockbox.key();
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let previous_total_entries = master_lockbox.total_entries;
+    let previous_storage_used = master_lockbox.storage_used;
+    rebuild_master_aggregates(master_lockbox, chunks.chunks());
+
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        recount_categories(registry, chunks.chunks());
+        master_lockbox.categories_count = registry.categories.len() as u32;
+    }
+
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        let entries = category_index_entries(chunks.chunks());
+        require!(
+            entries.len() <= MAX_CATEGORY_INDEX_ENTRIES,
+            LockboxError::CategoryIndexFull
+        );
+        category_index.entries = entries;
+        fit_category_index(
+            category_index,
+            ctx.accounts.owner.to_account_info(),
+            Some(ctx.accounts.system_program.to_account_info()),
+        )?;
+    }
+
+    enforce_invariants(master_key, master_lockbox, &[])?;
+
+    emit!(IndexesRebuiltEvent {
+        master_lockbox: master_key,
+        previous_total_entries,
+        total_entries: master_lockbox.total_entries,
+        previous_storage_used,
+        storage_used: master_lockbox.storage_used,
+    });
+
+    msg!("Indexes rebuilt: {} entries, {} bytes used",
+        master_lockbox.total_entries, master_lockbox.storage_used);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct IndexesRebuiltEvent {
+    pub master_lockbox: Pubkey,
+    pub previous_total_entries: u64,
+    pub total_entries: u64,
+    pub previous_storage_used: u64,
+    pub storage_used: u64,
+}
@@ -9,18 +9,28 @@
 //! ### Setup Phase
 //! 1. `initialize_recovery_config` - Owner creates recovery configuration
 //! 2. `add_guardian` - Owner adds guardians with encrypted shares
-//! 3. `accept_guardianship` - Guardian accepts their role
-//! 4. `remove_guardian` - Owner removes a guardian
+//! 3. `set_guardian_agreement` - Owner records the hash of the guardian's terms (optional)
+//! 4. `accept_guardianship` - Guardian accepts their role (and co-signs the terms)
+//! 5. `remove_guardian` - Owner removes a guardian
+//! 6. `resend_guardian_invitation` - Owner restarts a pending guardian's acceptance window
+//! 7. `expire_guardian_invitations` - Anyone removes guardians whose invitations lapsed
+//! 8. `disable_recovery` / `enable_recovery` - Owner pauses or resumes recovery
+//! 9. `set_owner_activity_window` - Owner blocks recovery while they are active
 //!
 //! ### Recovery Phase
 //! 1. `initiate_recovery` - Guardian starts recovery request (with time-lock)
 //! 2. `approve_recovery` - M guardians submit their shares
 //! 3. `complete_recovery` - Reconstruct key and transfer ownership
 //! 4. `cancel_recovery` - Owner cancels active recovery
+//!
+//! ### Teardown
+//! 1. `close_recovery_config` - Owner closes the config once no request is in progress
 
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::*;
+use super::deny_list::check_not_denied;
+use super::inbox::deliver_to_inbox;
 
 /// Initialize recovery configuration
 ///
@@ -43,7 +53,7 @@ pub fn initialize_recovery_config_handler(
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
     let master_lockbox = &ctx.accounts.master_lockbox;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify subscription tier (Premium or Pro required)
     require!(
@@ -69,9 +79,13 @@ pub fn initialize_recovery_config_handler(
     recovery_config.total_guardians = 0;
     recovery_config.guardians = Vec::new();
     recovery_config.recovery_delay = recovery_delay;
-    recovery_config.created_at = clock.unix_timestamp;
-    recovery_config.last_modified = clock.unix_timestamp;
+    recovery_config.invitation_ttl = DEFAULT_INVITATION_TTL;
+    recovery_config.recovery_disabled = false;
+    recovery_config.owner_activity_window = 0;
+    recovery_config.created_at = now;
+    recovery_config.last_modified = now;
     recovery_config.last_request_id = 0;
+    recovery_config.version = RecoveryConfig::CURRENT_VERSION;
     recovery_config.bump = ctx.bumps.recovery_config;
 
     msg!("Recovery configuration initialized: threshold={}, delay={}s", threshold, recovery_delay);
@@ -93,7 +107,9 @@ pub fn initialize_recovery_config_handler(
 /// # Security
 /// - Only owner can add guardians
 /// - Share must be encrypted with guardian's pubkey
-/// - Maximum 10 guardians allowed
+/// - Maximum 10 guardians allowed (20 on Pro; the account grows as needed)
+/// - Lapsed invitations are expired first so they don't count toward the limit
+/// - Keys on the vault's deny-list are rejected
 pub fn add_guardian_handler(
     ctx: Context<AddGuardian>,
     guardian_pubkey: Pubkey,
@@ -102,7 +118,7 @@ pub fn add_guardian_handler(
     nickname_encrypted: Vec<u8>,
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -110,9 +126,12 @@ pub fn add_guardian_handler(
         LockboxError::Unauthorized
     );
 
-    // Check maximum guardians
+    let expired = recovery_config.expire_invitations(now);
+    emit_expired_invitations(recovery_config.owner, &expired);
+
+    // Check maximum guardians for the owner's tier
     require!(
-        recovery_config.guardians.len() < MAX_GUARDIANS,
+        recovery_config.guardians.len() < ctx.accounts.master_lockbox.subscription_tier.max_guardians(),
         LockboxError::TooManyGuardians
     );
 
@@ -121,6 +140,7 @@ pub fn add_guardian_handler(
         !recovery_config.guardians.iter().any(|g| g.guardian_pubkey == guardian_pubkey),
         LockboxError::GuardianAlreadyExists
     );
+    check_not_denied(&ctx.accounts.deny_list, &guardian_pubkey)?;
 
     // SECURITY: Validate share_index is non-zero (1-indexed for Shamir)
     require!(
@@ -147,30 +167,100 @@ pub fn add_guardian_handler(
     );
 
     // Add guardian
+    let expires_at = now + recovery_config.invitation_ttl;
     recovery_config.guardians.push(Guardian {
         guardian_pubkey,
         share_index,
         encrypted_share,
-        added_at: clock.unix_timestamp,
+        added_at: now,
         nickname_encrypted,
         status: GuardianStatus::PendingAcceptance,
+        invited_at: now,
+        expires_at,
+        accepted_at: None,
+        acceptance_hash: [0u8; 32],
     });
 
     recovery_config.total_guardians = recovery_config.guardians.len() as u8;
-    recovery_config.last_modified = clock.unix_timestamp;
+    recovery_config.last_modified = now;
+
+    let config_key = recovery_config.key();
+    if let Some(inbox) = ctx.accounts.guardian_inbox.as_mut() {
+        deliver_to_inbox(
+            inbox,
+            InboxItemKind::GuardianInvitation,
+            ctx.accounts.owner.key(),
+            config_key,
+            now,
+        );
+    }
 
     msg!("Guardian added: pubkey={}, share_index={}", guardian_pubkey, share_index);
 
     Ok(())
 }
 
+/// Record the hash of an off-chain guardian agreement
+///
+/// The guardian must still be pending; once they accept, the terms they
+/// co-signed cannot be replaced.
+///
+/// # Arguments
+/// * `guardian_pubkey` - Guardian bound by the agreement
+/// * `agreement_hash` - SHA-256 of the agreement document
+pub fn set_guardian_agreement_handler(
+    ctx: Context<SetGuardianAgreement>,
+    guardian_pubkey: Pubkey,
+    agreement_hash: [u8; 32],
+) -> Result<()> {
+    let recovery_config = &ctx.accounts.recovery_config;
+    let agreement = &mut ctx.accounts.guardian_agreement;
+    let now = cluster_timestamp()?;
+
+    let guardian = recovery_config
+        .guardians
+        .iter()
+        .find(|g| g.guardian_pubkey == guardian_pubkey)
+        .ok_or(LockboxError::GuardianNotFound)?;
+
+    require!(
+        guardian.status == GuardianStatus::PendingAcceptance,
+        LockboxError::GuardianAlreadyAccepted
+    );
+
+    agreement.version = GuardianAgreement::CURRENT_VERSION;
+    agreement.recovery_config = recovery_config.key();
+    agreement.owner = recovery_config.owner;
+    agreement.guardian = guardian_pubkey;
+    agreement.agreement_hash = agreement_hash;
+    agreement.set_at = now;
+    agreement.accepted_at = None;
+    agreement.bump = ctx.bumps.guardian_agreement;
+
+    msg!("Guardian agreement set: pubkey={}", guardian_pubkey);
+
+    Ok(())
+}
+
 /// Guardian accepts their role
 ///
 /// Guardian explicitly accepts their role in the recovery network.
 /// This activates the guardian and allows them to participate in recovery.
-pub fn accept_guardianship_handler(ctx: Context<AcceptGuardianship>) -> Result<()> {
+/// The acceptance time and `Guardian::acceptance_hash` are recorded on the
+/// guardian for later audits.
+///
+/// # Arguments
+/// * `agreement_hash` - Hash of the terms the guardian accepts; must match
+///   the owner's recorded agreement if one exists, ignored otherwise
+pub fn accept_guardianship_handler(
+    ctx: Context<AcceptGuardianship>,
+    agreement_hash: [u8; 32],
+) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
+    let config_key = recovery_config.key();
     let guardian_pubkey = ctx.accounts.guardian.key();
+    let now = cluster_timestamp()?;
+    let was_armed = recovery_config.is_armed();
 
     // Find guardian
     let guardian = recovery_config
@@ -184,9 +274,44 @@ pub fn accept_guardianship_handler(ctx: Context<AcceptGuardianship>) -> Result<(
         guardian.status == GuardianStatus::PendingAcceptance,
         LockboxError::GuardianAlreadyAccepted
     );
+    require!(
+        !guardian.is_invitation_expired(now),
+        LockboxError::GuardianInvitationExpired
+    );
+
+    // Co-sign the owner's terms, if any
+    let agreement_info = ctx.accounts.guardian_agreement.to_account_info();
+    let mut agreed_terms = [0u8; 32];
+    if let Some(mut agreement) = GuardianAgreement::load(&agreement_info)? {
+        require!(
+            agreement.agreement_hash == agreement_hash,
+            LockboxError::AgreementHashMismatch
+        );
+        agreement.accepted_at = Some(now);
+        agreement.try_serialize(&mut &mut agreement_info.try_borrow_mut_data()?[..])?;
+        agreed_terms = agreement_hash;
+
+        emit!(GuardianAgreementAcceptedEvent {
+            owner: agreement.owner,
+            guardian: guardian_pubkey,
+            agreement_hash,
+            accepted_at: now,
+        });
+    }
 
-    // Activate guardian
+    // Activate guardian and record the consent for audits
     guardian.status = GuardianStatus::Active;
+    guardian.accepted_at = Some(now);
+    let acceptance_hash = guardian.acceptance_hash(&config_key, &agreed_terms, now);
+    guardian.acceptance_hash = acceptance_hash;
+
+    emit!(GuardianAcceptedEvent {
+        owner: recovery_config.owner,
+        guardian: guardian_pubkey,
+        acceptance_hash,
+        accepted_at: now,
+    });
+    emit_if_armed_changed(recovery_config, was_armed);
 
     msg!("Guardian accepted: pubkey={}", guardian_pubkey);
 
@@ -205,7 +330,7 @@ pub fn remove_guardian_handler(
     guardian_pubkey: Pubkey,
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -239,19 +364,203 @@ pub fn remove_guardian_handler(
     }
 
     // Remove guardian
+    let was_armed = recovery_config.is_armed();
     recovery_config.guardians.remove(guardian_index);
     recovery_config.total_guardians = recovery_config.guardians.len() as u8;
-    recovery_config.last_modified = clock.unix_timestamp;
+    recovery_config.last_modified = now;
+    emit_if_armed_changed(recovery_config, was_armed);
 
     msg!("Guardian removed: pubkey={}, remaining={}", guardian_pubkey, recovery_config.total_guardians);
 
     Ok(())
 }
 
+/// Resend a pending guardian's invitation
+///
+/// Restarts the acceptance window from now using the config's current
+/// invitation TTL. Also revives a lapsed invitation that no one has
+/// expired yet. Optionally delivers the invitation to the guardian's inbox
+/// again.
+///
+/// # Arguments
+/// * `guardian_pubkey` - Pending guardian to re-invite
+pub fn resend_guardian_invitation_handler(
+    ctx: Context<ResendGuardianInvitation>,
+    guardian_pubkey: Pubkey,
+) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let now = cluster_timestamp()?;
+    let invitation_ttl = recovery_config.invitation_ttl;
+
+    let guardian = recovery_config
+        .guardians
+        .iter_mut()
+        .find(|g| g.guardian_pubkey == guardian_pubkey)
+        .ok_or(LockboxError::GuardianNotFound)?;
+    require!(
+        guardian.status == GuardianStatus::PendingAcceptance,
+        LockboxError::GuardianAlreadyAccepted
+    );
+
+    guardian.invited_at = now;
+    guardian.expires_at = now + invitation_ttl;
+    let expires_at = guardian.expires_at;
+    recovery_config.last_modified = now;
+
+    let config_key = recovery_config.key();
+    if let Some(inbox) = ctx.accounts.guardian_inbox.as_mut() {
+        deliver_to_inbox(
+            inbox,
+            InboxItemKind::GuardianInvitation,
+            ctx.accounts.owner.key(),
+            config_key,
+            now,
+        );
+    }
+
+    emit!(GuardianInvitationResentEvent {
+        owner: ctx.accounts.owner.key(),
+        guardian: guardian_pubkey,
+        expires_at,
+    });
+
+    msg!("Guardian invitation resent: pubkey={}, expires_at={}", guardian_pubkey, expires_at);
+
+    Ok(())
+}
+
+/// Remove guardians whose invitations lapsed (permissionless crank)
+///
+/// Emits one `GuardianInvitationExpiredEvent` per removed guardian.
+/// `add_guardian` runs the same cleanup, so this is only needed to tidy a
+/// config the owner isn't otherwise touching.
+pub fn expire_guardian_invitations_handler(ctx: Context<ExpireGuardianInvitations>) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let now = cluster_timestamp()?;
+
+    let expired = recovery_config.expire_invitations(now);
+    if !expired.is_empty() {
+        recovery_config.last_modified = now;
+    }
+    emit_expired_invitations(recovery_config.owner, &expired);
+
+    msg!("Expired {} guardian invitations", expired.len());
+
+    Ok(())
+}
+
+/// Set how long future guardian invitations stay open
+///
+/// Applies to invitations added or resent afterwards; open invitations
+/// keep their current expiry.
+///
+/// # Arguments
+/// * `invitation_ttl` - Seconds, between `MIN_INVITATION_TTL` and `MAX_INVITATION_TTL`
+pub fn set_guardian_invitation_ttl_handler(
+    ctx: Context<SetGuardianInvitationTtl>,
+    invitation_ttl: i64,
+) -> Result<()> {
+    require!(
+        (MIN_INVITATION_TTL..=MAX_INVITATION_TTL).contains(&invitation_ttl),
+        LockboxError::InvalidInvitationTtl
+    );
+
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    recovery_config.invitation_ttl = invitation_ttl;
+    recovery_config.last_modified = cluster_timestamp()?;
+
+    msg!("Guardian invitation TTL set to {}s", invitation_ttl);
+
+    Ok(())
+}
+
+/// Pause recovery on this config
+///
+/// Guardians stay in place, but `initiate_recovery` and `complete_recovery`
+/// fail with `RecoveryDisabled` until `enable_recovery` is called. Requests
+/// already in progress should be cancelled with `cancel_recovery`.
+pub fn disable_recovery_handler(ctx: Context<SetRecoveryEnabled>) -> Result<()> {
+    set_recovery_enabled(&mut ctx.accounts.recovery_config, false)
+}
+
+/// Resume recovery after `disable_recovery`
+pub fn enable_recovery_handler(ctx: Context<SetRecoveryEnabled>) -> Result<()> {
+    set_recovery_enabled(&mut ctx.accounts.recovery_config, true)
+}
+
+fn set_recovery_enabled(recovery_config: &mut RecoveryConfig, enabled: bool) -> Result<()> {
+    let now = cluster_timestamp()?;
+    recovery_config.recovery_disabled = !enabled;
+    recovery_config.last_modified = now;
+
+    emit!(RecoveryEnabledChangedEvent {
+        owner: recovery_config.owner,
+        enabled,
+        changed_at: now,
+    });
+
+    msg!("Recovery {}", if enabled { "enabled" } else { "disabled" });
+
+    Ok(())
+}
+
+/// Block recovery while the owner has recently used the vault
+///
+/// Guardians cannot start a request until `window` seconds after the
+/// master lockbox was last accessed. Requests already in progress are
+/// unaffected.
+///
+/// # Arguments
+/// * `window` - 0 to disable, otherwise between `MIN_OWNER_ACTIVITY_WINDOW`
+///   and `MAX_OWNER_ACTIVITY_WINDOW` seconds
+pub fn set_owner_activity_window_handler(
+    ctx: Context<SetOwnerActivityWindow>,
+    window: i64,
+) -> Result<()> {
+    require!(
+        window == 0 || (MIN_OWNER_ACTIVITY_WINDOW..=MAX_OWNER_ACTIVITY_WINDOW).contains(&window),
+        LockboxError::InvalidActivityWindow
+    );
+
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    recovery_config.owner_activity_window = window;
+    recovery_config.last_modified = cluster_timestamp()?;
+
+    msg!("Owner activity window set to {}s", window);
+
+    Ok(())
+}
+
+/// Emit `RecoveryArmedChangedEvent` if accepting or removing a guardian
+/// moved the config across its threshold
+fn emit_if_armed_changed(recovery_config: &RecoveryConfig, was_armed: bool) {
+    let armed = recovery_config.is_armed();
+    if armed != was_armed {
+        emit!(RecoveryArmedChangedEvent {
+            owner: recovery_config.owner,
+            armed,
+            active_guardians: recovery_config.active_guardian_count() as u8,
+            threshold: recovery_config.threshold,
+        });
+    }
+}
+
+fn emit_expired_invitations(owner: Pubkey, expired: &[Guardian]) {
+    for guardian in expired {
+        emit!(GuardianInvitationExpiredEvent {
+            owner,
+            guardian: guardian.guardian_pubkey,
+            invited_at: guardian.invited_at,
+            expired_at: guardian.expires_at,
+        });
+    }
+}
+
 /// Initiate wallet recovery
 ///
 /// A guardian starts the recovery process. This creates a RecoveryRequest
-/// with a time-lock delay. The owner can cancel during this delay.
+/// with a time-lock delay. The owner can cancel during this delay. The
+/// config must be armed (accepted guardians >= threshold).
 ///
 /// # Arguments
 /// * `request_id` - Unique request ID (monotonic counter)
@@ -263,7 +572,7 @@ pub fn initiate_recovery_handler(
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
     let recovery_request = &mut ctx.accounts.recovery_request;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
     let requester = ctx.accounts.guardian.key();
 
     // Verify guardian is active
@@ -271,6 +580,16 @@ pub fn initiate_recovery_handler(
         recovery_config.is_active_guardian(&requester),
         LockboxError::NotActiveGuardian
     );
+    require!(!recovery_config.recovery_disabled, LockboxError::RecoveryDisabled);
+    require!(recovery_config.is_armed(), LockboxError::RecoveryNotArmed);
+    check_recorded_timestamp(ctx.accounts.master_lockbox.last_accessed, now)?;
+    require!(
+        !recovery_config.is_owner_recently_active(
+            ctx.accounts.master_lockbox.last_accessed,
+            now
+        ),
+        LockboxError::OwnerRecentlyActive
+    );
 
     // SECURITY: Enforce monotonic request_id to prevent replay attacks
     require!(
@@ -282,12 +601,13 @@ pub fn initiate_recovery_handler(
     recovery_request.owner = recovery_config.owner;
     recovery_request.requester = requester;
     recovery_request.request_id = request_id;
-    recovery_request.requested_at = clock.unix_timestamp;
-    recovery_request.ready_at = clock.unix_timestamp + recovery_config.recovery_delay;
+    recovery_request.requested_at = now;
+    recovery_request.ready_at = now + recovery_config.recovery_delay;
     recovery_request.expires_at = recovery_request.ready_at + RECOVERY_EXPIRATION_PERIOD;
     recovery_request.approvals = Vec::new();
     recovery_request.new_owner = new_owner;
     recovery_request.status = RecoveryStatus::Pending;
+    recovery_request.version = RecoveryRequest::CURRENT_VERSION;
     recovery_request.bump = ctx.bumps.recovery_request;
 
     // Update last request ID
@@ -323,7 +643,7 @@ pub fn approve_recovery_handler(
 ) -> Result<()> {
     let recovery_config = &ctx.accounts.recovery_config;
     let recovery_request = &mut ctx.accounts.recovery_request;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
     let guardian_pubkey = ctx.accounts.guardian.key();
 
     // Verify guardian is active
@@ -334,13 +654,13 @@ pub fn approve_recovery_handler(
 
     // Verify recovery is ready (time-lock elapsed)
     require!(
-        recovery_request.is_ready(clock.unix_timestamp),
+        recovery_request.is_ready(now),
         LockboxError::RecoveryNotReady
     );
 
     // SECURITY: Check if recovery has expired
     require!(
-        clock.unix_timestamp <= recovery_request.expires_at,
+        now <= recovery_request.expires_at,
         LockboxError::RecoveryExpired
     );
 
@@ -350,6 +670,12 @@ pub fn approve_recovery_handler(
         LockboxError::GuardianAlreadyApproved
     );
 
+    // Pro tier configs can have more guardians than a request stores
+    require!(
+        recovery_request.approvals.len() < MAX_RECOVERY_APPROVALS,
+        LockboxError::RecoveryApprovalsFull
+    );
+
     // Get guardian share index
     let guardian = recovery_config
         .get_guardian(&guardian_pubkey)
@@ -360,7 +686,7 @@ pub fn approve_recovery_handler(
         guardian: guardian_pubkey,
         share_index: guardian.share_index,
         share_decrypted,
-        approved_at: clock.unix_timestamp,
+        approved_at: now,
     });
 
     // Check if we have enough approvals
@@ -372,6 +698,14 @@ pub fn approve_recovery_handler(
         );
     }
 
+    emit!(RecoveryProgressEvent {
+        owner: recovery_request.owner,
+        request_id: recovery_request.request_id,
+        guardian: guardian_pubkey,
+        approvals: recovery_request.approvals.len() as u8,
+        threshold: recovery_config.threshold,
+    });
+
     msg!(
         "Recovery approved: guardian={}, approvals={}/{}",
         guardian_pubkey,
@@ -387,6 +721,8 @@ pub fn approve_recovery_handler(
 /// After M guardians approve, the master key can be reconstructed client-side
 /// and ownership transferred to the new wallet.
 ///
+/// Must be signed by the requester or the requested new owner.
+///
 /// # NOTE
 /// The actual Shamir reconstruction happens CLIENT-SIDE. This instruction
 /// only transfers ownership after verification that sufficient shares exist.
@@ -395,6 +731,9 @@ pub fn complete_recovery_handler(ctx: Context<CompleteRecovery>) -> Result<()> {
     let recovery_request = &mut ctx.accounts.recovery_request;
     let master_lockbox = &mut ctx.accounts.master_lockbox;
 
+    // A request started before the owner paused recovery cannot finish
+    require!(!recovery_config.recovery_disabled, LockboxError::RecoveryDisabled);
+
     // Verify sufficient approvals
     require!(
         recovery_request.has_sufficient_approvals(recovery_config.threshold),
@@ -421,6 +760,7 @@ pub fn complete_recovery_handler(ctx: Context<CompleteRecovery>) -> Result<()> {
         previous_owner: recovery_config.owner,
         new_owner,
         request_id: recovery_request.request_id,
+        completed_by: ctx.accounts.caller.key(),
     });
 
     Ok(())
@@ -454,6 +794,28 @@ pub fn cancel_recovery_handler(ctx: Context<CancelRecovery>) -> Result<()> {
     Ok(())
 }
 
+/// Close the recovery configuration and reclaim its rent
+///
+/// Blocked while the latest recovery request is still pending or awaiting
+/// approvals. Request IDs are monotonic and each new request needs the
+/// config, so only the latest request can still be in progress.
+pub fn close_recovery_config_handler(ctx: Context<CloseRecoveryConfig>) -> Result<()> {
+    let request_info = ctx.accounts.latest_request.to_account_info();
+    let now = cluster_timestamp()?;
+
+    if request_info.owner == &crate::ID && !request_info.data_is_empty() {
+        let request = RecoveryRequest::try_deserialize(&mut &request_info.try_borrow_data()?[..])?;
+        require!(
+            !request.is_in_progress(now),
+            LockboxError::ActiveRecoveryExists
+        );
+    }
+
+    msg!("Recovery configuration closed - rent reclaimed");
+
+    Ok(())
+}
+
 // ============================================================================
 // Account Validation Contexts
 // ============================================================================
@@ -464,15 +826,15 @@ pub struct InitializeRecoveryConfig<'info> {
         init,
         payer = owner,
         space = 8 + RecoveryConfig::INIT_SPACE,
-        seeds = [b"recovery_config", owner.key().as_ref()],
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
         bump
     )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
     #[account(
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -483,23 +845,97 @@ pub struct InitializeRecoveryConfig<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
 pub struct AddGuardian<'info> {
     #[account(
         mut,
-        seeds = [b"recovery_config", owner.key().as_ref()],
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized,
+        realloc = RecoveryConfig::space_for(recovery_config.guardians.len() + 1),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    /// Owner's vault (read-only, for the tier's guardian limit)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional: the guardian's inbox, notified of the invitation
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, guardian_pubkey.as_ref()],
+        bump = guardian_inbox.bump
+    )]
+    pub guardian_inbox: Option<Account<'info, Inbox>>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
+pub struct SetGuardianAgreement<'info> {
+    #[account(
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
         bump = recovery_config.bump,
         constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
     )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + GuardianAgreement::INIT_SPACE,
+        seeds = [
+            GuardianAgreement::SEEDS_PREFIX,
+            recovery_config.key().as_ref(),
+            guardian_pubkey.as_ref()
+        ],
+        bump
+    )]
+    pub guardian_agreement: Account<'info, GuardianAgreement>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct AcceptGuardianship<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
+    /// CHECK: Agreement PDA for this guardian; may be uninitialized when the
+    /// owner set no terms. Loaded in the handler via `GuardianAgreement::load`
+    #[account(
+        mut,
+        seeds = [
+            GuardianAgreement::SEEDS_PREFIX,
+            recovery_config.key().as_ref(),
+            guardian.key().as_ref()
+        ],
+        bump
+    )]
+    pub guardian_agreement: UncheckedAccount<'info>,
+
     pub guardian: Signer<'info>,
 }
 
@@ -507,7 +943,78 @@ pub struct AcceptGuardianship<'info> {
 pub struct RemoveGuardian<'info> {
     #[account(
         mut,
-        seeds = [b"recovery_config", owner.key().as_ref()],
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
+pub struct ResendGuardianInvitation<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    pub owner: Signer<'info>,
+
+    /// Optional: the guardian's inbox, notified of the invitation again
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, guardian_pubkey.as_ref()],
+        bump = guardian_inbox.bump
+    )]
+    pub guardian_inbox: Option<Account<'info, Inbox>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireGuardianInvitations<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardianInvitationTtl<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecoveryEnabled<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOwnerActivityWindow<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
         bump = recovery_config.bump,
         constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
     )]
@@ -521,7 +1028,7 @@ pub struct RemoveGuardian<'info> {
 pub struct InitiateRecovery<'info> {
     #[account(
         mut,
-        seeds = [b"recovery_config", recovery_config.owner.as_ref()],
+        seeds = [RecoveryConfig::SEEDS_PREFIX, recovery_config.owner.as_ref()],
         bump = recovery_config.bump
     )]
     pub recovery_config: Account<'info, RecoveryConfig>,
@@ -530,11 +1037,19 @@ pub struct InitiateRecovery<'info> {
         init,
         payer = guardian,
         space = 8 + RecoveryRequest::INIT_SPACE,
-        seeds = [b"recovery_request", recovery_config.owner.as_ref(), &request_id.to_le_bytes()],
+        seeds = [RecoveryRequest::SEEDS_PREFIX, recovery_config.owner.as_ref(), &request_id.to_le_bytes()],
         bump
     )]
     pub recovery_request: Account<'info, RecoveryRequest>,
 
+    /// Read for the owner's last activity
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == recovery_config.owner @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
     #[account(mut)]
     pub guardian: Signer<'info>,
 
@@ -543,9 +1058,22 @@ pub struct InitiateRecovery<'info> {
 
 #[derive(Accounts)]
 pub struct ApproveRecovery<'info> {
+    #[account(
+        seeds = [RecoveryConfig::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            RecoveryRequest::SEEDS_PREFIX,
+            recovery_config.owner.as_ref(),
+            &recovery_request.request_id.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.owner == recovery_config.owner @ LockboxError::RecoveryRequestMismatch
+    )]
     pub recovery_request: Account<'info, RecoveryRequest>,
 
     pub guardian: Signer<'info>,
@@ -553,34 +1081,91 @@ pub struct ApproveRecovery<'info> {
 
 #[derive(Accounts)]
 pub struct CompleteRecovery<'info> {
+    #[account(
+        seeds = [RecoveryConfig::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            RecoveryRequest::SEEDS_PREFIX,
+            recovery_config.owner.as_ref(),
+            &recovery_request.request_id.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.owner == recovery_config.owner @ LockboxError::RecoveryRequestMismatch
+    )]
     pub recovery_request: Account<'info, RecoveryRequest>,
 
     #[account(
         mut,
-        seeds = [b"master_lockbox", recovery_config.owner.as_ref()],
-        bump = master_lockbox.bump
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == recovery_config.owner @ LockboxError::Unauthorized,
+        constraint = master_lockbox.allows_sensitive_invocation() @ LockboxError::CpiNotAllowed
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Requester or requested new owner
+    #[account(
+        constraint = recovery_request.can_complete(&caller.key()) @ LockboxError::Unauthorized
+    )]
+    pub caller: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct CancelRecovery<'info> {
     #[account(
-        seeds = [b"recovery_config", owner.key().as_ref()],
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
         bump = recovery_config.bump,
         constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
     )]
     pub recovery_config: Account<'info, RecoveryConfig>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            RecoveryRequest::SEEDS_PREFIX,
+            owner.key().as_ref(),
+            &recovery_request.request_id.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.owner == owner.key() @ LockboxError::RecoveryRequestMismatch
+    )]
     pub recovery_request: Account<'info, RecoveryRequest>,
 
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct CloseRecoveryConfig<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [RecoveryConfig::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfig>,
+
+    /// CHECK: Latest recovery request PDA; may not exist. Inspected in the
+    /// handler only if it is a program-owned account
+    #[account(
+        seeds = [
+            RecoveryRequest::SEEDS_PREFIX,
+            owner.key().as_ref(),
+            &recovery_config.last_request_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub latest_request: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
 // ============================================================================
 // Events
 // ============================================================================
@@ -598,4 +1183,62 @@ pub struct RecoveryCompletedEvent {
     pub previous_owner: Pubkey,
     pub new_owner: Pubkey,
     pub request_id: u64,
+    pub completed_by: Pubkey,
+}
+
+/// Emitted after every guardian approval so watchers can report progress
+#[event]
+pub struct RecoveryProgressEvent {
+    pub owner: Pubkey,
+    pub request_id: u64,
+    pub guardian: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct GuardianAcceptedEvent {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub acceptance_hash: [u8; 32],
+    pub accepted_at: i64,
+}
+
+#[event]
+pub struct GuardianAgreementAcceptedEvent {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub agreement_hash: [u8; 32],
+    pub accepted_at: i64,
+}
+
+#[event]
+pub struct GuardianInvitationResentEvent {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct GuardianInvitationExpiredEvent {
+    pub owner: Pubkey,
+    pub guardian: Pubkey,
+    pub invited_at: i64,
+    pub expired_at: i64,
+}
+
+#[event]
+pub struct RecoveryArmedChangedEvent {
+    pub owner: Pubkey,
+    /// Whether accepted guardians now meet the threshold
+    pub armed: bool,
+    pub active_guardians: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct RecoveryEnabledChangedEvent {
+    pub owner: Pubkey,
+    pub enabled: bool,
+    pub changed_at: i64,
 }
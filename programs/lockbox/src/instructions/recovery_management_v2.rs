@@ -22,6 +22,11 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::hash::hash;
 use crate::state::*;
 use crate::errors::*;
+use super::deny_list::check_not_denied;
+use super::inbox::deliver_to_inbox;
+
+/// Most guardians `add_guardians_batch` adds in one instruction
+pub const MAX_GUARDIAN_BATCH: usize = 10;
 
 /// Initialize recovery configuration V2 (with commitments)
 ///
@@ -35,7 +40,7 @@ pub fn initialize_recovery_config_v2_handler(
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
     let master_lockbox = &ctx.accounts.master_lockbox;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify subscription tier
     require!(
@@ -64,10 +69,13 @@ pub fn initialize_recovery_config_v2_handler(
     recovery_config.total_guardians = 0;
     recovery_config.guardians = Vec::new();
     recovery_config.recovery_delay = recovery_delay;
-    recovery_config.created_at = clock.unix_timestamp;
-    recovery_config.last_modified = clock.unix_timestamp;
+    recovery_config.created_at = now;
+    recovery_config.last_modified = now;
     recovery_config.last_request_id = 0;
     recovery_config.master_secret_hash = master_secret_hash;
+    recovery_config.cooldown_mode = CooldownMode::Timestamp;
+    recovery_config.recovery_cooldown = DEFAULT_RECOVERY_COOLDOWN;
+    recovery_config.version = RecoveryConfigV2::CURRENT_VERSION;
     recovery_config.bump = ctx.bumps.recovery_config;
 
     msg!("Recovery config V2 initialized: threshold={}, delay={}s", threshold, recovery_delay);
@@ -87,7 +95,7 @@ pub fn add_guardian_v2_handler(
     nickname_encrypted: Vec<u8>,
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify owner
     require!(
@@ -95,42 +103,32 @@ pub fn add_guardian_v2_handler(
         LockboxError::Unauthorized
     );
 
-    // Check maximum guardians
-    require!(
-        recovery_config.guardians.len() < MAX_GUARDIANS,
-        LockboxError::TooManyGuardians
-    );
-
-    // Check guardian doesn't exist
-    require!(
-        !recovery_config.guardians.iter().any(|g| g.guardian_pubkey == guardian_pubkey),
-        LockboxError::GuardianAlreadyExists
-    );
-
-    // Validate share_index
-    require!(
-        share_index > 0 && share_index <= 255,
-        LockboxError::InvalidShareIndex
-    );
-
-    // Validate uniqueness
-    require!(
-        !recovery_config.guardians.iter().any(|g| g.share_index == share_index),
-        LockboxError::DuplicateShareIndex
-    );
-
-    // Add guardian
-    recovery_config.guardians.push(GuardianV2 {
-        guardian_pubkey,
-        share_index,
-        share_commitment,
-        added_at: clock.unix_timestamp,
-        nickname_encrypted,
-        status: GuardianStatus::PendingAcceptance,
-    });
-
-    recovery_config.total_guardians = recovery_config.guardians.len() as u8;
-    recovery_config.last_modified = clock.unix_timestamp;
+    check_not_denied(&ctx.accounts.deny_list, &guardian_pubkey)?;
+
+    // Tier limit, unique key and share index
+    recovery_config.add_guardian(
+        GuardianV2 {
+            guardian_pubkey,
+            share_index,
+            share_commitment,
+            added_at: now,
+            nickname_encrypted,
+            status: GuardianStatus::PendingAcceptance,
+        },
+        ctx.accounts.master_lockbox.subscription_tier.max_guardians(),
+    )?;
+    recovery_config.last_modified = now;
+
+    let config_key = recovery_config.key();
+    if let Some(inbox) = ctx.accounts.guardian_inbox.as_mut() {
+        deliver_to_inbox(
+            inbox,
+            InboxItemKind::GuardianInvitation,
+            ctx.accounts.owner.key(),
+            config_key,
+            now,
+        );
+    }
 
     emit!(GuardianAddedV2Event {
         owner: recovery_config.owner,
@@ -143,6 +141,67 @@ pub fn add_guardian_v2_handler(
     Ok(())
 }
 
+/// One guardian of an `add_guardians_batch` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NewGuardian {
+    pub guardian_pubkey: Pubkey,
+    pub share_index: u8,
+    pub share_commitment: [u8; 32],
+    pub nickname_encrypted: Vec<u8>,
+}
+
+/// Add several guardians with share commitments in one instruction (V2)
+///
+/// Either every guardian is added or none is, so a setup sent together
+/// with `initialize_recovery_config_v2` cannot leave a half-configured
+/// config behind. Guardians' inboxes are not notified; invite them
+/// off-chain alongside their shares.
+///
+/// # Errors
+/// * `InvalidBatchSize` - Batch is empty or exceeds `MAX_GUARDIAN_BATCH`
+/// * `KeyDenied` - A guardian is on the vault's deny-list
+/// * Any error of `RecoveryConfigV2::add_guardian`, for the first guardian
+///   that fails
+pub fn add_guardians_batch_handler(
+    ctx: Context<AddGuardiansBatch>,
+    guardians: Vec<NewGuardian>,
+) -> Result<()> {
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    let max_guardians = ctx.accounts.master_lockbox.subscription_tier.max_guardians();
+    let now = cluster_timestamp()?;
+
+    require!(
+        (1..=MAX_GUARDIAN_BATCH).contains(&guardians.len()),
+        LockboxError::InvalidBatchSize
+    );
+
+    for guardian in guardians {
+        check_not_denied(&ctx.accounts.deny_list, &guardian.guardian_pubkey)?;
+        recovery_config.add_guardian(
+            GuardianV2 {
+                guardian_pubkey: guardian.guardian_pubkey,
+                share_index: guardian.share_index,
+                share_commitment: guardian.share_commitment,
+                added_at: now,
+                nickname_encrypted: guardian.nickname_encrypted,
+                status: GuardianStatus::PendingAcceptance,
+            },
+            max_guardians,
+        )?;
+
+        emit!(GuardianAddedV2Event {
+            owner: recovery_config.owner,
+            guardian: guardian.guardian_pubkey,
+            share_index: guardian.share_index,
+        });
+    }
+    recovery_config.last_modified = now;
+
+    msg!("{} guardians added", recovery_config.guardians.len());
+
+    Ok(())
+}
+
 /// Initiate recovery V2 (generates challenge)
 ///
 /// Creates on-chain encrypted challenge that proves requester
@@ -158,7 +217,8 @@ pub fn initiate_recovery_v2_handler(
 ) -> Result<()> {
     let recovery_config = &mut ctx.accounts.recovery_config;
     let recovery_request = &mut ctx.accounts.recovery_request;
-    let clock = Clock::get()?;
+    let clock = cluster_clock()?;
+    let now = clock.unix_timestamp;
     let requester = ctx.accounts.guardian.key();
 
     // Verify guardian is active
@@ -166,11 +226,13 @@ pub fn initiate_recovery_v2_handler(
         recovery_config.is_active_guardian(&requester),
         LockboxError::NotActiveGuardian
     );
+    require!(recovery_config.is_armed(), LockboxError::RecoveryNotArmed);
 
-    // SECURITY FIX (Phase 3): Check recovery rate limit (1 hour cooldown)
-    const RECOVERY_COOLDOWN: i64 = 3600; // 1 hour in seconds
+    // SECURITY FIX (Phase 3): Check recovery rate limit (configurable
+    // cooldown, one hour by default)
+    check_recorded_timestamp(recovery_config.last_recovery_attempt, now)?;
     require!(
-        recovery_config.check_recovery_rate_limit(clock.unix_timestamp, RECOVERY_COOLDOWN),
+        recovery_config.is_recovery_cooldown_over(&clock),
         LockboxError::RecoveryRateLimitExceeded
     );
 
@@ -183,8 +245,8 @@ pub fn initiate_recovery_v2_handler(
     // Update last_request_id BEFORE creating request (atomic operation)
     recovery_config.last_request_id = request_id;
 
-    // Update last_recovery_attempt timestamp for rate limiting
-    recovery_config.last_recovery_attempt = clock.unix_timestamp;
+    // Record the attempt for rate limiting
+    recovery_config.record_recovery_attempt(&clock);
 
     // Validate challenge format (80 bytes: 24 nonce + 32 ciphertext + 16 tag)
     require!(
@@ -196,17 +258,18 @@ pub fn initiate_recovery_v2_handler(
     recovery_request.owner = recovery_config.owner;
     recovery_request.requester = requester;
     recovery_request.request_id = request_id;
-    recovery_request.requested_at = clock.unix_timestamp;
-    recovery_request.ready_at = clock.unix_timestamp + recovery_config.recovery_delay;
+    recovery_request.requested_at = now;
+    recovery_request.ready_at = now + recovery_config.recovery_delay;
     recovery_request.expires_at = recovery_request.ready_at + RECOVERY_EXPIRATION_PERIOD;
     recovery_request.challenge = RecoveryChallenge {
         encrypted_challenge,
         challenge_hash,
-        created_at: clock.unix_timestamp,
+        created_at: now,
     };
     recovery_request.participating_guardians = Vec::new();
     recovery_request.new_owner = new_owner;
     recovery_request.status = RecoveryStatus::Pending;
+    recovery_request.version = RecoveryRequestV2::CURRENT_VERSION;
     recovery_request.bump = ctx.bumps.recovery_request;
 
     // Note: last_request_id already updated atomically above (line 177)
@@ -237,7 +300,7 @@ pub fn confirm_participation_handler(
 ) -> Result<()> {
     let recovery_config = &ctx.accounts.recovery_config;
     let recovery_request = &mut ctx.accounts.recovery_request;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
     let guardian_pubkey = ctx.accounts.guardian.key();
 
     // Verify guardian is active
@@ -248,7 +311,7 @@ pub fn confirm_participation_handler(
 
     // Verify recovery is ready
     require!(
-        recovery_request.is_ready_for_proof(clock.unix_timestamp),
+        recovery_request.is_ready_for_proof(now),
         LockboxError::RecoveryNotReady
     );
 
@@ -258,6 +321,12 @@ pub fn confirm_participation_handler(
         LockboxError::GuardianAlreadyApproved
     );
 
+    // Pro tier configs can have more guardians than a request stores
+    require!(
+        recovery_request.participating_guardians.len() < MAX_RECOVERY_APPROVALS,
+        LockboxError::RecoveryApprovalsFull
+    );
+
     // Add confirmation
     recovery_request.participating_guardians.push(guardian_pubkey);
 
@@ -271,6 +340,14 @@ pub fn confirm_participation_handler(
         );
     }
 
+    emit!(RecoveryProgressV2Event {
+        owner: recovery_request.owner,
+        request_id: recovery_request.request_id,
+        guardian: guardian_pubkey,
+        confirmations: recovery_request.participating_guardians.len() as u8,
+        threshold: recovery_config.threshold,
+    });
+
     msg!(
         "Guardian confirmed: {}, total={}/{}",
         guardian_pubkey,
@@ -300,7 +377,7 @@ pub fn complete_recovery_with_proof_handler(
     let recovery_config = &ctx.accounts.recovery_config;
     let recovery_request = &mut ctx.accounts.recovery_request;
     let master_lockbox = &mut ctx.accounts.master_lockbox;
-    let clock = Clock::get()?;
+    let now = cluster_timestamp()?;
 
     // Verify sufficient participants
     require!(
@@ -316,7 +393,7 @@ pub fn complete_recovery_with_proof_handler(
 
     // Verify not expired
     require!(
-        clock.unix_timestamp <= recovery_request.expires_at,
+        now <= recovery_request.expires_at,
         LockboxError::RecoveryExpired
     );
 
@@ -378,6 +455,58 @@ pub fn complete_recovery_with_proof_handler(
     Ok(())
 }
 
+/// Set how the V2 recovery cooldown is measured and how long it is
+///
+/// Guardians cannot start a request until `cooldown` units have passed
+/// since the last one. Slot and epoch cooldowns follow the ledger rather
+/// than the validator-voted clock, so they cannot be shortened by clock
+/// drift.
+///
+/// # Arguments
+/// * `mode` - Clock the cooldown is measured on
+/// * `cooldown` - Seconds, slots or epochs (per `mode`), between 1 and
+///   `mode.max_cooldown()`
+///
+/// # Errors
+/// * `InvalidRecoveryCooldown` - `cooldown` is out of range for `mode`
+pub fn set_recovery_cooldown_v2_handler(
+    ctx: Context<SetRecoveryCooldownV2>,
+    mode: CooldownMode,
+    cooldown: u64,
+) -> Result<()> {
+    require!(mode.is_valid_cooldown(cooldown), LockboxError::InvalidRecoveryCooldown);
+
+    let recovery_config = &mut ctx.accounts.recovery_config;
+    recovery_config.cooldown_mode = mode;
+    recovery_config.recovery_cooldown = cooldown;
+    recovery_config.last_modified = cluster_timestamp()?;
+
+    msg!("Recovery cooldown set to {} ({:?})", cooldown, mode);
+
+    Ok(())
+}
+
+/// Close the V2 recovery configuration and reclaim its rent
+///
+/// Blocked while the latest recovery request is still in progress (see
+/// `close_recovery_config`).
+pub fn close_recovery_config_v2_handler(ctx: Context<CloseRecoveryConfigV2>) -> Result<()> {
+    let request_info = ctx.accounts.latest_request.to_account_info();
+    let now = cluster_timestamp()?;
+
+    if request_info.owner == &crate::ID && !request_info.data_is_empty() {
+        let request = RecoveryRequestV2::try_deserialize(&mut &request_info.try_borrow_data()?[..])?;
+        require!(
+            !request.is_in_progress(now),
+            LockboxError::ActiveRecoveryExists
+        );
+    }
+
+    msg!("Recovery configuration V2 closed - rent reclaimed");
+
+    Ok(())
+}
+
 // ============================================================================
 // Account Validation Contexts
 // ============================================================================
@@ -388,15 +517,15 @@ pub struct InitializeRecoveryConfigV2<'info> {
         init,
         payer = owner,
         space = 8 + RecoveryConfigV2::INIT_SPACE,
-        seeds = [b"recovery_config_v2", owner.key().as_ref()],
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, owner.key().as_ref()],
         bump
     )]
     pub recovery_config: Account<'info, RecoveryConfigV2>,
 
     #[account(
-        seeds = [b"master_lockbox", owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -407,23 +536,84 @@ pub struct InitializeRecoveryConfigV2<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
 pub struct AddGuardianV2<'info> {
     #[account(
         mut,
-        seeds = [b"recovery_config_v2", owner.key().as_ref()],
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, owner.key().as_ref()],
         bump = recovery_config.bump,
-        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized,
+        realloc = RecoveryConfigV2::space_for(recovery_config.guardians.len() + 1),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub recovery_config: Account<'info, RecoveryConfigV2>,
+
+    /// Owner's vault (read-only, for the tier's guardian limit)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional: the guardian's inbox, notified of the invitation
+    #[account(
+        mut,
+        seeds = [Inbox::SEEDS_PREFIX, guardian_pubkey.as_ref()],
+        bump = guardian_inbox.bump
+    )]
+    pub guardian_inbox: Option<Account<'info, Inbox>>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardians: Vec<NewGuardian>)]
+pub struct AddGuardiansBatch<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized,
+        realloc = RecoveryConfigV2::space_for(recovery_config.guardians.len() + guardians.len()),
+        realloc::payer = owner,
+        realloc::zero = false
     )]
     pub recovery_config: Account<'info, RecoveryConfigV2>,
 
+    /// Owner's vault (read-only, for the tier's guardian limit)
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: The vault's deny-list, which may not exist; the address is
+    /// fixed by seeds and the data is read with `DenyList::load`
+    #[account(seeds = [DenyList::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub deny_list: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 pub struct InitiateRecoveryV2<'info> {
     #[account(
         mut,
-        seeds = [b"recovery_config_v2", recovery_config.owner.as_ref()],
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, recovery_config.owner.as_ref()],
         bump = recovery_config.bump
     )]
     pub recovery_config: Account<'info, RecoveryConfigV2>,
@@ -434,7 +624,7 @@ pub struct InitiateRecoveryV2<'info> {
         space = 8 + RecoveryRequestV2::INIT_SPACE,
         // SECURITY FIX (VULN-003): Use next request_id in PDA derivation
         seeds = [
-            b"recovery_request_v2",
+            RecoveryRequestV2::SEEDS_PREFIX,
             recovery_config.owner.as_ref(),
             &(recovery_config.last_request_id + 1).to_le_bytes()
         ],
@@ -450,9 +640,22 @@ pub struct InitiateRecoveryV2<'info> {
 
 #[derive(Accounts)]
 pub struct ConfirmParticipation<'info> {
+    #[account(
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
     pub recovery_config: Account<'info, RecoveryConfigV2>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            RecoveryRequestV2::SEEDS_PREFIX,
+            recovery_config.owner.as_ref(),
+            &recovery_request.request_id.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.owner == recovery_config.owner @ LockboxError::RecoveryRequestMismatch
+    )]
     pub recovery_request: Account<'info, RecoveryRequestV2>,
 
     pub guardian: Signer<'info>,
@@ -460,21 +663,76 @@ pub struct ConfirmParticipation<'info> {
 
 #[derive(Accounts)]
 pub struct CompleteRecoveryV2<'info> {
+    #[account(
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, recovery_config.owner.as_ref()],
+        bump = recovery_config.bump
+    )]
     pub recovery_config: Account<'info, RecoveryConfigV2>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            RecoveryRequestV2::SEEDS_PREFIX,
+            recovery_config.owner.as_ref(),
+            &recovery_request.request_id.to_le_bytes()
+        ],
+        bump = recovery_request.bump,
+        constraint = recovery_request.owner == recovery_config.owner @ LockboxError::RecoveryRequestMismatch
+    )]
     pub recovery_request: Account<'info, RecoveryRequestV2>,
 
     #[account(
         mut,
-        seeds = [b"master_lockbox", recovery_config.owner.as_ref()],
-        bump = master_lockbox.bump
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == recovery_config.owner @ LockboxError::Unauthorized,
+        constraint = master_lockbox.allows_sensitive_invocation() @ LockboxError::CpiNotAllowed
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
     pub requester: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetRecoveryCooldownV2<'info> {
+    #[account(
+        mut,
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfigV2>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRecoveryConfigV2<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [RecoveryConfigV2::SEEDS_PREFIX, owner.key().as_ref()],
+        bump = recovery_config.bump,
+        constraint = recovery_config.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub recovery_config: Account<'info, RecoveryConfigV2>,
+
+    /// CHECK: Latest recovery request PDA; may not exist. Inspected in the
+    /// handler only if it is a program-owned account
+    #[account(
+        seeds = [
+            RecoveryRequestV2::SEEDS_PREFIX,
+            owner.key().as_ref(),
+            &recovery_config.last_request_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub latest_request: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
 // ============================================================================
 // Events
 // ============================================================================
@@ -494,6 +752,16 @@ pub struct RecoveryInitiatedV2Event {
     pub ready_at: i64,
 }
 
+/// Emitted after every guardian confirmation so watchers can report progress
+#[event]
+pub struct RecoveryProgressV2Event {
+    pub owner: Pubkey,
+    pub request_id: u64,
+    pub guardian: Pubkey,
+    pub confirmations: u8,
+    pub threshold: u8,
+}
+
 #[event]
 pub struct RecoveryCompletedV2Event {
     pub previous_owner: Pubkey,
@@ -0,0 +1,149 @@
+/**
+ * Treasury Refunds
+ *
+ * Customer-support refunds are paid by the program admin from the
+ * treasury PDA with `issue_refund`. Each one is netted out of the vault's
+ * current billing period (so the next billing snapshot reports what was
+ * actually kept) and recorded in a `RefundRecord` naming the vault, the
+ * billing period, the amount and a hash of the reason.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::errors::LockboxError;
+use crate::state::{cluster_timestamp, MasterLockbox, ProgramConfig, RefundRecord, TREASURY_SEED};
+
+/// Check the treasury can pay `amount` and stay rent exempt
+///
+/// # Errors
+/// * `InsufficientTreasuryFunds` - The payout would leave the treasury
+///   below the rent-exempt minimum
+pub fn check_treasury_payout(treasury_lamports: u64, amount: u64, rent: &Rent) -> Result<()> {
+    let available = treasury_lamports.saturating_sub(rent.minimum_balance(0));
+    require!(amount <= available, LockboxError::InsufficientTreasuryFunds);
+    Ok(())
+}
+
+/// Refund a vault owner from the treasury (admin only)
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct IssueRefund<'info> {
+    #[account(
+        mut,
+        seeds = [ProgramConfig::SEEDS_PREFIX],
+        bump = program_config.bump,
+        has_one = admin @ LockboxError::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == owner @ LockboxError::InvalidRefund
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RefundRecord::INIT_SPACE,
+        seeds = [RefundRecord::SEEDS_PREFIX, &program_config.refund_count.to_le_bytes()],
+        bump
+    )]
+    pub refund_record: Account<'info, RefundRecord>,
+
+    /// CHECK: Treasury PDA (system account) paying the refund
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Vault owner receiving the refund
+    #[account(mut, constraint = recipient.key() == owner @ LockboxError::InvalidRefund)]
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Pay `amount` lamports from the treasury to the vault's owner
+///
+/// # Arguments
+/// * `owner` - Current owner of the vault, who receives the refund
+/// * `amount` - Lamports refunded
+/// * `reason_hash` - Hash of the off-chain refund reason
+///
+/// # Errors
+/// * `Unauthorized` - Signer is not the program admin
+/// * `InvalidRefund` - Zero amount, or `owner` does not own the vault
+/// * `InsufficientTreasuryFunds` - The treasury would drop below rent
+///   exemption
+pub fn issue_refund_handler(
+    ctx: Context<IssueRefund>,
+    owner: Pubkey,
+    amount: u64,
+    reason_hash: [u8; 32],
+) -> Result<()> {
+    require!(amount > 0, LockboxError::InvalidRefund);
+    check_treasury_payout(ctx.accounts.treasury.lamports(), amount, &Rent::get()?)?;
+
+    transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+            &[&[TREASURY_SEED, &[ctx.bumps.treasury]]],
+        ),
+        amount,
+    )?;
+
+    let current_timestamp = cluster_timestamp()?;
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    master_lockbox.record_refund(amount);
+
+    let program_config = &mut ctx.accounts.program_config;
+    let refund_id = program_config.refund_count;
+    program_config.refund_count += 1;
+
+    let refund_record = &mut ctx.accounts.refund_record;
+    refund_record.version = RefundRecord::CURRENT_VERSION;
+    refund_record.refund_id = refund_id;
+    refund_record.master_lockbox = master_lockbox.key();
+    refund_record.owner = owner;
+    refund_record.amount = amount;
+    refund_record.reason_hash = reason_hash;
+    refund_record.tier = master_lockbox.subscription_tier;
+    refund_record.billing_sequence = master_lockbox.billing_sequence;
+    refund_record.issued_by = ctx.accounts.admin.key();
+    refund_record.issued_at = current_timestamp;
+    refund_record.bump = ctx.bumps.refund_record;
+
+    emit!(RefundIssuedEvent {
+        refund_id,
+        master_lockbox: refund_record.master_lockbox,
+        owner,
+        amount,
+        reason_hash,
+        billing_sequence: refund_record.billing_sequence,
+    });
+
+    msg!("Refund {}: {} lamports to {}", refund_id, amount, owner);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct RefundIssuedEvent {
+    pub refund_id: u64,
+    pub master_lockbox: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub reason_hash: [u8; 32],
+    pub billing_sequence: u64,
+}
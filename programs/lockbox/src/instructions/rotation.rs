@@ -0,0 +1,100 @@
+/**
+ * Credential Rotation Tracking
+ *
+ * The owner gives an entry a rotation date (`rotate_after` in its header);
+ * once it passes, anyone may call `emit_rotation_due` to log a
+ * `RotationDueEvent` for the entry. Rotating a credential means rewriting
+ * the entry and setting its next date, so the event log together with the
+ * entries' `last_modified` times shows whether rotation kept to schedule.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{MasterLockbox, StorageChunk};
+use crate::errors::LockboxError;
+use super::password_entry::SetEntryArchived;
+
+/// Set or clear an entry's rotation date
+///
+/// `rotate_after` is a Unix timestamp; 0 removes the schedule. Shares
+/// `set_entry_archived`'s accounts and leaves `last_modified` alone.
+///
+/// # Errors
+/// * `InvalidRotationDate` - `rotate_after` is negative
+/// * `EntryNotFound` - Entry's head segment is not in the chunk
+/// * `EntryTrashed` - Entry is in the trash
+pub fn set_rotation_date_handler(
+    ctx: Context<SetEntryArchived>,
+    _chunk_index: u16,
+    entry_id: u64,
+    rotate_after: i64,
+) -> Result<()> {
+    require!(rotate_after >= 0, LockboxError::InvalidRotationDate);
+
+    let header = ctx.accounts.storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.is_continuation(), LockboxError::EntryNotFound);
+    require!(!header.is_trashed(), LockboxError::EntryTrashed);
+    header.rotate_after = rotate_after;
+
+    msg!("Password entry {} rotation date set to {}", entry_id, rotate_after);
+    Ok(())
+}
+
+/// Log the entries of one chunk that are past their rotation date
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct EmitRotationDue<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+}
+
+/// Emit a `RotationDueEvent` for every live entry past its rotation date
+///
+/// Anyone may call this; it only reads the chunk.
+pub fn emit_rotation_due_handler(ctx: Context<EmitRotationDue>, chunk_index: u16) -> Result<()> {
+    let storage_chunk = &ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let due = storage_chunk.rotation_due(current_timestamp);
+    for (entry_id, rotate_after) in &due {
+        let header = storage_chunk.get_entry_header(*entry_id)?;
+        emit!(RotationDueEvent {
+            master_lockbox: storage_chunk.master_lockbox,
+            chunk_index,
+            entry_id: *entry_id,
+            rotate_after: *rotate_after,
+            last_modified: header.last_modified,
+            reported_at: current_timestamp,
+        });
+    }
+
+    msg!("{} entries due for rotation in chunk {}", due.len(), chunk_index);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct RotationDueEvent {
+    pub master_lockbox: Pubkey,
+    pub chunk_index: u16,
+    pub entry_id: u64,
+    pub rotate_after: i64,
+    pub last_modified: i64,
+    pub reported_at: i64,
+}
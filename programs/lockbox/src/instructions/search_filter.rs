@@ -0,0 +1,192 @@
+/**
+ * Search Filter Instructions
+ *
+ * The vault's `SearchFilter` is a Bloom filter over the same blind-index
+ * tokens `index_entry` writes. Before downloading a vault's headers or a
+ * search index chunk, clients fetch the 1 KB filter (or simulate
+ * `check_token`) to rule out vaults and chunks that cannot match.
+ *
+ * Like the search index, the filter is maintained by the client:
+ * `add_search_tokens` goes in the transaction that stores or updates an
+ * entry and creates the filter on first use. Bits are never cleared for a
+ * single entry, so `reset_search_filter` followed by `add_search_tokens`
+ * for every live entry rebuilds it after many deletions.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{MasterLockbox, SearchFilter, MAX_SEARCH_TOKENS_PER_ENTRY};
+
+/// Add an entry's tokens to the vault's search filter
+#[derive(Accounts)]
+pub struct AddSearchTokens<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + SearchFilter::INIT_SPACE,
+        seeds = [SearchFilter::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub search_filter: Box<Account<'info, SearchFilter>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Add the search tokens of an entry stored in chunk `chunk_index`
+///
+/// # Errors
+/// * `ChunkNotFound` - Chunk is not registered in the master lockbox
+/// * `TooManySearchTokens` - More than `MAX_SEARCH_TOKENS_PER_ENTRY` tokens
+/// * `InvalidSearchToken` - A token is all zeroes
+pub fn add_search_tokens_handler(
+    ctx: Context<AddSearchTokens>,
+    chunk_index: u16,
+    tokens: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        !ctx.accounts.master_lockbox.is_chunk_index_available(chunk_index),
+        LockboxError::ChunkNotFound
+    );
+    require!(
+        tokens.len() <= MAX_SEARCH_TOKENS_PER_ENTRY,
+        LockboxError::TooManySearchTokens
+    );
+    require!(
+        tokens.iter().all(|token| *token != [0u8; 32]),
+        LockboxError::InvalidSearchToken
+    );
+
+    let search_filter = &mut ctx.accounts.search_filter;
+    if search_filter.version == 0 {
+        search_filter.version = SearchFilter::CURRENT_VERSION;
+        search_filter.master_lockbox = ctx.accounts.master_lockbox.key();
+        search_filter.bump = ctx.bumps.search_filter;
+    }
+    for token in &tokens {
+        search_filter.insert(token, chunk_index);
+    }
+
+    emit!(SearchFilterUpdatedEvent {
+        master_lockbox: search_filter.master_lockbox,
+        chunk_index,
+        token_count: tokens.len() as u8,
+        fill_ratio_bps: search_filter.fill_ratio_bps(),
+    });
+
+    msg!("{} tokens added to search filter", tokens.len());
+    Ok(())
+}
+
+/// Clear the vault's search filter
+#[derive(Accounts)]
+pub struct ResetSearchFilter<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [SearchFilter::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = search_filter.bump
+    )]
+    pub search_filter: Box<Account<'info, SearchFilter>>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Clear the search filter so it can be rebuilt
+pub fn reset_search_filter_handler(ctx: Context<ResetSearchFilter>) -> Result<()> {
+    let search_filter = &mut ctx.accounts.search_filter;
+    search_filter.clear();
+
+    emit!(SearchFilterUpdatedEvent {
+        master_lockbox: search_filter.master_lockbox,
+        chunk_index: 0,
+        token_count: 0,
+        fill_ratio_bps: 0,
+    });
+
+    msg!("Search filter reset");
+    Ok(())
+}
+
+/// Close the vault's search filter
+#[derive(Accounts)]
+pub struct CloseSearchFilter<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [SearchFilter::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = search_filter.bump
+    )]
+    pub search_filter: Box<Account<'info, SearchFilter>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_search_filter_handler(_ctx: Context<CloseSearchFilter>) -> Result<()> {
+    msg!("Search filter closed - rent reclaimed");
+    Ok(())
+}
+
+/// Check a token against a vault's search filter
+#[derive(Accounts)]
+pub struct QuerySearchFilter<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [SearchFilter::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = search_filter.bump
+    )]
+    pub search_filter: Box<Account<'info, SearchFilter>>,
+}
+
+/// Whether `token` may be in the vault, or in chunk `chunk_index`
+///
+/// `false` means no entry carries the token; `true` may be a false
+/// positive, so follow up with `search_index` or the chunk's headers.
+pub fn check_token_handler(
+    ctx: Context<QuerySearchFilter>,
+    token: [u8; 32],
+    chunk_index: Option<u16>,
+) -> Result<bool> {
+    Ok(ctx.accounts.search_filter.contains(&token, chunk_index))
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SearchFilterUpdatedEvent {
+    pub master_lockbox: Pubkey,
+    pub chunk_index: u16,
+    /// 0 when the filter was reset
+    pub token_count: u8,
+    pub fill_ratio_bps: u16,
+}
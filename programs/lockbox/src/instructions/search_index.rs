@@ -0,0 +1,164 @@
+/**
+ * Encrypted Search Index Instructions
+ *
+ * A `SearchIndex` chunk maps blind-index tokens to entry IDs. Clients derive
+ * each token as `HMAC(search_key, keyword)`, so the index shows which
+ * entries share a keyword but not the keyword itself. The chunk is created
+ * with `initialize_search_index` and sized and grown like any other chunk;
+ * it holds postings instead of entries.
+ *
+ * The program does not see plaintext, so it cannot index entries itself:
+ * clients send `index_entry` alongside `store_password_entry` and
+ * `update_password_entry`, and `deindex_entry` alongside
+ * `delete_password_entry`, in the same transaction.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::instructions::invariants::enforce_invariants;
+use crate::state::{MasterLockbox, StorageChunk, StorageType};
+
+/// Most entry IDs `search_index` returns (fits the 1 KB return data limit)
+pub const MAX_SEARCH_RESULTS: usize = 127;
+
+/// Set or clear an entry's tokens in a search index chunk
+#[derive(Accounts)]
+#[instruction(index_chunk_index: u16)]
+pub struct UpdateSearchIndex<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &index_chunk_index.to_le_bytes()
+        ],
+        bump = index_chunk.bump,
+        constraint = index_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = index_chunk.owner == owner.key() @ LockboxError::Unauthorized,
+        constraint = index_chunk.data_type == StorageType::SearchIndex @ LockboxError::NotSearchIndexChunk
+    )]
+    pub index_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+}
+
+fn write_tokens(
+    accounts: &mut UpdateSearchIndex,
+    entry_id: u64,
+    tokens: &[[u8; 32]],
+) -> Result<()> {
+    let master_lockbox = &mut accounts.master_lockbox;
+    let index_chunk = &mut accounts.index_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    master_lockbox.check_chunk_layout(index_chunk)?;
+    index_chunk.set_search_tokens(entry_id, tokens, current_timestamp)?;
+
+    master_lockbox.update_chunk_usage(index_chunk.chunk_index, index_chunk.current_size)?;
+    master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**index_chunk])?;
+
+    emit!(SearchIndexUpdatedEvent {
+        master_lockbox: master_lockbox.key(),
+        index_chunk_index: index_chunk.chunk_index,
+        entry_id,
+        token_count: tokens.len() as u8,
+    });
+    Ok(())
+}
+
+/// Replace an entry's search tokens
+///
+/// The entry itself is not checked: it may live in any chunk, and a
+/// `store_password_entry` earlier in the same transaction is what gives it
+/// its ID.
+///
+/// # Errors
+/// * `NotSearchIndexChunk` - Chunk is not a `SearchIndex` chunk
+/// * `TooManySearchTokens` - More than `MAX_SEARCH_TOKENS_PER_ENTRY` tokens
+/// * `InvalidSearchToken` - A token is all zeroes
+/// * `SearchIndexFull` - The chunk has no room; grow it with `expand_chunk`
+pub fn index_entry_handler(
+    ctx: Context<UpdateSearchIndex>,
+    _index_chunk_index: u16,
+    entry_id: u64,
+    tokens: Vec<[u8; 32]>,
+) -> Result<()> {
+    write_tokens(ctx.accounts, entry_id, &tokens)?;
+
+    msg!("Password entry {} indexed under {} tokens", entry_id, tokens.len());
+    Ok(())
+}
+
+/// Remove every token of an entry from a search index chunk
+///
+/// # Errors
+/// * `NotSearchIndexChunk` - Chunk is not a `SearchIndex` chunk
+pub fn deindex_entry_handler(
+    ctx: Context<UpdateSearchIndex>,
+    _index_chunk_index: u16,
+    entry_id: u64,
+) -> Result<()> {
+    write_tokens(ctx.accounts, entry_id, &[])?;
+
+    msg!("Password entry {} removed from search index", entry_id);
+    Ok(())
+}
+
+/// Look up a token in a search index chunk
+#[derive(Accounts)]
+#[instruction(index_chunk_index: u16)]
+pub struct QuerySearchIndex<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &index_chunk_index.to_le_bytes()
+        ],
+        bump = index_chunk.bump,
+        constraint = index_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = index_chunk.data_type == StorageType::SearchIndex @ LockboxError::NotSearchIndexChunk
+    )]
+    pub index_chunk: Account<'info, StorageChunk>,
+}
+
+/// Return the IDs of the entries indexed under `token`
+///
+/// At most `MAX_SEARCH_RESULTS` IDs are returned; clients needing every
+/// match read the chunk's postings directly.
+pub fn search_index_handler(
+    ctx: Context<QuerySearchIndex>,
+    _index_chunk_index: u16,
+    token: [u8; 32],
+) -> Result<Vec<u64>> {
+    let mut entry_ids = ctx.accounts.index_chunk.entries_for_token(&token);
+    entry_ids.truncate(MAX_SEARCH_RESULTS);
+    Ok(entry_ids)
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SearchIndexUpdatedEvent {
+    pub master_lockbox: Pubkey,
+    pub index_chunk_index: u16,
+    pub entry_id: u64,
+    /// 0 when the entry was removed from the index
+    pub token_count: u8,
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use crate::state::{MasterLockbox, SecurityPolicy, HEAT_MAP_BUCKETS};
+
+/// Create or replace the vault's security policy
+#[derive(Accounts)]
+pub struct UpdateSecurityPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + SecurityPolicy::INIT_SPACE,
+        seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub security_policy: Account<'info, SecurityPolicy>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Current co-signer; required to change the policy once one is set
+    pub delete_cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace every policy setting in one call
+///
+/// Once a delete co-signer is configured it must also sign policy changes,
+/// otherwise the owner key alone could remove the requirement. Clearing
+/// `PRIVACY_HEAT_MAP` discards the counts gathered so far.
+///
+/// # Errors
+/// * `CosignerRequired` - Policy has a co-signer who did not sign
+/// * `InvalidPrivacyFlags` - Unknown bits in `privacy_flags`
+pub fn update_security_policy_handler(
+    ctx: Context<UpdateSecurityPolicy>,
+    delete_cosigner: Option<Pubkey>,
+    read_cooldown: u16,
+    padded_sizes: bool,
+    privacy_flags: u8,
+    freeze_on_honeytoken: bool,
+) -> Result<()> {
+    let policy = &mut ctx.accounts.security_policy;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        privacy_flags & !SecurityPolicy::PRIVACY_FLAGS_MASK == 0,
+        crate::errors::LockboxError::InvalidPrivacyFlags
+    );
+
+    if policy.version == 0 {
+        policy.version = SecurityPolicy::CURRENT_VERSION;
+        policy.master_lockbox = ctx.accounts.master_lockbox.key();
+        policy.owner = ctx.accounts.owner.key();
+        policy.bump = ctx.bumps.security_policy;
+    } else if let Some(current) = policy.delete_cosigner {
+        require!(
+            ctx.accounts.delete_cosigner.as_ref().is_some_and(|s| s.key() == current),
+            crate::errors::LockboxError::CosignerRequired
+        );
+    }
+
+    policy.delete_cosigner = delete_cosigner;
+    policy.read_cooldown = read_cooldown;
+    policy.padded_sizes = padded_sizes;
+    policy.privacy_flags = privacy_flags;
+    policy.freeze_on_honeytoken = freeze_on_honeytoken;
+    policy.updated_at = current_timestamp;
+    if !policy.has_privacy_flag(SecurityPolicy::PRIVACY_HEAT_MAP) {
+        ctx.accounts.master_lockbox.clear_heat_map();
+    }
+
+    emit!(SecurityPolicyUpdatedEvent {
+        owner: policy.owner,
+        delete_cosigner,
+        read_cooldown,
+        padded_sizes,
+        privacy_flags,
+        freeze_on_honeytoken,
+        updated_at: current_timestamp,
+    });
+
+    msg!("Security policy updated");
+    Ok(())
+}
+
+/// Count a read toward the heat map if the owner opted in, emitting the
+/// previous period's counts when a new period starts
+pub fn record_heat_map_read(
+    master_lockbox: &mut Account<MasterLockbox>,
+    policy: Option<&SecurityPolicy>,
+    category: u32,
+    current_timestamp: i64,
+) {
+    if !policy.is_some_and(|p| p.has_privacy_flag(SecurityPolicy::PRIVACY_HEAT_MAP)) {
+        return;
+    }
+    if let Some((period_start, reads)) = master_lockbox.record_heat_map_read(category, current_timestamp) {
+        emit!(HeatMapPeriodEvent {
+            master_lockbox: master_lockbox.key(),
+            period_start,
+            reads,
+        });
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Reads per category bucket over one `HEAT_MAP_PERIOD`, emitted once the
+/// period has ended
+#[event]
+pub struct HeatMapPeriodEvent {
+    pub master_lockbox: Pubkey,
+    pub period_start: i64,
+    pub reads: [u16; HEAT_MAP_BUCKETS],
+}
+
+#[event]
+pub struct SecurityPolicyUpdatedEvent {
+    pub owner: Pubkey,
+    pub delete_cosigner: Option<Pubkey>,
+    pub read_cooldown: u16,
+    pub padded_sizes: bool,
+    pub privacy_flags: u8,
+    pub freeze_on_honeytoken: bool,
+    pub updated_at: i64,
+}
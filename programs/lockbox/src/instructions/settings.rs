@@ -0,0 +1,77 @@
+/**
+ * Encrypted Settings
+ *
+ * A small client-encrypted blob on the master lockbox for preferences that
+ * should follow the owner across devices (autofill rules, lock timeouts).
+ * The program never interprets it; it only bounds the size per tier and
+ * resizes the account so the owner pays rent for the bytes actually used.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::MasterLockbox;
+use crate::errors::LockboxError;
+use super::password_entry::MIN_AEAD_SIZE;
+
+/// Replace (or clear) the encrypted settings blob
+#[derive(Accounts)]
+#[instruction(encrypted_settings: Vec<u8>)]
+pub struct UpdateSettings<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        realloc = MasterLockbox::calculate_space(master_lockbox.storage_chunks.len())
+            + encrypted_settings.len(),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Store `encrypted_settings` on the master lockbox
+///
+/// An empty blob clears the settings and refunds their rent.
+pub fn update_settings_handler(
+    ctx: Context<UpdateSettings>,
+    encrypted_settings: Vec<u8>,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // Same AEAD framing as entries: nonce + ciphertext + tag
+    require!(
+        encrypted_settings.is_empty() || encrypted_settings.len() >= MIN_AEAD_SIZE,
+        LockboxError::InvalidDataSize
+    );
+    master_lockbox.check_settings_size(encrypted_settings.len(), current_timestamp)?;
+
+    let size = encrypted_settings.len() as u16;
+    master_lockbox.encrypted_settings = encrypted_settings;
+
+    emit!(SettingsUpdatedEvent {
+        owner: master_lockbox.owner,
+        size,
+        updated_at: current_timestamp,
+    });
+
+    msg!("Settings updated ({} bytes)", size);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Lets other devices know to refetch the settings
+#[event]
+pub struct SettingsUpdatedEvent {
+    pub owner: Pubkey,
+    pub size: u16,
+    pub updated_at: i64,
+}
@@ -0,0 +1,1070 @@
+/**
+ * Shared Vaults
+ *
+ * A shared vault holds entries encrypted under one vault key, which the
+ * client wraps to each member's key when adding them. Admins manage
+ * members; admins and writers store, update and delete entries; readers
+ * only read.
+ *
+ * Storage comes out of the tier of the master lockbox that created the
+ * vault (a personal vault or an organization's), so group data is paid for
+ * by whoever set it up. It is accounted separately: every shared store,
+ * update and delete moves the shared vault's `storage_used` and the master
+ * lockbox's `shared_storage_used`, which counts against the capacity limit
+ * together with, but never inside, the vault's own `storage_used`. The
+ * master lockbox cannot be closed while its shared vaults hold entries.
+ *
+ * Within a shared vault, each entry's bytes are attributed to the member
+ * who last wrote it (`SharedVaultMember::bytes_written`), and every change
+ * to a member's total is emitted as a `SharedMemberUsageEvent`, so group
+ * usage can be billed back internally.
+ *
+ * A writer about to rotate a shared credential checks the entry out
+ * (`checkout_entry`) so no one else edits or deletes it mid-rotation, and
+ * checks it back in (`checkin_entry`) when done. Checkouts are short and
+ * lapse on their own, so a member who goes offline never locks an entry;
+ * any writer may take over once the checkout has expired.
+ *
+ * An admin can gate a vault on a token (`set_shared_vault_gate`): any
+ * holder of the mint, or of an NFT in a verified Metaplex collection, can
+ * then join as a Reader (`join_shared_vault_with_token`). The holding is
+ * checked when they join and again on every `retrieve_shared_entry`, so
+ * selling the token ends access. A gated joiner cannot wrap the vault key
+ * to themselves; an admin (or the admin's bot) delivers it afterwards with
+ * `set_member_vault_key`.
+ *
+ * Every member can read every entry, so offboarding someone means the
+ * secrets they saw must be changed. `remove_member_with_rotation_flag`
+ * removes the member and flags every entry `needs_rotation`; the vault's
+ * `rotation_pending` counts flagged entries until updates clear them.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{
+    cluster_timestamp, MasterLockbox, SharedEntry, SharedVault, SharedVaultGate, SharedVaultRole,
+    MAX_SHARED_ENTRY_SIZE, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID,
+};
+use super::password_entry::{check_aead_size, check_storage_capacity, check_subscription_active};
+
+/// Fail with `InvalidDataSize` unless `size` is a valid shared entry payload
+fn check_shared_entry_size(size: usize) -> Result<()> {
+    check_aead_size(size)?;
+    require!(size <= MAX_SHARED_ENTRY_SIZE, LockboxError::InvalidDataSize);
+    Ok(())
+}
+
+/// Move a shared vault's usage, and the creating lockbox's shared usage, by
+/// `delta` bytes
+pub fn apply_shared_usage(master_lockbox: &mut MasterLockbox, shared_vault: &mut SharedVault, delta: i64) {
+    if delta >= 0 {
+        master_lockbox.shared_storage_used =
+            master_lockbox.shared_storage_used.saturating_add(delta as u64);
+        shared_vault.storage_used = shared_vault.storage_used.saturating_add(delta as u64);
+    } else {
+        master_lockbox.shared_storage_used =
+            master_lockbox.shared_storage_used.saturating_sub(delta.unsigned_abs());
+        shared_vault.storage_used = shared_vault.storage_used.saturating_sub(delta.unsigned_abs());
+    }
+}
+
+/// Move an entry's bytes from `previous` to `current` (member, bytes)
+/// attribution, returning every member's new `bytes_written` that changed
+///
+/// Bytes last written by a wallet that has since left are not tracked.
+pub fn attribute_entry_bytes(
+    shared_vault: &mut SharedVault,
+    previous: Option<(Pubkey, u64)>,
+    current: Option<(Pubkey, u64)>,
+) -> Vec<(Pubkey, u64)> {
+    let mut deltas: Vec<(Pubkey, i64)> = Vec::new();
+    let moves = previous
+        .map(|(member, bytes)| (member, -(bytes as i64)))
+        .into_iter()
+        .chain(current.map(|(member, bytes)| (member, bytes as i64)));
+    for (member, delta) in moves {
+        match deltas.iter_mut().find(|(m, _)| *m == member) {
+            Some(existing) => existing.1 += delta,
+            None => deltas.push((member, delta)),
+        }
+    }
+
+    deltas
+        .into_iter()
+        .filter(|(_, delta)| *delta != 0)
+        .filter_map(|(member, delta)| {
+            shared_vault
+                .record_member_write(&member, delta)
+                .map(|bytes_written| (member, bytes_written))
+        })
+        .collect()
+}
+
+fn emit_member_usage(shared_vault: Pubkey, changes: Vec<(Pubkey, u64)>) {
+    for (member, bytes_written) in changes {
+        emit!(SharedMemberUsageEvent {
+            shared_vault,
+            member,
+            bytes_written,
+        });
+    }
+}
+
+/// Size of an SPL token account (Token-2022 accounts may append extensions)
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Metaplex `Key::MetadataV1` tag at the start of a metadata account
+const METADATA_V1_KEY: u8 = 4;
+
+/// Fail with `TokenGateNotSatisfied` unless `holder` meets `gate`
+///
+/// `token_account` must be an initialized SPL Token (or Token-2022)
+/// account owned by `holder` with a non-zero balance. A `Mint` gate needs
+/// it to hold that mint; a `Collection` gate needs `token_metadata` to be
+/// the Metaplex metadata of its mint, naming the collection as verified.
+pub fn check_token_gate(
+    gate: &SharedVaultGate,
+    holder: &Pubkey,
+    token_account: &AccountInfo,
+    token_metadata: Option<&AccountInfo>,
+) -> Result<()> {
+    require!(
+        token_account.owner == &SPL_TOKEN_PROGRAM_ID || token_account.owner == &SPL_TOKEN_2022_PROGRAM_ID,
+        LockboxError::TokenGateNotSatisfied
+    );
+    let data = token_account.try_borrow_data()?;
+    require!(data.len() >= TOKEN_ACCOUNT_LEN, LockboxError::TokenGateNotSatisfied);
+    let mint = Pubkey::try_from(&data[0..32]).map_err(|_| LockboxError::TokenGateNotSatisfied)?;
+    let owner = Pubkey::try_from(&data[32..64]).map_err(|_| LockboxError::TokenGateNotSatisfied)?;
+    let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+    // Account state: 0 = uninitialized, 1 = initialized, 2 = frozen
+    let initialized = data[108] != 0;
+    require!(
+        initialized && owner == *holder && amount > 0,
+        LockboxError::TokenGateNotSatisfied
+    );
+
+    match gate {
+        SharedVaultGate::Mint(gate_mint) => {
+            require!(mint == *gate_mint, LockboxError::TokenGateNotSatisfied);
+        }
+        SharedVaultGate::Collection(collection) => {
+            let metadata = token_metadata.ok_or(LockboxError::TokenGateNotSatisfied)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+                &TOKEN_METADATA_PROGRAM_ID,
+            );
+            require!(
+                metadata.key() == expected && metadata.owner == &TOKEN_METADATA_PROGRAM_ID,
+                LockboxError::TokenGateNotSatisfied
+            );
+            let verified_collection = metadata_verified_collection(&metadata.try_borrow_data()?);
+            require!(
+                verified_collection == Some(*collection),
+                LockboxError::TokenGateNotSatisfied
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The verified collection recorded in Metaplex metadata, if any
+fn metadata_verified_collection(data: &[u8]) -> Option<Pubkey> {
+    struct Cursor<'a>(&'a [u8]);
+    impl<'a> Cursor<'a> {
+        fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+            let (head, rest) = (self.0.get(..len)?, self.0.get(len..)?);
+            self.0 = rest;
+            Some(head)
+        }
+        fn u8(&mut self) -> Option<u8> {
+            self.take(1).map(|b| b[0])
+        }
+        fn u32(&mut self) -> Option<u32> {
+            self.take(4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        }
+        fn string(&mut self) -> Option<()> {
+            let len = self.u32()? as usize;
+            self.take(len).map(|_| ())
+        }
+    }
+
+    let mut cursor = Cursor(data);
+    if cursor.u8()? != METADATA_V1_KEY {
+        return None;
+    }
+    cursor.take(32 + 32)?; // update_authority, mint
+    cursor.string()?; // name
+    cursor.string()?; // symbol
+    cursor.string()?; // uri
+    cursor.take(2)?; // seller_fee_basis_points
+    if cursor.u8()? == 1 {
+        // creators: address, verified, share
+        let creators = cursor.u32()? as usize;
+        cursor.take(creators.checked_mul(32 + 1 + 1)?)?;
+    }
+    cursor.take(1 + 1)?; // primary_sale_happened, is_mutable
+    if cursor.u8()? == 1 {
+        cursor.take(1)?; // edition_nonce
+    }
+    if cursor.u8()? == 1 {
+        cursor.take(1)?; // token_standard
+    }
+    if cursor.u8()? != 1 {
+        return None;
+    }
+    let verified = cursor.u8()? == 1;
+    let key = Pubkey::try_from(cursor.take(32)?).ok()?;
+    verified.then_some(key)
+}
+
+/// Create a shared vault drawing on the owner's vault capacity
+#[derive(Accounts)]
+#[instruction(vault_id: u32)]
+pub struct CreateSharedVault<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = SharedVault::space_for(1),
+        seeds = [SharedVault::SEEDS_PREFIX, master_lockbox.key().as_ref(), &vault_id.to_le_bytes()],
+        bump
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create shared vault `vault_id` with the owner as its first admin
+///
+/// # Errors
+/// * `InvalidDataSize` - The wrapped vault key is empty or over 128 bytes
+pub fn create_shared_vault_handler(
+    ctx: Context<CreateSharedVault>,
+    vault_id: u32,
+    wrapped_vault_key: Vec<u8>,
+) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let owner = ctx.accounts.owner.key();
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    shared_vault.version = SharedVault::CURRENT_VERSION;
+    shared_vault.master_lockbox = ctx.accounts.master_lockbox.key();
+    shared_vault.creator = owner;
+    shared_vault.vault_id = vault_id;
+    shared_vault.gate = None;
+    shared_vault.members = Vec::new();
+    shared_vault.storage_used = 0;
+    shared_vault.entry_count = 0;
+    shared_vault.rotation_pending = 0;
+    shared_vault.next_entry_id = 0;
+    shared_vault.created_at = current_timestamp;
+    shared_vault.bump = ctx.bumps.shared_vault;
+    shared_vault.add_member(owner, SharedVaultRole::Admin, wrapped_vault_key, current_timestamp)?;
+
+    msg!("Shared vault {} created", vault_id);
+    Ok(())
+}
+
+/// Add a member (grows the vault by one member)
+#[derive(Accounts)]
+pub struct AddSharedVaultMember<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.is_admin(&admin.key()) @ LockboxError::SharedVaultAccessDenied,
+        realloc = SharedVault::space_for(shared_vault.members.len() + 1),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Add `member` with `role`, holding the vault key wrapped to them
+///
+/// # Errors
+/// * `InvalidDataSize` - The wrapped vault key is empty or over 128 bytes
+/// * `SharedVaultFull` - The vault has `MAX_SHARED_VAULT_MEMBERS` members
+/// * `SharedVaultMemberExists` - The wallet is already a member
+pub fn add_shared_vault_member_handler(
+    ctx: Context<AddSharedVaultMember>,
+    member: Pubkey,
+    role: SharedVaultRole,
+    wrapped_vault_key: Vec<u8>,
+) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    ctx.accounts
+        .shared_vault
+        .add_member(member, role, wrapped_vault_key, current_timestamp)?;
+
+    msg!("Shared vault member {} added as {:?}", member, role);
+    Ok(())
+}
+
+/// Remove a member (shrinks the vault by one member)
+#[derive(Accounts)]
+pub struct RemoveSharedVaultMember<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.is_admin(&admin.key()) @ LockboxError::SharedVaultAccessDenied,
+        realloc = SharedVault::space_for(shared_vault.members.len().saturating_sub(1)),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Remove `member`; the vault's creator cannot be removed
+///
+/// The member keeps whatever they already decrypted; rotate the vault key
+/// and re-encrypt entries to lock them out of future changes.
+///
+/// # Errors
+/// * `SharedVaultMemberNotFound` - The wallet is not a member
+/// * `Unauthorized` - The wallet is the vault's creator
+pub fn remove_shared_vault_member_handler(
+    ctx: Context<RemoveSharedVaultMember>,
+    member: Pubkey,
+) -> Result<()> {
+    ctx.accounts.shared_vault.remove_member(&member)?;
+
+    msg!("Shared vault member {} removed", member);
+    Ok(())
+}
+
+/// Flag each of `entries` for rotation, counting newly flagged entries in
+/// the vault's `rotation_pending`
+///
+/// Returns the IDs of entries newly flagged; already flagged entries stay
+/// flagged and are not counted again.
+pub fn flag_entries_for_rotation(shared_vault: &mut SharedVault, entries: &mut [SharedEntry]) -> Vec<u64> {
+    let mut flagged = Vec::new();
+    for entry in entries.iter_mut().filter(|entry| !entry.needs_rotation) {
+        entry.needs_rotation = true;
+        flagged.push(entry.entry_id);
+    }
+    shared_vault.rotation_pending = shared_vault.rotation_pending.saturating_add(flagged.len() as u32);
+    flagged
+}
+
+/// Remove `member` and flag every entry they could read for rotation
+///
+/// Uses the `RemoveSharedVaultMember` accounts, plus every live entry of
+/// the vault as a writable remaining account.
+///
+/// # Errors
+/// * `SharedVaultMemberNotFound` - The wallet is not a member
+/// * `Unauthorized` - The wallet is the vault's creator
+/// * `InvalidSharedEntryAccount` - A remaining account is not a writable
+///   entry of this vault, or appears twice
+/// * `SharedEntriesMissing` - Not every live entry was passed
+pub fn remove_member_with_rotation_flag_handler(
+    ctx: Context<RemoveSharedVaultMember>,
+    member: Pubkey,
+) -> Result<()> {
+    let shared_vault_key = ctx.accounts.shared_vault.key();
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    shared_vault.remove_member(&member)?;
+
+    let mut infos: Vec<&AccountInfo> = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut entries = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts {
+        require!(
+            info.owner == &crate::ID
+                && info.is_writable
+                && !infos.iter().any(|seen| seen.key == info.key),
+            LockboxError::InvalidSharedEntryAccount
+        );
+        let entry = SharedEntry::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(entry.shared_vault == shared_vault_key, LockboxError::InvalidSharedEntryAccount);
+        infos.push(info);
+        entries.push(entry);
+    }
+    require!(
+        entries.len() == shared_vault.entry_count as usize,
+        LockboxError::SharedEntriesMissing
+    );
+
+    let flagged = flag_entries_for_rotation(shared_vault, &mut entries);
+    for (info, entry) in infos.iter().zip(&entries) {
+        entry.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    for entry_id in &flagged {
+        emit!(SharedEntryRotationFlaggedEvent {
+            shared_vault: shared_vault_key,
+            entry_id: *entry_id,
+            removed_member: member,
+        });
+    }
+    emit!(SharedMemberRemovedForRotationEvent {
+        shared_vault: shared_vault_key,
+        member,
+        flagged_entries: flagged.len() as u32,
+        rotation_pending: shared_vault.rotation_pending,
+    });
+
+    msg!("Shared vault member {} removed; {} entries need rotation", member, shared_vault.rotation_pending);
+    Ok(())
+}
+
+/// Store a new shared entry
+#[derive(Accounts)]
+#[instruction(encrypted_data: Vec<u8>)]
+pub struct StoreSharedEntry<'info> {
+    /// Vault whose tier provides the storage
+    #[account(mut, address = shared_vault.master_lockbox)]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.can_write(&member.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        init,
+        payer = member,
+        space = SharedEntry::space_for(encrypted_data.len()),
+        seeds = [
+            SharedEntry::SEEDS_PREFIX,
+            shared_vault.key().as_ref(),
+            &shared_vault.next_entry_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub shared_entry: Account<'info, SharedEntry>,
+
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Store `encrypted_data` as the vault's next entry
+///
+/// # Errors
+/// * `InvalidDataSize` - Payload is not a valid AEAD payload or exceeds
+///   `MAX_SHARED_ENTRY_SIZE`
+/// * `SubscriptionExpired` - The creating vault's paid tier has lapsed
+/// * `InsufficientStorageCapacity` - The creating vault's own and shared
+///   usage would exceed its capacity limit
+pub fn store_shared_entry_handler(ctx: Context<StoreSharedEntry>, encrypted_data: Vec<u8>) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let size = encrypted_data.len();
+    check_shared_entry_size(size)?;
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    check_subscription_active(master_lockbox, current_timestamp)?;
+    check_storage_capacity(master_lockbox, size as u64, current_timestamp)?;
+
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    let entry_id = shared_vault.next_entry_id;
+    let member = ctx.accounts.member.key();
+
+    let shared_entry = &mut ctx.accounts.shared_entry;
+    shared_entry.version = SharedEntry::CURRENT_VERSION;
+    shared_entry.shared_vault = shared_vault.key();
+    shared_entry.entry_id = entry_id;
+    shared_entry.last_modified_by = member;
+    shared_entry.checkout = None;
+    shared_entry.needs_rotation = false;
+    shared_entry.encrypted_data = encrypted_data;
+    shared_entry.created_at = current_timestamp;
+    shared_entry.last_modified = current_timestamp;
+    shared_entry.bump = ctx.bumps.shared_entry;
+
+    shared_vault.next_entry_id = entry_id
+        .checked_add(1)
+        .ok_or(LockboxError::InvalidDataSize)?;
+    shared_vault.entry_count += 1;
+    apply_shared_usage(master_lockbox, shared_vault, size as i64);
+    let changes = attribute_entry_bytes(shared_vault, None, Some((member, size as u64)));
+    emit_member_usage(shared_vault.key(), changes);
+
+    emit!(SharedStorageChangedEvent {
+        shared_vault: shared_vault.key(),
+        master_lockbox: master_lockbox.key(),
+        entry_id,
+        member,
+        bytes_delta: size as i64,
+        storage_used: shared_vault.storage_used,
+        shared_storage_used: master_lockbox.shared_storage_used,
+    });
+
+    msg!("Shared entry {} stored ({} bytes)", entry_id, size);
+    Ok(())
+}
+
+/// Replace a shared entry's ciphertext
+#[derive(Accounts)]
+#[instruction(entry_id: u64, encrypted_data: Vec<u8>)]
+pub struct UpdateSharedEntry<'info> {
+    /// Vault whose tier provides the storage
+    #[account(mut, address = shared_vault.master_lockbox)]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.can_write(&member.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        mut,
+        seeds = [SharedEntry::SEEDS_PREFIX, shared_vault.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = shared_entry.bump,
+        realloc = SharedEntry::space_for(encrypted_data.len()),
+        realloc::payer = member,
+        realloc::zero = false,
+    )]
+    pub shared_entry: Account<'info, SharedEntry>,
+
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace entry `entry_id` with `encrypted_data`
+///
+/// Only growth is checked against the creating vault's capacity, so an
+/// over-quota vault can still shrink its shared entries. The entry's bytes
+/// move from its previous author to the updating member, and a
+/// `needs_rotation` flag is cleared.
+///
+/// # Errors
+/// * `InvalidDataSize` - Payload is not a valid AEAD payload or exceeds
+///   `MAX_SHARED_ENTRY_SIZE`
+/// * `SubscriptionExpired` - The creating vault's paid tier has lapsed
+/// * `InsufficientStorageCapacity` - Growth would exceed the creating
+///   vault's capacity limit
+/// * `EntryCheckedOut` - Another member has the entry checked out
+pub fn update_shared_entry_handler(
+    ctx: Context<UpdateSharedEntry>,
+    entry_id: u64,
+    encrypted_data: Vec<u8>,
+) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    check_shared_entry_size(encrypted_data.len())?;
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    check_subscription_active(master_lockbox, current_timestamp)?;
+
+    let member = ctx.accounts.member.key();
+    let shared_entry = &mut ctx.accounts.shared_entry;
+    shared_entry.check_editor(&member, current_timestamp)?;
+    let delta = encrypted_data.len() as i64 - shared_entry.encrypted_data.len() as i64;
+    if delta > 0 {
+        check_storage_capacity(master_lockbox, delta as u64, current_timestamp)?;
+    }
+
+    let previous = (shared_entry.last_modified_by, shared_entry.encrypted_data.len() as u64);
+    let current = (member, encrypted_data.len() as u64);
+    let rotated = shared_entry.needs_rotation;
+    shared_entry.encrypted_data = encrypted_data;
+    shared_entry.last_modified_by = member;
+    shared_entry.last_modified = current_timestamp;
+    shared_entry.needs_rotation = false;
+
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    if rotated {
+        shared_vault.rotation_pending = shared_vault.rotation_pending.saturating_sub(1);
+    }
+    apply_shared_usage(master_lockbox, shared_vault, delta);
+    let changes = attribute_entry_bytes(shared_vault, Some(previous), Some(current));
+    emit_member_usage(shared_vault.key(), changes);
+
+    emit!(SharedStorageChangedEvent {
+        shared_vault: shared_vault.key(),
+        master_lockbox: master_lockbox.key(),
+        entry_id,
+        member,
+        bytes_delta: delta,
+        storage_used: shared_vault.storage_used,
+        shared_storage_used: master_lockbox.shared_storage_used,
+    });
+
+    msg!("Shared entry {} updated", entry_id);
+    Ok(())
+}
+
+/// Delete a shared entry and reclaim its rent
+#[derive(Accounts)]
+#[instruction(entry_id: u64)]
+pub struct DeleteSharedEntry<'info> {
+    /// Vault whose tier provides the storage
+    #[account(mut, address = shared_vault.master_lockbox)]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.can_write(&member.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        mut,
+        close = member,
+        seeds = [SharedEntry::SEEDS_PREFIX, shared_vault.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = shared_entry.bump
+    )]
+    pub shared_entry: Account<'info, SharedEntry>,
+
+    #[account(mut)]
+    pub member: Signer<'info>,
+}
+
+/// Delete entry `entry_id`, releasing its bytes from both usage counters
+/// and from its last author's `bytes_written`
+///
+/// # Errors
+/// * `EntryCheckedOut` - Another member has the entry checked out
+pub fn delete_shared_entry_handler(ctx: Context<DeleteSharedEntry>, entry_id: u64) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    ctx.accounts
+        .shared_entry
+        .check_editor(ctx.accounts.member.key, current_timestamp)?;
+
+    let size = ctx.accounts.shared_entry.encrypted_data.len() as i64;
+    let author = ctx.accounts.shared_entry.last_modified_by;
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    shared_vault.entry_count = shared_vault.entry_count.saturating_sub(1);
+    if ctx.accounts.shared_entry.needs_rotation {
+        shared_vault.rotation_pending = shared_vault.rotation_pending.saturating_sub(1);
+    }
+    apply_shared_usage(master_lockbox, shared_vault, -size);
+    let changes = attribute_entry_bytes(shared_vault, Some((author, size as u64)), None);
+    emit_member_usage(shared_vault.key(), changes);
+
+    emit!(SharedStorageChangedEvent {
+        shared_vault: shared_vault.key(),
+        master_lockbox: master_lockbox.key(),
+        entry_id,
+        member: ctx.accounts.member.key(),
+        bytes_delta: -size,
+        storage_used: shared_vault.storage_used,
+        shared_storage_used: master_lockbox.shared_storage_used,
+    });
+
+    msg!("Shared entry {} deleted", entry_id);
+    Ok(())
+}
+
+/// Check a shared entry out or back in
+#[derive(Accounts)]
+#[instruction(entry_id: u64)]
+pub struct ManageEntryCheckout<'info> {
+    #[account(
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.can_write(&member.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        mut,
+        seeds = [SharedEntry::SEEDS_PREFIX, shared_vault.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = shared_entry.bump
+    )]
+    pub shared_entry: Account<'info, SharedEntry>,
+
+    pub member: Signer<'info>,
+}
+
+/// Check entry `entry_id` out for `duration` seconds, or renew the checkout
+///
+/// # Errors
+/// * `InvalidEntryCheckoutDuration` - Duration is not within
+///   `1..=MAX_ENTRY_CHECKOUT_DURATION`
+/// * `EntryCheckedOut` - Another member holds an unexpired checkout
+pub fn checkout_entry_handler(ctx: Context<ManageEntryCheckout>, entry_id: u64, duration: i64) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let member = ctx.accounts.member.key();
+    let shared_entry = &mut ctx.accounts.shared_entry;
+
+    let takeover = shared_entry.check_out(member, duration, current_timestamp)?;
+    let expires_at = current_timestamp.saturating_add(duration);
+
+    emit!(EntryCheckedOutEvent {
+        shared_vault: ctx.accounts.shared_vault.key(),
+        entry_id,
+        member,
+        expires_at,
+        takeover,
+    });
+
+    msg!("Shared entry {} checked out by {} until {}", entry_id, member, expires_at);
+    Ok(())
+}
+
+/// Check entry `entry_id` back in before its checkout lapses
+///
+/// # Errors
+/// * `EntryNotCheckedOut` - The member does not hold a live checkout
+pub fn checkin_entry_handler(ctx: Context<ManageEntryCheckout>, entry_id: u64) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let member = ctx.accounts.member.key();
+    let shared_entry = &mut ctx.accounts.shared_entry;
+
+    require!(
+        shared_entry
+            .checkout_at(current_timestamp)
+            .is_some_and(|checkout| checkout.member == member),
+        LockboxError::EntryNotCheckedOut
+    );
+    shared_entry.checkout = None;
+
+    emit!(EntryCheckedInEvent {
+        shared_vault: ctx.accounts.shared_vault.key(),
+        entry_id,
+        member,
+        checked_in_at: current_timestamp,
+    });
+
+    msg!("Shared entry {} checked in by {}", entry_id, member);
+    Ok(())
+}
+
+/// Read a shared entry
+#[derive(Accounts)]
+#[instruction(entry_id: u64)]
+pub struct RetrieveSharedEntry<'info> {
+    #[account(
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.get_member(&member.key()).is_some() @ LockboxError::SharedVaultMemberNotFound
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        seeds = [SharedEntry::SEEDS_PREFIX, shared_vault.key().as_ref(), &entry_id.to_le_bytes()],
+        bump = shared_entry.bump
+    )]
+    pub shared_entry: Account<'info, SharedEntry>,
+
+    pub member: Signer<'info>,
+
+    /// CHECK: Token-gated members only: the member's token account holding
+    /// the gate token; parsed and checked by `check_token_gate`
+    pub token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection-gated members only: the Metaplex metadata of the
+    /// token's mint; address and owner checked by `check_token_gate`
+    pub token_metadata: Option<UncheckedAccount<'info>>,
+}
+
+/// Return entry `entry_id`'s ciphertext to a member
+///
+/// A member who joined through the vault's token gate must still hold the
+/// gate token, and pass its token account (and, for a collection gate, the
+/// NFT's metadata). Removing the gate ends such members' access.
+///
+/// # Errors
+/// * `TokenGateNotSatisfied` - A token-gated member no longer meets the gate
+pub fn retrieve_shared_entry_handler(ctx: Context<RetrieveSharedEntry>, _entry_id: u64) -> Result<Vec<u8>> {
+    let member = ctx.accounts.member.key();
+    let shared_vault = &ctx.accounts.shared_vault;
+    if shared_vault.get_member(&member).is_some_and(|m| m.token_gated) {
+        let gate = shared_vault.gate.ok_or(LockboxError::TokenGateNotSatisfied)?;
+        let token_account = ctx
+            .accounts
+            .token_account
+            .as_ref()
+            .ok_or(LockboxError::TokenGateNotSatisfied)?;
+        check_token_gate(
+            &gate,
+            &member,
+            token_account,
+            ctx.accounts.token_metadata.as_ref().map(|m| m.as_ref()),
+        )?;
+    }
+    Ok(ctx.accounts.shared_entry.encrypted_data.clone())
+}
+
+/// Set or clear the token that lets holders self-join
+#[derive(Accounts)]
+pub struct SetSharedVaultGate<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.is_admin(&admin.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Gate the vault on `gate`, or make it invite-only again with `None`
+///
+/// Members who already joined through a gate keep read access only while
+/// they meet the gate in force when they read.
+pub fn set_shared_vault_gate_handler(ctx: Context<SetSharedVaultGate>, gate: Option<SharedVaultGate>) -> Result<()> {
+    ctx.accounts.shared_vault.gate = gate;
+
+    msg!("Shared vault gate set to {:?}", gate);
+    Ok(())
+}
+
+/// Join a token-gated shared vault as a Reader
+#[derive(Accounts)]
+pub struct JoinSharedVaultWithToken<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.gate.is_some() @ LockboxError::SharedVaultNotGated,
+        realloc = SharedVault::space_for(shared_vault.members.len() + 1),
+        realloc::payer = member,
+        realloc::zero = false,
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    /// CHECK: The member's token account holding the gate token; parsed and
+    /// checked by `check_token_gate`
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Collection gates only: the Metaplex metadata of the token's
+    /// mint; address and owner checked by `check_token_gate`
+    pub token_metadata: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Join as a Reader by proving a holding of the vault's gate token
+///
+/// The member has no wrapped vault key until an admin delivers one with
+/// `set_member_vault_key`.
+///
+/// # Errors
+/// * `SharedVaultNotGated` - The vault is invite-only
+/// * `TokenGateNotSatisfied` - The member does not hold the gate token
+/// * `SharedVaultFull` - The vault has `MAX_SHARED_VAULT_MEMBERS` members
+/// * `SharedVaultMemberExists` - The wallet is already a member
+pub fn join_shared_vault_with_token_handler(ctx: Context<JoinSharedVaultWithToken>) -> Result<()> {
+    let current_timestamp = cluster_timestamp()?;
+    let member = ctx.accounts.member.key();
+    let shared_vault = &mut ctx.accounts.shared_vault;
+    let gate = shared_vault.gate.ok_or(LockboxError::SharedVaultNotGated)?;
+    check_token_gate(
+        &gate,
+        &member,
+        &ctx.accounts.token_account,
+        ctx.accounts.token_metadata.as_ref().map(|m| m.as_ref()),
+    )?;
+    shared_vault.add_token_gated_member(member, current_timestamp)?;
+
+    emit!(SharedVaultJoinedEvent {
+        shared_vault: shared_vault.key(),
+        member,
+        gate,
+        joined_at: current_timestamp,
+    });
+
+    msg!("{} joined shared vault {} by token", member, shared_vault.vault_id);
+    Ok(())
+}
+
+/// Deliver or replace a member's wrapped vault key
+#[derive(Accounts)]
+pub struct SetMemberVaultKey<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.is_admin(&admin.key()) @ LockboxError::SharedVaultAccessDenied
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Set `member`'s copy of the vault key, wrapped to them
+///
+/// # Errors
+/// * `InvalidDataSize` - The wrapped vault key is empty or over 128 bytes
+/// * `SharedVaultMemberNotFound` - The wallet is not a member
+pub fn set_member_vault_key_handler(
+    ctx: Context<SetMemberVaultKey>,
+    member: Pubkey,
+    wrapped_vault_key: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts
+        .shared_vault
+        .set_member_vault_key(&member, wrapped_vault_key)?;
+
+    msg!("Vault key delivered to {}", member);
+    Ok(())
+}
+
+/// Close an empty shared vault and reclaim its rent
+#[derive(Accounts)]
+pub struct CloseSharedVault<'info> {
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+            SharedVault::SEEDS_PREFIX,
+            shared_vault.master_lockbox.as_ref(),
+            &shared_vault.vault_id.to_le_bytes()
+        ],
+        bump = shared_vault.bump,
+        constraint = shared_vault.is_admin(&admin.key()) @ LockboxError::SharedVaultAccessDenied,
+        constraint = shared_vault.entry_count == 0 @ LockboxError::SharedVaultNotEmpty
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn close_shared_vault_handler(ctx: Context<CloseSharedVault>) -> Result<()> {
+    msg!("Shared vault {} closed", ctx.accounts.shared_vault.vault_id);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Emitted on every shared store, update and delete
+#[event]
+pub struct SharedStorageChangedEvent {
+    pub shared_vault: Pubkey,
+    /// Vault whose tier provides the storage
+    pub master_lockbox: Pubkey,
+    pub entry_id: u64,
+    pub member: Pubkey,
+    /// Bytes added (negative when removed)
+    pub bytes_delta: i64,
+    /// Shared vault's usage after the change
+    pub storage_used: u64,
+    /// Creating vault's total shared usage after the change
+    pub shared_storage_used: u64,
+}
+
+#[event]
+pub struct EntryCheckedOutEvent {
+    pub shared_vault: Pubkey,
+    pub entry_id: u64,
+    pub member: Pubkey,
+    pub expires_at: i64,
+    /// The previous holder's checkout had lapsed
+    pub takeover: bool,
+}
+
+#[event]
+pub struct EntryCheckedInEvent {
+    pub shared_vault: Pubkey,
+    pub entry_id: u64,
+    pub member: Pubkey,
+    pub checked_in_at: i64,
+}
+
+#[event]
+pub struct SharedVaultJoinedEvent {
+    pub shared_vault: Pubkey,
+    pub member: Pubkey,
+    /// Gate the member proved a holding of
+    pub gate: SharedVaultGate,
+    pub joined_at: i64,
+}
+
+#[event]
+pub struct SharedEntryRotationFlaggedEvent {
+    pub shared_vault: Pubkey,
+    pub entry_id: u64,
+    /// Member whose removal flagged the entry
+    pub removed_member: Pubkey,
+}
+
+#[event]
+pub struct SharedMemberRemovedForRotationEvent {
+    pub shared_vault: Pubkey,
+    pub member: Pubkey,
+    /// Entries newly flagged by this removal
+    pub flagged_entries: u32,
+    /// Entries awaiting rotation in the vault after this removal
+    pub rotation_pending: u32,
+}
+
+/// Emitted whenever a member's `bytes_written` changes
+#[event]
+pub struct SharedMemberUsageEvent {
+    pub shared_vault: Pubkey,
+    pub member: Pubkey,
+    /// Member's total after the change
+    pub bytes_written: u64,
+}
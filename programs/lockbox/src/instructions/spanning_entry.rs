@@ -0,0 +1,474 @@
+/**
+ * Cross-Chunk Entries
+ *
+ * An entry larger than the free space left in any one chunk is split into
+ * segments held by several chunks, so total capacity is usable without
+ * first expanding a chunk to fit it. The head segment carries the entry's
+ * metadata; every segment's header names the chunk holding the next one
+ * (`next_chunk`) and where that segment starts in the reassembled
+ * ciphertext (`next_offset`). Continuation segments share the entry ID,
+ * carry `FLAG_CONTINUATION` and are not counted as entries.
+ *
+ * Spanning entries are written, read and deleted whole through the
+ * instructions here; single-chunk instructions (update, move, trash,
+ * alias, copy, re-key) reject them with `EntrySpansChunks`. To change one,
+ * delete it and store it again. A vault holding spanning entries can't
+ * finish a transfer re-key, so delete them before listing the vault.
+ *
+ * Chunks are passed as writable remaining accounts. For a store they are
+ * filled in the order given; reads and deletes accept them in any order.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::state::{
+    CategoryIndex, CategoryRegistry, DataEntryHeader, MasterLockbox, PasswordEntryType,
+    SecurityPolicy, StorageChunk, TREASURY_SEED,
+};
+use super::category_index::fit_category_index;
+use super::chunk_set::ChunkSet;
+use super::security_policy::record_heat_map_read;
+use super::password_entry::{
+    check_aead_size, check_entry_type_size, check_payload_framing, check_storage_capacity,
+    check_subscription_active, check_write_rate,
+};
+use super::subscription::{charge_entry_write_fee, EntryWriteFeeEvent};
+
+/// Split `size` bytes over chunks with `free` bytes available, in order
+///
+/// Returns the segment size taken from each chunk; chunks without free
+/// space contribute nothing and the list stops at the last segment.
+///
+/// # Errors
+/// * `InsufficientChunkCapacity` - The chunks can't hold `size` bytes
+pub fn plan_segments(free: &[u32], size: u32) -> Result<Vec<u32>> {
+    let mut remaining = size;
+    let mut segments = Vec::new();
+    for &available in free {
+        if remaining == 0 {
+            break;
+        }
+        let segment = available.min(remaining);
+        segments.push(segment);
+        remaining -= segment;
+    }
+    require!(remaining == 0, LockboxError::InsufficientChunkCapacity);
+    Ok(segments)
+}
+
+/// Positions in `chunks` of every segment of `entry_id`, head first
+///
+/// # Errors
+/// * `EntryNotFound` - No chunk holds the entry's head segment
+/// * `EntrySegmentMissing` - A linked segment's chunk was not passed, or the
+///   chain is broken
+fn walk_segments(chunks: &[StorageChunk], entry_id: u64) -> Result<Vec<(usize, &DataEntryHeader)>> {
+    let head = chunks
+        .iter()
+        .enumerate()
+        .find_map(|(position, chunk)| {
+            chunk
+                .entry_headers
+                .iter()
+                .find(|h| h.entry_id == entry_id && !h.is_continuation())
+                .map(|header| (position, header))
+        })
+        .ok_or(LockboxError::EntryNotFound)?;
+
+    let mut segments = vec![head];
+    let mut reassembled = head.1.size;
+    let mut header = head.1;
+    while header.has_next() {
+        // Each chunk holds at most one segment of an entry
+        require!(segments.len() < chunks.len(), LockboxError::EntrySegmentMissing);
+        let (position, next) = chunks
+            .iter()
+            .enumerate()
+            .find(|(_, chunk)| chunk.chunk_index == header.next_chunk)
+            .and_then(|(position, chunk)| {
+                chunk
+                    .entry_headers
+                    .iter()
+                    .find(|h| h.entry_id == entry_id && h.is_continuation())
+                    .map(|next| (position, next))
+            })
+            .ok_or(LockboxError::EntrySegmentMissing)?;
+        require!(header.next_offset == reassembled, LockboxError::EntrySegmentMissing);
+
+        reassembled = reassembled.saturating_add(next.size);
+        segments.push((position, next));
+        header = next;
+    }
+    Ok(segments)
+}
+
+/// Reassemble the ciphertext of `entry_id` from its segments in `chunks`
+///
+/// # Errors
+/// * `EntryNotFound` - No chunk holds the entry's head segment
+/// * `EntrySegmentMissing` - A segment's chunk was not passed, or the chain
+///   is broken
+pub fn reassemble_entry(chunks: &[StorageChunk], entry_id: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for (position, header) in walk_segments(chunks, entry_id)? {
+        let start = header.offset as usize;
+        let segment = chunks[position]
+            .encrypted_data
+            .get(start..start + header.size as usize)
+            .ok_or(LockboxError::InvalidEntryOffset)?;
+        data.extend_from_slice(segment);
+    }
+    Ok(data)
+}
+
+/// Store an entry across several chunks
+#[derive(Accounts)]
+pub struct StoreSpanningEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ LockboxError::CategoryIndexRequired
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Keeps the category index in sync; required once the vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    /// CHECK: Treasury PDA (system account); required when the tier's
+    /// entry write fee is non-zero
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Required together with `treasury`
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Store an entry split over the chunks passed as remaining accounts
+///
+/// Each chunk in turn is filled up to its free space until the ciphertext
+/// is placed; the entry counts once against the tier's entry size limit,
+/// storage capacity, write burst and write fee.
+///
+/// # Errors
+/// * `InsufficientChunkCapacity` - The passed chunks can't hold the entry
+/// * `InvalidChunkAccount` - A passed account is not a chunk of this vault
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+pub fn store_spanning_entry_handler(
+    ctx: Context<StoreSpanningEntry>,
+    encrypted_data: Vec<u8>,
+    entry_type: PasswordEntryType,
+    category: u32,
+    title_hash: [u8; 32],
+) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let master_key = ctx.accounts.master_lockbox.key();
+
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    ctx.accounts.master_lockbox.check_writer(device.as_ref(), current_timestamp)?;
+    check_write_rate(&ctx.accounts.master_lockbox, current_timestamp)?;
+    check_aead_size(encrypted_data.len())?;
+    check_payload_framing(&encrypted_data)?;
+    check_entry_type_size(entry_type, encrypted_data.len())?;
+    ctx.accounts.master_lockbox.check_entry_size(encrypted_data.len(), current_timestamp)?;
+    if let Some(policy) = SecurityPolicy::load(&ctx.accounts.security_policy)? {
+        policy.check_size(encrypted_data.len())?;
+    }
+    check_subscription_active(&ctx.accounts.master_lockbox, current_timestamp)?;
+
+    let data_size = encrypted_data.len() as u32;
+    check_storage_capacity(&ctx.accounts.master_lockbox, data_size as u64, current_timestamp)?;
+
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, &ctx.accounts.master_lockbox, &[])?;
+    let free: Vec<u32> = chunks.chunks().iter().map(|chunk| chunk.available_space()).collect();
+    let plan = plan_segments(&free, data_size)?;
+    // (chunk index, segment size) of every chunk receiving a segment, head first
+    let segments: Vec<(u16, u32)> = chunks
+        .chunks()
+        .iter()
+        .zip(plan)
+        .filter(|(_, size)| *size > 0)
+        .map(|(chunk, size)| (chunk.chunk_index, size))
+        .collect();
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    master_lockbox.record_write_burst(encrypted_data.len(), Clock::get()?.slot)?;
+    let entry_id = master_lockbox.get_next_entry_id();
+
+    let fee = charge_entry_write_fee(
+        master_lockbox.subscription_tier,
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+        ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+    )?;
+    if fee > 0 {
+        emit!(EntryWriteFeeEvent { owner: master_lockbox.owner, entry_id, amount: fee });
+    }
+
+    let mut written = 0u32;
+    for (segment_index, &(chunk_index, size)) in segments.iter().enumerate() {
+        let next_chunk = segments
+            .get(segment_index + 1)
+            .map_or(DataEntryHeader::NO_NEXT_CHUNK, |&(index, _)| index);
+        let chunk = chunks.get_mut(chunk_index).ok_or(LockboxError::InvalidChunkAccount)?;
+        let header = DataEntryHeader {
+            entry_id,
+            offset: chunk.current_size,
+            size,
+            entry_type,
+            category,
+            title_hash,
+            created_at: current_timestamp,
+            last_modified: current_timestamp,
+            access_count: 0,
+            flags: if segment_index == 0 { 0 } else { DataEntryHeader::FLAG_CONTINUATION },
+            next_chunk,
+            next_offset: if next_chunk == DataEntryHeader::NO_NEXT_CHUNK { 0 } else { written + size },
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        };
+        let segment = encrypted_data[written as usize..(written + size) as usize].to_vec();
+        chunk.add_entry(header, segment, current_timestamp)?;
+        written += size;
+    }
+
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        registry.record_usage(category, 1, data_size as i64);
+    }
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        category_index.insert(category, entry_id)?;
+        fit_category_index(
+            category_index,
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.system_program.as_ref().map(|p| p.to_account_info()),
+        )?;
+    }
+    chunks.commit(master_lockbox)?;
+    master_lockbox.increment_entries();
+    master_lockbox.touch(current_timestamp);
+
+    emit!(SpanningEntryStoredEvent {
+        master_lockbox: master_key,
+        entry_id,
+        size: data_size,
+        chunks: segments.iter().map(|&(index, _)| index).collect(),
+    });
+
+    msg!("Entry {} stored across {} chunks", entry_id, segments.len());
+
+    Ok(())
+}
+
+/// Retrieve an entry stored across several chunks
+#[derive(Accounts)]
+pub struct RetrieveSpanningEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+}
+
+/// Reassemble and return an entry's ciphertext
+///
+/// Every chunk holding a segment must be passed. Counts as an access on the
+/// head segment, subject to the same read cooldown and privacy flags as
+/// `retrieve_password_entry`.
+///
+/// # Errors
+/// * `EntrySegmentMissing` - A segment's chunk was not passed
+pub fn retrieve_spanning_entry_handler(
+    ctx: Context<RetrieveSpanningEntry>,
+    entry_id: u64,
+) -> Result<Vec<u8>> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    check_subscription_active(&ctx.accounts.master_lockbox, current_timestamp)?;
+
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+    if let Some(policy) = &policy {
+        require!(
+            ctx.accounts.master_lockbox.check_rate_limit(current_timestamp, policy.read_cooldown as i64),
+            LockboxError::ReadCooldownActive
+        );
+    }
+    let has_flag = |flag| policy.as_ref().is_some_and(|p| p.has_privacy_flag(flag));
+
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, &ctx.accounts.master_lockbox, &[])?;
+    let data = reassemble_entry(chunks.chunks(), entry_id)?;
+
+    let head = chunks
+        .iter_mut()
+        .flat_map(|chunk| chunk.entry_headers.iter_mut())
+        .find(|h| h.entry_id == entry_id && !h.is_continuation())
+        .ok_or(LockboxError::EntryNotFound)?;
+    if !has_flag(SecurityPolicy::PRIVACY_NO_ACCESS_COUNT) {
+        head.access_count += 1;
+    }
+    let category = head.category;
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    if !has_flag(SecurityPolicy::PRIVACY_NO_RECENT_ENTRIES) {
+        master_lockbox.record_recent_entry(entry_id);
+    }
+    record_heat_map_read(master_lockbox, policy.as_ref(), category, current_timestamp);
+    chunks.commit(master_lockbox)?;
+    master_lockbox.touch(current_timestamp);
+
+    msg!("Entry {} retrieved", entry_id);
+
+    Ok(data)
+}
+
+/// Delete an entry stored across several chunks
+#[derive(Accounts)]
+pub struct DeleteSpanningEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ LockboxError::CategoryIndexRequired
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Security policy PDA, which may not exist; the address is fixed
+    /// by seeds and the data is read with `SecurityPolicy::load`
+    #[account(seeds = [SecurityPolicy::SEEDS_PREFIX, master_lockbox.key().as_ref()], bump)]
+    pub security_policy: UncheckedAccount<'info>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Keeps the category index in sync; required once the vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+
+    /// Required when the security policy names a delete co-signer
+    pub delete_cosigner: Option<Signer<'info>>,
+
+    /// Optional: device holding the writer lease
+    pub device: Option<Signer<'info>>,
+}
+
+/// Delete every segment of an entry
+///
+/// Every chunk holding a segment must be passed.
+///
+/// # Errors
+/// * `EntrySegmentMissing` - A segment's chunk was not passed
+/// * `CosignerRequired` - The security policy's delete co-signer did not sign
+/// * `WriterLeaseHeld` - Another device holds the writer lease
+pub fn delete_spanning_entry_handler(ctx: Context<DeleteSpanningEntry>, entry_id: u64) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let device = ctx.accounts.device.as_ref().map(|d| d.key());
+    ctx.accounts.master_lockbox.check_writer(device.as_ref(), current_timestamp)?;
+    check_write_rate(&ctx.accounts.master_lockbox, current_timestamp)?;
+    check_subscription_active(&ctx.accounts.master_lockbox, current_timestamp)?;
+
+    let policy = SecurityPolicy::load(&ctx.accounts.security_policy)?;
+    if let Some(cosigner) = policy.and_then(|p| p.delete_cosigner) {
+        require!(
+            ctx.accounts.delete_cosigner.as_ref().is_some_and(|s| s.key() == cosigner),
+            LockboxError::CosignerRequired
+        );
+    }
+
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, &ctx.accounts.master_lockbox, &[])?;
+    let segments: Vec<(u16, u32, u32)> = walk_segments(chunks.chunks(), entry_id)?
+        .into_iter()
+        .map(|(position, header)| (chunks.chunks()[position].chunk_index, header.size, header.category))
+        .collect();
+
+    let mut size = 0u32;
+    for &(chunk_index, segment_size, _) in &segments {
+        let chunk = chunks.get_mut(chunk_index).ok_or(LockboxError::EntrySegmentMissing)?;
+        chunk.delete_entry(entry_id, current_timestamp)?;
+        size += segment_size;
+    }
+
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        registry.record_usage(segments[0].2, -1, -(size as i64));
+    }
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        category_index.remove(segments[0].2, entry_id);
+    }
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    chunks.commit(master_lockbox)?;
+    master_lockbox.decrement_entries();
+    master_lockbox.forget_recent_entry(entry_id);
+    master_lockbox.touch(current_timestamp);
+
+    msg!("Entry {} deleted from {} chunks", entry_id, segments.len());
+
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SpanningEntryStoredEvent {
+    pub master_lockbox: Pubkey,
+    pub entry_id: u64,
+    pub size: u32,
+    /// Chunk indexes holding the segments, head first
+    pub chunks: Vec<u16>,
+}
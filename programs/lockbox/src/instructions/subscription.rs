@@ -1,14 +1,18 @@
 use anchor_lang::prelude::*;
-use crate::state::{MasterLockbox, SubscriptionTier};
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{
+    cluster_timestamp, MasterLockbox, SubscriptionStatus, SubscriptionTier, CAPACITY_ADDON_PRICE,
+    MAX_DUNNING_WINDOW, TREASURY_SEED,
+};
 
 /// Upgrade subscription tier
 #[derive(Accounts)]
 pub struct UpgradeSubscription<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -28,7 +32,7 @@ pub fn upgrade_subscription_handler(
     new_tier: SubscriptionTier,
 ) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
-    let current_timestamp = Clock::get()?.unix_timestamp;
+    let current_timestamp = cluster_timestamp()?;
 
     // Validate upgrade
     require!(
@@ -61,6 +65,7 @@ pub fn upgrade_subscription_handler(
 
     // Upgrade subscription
     master_lockbox.upgrade_subscription(new_tier, current_timestamp)?;
+    master_lockbox.record_payment(payment_amount);
     master_lockbox.touch(current_timestamp);
 
     msg!(
@@ -77,9 +82,9 @@ pub fn upgrade_subscription_handler(
 pub struct RenewSubscription<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -96,7 +101,7 @@ pub struct RenewSubscription<'info> {
 
 pub fn renew_subscription_handler(ctx: Context<RenewSubscription>) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
-    let current_timestamp = Clock::get()?.unix_timestamp;
+    let current_timestamp = cluster_timestamp()?;
 
     // Can't renew free tier
     require!(
@@ -123,22 +128,29 @@ pub fn renew_subscription_handler(ctx: Context<RenewSubscription>) -> Result<()>
         ],
     )?;
 
-    // Extend subscription
-    let duration = master_lockbox.subscription_tier.duration_seconds();
+    master_lockbox.record_payment(payment_amount);
 
-    // If already expired, start from now; otherwise extend from current expiry
-    if current_timestamp >= master_lockbox.subscription_expires {
-        master_lockbox.subscription_expires = current_timestamp + duration;
-    } else {
-        master_lockbox.subscription_expires += duration;
+    // Extend subscription (from now if already expired, which also resets
+    // the loyalty streak)
+    let was_past_due = master_lockbox.subscription_status == SubscriptionStatus::PastDue;
+    let duration = master_lockbox.subscription_tier.duration_seconds();
+    master_lockbox.renew_subscription(duration, current_timestamp);
+
+    if was_past_due {
+        emit!(SubscriptionRestoredEvent {
+            owner: master_lockbox.owner,
+            tier: master_lockbox.subscription_tier,
+            expires: master_lockbox.subscription_expires,
+        });
     }
 
     master_lockbox.touch(current_timestamp);
 
     msg!(
-        "Subscription renewed for {:?} (new expiry: {})",
+        "Subscription renewed for {:?} (new expiry: {}, streak: {})",
         master_lockbox.subscription_tier,
-        master_lockbox.subscription_expires
+        master_lockbox.subscription_expires,
+        master_lockbox.consecutive_renewals
     );
 
     Ok(())
@@ -149,9 +161,9 @@ pub fn renew_subscription_handler(ctx: Context<RenewSubscription>) -> Result<()>
 pub struct DowngradeSubscription<'info> {
     #[account(
         mut,
-        seeds = [MasterLockbox::SEEDS_PREFIX, owner.key().as_ref()],
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
         bump = master_lockbox.bump,
-        constraint = master_lockbox.owner == owner.key() @ crate::errors::LockboxError::Unauthorized
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
     )]
     pub master_lockbox: Account<'info, MasterLockbox>,
 
@@ -161,7 +173,7 @@ pub struct DowngradeSubscription<'info> {
 
 pub fn downgrade_subscription_handler(ctx: Context<DowngradeSubscription>) -> Result<()> {
     let master_lockbox = &mut ctx.accounts.master_lockbox;
-    let current_timestamp = Clock::get()?.unix_timestamp;
+    let current_timestamp = cluster_timestamp()?;
 
     // Can only downgrade if subscription expired
     require!(
@@ -169,19 +181,281 @@ pub fn downgrade_subscription_handler(ctx: Context<DowngradeSubscription>) -> Re
         crate::errors::LockboxError::CannotDowngrade
     );
 
-    // Check if current storage exceeds free tier limit
-    let free_capacity = SubscriptionTier::Free.max_capacity();
+    // Check if current storage, shared vaults included, exceeds free tier
+    // limit (plus active add-ons)
+    let free_capacity = SubscriptionTier::Free.max_capacity()
+        + master_lockbox.active_bonus_capacity(current_timestamp);
     require!(
-        master_lockbox.storage_used <= free_capacity,
+        master_lockbox.total_storage_used() <= free_capacity,
         crate::errors::LockboxError::InsufficientStorageCapacity
     );
 
     // Downgrade to free
-    master_lockbox.subscription_tier = SubscriptionTier::Free;
-    master_lockbox.subscription_expires = 0;
+    master_lockbox.downgrade_to_free();
     master_lockbox.touch(current_timestamp);
 
     msg!("Subscription downgraded to Free tier");
 
     Ok(())
 }
+
+/// Buy capacity add-on packs (independent of tier)
+#[derive(Accounts)]
+pub struct PurchaseCapacityAddon<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Treasury PDA (system account) receiving the add-on payment
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn purchase_capacity_addon_handler(ctx: Context<PurchaseCapacityAddon>, packs: u8) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = cluster_timestamp()?;
+
+    let duration = master_lockbox.subscription_tier.duration_seconds();
+    master_lockbox.purchase_capacity_addon(packs, duration, current_timestamp)?;
+
+    // One month of every pack held
+    let payment_amount = packs as u64 * CAPACITY_ADDON_PRICE;
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            },
+        ),
+        payment_amount,
+    )?;
+
+    master_lockbox.record_payment(payment_amount);
+    master_lockbox.touch(current_timestamp);
+
+    emit!(CapacityAddonPurchasedEvent {
+        owner: master_lockbox.owner,
+        packs,
+        bonus_capacity: master_lockbox.bonus_capacity,
+        expires: master_lockbox.bonus_capacity_expires,
+        amount: payment_amount,
+    });
+
+    msg!(
+        "Capacity add-on: {} packs (+{} bytes, expires: {})",
+        packs,
+        master_lockbox.bonus_capacity,
+        master_lockbox.bonus_capacity_expires
+    );
+
+    Ok(())
+}
+
+/// Advance an expired subscription through dunning (permissionless crank)
+#[derive(Accounts)]
+pub struct ProcessSubscriptionExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+}
+
+/// Move an expired subscription to `PastDue`, or downgrade a past-due one
+///
+/// Writes are already blocked once the subscription expires; `PastDue`
+/// makes that state visible and starts the dunning window without touching
+/// stored data. When the window ends the vault drops to Free; entries over
+/// the Free limit stay readable, but new writes need capacity again.
+///
+/// # Errors
+/// * `SubscriptionTransitionNotDue` - Still paid up, already Free, or the
+///   dunning window is open
+pub fn process_subscription_expiry_handler(ctx: Context<ProcessSubscriptionExpiry>) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = cluster_timestamp()?;
+
+    if master_lockbox.is_past_due_pending(current_timestamp) {
+        master_lockbox.subscription_status = SubscriptionStatus::PastDue;
+
+        emit!(SubscriptionPastDueEvent {
+            owner: master_lockbox.owner,
+            tier: master_lockbox.subscription_tier,
+            expired_at: master_lockbox.subscription_expires,
+            downgrade_at: master_lockbox.dunning_deadline(),
+        });
+
+        msg!("Subscription past due (downgrade at {})", master_lockbox.dunning_deadline());
+        return Ok(());
+    }
+
+    require!(
+        master_lockbox.is_dunning_over(current_timestamp),
+        crate::errors::LockboxError::SubscriptionTransitionNotDue
+    );
+
+    let previous_tier = master_lockbox.subscription_tier;
+    master_lockbox.downgrade_to_free();
+
+    emit!(SubscriptionAutoDowngradedEvent {
+        owner: master_lockbox.owner,
+        previous_tier,
+        storage_used: master_lockbox.storage_used,
+        downgraded_at: current_timestamp,
+    });
+
+    msg!("Subscription downgraded from {:?} after dunning", previous_tier);
+
+    Ok(())
+}
+
+/// Set the dunning window
+#[derive(Accounts)]
+pub struct SetDunningWindow<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Set how long a past-due vault keeps its tier after expiry
+///
+/// # Arguments
+/// * `window` - Seconds, 0 (downgrade at the first crank after expiry) to
+///   `MAX_DUNNING_WINDOW`
+pub fn set_dunning_window_handler(ctx: Context<SetDunningWindow>, window: i64) -> Result<()> {
+    require!(
+        (0..=MAX_DUNNING_WINDOW).contains(&window),
+        crate::errors::LockboxError::InvalidDunningWindow
+    );
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    master_lockbox.dunning_window = window;
+    master_lockbox.touch(cluster_timestamp()?);
+
+    msg!("Dunning window set to {}s", window);
+
+    Ok(())
+}
+
+/// Set the write burst limit
+#[derive(Accounts)]
+pub struct SetWriteBurstLimit<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ crate::errors::LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Lower the vault's write burst limit below the tier's
+///
+/// # Arguments
+/// * `limit` - Bytes per `WRITE_BURST_WINDOW_SLOTS`, 0 (tier default) or
+///   from the tier's maximum entry size up to its burst limit. The tier
+///   still caps the limit after a downgrade.
+pub fn set_write_burst_limit_handler(ctx: Context<SetWriteBurstLimit>, limit: u32) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let tier = master_lockbox.subscription_tier;
+    require!(
+        limit == 0
+            || (tier.max_entry_size() as u32..=tier.max_write_burst()).contains(&limit),
+        crate::errors::LockboxError::InvalidWriteBurstLimit
+    );
+
+    master_lockbox.write_burst_limit = limit;
+    master_lockbox.touch(cluster_timestamp()?);
+
+    msg!("Write burst limit set to {} bytes", limit);
+
+    Ok(())
+}
+
+/// Charge the tier's entry write fee, routed to the treasury PDA
+///
+/// Returns the lamports charged (0 when the tier waives the fee). The
+/// treasury and system program are only required when a fee is due.
+pub fn charge_entry_write_fee<'info>(
+    tier: SubscriptionTier,
+    payer: AccountInfo<'info>,
+    treasury: Option<AccountInfo<'info>>,
+    system_program: Option<AccountInfo<'info>>,
+) -> Result<u64> {
+    let fee = tier.entry_write_fee();
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    let (Some(treasury), Some(system_program)) = (treasury, system_program) else {
+        return err!(crate::errors::LockboxError::EntryFeeAccountsMissing);
+    };
+    transfer(
+        CpiContext::new(system_program, Transfer { from: payer, to: treasury }),
+        fee,
+    )?;
+
+    Ok(fee)
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct EntryWriteFeeEvent {
+    pub owner: Pubkey,
+    pub entry_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CapacityAddonPurchasedEvent {
+    pub owner: Pubkey,
+    pub packs: u8,
+    pub bonus_capacity: u64,
+    pub expires: i64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SubscriptionPastDueEvent {
+    pub owner: Pubkey,
+    pub tier: SubscriptionTier,
+    pub expired_at: i64,
+    pub downgrade_at: i64,
+}
+
+#[event]
+pub struct SubscriptionRestoredEvent {
+    pub owner: Pubkey,
+    pub tier: SubscriptionTier,
+    pub expires: i64,
+}
+
+#[event]
+pub struct SubscriptionAutoDowngradedEvent {
+    pub owner: Pubkey,
+    pub previous_tier: SubscriptionTier,
+    pub storage_used: u64,
+    pub downgraded_at: i64,
+}
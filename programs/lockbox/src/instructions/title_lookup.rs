@@ -0,0 +1,78 @@
+/**
+ * Title Lookup
+ *
+ * Every entry header carries the blind hash of its title
+ * (`HMAC(search_key, normalized_title)`). `find_entries_by_title` resolves
+ * such a hash to the chunk and ID of each matching entry in one simulated
+ * call, so clients no longer fetch and scan every chunk's headers.
+ *
+ * Chunks are passed as remaining accounts, read-only; only the chunks
+ * passed are searched. Titles are not unique, so every match is returned.
+ */
+
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+use crate::instructions::chunk_set::ChunkSet;
+use crate::state::{MasterLockbox, StorageChunk};
+
+/// Most matches `find_entries_by_title` returns (fits the 1 KB return data limit)
+pub const MAX_TITLE_MATCHES: usize = 100;
+
+/// Where an entry lives
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EntryLocation {
+    pub chunk_index: u16,
+    pub entry_id: u64,
+}
+
+/// Live entries of `chunks` whose title hashes to `title_hash`
+///
+/// Continuation segments and trashed entries are skipped. Matches are in
+/// chunk order, then header order.
+pub fn entries_with_title(chunks: &[StorageChunk], title_hash: &[u8; 32]) -> Vec<EntryLocation> {
+    chunks
+        .iter()
+        .flat_map(|chunk| {
+            chunk
+                .entry_headers
+                .iter()
+                .filter(|header| {
+                    header.title_hash == *title_hash && !header.is_continuation() && !header.is_trashed()
+                })
+                .map(|header| EntryLocation {
+                    chunk_index: chunk.chunk_index,
+                    entry_id: header.entry_id,
+                })
+        })
+        .collect()
+}
+
+/// Find entries by title hash across the vault's chunks
+#[derive(Accounts)]
+pub struct FindEntriesByTitle<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+}
+
+/// Return the location of every live entry titled `title_hash`
+///
+/// At most `MAX_TITLE_MATCHES` locations are returned.
+///
+/// # Errors
+/// * `InvalidTitleHash` - Hash is all zeroes
+/// * `InvalidChunkAccount` - A remaining account is not a registered chunk
+///   of this lockbox, or appears twice
+pub fn find_entries_by_title_handler(
+    ctx: Context<FindEntriesByTitle>,
+    title_hash: [u8; 32],
+) -> Result<Vec<EntryLocation>> {
+    require!(title_hash != [0u8; 32], LockboxError::InvalidTitleHash);
+
+    let chunks = ChunkSet::load_read_only(ctx.remaining_accounts, &ctx.accounts.master_lockbox)?;
+    let mut matches = entries_with_title(chunks.chunks(), &title_hash);
+    matches.truncate(MAX_TITLE_MATCHES);
+    Ok(matches)
+}
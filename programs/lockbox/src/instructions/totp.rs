@@ -0,0 +1,133 @@
+/**
+ * TOTP Entry Instructions
+ *
+ * TOTP seeds are stored as their own `PasswordEntryType::Totp` entries
+ * (size-checked at write time against `TOTP_MIN_PAYLOAD_SIZE` and
+ * `TOTP_MAX_PAYLOAD_SIZE`) so a 2FA seed never shares a ciphertext with
+ * the password it protects. `attach_totp` records the login it belongs to
+ * in the TOTP entry's `parent_entry`; `detach_totp` clears it.
+ *
+ * The link lives on the TOTP side only. Deleting the login leaves the TOTP
+ * entry pointing at a missing ID, which clients treat as detached.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{MasterLockbox, PasswordEntryType, StorageChunk};
+use crate::errors::LockboxError;
+use super::password_entry::SetEntryArchived;
+
+/// Attach a TOTP entry to a login
+#[derive(Accounts)]
+#[instruction(chunk_index: u16, entry_id: u64, parent_chunk_index: u16)]
+pub struct AttachTotp<'info> {
+    #[account(
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// Chunk holding the TOTP entry
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// Chunk holding the login (may be the same chunk)
+    #[account(
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &parent_chunk_index.to_le_bytes()
+        ],
+        bump = parent_chunk.bump,
+        constraint = parent_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized
+    )]
+    pub parent_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Record `parent_entry_id` as the login a TOTP entry belongs to
+///
+/// Re-attaching replaces the previous login.
+///
+/// # Errors
+/// * `NotTotpEntry` - Entry is not a `Totp` entry
+/// * `InvalidTotpParent` - Parent is not a live `Login` entry
+/// * `EntryNotFound` - Either entry is not in its chunk
+pub fn attach_totp_handler(
+    ctx: Context<AttachTotp>,
+    _chunk_index: u16,
+    entry_id: u64,
+    _parent_chunk_index: u16,
+    parent_entry_id: u64,
+) -> Result<()> {
+    let parent = ctx.accounts.parent_chunk.get_entry_header(parent_entry_id)?;
+    require!(
+        parent.entry_type == PasswordEntryType::Login
+            && !parent.is_alias()
+            && !parent.is_trashed()
+            && !parent.is_continuation(),
+        LockboxError::InvalidTotpParent
+    );
+
+    let header = ctx.accounts.storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(header.entry_type == PasswordEntryType::Totp, LockboxError::NotTotpEntry);
+    require!(!header.is_trashed(), LockboxError::EntryTrashed);
+    header.parent_entry = parent_entry_id;
+
+    emit!(TotpAttachmentChangedEvent {
+        master_lockbox: ctx.accounts.master_lockbox.key(),
+        totp_entry: entry_id,
+        parent_entry: parent_entry_id,
+    });
+
+    msg!("TOTP entry {} attached to login {}", entry_id, parent_entry_id);
+    Ok(())
+}
+
+/// Detach a TOTP entry from its login
+///
+/// Shares `set_entry_archived`'s accounts.
+///
+/// # Errors
+/// * `NotTotpEntry` - Entry is not a `Totp` entry
+pub fn detach_totp_handler(
+    ctx: Context<SetEntryArchived>,
+    _chunk_index: u16,
+    entry_id: u64,
+) -> Result<()> {
+    let header = ctx.accounts.storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(header.entry_type == PasswordEntryType::Totp, LockboxError::NotTotpEntry);
+    header.parent_entry = 0;
+
+    emit!(TotpAttachmentChangedEvent {
+        master_lockbox: ctx.accounts.master_lockbox.key(),
+        totp_entry: entry_id,
+        parent_entry: 0,
+    });
+
+    msg!("TOTP entry {} detached", entry_id);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct TotpAttachmentChangedEvent {
+    pub master_lockbox: Pubkey,
+    pub totp_entry: u64,
+    /// Login the entry is now attached to (0 = detached)
+    pub parent_entry: u64,
+}
@@ -0,0 +1,280 @@
+/**
+ * Trash Instructions
+ *
+ * Trashed entries stay in their chunk (flagged `FLAG_TRASHED`) until the
+ * owner's retention period has passed, after which anyone may purge them
+ * with `purge_expired_trash`. The crank keeps `storage_used` honest without
+ * the owner having to clean up manually. Until then the owner can take an
+ * entry back with `restore_password_entry`.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::{AliasTarget, CategoryIndex, CategoryRegistry, MasterLockbox, StorageChunk};
+use crate::errors::LockboxError;
+use super::invariants::enforce_invariants;
+
+/// Minimum trash retention (1 day)
+pub const MIN_TRASH_RETENTION: i64 = 24 * 60 * 60;
+
+/// Maximum trash retention (365 days)
+pub const MAX_TRASH_RETENTION: i64 = 365 * 24 * 60 * 60;
+
+/// Move an entry to the trash
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct TrashPasswordEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn trash_password_entry_handler(
+    ctx: Context<TrashPasswordEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // SECURITY: Rate limiting
+    require!(
+        master_lockbox.check_rate_limit(current_timestamp, 1),
+        LockboxError::RateLimitExceeded
+    );
+
+    // last_modified doubles as the trash timestamp for the purge crank
+    let header = storage_chunk.get_entry_header_mut(entry_id)?;
+    require!(!header.spans_chunks(), LockboxError::EntrySpansChunks);
+    header.set_trashed(true);
+    header.last_modified = current_timestamp;
+    storage_chunk.last_modified = current_timestamp;
+
+    master_lockbox.forget_recent_entry(entry_id);
+    master_lockbox.touch(current_timestamp);
+
+    msg!("Password entry {} moved to trash", entry_id);
+    Ok(())
+}
+
+/// Take an entry back out of the trash
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct RestorePasswordEntry<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Restore a trashed entry while its retention period is still running
+///
+/// # Errors
+/// * `EntryNotTrashed` - Entry is live
+/// * `TrashRetentionElapsed` - Entry is due for `purge_expired_trash`
+pub fn restore_password_entry_handler(
+    ctx: Context<RestorePasswordEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    // SECURITY: Rate limiting
+    require!(
+        master_lockbox.check_rate_limit(current_timestamp, 1),
+        LockboxError::RateLimitExceeded
+    );
+
+    storage_chunk.restore_trashed(entry_id, current_timestamp, master_lockbox.trash_retention)?;
+    master_lockbox.touch(current_timestamp);
+
+    msg!("Password entry {} restored from trash", entry_id);
+    Ok(())
+}
+
+/// Change how long trashed entries are kept
+#[derive(Accounts)]
+pub struct SetTrashRetention<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn set_trash_retention_handler(
+    ctx: Context<SetTrashRetention>,
+    retention: i64,
+) -> Result<()> {
+    require!(
+        (MIN_TRASH_RETENTION..=MAX_TRASH_RETENTION).contains(&retention),
+        LockboxError::InvalidTrashRetention
+    );
+
+    ctx.accounts.master_lockbox.trash_retention = retention;
+
+    msg!("Trash retention set to {}s", retention);
+    Ok(())
+}
+
+/// Purge expired trash from a chunk (permissionless crank)
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct PurgeExpiredTrash<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = !master_lockbox.listed_for_transfer @ LockboxError::VaultListedForTransfer,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ LockboxError::CategoryRegistryRequired,
+        constraint = category_index.is_some() || !master_lockbox.has_category_index
+            @ LockboxError::CategoryIndexRequired
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// Keeps per-category entry and byte counts in sync; required once the
+    /// vault has a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    /// Keeps the category index in sync; required once the vault has one
+    #[account(
+        mut,
+        seeds = [CategoryIndex::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+}
+
+/// Permanently delete trashed entries older than the owner's retention period
+///
+/// Anyone may call this; it only removes entries the owner already trashed
+/// and whose retention has elapsed. Aliases of purged entries in the same
+/// chunk are removed too. Emits one `TrashPurgedEvent` per purged entry.
+pub fn purge_expired_trash_handler(
+    ctx: Context<PurgeExpiredTrash>,
+    _chunk_index: u16,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let expired = storage_chunk.expired_trash(current_timestamp, master_lockbox.trash_retention);
+
+    for (entry_id, trashed_at) in &expired {
+        let header = storage_chunk.get_entry_header(*entry_id)?;
+        let (category, size, is_alias) = (header.category, header.size, header.is_alias());
+        storage_chunk.delete_entry(*entry_id, current_timestamp)?;
+        master_lockbox.decrement_entries();
+
+        if let Some(registry) = ctx.accounts.category_registry.as_mut().filter(|_| !is_alias) {
+            registry.record_usage(category, -1, -(size as i64));
+        }
+        if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+            category_index.remove(category, *entry_id);
+        }
+
+        let target = AliasTarget {
+            chunk_index: storage_chunk.chunk_index,
+            entry_id: *entry_id,
+        };
+        for _ in 0..storage_chunk.delete_aliases_of(target, current_timestamp)? {
+            master_lockbox.decrement_entries();
+        }
+
+        emit!(TrashPurgedEvent {
+            owner: master_lockbox.owner,
+            chunk_index: storage_chunk.chunk_index,
+            entry_id: *entry_id,
+            size,
+            trashed_at: *trashed_at,
+            purged_at: current_timestamp,
+        });
+    }
+
+    master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    msg!(
+        "Purged {} expired trash entries from chunk {}",
+        expired.len(),
+        storage_chunk.chunk_index
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct TrashPurgedEvent {
+    pub owner: Pubkey,
+    pub chunk_index: u16,
+    pub entry_id: u64,
+    pub size: u32,
+    pub trashed_at: i64,
+    pub purged_at: i64,
+}
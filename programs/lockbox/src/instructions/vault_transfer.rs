@@ -0,0 +1,523 @@
+/**
+ * Vault Transfer Escrow
+ *
+ * Hands a vault (e.g. a business's shared credential store) to a new owner
+ * without off-chain trust between the parties:
+ *
+ * 1. The seller lists the vault for one named buyer at a fixed price
+ *    (`list_vault_transfer`), and may withdraw it until it is accepted.
+ *    While listed, entries cannot be written or deleted and the vault
+ *    cannot be scheduled for deletion, so the buyer gets what was listed.
+ * 2. The buyer accepts (`accept_vault_transfer`): the price moves to the
+ *    seller and the vault and every chunk move to the buyer in the same
+ *    instruction, so neither side can take the other's half and walk away.
+ * 3. The seller still knows the old vault key, so the buyer re-encrypts
+ *    every entry under their own key (`rekey_transferred_entry`) and closes
+ *    the sale with `complete_vault_rekey`, which fails while any entry
+ *    written before the handover remains.
+ *
+ * The master lockbox keeps its address, derived from its `creator`, so the
+ * buyer addresses it the same way the seller did. Recovery and emergency
+ * access configs are keyed by the seller's wallet; accepting closes them,
+ * and they only ever act on a vault their wallet still owns, so the
+ * seller's guardians cannot hand the vault back.
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::errors::LockboxError;
+use crate::state::{
+    CategoryRegistry, EmergencyAccess, MasterLockbox, OwnerKind, RecoveryConfig, RecoveryConfigV2, StorageChunk,
+    VaultTransfer, VaultTransferStatus, MAX_VAULT_TRANSFER_LISTING,
+};
+use super::chunk_set::ChunkSet;
+use super::invariants::enforce_invariants;
+use super::password_entry::{
+    check_aead_size, check_payload_framing, check_storage_capacity, check_write_rate,
+    PAYLOAD_HEADER_SIZE,
+};
+
+/// List a vault for transfer to a buyer
+#[derive(Accounts)]
+pub struct ListVaultTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized,
+        constraint = master_lockbox.allows_sensitive_invocation() @ LockboxError::CpiNotAllowed
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VaultTransfer::INIT_SPACE,
+        seeds = [VaultTransfer::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump
+    )]
+    pub vault_transfer: Account<'info, VaultTransfer>,
+
+    /// Seller; pays for the listing and gets the rent back when it closes
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Offer the vault to `buyer` for `price` lamports for `duration` seconds
+///
+/// # Errors
+/// * `InvalidVaultTransfer` - Buyer is the seller, or duration is not
+///   within `1..=MAX_VAULT_TRANSFER_LISTING`
+/// * `VaultDeletionAlreadyRequested` - The vault is scheduled for deletion
+pub fn list_vault_transfer_handler(
+    ctx: Context<ListVaultTransfer>,
+    buyer: Pubkey,
+    price: u64,
+    duration: i64,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        buyer != master_lockbox.owner && (1..=MAX_VAULT_TRANSFER_LISTING).contains(&duration),
+        LockboxError::InvalidVaultTransfer
+    );
+    require!(
+        !master_lockbox.is_deletion_requested(),
+        LockboxError::VaultDeletionAlreadyRequested
+    );
+
+    let vault_transfer = &mut ctx.accounts.vault_transfer;
+    vault_transfer.version = VaultTransfer::CURRENT_VERSION;
+    vault_transfer.master_lockbox = master_lockbox.key();
+    vault_transfer.seller = master_lockbox.owner;
+    vault_transfer.buyer = buyer;
+    vault_transfer.price = price;
+    vault_transfer.status = VaultTransferStatus::Listed;
+    vault_transfer.listed_at = current_timestamp;
+    vault_transfer.expires_at = current_timestamp.saturating_add(duration);
+    vault_transfer.accepted_at = 0;
+    vault_transfer.bump = ctx.bumps.vault_transfer;
+    master_lockbox.listed_for_transfer = true;
+
+    emit!(VaultTransferListedEvent {
+        master_lockbox: master_lockbox.key(),
+        seller: master_lockbox.owner,
+        buyer,
+        price,
+        expires_at: vault_transfer.expires_at,
+    });
+
+    msg!("Vault listed for transfer to {} for {} lamports", buyer, price);
+    Ok(())
+}
+
+/// Withdraw a listing the buyer has not accepted
+#[derive(Accounts)]
+pub struct CancelVaultTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [VaultTransfer::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = vault_transfer.bump,
+        constraint = vault_transfer.seller == owner.key() @ LockboxError::Unauthorized
+    )]
+    pub vault_transfer: Account<'info, VaultTransfer>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Close the listing and reclaim its rent
+///
+/// Expired listings are cancelled the same way, and keep the vault frozen
+/// until they are.
+///
+/// # Errors
+/// * `VaultTransferAlreadyAccepted` - The buyer has already paid
+pub fn cancel_vault_transfer_handler(ctx: Context<CancelVaultTransfer>) -> Result<()> {
+    let vault_transfer = &ctx.accounts.vault_transfer;
+
+    require!(
+        vault_transfer.status == VaultTransferStatus::Listed,
+        LockboxError::VaultTransferAlreadyAccepted
+    );
+    ctx.accounts.master_lockbox.listed_for_transfer = false;
+
+    emit!(VaultTransferCancelledEvent {
+        master_lockbox: vault_transfer.master_lockbox,
+        seller: vault_transfer.seller,
+        buyer: vault_transfer.buyer,
+    });
+
+    msg!("Vault transfer to {} cancelled", vault_transfer.buyer);
+    Ok(())
+}
+
+/// Pay for a listed vault and take ownership
+///
+/// Remaining accounts: every storage chunk of the vault, writable.
+#[derive(Accounts)]
+pub struct AcceptVaultTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.owner == seller.key() @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [VaultTransfer::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = vault_transfer.bump
+    )]
+    pub vault_transfer: Account<'info, VaultTransfer>,
+
+    /// CHECK: Seller's wallet, fixed by the listing; receives the price
+    #[account(mut, address = vault_transfer.seller @ LockboxError::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Seller's recovery config, which may not exist; the address is
+    /// fixed by seeds and the account is closed if it does
+    #[account(mut, seeds = [RecoveryConfig::SEEDS_PREFIX, seller.key().as_ref()], bump)]
+    pub seller_recovery_config: UncheckedAccount<'info>,
+
+    /// CHECK: Seller's V2 recovery config, which may not exist; the address
+    /// is fixed by seeds and the account is closed if it does
+    #[account(mut, seeds = [RecoveryConfigV2::SEEDS_PREFIX, seller.key().as_ref()], bump)]
+    pub seller_recovery_config_v2: UncheckedAccount<'info>,
+
+    /// CHECK: Seller's emergency access config, which may not exist; the
+    /// address is fixed by seeds and the account is closed if it does
+    #[account(mut, seeds = [EmergencyAccess::SEEDS_PREFIX, seller.key().as_ref()], bump)]
+    pub seller_emergency_access: UncheckedAccount<'info>,
+}
+
+/// Close a program account if it exists, refunding its rent to `receiver`
+fn close_if_open<'info>(account: &AccountInfo<'info>, receiver: &AccountInfo<'info>) -> Result<bool> {
+    if account.owner != &crate::ID || account.lamports() == 0 {
+        return Ok(false);
+    }
+    account.try_borrow_mut_data()?.fill(0);
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? -= lamports;
+    **receiver.try_borrow_mut_lamports()? += lamports;
+    account.assign(&anchor_lang::system_program::ID);
+    account.realloc(0, false)?;
+    Ok(true)
+}
+
+/// Pay the seller and take over the vault and its chunks
+///
+/// `price` is the price the buyer agreed to, so a relisting at a different
+/// price cannot be accepted by a stale transaction. The buyer is recorded as
+/// a wallet owner and must sign top-level. Any writer lease is dropped, and
+/// the seller's recovery and emergency access configs are closed with their
+/// rent returned to the seller.
+///
+/// # Errors
+/// * `VaultTransferNotOpen` - Listing is for another buyer, already
+///   accepted, or expired
+/// * `VaultTransferPriceMismatch` - `price` differs from the listing
+/// * `VaultTransferChunksMissing` - Not every chunk of the vault was passed
+/// * `CpiNotAllowed` - Accepted through CPI
+pub fn accept_vault_transfer_handler(
+    ctx: Context<AcceptVaultTransfer>,
+    price: u64,
+) -> Result<()> {
+    let buyer = ctx.accounts.buyer.key();
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let vault_transfer = &mut ctx.accounts.vault_transfer;
+    require!(
+        vault_transfer.is_open_to(&buyer, current_timestamp),
+        LockboxError::VaultTransferNotOpen
+    );
+    require!(price == vault_transfer.price, LockboxError::VaultTransferPriceMismatch);
+
+    // Entries the seller wrote carry earlier timestamps than the handover,
+    // which is what `complete_vault_rekey` checks
+    check_write_rate(&ctx.accounts.master_lockbox, current_timestamp)?;
+
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, &ctx.accounts.master_lockbox, &[])?;
+    require!(
+        chunks.len() == ctx.accounts.master_lockbox.storage_chunks.len(),
+        LockboxError::VaultTransferChunksMissing
+    );
+
+    if price > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            price,
+        )?;
+    }
+
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    master_lockbox.owner = buyer;
+    master_lockbox.owner_kind = OwnerKind::Wallet;
+    require!(master_lockbox.allows_sensitive_invocation(), LockboxError::CpiNotAllowed);
+    master_lockbox.active_writer = None;
+    master_lockbox.listed_for_transfer = false;
+    for chunk in chunks.iter_mut() {
+        chunk.owner = buyer;
+    }
+    chunks.commit(master_lockbox)?;
+    master_lockbox.touch(current_timestamp);
+
+    vault_transfer.status = VaultTransferStatus::Accepted;
+    vault_transfer.accepted_at = current_timestamp;
+
+    let seller = ctx.accounts.seller.to_account_info();
+    let mut closed_seller_configs = 0u8;
+    for config in [
+        &ctx.accounts.seller_recovery_config,
+        &ctx.accounts.seller_recovery_config_v2,
+        &ctx.accounts.seller_emergency_access,
+    ] {
+        if close_if_open(config, &seller)? {
+            closed_seller_configs += 1;
+        }
+    }
+
+    emit!(VaultTransferAcceptedEvent {
+        master_lockbox: master_lockbox.key(),
+        seller: vault_transfer.seller,
+        buyer,
+        price,
+        accepted_at: current_timestamp,
+        closed_seller_configs,
+    });
+
+    msg!("Vault transferred from {} to {}", vault_transfer.seller, buyer);
+    Ok(())
+}
+
+/// Re-encrypt one entry of a transferred vault
+#[derive(Accounts)]
+#[instruction(chunk_index: u16)]
+pub struct RekeyTransferredEntry<'info> {
+    #[account(
+        seeds = [VaultTransfer::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = vault_transfer.bump,
+        constraint = vault_transfer.buyer == buyer.key() @ LockboxError::Unauthorized
+    )]
+    pub vault_transfer: Account<'info, VaultTransfer>,
+
+    #[account(
+        mut,
+        address = vault_transfer.master_lockbox @ LockboxError::Unauthorized,
+        constraint = master_lockbox.is_authorized_owner(&buyer) @ LockboxError::Unauthorized,
+        constraint = category_registry.is_some() || !master_lockbox.has_category_registry
+            @ LockboxError::CategoryRegistryRequired
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    #[account(
+        mut,
+        seeds = [
+            StorageChunk::SEEDS_PREFIX,
+            master_lockbox.key().as_ref(),
+            &chunk_index.to_le_bytes()
+        ],
+        bump = storage_chunk.bump,
+        constraint = storage_chunk.master_lockbox == master_lockbox.key() @ LockboxError::Unauthorized,
+        constraint = storage_chunk.owner == buyer.key() @ LockboxError::Unauthorized
+    )]
+    pub storage_chunk: Account<'info, StorageChunk>,
+
+    /// Keeps per-category byte counts in sync; required once the vault has
+    /// a registry
+    #[account(
+        mut,
+        seeds = [CategoryRegistry::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+
+    pub buyer: Signer<'info>,
+}
+
+/// Replace an entry's ciphertext with one under the buyer's key
+///
+/// Re-encryption keeps the plaintext, so the ciphertext keeps its size;
+/// only an entry written before payload framing grows, by the framing
+/// header. No fee is charged.
+///
+/// # Errors
+/// * `VaultTransferNotAccepted` - The vault has not changed hands
+/// * `EntryIsAlias` - Aliases hold no ciphertext
+/// * `EntryTrashed` - Restore the entry first, or let it purge
+/// * `MalformedPayload` - Ciphertext lacks the payload framing header
+/// * `InvalidDataSize` - Ciphertext size differs from the entry's (plus
+///   the framing header, for an unframed entry)
+/// * `InsufficientStorageCapacity` - No room for the framing header
+/// * `StaleChunkLayout` - The chunk changed since the client read it
+pub fn rekey_transferred_entry_handler(
+    ctx: Context<RekeyTransferredEntry>,
+    _chunk_index: u16,
+    entry_id: u64,
+    reencrypted_data: Vec<u8>,
+    expected_layout_epoch: u32,
+) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let storage_chunk = &mut ctx.accounts.storage_chunk;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        ctx.accounts.vault_transfer.status == VaultTransferStatus::Accepted,
+        LockboxError::VaultTransferNotAccepted
+    );
+    check_write_rate(master_lockbox, current_timestamp)?;
+    master_lockbox.check_chunk_layout(storage_chunk)?;
+    storage_chunk.check_layout_epoch(expected_layout_epoch)?;
+    check_aead_size(reencrypted_data.len())?;
+    check_payload_framing(&reencrypted_data)?;
+
+    let header = storage_chunk.get_entry_header(entry_id)?;
+    require!(!header.is_alias(), LockboxError::EntryIsAlias);
+    require!(!header.is_trashed(), LockboxError::EntryTrashed);
+    require!(!header.spans_chunks(), LockboxError::EntrySpansChunks);
+    let (category, old_size) = (header.category, header.size as usize);
+    let growth = reencrypted_data.len().saturating_sub(old_size);
+    require!(
+        reencrypted_data.len() >= old_size && (growth == 0 || growth == PAYLOAD_HEADER_SIZE),
+        LockboxError::InvalidDataSize
+    );
+    check_storage_capacity(master_lockbox, growth as u64, current_timestamp)?;
+    master_lockbox.record_write_burst(reencrypted_data.len(), Clock::get()?.slot)?;
+
+    storage_chunk.update_entry(entry_id, reencrypted_data, current_timestamp)?;
+    if let Some(registry) = ctx.accounts.category_registry.as_mut() {
+        registry.record_usage(category, 0, growth as i64);
+    }
+    master_lockbox.update_chunk_usage(storage_chunk.chunk_index, storage_chunk.current_size)?;
+    master_lockbox.touch(current_timestamp);
+    enforce_invariants(master_lockbox.key(), master_lockbox, &[&**storage_chunk])?;
+
+    msg!("Entry {} re-keyed", entry_id);
+    Ok(())
+}
+
+/// Close a transfer once every entry is re-keyed
+///
+/// Remaining accounts: every storage chunk of the vault, writable.
+#[derive(Accounts)]
+pub struct CompleteVaultRekey<'info> {
+    #[account(
+        mut,
+        close = seller,
+        seeds = [VaultTransfer::SEEDS_PREFIX, master_lockbox.key().as_ref()],
+        bump = vault_transfer.bump,
+        constraint = vault_transfer.buyer == buyer.key() @ LockboxError::Unauthorized
+    )]
+    pub vault_transfer: Account<'info, VaultTransfer>,
+
+    #[account(
+        address = vault_transfer.master_lockbox @ LockboxError::Unauthorized,
+        constraint = master_lockbox.is_authorized_owner(&buyer) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    /// CHECK: Seller's wallet, fixed by the listing; gets the listing rent back
+    #[account(mut, address = vault_transfer.seller @ LockboxError::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+
+    pub buyer: Signer<'info>,
+}
+
+/// Verify no entry predates the handover and close the transfer record
+///
+/// # Errors
+/// * `VaultTransferNotAccepted` - The vault has not changed hands
+/// * `VaultTransferChunksMissing` - Not every chunk of the vault was passed
+/// * `VaultRekeyIncomplete` - Some entry still holds the seller's ciphertext
+pub fn complete_vault_rekey_handler(ctx: Context<CompleteVaultRekey>) -> Result<()> {
+    let vault_transfer = &ctx.accounts.vault_transfer;
+    let master_lockbox = &ctx.accounts.master_lockbox;
+
+    require!(
+        vault_transfer.status == VaultTransferStatus::Accepted,
+        LockboxError::VaultTransferNotAccepted
+    );
+
+    let mut chunks = ChunkSet::load(ctx.remaining_accounts, master_lockbox, &[])?;
+    require!(
+        chunks.len() == master_lockbox.storage_chunks.len(),
+        LockboxError::VaultTransferChunksMissing
+    );
+    let remaining: u32 = chunks
+        .iter_mut()
+        .map(|chunk| chunk.entries_written_before(vault_transfer.accepted_at))
+        .sum();
+    require!(remaining == 0, LockboxError::VaultRekeyIncomplete);
+
+    emit!(VaultRekeyCompletedEvent {
+        master_lockbox: master_lockbox.key(),
+        seller: vault_transfer.seller,
+        buyer: vault_transfer.buyer,
+        completed_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Vault transfer to {} complete", vault_transfer.buyer);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct VaultTransferListedEvent {
+    pub master_lockbox: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct VaultTransferCancelledEvent {
+    pub master_lockbox: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+}
+
+#[event]
+pub struct VaultTransferAcceptedEvent {
+    pub master_lockbox: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub accepted_at: i64,
+    pub closed_seller_configs: u8,
+}
+
+#[event]
+pub struct VaultRekeyCompletedEvent {
+    pub master_lockbox: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub completed_at: i64,
+}
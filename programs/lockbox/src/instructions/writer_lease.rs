@@ -0,0 +1,108 @@
+/**
+ * Multi-Device Write Fencing
+ *
+ * Structural operations (`expand_chunk`, `shrink_chunk`,
+ * `migrate_archived_entries`) rewrite chunk offsets. If two devices run
+ * them at once, each works from offsets the other just invalidated. A
+ * device that wants to restructure the vault first takes the writer lease
+ * with `acquire_writer_lease`; while the lease is live, structural
+ * operations must be co-signed by the holding device's key.
+ *
+ * The lease is optional: with no live lease, structural operations run
+ * unfenced as before. Leases are short and lapse on their own, so a device
+ * that goes offline mid-operation never locks the vault; any device may
+ * take over once the lease has expired.
+ */
+
+use anchor_lang::prelude::*;
+use crate::state::MasterLockbox;
+use crate::errors::LockboxError;
+
+/// Acquire or release the writer lease
+#[derive(Accounts)]
+pub struct ManageWriterLease<'info> {
+    #[account(
+        mut,
+        seeds = [MasterLockbox::SEEDS_PREFIX, master_lockbox.creator.as_ref()],
+        bump = master_lockbox.bump,
+        constraint = master_lockbox.is_authorized_owner(&owner) @ LockboxError::Unauthorized
+    )]
+    pub master_lockbox: Account<'info, MasterLockbox>,
+
+    pub owner: Signer<'info>,
+
+    /// Device key taking or giving up the lease
+    pub device: Signer<'info>,
+}
+
+/// Take the writer lease for `duration` seconds, or renew it
+///
+/// # Errors
+/// * `InvalidWriterLeaseDuration` - Duration is not within
+///   `1..=MAX_WRITER_LEASE_DURATION`
+/// * `WriterLeaseHeld` - Another device holds an unexpired lease
+pub fn acquire_writer_lease_handler(ctx: Context<ManageWriterLease>, duration: i64) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let device = ctx.accounts.device.key();
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    let takeover = master_lockbox.acquire_writer_lease(device, duration, current_timestamp)?;
+    let expires_at = current_timestamp.saturating_add(duration);
+
+    emit!(WriterLeaseAcquiredEvent {
+        owner: master_lockbox.owner,
+        device,
+        expires_at,
+        takeover,
+    });
+
+    msg!("Writer lease held by {} until {}", device, expires_at);
+    Ok(())
+}
+
+/// Give up the writer lease early
+///
+/// # Errors
+/// * `WriterLeaseNotHeld` - The device does not hold a live lease
+pub fn release_writer_lease_handler(ctx: Context<ManageWriterLease>) -> Result<()> {
+    let master_lockbox = &mut ctx.accounts.master_lockbox;
+    let device = ctx.accounts.device.key();
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        master_lockbox
+            .active_writer_at(current_timestamp)
+            .is_some_and(|lease| lease.device == device),
+        LockboxError::WriterLeaseNotHeld
+    );
+    master_lockbox.active_writer = None;
+
+    emit!(WriterLeaseReleasedEvent {
+        owner: master_lockbox.owner,
+        device,
+        released_at: current_timestamp,
+    });
+
+    msg!("Writer lease released by {}", device);
+    Ok(())
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct WriterLeaseAcquiredEvent {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub expires_at: i64,
+    /// The previous holder's lease had lapsed
+    pub takeover: bool,
+}
+
+#[event]
+pub struct WriterLeaseReleasedEvent {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub released_at: i64,
+}
@@ -50,6 +50,7 @@ use errors::*;
 
 /// Maximum encrypted payload size: 1024 bytes (1 KiB)
 /// This limit prevents excessive storage costs and transaction size issues
+/// (v1 lockbox only; v2 entries are limited by `SubscriptionTier::max_entry_size`)
 const MAX_ENCRYPTED_SIZE: usize = 1024;
 
 /// Salt size for HKDF: 32 bytes
@@ -68,6 +69,115 @@ const FEE_LAMPORTS: u64 = 1_000_000;
 /// Rate limiting to prevent brute force attempts
 const COOLDOWN_SLOTS: u64 = 10;
 
+// ============================================================================
+// IDL Constants - PDA seed prefixes and account sizes
+// ============================================================================
+//
+// Exported through the IDL so SDKs derive addresses from it instead of
+// hardcoding seed strings. Each aliases the state type's own constant.
+
+#[constant]
+pub const MASTER_LOCKBOX_SEED: &[u8] = MasterLockbox::SEEDS_PREFIX;
+
+#[constant]
+pub const STORAGE_CHUNK_SEED: &[u8] = StorageChunk::SEEDS_PREFIX;
+
+#[constant]
+pub const CATEGORY_REGISTRY_SEED: &[u8] = CategoryRegistry::SEEDS_PREFIX;
+
+#[constant]
+pub const ENTRY_NOTES_SEED: &[u8] = EntryNotes::SEEDS_PREFIX;
+
+#[constant]
+pub const SECURITY_POLICY_SEED: &[u8] = SecurityPolicy::SEEDS_PREFIX;
+
+#[constant]
+pub const RECOVERY_CONFIG_SEED: &[u8] = RecoveryConfig::SEEDS_PREFIX;
+
+#[constant]
+pub const RECOVERY_REQUEST_SEED: &[u8] = RecoveryRequest::SEEDS_PREFIX;
+
+#[constant]
+pub const GUARDIAN_AGREEMENT_SEED: &[u8] = GuardianAgreement::SEEDS_PREFIX;
+
+#[constant]
+pub const RECOVERY_CONFIG_V2_SEED: &[u8] = RecoveryConfigV2::SEEDS_PREFIX;
+
+#[constant]
+pub const RECOVERY_REQUEST_V2_SEED: &[u8] = RecoveryRequestV2::SEEDS_PREFIX;
+
+#[constant]
+pub const EMERGENCY_ACCESS_SEED: &[u8] = EmergencyAccess::SEEDS_PREFIX;
+
+#[constant]
+pub const EMERGENCY_GRANT_SEED: &[u8] = EmergencyGrantRecord::SEEDS_PREFIX;
+
+#[constant]
+pub const ALIAS_SEED: &[u8] = Alias::SEEDS_PREFIX;
+
+#[constant]
+pub const CONTACT_BOOK_SEED: &[u8] = ContactBook::SEEDS_PREFIX;
+
+#[constant]
+pub const INBOX_SEED: &[u8] = Inbox::SEEDS_PREFIX;
+
+#[constant]
+pub const ENTRY_PROVENANCE_SEED: &[u8] = EntryProvenance::SEEDS_PREFIX;
+
+#[constant]
+pub const BILLING_RECEIPTS_SEED: &[u8] = BillingReceipts::SEEDS_PREFIX;
+
+#[constant]
+pub const VAULT_TRANSFER_SEED: &[u8] = VaultTransfer::SEEDS_PREFIX;
+
+#[constant]
+pub const EXPORT_LOG_SEED: &[u8] = ExportLog::SEEDS_PREFIX;
+
+#[constant]
+pub const ENTRY_COPIES_SEED: &[u8] = EntryCopies::SEEDS_PREFIX;
+
+#[constant]
+pub const DENY_LIST_SEED: &[u8] = DenyList::SEEDS_PREFIX;
+
+#[constant]
+pub const TAG_REGISTRY_SEED: &[u8] = TagRegistry::SEEDS_PREFIX;
+
+#[constant]
+pub const SEARCH_FILTER_SEED: &[u8] = SearchFilter::SEEDS_PREFIX;
+
+#[constant]
+pub const CATEGORY_INDEX_SEED: &[u8] = CategoryIndex::SEEDS_PREFIX;
+
+/// Master lockbox size before any chunks are registered
+#[constant]
+pub const MASTER_LOCKBOX_INIT_SPACE: u32 = MasterLockbox::INIT_SPACE as u32;
+
+/// Storage chunk size excluding its data region
+#[constant]
+pub const STORAGE_CHUNK_BASE_SPACE: u32 = StorageChunk::BASE_SPACE as u32;
+
+/// Master lockbox growth per registered chunk
+#[constant]
+pub const MASTER_LOCKBOX_CHUNK_SPACE: u32 = MasterLockbox::STORAGE_CHUNK_INFO_SIZE as u32;
+
+/// Category registry account size
+#[constant]
+pub const CATEGORY_REGISTRY_SPACE: u32 = 8 + CategoryRegistry::INIT_SPACE as u32;
+
+/// Recovery config size with up to `MAX_GUARDIANS` guardians
+#[constant]
+pub const RECOVERY_CONFIG_V2_SPACE: u32 = RecoveryConfigV2::space_for(0) as u32;
+
+/// Emergency access size with up to `MAX_EMERGENCY_CONTACTS` contacts
+#[constant]
+pub const EMERGENCY_ACCESS_SPACE: u32 = EmergencyAccess::space_for(0) as u32;
+
+#[constant]
+pub const MIN_CHUNK_SIZE: u32 = StorageChunk::MIN_CHUNK_SIZE;
+
+#[constant]
+pub const MAX_CHUNK_SIZE: u32 = StorageChunk::MAX_CHUNK_SIZE;
+
 #[program]
 pub mod lockbox {
     use super::*;
@@ -81,6 +191,15 @@ pub mod lockbox {
         instructions::initialize::handler(ctx)
     }
 
+    /// Initialize a master lockbox owned by a program PDA (via CPI)
+    pub fn initialize_program_owned_lockbox(
+        ctx: Context<InitializeMasterLockbox>,
+        program_id: Pubkey,
+        owner_seeds: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::initialize::initialize_program_owned_handler(ctx, program_id, owner_seeds)
+    }
+
     /// Initialize a new storage chunk (v2)
     pub fn initialize_storage_chunk(
         ctx: Context<InitializeStorageChunk>,
@@ -97,6 +216,19 @@ pub mod lockbox {
     }
 
     /// Store a new password entry (v2)
+    ///
+    /// Pass `client_entry_id` to use a stable client-generated ID (high bit
+    /// set) instead of the next counter ID; it is reserved in the vault's
+    /// `ClientEntryIds` account, which must be passed with it.
+    ///
+    /// Passing the optional `payer` and `system_program` accounts lets the
+    /// chunk grow by up to `AUTO_EXPAND_MAX_MARGIN` bytes when it is short.
+    ///
+    /// The ciphertext may be up to the tier's `max_entry_size` (1KB on
+    /// Free/Basic, 4KB on Premium, 16KB on Pro).
+    ///
+    /// Entry payloads must start with the framing header (version, nonce
+    /// length, suite ID); see `check_payload_framing`.
     pub fn store_password_entry(
         ctx: Context<StorePasswordEntry>,
         chunk_index: u16,
@@ -104,6 +236,7 @@ pub mod lockbox {
         entry_type: PasswordEntryType,
         category: u32,
         title_hash: [u8; 32],
+        client_entry_id: Option<u64>,
     ) -> Result<()> {
         instructions::password_entry::store_password_entry_handler(
             ctx,
@@ -112,40 +245,554 @@ pub mod lockbox {
             entry_type,
             category,
             title_hash,
+            client_entry_id,
+        )
+    }
+
+    /// Create the vault's client entry ID account (v2)
+    ///
+    /// Pass every registered chunk as a read-only remaining account; client
+    /// IDs already stored are reserved.
+    pub fn initialize_client_entry_ids(ctx: Context<InitializeClientEntryIds>) -> Result<()> {
+        instructions::client_entry_ids::initialize_client_entry_ids_handler(ctx)
+    }
+
+    /// Store up to `MAX_STORE_BATCH` new entries in one chunk (v2)
+    ///
+    /// Takes the same accounts as `store_password_entry`; entries get
+    /// consecutive counter IDs and the batch is written all or nothing.
+    pub fn store_password_entries_batch(
+        ctx: Context<StorePasswordEntry>,
+        chunk_index: u16,
+        entries: Vec<NewPasswordEntry>,
+    ) -> Result<()> {
+        instructions::password_entry::store_password_entries_batch_handler(ctx, chunk_index, entries)
+    }
+
+    /// Store an entry split across the chunks passed as remaining accounts,
+    /// for entries larger than any one chunk's free space (v2)
+    pub fn store_spanning_entry(
+        ctx: Context<StoreSpanningEntry>,
+        encrypted_data: Vec<u8>,
+        entry_type: PasswordEntryType,
+        category: u32,
+        title_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::spanning_entry::store_spanning_entry_handler(
+            ctx,
+            encrypted_data,
+            entry_type,
+            category,
+            title_hash,
         )
     }
 
+    /// Reassemble an entry stored across chunks; every chunk holding a
+    /// segment is passed as a remaining account (v2)
+    pub fn retrieve_spanning_entry(ctx: Context<RetrieveSpanningEntry>, entry_id: u64) -> Result<Vec<u8>> {
+        instructions::spanning_entry::retrieve_spanning_entry_handler(ctx, entry_id)
+    }
+
+    /// Delete every segment of an entry stored across chunks (v2)
+    pub fn delete_spanning_entry(ctx: Context<DeleteSpanningEntry>, entry_id: u64) -> Result<()> {
+        instructions::spanning_entry::delete_spanning_entry_handler(ctx, entry_id)
+    }
+
     /// Retrieve a password entry (v2)
+    ///
+    /// For an alias this returns the serialized `AliasTarget` to follow.
+    /// With `simulate`, the read changes no state (no access count, recent
+    /// entry or timestamp updates), for free preflight reads over RPC.
     pub fn retrieve_password_entry(
         ctx: Context<RetrievePasswordEntry>,
         chunk_index: u16,
         entry_id: u64,
+        simulate: bool,
     ) -> Result<Vec<u8>> {
-        instructions::password_entry::retrieve_password_entry_handler(ctx, chunk_index, entry_id)
+        instructions::password_entry::retrieve_password_entry_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            simulate,
+        )
     }
 
     /// Update a password entry (v2)
+    ///
+    /// Copies made with `copy_entry_to_vault` are flagged stale.
+    ///
+    /// `expected_layout_epoch` is the chunk's `layout_epoch` as the client
+    /// read it; the call fails with `StaleChunkLayout` if it has changed.
     pub fn update_password_entry(
         ctx: Context<UpdatePasswordEntry>,
         chunk_index: u16,
         entry_id: u64,
         new_encrypted_data: Vec<u8>,
+        expected_layout_epoch: u32,
     ) -> Result<()> {
         instructions::password_entry::update_password_entry_handler(
             ctx,
             chunk_index,
             entry_id,
             new_encrypted_data,
+            expected_layout_epoch,
         )
     }
 
     /// Delete a password entry (v2)
+    ///
+    /// Deleting a real entry also removes its aliases in the same chunk and in
+    /// any other chunks passed as writable remaining accounts, and revokes
+    /// its copies in other vaults.
+    ///
+    /// `expected_layout_epoch` is the chunk's `layout_epoch` as the client
+    /// read it; the call fails with `StaleChunkLayout` if it has changed.
     pub fn delete_password_entry(
         ctx: Context<DeletePasswordEntry>,
         chunk_index: u16,
         entry_id: u64,
+        expected_layout_epoch: u32,
+    ) -> Result<()> {
+        instructions::password_entry::delete_password_entry_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            expected_layout_epoch,
+        )
+    }
+
+    /// Move a password entry to another chunk (v2)
+    ///
+    /// Pass the destination chunk, plus any chunks holding aliases of the
+    /// entry, as writable remaining accounts.
+    ///
+    /// `expected_layout_epoch` is the source chunk's `layout_epoch` as the
+    /// client read it. The move bumps both chunks' epochs.
+    pub fn move_password_entry(
+        ctx: Context<MovePasswordEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+        dest_chunk_index: u16,
+        expected_layout_epoch: u32,
+    ) -> Result<()> {
+        instructions::password_entry::move_password_entry_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            dest_chunk_index,
+            expected_layout_epoch,
+        )
+    }
+
+    /// Create an alias of an existing entry (v2)
+    ///
+    /// The alias carries its own category and title hash and points at
+    /// `target_entry_id` in `target_chunk_index`, so a credential can appear
+    /// under several categories without duplicating ciphertext.
+    pub fn create_entry_alias(
+        ctx: Context<CreateEntryAlias>,
+        chunk_index: u16,
+        target_chunk_index: u16,
+        target_entry_id: u64,
+        category: u32,
+        title_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::password_entry::create_entry_alias_handler(
+            ctx,
+            chunk_index,
+            target_chunk_index,
+            target_entry_id,
+            category,
+            title_hash,
+        )
+    }
+
+    /// Copy an entry into another owner's vault (v2)
+    ///
+    /// Both vault owners sign. The copy is stored from `reencrypted_data`
+    /// and linked to its source by an `EntryProvenance` record; the source
+    /// entry's `EntryCopies` list links back to it.
+    ///
+    /// `expected_layout_epoch` is the source chunk's `layout_epoch` as the
+    /// client read the entry from it.
+    pub fn copy_entry_to_vault(
+        ctx: Context<CopyEntryToVault>,
+        source_chunk_index: u16,
+        chunk_index: u16,
+        source_entry_id: u64,
+        reencrypted_data: Vec<u8>,
+        title_hash: [u8; 32],
+        expected_layout_epoch: u32,
+    ) -> Result<()> {
+        instructions::entry_copy::copy_entry_to_vault_handler(
+            ctx,
+            source_chunk_index,
+            chunk_index,
+            source_entry_id,
+            reencrypted_data,
+            title_hash,
+            expected_layout_epoch,
+        )
+    }
+
+    /// List who besides the owner can see an entry (v2, read-only)
+    ///
+    /// Returns `Vec<EntryGrant>` via return data: the entry's cross-vault
+    /// copies, then the vault's accepted and granted emergency contacts.
+    pub fn list_entry_grants(
+        ctx: Context<ListEntryGrants>,
+        chunk_index: u16,
+        entry_id: u64,
+    ) -> Result<Vec<EntryGrant>> {
+        instructions::entry_grants::list_entry_grants_handler(ctx, chunk_index, entry_id)
+    }
+
+    /// Mark or unmark an entry as archived (v2)
+    pub fn set_entry_archived(
+        ctx: Context<SetEntryArchived>,
+        chunk_index: u16,
+        entry_id: u64,
+        archived: bool,
+    ) -> Result<()> {
+        instructions::password_entry::set_entry_archived_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            archived,
+        )
+    }
+
+    /// Set an entry's favorite/archived flags without rewriting it (v2)
+    ///
+    /// `flags` is a combination of `DataEntryHeader::FLAG_FAVORITE` and
+    /// `FLAG_ARCHIVED`; it replaces both. Shares `set_entry_archived`'s
+    /// accounts.
+    pub fn set_entry_flags(
+        ctx: Context<SetEntryArchived>,
+        chunk_index: u16,
+        entry_id: u64,
+        flags: u8,
+    ) -> Result<()> {
+        instructions::password_entry::set_entry_flags_handler(ctx, chunk_index, entry_id, flags)
+    }
+
+    /// Attach a TOTP entry to the login it protects (v2)
+    pub fn attach_totp(
+        ctx: Context<AttachTotp>,
+        chunk_index: u16,
+        entry_id: u64,
+        parent_chunk_index: u16,
+        parent_entry_id: u64,
+    ) -> Result<()> {
+        instructions::totp::attach_totp_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            parent_chunk_index,
+            parent_entry_id,
+        )
+    }
+
+    /// Detach a TOTP entry from its login (v2)
+    ///
+    /// Shares `set_entry_archived`'s accounts.
+    pub fn detach_totp(ctx: Context<SetEntryArchived>, chunk_index: u16, entry_id: u64) -> Result<()> {
+        instructions::totp::detach_totp_handler(ctx, chunk_index, entry_id)
+    }
+
+    /// Link two related entries, e.g. a login and its recovery codes (v2)
+    ///
+    /// Both entries record the link. Omit `linked_chunk` when both are in
+    /// the same chunk.
+    pub fn link_entries(
+        ctx: Context<LinkEntries>,
+        chunk_index: u16,
+        entry_id: u64,
+        linked_chunk_index: u16,
+        linked_entry_id: u64,
+    ) -> Result<()> {
+        instructions::entry_links::link_entries_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            linked_chunk_index,
+            linked_entry_id,
+        )
+    }
+
+    /// Unlink two entries (v2)
+    pub fn unlink_entries(
+        ctx: Context<LinkEntries>,
+        chunk_index: u16,
+        entry_id: u64,
+        linked_chunk_index: u16,
+        linked_entry_id: u64,
+    ) -> Result<()> {
+        instructions::entry_links::unlink_entries_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            linked_chunk_index,
+            linked_entry_id,
+        )
+    }
+
+    /// Create a search index chunk (v2)
+    ///
+    /// Same as `initialize_storage_chunk` with `StorageType::SearchIndex`;
+    /// the chunk holds blind-index postings instead of entries.
+    pub fn initialize_search_index(
+        ctx: Context<InitializeStorageChunk>,
+        chunk_index: u16,
+        initial_capacity: u32,
+    ) -> Result<()> {
+        instructions::initialize::initialize_storage_chunk_handler(
+            ctx,
+            chunk_index,
+            initial_capacity,
+            StorageType::SearchIndex,
+        )
+    }
+
+    /// Replace an entry's blind-index tokens in a search index chunk (v2)
+    ///
+    /// Send alongside `store_password_entry` / `update_password_entry`.
+    pub fn index_entry(
+        ctx: Context<UpdateSearchIndex>,
+        index_chunk_index: u16,
+        entry_id: u64,
+        tokens: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::search_index::index_entry_handler(ctx, index_chunk_index, entry_id, tokens)
+    }
+
+    /// Remove an entry from a search index chunk (v2)
+    ///
+    /// Send alongside `delete_password_entry`.
+    pub fn deindex_entry(
+        ctx: Context<UpdateSearchIndex>,
+        index_chunk_index: u16,
+        entry_id: u64,
+    ) -> Result<()> {
+        instructions::search_index::deindex_entry_handler(ctx, index_chunk_index, entry_id)
+    }
+
+    /// Look up a blind-index token (v2, read-only)
+    ///
+    /// Returns `Vec<u64>` entry IDs via return data.
+    pub fn search_index(
+        ctx: Context<QuerySearchIndex>,
+        index_chunk_index: u16,
+        token: [u8; 32],
+    ) -> Result<Vec<u64>> {
+        instructions::search_index::search_index_handler(ctx, index_chunk_index, token)
+    }
+
+    /// Add an entry's search tokens to the vault's Bloom filter (v2)
+    ///
+    /// Creates the filter on first use. `chunk_index` is the chunk holding
+    /// the entry, so `check_token` can also rule out single chunks.
+    pub fn add_search_tokens(
+        ctx: Context<AddSearchTokens>,
+        chunk_index: u16,
+        tokens: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::search_filter::add_search_tokens_handler(ctx, chunk_index, tokens)
+    }
+
+    /// Check a blind-index token against the vault's Bloom filter (v2, read-only)
+    ///
+    /// Returns `bool` via return data: `false` rules the vault (or the chunk,
+    /// if `chunk_index` is given) out; `true` may be a false positive.
+    pub fn check_token(
+        ctx: Context<QuerySearchFilter>,
+        token: [u8; 32],
+        chunk_index: Option<u16>,
+    ) -> Result<bool> {
+        instructions::search_filter::check_token_handler(ctx, token, chunk_index)
+    }
+
+    /// Clear the vault's Bloom filter before rebuilding it (v2)
+    pub fn reset_search_filter(ctx: Context<ResetSearchFilter>) -> Result<()> {
+        instructions::search_filter::reset_search_filter_handler(ctx)
+    }
+
+    /// Close the vault's Bloom filter and reclaim its rent
+    pub fn close_search_filter(ctx: Context<CloseSearchFilter>) -> Result<()> {
+        instructions::search_filter::close_search_filter_handler(ctx)
+    }
+
+    /// Find entries by blind title hash (v2, read-only)
+    ///
+    /// Pass the chunks to search as read-only remaining accounts. Returns
+    /// `Vec<EntryLocation>` via return data.
+    pub fn find_entries_by_title(
+        ctx: Context<FindEntriesByTitle>,
+        title_hash: [u8; 32],
+    ) -> Result<Vec<EntryLocation>> {
+        instructions::title_lookup::find_entries_by_title_handler(ctx, title_hash)
+    }
+
+    /// Set or clear an entry's rotation date (v2)
+    ///
+    /// `rotate_after` is a Unix timestamp (0 = no schedule). Shares
+    /// `set_entry_archived`'s accounts.
+    pub fn set_rotation_date(
+        ctx: Context<SetEntryArchived>,
+        chunk_index: u16,
+        entry_id: u64,
+        rotate_after: i64,
+    ) -> Result<()> {
+        instructions::rotation::set_rotation_date_handler(ctx, chunk_index, entry_id, rotate_after)
+    }
+
+    /// Log a chunk's entries that are past their rotation date (permissionless)
+    pub fn emit_rotation_due(ctx: Context<EmitRotationDue>, chunk_index: u16) -> Result<()> {
+        instructions::rotation::emit_rotation_due_handler(ctx, chunk_index)
+    }
+
+    /// Tag an entry with a blind tag hash (v2)
+    ///
+    /// `tag_hash` is `HMAC(tag_key, tag_name)`, computed client-side. New
+    /// tags are registered (and the registry created) on first use.
+    pub fn tag_entry(
+        ctx: Context<TagEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+        tag_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::entry_tags::tag_entry_handler(ctx, chunk_index, entry_id, tag_hash)
+    }
+
+    /// Remove a blind tag from an entry (v2)
+    pub fn untag_entry(
+        ctx: Context<TagEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+        tag_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::entry_tags::untag_entry_handler(ctx, chunk_index, entry_id, tag_hash)
+    }
+
+    /// Delete a blind tag from the registry and every entry (v2)
+    ///
+    /// Pass every storage chunk of the vault as writable remaining accounts.
+    pub fn delete_tag(ctx: Context<DeleteTag>, tag_hash: [u8; 32]) -> Result<()> {
+        instructions::entry_tags::delete_tag_handler(ctx, tag_hash)
+    }
+
+    /// List a chunk's entries carrying a blind tag (v2, read-only)
+    ///
+    /// Returns `Vec<u64>` entry IDs via return data; query each chunk in turn.
+    pub fn list_tagged_entries(
+        ctx: Context<ListTaggedEntries>,
+        chunk_index: u16,
+        tag_hash: [u8; 32],
+    ) -> Result<Vec<u64>> {
+        instructions::entry_tags::list_tagged_entries_handler(ctx, chunk_index, tag_hash)
+    }
+
+    /// Close the empty tag registry and reclaim its rent
+    pub fn close_tag_registry(ctx: Context<CloseTagRegistry>) -> Result<()> {
+        instructions::entry_tags::close_tag_registry_handler(ctx)
+    }
+
+    /// Move an entry to the trash (v2)
+    ///
+    /// The entry keeps its data until the retention period elapses and
+    /// `purge_expired_trash` removes it.
+    pub fn trash_password_entry(
+        ctx: Context<TrashPasswordEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+    ) -> Result<()> {
+        instructions::trash::trash_password_entry_handler(ctx, chunk_index, entry_id)
+    }
+
+    /// Take an entry back out of the trash before its retention elapses (v2)
+    pub fn restore_password_entry(
+        ctx: Context<RestorePasswordEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+    ) -> Result<()> {
+        instructions::trash::restore_password_entry_handler(ctx, chunk_index, entry_id)
+    }
+
+    /// Set how long trashed entries are kept (v2, default 30 days)
+    pub fn set_trash_retention(ctx: Context<SetTrashRetention>, retention: i64) -> Result<()> {
+        instructions::trash::set_trash_retention_handler(ctx, retention)
+    }
+
+    /// Purge trashed entries past their retention period (v2, permissionless crank)
+    pub fn purge_expired_trash(ctx: Context<PurgeExpiredTrash>, chunk_index: u16) -> Result<()> {
+        instructions::trash::purge_expired_trash_handler(ctx, chunk_index)
+    }
+
+    /// Replace the encrypted settings blob synced across devices (v2)
+    ///
+    /// Limited per tier (128 / 256 / 512 bytes); an empty blob clears it.
+    /// The master lockbox is resized to fit, charging or refunding the owner.
+    pub fn update_settings(
+        ctx: Context<UpdateSettings>,
+        encrypted_settings: Vec<u8>,
+    ) -> Result<()> {
+        instructions::settings::update_settings_handler(ctx, encrypted_settings)
+    }
+
+    /// Opt in or out of the on-chain recently-used entry list (v2)
+    ///
+    /// When enabled, `retrieve_password_entry` records the last 8 retrieved
+    /// entry IDs on the master lockbox. Disabling clears the list.
+    pub fn set_recent_entries_tracking(
+        ctx: Context<SetRecentEntriesTracking>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::password_entry::set_recent_entries_tracking_handler(ctx, enabled)
+    }
+
+    /// Create or replace the vault's security policy (v2)
+    ///
+    /// Entry instructions enforce the policy whenever it exists. A configured
+    /// delete co-signer must also sign later policy changes.
+    pub fn update_security_policy(
+        ctx: Context<UpdateSecurityPolicy>,
+        delete_cosigner: Option<Pubkey>,
+        read_cooldown: u16,
+        padded_sizes: bool,
+        privacy_flags: u8,
+        freeze_on_honeytoken: bool,
+    ) -> Result<()> {
+        instructions::security_policy::update_security_policy_handler(
+            ctx,
+            delete_cosigner,
+            read_cooldown,
+            padded_sizes,
+            privacy_flags,
+            freeze_on_honeytoken,
+        )
+    }
+
+    /// Append an encrypted note to an entry (v2)
+    ///
+    /// Notes are stored in a per-entry side account (max 16 notes of up to
+    /// 128 bytes) so annotations never rewrite the entry ciphertext.
+    pub fn append_entry_note(
+        ctx: Context<AppendEntryNote>,
+        chunk_index: u16,
+        entry_id: u64,
+        encrypted_note: Vec<u8>,
     ) -> Result<()> {
-        instructions::password_entry::delete_password_entry_handler(ctx, chunk_index, entry_id)
+        instructions::entry_notes::append_entry_note_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            encrypted_note,
+        )
+    }
+
+    /// Close an entry's notes account and reclaim rent (v2)
+    pub fn close_entry_notes(ctx: Context<CloseEntryNotes>, entry_id: u64) -> Result<()> {
+        instructions::entry_notes::close_entry_notes_handler(ctx, entry_id)
     }
 
     /// Upgrade subscription tier (v2)
@@ -166,6 +813,46 @@ pub mod lockbox {
         instructions::subscription::downgrade_subscription_handler(ctx)
     }
 
+    /// Move an expired subscription to PastDue, then downgrade it to Free
+    /// once the dunning window ends (v2, permissionless crank)
+    pub fn process_subscription_expiry(ctx: Context<ProcessSubscriptionExpiry>) -> Result<()> {
+        instructions::subscription::process_subscription_expiry_handler(ctx)
+    }
+
+    /// Set the grace period between expiry and automatic downgrade (v2)
+    pub fn set_dunning_window(ctx: Context<SetDunningWindow>, window: i64) -> Result<()> {
+        instructions::subscription::set_dunning_window_handler(ctx, window)
+    }
+
+    /// Lower the bytes the vault may write per slot window below its tier's
+    /// burst limit (v2, 0 restores the tier default)
+    pub fn set_write_burst_limit(ctx: Context<SetWriteBurstLimit>, limit: u32) -> Result<()> {
+        instructions::subscription::set_write_burst_limit_handler(ctx, limit)
+    }
+
+    /// Buy capacity add-on packs, 50KB each at 0.005 SOL/month (v2)
+    ///
+    /// Adds storage on top of any tier, so a vault just over a tier
+    /// boundary doesn't need the next tier. Buying the same pack count while
+    /// active extends the term by a month; a different count starts a new
+    /// month-long term now.
+    pub fn purchase_capacity_addon(ctx: Context<PurchaseCapacityAddon>, packs: u8) -> Result<()> {
+        instructions::subscription::purchase_capacity_addon_handler(ctx, packs)
+    }
+
+    /// Create the vault's billing receipts account (v2)
+    pub fn initialize_billing_receipts(ctx: Context<InitializeBillingReceipts>) -> Result<()> {
+        instructions::billing::initialize_billing_receipts_handler(ctx)
+    }
+
+    /// Close a paid vault's 30-day billing period (v2, permissionless)
+    ///
+    /// Emits `BillingSnapshotEvent` with the period's payments and storage,
+    /// and records the receipt if `billing_receipts` is passed.
+    pub fn emit_billing_snapshot(ctx: Context<EmitBillingSnapshot>) -> Result<()> {
+        instructions::billing::emit_billing_snapshot_handler(ctx)
+    }
+
     /// Expand an existing storage chunk (v2)
     ///
     /// Uses Solana's realloc to dynamically increase chunk capacity without
@@ -184,6 +871,84 @@ pub mod lockbox {
         instructions::chunk_management::expand_chunk_handler(ctx, additional_size)
     }
 
+    /// Pre-flight check for `store_password_entry` (v2, read-only)
+    ///
+    /// Returns a `PreflightResult` via return data describing why a store of
+    /// `size` bytes into the chunk would fail, or the fee it would charge.
+    /// Intended to be simulated before asking the owner to sign.
+    pub fn can_store(
+        ctx: Context<CanStore>,
+        chunk_index: u16,
+        size: u32,
+    ) -> Result<PreflightResult> {
+        instructions::preflight::can_store_handler(ctx, chunk_index, size)
+    }
+
+    /// Pre-flight check for `expand_chunk` (v2, read-only)
+    ///
+    /// Returns a `PreflightResult` via return data; on success the fee is
+    /// the additional rent the expansion would cost.
+    pub fn can_expand(
+        ctx: Context<CanExpand>,
+        chunk_index: u16,
+        additional_size: u32,
+    ) -> Result<PreflightResult> {
+        instructions::preflight::can_expand_handler(ctx, chunk_index, additional_size)
+    }
+
+    /// Shrink a storage chunk and refund the freed rent (v2)
+    ///
+    /// Releases `reduce_by` bytes of unused capacity; lamports above the new
+    /// rent-exempt minimum are returned to the owner and reported in a
+    /// `RentReclaimedEvent`.
+    pub fn shrink_chunk(
+        ctx: Context<ShrinkChunk>,
+        chunk_index: u16,
+        reduce_by: u32,
+    ) -> Result<()> {
+        instructions::chunk_management::shrink_chunk_handler(ctx, chunk_index, reduce_by)
+    }
+
+    /// Move archived entries from a hot chunk into a cold archive chunk (v2)
+    ///
+    /// The cold chunk must be created with `StorageType::ArchivedPasswords`.
+    /// Moves up to `max_entries` per call; entry IDs are preserved.
+    pub fn migrate_archived_entries(
+        ctx: Context<MigrateArchivedEntries>,
+        source_chunk_index: u16,
+        cold_chunk_index: u16,
+        max_entries: u8,
+    ) -> Result<()> {
+        instructions::chunk_management::migrate_archived_entries_handler(
+            ctx,
+            source_chunk_index,
+            cold_chunk_index,
+            max_entries,
+        )
+    }
+
+    /// Take or renew the writer lease for a device (v2)
+    ///
+    /// While the lease is live, `expand_chunk`, `shrink_chunk` and
+    /// `migrate_archived_entries` must be co-signed by the `device` key. Any
+    /// device may take over once the lease has expired.
+    pub fn acquire_writer_lease(ctx: Context<ManageWriterLease>, duration: i64) -> Result<()> {
+        instructions::writer_lease::acquire_writer_lease_handler(ctx, duration)
+    }
+
+    /// Give up the writer lease before it expires (v2)
+    pub fn release_writer_lease(ctx: Context<ManageWriterLease>) -> Result<()> {
+        instructions::writer_lease::release_writer_lease_handler(ctx)
+    }
+
+    /// Emit a chunk health report (v2, permissionless)
+    ///
+    /// Reports entry count, slack and fragmentation, trashed bytes and rent
+    /// status so maintenance UIs can recommend compaction or expansion.
+    pub fn emit_chunk_report(ctx: Context<EmitChunkReport>, chunk_index: u16) -> Result<()> {
+        instructions::chunk_management::emit_chunk_report_handler(ctx, chunk_index)
+    }
+
     /// Initialize category registry (v2)
     ///
     /// Creates the category registry account for organizing password entries.
@@ -253,13 +1018,73 @@ pub mod lockbox {
     /// Removes a category from the registry. Category must be empty
     /// (no password entries assigned to it).
     ///
-    /// # Arguments
-    /// * `category_id` - ID of category to delete
-    pub fn delete_category(
-        ctx: Context<DeleteCategory>,
-        category_id: u8,
-    ) -> Result<()> {
-        instructions::category_management::delete_category_handler(ctx, category_id)
+    /// # Arguments
+    /// * `category_id` - ID of category to delete
+    pub fn delete_category(
+        ctx: Context<DeleteCategory>,
+        category_id: u8,
+    ) -> Result<()> {
+        instructions::category_management::delete_category_handler(ctx, category_id)
+    }
+
+    /// Emit per-category entry and byte usage (v2, permissionless)
+    ///
+    /// Counts are maintained by store/update/delete, which require
+    /// `category_registry` once it exists.
+    pub fn emit_category_summary(ctx: Context<EmitCategorySummary>) -> Result<()> {
+        instructions::category_management::emit_category_summary_handler(ctx)
+    }
+
+    /// Create the category index (v2)
+    ///
+    /// Entry instructions that add or remove entries require it from then
+    /// on; backfill existing entries with `rebuild_category_index`.
+    pub fn initialize_category_index(ctx: Context<InitializeCategoryIndex>) -> Result<()> {
+        instructions::category_index::initialize_category_index_handler(ctx)
+    }
+
+    /// Recompute the category index from every chunk (v2)
+    ///
+    /// Pass every registered chunk as a read-only remaining account.
+    pub fn rebuild_category_index(ctx: Context<RebuildCategoryIndex>) -> Result<()> {
+        instructions::category_index::rebuild_category_index_handler(ctx)
+    }
+
+    /// List the entry IDs filed under a category (v2, read-only)
+    ///
+    /// Returns `Vec<u64>` via return data.
+    pub fn list_category_entries(
+        ctx: Context<QueryCategoryIndex>,
+        category: u32,
+    ) -> Result<Vec<u64>> {
+        instructions::category_index::list_category_entries_handler(ctx, category)
+    }
+
+    /// Close the category index and reclaim its rent
+    pub fn close_category_index(ctx: Context<CloseCategoryIndex>) -> Result<()> {
+        instructions::category_index::close_category_index_handler(ctx)
+    }
+
+    /// Recompute the vault's counters and indexes from every chunk (v2)
+    ///
+    /// Repairs `total_entries`, storage usage and capacity, and, when passed,
+    /// the category registry's counts and the category index. Pass every
+    /// registered chunk as a read-only remaining account.
+    pub fn rebuild_indexes(ctx: Context<RebuildIndexes>) -> Result<()> {
+        instructions::rebuild_indexes::rebuild_indexes_handler(ctx)
+    }
+
+    /// Request vault deletion (v2)
+    ///
+    /// Sets a tombstone; `finalize_vault_deletion` and destroying non-empty
+    /// chunks are allowed once `VAULT_DELETION_DELAY` (7 days) has passed.
+    pub fn request_vault_deletion(ctx: Context<VaultDeletion>) -> Result<()> {
+        instructions::close_account::request_vault_deletion_handler(ctx)
+    }
+
+    /// Cancel a pending vault deletion (v2)
+    pub fn cancel_vault_deletion(ctx: Context<VaultDeletion>) -> Result<()> {
+        instructions::close_account::cancel_vault_deletion_handler(ctx)
     }
 
     /// Close Master Lockbox account and reclaim rent (v2)
@@ -270,29 +1095,36 @@ pub mod lockbox {
     ///
     /// # Security
     /// - Only the account owner can close their account
+    /// - Deletion must have been requested at least `VAULT_DELETION_DELAY` ago
+    /// - Registered chunks, the category registry, security policy, deny-list,
+    ///   recovery configs and emergency access must be closed first and passed as
+    ///   remaining accounts
+    /// - Shared vaults created by this vault must hold no entries
     /// - All lamports (rent) are returned to the owner
     /// - Account is marked for garbage collection
     ///
     /// # Returns
     /// * `Ok(())` on successful closure
     /// * `Err(LockboxError::Unauthorized)` if signer is not owner
-    pub fn close_master_lockbox(ctx: Context<CloseMasterLockbox>) -> Result<()> {
-        instructions::close_account::close_master_lockbox_handler(ctx)
+    pub fn finalize_vault_deletion(ctx: Context<FinalizeVaultDeletion>) -> Result<()> {
+        instructions::close_account::finalize_vault_deletion_handler(ctx)
     }
 
     /// Close a storage chunk and reclaim rent (v2)
     ///
     /// Closes an individual storage chunk and returns rent to the owner.
     /// Should be called before closing the Master Lockbox for maximum
-    /// rent recovery.
+    /// rent recovery. The chunk is unregistered from the master lockbox.
     ///
     /// # Arguments
     /// * `chunk_index` - Index of the chunk to close
+    /// * `allow_non_empty` - Destroy any entries still stored in the chunk
     pub fn close_storage_chunk(
         ctx: Context<CloseStorageChunk>,
         chunk_index: u16,
+        allow_non_empty: bool,
     ) -> Result<()> {
-        instructions::close_account::close_storage_chunk_handler(ctx, chunk_index)
+        instructions::close_account::close_storage_chunk_handler(ctx, chunk_index, allow_non_empty)
     }
 
     /// Force close an orphaned storage chunk (v2)
@@ -310,6 +1142,7 @@ pub mod lockbox {
     /// - Only the master lockbox owner can force close chunks
     /// - PDA derivation is validated to ensure correct ownership
     /// - All rent is returned to the owner
+    /// - Data is zeroed and the account is reassigned to the system program
     ///
     /// # Arguments
     /// * `chunk_index` - Index of the orphaned chunk to force close
@@ -323,6 +1156,14 @@ pub mod lockbox {
         instructions::close_account::force_close_orphaned_chunk_handler(ctx, chunk_index)
     }
 
+    /// Report chunk accounts missing from, or unknown to, the chunk list (v2)
+    ///
+    /// Candidate chunk PDAs are passed as remaining accounts; one
+    /// `OrphanDetectedEvent` is emitted per discrepancy. Permissionless.
+    pub fn detect_orphans(ctx: Context<DetectOrphans>) -> Result<()> {
+        instructions::close_account::detect_orphans_handler(ctx)
+    }
+
     // ============================================================================
     // Social Recovery Instructions (v2)
     // ============================================================================
@@ -357,9 +1198,27 @@ pub mod lockbox {
         )
     }
 
+    /// Record the hash of a guardian's off-chain agreement
+    pub fn set_guardian_agreement(
+        ctx: Context<SetGuardianAgreement>,
+        guardian_pubkey: Pubkey,
+        agreement_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::recovery_management::set_guardian_agreement_handler(
+            ctx,
+            guardian_pubkey,
+            agreement_hash,
+        )
+    }
+
     /// Guardian accepts their role
-    pub fn accept_guardianship(ctx: Context<AcceptGuardianship>) -> Result<()> {
-        instructions::recovery_management::accept_guardianship_handler(ctx)
+    ///
+    /// `agreement_hash` must match the owner's recorded agreement, if any.
+    pub fn accept_guardianship(
+        ctx: Context<AcceptGuardianship>,
+        agreement_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::recovery_management::accept_guardianship_handler(ctx, agreement_hash)
     }
 
     /// Remove a guardian
@@ -370,6 +1229,45 @@ pub mod lockbox {
         instructions::recovery_management::remove_guardian_handler(ctx, guardian_pubkey)
     }
 
+    /// Restart a pending guardian's acceptance window
+    pub fn resend_guardian_invitation(
+        ctx: Context<ResendGuardianInvitation>,
+        guardian_pubkey: Pubkey,
+    ) -> Result<()> {
+        instructions::recovery_management::resend_guardian_invitation_handler(ctx, guardian_pubkey)
+    }
+
+    /// Remove guardians whose invitations lapsed (permissionless crank)
+    pub fn expire_guardian_invitations(ctx: Context<ExpireGuardianInvitations>) -> Result<()> {
+        instructions::recovery_management::expire_guardian_invitations_handler(ctx)
+    }
+
+    /// Set how long future guardian invitations stay open
+    pub fn set_guardian_invitation_ttl(
+        ctx: Context<SetGuardianInvitationTtl>,
+        invitation_ttl: i64,
+    ) -> Result<()> {
+        instructions::recovery_management::set_guardian_invitation_ttl_handler(ctx, invitation_ttl)
+    }
+
+    /// Pause recovery without removing guardians
+    pub fn disable_recovery(ctx: Context<SetRecoveryEnabled>) -> Result<()> {
+        instructions::recovery_management::disable_recovery_handler(ctx)
+    }
+
+    /// Resume recovery after `disable_recovery`
+    pub fn enable_recovery(ctx: Context<SetRecoveryEnabled>) -> Result<()> {
+        instructions::recovery_management::enable_recovery_handler(ctx)
+    }
+
+    /// Block recovery while the owner has recently used the vault
+    pub fn set_owner_activity_window(
+        ctx: Context<SetOwnerActivityWindow>,
+        window: i64,
+    ) -> Result<()> {
+        instructions::recovery_management::set_owner_activity_window_handler(ctx, window)
+    }
+
     /// Initiate wallet recovery
     pub fn initiate_recovery(
         ctx: Context<InitiateRecovery>,
@@ -388,6 +1286,9 @@ pub mod lockbox {
     }
 
     /// Complete recovery and transfer ownership
+    ///
+    /// Signed by the requester or the requested new owner. Wallet vaults
+    /// only accept this as a top-level instruction.
     pub fn complete_recovery(ctx: Context<CompleteRecovery>) -> Result<()> {
         instructions::recovery_management::complete_recovery_handler(ctx)
     }
@@ -397,6 +1298,11 @@ pub mod lockbox {
         instructions::recovery_management::cancel_recovery_handler(ctx)
     }
 
+    /// Close the recovery configuration (no request may be in progress)
+    pub fn close_recovery_config(ctx: Context<CloseRecoveryConfig>) -> Result<()> {
+        instructions::recovery_management::close_recovery_config_handler(ctx)
+    }
+
     // ============================================================================
     // Emergency Access Instructions (v2)
     // ============================================================================
@@ -406,11 +1312,13 @@ pub mod lockbox {
         ctx: Context<InitializeEmergencyAccess>,
         inactivity_period: i64,
         grace_period: i64,
+        notification_interval: i64,
     ) -> Result<()> {
         instructions::emergency_access_management::initialize_emergency_access_handler(
             ctx,
             inactivity_period,
             grace_period,
+            notification_interval,
         )
     }
 
@@ -431,6 +1339,19 @@ pub mod lockbox {
         )
     }
 
+    /// Add up to `MAX_EMERGENCY_CONTACT_BATCH` emergency contacts in one
+    /// instruction
+    ///
+    /// All-or-nothing; send it in the same transaction as
+    /// `initialize_emergency_access` to set up the dead man's switch with one
+    /// signature.
+    pub fn add_emergency_contacts_batch(
+        ctx: Context<AddEmergencyContactsBatch>,
+        contacts: Vec<NewEmergencyContact>,
+    ) -> Result<()> {
+        instructions::emergency_access_management::add_emergency_contacts_batch_handler(ctx, contacts)
+    }
+
     /// Emergency contact accepts their role
     pub fn accept_emergency_contact(ctx: Context<AcceptEmergencyContact>) -> Result<()> {
         instructions::emergency_access_management::accept_emergency_contact_handler(ctx)
@@ -447,6 +1368,56 @@ pub mod lockbox {
         )
     }
 
+    /// Restart a pending emergency contact's acceptance window
+    pub fn resend_emergency_contact_invitation(
+        ctx: Context<ResendEmergencyContactInvitation>,
+        contact_pubkey: Pubkey,
+    ) -> Result<()> {
+        instructions::emergency_access_management::resend_emergency_contact_invitation_handler(
+            ctx,
+            contact_pubkey,
+        )
+    }
+
+    /// Remove emergency contacts whose invitations lapsed (permissionless crank)
+    pub fn expire_pending_contacts(ctx: Context<ExpirePendingContacts>) -> Result<()> {
+        instructions::emergency_access_management::expire_pending_contacts_handler(ctx)
+    }
+
+    /// Set how long future emergency contact invitations stay open
+    pub fn set_emergency_invitation_ttl(
+        ctx: Context<SetEmergencyInvitationTtl>,
+        invitation_ttl: i64,
+    ) -> Result<()> {
+        instructions::emergency_access_management::set_emergency_invitation_ttl_handler(
+            ctx,
+            invitation_ttl,
+        )
+    }
+
+    /// Sign standing consent to release data to FullAccess-level contacts
+    ///
+    /// Required before `activate_emergency_access` grants FullAccess or
+    /// TransferOwnership contacts; ViewOnly contacts need no consent.
+    pub fn set_export_consent(
+        ctx: Context<SetExportConsent>,
+        contacts: Vec<Pubkey>,
+        scope_hash: [u8; 32],
+        conditions_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::emergency_access_management::set_export_consent_handler(
+            ctx,
+            contacts,
+            scope_hash,
+            conditions_hash,
+        )
+    }
+
+    /// Withdraw export consent
+    pub fn revoke_export_consent(ctx: Context<SetExportConsent>) -> Result<()> {
+        instructions::emergency_access_management::revoke_export_consent_handler(ctx)
+    }
+
     /// Record activity (extends countdown)
     pub fn record_activity(ctx: Context<RecordActivity>) -> Result<()> {
         instructions::emergency_access_management::record_activity_handler(ctx)
@@ -462,16 +1433,49 @@ pub mod lockbox {
         instructions::emergency_access_management::check_and_start_countdown_handler(ctx)
     }
 
+    /// Re-notify emergency contacts during the countdown (cron job)
+    pub fn notify_emergency_contacts(ctx: Context<NotifyEmergencyContacts>) -> Result<()> {
+        instructions::emergency_access_management::notify_emergency_contacts_handler(ctx)
+    }
+
+    /// Change how often contacts are notified during the countdown
+    pub fn set_notification_interval(
+        ctx: Context<SetNotificationInterval>,
+        notification_interval: i64,
+    ) -> Result<()> {
+        instructions::emergency_access_management::set_notification_interval_handler(
+            ctx,
+            notification_interval,
+        )
+    }
+
     /// Activate emergency access
+    ///
+    /// Writes an immutable `EmergencyGrantRecord` paid for by the caller.
+    /// FullAccess and TransferOwnership contacts are only granted if named
+    /// in the owner's export consent. Wallet vaults only accept this as a
+    /// top-level instruction.
     pub fn activate_emergency_access(ctx: Context<ActivateEmergencyAccess>) -> Result<()> {
         instructions::emergency_access_management::activate_emergency_access_handler(ctx)
     }
 
+    /// Rehearse countdown and activation without granting access
+    ///
+    /// Emits the real flow's events with `test_mode = true`.
+    pub fn test_emergency_flow(ctx: Context<TestEmergencyFlow>) -> Result<()> {
+        instructions::emergency_access_management::test_emergency_flow_handler(ctx)
+    }
+
     /// Cancel emergency countdown
     pub fn cancel_emergency_countdown(ctx: Context<CancelEmergencyCountdown>) -> Result<()> {
         instructions::emergency_access_management::cancel_emergency_countdown_handler(ctx)
     }
 
+    /// Close the emergency access configuration (no countdown may be running)
+    pub fn close_emergency_access(ctx: Context<CloseEmergencyAccess>) -> Result<()> {
+        instructions::emergency_access_management::close_emergency_access_handler(ctx)
+    }
+
     // ============================================================================
     // Social Recovery Instructions V2 (Secure - Recommended)
     // ============================================================================
@@ -508,6 +1512,17 @@ pub mod lockbox {
         )
     }
 
+    /// Add up to `MAX_GUARDIAN_BATCH` guardians V2 in one instruction
+    ///
+    /// All-or-nothing; send it in the same transaction as
+    /// `initialize_recovery_config_v2` to set up recovery atomically.
+    pub fn add_guardians_batch(
+        ctx: Context<AddGuardiansBatch>,
+        guardians: Vec<NewGuardian>,
+    ) -> Result<()> {
+        instructions::recovery_management_v2::add_guardians_batch_handler(ctx, guardians)
+    }
+
     /// Initiate recovery V2 with challenge generation
     ///
     /// SECURITY FIX (VULN-003): request_id is now generated atomically on-chain
@@ -535,6 +1550,7 @@ pub mod lockbox {
     ///
     /// SECURITY FIX (VULN-002): Enhanced to require master_secret submission
     /// for stronger cryptographic binding between challenge and secret.
+    /// Wallet vaults only accept this as a top-level instruction.
     pub fn complete_recovery_with_proof(
         ctx: Context<CompleteRecoveryV2>,
         challenge_plaintext: [u8; 32],
@@ -547,6 +1563,425 @@ pub mod lockbox {
         )
     }
 
+    /// Set the V2 recovery cooldown and the clock it is measured on
+    pub fn set_recovery_cooldown_v2(
+        ctx: Context<SetRecoveryCooldownV2>,
+        mode: CooldownMode,
+        cooldown: u64,
+    ) -> Result<()> {
+        instructions::recovery_management_v2::set_recovery_cooldown_v2_handler(ctx, mode, cooldown)
+    }
+
+    /// Close the V2 recovery configuration (no request may be in progress)
+    pub fn close_recovery_config_v2(ctx: Context<CloseRecoveryConfigV2>) -> Result<()> {
+        instructions::recovery_management_v2::close_recovery_config_v2_handler(ctx)
+    }
+
+    // ============================================================================
+    // Handle Aliases
+    // ============================================================================
+
+    /// Register a global handle (by hash) resolving to the signer's vault
+    ///
+    /// Charges `ALIAS_REGISTRATION_FEE_LAMPORTS` to the treasury plus rent.
+    /// Fails if the handle is already taken.
+    pub fn register_alias(ctx: Context<RegisterAlias>, handle_hash: [u8; 32]) -> Result<()> {
+        instructions::alias_registry::register_alias_handler(ctx, handle_hash)
+    }
+
+    /// Point a handle at another vault owner (current holder only)
+    pub fn transfer_alias(ctx: Context<TransferAlias>, new_owner: Pubkey) -> Result<()> {
+        instructions::alias_registry::transfer_alias_handler(ctx, new_owner)
+    }
+
+    /// Release a handle and reclaim its rent (the fee is not refunded)
+    pub fn release_alias(ctx: Context<ReleaseAlias>) -> Result<()> {
+        instructions::alias_registry::release_alias_handler(ctx)
+    }
+
+    // ============================================================================
+    // Contact Book
+    // ============================================================================
+
+    /// Create the owner's (empty) contact book
+    pub fn initialize_contact_book(ctx: Context<InitializeContactBook>) -> Result<()> {
+        instructions::contact_book::initialize_contact_book_handler(ctx)
+    }
+
+    /// Save a recipient key under an encrypted label (limit depends on tier)
+    pub fn add_contact(
+        ctx: Context<AddContact>,
+        contact: Pubkey,
+        label_encrypted: Vec<u8>,
+    ) -> Result<()> {
+        instructions::contact_book::add_contact_handler(ctx, contact, label_encrypted)
+    }
+
+    /// Replace a contact's encrypted label
+    pub fn update_contact(
+        ctx: Context<UpdateContact>,
+        contact: Pubkey,
+        label_encrypted: Vec<u8>,
+    ) -> Result<()> {
+        instructions::contact_book::update_contact_handler(ctx, contact, label_encrypted)
+    }
+
+    /// Remove a contact and refund its share of rent
+    pub fn remove_contact(ctx: Context<RemoveContact>, contact: Pubkey) -> Result<()> {
+        instructions::contact_book::remove_contact_handler(ctx, contact)
+    }
+
+    /// Close the contact book and reclaim its rent
+    pub fn close_contact_book(ctx: Context<CloseContactBook>) -> Result<()> {
+        instructions::contact_book::close_contact_book_handler(ctx)
+    }
+
+    /// Deny a key, e.g. one reported stolen (v2)
+    ///
+    /// Denied keys cannot be added as guardians, emergency contacts,
+    /// contacts or copy recipients. Creates the deny-list on first use.
+    pub fn deny_key(ctx: Context<DenyKey>, key: Pubkey) -> Result<()> {
+        instructions::deny_list::deny_key_handler(ctx, key)
+    }
+
+    /// Take a key off the deny-list (v2)
+    pub fn allow_key(ctx: Context<AllowKey>, key: Pubkey) -> Result<()> {
+        instructions::deny_list::allow_key_handler(ctx, key)
+    }
+
+    /// Close the deny-list and reclaim its rent
+    pub fn close_deny_list(ctx: Context<CloseDenyList>) -> Result<()> {
+        instructions::deny_list::close_deny_list_handler(ctx)
+    }
+
+    // ============================================================================
+    // Inbox
+    // ============================================================================
+
+    /// Create the signer's inbox for incoming invitations and shares
+    pub fn initialize_inbox(ctx: Context<InitializeInbox>) -> Result<()> {
+        instructions::inbox::initialize_inbox_handler(ctx)
+    }
+
+    /// Accept an inbox item
+    ///
+    /// Only clears the pointer; pair with the offer's own accept instruction
+    /// (`accept_guardianship`, `accept_emergency_contact`) in one transaction.
+    pub fn accept_inbox_item(
+        ctx: Context<ResolveInboxItem>,
+        kind: InboxItemKind,
+        account: Pubkey,
+    ) -> Result<()> {
+        instructions::inbox::accept_inbox_item_handler(ctx, kind, account)
+    }
+
+    /// Dismiss an inbox item without acting on it
+    pub fn dismiss_inbox_item(
+        ctx: Context<ResolveInboxItem>,
+        kind: InboxItemKind,
+        account: Pubkey,
+    ) -> Result<()> {
+        instructions::inbox::dismiss_inbox_item_handler(ctx, kind, account)
+    }
+
+    /// Close the signer's inbox and reclaim its rent
+    pub fn close_inbox(ctx: Context<CloseInbox>) -> Result<()> {
+        instructions::inbox::close_inbox_handler(ctx)
+    }
+
+    // ============================================================================
+    // Export Manifests
+    // ============================================================================
+
+    /// Commit the hash of a client-built export manifest (v2)
+    ///
+    /// Lets a user prove later which entries they exported and when; the
+    /// manifest carries sizes and ciphertext hashes, never plaintext.
+    pub fn commit_export_manifest(
+        ctx: Context<CommitExportManifest>,
+        manifest_hash: [u8; 32],
+        format_version: u8,
+        entry_count: u32,
+    ) -> Result<()> {
+        instructions::export_manifest::commit_export_manifest_handler(
+            ctx,
+            manifest_hash,
+            format_version,
+            entry_count,
+        )
+    }
+
+    // ============================================================================
+    // Vault Transfer Escrow
+    // ============================================================================
+
+    /// List the vault for sale to `buyer` at `price` lamports
+    ///
+    /// The listing stays open for `duration` seconds (at most 30 days).
+    pub fn list_vault_transfer(
+        ctx: Context<ListVaultTransfer>,
+        buyer: Pubkey,
+        price: u64,
+        duration: i64,
+    ) -> Result<()> {
+        instructions::vault_transfer::list_vault_transfer_handler(ctx, buyer, price, duration)
+    }
+
+    /// Withdraw an unaccepted listing and reclaim its rent
+    pub fn cancel_vault_transfer(ctx: Context<CancelVaultTransfer>) -> Result<()> {
+        instructions::vault_transfer::cancel_vault_transfer_handler(ctx)
+    }
+
+    /// Pay for a listed vault and take ownership of it and its chunks
+    ///
+    /// Pass every storage chunk as a writable remaining account. The buyer
+    /// must then re-key every entry before the transfer can be completed.
+    pub fn accept_vault_transfer(ctx: Context<AcceptVaultTransfer>, price: u64) -> Result<()> {
+        instructions::vault_transfer::accept_vault_transfer_handler(ctx, price)
+    }
+
+    /// Replace a transferred entry's ciphertext with one under the buyer's key
+    ///
+    /// `expected_layout_epoch` is the chunk's `layout_epoch` as the client
+    /// read it; the call fails with `StaleChunkLayout` if it has changed.
+    pub fn rekey_transferred_entry(
+        ctx: Context<RekeyTransferredEntry>,
+        chunk_index: u16,
+        entry_id: u64,
+        reencrypted_data: Vec<u8>,
+        expected_layout_epoch: u32,
+    ) -> Result<()> {
+        instructions::vault_transfer::rekey_transferred_entry_handler(
+            ctx,
+            chunk_index,
+            entry_id,
+            reencrypted_data,
+            expected_layout_epoch,
+        )
+    }
+
+    /// Close a transfer once no entry predates the handover
+    ///
+    /// Pass every storage chunk as a writable remaining account.
+    pub fn complete_vault_rekey(ctx: Context<CompleteVaultRekey>) -> Result<()> {
+        instructions::vault_transfer::complete_vault_rekey_handler(ctx)
+    }
+
+    // ============================================================================
+    // Shared Vaults
+    // ============================================================================
+
+    /// Create a shared vault whose storage counts against this vault's tier
+    ///
+    /// `wrapped_vault_key` is the shared vault key wrapped to the owner,
+    /// who becomes its first admin.
+    pub fn create_shared_vault(
+        ctx: Context<CreateSharedVault>,
+        vault_id: u32,
+        wrapped_vault_key: Vec<u8>,
+    ) -> Result<()> {
+        instructions::shared_vault::create_shared_vault_handler(ctx, vault_id, wrapped_vault_key)
+    }
+
+    /// Add a member to a shared vault (admin only)
+    pub fn add_shared_vault_member(
+        ctx: Context<AddSharedVaultMember>,
+        member: Pubkey,
+        role: SharedVaultRole,
+        wrapped_vault_key: Vec<u8>,
+    ) -> Result<()> {
+        instructions::shared_vault::add_shared_vault_member_handler(ctx, member, role, wrapped_vault_key)
+    }
+
+    /// Remove a member from a shared vault (admin only)
+    pub fn remove_shared_vault_member(ctx: Context<RemoveSharedVaultMember>, member: Pubkey) -> Result<()> {
+        instructions::shared_vault::remove_shared_vault_member_handler(ctx, member)
+    }
+
+    /// Remove a member from a shared vault and flag every entry they could
+    /// read as `needs_rotation` (admin only)
+    ///
+    /// Pass every entry of the vault as a writable remaining account.
+    pub fn remove_member_with_rotation_flag(ctx: Context<RemoveSharedVaultMember>, member: Pubkey) -> Result<()> {
+        instructions::shared_vault::remove_member_with_rotation_flag_handler(ctx, member)
+    }
+
+    /// Store a shared entry, counted in the creating vault's shared usage
+    pub fn store_shared_entry(ctx: Context<StoreSharedEntry>, encrypted_data: Vec<u8>) -> Result<()> {
+        instructions::shared_vault::store_shared_entry_handler(ctx, encrypted_data)
+    }
+
+    /// Replace a shared entry's ciphertext
+    pub fn update_shared_entry(
+        ctx: Context<UpdateSharedEntry>,
+        entry_id: u64,
+        encrypted_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::shared_vault::update_shared_entry_handler(ctx, entry_id, encrypted_data)
+    }
+
+    /// Delete a shared entry and release its storage
+    pub fn delete_shared_entry(ctx: Context<DeleteSharedEntry>, entry_id: u64) -> Result<()> {
+        instructions::shared_vault::delete_shared_entry_handler(ctx, entry_id)
+    }
+
+    /// Check a shared entry out for `duration` seconds so no other member
+    /// can update or delete it until it is checked in or the checkout lapses
+    pub fn checkout_entry(ctx: Context<ManageEntryCheckout>, entry_id: u64, duration: i64) -> Result<()> {
+        instructions::shared_vault::checkout_entry_handler(ctx, entry_id, duration)
+    }
+
+    /// Release a shared entry checked out with `checkout_entry`
+    pub fn checkin_entry(ctx: Context<ManageEntryCheckout>, entry_id: u64) -> Result<()> {
+        instructions::shared_vault::checkin_entry_handler(ctx, entry_id)
+    }
+
+    /// Return a shared entry's ciphertext to a member
+    pub fn retrieve_shared_entry(ctx: Context<RetrieveSharedEntry>, entry_id: u64) -> Result<Vec<u8>> {
+        instructions::shared_vault::retrieve_shared_entry_handler(ctx, entry_id)
+    }
+
+    /// Let holders of a token (or of an NFT in a verified collection)
+    /// self-join as Readers, or make the vault invite-only with `None`
+    pub fn set_shared_vault_gate(ctx: Context<SetSharedVaultGate>, gate: Option<SharedVaultGate>) -> Result<()> {
+        instructions::shared_vault::set_shared_vault_gate_handler(ctx, gate)
+    }
+
+    /// Join a gated shared vault as a Reader by proving a token holding
+    ///
+    /// Reads revalidate the holding, so passing the token on ends access.
+    pub fn join_shared_vault_with_token(ctx: Context<JoinSharedVaultWithToken>) -> Result<()> {
+        instructions::shared_vault::join_shared_vault_with_token_handler(ctx)
+    }
+
+    /// Deliver or replace a member's wrapped vault key (admin only)
+    pub fn set_member_vault_key(
+        ctx: Context<SetMemberVaultKey>,
+        member: Pubkey,
+        wrapped_vault_key: Vec<u8>,
+    ) -> Result<()> {
+        instructions::shared_vault::set_member_vault_key_handler(ctx, member, wrapped_vault_key)
+    }
+
+    /// Close a shared vault once it has no entries
+    pub fn close_shared_vault(ctx: Context<CloseSharedVault>) -> Result<()> {
+        instructions::shared_vault::close_shared_vault_handler(ctx)
+    }
+
+    // ============================================================================
+    // Program Config, Organization Invoices & Refunds
+    // ============================================================================
+
+    /// Create the program config naming the billing admin
+    ///
+    /// Only the program's upgrade authority can call this, once.
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        admin: Pubkey,
+        invoice_receiver: Pubkey,
+    ) -> Result<()> {
+        instructions::program_config::initialize_program_config_handler(ctx, admin, invoice_receiver)
+    }
+
+    /// Hand over the admin role or change the invoice receiver (admin only)
+    pub fn set_program_config(
+        ctx: Context<SetProgramConfig>,
+        admin: Pubkey,
+        invoice_receiver: Pubkey,
+    ) -> Result<()> {
+        instructions::program_config::set_program_config_handler(ctx, admin, invoice_receiver)
+    }
+
+    /// Issue a subscription invoice to an organization's vault (admin only)
+    ///
+    /// # Arguments
+    /// * `invoice_id` - Admin-chosen number, unique per vault
+    /// * `tier` - Tier paid for; the vault's current tier or a higher one
+    /// * `amount` - Amount due, in base units of the invoice receiver's mint
+    /// * `period` - Seconds of subscription bought
+    /// * `memo_hash` - Hash of the off-chain invoice memo
+    pub fn issue_invoice(
+        ctx: Context<IssueInvoice>,
+        invoice_id: u64,
+        tier: SubscriptionTier,
+        amount: u64,
+        period: i64,
+        memo_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::invoice::issue_invoice_handler(ctx, invoice_id, tier, amount, period, memo_hash)
+    }
+
+    /// Pay an invoice from a token account and extend the vault's subscription
+    pub fn pay_invoice(ctx: Context<PayInvoice>, invoice_id: u64) -> Result<()> {
+        instructions::invoice::pay_invoice_handler(ctx, invoice_id)
+    }
+
+    /// Withdraw an unpaid invoice (admin only)
+    pub fn void_invoice(ctx: Context<VoidInvoice>, invoice_id: u64) -> Result<()> {
+        instructions::invoice::void_invoice_handler(ctx, invoice_id)
+    }
+
+    /// Refund a vault owner from the treasury (admin only)
+    ///
+    /// Nets the refund out of the vault's current billing period and
+    /// records it in a `RefundRecord`.
+    ///
+    /// # Arguments
+    /// * `owner` - Current owner of the vault, who receives the refund
+    /// * `amount` - Lamports refunded
+    /// * `reason_hash` - Hash of the off-chain refund reason
+    pub fn issue_refund(
+        ctx: Context<IssueRefund>,
+        owner: Pubkey,
+        amount: u64,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::refund::issue_refund_handler(ctx, owner, amount, reason_hash)
+    }
+
+    // ============================================================================
+    // Account Schema Migrations
+    // ============================================================================
+
+    /// Rewrite an unversioned account in the schema v1 layout
+    ///
+    /// Required once for every MasterLockbox, StorageChunk, recovery and
+    /// emergency access account created before the `version` byte existed.
+    pub fn migrate_account_v1(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migration::migrate_account_v1_handler(ctx)
+    }
+
+    /// Rewrite a schema v1 EmergencyAccess, RecoveryConfig, RecoveryConfigV2,
+    /// MasterLockbox or StorageChunk account in the v2 layout
+    ///
+    /// EmergencyAccess gains the countdown notification settings
+    /// (notifications start off); RecoveryConfig gains guardian invitation
+    /// expiry, with existing invitations dated from when the guardian was
+    /// added; RecoveryConfigV2 gains a cooldown mode, keeping its one-hour
+    /// timestamp cooldown; MasterLockbox gains billing period tracking; StorageChunk and
+    /// its registration gain a layout epoch starting at 0.
+    pub fn migrate_account_v2(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migration::migrate_account_v2_handler(ctx)
+    }
+
+    /// Rewrite a schema v2 EmergencyAccess or StorageChunk account in the
+    /// v3 layout
+    ///
+    /// EmergencyAccess adds contact invitation expiry, with existing
+    /// invitations dated from when the contact was added; StorageChunk entry
+    /// headers gain segment links, with every existing entry held whole.
+    pub fn migrate_account_v3(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migration::migrate_account_v3_handler(ctx)
+    }
+
+    /// Rewrite a schema v3 StorageChunk account in the v4 layout
+    ///
+    /// Entry headers gain a tag mask, rotation date and TOTP parent, with
+    /// every existing entry untagged, unscheduled and unattached.
+    pub fn migrate_account_v4(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migration::migrate_account_v4_handler(ctx)
+    }
+
     // ============================================================================
     // V1 Instructions - Legacy (Backward Compatibility)
     // ============================================================================
@@ -630,6 +2065,12 @@ pub mod lockbox {
         lockbox.last_action_slot = clock.slot;
         lockbox.bump = ctx.bumps.lockbox;
 
+        emit!(V1DataStored {
+            owner: lockbox.owner,
+            payload_size: lockbox.ciphertext.len() as u32,
+            slot: clock.slot,
+        });
+
         msg!("Encrypted data stored successfully (v1)");
         Ok(())
     }
@@ -656,6 +2097,12 @@ pub mod lockbox {
             LockboxError::CooldownNotElapsed
         );
 
+        emit!(V1DataRetrieved {
+            owner: lockbox.owner,
+            payload_size: lockbox.ciphertext.len() as u32,
+            slot: clock.slot,
+        });
+
         Ok(EncryptedData {
             ciphertext: lockbox.ciphertext.clone(),
             nonce: lockbox.nonce,
@@ -775,6 +2222,22 @@ pub struct EncryptedData {
     pub salt: [u8; SALT_SIZE],
 }
 
+/// Emitted by `store_encrypted` so remaining v1 usage can be measured
+#[event]
+pub struct V1DataStored {
+    pub owner: Pubkey,
+    pub payload_size: u32,
+    pub slot: u64,
+}
+
+/// Emitted by `retrieve_encrypted` so remaining v1 usage can be measured
+#[event]
+pub struct V1DataRetrieved {
+    pub owner: Pubkey,
+    pub payload_size: u32,
+    pub slot: u64,
+}
+
 /// Custom error codes for the Lockbox program
 ///
 /// These provide precise error reporting for various failure conditions.
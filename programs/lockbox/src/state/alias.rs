@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+/// One-time registration fee for a handle alias (anti-squatting)
+///
+/// Paid to the treasury and not refunded on release; the account rent is.
+pub const ALIAS_REGISTRATION_FEE_LAMPORTS: u64 = 10_000_000; // 0.01 SOL
+
+/// Global handle-to-vault mapping ("@alice" -> owner)
+///
+/// Unrelated to entry aliases (`AliasTarget`). Handles are never stored in
+/// the clear: clients normalize the handle (lowercase, no leading `@`) and
+/// hash it with SHA-256, so the PDA doubles as the uniqueness check.
+///
+/// # PDA Derivation
+/// Seeds: ["alias", handle_hash]
+#[account]
+#[derive(InitSpace)]
+pub struct Alias {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// SHA-256 of the normalized handle
+    pub handle_hash: [u8; 32],
+
+    /// Vault owner the handle resolves to
+    pub owner: Pubkey,
+
+    /// When the handle was registered (or last transferred)
+    pub registered_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Alias {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"alias";
+}
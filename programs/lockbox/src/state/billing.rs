@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use super::subscription::SubscriptionTier;
+
+/// Length of a billing period between snapshots (30 days)
+pub const BILLING_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Receipts kept per vault (one year of monthly snapshots)
+pub const MAX_BILLING_RECEIPTS: usize = 12;
+
+/// One closed billing period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct BillingReceipt {
+    /// Snapshot sequence number, starting at 0 per vault
+    pub sequence: u64,
+
+    /// Tier at the end of the period
+    pub tier: SubscriptionTier,
+
+    /// Unix timestamp the period started
+    pub period_start: i64,
+
+    /// Unix timestamp the period was closed
+    pub period_end: i64,
+
+    /// Lamports paid for the subscription during the period
+    pub amount_paid: u64,
+
+    /// Bytes stored when the period was closed
+    pub storage_used: u64,
+}
+
+/// Recent billing receipts for a vault
+///
+/// Optional; when it exists, `emit_billing_snapshot` also records each
+/// receipt here so businesses can read their expense history from account
+/// state instead of replaying logs. The oldest receipt is dropped once
+/// `MAX_BILLING_RECEIPTS` are held.
+///
+/// # PDA Derivation
+/// Seeds: ["billing_receipts", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct BillingReceipts {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox these receipts belong to
+    pub master_lockbox: Pubkey,
+
+    /// Owner's wallet address
+    pub owner: Pubkey,
+
+    /// Receipts, oldest first
+    #[max_len(MAX_BILLING_RECEIPTS)]
+    pub receipts: Vec<BillingReceipt>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl BillingReceipts {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"billing_receipts";
+
+    /// Append a receipt, dropping the oldest when full
+    pub fn record(&mut self, receipt: BillingReceipt) {
+        if self.receipts.len() >= MAX_BILLING_RECEIPTS {
+            self.receipts.remove(0);
+        }
+        self.receipts.push(receipt);
+    }
+}
@@ -25,6 +25,9 @@ pub struct Category {
     /// Number of entries in this category
     pub entry_count: u32,
 
+    /// Encrypted bytes stored under this category
+    pub bytes_used: u64,
+
     /// Creation timestamp
     pub created_at: i64,
 
@@ -63,6 +66,7 @@ impl Category {
             color,
             parent_id,
             entry_count: 0,
+            bytes_used: 0,
             created_at,
             last_modified: created_at,
             flags: 0,
@@ -142,6 +146,9 @@ impl Category {
 #[account]
 #[derive(InitSpace)]
 pub struct CategoryRegistry {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Owner's wallet address
     pub owner: Pubkey,
 
@@ -163,6 +170,9 @@ pub struct CategoryRegistry {
 }
 
 impl CategoryRegistry {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
     /// Seeds for PDA derivation
     pub const SEEDS_PREFIX: &'static [u8] = b"category_registry";
 
@@ -228,4 +238,29 @@ impl CategoryRegistry {
 
         Ok(())
     }
+
+    /// Apply an entry add/remove/resize to a category's usage counters
+    ///
+    /// Entries filed under an unregistered category ID (e.g. uncategorized)
+    /// are not tracked.
+    pub fn record_usage(&mut self, category: u32, entry_delta: i32, byte_delta: i64) {
+        let Some(category) = u8::try_from(category)
+            .ok()
+            .and_then(|id| self.get_category_mut(id))
+        else {
+            return;
+        };
+
+        if entry_delta >= 0 {
+            category.entry_count = category.entry_count.saturating_add(entry_delta as u32);
+        } else {
+            category.entry_count = category.entry_count.saturating_sub(entry_delta.unsigned_abs());
+        }
+
+        if byte_delta >= 0 {
+            category.bytes_used = category.bytes_used.saturating_add(byte_delta as u64);
+        } else {
+            category.bytes_used = category.bytes_used.saturating_sub(byte_delta.unsigned_abs());
+        }
+    }
 }
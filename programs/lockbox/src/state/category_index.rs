@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+/// Most entries a category index holds (keeps the account under 12 KB so it
+/// deserializes comfortably within the 32 KB program heap)
+pub const MAX_CATEGORY_INDEX_ENTRIES: usize = 1000;
+
+/// One indexed entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct CategoryIndexEntry {
+    pub category: u32,
+    pub entry_id: u64,
+}
+
+/// Secondary index from category ID to entry IDs
+///
+/// Kept in `(category, entry_id)` order so one category's entries are a
+/// contiguous run. Entry instructions keep it current when it is passed
+/// (like the category registry); aliases and continuation segments are not
+/// indexed, and trashed entries stay indexed until purged.
+///
+/// # PDA Derivation
+/// Seeds: ["category_index", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct CategoryIndex {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this index belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Indexed entries (sized by `space_for`)
+    #[max_len(0)]
+    pub entries: Vec<CategoryIndexEntry>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl CategoryIndex {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"category_index";
+
+    /// Account size holding `entries` entries
+    pub const fn space_for(entries: usize) -> usize {
+        8 + Self::INIT_SPACE + entries * CategoryIndexEntry::INIT_SPACE
+    }
+
+    fn position(&self, category: u32, entry_id: u64) -> std::result::Result<usize, usize> {
+        self.entries
+            .binary_search_by_key(&(category, entry_id), |e| (e.category, e.entry_id))
+    }
+
+    /// Index `entry_id` under `category` (no-op if already indexed)
+    ///
+    /// # Errors
+    /// * `CategoryIndexFull` - `MAX_CATEGORY_INDEX_ENTRIES` entries are indexed
+    pub fn insert(&mut self, category: u32, entry_id: u64) -> Result<()> {
+        if let Err(at) = self.position(category, entry_id) {
+            require!(
+                self.entries.len() < MAX_CATEGORY_INDEX_ENTRIES,
+                crate::errors::LockboxError::CategoryIndexFull
+            );
+            self.entries.insert(at, CategoryIndexEntry { category, entry_id });
+        }
+        Ok(())
+    }
+
+    /// Remove `entry_id` from `category`; returns whether it was indexed
+    pub fn remove(&mut self, category: u32, entry_id: u64) -> bool {
+        match self.position(category, entry_id) {
+            Ok(at) => {
+                self.entries.remove(at);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// IDs of the entries indexed under `category`, ascending
+    pub fn entries_in(&self, category: u32) -> Vec<u64> {
+        let start = self.entries.partition_point(|e| e.category < category);
+        self.entries[start..]
+            .iter()
+            .take_while(|e| e.category == category)
+            .map(|e| e.entry_id)
+            .collect()
+    }
+}
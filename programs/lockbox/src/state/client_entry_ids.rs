@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+/// Most client-assigned entry IDs a vault can reserve (keeps the account
+/// under 8 KB, like the category index, for the 32 KB program heap)
+pub const MAX_CLIENT_ENTRY_IDS: usize = 1000;
+
+/// Client-assigned entry IDs reserved in a vault
+///
+/// `store_password_entry` only accepts a `client_entry_id` together with
+/// this account and reserves the ID here, so a client ID is unique across
+/// every chunk of the vault rather than just the chunk it is stored in.
+/// IDs stay reserved after the entry is deleted: a stable ID names one
+/// entry for the life of the vault.
+///
+/// # PDA Derivation
+/// Seeds: ["client_entry_ids", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct ClientEntryIds {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox these IDs belong to
+    pub master_lockbox: Pubkey,
+
+    /// Reserved IDs, ascending (sized by `space_for`)
+    #[max_len(0)]
+    pub ids: Vec<u64>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ClientEntryIds {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"client_entry_ids";
+
+    /// Account size holding `ids` IDs
+    pub const fn space_for(ids: usize) -> usize {
+        8 + Self::INIT_SPACE + ids * 8
+    }
+
+    /// Whether `entry_id` is reserved
+    pub fn contains(&self, entry_id: u64) -> bool {
+        self.ids.binary_search(&entry_id).is_ok()
+    }
+
+    /// Reserve `entry_id` for a new entry
+    ///
+    /// # Errors
+    /// * `DuplicateEntryId` - The ID is already reserved in this vault
+    /// * `ClientEntryIdsFull` - `MAX_CLIENT_ENTRY_IDS` IDs are reserved
+    pub fn reserve(&mut self, entry_id: u64) -> Result<()> {
+        let at = self
+            .ids
+            .binary_search(&entry_id)
+            .err()
+            .ok_or(crate::errors::LockboxError::DuplicateEntryId)?;
+        require!(
+            self.ids.len() < MAX_CLIENT_ENTRY_IDS,
+            crate::errors::LockboxError::ClientEntryIdsFull
+        );
+        self.ids.insert(at, entry_id);
+        Ok(())
+    }
+}
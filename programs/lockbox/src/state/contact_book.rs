@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+
+/// Most contacts any tier may keep
+pub const MAX_CONTACTS: usize = 100;
+
+/// A wallet the owner shares with, under a client-encrypted label
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, Debug)]
+pub struct Contact {
+    /// Recipient's wallet (or vault owner) public key
+    pub contact: Pubkey,
+
+    /// Encrypted display label (nonce + ciphertext + tag, max 64 bytes)
+    #[max_len(64)]
+    pub label_encrypted: Vec<u8>,
+
+    /// When the contact was added
+    pub added_at: i64,
+
+    /// Last label change
+    pub last_modified: i64,
+}
+
+impl Contact {
+    /// Maximum encrypted label size
+    pub const MAX_LABEL_SIZE: usize = 64;
+}
+
+/// Per-user address book for sharing and one-time secrets
+///
+/// Recipient keys are public (they must be, to encrypt to them); only the
+/// labels are encrypted. The account grows and shrinks by one `Contact` per
+/// add/remove, so the owner only pays rent for contacts actually stored.
+///
+/// # PDA Derivation
+/// Seeds: ["contact_book", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct ContactBook {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Owner's wallet address
+    pub owner: Pubkey,
+
+    /// Master lockbox this book belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Contacts in insertion order (sized by `space_for`)
+    #[max_len(0)]
+    pub contacts: Vec<Contact>,
+
+    /// Creation timestamp
+    pub created_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ContactBook {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"contact_book";
+
+    /// Account size holding `contacts` contacts
+    pub const fn space_for(contacts: usize) -> usize {
+        8 + Self::INIT_SPACE + contacts * Contact::INIT_SPACE
+    }
+
+    /// Get a contact by public key
+    pub fn get_contact_mut(&mut self, contact: &Pubkey) -> Result<&mut Contact> {
+        self.contacts
+            .iter_mut()
+            .find(|c| &c.contact == contact)
+            .ok_or(crate::errors::LockboxError::ContactBookEntryNotFound.into())
+    }
+
+    /// Add a contact, enforcing the tier limit and uniqueness
+    pub fn add_contact(
+        &mut self,
+        contact: Pubkey,
+        label_encrypted: Vec<u8>,
+        max_contacts: usize,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        require!(
+            label_encrypted.len() <= Contact::MAX_LABEL_SIZE,
+            crate::errors::LockboxError::InvalidDataSize
+        );
+        require!(
+            self.contacts.len() < max_contacts,
+            crate::errors::LockboxError::ContactBookFull
+        );
+        require!(
+            !self.contacts.iter().any(|c| c.contact == contact),
+            crate::errors::LockboxError::ContactBookEntryExists
+        );
+
+        self.contacts.push(Contact {
+            contact,
+            label_encrypted,
+            added_at: current_timestamp,
+            last_modified: current_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Remove a contact by public key
+    pub fn remove_contact(&mut self, contact: &Pubkey) -> Result<()> {
+        let index = self
+            .contacts
+            .iter()
+            .position(|c| &c.contact == contact)
+            .ok_or(crate::errors::LockboxError::ContactBookEntryNotFound)?;
+        self.contacts.remove(index);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+/// Most keys a vault may deny
+pub const MAX_DENIED_KEYS: usize = 32;
+
+/// Keys that may not be made guardians, emergency contacts or share recipients
+///
+/// Maintained by the owner (e.g. for keys reported stolen) and checked by
+/// `add_guardian`, `add_guardian_v2`, `add_emergency_contact`, `add_contact`
+/// and `copy_entry_to_vault`. Denying a key does not remove it from places
+/// it was added before.
+///
+/// # PDA Derivation
+/// Seeds: ["deny_list", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct DenyList {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this list belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Denied keys in insertion order
+    #[max_len(MAX_DENIED_KEYS)]
+    pub keys: Vec<Pubkey>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl DenyList {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"deny_list";
+
+    /// Read the deny-list if it exists
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    }
+
+    /// Whether `key` is denied
+    pub fn is_denied(&self, key: &Pubkey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Deny a key
+    ///
+    /// # Errors
+    /// * `KeyAlreadyDenied` - Key is already on the list
+    /// * `DenyListFull` - `MAX_DENIED_KEYS` keys are already denied
+    pub fn deny(&mut self, key: Pubkey) -> Result<()> {
+        require!(!self.is_denied(&key), crate::errors::LockboxError::KeyAlreadyDenied);
+        require!(self.keys.len() < MAX_DENIED_KEYS, crate::errors::LockboxError::DenyListFull);
+        self.keys.push(key);
+        Ok(())
+    }
+
+    /// Take a key off the list
+    ///
+    /// # Errors
+    /// * `KeyNotDenied` - Key is not on the list
+    pub fn allow(&mut self, key: &Pubkey) -> Result<()> {
+        let position = self
+            .keys
+            .iter()
+            .position(|k| k == key)
+            .ok_or(crate::errors::LockboxError::KeyNotDenied)?;
+        self.keys.remove(position);
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@
 //! - **EmergencyAccess**: Configuration for inactivity monitoring and emergency contacts
 //! - **EmergencyContact**: Individual contact with access level and encrypted emergency key
 //! - **ActivityLog**: Track user activity to detect inactivity
+//! - **EmergencyGrantRecord**: Immutable record of the grants made by one activation
 //!
 //! ## Security Model
 //!
@@ -21,6 +22,7 @@
 //!    - Stage 1: Inactivity detected → countdown starts
 //!    - Owner receives notifications (email/SMS/on-chain events)
 //!    - Grace period (e.g., 7 days) to cancel
+//!    - Active contacts are re-notified at a configurable interval
 //!    - Stage 2: Grace period expires → emergency access granted
 //!
 //! 3. **Emergency Contacts**:
@@ -35,10 +37,18 @@
 //!    - Activity notifications to emergency contacts
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use super::timing::{elapsed_since, has_reached};
 
 /// Maximum number of emergency contacts
+///
+/// Emergency access accounts are allocated for this many contacts; tiers
+/// with a higher limit grow the account as contacts are added.
 pub const MAX_EMERGENCY_CONTACTS: usize = 5;
 
+/// Maximum number of emergency contacts on the Pro tier
+pub const PRO_MAX_EMERGENCY_CONTACTS: usize = 10;
+
 /// Default inactivity period: 90 days in seconds
 pub const DEFAULT_INACTIVITY_PERIOD: i64 = 90 * 24 * 60 * 60;
 
@@ -51,6 +61,9 @@ pub const MAX_INACTIVITY_PERIOD: i64 = 365 * 24 * 60 * 60;
 /// Default grace period: 7 days
 pub const DEFAULT_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
 
+/// Minimum interval between countdown notifications: 1 hour
+pub const MIN_NOTIFICATION_INTERVAL: i64 = 60 * 60;
+
 /// Emergency access configuration account
 ///
 /// Stores the emergency contacts and inactivity monitoring settings.
@@ -66,6 +79,9 @@ pub const DEFAULT_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
 #[account]
 #[derive(InitSpace)]
 pub struct EmergencyAccess {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Owner of this emergency access configuration
     pub owner: Pubkey,
 
@@ -79,22 +95,54 @@ pub struct EmergencyAccess {
     /// Grace period in seconds after countdown starts (e.g., 7 days)
     pub grace_period: i64,
 
+    /// Seconds between contact notifications during the countdown (0 = off)
+    pub notification_interval: i64,
+
+    /// Seconds a pending contact has to accept before the invitation expires
+    pub invitation_ttl: i64,
+
     /// Unix timestamp of last activity (updated on any password operation)
     pub last_activity: i64,
 
     /// Unix timestamp when countdown started (None if not started)
     pub countdown_started: Option<i64>,
 
+    /// Unix timestamp of the last contact notification in this countdown
+    pub last_notified_at: i64,
+
     /// Current status of emergency access
     pub status: EmergencyStatus,
 
     /// Unix timestamp when this config was created
     pub created_at: i64,
 
+    /// Owner's standing consent to export on activation (None = not given)
+    ///
+    /// FullAccess and TransferOwnership contacts are only granted access if
+    /// they are named here.
+    pub export_consent: Option<ExportConsent>,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
+/// Owner-signed standing consent to release vault data on activation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct ExportConsent {
+    /// Contacts the owner consents to release data to
+    #[max_len(PRO_MAX_EMERGENCY_CONTACTS)]
+    pub contacts: Vec<Pubkey>,
+
+    /// SHA-256 of the client-defined export scope (e.g. which categories)
+    pub scope_hash: [u8; 32],
+
+    /// SHA-256 of the conditions document the owner agreed to
+    pub conditions_hash: [u8; 32],
+
+    /// Unix timestamp the owner signed the consent
+    pub signed_at: i64,
+}
+
 /// Emergency contact struct
 ///
 /// Represents a trusted contact who can gain access to the vault
@@ -125,6 +173,74 @@ pub struct EmergencyContact {
 
     /// Contact status
     pub status: EmergencyContactStatus,
+
+    /// Unix timestamp when the invitation was last sent (added or resent)
+    pub invited_at: i64,
+
+    /// Unix timestamp after which a pending invitation lapses
+    pub expires_at: i64,
+}
+
+impl EmergencyContact {
+    /// Check if the contact never accepted and the invitation has lapsed
+    pub fn is_invitation_expired(&self, current_time: i64) -> bool {
+        self.status == EmergencyContactStatus::PendingAcceptance && has_reached(self.expires_at, current_time)
+    }
+}
+
+/// Immutable record of an emergency activation
+///
+/// Written once by `activate_emergency_access` and never modified, so heirs
+/// and executors can later prove which contacts were granted what, and when,
+/// even after the owner edits or closes the emergency access config.
+///
+/// # PDA Derivation
+/// Seeds: ["emergency_grant", emergency_access, countdown_started_le]
+#[account]
+#[derive(InitSpace)]
+pub struct EmergencyGrantRecord {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Owner of the emergency access configuration
+    pub owner: Pubkey,
+
+    /// Emergency access configuration that was activated
+    pub emergency_access: Pubkey,
+
+    /// Unix timestamp when the countdown that led to activation started
+    pub countdown_started: i64,
+
+    /// Unix timestamp of activation
+    pub activated_at: i64,
+
+    /// Grants made at activation
+    #[max_len(MAX_EMERGENCY_CONTACTS)]
+    pub grants: Vec<EmergencyGrant>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// A single grant made on emergency activation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct EmergencyGrant {
+    /// Contact who was granted access
+    pub contact: Pubkey,
+
+    /// Access level granted
+    pub access_level: EmergencyAccessLevel,
+
+    /// SHA-256 of the contact's encrypted emergency key at activation
+    pub key_hash: [u8; 32],
+}
+
+impl EmergencyGrantRecord {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"emergency_grant";
 }
 
 /// Emergency access level enum
@@ -173,23 +289,77 @@ pub enum EmergencyStatus {
 }
 
 impl EmergencyAccess {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 3;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"emergency_access";
+
+    /// Read the emergency access config if it exists
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    }
+
     /// Check if inactivity period is within allowed bounds
     pub fn is_inactivity_period_valid(&self) -> bool {
         self.inactivity_period >= MIN_INACTIVITY_PERIOD
             && self.inactivity_period <= MAX_INACTIVITY_PERIOD
     }
 
+    /// Account size holding `contacts` contacts (never below the default allocation)
+    pub const fn space_for(contacts: usize) -> usize {
+        8 + Self::INIT_SPACE
+            + contacts.saturating_sub(MAX_EMERGENCY_CONTACTS) * EmergencyContact::INIT_SPACE
+    }
+
+    /// Add a contact, enforcing the tier limit, unique keys and field sizes
+    ///
+    /// # Errors
+    /// * `TooManyContacts` - `max_contacts` contacts already exist
+    /// * `ContactAlreadyExists` - Key is already a contact
+    /// * `InvalidNicknameSize` - Name exceeds 64 bytes
+    /// * `InvalidKeySize` - Encrypted key exceeds 128 bytes
+    pub fn add_contact(&mut self, contact: EmergencyContact, max_contacts: usize) -> Result<()> {
+        use crate::errors::LockboxError;
+
+        require!(self.emergency_contacts.len() < max_contacts, LockboxError::TooManyContacts);
+        require!(
+            self.get_contact(&contact.contact_pubkey).is_none(),
+            LockboxError::ContactAlreadyExists
+        );
+        require!(contact.contact_name_encrypted.len() <= 64, LockboxError::InvalidNicknameSize);
+        require!(contact.encrypted_key.len() <= 128, LockboxError::InvalidKeySize);
+
+        self.emergency_contacts.push(contact);
+        Ok(())
+    }
+
+    /// Check a notification interval (0 disables notifications)
+    pub fn is_notification_interval_valid(interval: i64, grace_period: i64) -> bool {
+        interval == 0 || (MIN_NOTIFICATION_INTERVAL..=grace_period).contains(&interval)
+    }
+
+    /// Check if contacts are due another countdown notification
+    pub fn should_notify_contacts(&self, current_time: i64) -> bool {
+        self.status == EmergencyStatus::CountdownStarted
+            && self.notification_interval > 0
+            && elapsed_since(self.last_notified_at, current_time) >= self.notification_interval
+    }
+
     /// Check if enough time has passed to start countdown
     pub fn should_start_countdown(&self, current_time: i64) -> bool {
         self.status == EmergencyStatus::Active
-            && (current_time - self.last_activity) >= self.inactivity_period
+            && elapsed_since(self.last_activity, current_time) >= self.inactivity_period
     }
 
     /// Check if grace period has elapsed and emergency should activate
     pub fn should_activate_emergency(&self, current_time: i64) -> bool {
         if let Some(countdown_start) = self.countdown_started {
             self.status == EmergencyStatus::CountdownStarted
-                && (current_time - countdown_start) >= self.grace_period
+                && elapsed_since(countdown_start, current_time) >= self.grace_period
         } else {
             false
         }
@@ -205,19 +375,54 @@ impl EmergencyAccess {
     /// Start countdown
     pub fn start_countdown(&mut self, current_time: i64) {
         self.countdown_started = Some(current_time);
+        self.last_notified_at = current_time;
         self.status = EmergencyStatus::CountdownStarted;
     }
 
+    /// Whether the owner's export consent covers granting `contact` access
+    ///
+    /// ViewOnly contacts need no consent; FullAccess and TransferOwnership
+    /// contacts must be named in `export_consent`.
+    pub fn is_export_consented(&self, contact: &EmergencyContact) -> bool {
+        contact.access_level == EmergencyAccessLevel::ViewOnly
+            || self
+                .export_consent
+                .as_ref()
+                .is_some_and(|consent| consent.contacts.contains(&contact.contact_pubkey))
+    }
+
+    /// Contacts that would be granted access if the countdown completes
+    pub fn active_contacts(&self) -> impl Iterator<Item = &EmergencyContact> {
+        self.emergency_contacts
+            .iter()
+            .filter(|c| c.status == EmergencyContactStatus::Active)
+    }
+
     /// Activate emergency access
-    pub fn activate_emergency(&mut self, current_time: i64) {
+    ///
+    /// Returns the grants made, one per contact that was active. Contacts
+    /// above ViewOnly without export consent stay active but are not granted.
+    pub fn activate_emergency(&mut self, current_time: i64) -> Vec<EmergencyGrant> {
         self.status = EmergencyStatus::EmergencyActive;
-        // Grant access to all active emergency contacts
-        for contact in &mut self.emergency_contacts {
-            if contact.status == EmergencyContactStatus::Active {
+        let consented: Vec<bool> = self
+            .emergency_contacts
+            .iter()
+            .map(|c| self.is_export_consented(c))
+            .collect();
+        let mut grants = Vec::new();
+        // Grant access to all active, consented emergency contacts
+        for (contact, consented) in self.emergency_contacts.iter_mut().zip(consented) {
+            if contact.status == EmergencyContactStatus::Active && consented {
                 contact.status = EmergencyContactStatus::AccessGranted;
                 contact.access_granted_at = Some(current_time);
+                grants.push(EmergencyGrant {
+                    contact: contact.contact_pubkey,
+                    access_level: contact.access_level,
+                    key_hash: hash(&contact.encrypted_key).to_bytes(),
+                });
             }
         }
+        grants
     }
 
     /// Cancel countdown (owner is back)
@@ -243,9 +448,18 @@ impl EmergencyAccess {
 
     /// Count active emergency contacts
     pub fn active_contact_count(&self) -> usize {
-        self.emergency_contacts
-            .iter()
-            .filter(|c| c.status == EmergencyContactStatus::Active)
-            .count()
+        self.active_contacts().count()
+    }
+
+    /// Remove contacts whose invitations lapsed, returning them
+    ///
+    /// Pending contacts are never granted access, so dropping them does not
+    /// change who is covered if the countdown completes.
+    pub fn expire_pending_contacts(&mut self, current_time: i64) -> Vec<EmergencyContact> {
+        let (expired, kept) = std::mem::take(&mut self.emergency_contacts)
+            .into_iter()
+            .partition(|c| c.is_invitation_expired(current_time));
+        self.emergency_contacts = kept;
+        expired
     }
 }
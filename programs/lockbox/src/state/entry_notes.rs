@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+/// Encrypted annotation attached to a password entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, Debug)]
+pub struct EntryNote {
+    /// Encrypted note (nonce + ciphertext + tag, max 128 bytes)
+    #[max_len(128)]
+    pub encrypted_note: Vec<u8>,
+
+    /// When the note was appended
+    pub created_at: i64,
+}
+
+/// Append-only notes for a single entry
+///
+/// Kept beside the storage chunk so small annotations ("rotated 2024-05")
+/// never rewrite the entry's main ciphertext.
+///
+/// # PDA Derivation
+/// Seeds: ["entry_notes", master_lockbox, entry_id]
+#[account]
+#[derive(InitSpace)]
+pub struct EntryNotes {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this entry belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Owner's wallet address
+    pub owner: Pubkey,
+
+    /// Annotated entry
+    pub entry_id: u64,
+
+    /// Notes in append order
+    #[max_len(16)]
+    pub notes: Vec<EntryNote>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl EntryNotes {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"entry_notes";
+
+    /// Maximum notes per entry
+    pub const MAX_NOTES: usize = 16;
+
+    /// Maximum encrypted note size
+    pub const MAX_NOTE_SIZE: usize = 128;
+
+    /// Append a note, enforcing the per-entry cap
+    pub fn append(&mut self, encrypted_note: Vec<u8>, current_timestamp: i64) -> Result<()> {
+        require!(
+            encrypted_note.len() <= Self::MAX_NOTE_SIZE,
+            crate::errors::LockboxError::InvalidDataSize
+        );
+        require!(
+            self.notes.len() < Self::MAX_NOTES,
+            crate::errors::LockboxError::EntryNotesFull
+        );
+
+        self.notes.push(EntryNote {
+            encrypted_note,
+            created_at: current_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+
+/// Where a copied entry came from
+///
+/// Written by `copy_entry_to_vault` next to the new entry, so the copy keeps
+/// a link back to the source vault and entry after the source changes or is
+/// deleted.
+///
+/// # PDA Derivation
+/// Seeds: ["entry_provenance", master_lockbox, entry_id]
+#[account]
+#[derive(InitSpace)]
+pub struct EntryProvenance {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox holding the copy
+    pub master_lockbox: Pubkey,
+
+    /// Owner of the vault holding the copy
+    pub owner: Pubkey,
+
+    /// ID of the copy in `master_lockbox`
+    pub entry_id: u64,
+
+    /// Master lockbox the entry was copied from
+    pub source_vault: Pubkey,
+
+    /// Chunk the source entry was in at copy time
+    pub source_chunk_index: u16,
+
+    /// ID of the source entry in `source_vault`
+    pub source_entry_id: u64,
+
+    /// Unix timestamp of the copy
+    pub copied_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl EntryProvenance {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"entry_provenance";
+}
+
+/// Most copies tracked per source entry
+pub const MAX_ENTRY_COPIES: usize = 16;
+
+/// A copy of an entry living in another vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct EntryCopy {
+    /// Master lockbox holding the copy
+    pub target_vault: Pubkey,
+
+    /// ID of the copy in `target_vault`
+    pub entry_id: u64,
+
+    /// Unix timestamp of the copy
+    pub copied_at: i64,
+
+    /// Source entry changed after the copy was made
+    pub stale: bool,
+}
+
+/// Back-references from an entry to its copies in other vaults
+///
+/// The counterpart of `EntryProvenance` on the source side. Updating the
+/// source flags its copies stale and deleting it revokes them (closing this
+/// account), each with an `EntryCopiesChangedEvent` so recipients know
+/// their copy is outdated.
+///
+/// # PDA Derivation
+/// Seeds: ["entry_copies", master_lockbox, entry_id]
+#[account]
+#[derive(InitSpace)]
+pub struct EntryCopies {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox holding the source entry
+    pub master_lockbox: Pubkey,
+
+    /// ID of the source entry
+    pub entry_id: u64,
+
+    /// Copies, oldest first
+    #[max_len(MAX_ENTRY_COPIES)]
+    pub copies: Vec<EntryCopy>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl EntryCopies {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"entry_copies";
+
+    /// Read the copy list if it exists
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    }
+
+    /// Track a new copy, dropping the oldest stale copy if the list is full
+    ///
+    /// # Errors
+    /// * `TooManyEntryCopies` - `MAX_ENTRY_COPIES` fresh copies already exist
+    pub fn record(&mut self, copy: EntryCopy) -> Result<()> {
+        if self.copies.len() >= MAX_ENTRY_COPIES {
+            let stale = self
+                .copies
+                .iter()
+                .position(|c| c.stale)
+                .ok_or(crate::errors::LockboxError::TooManyEntryCopies)?;
+            self.copies.remove(stale);
+        }
+        self.copies.push(copy);
+        Ok(())
+    }
+
+    /// Flag every fresh copy stale, returning the copies newly flagged
+    pub fn mark_stale(&mut self) -> Vec<EntryCopy> {
+        let mut flagged = Vec::new();
+        for copy in self.copies.iter_mut().filter(|c| !c.stale) {
+            copy.stale = true;
+            flagged.push(*copy);
+        }
+        flagged
+    }
+}
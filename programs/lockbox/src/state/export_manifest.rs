@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+/// Export manifest format the program accepts commitments for
+///
+/// Bumped whenever the client-side manifest encoding changes, so a
+/// commitment always says how to re-derive its hash.
+pub const EXPORT_MANIFEST_VERSION: u8 = 1;
+
+/// Commitments kept per vault
+pub const MAX_EXPORT_COMMITMENTS: usize = 16;
+
+/// Hash of one export manifest and when it was committed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct ExportCommitment {
+    /// SHA-256 of the encoded manifest
+    pub manifest_hash: [u8; 32],
+
+    /// Manifest format (`EXPORT_MANIFEST_VERSION` at commit time)
+    pub format_version: u8,
+
+    /// Entries listed in the manifest
+    pub entry_count: u32,
+
+    /// Unix timestamp of the commitment
+    pub committed_at: i64,
+}
+
+/// Recent export commitments for a vault
+///
+/// Each `commit_export_manifest` appends the hash of a client-built
+/// manifest (entry count and per-entry type, size and ciphertext hash; no
+/// plaintext), so a user leaving for another password manager can later
+/// prove what they exported and when. The oldest commitment is dropped once
+/// `MAX_EXPORT_COMMITMENTS` are held; the emitted events keep the full
+/// history.
+///
+/// # PDA Derivation
+/// Seeds: ["export_log", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct ExportLog {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox these commitments belong to
+    pub master_lockbox: Pubkey,
+
+    /// Owner's wallet address
+    pub owner: Pubkey,
+
+    /// Commitments, oldest first
+    #[max_len(MAX_EXPORT_COMMITMENTS)]
+    pub commitments: Vec<ExportCommitment>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ExportLog {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"export_log";
+
+    /// Append a commitment, dropping the oldest when full
+    pub fn record(&mut self, commitment: ExportCommitment) {
+        if self.commitments.len() >= MAX_EXPORT_COMMITMENTS {
+            self.commitments.remove(0);
+        }
+        self.commitments.push(commitment);
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+/// Maximum pending items per inbox
+pub const MAX_INBOX_ITEMS: usize = 32;
+
+/// What an inbox item points at
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum InboxItemKind {
+    /// An entry shared with the recipient
+    SharedEntry,
+
+    /// Invitation to guard a recovery config (V1 or V2)
+    GuardianInvitation,
+
+    /// Designation as an emergency contact
+    EmergencyContact,
+}
+
+/// Pointer to something addressed to the inbox owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct InboxItem {
+    pub kind: InboxItemKind,
+
+    /// Wallet that sent the item
+    pub sender: Pubkey,
+
+    /// Account holding the offer (recovery config, emergency access, ...)
+    pub account: Pubkey,
+
+    /// When the item was delivered
+    pub received_at: i64,
+}
+
+/// Per-wallet inbox of incoming shares and requests
+///
+/// Keyed by wallet rather than vault, since guardians and emergency
+/// contacts need not have a lockbox of their own. Senders deliver a pointer
+/// as a side effect of the instruction that creates the offer; the owner
+/// accepts or dismisses it.
+///
+/// # PDA Derivation
+/// Seeds: ["inbox", owner]
+#[account]
+#[derive(InitSpace)]
+pub struct Inbox {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Recipient wallet
+    pub owner: Pubkey,
+
+    /// Pending items, oldest first
+    #[max_len(MAX_INBOX_ITEMS)]
+    pub items: Vec<InboxItem>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Inbox {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"inbox";
+
+    /// Add an item unless the inbox is full or already points at `item.account`
+    ///
+    /// Returns whether the item was added. Delivery never fails the sender's
+    /// instruction; a full inbox just misses the notification.
+    pub fn deliver(&mut self, item: InboxItem) -> bool {
+        let duplicate = self
+            .items
+            .iter()
+            .any(|i| i.kind == item.kind && i.account == item.account);
+        if duplicate || self.items.len() >= MAX_INBOX_ITEMS {
+            return false;
+        }
+        self.items.push(item);
+        true
+    }
+
+    /// Remove and return the item of `kind` pointing at `account`
+    pub fn take(&mut self, kind: InboxItemKind, account: &Pubkey) -> Result<InboxItem> {
+        let index = self
+            .items
+            .iter()
+            .position(|i| i.kind == kind && &i.account == account)
+            .ok_or(crate::errors::LockboxError::InboxItemNotFound)?;
+        Ok(self.items.remove(index))
+    }
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use super::master_lockbox::MasterLockbox;
+use super::subscription::SubscriptionTier;
+use crate::errors::LockboxError;
+
+/// Longest subscription term one invoice can buy (3 years)
+pub const MAX_INVOICE_PERIOD: i64 = 3 * 365 * 24 * 60 * 60;
+
+/// Stage of an invoice
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum InvoiceStatus {
+    /// Issued and awaiting payment
+    Open,
+    /// Settled; the subscription was extended
+    Paid,
+}
+
+/// Subscription invoice issued to an organization's vault
+///
+/// Created by the program admin with `issue_invoice` for procurement
+/// departments that pay against an invoice rather than upgrading from a
+/// wallet. `pay_invoice` transfers `amount` of the invoice currency from
+/// the organization's token account and extends the vault's subscription
+/// by `period`. Paid invoices stay open as the settlement record.
+///
+/// # PDA Derivation
+/// Seeds: ["invoice", master_lockbox, invoice_id (u64 LE)]
+#[account]
+#[derive(InitSpace)]
+pub struct Invoice {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Organization's master lockbox being billed
+    pub master_lockbox: Pubkey,
+
+    /// Admin-chosen number, unique per vault
+    pub invoice_id: u64,
+
+    /// Admin who issued the invoice and paid its rent
+    pub issued_by: Pubkey,
+
+    /// Tier the invoice pays for
+    pub tier: SubscriptionTier,
+
+    /// Amount due, in base units of the invoice receiver's mint
+    pub amount: u64,
+
+    /// Seconds of subscription the invoice buys
+    pub period: i64,
+
+    /// Hash of the off-chain invoice memo (PO number, billing contact, ...)
+    pub memo_hash: [u8; 32],
+
+    /// Current stage
+    pub status: InvoiceStatus,
+
+    /// Unix timestamp the invoice was issued
+    pub issued_at: i64,
+
+    /// Unix timestamp the invoice was paid (0 while open)
+    pub paid_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Invoice {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"invoice";
+
+    /// Check the terms of a new invoice
+    ///
+    /// # Errors
+    /// * `InvalidInvoice` - Free tier, zero amount, or a period outside
+    ///   1 second to `MAX_INVOICE_PERIOD`
+    pub fn check_terms(tier: SubscriptionTier, amount: u64, period: i64) -> Result<()> {
+        require!(
+            tier != SubscriptionTier::Free && amount > 0 && (1..=MAX_INVOICE_PERIOD).contains(&period),
+            LockboxError::InvalidInvoice
+        );
+        Ok(())
+    }
+
+    /// Mark the invoice paid and extend `master_lockbox`'s subscription
+    ///
+    /// An invoice for the vault's current tier renews it by `period`, from
+    /// the current expiry if still paid up. An invoice for a higher tier
+    /// upgrades the vault, and the new term runs `period` from now.
+    ///
+    /// # Errors
+    /// * `InvoiceNotOpen` - Already paid
+    /// * `InvalidTierUpgrade` - The invoice's tier is below the vault's
+    pub fn settle(&mut self, master_lockbox: &mut MasterLockbox, current_timestamp: i64) -> Result<()> {
+        require!(self.status == InvoiceStatus::Open, LockboxError::InvoiceNotOpen);
+
+        if self.tier == master_lockbox.subscription_tier {
+            master_lockbox.renew_subscription(self.period, current_timestamp);
+        } else {
+            master_lockbox.upgrade_subscription(self.tier, current_timestamp)?;
+            master_lockbox.subscription_expires = current_timestamp + self.period;
+        }
+
+        self.status = InvoiceStatus::Paid;
+        self.paid_at = current_timestamp;
+        Ok(())
+    }
+}
@@ -1,17 +1,75 @@
 use anchor_lang::prelude::*;
-use super::subscription::{SubscriptionTier, StorageChunkInfo};
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use super::billing::{BillingReceipt, BILLING_PERIOD};
+use super::storage_chunk::StorageChunk;
+use super::timing::has_reached;
+use super::subscription::{
+    SubscriptionStatus, SubscriptionTier, StorageChunkInfo, CAPACITY_ADDON_SIZE,
+    DEFAULT_DUNNING_WINDOW, LOYALTY_BONUS_BPS_PER_RENEWAL, MAX_CAPACITY_ADDON_PACKS,
+    MAX_LOYALTY_BONUS_BPS,
+};
+
+/// How the vault owner authorizes instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum OwnerKind {
+    /// Ed25519 wallet signing transactions directly
+    Wallet,
+
+    /// PDA of `program_id`, signing via `invoke_signed`
+    ProgramDerived { program_id: Pubkey },
+}
+
+/// Number of recently retrieved entry IDs kept on the master lockbox
+pub const MAX_RECENT_ENTRIES: usize = 8;
+
+/// Default time trashed entries are kept before they can be purged (30 days)
+pub const DEFAULT_TRASH_RETENTION: i64 = 30 * 24 * 60 * 60;
+
+/// Delay between `request_vault_deletion` and `finalize_vault_deletion` (7 days)
+pub const VAULT_DELETION_DELAY: i64 = 7 * 24 * 60 * 60;
+
+/// Longest writer lease a device can hold without renewing (10 minutes)
+pub const MAX_WRITER_LEASE_DURATION: i64 = 10 * 60;
+
+/// Slots in one write burst window (~60 seconds at 400ms slots)
+pub const WRITE_BURST_WINDOW_SLOTS: u64 = 150;
+
+/// Length of one heat map period (7 days)
+pub const HEAT_MAP_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+/// Category buckets in the heat map; categories share a bucket modulo this
+pub const HEAT_MAP_BUCKETS: usize = 16;
+
+/// Exclusive right of one device to restructure the vault's chunks
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct WriterLease {
+    /// Device key holding the lease (client-generated, per device)
+    pub device: Pubkey,
+
+    /// Unix timestamp the lease lapses; any device may take over after it
+    pub expires_at: i64,
+}
+
+/// Largest encrypted settings blob on any tier
+pub const MAX_SETTINGS_SIZE: usize = 512;
 
 /// Master lockbox account - manages user's password vault
 #[account]
 #[derive(InitSpace)]
 pub struct MasterLockbox {
-    /// Owner's wallet address
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Owner's wallet address (or owning program's PDA, see `owner_kind`)
     pub owner: Pubkey,
 
     /// Total number of password entries across all chunks
     pub total_entries: u64,
 
     /// Number of storage chunks allocated
+    ///
+    /// One past the highest registered chunk index, so it stays the next
+    /// index to allocate when a trailing chunk is closed.
     pub storage_chunks_count: u16,
 
     /// Current subscription tier
@@ -46,14 +104,109 @@ pub struct MasterLockbox {
     /// Account creation timestamp
     pub created_at: i64,
 
+    /// Record retrievals in `recent_entries` (off by default for privacy)
+    pub recent_entries_enabled: bool,
+
+    /// Most recently retrieved entry IDs, newest first (0 = empty slot)
+    pub recent_entries: [u64; MAX_RECENT_ENTRIES],
+
+    /// Seconds a trashed entry is kept before `purge_expired_trash` may delete it
+    pub trash_retention: i64,
+
+    /// Whether the owner is a wallet or a program-signed PDA
+    pub owner_kind: OwnerKind,
+
+    /// Sequence number of the next billing snapshot
+    pub billing_sequence: u64,
+
+    /// Start of the current billing period
+    pub billing_period_start: i64,
+
+    /// Lamports paid for the subscription since `billing_period_start`
+    pub billing_period_paid: u64,
+
+    /// Extra bytes from capacity add-on packs, on top of the tier limit
+    pub bonus_capacity: u64,
+
+    /// When the add-on packs lapse (0 = none purchased)
+    pub bonus_capacity_expires: i64,
+
+    /// Renewals made before the subscription lapsed, in a row
+    ///
+    /// Earns loyalty capacity (see `loyalty_bonus_capacity`); reset when the
+    /// subscription lapses or drops to Free.
+    pub consecutive_renewals: u32,
+
+    /// Good standing of the paid subscription (see `process_subscription_expiry`)
+    pub subscription_status: SubscriptionStatus,
+
+    /// Seconds after expiry a past-due vault keeps its tier before the
+    /// automatic downgrade to Free
+    pub dunning_window: i64,
+
+    /// When the owner requested vault deletion (0 = not requested)
+    pub deletion_requested_at: i64,
+
+    /// Device currently fencing structural operations (None = unfenced)
+    pub active_writer: Option<WriterLease>,
+
+    /// Slot the current write burst window opened
+    pub burst_window_start: u64,
+
+    /// Entry bytes written since `burst_window_start`
+    pub burst_bytes: u32,
+
+    /// Owner-lowered burst limit in bytes per window (0 = tier default)
+    pub write_burst_limit: u32,
+
+    /// Start of the heat map period being counted (0 = nothing counted)
+    pub heat_map_period_start: i64,
+
+    /// Reads per category bucket this period (opt-in, see `PRIVACY_HEAT_MAP`)
+    pub heat_map_reads: [u16; HEAT_MAP_BUCKETS],
+
+    /// Whether the vault has a category registry, which entry instructions
+    /// must then be passed so its counts cannot drift
+    pub has_category_registry: bool,
+
+    /// Whether the vault has a category index, which instructions adding or
+    /// removing entries must then be passed so it cannot drift
+    pub has_category_index: bool,
+
+    /// Wallet that created the vault, which the PDA is derived from
+    ///
+    /// Stays fixed when the vault changes hands, so the current owner can
+    /// still address it.
+    pub creator: Pubkey,
+
+    /// Whether the vault is listed for transfer; entry writes, deletes and
+    /// deletion requests are refused so the buyer gets what was listed
+    pub listed_for_transfer: bool,
+
+    /// Bytes held by shared vaults this vault created, counted against the
+    /// tier's capacity alongside (but separately from) `storage_used`
+    pub shared_storage_used: u64,
+
+    /// Client-encrypted preferences synced across devices (empty = unset)
+    ///
+    /// The account is resized to fit, so only the bytes in use cost rent.
+    #[max_len(MAX_SETTINGS_SIZE)]
+    pub encrypted_settings: Vec<u8>,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl MasterLockbox {
-    /// Seeds for PDA derivation
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// Seeds for PDA derivation, followed by `creator`
     pub const SEEDS_PREFIX: &'static [u8] = b"master_lockbox";
 
+    /// High bit marks client-assigned entry IDs; the counter never reaches it
+    pub const CLIENT_ENTRY_ID_BIT: u64 = 1 << 63;
+
     /// Size of a single StorageChunkInfo entry
     /// - chunk_address: 32 bytes (Pubkey)
     /// - chunk_index: 2 bytes (u16)
@@ -62,10 +215,12 @@ impl MasterLockbox {
     /// - data_type: 1 byte (u8 enum)
     /// - created_at: 8 bytes (i64)
     /// - last_modified: 8 bytes (i64)
-    const STORAGE_CHUNK_INFO_SIZE: usize = 32 + 2 + 4 + 4 + 1 + 8 + 8;
+    /// - layout_epoch: 4 bytes (u32)
+    pub const STORAGE_CHUNK_INFO_SIZE: usize = 32 + 2 + 4 + 4 + 1 + 8 + 8 + 4;
 
     /// Base space without any storage chunks
     const BASE_SPACE: usize = 8 + // discriminator
+        1 +  // version
         32 + // owner
         8 +  // total_entries
         2 +  // storage_chunks_count
@@ -79,6 +234,31 @@ impl MasterLockbox {
         8 +  // next_entry_id
         4 +  // categories_count
         8 +  // created_at
+        1 +  // recent_entries_enabled
+        8 * MAX_RECENT_ENTRIES + // recent_entries
+        8 +  // trash_retention
+        33 + // owner_kind (1 tag + 32 program_id)
+        8 +  // billing_sequence
+        8 +  // billing_period_start
+        8 +  // billing_period_paid
+        8 +  // bonus_capacity
+        8 +  // bonus_capacity_expires
+        4 +  // consecutive_renewals
+        1 +  // subscription_status
+        8 +  // dunning_window
+        8 +  // deletion_requested_at
+        41 + // active_writer (1 tag + 32 device + 8 expires_at)
+        8 +  // burst_window_start
+        4 +  // burst_bytes
+        4 +  // write_burst_limit
+        8 +  // heat_map_period_start
+        2 * HEAT_MAP_BUCKETS + // heat_map_reads
+        1 +  // has_category_registry
+        1 +  // has_category_index
+        32 + // creator
+        1 +  // listed_for_transfer
+        8 +  // shared_storage_used
+        4 +  // encrypted_settings vec length (starts at 0)
         1;   // bump
 
     /// Initial space calculation for account creation (0 chunks)
@@ -86,10 +266,18 @@ impl MasterLockbox {
 
     /// Calculate space needed for a specific number of chunks
     /// Used by realloc to dynamically grow the account
-    pub fn calculate_space(num_chunks: usize) -> usize {
+    pub const fn calculate_space(num_chunks: usize) -> usize {
         Self::BASE_SPACE + (num_chunks * Self::STORAGE_CHUNK_INFO_SIZE)
     }
 
+    /// Space for `num_chunks` chunks plus the current settings blob
+    ///
+    /// Reallocs must use this rather than `calculate_space` so they never
+    /// truncate the settings.
+    pub fn space_for_chunks(&self, num_chunks: usize) -> usize {
+        Self::calculate_space(num_chunks) + self.encrypted_settings.len()
+    }
+
     /// Initialize a new master lockbox
     pub fn initialize(
         &mut self,
@@ -110,10 +298,188 @@ impl MasterLockbox {
         self.next_entry_id = 1;
         self.categories_count = 0;
         self.created_at = current_timestamp;
+        self.recent_entries_enabled = false;
+        self.recent_entries = [0; MAX_RECENT_ENTRIES];
+        self.trash_retention = DEFAULT_TRASH_RETENTION;
+        self.owner_kind = OwnerKind::Wallet;
+        self.billing_sequence = 0;
+        self.billing_period_start = current_timestamp;
+        self.billing_period_paid = 0;
+        self.bonus_capacity = 0;
+        self.bonus_capacity_expires = 0;
+        self.consecutive_renewals = 0;
+        self.subscription_status = SubscriptionStatus::Active;
+        self.dunning_window = DEFAULT_DUNNING_WINDOW;
+        self.deletion_requested_at = 0;
+        self.active_writer = None;
+        self.burst_window_start = 0;
+        self.burst_bytes = 0;
+        self.write_burst_limit = 0;
+        self.clear_heat_map();
+        self.has_category_registry = false;
+        self.has_category_index = false;
+        self.creator = owner;
+        self.listed_for_transfer = false;
+        self.shared_storage_used = 0;
+        self.encrypted_settings = Vec::new();
+        self.version = Self::CURRENT_VERSION;
         self.bump = bump;
         Ok(())
     }
 
+    /// Check that `owner` may act for this vault
+    ///
+    /// Wallet owners sign the transaction directly. PDA owners can only sign
+    /// via `invoke_signed`, so their instructions must arrive through CPI.
+    pub fn is_authorized_owner(&self, owner: &AccountInfo) -> bool {
+        if !owner.is_signer || *owner.key != self.owner {
+            return false;
+        }
+
+        match self.owner_kind {
+            OwnerKind::Wallet => true,
+            OwnerKind::ProgramDerived { .. } => {
+                get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT
+            }
+        }
+    }
+
+    /// Check that a security-sensitive instruction may run at this stack height
+    ///
+    /// Ownership transfer, recovery completion and emergency activation for
+    /// wallet vaults must be top-level instructions, so a wrapper program
+    /// cannot slip them in behind an unrelated signing prompt. Program-owned
+    /// vaults are driven through CPI by design and are exempt.
+    pub fn allows_sensitive_invocation(&self) -> bool {
+        match self.owner_kind {
+            OwnerKind::Wallet => get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT,
+            OwnerKind::ProgramDerived { .. } => true,
+        }
+    }
+
+    /// Whether the vault carries a deletion tombstone
+    pub fn is_deletion_requested(&self) -> bool {
+        self.deletion_requested_at != 0
+    }
+
+    /// Whether a requested deletion has waited out `VAULT_DELETION_DELAY`
+    pub fn is_deletion_ready(&self, current_timestamp: i64) -> bool {
+        self.is_deletion_requested()
+            && current_timestamp >= self.deletion_requested_at.saturating_add(VAULT_DELETION_DELAY)
+    }
+
+    /// The writer lease, unless it has lapsed
+    pub fn active_writer_at(&self, current_timestamp: i64) -> Option<&WriterLease> {
+        self.active_writer
+            .as_ref()
+            .filter(|lease| current_timestamp < lease.expires_at)
+    }
+
+    /// Acquire or renew the writer lease for `device`
+    ///
+    /// Returns whether the lease was taken over from another device whose
+    /// lease had lapsed.
+    ///
+    /// # Errors
+    /// * `InvalidWriterLeaseDuration` - Duration is not within
+    ///   `1..=MAX_WRITER_LEASE_DURATION`
+    /// * `WriterLeaseHeld` - Another device holds an unexpired lease
+    pub fn acquire_writer_lease(
+        &mut self,
+        device: Pubkey,
+        duration: i64,
+        current_timestamp: i64,
+    ) -> Result<bool> {
+        require!(
+            (1..=MAX_WRITER_LEASE_DURATION).contains(&duration),
+            crate::errors::LockboxError::InvalidWriterLeaseDuration
+        );
+        self.check_writer(Some(&device), current_timestamp)?;
+
+        let takeover = self.active_writer.is_some_and(|lease| lease.device != device);
+        self.active_writer = Some(WriterLease {
+            device,
+            expires_at: current_timestamp.saturating_add(duration),
+        });
+        Ok(takeover)
+    }
+
+    /// Check that `device` may run a structural operation
+    ///
+    /// Passes when no unexpired lease is held or `device` holds it.
+    pub fn check_writer(&self, device: Option<&Pubkey>, current_timestamp: i64) -> Result<()> {
+        if let Some(lease) = self.active_writer_at(current_timestamp) {
+            require!(
+                device == Some(&lease.device),
+                crate::errors::LockboxError::WriterLeaseHeld
+            );
+        }
+        Ok(())
+    }
+
+    /// Burst limit in effect: the owner's limit, capped by the tier's
+    pub fn effective_write_burst_limit(&self) -> u32 {
+        let tier_limit = self.subscription_tier.max_write_burst();
+        match self.write_burst_limit {
+            0 => tier_limit,
+            limit => limit.min(tier_limit),
+        }
+    }
+
+    /// Count `bytes` against the write burst window containing `slot`
+    ///
+    /// Independent of storage capacity: a vault with room to spare still
+    /// can't push more than its burst limit through RPC nodes and indexers
+    /// in one window. The window restarts at the first write after it ends.
+    ///
+    /// # Errors
+    /// * `WriteBurstLimited` - The window's byte budget would be exceeded
+    pub fn record_write_burst(&mut self, bytes: usize, slot: u64) -> Result<()> {
+        if slot >= self.burst_window_start.saturating_add(WRITE_BURST_WINDOW_SLOTS) {
+            self.burst_window_start = slot;
+            self.burst_bytes = 0;
+        }
+
+        let total = (self.burst_bytes as u64).saturating_add(bytes as u64);
+        require!(
+            total <= self.effective_write_burst_limit() as u64,
+            crate::errors::LockboxError::WriteBurstLimited
+        );
+        self.burst_bytes = total as u32;
+        Ok(())
+    }
+
+    /// Count a read of an entry in `category` toward the heat map
+    ///
+    /// Only the period and bucket are kept, never the read's timestamp.
+    /// Returns the previous period's start and counts when this read opens
+    /// a new period, so they can be emitted.
+    pub fn record_heat_map_read(
+        &mut self,
+        category: u32,
+        current_timestamp: i64,
+    ) -> Option<(i64, [u16; HEAT_MAP_BUCKETS])> {
+        let period_start = current_timestamp - current_timestamp.rem_euclid(HEAT_MAP_PERIOD);
+        let mut finished = None;
+        if period_start != self.heat_map_period_start {
+            if self.heat_map_reads.iter().any(|&reads| reads > 0) {
+                finished = Some((self.heat_map_period_start, self.heat_map_reads));
+            }
+            self.heat_map_period_start = period_start;
+            self.heat_map_reads = [0; HEAT_MAP_BUCKETS];
+        }
+
+        let bucket = category as usize % HEAT_MAP_BUCKETS;
+        self.heat_map_reads[bucket] = self.heat_map_reads[bucket].saturating_add(1);
+        finished
+    }
+
+    /// Drop the heat map counts (on opting out)
+    pub fn clear_heat_map(&mut self) {
+        self.heat_map_period_start = 0;
+        self.heat_map_reads = [0; HEAT_MAP_BUCKETS];
+    }
+
     /// Register a new storage chunk
     pub fn add_chunk(&mut self, chunk_info: StorageChunkInfo) -> Result<()> {
         require!(
@@ -122,12 +488,47 @@ impl MasterLockbox {
         );
 
         self.storage_chunks.push(chunk_info);
-        self.storage_chunks_count += 1;
+        self.storage_chunks_count = self.storage_chunks_count.max(chunk_info.chunk_index + 1);
         self.total_capacity += chunk_info.max_capacity as u64;
 
         Ok(())
     }
 
+    /// Whether a new chunk may be created at `chunk_index`
+    ///
+    /// Indexes below `storage_chunks_count` whose chunk was closed form the
+    /// free list; otherwise only `storage_chunks_count` itself is allowed.
+    pub fn is_chunk_index_available(&self, chunk_index: u16) -> bool {
+        chunk_index <= self.storage_chunks_count
+            && !self.storage_chunks.iter().any(|c| c.chunk_index == chunk_index)
+    }
+
+    /// Lowest index a new chunk can be created at
+    pub fn next_chunk_index(&self) -> u16 {
+        (0..self.storage_chunks_count)
+            .find(|index| self.is_chunk_index_available(*index))
+            .unwrap_or(self.storage_chunks_count)
+    }
+
+    /// Unregister a storage chunk, releasing its bytes and capacity
+    pub fn remove_chunk(&mut self, chunk_index: u16) -> Result<StorageChunkInfo> {
+        let position = self.storage_chunks
+            .iter()
+            .position(|c| c.chunk_index == chunk_index)
+            .ok_or(crate::errors::LockboxError::ChunkNotFound)?;
+
+        let chunk_info = self.storage_chunks.remove(position);
+        self.storage_used = self.storage_used.saturating_sub(chunk_info.size_used as u64);
+        self.total_capacity = self.total_capacity.saturating_sub(chunk_info.max_capacity as u64);
+        self.storage_chunks_count = self.storage_chunks
+            .iter()
+            .map(|c| c.chunk_index + 1)
+            .max()
+            .unwrap_or(0);
+
+        Ok(chunk_info)
+    }
+
     /// Update chunk usage
     pub fn update_chunk_usage(&mut self, chunk_index: u16, new_size: u32) -> Result<()> {
         let chunk = self.storage_chunks
@@ -148,10 +549,100 @@ impl MasterLockbox {
         Ok(())
     }
 
+    /// Record a structural change to `chunk` in both the chunk and its info
+    pub fn bump_chunk_layout(&mut self, chunk: &mut StorageChunk) -> Result<()> {
+        let chunk_info = self.storage_chunks
+            .iter_mut()
+            .find(|c| c.chunk_index == chunk.chunk_index)
+            .ok_or(crate::errors::LockboxError::ChunkNotFound)?;
+
+        chunk.layout_epoch = chunk.layout_epoch.wrapping_add(1);
+        chunk_info.layout_epoch = chunk.layout_epoch;
+        Ok(())
+    }
+
+    /// Check that `chunk` and its registered info agree on the layout epoch
+    ///
+    /// Entry operations call this before touching offsets, so an operation
+    /// built against a layout that has since been restructured fails instead
+    /// of writing through stale offsets.
+    pub fn check_chunk_layout(&self, chunk: &StorageChunk) -> Result<()> {
+        let chunk_info = self.storage_chunks
+            .iter()
+            .find(|c| c.chunk_index == chunk.chunk_index)
+            .ok_or(crate::errors::LockboxError::ChunkNotFound)?;
+
+        require!(
+            chunk_info.layout_epoch == chunk.layout_epoch,
+            crate::errors::LockboxError::StaleChunkLayout
+        );
+        Ok(())
+    }
+
+    /// Whether `storage_used` matches the chunks' recorded usage
+    pub fn is_storage_used_consistent(&self) -> bool {
+        let chunk_total: u64 = self.storage_chunks.iter().map(|c| c.size_used as u64).sum();
+        self.storage_used == chunk_total
+    }
+
+    /// Add-on capacity that has not lapsed
+    pub fn active_bonus_capacity(&self, current_timestamp: i64) -> u64 {
+        if !has_reached(self.bonus_capacity_expires, current_timestamp) {
+            self.bonus_capacity
+        } else {
+            0
+        }
+    }
+
+    /// Capacity earned by consecutive renewals, capped at a share of the tier
+    pub fn loyalty_bonus_capacity(&self) -> u64 {
+        let bps = (self.consecutive_renewals as u64 * LOYALTY_BONUS_BPS_PER_RENEWAL)
+            .min(MAX_LOYALTY_BONUS_BPS);
+        self.subscription_tier.max_capacity() * bps / 10_000
+    }
+
+    /// Storage limit: the tier's capacity plus loyalty and active add-on capacity
+    pub fn capacity_limit(&self, current_timestamp: i64) -> u64 {
+        self.subscription_tier.max_capacity()
+            + self.loyalty_bonus_capacity()
+            + self.active_bonus_capacity(current_timestamp)
+    }
+
+    /// Bytes counted against the capacity limit: the vault's own entries
+    /// plus its shared vaults'
+    pub fn total_storage_used(&self) -> u64 {
+        self.storage_used.saturating_add(self.shared_storage_used)
+    }
+
     /// Check if there's enough capacity for new data
-    pub fn has_capacity(&self, additional_bytes: u64) -> bool {
-        let max_capacity = self.subscription_tier.max_capacity();
-        self.storage_used + additional_bytes <= max_capacity
+    pub fn has_capacity(&self, additional_bytes: u64, current_timestamp: i64) -> bool {
+        self.total_storage_used() + additional_bytes <= self.capacity_limit(current_timestamp)
+    }
+
+    /// Hold `packs` capacity add-on packs for the next billing month
+    ///
+    /// Buying the same number of packs while they are active extends the
+    /// current term; any other count starts a new term now.
+    pub fn purchase_capacity_addon(
+        &mut self,
+        packs: u8,
+        duration: i64,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        require!(
+            packs > 0 && packs <= MAX_CAPACITY_ADDON_PACKS,
+            crate::errors::LockboxError::InvalidCapacityAddon
+        );
+
+        let bonus_capacity = packs as u64 * CAPACITY_ADDON_SIZE;
+        if self.active_bonus_capacity(current_timestamp) == bonus_capacity {
+            self.bonus_capacity_expires += duration;
+        } else {
+            self.bonus_capacity = bonus_capacity;
+            self.bonus_capacity_expires = current_timestamp + duration;
+        }
+
+        Ok(())
     }
 
     /// Upgrade subscription tier
@@ -165,7 +656,14 @@ impl MasterLockbox {
             crate::errors::LockboxError::InvalidTierUpgrade
         );
 
+        // Billing periods and renewal streaks run from the start of the
+        // paid subscription
+        if self.subscription_tier == SubscriptionTier::Free {
+            self.billing_period_start = current_timestamp;
+            self.consecutive_renewals = 0;
+        }
         self.subscription_tier = new_tier;
+        self.subscription_status = SubscriptionStatus::Active;
 
         // Set expiration for paid tiers
         if new_tier != SubscriptionTier::Free {
@@ -175,12 +673,125 @@ impl MasterLockbox {
         Ok(())
     }
 
+    /// Extend a paid subscription by `duration`, tracking the renewal streak
+    ///
+    /// Renewing before expiry extends from the current expiry and continues
+    /// the streak; renewing a lapsed subscription starts both over from now.
+    pub fn renew_subscription(&mut self, duration: i64, current_timestamp: i64) {
+        if has_reached(self.subscription_expires, current_timestamp) {
+            self.subscription_expires = current_timestamp + duration;
+            self.consecutive_renewals = 1;
+        } else {
+            self.subscription_expires += duration;
+            self.consecutive_renewals = self.consecutive_renewals.saturating_add(1);
+        }
+        self.subscription_status = SubscriptionStatus::Active;
+    }
+
+    /// Drop to the Free tier, ending any paid term and renewal streak
+    pub fn downgrade_to_free(&mut self) {
+        self.subscription_tier = SubscriptionTier::Free;
+        self.subscription_expires = 0;
+        self.consecutive_renewals = 0;
+        self.subscription_status = SubscriptionStatus::Active;
+    }
+
+    /// Whether an active paid subscription has expired without renewal
+    pub fn is_past_due_pending(&self, current_timestamp: i64) -> bool {
+        self.subscription_tier != SubscriptionTier::Free
+            && self.subscription_status == SubscriptionStatus::Active
+            && has_reached(self.subscription_expires, current_timestamp)
+    }
+
+    /// When a past-due vault is downgraded to Free
+    pub fn dunning_deadline(&self) -> i64 {
+        self.subscription_expires.saturating_add(self.dunning_window)
+    }
+
+    /// Whether a past-due vault's dunning window has ended
+    pub fn is_dunning_over(&self, current_timestamp: i64) -> bool {
+        self.subscription_status == SubscriptionStatus::PastDue
+            && has_reached(self.dunning_deadline(), current_timestamp)
+    }
+
+    /// Count a subscription payment towards the current billing period
+    pub fn record_payment(&mut self, amount: u64) {
+        self.billing_period_paid = self.billing_period_paid.saturating_add(amount);
+    }
+
+    /// Net a refund out of the current billing period's payments
+    pub fn record_refund(&mut self, amount: u64) {
+        self.billing_period_paid = self.billing_period_paid.saturating_sub(amount);
+    }
+
+    /// Whether a paid vault's billing period has ended
+    pub fn is_billing_snapshot_due(&self, current_timestamp: i64) -> bool {
+        self.subscription_tier != SubscriptionTier::Free
+            && current_timestamp >= self.billing_period_start.saturating_add(BILLING_PERIOD)
+    }
+
+    /// Close the current billing period and start the next one at `current_timestamp`
+    pub fn close_billing_period(&mut self, current_timestamp: i64) -> BillingReceipt {
+        let receipt = BillingReceipt {
+            sequence: self.billing_sequence,
+            tier: self.subscription_tier,
+            period_start: self.billing_period_start,
+            period_end: current_timestamp,
+            amount_paid: self.billing_period_paid,
+            storage_used: self.storage_used,
+        };
+
+        self.billing_sequence += 1;
+        self.billing_period_start = current_timestamp;
+        self.billing_period_paid = 0;
+
+        receipt
+    }
+
     /// Check if subscription is active
     pub fn is_subscription_active(&self, current_timestamp: i64) -> bool {
         if self.subscription_tier == SubscriptionTier::Free {
             return true;
         }
-        current_timestamp < self.subscription_expires
+        !has_reached(self.subscription_expires, current_timestamp)
+    }
+
+    /// Tier whose limits apply now (a lapsed paid subscription counts as Free)
+    pub fn effective_tier(&self, current_timestamp: i64) -> SubscriptionTier {
+        if self.is_subscription_active(current_timestamp) {
+            self.subscription_tier
+        } else {
+            SubscriptionTier::Free
+        }
+    }
+
+    /// Check an entry's ciphertext size against the tier limit
+    ///
+    /// A lapsed paid subscription falls back to the Free limit.
+    pub fn check_entry_size(&self, size: usize, current_timestamp: i64) -> Result<()> {
+        let limit = self.effective_tier(current_timestamp).max_entry_size();
+        crate::errors::require_with_context(
+            size <= limit,
+            crate::errors::LockboxError::EntryTooLarge,
+            crate::errors::ErrorField::EntrySize,
+            limit as i64,
+            size as i64,
+        )
+    }
+
+    /// Check a settings blob size against the tier limit
+    ///
+    /// A lapsed paid subscription falls back to the Free limit. Clearing
+    /// (size 0) is always allowed.
+    pub fn check_settings_size(&self, size: usize, current_timestamp: i64) -> Result<()> {
+        let limit = self.effective_tier(current_timestamp).max_settings_size();
+        crate::errors::require_with_context(
+            size <= limit,
+            crate::errors::LockboxError::SettingsTooLarge,
+            crate::errors::ErrorField::SettingsSize,
+            limit as i64,
+            size as i64,
+        )
     }
 
     /// Get next entry ID and increment
@@ -190,6 +801,11 @@ impl MasterLockbox {
         id
     }
 
+    /// Check whether an entry ID lies in the client-assigned range
+    pub fn is_client_entry_id(entry_id: u64) -> bool {
+        entry_id & Self::CLIENT_ENTRY_ID_BIT != 0
+    }
+
     /// Increment total entries
     pub fn increment_entries(&mut self) {
         self.total_entries += 1;
@@ -202,6 +818,30 @@ impl MasterLockbox {
         }
     }
 
+    /// Move `entry_id` to the front of the recently-used list
+    ///
+    /// No-op unless the owner opted in via `recent_entries_enabled`.
+    pub fn record_recent_entry(&mut self, entry_id: u64) {
+        if !self.recent_entries_enabled {
+            return;
+        }
+
+        let end = self.recent_entries
+            .iter()
+            .position(|id| *id == entry_id)
+            .unwrap_or(MAX_RECENT_ENTRIES - 1);
+        self.recent_entries[..=end].rotate_right(1);
+        self.recent_entries[0] = entry_id;
+    }
+
+    /// Drop `entry_id` from the recently-used list (e.g. after deletion)
+    pub fn forget_recent_entry(&mut self, entry_id: u64) {
+        if let Some(pos) = self.recent_entries.iter().position(|id| *id == entry_id) {
+            self.recent_entries[pos..].rotate_left(1);
+            self.recent_entries[MAX_RECENT_ENTRIES - 1] = 0;
+        }
+    }
+
     /// Update last accessed timestamp
     pub fn touch(&mut self, timestamp: i64) {
         self.last_accessed = timestamp;
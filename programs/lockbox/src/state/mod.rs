@@ -5,6 +5,25 @@ pub mod category;
 pub mod recovery;
 pub mod recovery_v2;
 pub mod emergency_access;
+pub mod entry_notes;
+pub mod security_policy;
+pub mod alias;
+pub mod contact_book;
+pub mod inbox;
+pub mod entry_provenance;
+pub mod billing;
+pub mod vault_transfer;
+pub mod export_manifest;
+pub mod deny_list;
+pub mod tag_registry;
+pub mod search_filter;
+pub mod category_index;
+pub mod timing;
+pub mod shared_vault;
+pub mod program_config;
+pub mod invoice;
+pub mod refund;
+pub mod client_entry_ids;
 
 pub use master_lockbox::*;
 pub use storage_chunk::*;
@@ -13,3 +32,22 @@ pub use category::*;
 pub use recovery::*;
 pub use recovery_v2::*;
 pub use emergency_access::*;
+pub use entry_notes::*;
+pub use security_policy::*;
+pub use alias::*;
+pub use contact_book::*;
+pub use inbox::*;
+pub use entry_provenance::*;
+pub use billing::*;
+pub use vault_transfer::*;
+pub use export_manifest::*;
+pub use deny_list::*;
+pub use tag_registry::*;
+pub use search_filter::*;
+pub use category_index::*;
+pub use timing::*;
+pub use shared_vault::*;
+pub use program_config::*;
+pub use invoice::*;
+pub use refund::*;
+pub use client_entry_ids::*;
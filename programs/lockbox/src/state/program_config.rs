@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+/// Program-wide settings held by the program admin
+///
+/// Created once by the program's upgrade authority with
+/// `initialize_program_config`; afterwards only `admin` can change it. The
+/// admin issues organization invoices and treasury refunds.
+///
+/// # PDA Derivation
+/// Seeds: ["program_config"]
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Wallet allowed to issue invoices and refunds and change this config
+    pub admin: Pubkey,
+
+    /// SPL token account invoice payments are sent to; its mint is the
+    /// invoice currency
+    pub invoice_receiver: Pubkey,
+
+    /// Refund records issued so far; numbers the next one
+    pub refund_count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"program_config";
+}
@@ -8,6 +8,7 @@
 //!
 //! - **RecoveryConfig**: Master configuration for a user's social recovery setup
 //! - **Guardian**: Individual guardian with encrypted secret share
+//! - **GuardianAgreement**: Hash of the off-chain terms a guardian accepted
 //! - **RecoveryRequest**: Active recovery attempt with time-lock protection
 //! - **RecoveryApproval**: Guardian approval of recovery request with share submission
 //!
@@ -35,10 +36,18 @@
 //!    - Immutable recovery history
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use super::timing::{elapsed_since, has_reached};
 
 /// Maximum number of guardians allowed (prevents excessive account size)
+///
+/// Recovery configs are allocated for this many guardians; tiers with a
+/// higher limit grow the account as guardians are added.
 pub const MAX_GUARDIANS: usize = 10;
 
+/// Maximum number of guardians on the Pro tier
+pub const PRO_MAX_GUARDIANS: usize = 20;
+
 /// Maximum number of recovery approvals stored
 pub const MAX_RECOVERY_APPROVALS: usize = 10;
 
@@ -51,6 +60,21 @@ pub const MIN_RECOVERY_DELAY: i64 = 24 * 60 * 60;
 /// Maximum recovery delay: 30 days in seconds
 pub const MAX_RECOVERY_DELAY: i64 = 30 * 24 * 60 * 60;
 
+/// Default time a guardian has to accept an invitation: 14 days
+pub const DEFAULT_INVITATION_TTL: i64 = 14 * 24 * 60 * 60;
+
+/// Minimum invitation lifetime: 1 day
+pub const MIN_INVITATION_TTL: i64 = 24 * 60 * 60;
+
+/// Maximum invitation lifetime: 90 days
+pub const MAX_INVITATION_TTL: i64 = 90 * 24 * 60 * 60;
+
+/// Minimum owner activity window when enabled: 1 day
+pub const MIN_OWNER_ACTIVITY_WINDOW: i64 = 24 * 60 * 60;
+
+/// Maximum owner activity window: 365 days
+pub const MAX_OWNER_ACTIVITY_WINDOW: i64 = 365 * 24 * 60 * 60;
+
 /// Recovery configuration account
 ///
 /// Stores the guardian network and recovery settings for a user.
@@ -66,6 +90,9 @@ pub const MAX_RECOVERY_DELAY: i64 = 30 * 24 * 60 * 60;
 #[account]
 #[derive(InitSpace)]
 pub struct RecoveryConfig {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Owner of this recovery configuration (wallet that set it up)
     pub owner: Pubkey,
 
@@ -82,6 +109,16 @@ pub struct RecoveryConfig {
     /// Mandatory delay in seconds before recovery can complete
     pub recovery_delay: i64,
 
+    /// Seconds a pending guardian has to accept before the invitation expires
+    pub invitation_ttl: i64,
+
+    /// Owner has paused recovery; no request can start or complete
+    pub recovery_disabled: bool,
+
+    /// Recovery cannot start while the owner's vault was accessed within
+    /// this many seconds (0 = no activity check)
+    pub owner_activity_window: i64,
+
     /// Unix timestamp when this config was created
     pub created_at: i64,
 
@@ -130,6 +167,49 @@ pub struct Guardian {
 
     /// Guardian status
     pub status: GuardianStatus,
+
+    /// Unix timestamp when the invitation was last sent (added or resent)
+    pub invited_at: i64,
+
+    /// Unix timestamp after which a pending invitation lapses
+    pub expires_at: i64,
+
+    /// Unix timestamp when the guardian accepted (None until accepted)
+    pub accepted_at: Option<i64>,
+
+    /// Commitment to what the guardian consented to, from `acceptance_hash`
+    /// (zeroed until accepted)
+    pub acceptance_hash: [u8; 32],
+}
+
+impl Guardian {
+    /// Check if the guardian never accepted and the invitation has lapsed
+    pub fn is_invitation_expired(&self, current_time: i64) -> bool {
+        self.status == GuardianStatus::PendingAcceptance && has_reached(self.expires_at, current_time)
+    }
+
+    /// Hash binding an acceptance to the config, share and terms
+    ///
+    /// SHA-256 over the recovery config address, guardian pubkey, share
+    /// index, encrypted share, agreement hash (zeroed when the owner set no
+    /// terms) and acceptance time. Auditors recompute it to confirm what a
+    /// guardian consented to and when.
+    pub fn acceptance_hash(
+        &self,
+        recovery_config: &Pubkey,
+        agreement_hash: &[u8; 32],
+        accepted_at: i64,
+    ) -> [u8; 32] {
+        hashv(&[
+            recovery_config.as_ref(),
+            self.guardian_pubkey.as_ref(),
+            &[self.share_index],
+            &self.encrypted_share,
+            agreement_hash,
+            &accepted_at.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
 }
 
 /// Guardian status enum
@@ -145,6 +225,60 @@ pub enum GuardianStatus {
     Revoked,
 }
 
+/// Hash of an off-chain guardian agreement
+///
+/// The owner records the hash of the agreed terms (responsibilities,
+/// contact details) before the guardian accepts; `accept_guardianship` then
+/// requires the guardian to co-sign the same hash. `accept_guardianship`
+/// always receives this PDA's address, so a guardian cannot skip the terms
+/// by omitting the account.
+///
+/// # PDA Derivation
+/// Seeds: ["guardian_agreement", recovery_config, guardian_pubkey]
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianAgreement {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Recovery configuration the guardian belongs to
+    pub recovery_config: Pubkey,
+
+    /// Owner of the recovery configuration
+    pub owner: Pubkey,
+
+    /// Guardian bound by the agreement
+    pub guardian: Pubkey,
+
+    /// SHA-256 of the off-chain agreement document
+    pub agreement_hash: [u8; 32],
+
+    /// Unix timestamp when the owner recorded the hash
+    pub set_at: i64,
+
+    /// Unix timestamp when the guardian co-signed (None until accepted)
+    pub accepted_at: Option<i64>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl GuardianAgreement {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"guardian_agreement";
+
+    /// Read the agreement from its PDA, or `None` if the owner never set one
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    }
+}
+
 /// Active recovery request account
 ///
 /// Created when a guardian initiates recovery. Contains time-lock logic
@@ -161,6 +295,9 @@ pub enum GuardianStatus {
 #[account]
 #[derive(InitSpace)]
 pub struct RecoveryRequest {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Original owner whose vault is being recovered
     pub owner: Pubkey,
 
@@ -237,6 +374,17 @@ pub enum RecoveryStatus {
 }
 
 impl RecoveryConfig {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"recovery_config";
+
+    /// Account size holding `guardians` guardians (never below the default allocation)
+    pub const fn space_for(guardians: usize) -> usize {
+        8 + Self::INIT_SPACE + guardians.saturating_sub(MAX_GUARDIANS) * Guardian::INIT_SPACE
+    }
+
     /// Validate recovery configuration parameters
     pub fn validate_threshold(&self) -> bool {
         self.threshold > 0
@@ -257,6 +405,25 @@ impl RecoveryConfig {
             .count()
     }
 
+    /// Check if enough guardians have accepted to meet the threshold
+    ///
+    /// An unarmed config cannot recover the vault, however many guardians
+    /// it lists, so `initiate_recovery` refuses to start.
+    pub fn is_armed(&self) -> bool {
+        self.threshold > 0 && self.active_guardian_count() >= self.threshold as usize
+    }
+
+    /// Check whether the owner used the vault within the activity window
+    ///
+    /// `last_accessed` is the master lockbox's `last_accessed`. An owner who
+    /// is still using the vault does not need recovery, so a request started
+    /// in that window is most likely social engineering.
+    pub fn is_owner_recently_active(&self, last_accessed: i64, current_timestamp: i64) -> bool {
+        self.owner_activity_window > 0
+            && last_accessed > 0
+            && elapsed_since(last_accessed, current_timestamp) < self.owner_activity_window
+    }
+
     /// Get guardian by pubkey
     pub fn get_guardian(&self, pubkey: &Pubkey) -> Option<&Guardian> {
         self.guardians.iter().find(|g| &g.guardian_pubkey == pubkey)
@@ -268,12 +435,31 @@ impl RecoveryConfig {
             .iter()
             .any(|g| &g.guardian_pubkey == pubkey && g.status == GuardianStatus::Active)
     }
+
+    /// Remove guardians whose invitations lapsed, returning them
+    ///
+    /// Pending guardians hold no share anyone has accepted, so dropping them
+    /// never reduces the set of guardians able to take part in recovery.
+    pub fn expire_invitations(&mut self, current_time: i64) -> Vec<Guardian> {
+        let (expired, kept) = std::mem::take(&mut self.guardians)
+            .into_iter()
+            .partition(|g| g.is_invitation_expired(current_time));
+        self.guardians = kept;
+        self.total_guardians = self.guardians.len() as u8;
+        expired
+    }
 }
 
 impl RecoveryRequest {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"recovery_request";
+
     /// Check if recovery delay has elapsed
     pub fn is_ready(&self, current_time: i64) -> bool {
-        current_time >= self.ready_at && self.status == RecoveryStatus::Pending
+        has_reached(self.ready_at, current_time) && self.status == RecoveryStatus::Pending
     }
 
     /// Check if enough guardians have approved
@@ -286,9 +472,22 @@ impl RecoveryRequest {
         self.approvals.iter().any(|a| &a.guardian == guardian)
     }
 
+    /// Check if `caller` may complete the request (requester or new owner)
+    pub fn can_complete(&self, caller: &Pubkey) -> bool {
+        &self.requester == caller || self.new_owner.as_ref() == Some(caller)
+    }
+
+    /// Check if the request can still progress (not finished and not expired)
+    pub fn is_in_progress(&self, current_time: i64) -> bool {
+        matches!(
+            self.status,
+            RecoveryStatus::Pending | RecoveryStatus::ReadyForReconstruction
+        ) && !has_reached(self.expires_at, current_time)
+    }
+
     /// Check if request has expired
     pub fn is_expired(&self, current_time: i64, expiry_period: i64) -> bool {
         self.status == RecoveryStatus::ReadyForReconstruction
-            && current_time > self.ready_at + expiry_period
+            && has_reached(self.ready_at.saturating_add(expiry_period), current_time)
     }
 }
@@ -26,6 +26,7 @@
 //! - Simple cryptographic primitives (no zkSNARKs needed)
 
 use anchor_lang::prelude::*;
+use super::timing::{elapsed_since, has_reached, is_within};
 
 /// Recovery challenge generated during recovery initiation
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -82,6 +83,9 @@ pub struct GuardianV2 {
 #[account]
 #[derive(InitSpace)]
 pub struct RecoveryRequestV2 {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Original owner whose vault is being recovered
     pub owner: Pubkey,
 
@@ -119,6 +123,12 @@ pub struct RecoveryRequestV2 {
 }
 
 impl RecoveryRequestV2 {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"recovery_request_v2";
+
     /// Check if enough guardians have confirmed participation
     pub fn has_sufficient_participants(&self, threshold: u8) -> bool {
         self.participating_guardians.len() >= threshold as usize
@@ -129,18 +139,62 @@ impl RecoveryRequestV2 {
         self.participating_guardians.iter().any(|g| g == guardian)
     }
 
+    /// Check if the request can still progress (not finished and not expired)
+    pub fn is_in_progress(&self, current_time: i64) -> bool {
+        matches!(
+            self.status,
+            crate::state::RecoveryStatus::Pending | crate::state::RecoveryStatus::ReadyForReconstruction
+        ) && !has_reached(self.expires_at, current_time)
+    }
+
     /// Check if request is ready for proof submission
     pub fn is_ready_for_proof(&self, current_time: i64) -> bool {
-        current_time >= self.ready_at
-            && current_time <= self.expires_at
+        is_within(self.ready_at, self.expires_at, current_time)
             && self.status == crate::state::RecoveryStatus::ReadyForReconstruction
     }
 }
 
+/// Default minimum gap between recovery initiations (one hour, in seconds)
+pub const DEFAULT_RECOVERY_COOLDOWN: u64 = 3600;
+
+/// Clock a recovery cooldown is measured on
+///
+/// `unix_timestamp` is voted by validators and may be nudged within the
+/// cluster's drift tolerance; slots and epochs only ever advance with the
+/// ledger, so they cannot be gamed to shorten the cooldown.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum CooldownMode {
+    /// Seconds of cluster time
+    Timestamp,
+    /// Slots (about 400ms each)
+    Slot,
+    /// Epochs (about two days each on mainnet)
+    Epoch,
+}
+
+impl CooldownMode {
+    /// Longest cooldown allowed in this mode's units (about a week)
+    pub fn max_cooldown(&self) -> u64 {
+        match self {
+            CooldownMode::Timestamp => 7 * 24 * 60 * 60,
+            CooldownMode::Slot => 7 * 24 * 60 * 60 * 5 / 2,
+            CooldownMode::Epoch => 4,
+        }
+    }
+
+    /// Whether `cooldown` units is a valid cooldown in this mode
+    pub fn is_valid_cooldown(&self, cooldown: u64) -> bool {
+        (1..=self.max_cooldown()).contains(&cooldown)
+    }
+}
+
 /// Configuration for recovery V2 (compatible with V1)
 #[account]
 #[derive(InitSpace)]
 pub struct RecoveryConfigV2 {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Owner of this recovery configuration
     pub owner: Pubkey,
 
@@ -174,11 +228,63 @@ pub struct RecoveryConfigV2 {
     /// Unix timestamp of last recovery initiation attempt
     pub last_recovery_attempt: i64,
 
+    /// Clock `recovery_cooldown` is measured on
+    pub cooldown_mode: CooldownMode,
+
+    /// Minimum gap between recovery initiations, in `cooldown_mode` units
+    pub recovery_cooldown: u64,
+
+    /// Slot of the last recovery initiation attempt
+    pub last_recovery_attempt_slot: u64,
+
+    /// Epoch of the last recovery initiation attempt
+    pub last_recovery_attempt_epoch: u64,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl RecoveryConfigV2 {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"recovery_config_v2";
+
+    /// Account size holding `guardians` guardians (never below the default allocation)
+    pub const fn space_for(guardians: usize) -> usize {
+        8 + Self::INIT_SPACE
+            + guardians.saturating_sub(crate::state::MAX_GUARDIANS) * GuardianV2::INIT_SPACE
+    }
+
+    /// Add a guardian, enforcing the tier limit and unique keys and share indexes
+    ///
+    /// # Errors
+    /// * `TooManyGuardians` - `max_guardians` guardians already exist
+    /// * `GuardianAlreadyExists` - Key is already a guardian
+    /// * `InvalidShareIndex` - Share index 0 is reserved
+    /// * `DuplicateShareIndex` - Another guardian holds the share index
+    /// * `InvalidNicknameSize` - Nickname exceeds 64 bytes
+    pub fn add_guardian(&mut self, guardian: GuardianV2, max_guardians: usize) -> Result<()> {
+        use crate::errors::LockboxError;
+
+        require!(self.guardians.len() < max_guardians, LockboxError::TooManyGuardians);
+        require!(
+            !self.guardians.iter().any(|g| g.guardian_pubkey == guardian.guardian_pubkey),
+            LockboxError::GuardianAlreadyExists
+        );
+        require!(guardian.share_index > 0, LockboxError::InvalidShareIndex);
+        require!(
+            !self.guardians.iter().any(|g| g.share_index == guardian.share_index),
+            LockboxError::DuplicateShareIndex
+        );
+        require!(guardian.nickname_encrypted.len() <= 64, LockboxError::InvalidNicknameSize);
+
+        self.guardians.push(guardian);
+        self.total_guardians = self.guardians.len() as u8;
+        Ok(())
+    }
+
     /// Verify a share matches its commitment
     pub fn verify_share_commitment(
         &self,
@@ -207,6 +313,11 @@ impl RecoveryConfigV2 {
             .count()
     }
 
+    /// Check if enough guardians have accepted to meet the threshold
+    pub fn is_armed(&self) -> bool {
+        self.threshold > 0 && self.active_guardian_count() >= self.threshold as usize
+    }
+
     /// Check if guardian is active
     pub fn is_active_guardian(&self, pubkey: &Pubkey) -> bool {
         self.guardians
@@ -220,6 +331,36 @@ impl RecoveryConfigV2 {
         if self.last_recovery_attempt == 0 {
             return true; // First attempt
         }
-        current_time - self.last_recovery_attempt >= cooldown_seconds
+        elapsed_since(self.last_recovery_attempt, current_time) >= cooldown_seconds
+    }
+
+    /// Check the configured recovery cooldown against `clock`
+    ///
+    /// Measures the gap since the last initiation on the clock chosen by
+    /// `cooldown_mode`. A config that has never seen an attempt is always
+    /// past its cooldown.
+    pub fn is_recovery_cooldown_over(&self, clock: &Clock) -> bool {
+        if self.last_recovery_attempt == 0 {
+            return true; // First attempt
+        }
+        match self.cooldown_mode {
+            CooldownMode::Timestamp => {
+                self.check_recovery_rate_limit(clock.unix_timestamp, self.recovery_cooldown as i64)
+            }
+            CooldownMode::Slot => {
+                clock.slot.saturating_sub(self.last_recovery_attempt_slot) >= self.recovery_cooldown
+            }
+            CooldownMode::Epoch => {
+                clock.epoch.saturating_sub(self.last_recovery_attempt_epoch) >= self.recovery_cooldown
+            }
+        }
+    }
+
+    /// Record a recovery initiation on every clock, so the cooldown holds
+    /// even if the owner later switches `cooldown_mode`
+    pub fn record_recovery_attempt(&mut self, clock: &Clock) {
+        self.last_recovery_attempt = clock.unix_timestamp;
+        self.last_recovery_attempt_slot = clock.slot;
+        self.last_recovery_attempt_epoch = clock.epoch;
     }
 }
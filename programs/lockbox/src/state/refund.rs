@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use super::subscription::SubscriptionTier;
+
+/// Record of a refund paid from the treasury
+///
+/// Created by `issue_refund`. Ties the payout to the vault and the billing
+/// period it was netted from, so support refunds are auditable against
+/// the subscription history instead of being bare wallet transfers.
+///
+/// # PDA Derivation
+/// Seeds: ["refund_record", refund_id (u64 LE)]
+#[account]
+#[derive(InitSpace)]
+pub struct RefundRecord {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Program-wide sequence number, from `ProgramConfig::refund_count`
+    pub refund_id: u64,
+
+    /// Master lockbox the refund applies to
+    pub master_lockbox: Pubkey,
+
+    /// Vault owner the lamports were paid to
+    pub owner: Pubkey,
+
+    /// Lamports refunded
+    pub amount: u64,
+
+    /// Hash of the off-chain refund reason (support ticket, ...)
+    pub reason_hash: [u8; 32],
+
+    /// Vault's tier when refunded
+    pub tier: SubscriptionTier,
+
+    /// Billing snapshot sequence of the period the refund was netted from
+    pub billing_sequence: u64,
+
+    /// Admin who issued the refund
+    pub issued_by: Pubkey,
+
+    /// Unix timestamp the refund was paid
+    pub issued_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RefundRecord {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"refund_record";
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Size of a vault's search filter bit array, in bytes
+pub const SEARCH_FILTER_BYTES: usize = 1024;
+
+/// Bits set per inserted key
+pub const SEARCH_FILTER_HASHES: usize = 4;
+
+const SEARCH_FILTER_BITS: usize = SEARCH_FILTER_BYTES * 8;
+
+/// Bloom filter of a vault's search tokens
+///
+/// Every token is inserted twice: once on its own, so a client can rule out
+/// a whole vault, and once scoped to the chunk holding the entry, so it can
+/// skip chunks without downloading their headers. Tokens are already
+/// uniform HMAC outputs (`HMAC(search_key, keyword)`), so the vault key's
+/// bit positions are read straight from the token; the chunk key hashes
+/// the chunk index in first.
+///
+/// A Bloom filter only answers "maybe" or "no": deleting an entry leaves
+/// its bits set, and clients rebuild the filter with `reset_search_filter`
+/// once stale bits push the false positive rate up.
+///
+/// # PDA Derivation
+/// Seeds: ["search_filter", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct SearchFilter {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this filter belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Filter bit array
+    pub bits: [u8; SEARCH_FILTER_BYTES],
+
+    /// Tokens inserted since the filter was last reset
+    pub insertions: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SearchFilter {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"search_filter";
+
+    /// Filter key of `token` scoped to one chunk
+    fn chunk_key(token: &[u8; 32], chunk_index: u16) -> [u8; 32] {
+        hashv(&[Self::SEEDS_PREFIX, &chunk_index.to_le_bytes(), token]).to_bytes()
+    }
+
+    fn positions(key: &[u8; 32]) -> impl Iterator<Item = usize> + '_ {
+        (0..SEARCH_FILTER_HASHES).map(move |i| {
+            u16::from_le_bytes([key[2 * i], key[2 * i + 1]]) as usize % SEARCH_FILTER_BITS
+        })
+    }
+
+    fn set(&mut self, key: &[u8; 32]) {
+        for bit in Self::positions(key) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    fn test(&self, key: &[u8; 32]) -> bool {
+        Self::positions(key).all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Add `token` for an entry stored in chunk `chunk_index`
+    pub fn insert(&mut self, token: &[u8; 32], chunk_index: u16) {
+        self.set(token);
+        self.set(&Self::chunk_key(token, chunk_index));
+        self.insertions = self.insertions.saturating_add(1);
+    }
+
+    /// Whether `token` may be in the vault, or in chunk `chunk_index`
+    ///
+    /// `false` is definite; `true` may be a false positive.
+    pub fn contains(&self, token: &[u8; 32], chunk_index: Option<u16>) -> bool {
+        match chunk_index {
+            Some(chunk_index) => self.test(&Self::chunk_key(token, chunk_index)),
+            None => self.test(token),
+        }
+    }
+
+    /// Clear every bit
+    pub fn clear(&mut self) {
+        self.bits = [0u8; SEARCH_FILTER_BYTES];
+        self.insertions = 0;
+    }
+
+    /// Share of bits set, in basis points
+    ///
+    /// The false positive rate is roughly this ratio to the power of
+    /// `SEARCH_FILTER_HASHES`.
+    pub fn fill_ratio_bps(&self) -> u16 {
+        let set: u32 = self.bits.iter().map(|byte| byte.count_ones()).sum();
+        (set as u64 * 10_000 / SEARCH_FILTER_BITS as u64) as u16
+    }
+}
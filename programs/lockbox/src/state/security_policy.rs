@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+/// Owner-configurable security toggles for a vault
+///
+/// Entry handlers always receive this PDA's address and apply the policy
+/// when the account exists, so omitting it cannot bypass the policy.
+///
+/// # PDA Derivation
+/// Seeds: ["security_policy", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct SecurityPolicy {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this policy applies to
+    pub master_lockbox: Pubkey,
+
+    /// Owner's wallet address
+    pub owner: Pubkey,
+
+    /// Second signer required to delete entries (None = owner alone)
+    pub delete_cosigner: Option<Pubkey>,
+
+    /// Minimum seconds between vault accesses before a retrieve is allowed
+    pub read_cooldown: u16,
+
+    /// Require ciphertext sizes to be a multiple of `PADDING_BLOCK`
+    pub padded_sizes: bool,
+
+    /// `PRIVACY_*` bit flags
+    pub privacy_flags: u8,
+
+    /// Freeze the vault when a honeytoken entry is read
+    pub freeze_on_honeytoken: bool,
+
+    /// Last policy change
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SecurityPolicy {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"security_policy";
+
+    /// Block size for padded ciphertexts
+    pub const PADDING_BLOCK: usize = 32;
+
+    /// Do not increment per-entry access counts on retrieve
+    pub const PRIVACY_NO_ACCESS_COUNT: u8 = 0x01;
+
+    /// Never record the recently-used list, even if tracking is enabled
+    pub const PRIVACY_NO_RECENT_ENTRIES: u8 = 0x02;
+
+    /// Opt in to weekly per-category read counts (`HeatMapPeriodEvent`)
+    pub const PRIVACY_HEAT_MAP: u8 = 0x04;
+
+    /// All known privacy flags
+    pub const PRIVACY_FLAGS_MASK: u8 =
+        Self::PRIVACY_NO_ACCESS_COUNT | Self::PRIVACY_NO_RECENT_ENTRIES | Self::PRIVACY_HEAT_MAP;
+
+    /// Read the policy from its PDA, or `None` if the owner never created one
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    }
+
+    /// Check a ciphertext size against the padding requirement
+    pub fn check_size(&self, size: usize) -> Result<()> {
+        require!(
+            !self.padded_sizes || size.is_multiple_of(Self::PADDING_BLOCK),
+            crate::errors::LockboxError::UnpaddedCiphertext
+        );
+        Ok(())
+    }
+
+    /// Whether a privacy flag is set
+    pub fn has_privacy_flag(&self, flag: u8) -> bool {
+        self.privacy_flags & flag != 0
+    }
+}
@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+
+/// Most members a shared vault may have
+pub const MAX_SHARED_VAULT_MEMBERS: usize = 32;
+
+/// Largest wrapped copy of the shared vault key held for one member
+pub const MAX_WRAPPED_VAULT_KEY_SIZE: usize = 128;
+
+/// Largest encrypted payload of one shared entry
+pub const MAX_SHARED_ENTRY_SIZE: usize = 1024;
+
+/// SPL Token program, whose token accounts satisfy a token gate
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL Token-2022 program; its token accounts share the SPL Token layout
+pub const SPL_TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLXF4cxhbDcvbRW5y3");
+
+/// Metaplex Token Metadata program, which records NFT collections
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Longest a member can hold a shared entry checked out without renewing (1 hour)
+pub const MAX_ENTRY_CHECKOUT_DURATION: i64 = 60 * 60;
+
+fn check_wrapped_vault_key(wrapped_vault_key: &[u8]) -> Result<()> {
+    require!(
+        !wrapped_vault_key.is_empty() && wrapped_vault_key.len() <= MAX_WRAPPED_VAULT_KEY_SIZE,
+        crate::errors::LockboxError::InvalidDataSize
+    );
+    Ok(())
+}
+
+/// What a member may do in a shared vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum SharedVaultRole {
+    /// Manage members and write entries
+    Admin,
+    /// Store, update and delete entries
+    Writer,
+    /// Read entries only
+    Reader,
+}
+
+impl SharedVaultRole {
+    /// Whether the role may store, update or delete entries
+    pub fn can_write(&self) -> bool {
+        matches!(self, SharedVaultRole::Admin | SharedVaultRole::Writer)
+    }
+}
+
+/// Holding that lets any wallet join a shared vault as a Reader
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum SharedVaultGate {
+    /// At least one token of this mint
+    Mint(Pubkey),
+    /// An NFT whose Metaplex metadata names this collection, verified
+    Collection(Pubkey),
+}
+
+/// A wallet with access to a shared vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, Debug)]
+pub struct SharedVaultMember {
+    /// Member's wallet
+    pub member: Pubkey,
+
+    /// Member's role
+    pub role: SharedVaultRole,
+
+    /// Shared vault key wrapped to the member's key (nonce + ciphertext + tag)
+    #[max_len(128)]
+    pub wrapped_vault_key: Vec<u8>,
+
+    /// When the member was added
+    pub joined_at: i64,
+
+    /// Bytes of the vault's entries the member last wrote; moves with every
+    /// shared store, update and delete, for internal billing
+    pub bytes_written: u64,
+
+    /// Joined by holding the vault's gate token; reads require it still
+    pub token_gated: bool,
+}
+
+/// A vault of entries encrypted under one key shared by its members
+///
+/// Storage is drawn from the capacity of the master lockbox that created it
+/// (a personal vault, or an organization's), but recorded separately: each
+/// shared entry's bytes count toward the vault's `storage_used` and the
+/// master lockbox's `shared_storage_used`, never its `storage_used`, so the
+/// owner can see how much of their tier group data takes up.
+///
+/// # PDA Derivation
+/// Seeds: ["shared_vault", master_lockbox, vault_id_le]
+#[account]
+#[derive(InitSpace)]
+pub struct SharedVault {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox whose tier provides the storage
+    pub master_lockbox: Pubkey,
+
+    /// Wallet that created the shared vault
+    pub creator: Pubkey,
+
+    /// Creator-chosen ID, unique per master lockbox
+    pub vault_id: u32,
+
+    /// Holding that lets any wallet self-join as a Reader (None = invite only)
+    pub gate: Option<SharedVaultGate>,
+
+    /// Members in join order (sized by `space_for`)
+    #[max_len(0)]
+    pub members: Vec<SharedVaultMember>,
+
+    /// Bytes held by this vault's entries
+    pub storage_used: u64,
+
+    /// Live entries
+    pub entry_count: u32,
+
+    /// Live entries flagged `needs_rotation`
+    pub rotation_pending: u32,
+
+    /// Next entry ID to assign
+    pub next_entry_id: u64,
+
+    /// Creation timestamp
+    pub created_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SharedVault {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"shared_vault";
+
+    /// Account size holding `members` members
+    pub const fn space_for(members: usize) -> usize {
+        8 + Self::INIT_SPACE + members * SharedVaultMember::INIT_SPACE
+    }
+
+    /// Get a member by wallet
+    pub fn get_member(&self, member: &Pubkey) -> Option<&SharedVaultMember> {
+        self.members.iter().find(|m| &m.member == member)
+    }
+
+    /// Whether `member` may store, update or delete entries
+    pub fn can_write(&self, member: &Pubkey) -> bool {
+        self.get_member(member).is_some_and(|m| m.role.can_write())
+    }
+
+    /// Whether `member` may manage members
+    pub fn is_admin(&self, member: &Pubkey) -> bool {
+        self.get_member(member).is_some_and(|m| m.role == SharedVaultRole::Admin)
+    }
+
+    /// Move `member`'s `bytes_written` by `delta`, returning the new total
+    ///
+    /// Returns `None` for a wallet that is no longer a member; its bytes are
+    /// no longer attributed to anyone.
+    pub fn record_member_write(&mut self, member: &Pubkey, delta: i64) -> Option<u64> {
+        let entry = self.members.iter_mut().find(|m| &m.member == member)?;
+        entry.bytes_written = if delta >= 0 {
+            entry.bytes_written.saturating_add(delta as u64)
+        } else {
+            entry.bytes_written.saturating_sub(delta.unsigned_abs())
+        };
+        Some(entry.bytes_written)
+    }
+
+    /// Add a member, enforcing the member limit and uniqueness
+    pub fn add_member(
+        &mut self,
+        member: Pubkey,
+        role: SharedVaultRole,
+        wrapped_vault_key: Vec<u8>,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        check_wrapped_vault_key(&wrapped_vault_key)?;
+        self.push_member(SharedVaultMember {
+            member,
+            role,
+            wrapped_vault_key,
+            joined_at: current_timestamp,
+            bytes_written: 0,
+            token_gated: false,
+        })
+    }
+
+    /// Add `member` as a token-gated Reader awaiting its wrapped vault key
+    ///
+    /// The joiner cannot wrap the vault key to themselves; an admin delivers
+    /// it with `set_member_vault_key`.
+    pub fn add_token_gated_member(&mut self, member: Pubkey, current_timestamp: i64) -> Result<()> {
+        self.push_member(SharedVaultMember {
+            member,
+            role: SharedVaultRole::Reader,
+            wrapped_vault_key: Vec::new(),
+            joined_at: current_timestamp,
+            bytes_written: 0,
+            token_gated: true,
+        })
+    }
+
+    /// Replace `member`'s wrapped vault key
+    pub fn set_member_vault_key(&mut self, member: &Pubkey, wrapped_vault_key: Vec<u8>) -> Result<()> {
+        check_wrapped_vault_key(&wrapped_vault_key)?;
+        let entry = self
+            .members
+            .iter_mut()
+            .find(|m| &m.member == member)
+            .ok_or(crate::errors::LockboxError::SharedVaultMemberNotFound)?;
+        entry.wrapped_vault_key = wrapped_vault_key;
+        Ok(())
+    }
+
+    fn push_member(&mut self, member: SharedVaultMember) -> Result<()> {
+        require!(
+            self.members.len() < MAX_SHARED_VAULT_MEMBERS,
+            crate::errors::LockboxError::SharedVaultFull
+        );
+        require!(
+            self.get_member(&member.member).is_none(),
+            crate::errors::LockboxError::SharedVaultMemberExists
+        );
+        self.members.push(member);
+        Ok(())
+    }
+
+    /// Remove a member; the creator cannot be removed
+    pub fn remove_member(&mut self, member: &Pubkey) -> Result<SharedVaultMember> {
+        require!(
+            member != &self.creator,
+            crate::errors::LockboxError::Unauthorized
+        );
+        let index = self
+            .members
+            .iter()
+            .position(|m| &m.member == member)
+            .ok_or(crate::errors::LockboxError::SharedVaultMemberNotFound)?;
+        Ok(self.members.remove(index))
+    }
+}
+
+/// Exclusive right of one member to edit a shared entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct EntryCheckout {
+    /// Member holding the entry
+    pub member: Pubkey,
+
+    /// Unix timestamp the checkout lapses; any writer may take over after it
+    pub expires_at: i64,
+}
+
+/// One encrypted entry of a shared vault
+///
+/// # PDA Derivation
+/// Seeds: ["shared_entry", shared_vault, entry_id_le]
+#[account]
+#[derive(InitSpace)]
+pub struct SharedEntry {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Shared vault this entry belongs to
+    pub shared_vault: Pubkey,
+
+    /// Entry ID within the shared vault
+    pub entry_id: u64,
+
+    /// Member who last stored or updated the entry
+    pub last_modified_by: Pubkey,
+
+    /// Member editing the entry, while it is checked out
+    pub checkout: Option<EntryCheckout>,
+
+    /// A member who could read the entry was removed; set by
+    /// `remove_member_with_rotation_flag`, cleared by the next update
+    pub needs_rotation: bool,
+
+    /// Ciphertext under the shared vault key (sized by `space_for`)
+    #[max_len(0)]
+    pub encrypted_data: Vec<u8>,
+
+    /// Creation timestamp
+    pub created_at: i64,
+
+    /// Last update timestamp
+    pub last_modified: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SharedEntry {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"shared_entry";
+
+    /// Account size holding `data_len` bytes of ciphertext
+    pub const fn space_for(data_len: usize) -> usize {
+        8 + Self::INIT_SPACE + data_len
+    }
+
+    /// The checkout, unless it has lapsed
+    pub fn checkout_at(&self, current_timestamp: i64) -> Option<&EntryCheckout> {
+        self.checkout
+            .as_ref()
+            .filter(|checkout| current_timestamp < checkout.expires_at)
+    }
+
+    /// Check out the entry to `member` for `duration` seconds, or renew it
+    ///
+    /// Returns whether the entry was taken over from another member whose
+    /// checkout had lapsed.
+    ///
+    /// # Errors
+    /// * `InvalidEntryCheckoutDuration` - Duration is not within
+    ///   `1..=MAX_ENTRY_CHECKOUT_DURATION`
+    /// * `EntryCheckedOut` - Another member holds an unexpired checkout
+    pub fn check_out(&mut self, member: Pubkey, duration: i64, current_timestamp: i64) -> Result<bool> {
+        require!(
+            (1..=MAX_ENTRY_CHECKOUT_DURATION).contains(&duration),
+            crate::errors::LockboxError::InvalidEntryCheckoutDuration
+        );
+        self.check_editor(&member, current_timestamp)?;
+
+        let takeover = self.checkout.is_some_and(|checkout| checkout.member != member);
+        self.checkout = Some(EntryCheckout {
+            member,
+            expires_at: current_timestamp.saturating_add(duration),
+        });
+        Ok(takeover)
+    }
+
+    /// Check that `member` may edit or delete the entry
+    ///
+    /// Passes when no unexpired checkout is held or `member` holds it.
+    pub fn check_editor(&self, member: &Pubkey, current_timestamp: i64) -> Result<()> {
+        if let Some(checkout) = self.checkout_at(current_timestamp) {
+            require!(
+                member == &checkout.member,
+                crate::errors::LockboxError::EntryCheckedOut
+            );
+        }
+        Ok(())
+    }
+}
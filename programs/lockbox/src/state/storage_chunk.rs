@@ -1,10 +1,19 @@
 use anchor_lang::prelude::*;
-use super::subscription::{StorageType, DataEntryHeader};
+use super::subscription::{StorageType, DataEntryHeader, AliasTarget};
+
+/// Bytes per search index posting: blind token (32) + entry ID (8)
+pub const SEARCH_POSTING_SIZE: usize = 40;
+
+/// Most blind-index tokens one entry may have in a search index chunk
+pub const MAX_SEARCH_TOKENS_PER_ENTRY: usize = 32;
 
 /// Storage chunk account - holds encrypted password entries
 #[account]
 #[derive(InitSpace)]
 pub struct StorageChunk {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
     /// Master lockbox this chunk belongs to
     pub master_lockbox: Pubkey,
 
@@ -40,16 +49,24 @@ pub struct StorageChunk {
     /// Last modification timestamp
     pub last_modified: i64,
 
+    /// Bumped on every structural change (realloc, archive migration, an
+    /// entry moving in or out); mirrored in the chunk's `StorageChunkInfo`
+    pub layout_epoch: u32,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl StorageChunk {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 4;
+
     /// Seeds for PDA derivation
     pub const SEEDS_PREFIX: &'static [u8] = b"storage_chunk";
 
     /// Initial space for a chunk (excluding dynamic data)
     pub const BASE_SPACE: usize = 8 + // discriminator
+        1 +  // version
         32 + // master_lockbox
         32 + // owner
         2 +  // chunk_index
@@ -61,8 +78,14 @@ impl StorageChunk {
         2 +  // entry_count
         8 +  // created_at
         8 +  // last_modified
+        4 +  // layout_epoch
         1;   // bump
 
+    /// Account size of a chunk with `capacity` bytes of data
+    pub const fn space(capacity: u32) -> usize {
+        Self::BASE_SPACE + capacity as usize
+    }
+
     /// Minimum chunk size (1KB)
     pub const MIN_CHUNK_SIZE: u32 = 1024;
 
@@ -91,6 +114,8 @@ impl StorageChunk {
         self.entry_count = 0;
         self.created_at = current_timestamp;
         self.last_modified = current_timestamp;
+        self.layout_epoch = 0;
+        self.version = Self::CURRENT_VERSION;
         self.bump = bump;
         Ok(())
     }
@@ -102,11 +127,21 @@ impl StorageChunk {
         encrypted_data: Vec<u8>,
         current_timestamp: i64,
     ) -> Result<()> {
+        require!(
+            self.data_type != StorageType::SearchIndex,
+            crate::errors::LockboxError::SearchIndexChunk
+        );
+
         require!(
             self.entry_headers.len() < 100,
             crate::errors::LockboxError::MaxEntriesPerChunk
         );
 
+        require!(
+            !self.has_entry(entry_header.entry_id),
+            crate::errors::LockboxError::DuplicateEntryId
+        );
+
         // SECURITY: Use checked_add to prevent integer overflow
         let data_len = encrypted_data.len() as u32;
         let new_size = self.current_size
@@ -148,6 +183,11 @@ impl StorageChunk {
         let old_size = self.entry_headers[header_idx].size;
         let new_size = new_encrypted_data.len() as u32;
 
+        require!(
+            old_offset + old_size as usize <= self.encrypted_data.len(),
+            crate::errors::LockboxError::InvalidEntryOffset
+        );
+
         // SECURITY: Calculate size difference using checked arithmetic to prevent overflows
         let new_total_size = if new_size > old_size {
             // Growing: add the difference
@@ -234,6 +274,11 @@ impl StorageChunk {
         let offset = header.offset as usize;
         let size = header.size as usize;
 
+        require!(
+            offset + size <= self.encrypted_data.len(),
+            crate::errors::LockboxError::InvalidEntryOffset
+        );
+
         // Remove data
         let mut new_data = Vec::with_capacity(self.encrypted_data.len() - size);
         new_data.extend_from_slice(&self.encrypted_data[..offset]);
@@ -250,10 +295,10 @@ impl StorageChunk {
 
         // Remove header
         self.entry_headers.remove(header_idx);
-        self.entry_count -= 1;
+        self.entry_count = self.entry_count.saturating_sub(1);
 
         self.encrypted_data = new_data;
-        self.current_size -= size as u32;
+        self.current_size = self.current_size.saturating_sub(size as u32);
         self.last_modified = current_timestamp;
 
         Ok(())
@@ -277,6 +322,267 @@ impl StorageChunk {
         Ok(self.encrypted_data[offset..offset + size].to_vec())
     }
 
+    /// Decode the target of an alias entry
+    pub fn get_alias_target(&self, entry_id: u64) -> Result<AliasTarget> {
+        let header = self.get_entry_header(entry_id)?;
+        require!(header.is_alias(), crate::errors::LockboxError::EntryNotAlias);
+
+        let data = self.get_entry_data(entry_id)?;
+        AliasTarget::try_from_slice(&data)
+            .map_err(|_| crate::errors::LockboxError::InvalidDataSize.into())
+    }
+
+    /// Delete every alias in this chunk pointing at `target`
+    ///
+    /// Returns the number of aliases removed.
+    pub fn delete_aliases_of(
+        &mut self,
+        target: AliasTarget,
+        current_timestamp: i64,
+    ) -> Result<u32> {
+        let alias_ids: Vec<u64> = self.entry_headers
+            .iter()
+            .filter(|h| h.is_alias())
+            .map(|h| h.entry_id)
+            .filter(|id| self.get_alias_target(*id).ok() == Some(target))
+            .collect();
+
+        for id in &alias_ids {
+            self.delete_entry(*id, current_timestamp)?;
+        }
+
+        Ok(alias_ids.len() as u32)
+    }
+
+    /// Remove an entry and return its header and data (for moving it)
+    pub fn take_entry(
+        &mut self,
+        entry_id: u64,
+        current_timestamp: i64,
+    ) -> Result<(DataEntryHeader, Vec<u8>)> {
+        let header = self.get_entry_header(entry_id)?.clone();
+        let data = self.get_entry_data(entry_id)?;
+        self.delete_entry(entry_id, current_timestamp)?;
+        Ok((header, data))
+    }
+
+    /// Re-point aliases of `from` at `to` after the target moved chunks
+    ///
+    /// Returns the number of aliases updated.
+    pub fn retarget_aliases(
+        &mut self,
+        from: AliasTarget,
+        to: AliasTarget,
+        current_timestamp: i64,
+    ) -> Result<u32> {
+        let payload = to.try_to_vec()?;
+        let offsets: Vec<usize> = self.entry_headers
+            .iter()
+            .filter(|h| h.is_alias())
+            .filter(|h| self.get_alias_target(h.entry_id).ok() == Some(from))
+            .map(|h| h.offset as usize)
+            .collect();
+
+        for offset in &offsets {
+            self.encrypted_data[*offset..*offset + AliasTarget::SIZE].copy_from_slice(&payload);
+        }
+        if !offsets.is_empty() {
+            self.last_modified = current_timestamp;
+        }
+
+        Ok(offsets.len() as u32)
+    }
+
+    /// Trashed entries whose retention has elapsed, as `(entry_id, trashed_at)`
+    pub fn expired_trash(&self, current_timestamp: i64, retention: i64) -> Vec<(u64, i64)> {
+        self.entry_headers
+            .iter()
+            .filter(|h| h.is_trashed())
+            .filter(|h| current_timestamp.saturating_sub(h.last_modified) >= retention)
+            .map(|h| (h.entry_id, h.last_modified))
+            .collect()
+    }
+
+    /// Live entries past their rotation date, as `(entry_id, rotate_after)`
+    ///
+    /// An entry spanning chunks is listed once, by the chunk holding its head.
+    pub fn rotation_due(&self, current_timestamp: i64) -> Vec<(u64, i64)> {
+        self.entry_headers
+            .iter()
+            .filter(|h| !h.is_trashed() && !h.is_continuation())
+            .filter(|h| h.is_rotation_due(current_timestamp))
+            .map(|h| (h.entry_id, h.rotate_after))
+            .collect()
+    }
+
+    /// Take an entry back out of the trash before its retention elapses
+    ///
+    /// `last_modified` keeps the trash time, so an entry trashed before a
+    /// vault transfer still counts as awaiting re-encryption once restored.
+    pub fn restore_trashed(&mut self, entry_id: u64, current_timestamp: i64, retention: i64) -> Result<()> {
+        let header = self.get_entry_header_mut(entry_id)?;
+        require!(header.is_trashed(), crate::errors::LockboxError::EntryNotTrashed);
+        require!(
+            current_timestamp.saturating_sub(header.last_modified) < retention,
+            crate::errors::LockboxError::TrashRetentionElapsed
+        );
+        header.set_trashed(false);
+        self.last_modified = current_timestamp;
+        Ok(())
+    }
+
+    /// Live entries whose ciphertext was last written before `timestamp`
+    ///
+    /// Aliases hold no ciphertext and trashed entries are frozen, so neither
+    /// is counted; an entry spanning chunks counts once, in its head's chunk.
+    pub fn entries_written_before(&self, timestamp: i64) -> u32 {
+        self.entry_headers
+            .iter()
+            .filter(|h| !h.is_alias() && !h.is_trashed() && !h.is_continuation())
+            .filter(|h| h.last_modified < timestamp)
+            .count() as u32
+    }
+
+    /// IDs of live entries carrying the tag with bit `mask`
+    ///
+    /// Tags live on an entry's head segment, so an entry spanning chunks is
+    /// listed once, by the chunk holding its head.
+    pub fn entries_with_tag(&self, mask: u16) -> Vec<u64> {
+        self.entry_headers
+            .iter()
+            .filter(|h| !h.is_trashed() && !h.is_continuation() && h.has_tag(mask))
+            .map(|h| h.entry_id)
+            .collect()
+    }
+
+    /// Remove the tag with bit `mask` from every entry; returns how many had it
+    pub fn clear_tag(&mut self, mask: u16) -> u32 {
+        let mut cleared = 0;
+        for header in self.entry_headers.iter_mut().filter(|h| h.has_tag(mask)) {
+            header.tag_mask &= !mask;
+            cleared += 1;
+        }
+        cleared
+    }
+
+    /// `(token, entry_id)` postings of a search index chunk
+    ///
+    /// A `SearchIndex` chunk has no entry headers; `encrypted_data` is a
+    /// packed list of `SEARCH_POSTING_SIZE`-byte postings.
+    pub fn search_postings(&self) -> impl Iterator<Item = ([u8; 32], u64)> + '_ {
+        self.encrypted_data.chunks_exact(SEARCH_POSTING_SIZE).map(|posting| {
+            let mut token = [0u8; 32];
+            token.copy_from_slice(&posting[..32]);
+            let mut entry_id = [0u8; 8];
+            entry_id.copy_from_slice(&posting[32..]);
+            (token, u64::from_le_bytes(entry_id))
+        })
+    }
+
+    /// IDs of the entries indexed under `token`, in posting order
+    pub fn entries_for_token(&self, token: &[u8; 32]) -> Vec<u64> {
+        self.search_postings()
+            .filter(|(t, _)| t == token)
+            .map(|(_, entry_id)| entry_id)
+            .collect()
+    }
+
+    /// Replace an entry's search tokens; returns the new postings size
+    ///
+    /// Duplicate tokens are indexed once. An empty list removes the entry
+    /// from the index.
+    ///
+    /// # Errors
+    /// * `NotSearchIndexChunk` - Chunk is not a `SearchIndex` chunk
+    /// * `TooManySearchTokens` - More than `MAX_SEARCH_TOKENS_PER_ENTRY` tokens
+    /// * `InvalidSearchToken` - A token is all zeroes
+    /// * `SearchIndexFull` - The postings exceed `max_capacity`
+    pub fn set_search_tokens(
+        &mut self,
+        entry_id: u64,
+        tokens: &[[u8; 32]],
+        current_timestamp: i64,
+    ) -> Result<u32> {
+        require!(
+            self.data_type == StorageType::SearchIndex,
+            crate::errors::LockboxError::NotSearchIndexChunk
+        );
+        require!(
+            tokens.len() <= MAX_SEARCH_TOKENS_PER_ENTRY,
+            crate::errors::LockboxError::TooManySearchTokens
+        );
+        require!(
+            tokens.iter().all(|t| *t != [0u8; 32]),
+            crate::errors::LockboxError::InvalidSearchToken
+        );
+
+        let mut postings: Vec<u8> = Vec::with_capacity(self.encrypted_data.len());
+        for (token, id) in self.search_postings().filter(|(_, id)| *id != entry_id) {
+            postings.extend_from_slice(&token);
+            postings.extend_from_slice(&id.to_le_bytes());
+        }
+        for (i, token) in tokens.iter().enumerate() {
+            if tokens[..i].contains(token) {
+                continue;
+            }
+            postings.extend_from_slice(token);
+            postings.extend_from_slice(&entry_id.to_le_bytes());
+        }
+
+        let new_size = postings.len() as u32;
+        require!(
+            new_size <= self.max_capacity,
+            crate::errors::LockboxError::SearchIndexFull
+        );
+
+        self.encrypted_data = postings;
+        self.current_size = new_size;
+        self.last_modified = current_timestamp;
+        Ok(new_size)
+    }
+
+    /// Layout statistics for maintenance tooling
+    ///
+    /// Free space is every byte of `max_capacity` not covered by an entry,
+    /// split into blocks by the entries' offsets. Fragmentation is the share
+    /// of free space outside the largest block, in basis points.
+    pub fn health(&self) -> ChunkHealth {
+        let mut spans: Vec<(u32, u32)> = self.entry_headers
+            .iter()
+            .map(|h| (h.offset, h.offset.saturating_add(h.size)))
+            .collect();
+        spans.sort_unstable();
+
+        let mut slack_bytes = 0u32;
+        let mut largest_free_block = 0u32;
+        let mut cursor = 0u32;
+        for (start, end) in spans.into_iter().chain([(self.max_capacity, self.max_capacity)]) {
+            let gap = start.saturating_sub(cursor);
+            slack_bytes = slack_bytes.saturating_add(gap);
+            largest_free_block = largest_free_block.max(gap);
+            cursor = cursor.max(end);
+        }
+
+        let fragmentation_bps = if slack_bytes == 0 {
+            0
+        } else {
+            ((slack_bytes - largest_free_block) as u64 * 10_000 / slack_bytes as u64) as u16
+        };
+
+        let trashed_bytes = self.entry_headers
+            .iter()
+            .filter(|h| h.is_trashed())
+            .map(|h| h.size)
+            .sum();
+
+        ChunkHealth {
+            slack_bytes,
+            largest_free_block,
+            fragmentation_bps,
+            trashed_bytes,
+        }
+    }
+
     /// Get available space in this chunk
     pub fn available_space(&self) -> u32 {
         self.max_capacity - self.current_size
@@ -287,6 +593,32 @@ impl StorageChunk {
         self.available_space() >= size
     }
 
+    /// Whether `entry_count` matches the header list
+    pub fn is_entry_count_consistent(&self) -> bool {
+        self.entry_count as usize == self.entry_headers.len()
+    }
+
+    /// Check that the chunk still has the layout the client read
+    ///
+    /// Entry operations take the `layout_epoch` the client saw when it
+    /// built them, so one queued behind a restructuring fails instead of
+    /// acting on a layout the client never saw.
+    ///
+    /// # Errors
+    /// * `StaleChunkLayout` - The chunk was restructured since it was read
+    pub fn check_layout_epoch(&self, expected_layout_epoch: u32) -> Result<()> {
+        require!(
+            self.layout_epoch == expected_layout_epoch,
+            crate::errors::LockboxError::StaleChunkLayout
+        );
+        Ok(())
+    }
+
+    /// Check whether an entry ID is already used in this chunk
+    pub fn has_entry(&self, entry_id: u64) -> bool {
+        self.entry_headers.iter().any(|h| h.entry_id == entry_id)
+    }
+
     /// Get entry header by ID
     pub fn get_entry_header(&self, entry_id: u64) -> Result<&DataEntryHeader> {
         self.entry_headers
@@ -303,3 +635,19 @@ impl StorageChunk {
             .ok_or(crate::errors::LockboxError::EntryNotFound.into())
     }
 }
+
+/// Free-space summary produced by `StorageChunk::health`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkHealth {
+    /// Capacity bytes not holding entry data
+    pub slack_bytes: u32,
+
+    /// Largest contiguous free region
+    pub largest_free_block: u32,
+
+    /// Share of slack outside the largest free block (basis points)
+    pub fragmentation_bps: u16,
+
+    /// Bytes held by trashed entries awaiting purge
+    pub trashed_bytes: u32,
+}
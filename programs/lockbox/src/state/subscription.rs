@@ -1,5 +1,38 @@
 use anchor_lang::prelude::*;
 
+/// Fee for each v2 entry store/update on tiers that don't waive it (0 disables)
+///
+/// Spam prevention only: rent already covers storage.
+pub const ENTRY_WRITE_FEE_LAMPORTS: u64 = 5_000;
+
+/// Seed of the treasury PDA that collects entry write fees
+///
+/// The treasury is a system account; it must be funded to rent exemption
+/// once before the first fee can land.
+#[constant]
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Storage added by one capacity add-on pack (50KB)
+pub const CAPACITY_ADDON_SIZE: u64 = 51_200;
+
+/// Monthly price of one capacity add-on pack (0.005 SOL)
+pub const CAPACITY_ADDON_PRICE: u64 = 5_000_000;
+
+/// Most add-on packs a vault can hold at once
+pub const MAX_CAPACITY_ADDON_PACKS: u8 = 20;
+
+/// Loyalty capacity earned per consecutive renewal, in basis points of the tier
+pub const LOYALTY_BONUS_BPS_PER_RENEWAL: u64 = 100;
+
+/// Cap on loyalty capacity, in basis points of the tier (10%)
+pub const MAX_LOYALTY_BONUS_BPS: u64 = 1_000;
+
+/// Default grace period after expiry before automatic downgrade (7 days)
+pub const DEFAULT_DUNNING_WINDOW: i64 = 7 * 24 * 60 * 60;
+
+/// Longest dunning window an owner can configure (30 days)
+pub const MAX_DUNNING_WINDOW: i64 = 30 * 24 * 60 * 60;
+
 /// Subscription tiers for storage capacity
 ///
 /// CRITICAL: These discriminants must NEVER change. The numeric values are
@@ -18,6 +51,19 @@ pub enum SubscriptionTier {
     // Reserve 4-254 for future tiers
 }
 
+/// Whether a paid subscription is in good standing
+///
+/// `process_subscription_expiry` moves an expired subscription to
+/// `PastDue`; once the dunning window also ends it downgrades the vault to
+/// Free and the status returns to `Active`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum SubscriptionStatus {
+    /// Paid up, or on the Free tier
+    Active,
+    /// Expired without renewal; writes are blocked until renewed or downgraded
+    PastDue,
+}
+
 impl SubscriptionTier {
     /// Get maximum storage capacity for this tier (in bytes)
     pub fn max_capacity(&self) -> u64 {
@@ -57,6 +103,72 @@ impl SubscriptionTier {
         }
     }
 
+    /// Fee charged per v2 entry write (paid tiers waive it)
+    pub fn entry_write_fee(&self) -> u64 {
+        match self {
+            SubscriptionTier::Free => ENTRY_WRITE_FEE_LAMPORTS,
+            _ => 0,
+        }
+    }
+
+    /// Maximum guardians per recovery configuration
+    pub fn max_guardians(&self) -> usize {
+        match self {
+            SubscriptionTier::Pro => crate::state::PRO_MAX_GUARDIANS,
+            _ => crate::state::MAX_GUARDIANS,
+        }
+    }
+
+    /// Maximum emergency contacts
+    pub fn max_emergency_contacts(&self) -> usize {
+        match self {
+            SubscriptionTier::Pro => crate::state::PRO_MAX_EMERGENCY_CONTACTS,
+            _ => crate::state::MAX_EMERGENCY_CONTACTS,
+        }
+    }
+
+    /// Maximum contact book entries
+    pub fn max_contacts(&self) -> usize {
+        match self {
+            SubscriptionTier::Free => 10,
+            SubscriptionTier::Basic => 25,
+            SubscriptionTier::Premium => 50,
+            SubscriptionTier::Pro => crate::state::MAX_CONTACTS,
+        }
+    }
+
+    /// Maximum ciphertext size of a single v2 entry
+    ///
+    /// Secure notes and SSH keys outgrow the v1 1KB limit once encrypted.
+    pub fn max_entry_size(&self) -> usize {
+        match self {
+            SubscriptionTier::Free | SubscriptionTier::Basic => 1024, // 1KB
+            SubscriptionTier::Premium => 4096,                        // 4KB
+            SubscriptionTier::Pro => 16_384,                          // 16KB
+        }
+    }
+
+    /// Entry bytes a vault may write per `WRITE_BURST_WINDOW_SLOTS`
+    ///
+    /// Always at least one maximum-size entry.
+    pub fn max_write_burst(&self) -> u32 {
+        match self {
+            SubscriptionTier::Free => 4096,         // 4KB
+            SubscriptionTier::Basic => 16_384,      // 16KB
+            SubscriptionTier::Premium => 65_536,    // 64KB
+            SubscriptionTier::Pro => 262_144,       // 256KB
+        }
+    }
+
+    /// Maximum encrypted settings blob size
+    pub fn max_settings_size(&self) -> usize {
+        match self {
+            SubscriptionTier::Free => 128,
+            SubscriptionTier::Basic => 256,
+            SubscriptionTier::Premium | SubscriptionTier::Pro => crate::state::MAX_SETTINGS_SIZE,
+        }
+    }
+
     /// Check if this tier supports categories (Basic and above)
     pub fn supports_categories(&self) -> bool {
         matches!(
@@ -83,6 +195,8 @@ pub struct StorageChunkInfo {
     pub created_at: i64,
     /// Last modification timestamp
     pub last_modified: i64,
+    /// Layout epoch of the chunk; see `StorageChunk::layout_epoch`
+    pub layout_epoch: u32,
 }
 
 /// Types of storage chunks
@@ -99,7 +213,9 @@ pub enum StorageType {
     SearchIndex = 2,
     /// Audit logs
     AuditLogs = 3,
-    // Reserve 4-254 for future use
+    /// Cold storage for archived password entries
+    ArchivedPasswords = 4,
+    // Reserve 5-254 for future use
 }
 
 /// Password entry types
@@ -123,10 +239,15 @@ pub enum PasswordEntryType {
     SshKey = 5,
     /// Cryptocurrency wallet
     CryptoWallet = 6,
-    // Reserve 7-254 for future use
+    /// TOTP (2FA) seed, optionally attached to a login
+    Totp = 7,
+    // Reserve 8-254 for future use
     // 255 could be used for "Unknown" during migration
 }
 
+/// Most entries one entry can be linked to
+pub const MAX_ENTRY_LINKS: usize = 8;
+
 /// Password entry metadata header
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, Debug)]
 pub struct DataEntryHeader {
@@ -150,34 +271,171 @@ pub struct DataEntryHeader {
     pub access_count: u32,
     /// Flags (favorite, archived, etc.)
     pub flags: u8,
+    /// Chunk holding the entry's next segment (`NO_NEXT_CHUNK` = last or only)
+    pub next_chunk: u16,
+    /// Where the next segment starts in the reassembled ciphertext
+    pub next_offset: u32,
+    /// Bitmask of `TagRegistry` slots (blind tags) carried by the entry
+    pub tag_mask: u16,
+    /// Time after which the entry is due for rotation (0 = no schedule)
+    pub rotate_after: i64,
+    /// Login a TOTP entry is attached to (0 = none)
+    pub parent_entry: u64,
+    /// Related entries (recovery codes, API keys, notes); both sides of a
+    /// link record it
+    #[max_len(MAX_ENTRY_LINKS)]
+    pub linked_entry_ids: Vec<u64>,
 }
 
 impl DataEntryHeader {
+    /// Flag marking a favorite entry
+    pub const FLAG_FAVORITE: u8 = 0x01;
+
+    /// Flag marking an archived entry (may be migrated to a cold chunk)
+    pub const FLAG_ARCHIVED: u8 = 0x02;
+
+    /// Flags clients may set directly with `set_entry_flags`
+    pub const USER_FLAGS: u8 = Self::FLAG_FAVORITE | Self::FLAG_ARCHIVED;
+
+    /// Flag marking an alias entry (payload is an `AliasTarget`, not ciphertext)
+    pub const FLAG_ALIAS: u8 = 0x04;
+
+    /// Flag marking a trashed (soft-deleted) entry; `last_modified` is the trash time
+    pub const FLAG_TRASHED: u8 = 0x08;
+
+    /// Flag marking a continuation segment of an entry spanning chunks
+    pub const FLAG_CONTINUATION: u8 = 0x10;
+
+    /// `next_chunk` of an entry's last (or only) segment
+    pub const NO_NEXT_CHUNK: u16 = u16::MAX;
+
     /// Check if entry is marked as favorite
     pub fn is_favorite(&self) -> bool {
-        self.flags & 0x01 != 0
+        self.flags & Self::FLAG_FAVORITE != 0
     }
 
     /// Check if entry is archived
     pub fn is_archived(&self) -> bool {
-        self.flags & 0x02 != 0
+        self.flags & Self::FLAG_ARCHIVED != 0
     }
 
     /// Set favorite flag
     pub fn set_favorite(&mut self, favorite: bool) {
         if favorite {
-            self.flags |= 0x01;
+            self.flags |= Self::FLAG_FAVORITE;
         } else {
-            self.flags &= !0x01;
+            self.flags &= !Self::FLAG_FAVORITE;
         }
     }
 
     /// Set archived flag
     pub fn set_archived(&mut self, archived: bool) {
         if archived {
-            self.flags |= 0x02;
+            self.flags |= Self::FLAG_ARCHIVED;
+        } else {
+            self.flags &= !Self::FLAG_ARCHIVED;
+        }
+    }
+
+    /// Replace the client-settable flags, keeping structural ones
+    ///
+    /// # Errors
+    /// * `InvalidEntryFlags` - `flags` has bits outside `USER_FLAGS`
+    pub fn set_user_flags(&mut self, flags: u8) -> Result<()> {
+        require!(flags & !Self::USER_FLAGS == 0, crate::errors::LockboxError::InvalidEntryFlags);
+        self.flags = (self.flags & !Self::USER_FLAGS) | flags;
+        Ok(())
+    }
+
+    /// Check if entry is an alias of another entry
+    pub fn is_alias(&self) -> bool {
+        self.flags & Self::FLAG_ALIAS != 0
+    }
+
+    /// Check if entry is in the trash
+    pub fn is_trashed(&self) -> bool {
+        self.flags & Self::FLAG_TRASHED != 0
+    }
+
+    /// Check if this is a continuation segment rather than an entry's head
+    pub fn is_continuation(&self) -> bool {
+        self.flags & Self::FLAG_CONTINUATION != 0
+    }
+
+    /// Check if the entry carries the tag with bit `mask`
+    pub fn has_tag(&self, mask: u16) -> bool {
+        self.tag_mask & mask != 0
+    }
+
+    /// Check if the entry's rotation date has passed
+    pub fn is_rotation_due(&self, current_timestamp: i64) -> bool {
+        self.rotate_after != 0 && current_timestamp >= self.rotate_after
+    }
+
+    /// Check if the entry is linked to `entry_id`
+    pub fn is_linked_to(&self, entry_id: u64) -> bool {
+        self.linked_entry_ids.contains(&entry_id)
+    }
+
+    /// Record a link to `entry_id`
+    ///
+    /// # Errors
+    /// * `InvalidEntryLink` - `entry_id` is this entry
+    /// * `EntryAlreadyLinked` - The entries are already linked
+    /// * `TooManyEntryLinks` - Entry already has `MAX_ENTRY_LINKS` links
+    pub fn add_link(&mut self, entry_id: u64) -> Result<()> {
+        use crate::errors::LockboxError;
+
+        require!(entry_id != self.entry_id, LockboxError::InvalidEntryLink);
+        require!(!self.is_linked_to(entry_id), LockboxError::EntryAlreadyLinked);
+        require!(
+            self.linked_entry_ids.len() < MAX_ENTRY_LINKS,
+            LockboxError::TooManyEntryLinks
+        );
+        self.linked_entry_ids.push(entry_id);
+        Ok(())
+    }
+
+    /// Drop the link to `entry_id`, returning whether there was one
+    pub fn remove_link(&mut self, entry_id: u64) -> bool {
+        let before = self.linked_entry_ids.len();
+        self.linked_entry_ids.retain(|&id| id != entry_id);
+        self.linked_entry_ids.len() != before
+    }
+
+    /// Check if another segment follows in `next_chunk`
+    pub fn has_next(&self) -> bool {
+        self.next_chunk != Self::NO_NEXT_CHUNK
+    }
+
+    /// Check if this header is any segment of an entry spanning chunks
+    pub fn spans_chunks(&self) -> bool {
+        self.is_continuation() || self.has_next()
+    }
+
+    /// Set trashed flag
+    pub fn set_trashed(&mut self, trashed: bool) {
+        if trashed {
+            self.flags |= Self::FLAG_TRASHED;
         } else {
-            self.flags &= !0x02;
+            self.flags &= !Self::FLAG_TRASHED;
         }
     }
 }
+
+/// Payload of an alias entry: points at the entry holding the ciphertext
+///
+/// Lets one credential appear under several categories without duplicating
+/// ciphertext. Stored unencrypted; clients follow it to the target entry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AliasTarget {
+    /// Chunk holding the target entry
+    pub chunk_index: u16,
+    /// Target entry ID (never itself an alias)
+    pub entry_id: u64,
+}
+
+impl AliasTarget {
+    /// Serialized size (chunk_index + entry_id)
+    pub const SIZE: usize = 2 + 8;
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+/// Most tags a vault may register (one bit each in `DataEntryHeader::tag_mask`)
+pub const MAX_TAGS: usize = 16;
+
+/// Blind tags registered for a vault
+///
+/// Clients compute each tag as `HMAC(tag_key, tag_name)`, so the chain only
+/// sees opaque hashes. A tag's slot index is its bit in the `tag_mask` of
+/// every entry header carrying it; slots are never compacted, so freed
+/// slots hold the zero hash until reused.
+///
+/// # PDA Derivation
+/// Seeds: ["tag_registry", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct TagRegistry {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox this registry belongs to
+    pub master_lockbox: Pubkey,
+
+    /// Blind tag hash per slot (all zeroes = free)
+    pub tags: [[u8; 32]; MAX_TAGS],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TagRegistry {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"tag_registry";
+
+    /// Slot holding `tag_hash`, if registered
+    pub fn slot_of(&self, tag_hash: &[u8; 32]) -> Option<usize> {
+        if *tag_hash == [0u8; 32] {
+            return None;
+        }
+        self.tags.iter().position(|tag| tag == tag_hash)
+    }
+
+    /// `tag_mask` bit of a registered tag
+    ///
+    /// # Errors
+    /// * `TagNotFound` - Tag is not registered
+    pub fn mask_of(&self, tag_hash: &[u8; 32]) -> Result<u16> {
+        let slot = self.slot_of(tag_hash).ok_or(crate::errors::LockboxError::TagNotFound)?;
+        Ok(1 << slot)
+    }
+
+    /// Register `tag_hash` if needed and return its `tag_mask` bit
+    ///
+    /// # Errors
+    /// * `InvalidTagHash` - Hash is all zeroes
+    /// * `TagRegistryFull` - All `MAX_TAGS` slots are in use
+    pub fn register(&mut self, tag_hash: [u8; 32]) -> Result<u16> {
+        require!(tag_hash != [0u8; 32], crate::errors::LockboxError::InvalidTagHash);
+        if let Some(slot) = self.slot_of(&tag_hash) {
+            return Ok(1 << slot);
+        }
+        let slot = self
+            .tags
+            .iter()
+            .position(|tag| *tag == [0u8; 32])
+            .ok_or(crate::errors::LockboxError::TagRegistryFull)?;
+        self.tags[slot] = tag_hash;
+        Ok(1 << slot)
+    }
+
+    /// Free the slot of `tag_hash` and return the bit it used
+    ///
+    /// # Errors
+    /// * `TagNotFound` - Tag is not registered
+    pub fn unregister(&mut self, tag_hash: &[u8; 32]) -> Result<u16> {
+        let slot = self.slot_of(tag_hash).ok_or(crate::errors::LockboxError::TagNotFound)?;
+        self.tags[slot] = [0u8; 32];
+        Ok(1 << slot)
+    }
+}
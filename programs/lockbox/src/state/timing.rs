@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::errors::LockboxError;
+
+/// Seconds a recorded timestamp may run ahead of the cluster clock
+///
+/// The cluster timestamp is a stake-weighted estimate that can step back
+/// slightly between slots, so a time recorded a few slots ago may read as
+/// being in the future. Anything further ahead than this is corrupt.
+pub const MAX_CLOCK_DRIFT: i64 = 120;
+
+/// Earliest plausible cluster timestamp (2020-01-01); anything earlier
+/// means the clock is unset or broken
+pub const MIN_CLUSTER_TIMESTAMP: i64 = 1_577_836_800;
+
+/// Current cluster time, checked for plausibility
+///
+/// Subscription, recovery and emergency access handlers read the clock
+/// through this so they all reject a broken clock the same way.
+///
+/// # Errors
+/// * `InvalidClockTimestamp` - The cluster clock is before
+///   `MIN_CLUSTER_TIMESTAMP`
+pub fn cluster_timestamp() -> Result<i64> {
+    Ok(cluster_clock()?.unix_timestamp)
+}
+
+/// The clock sysvar, with its timestamp checked like `cluster_timestamp`
+/// (for handlers that also need the slot or epoch)
+pub fn cluster_clock() -> Result<Clock> {
+    let clock = Clock::get()?;
+    check_cluster_timestamp(clock.unix_timestamp)?;
+    Ok(clock)
+}
+
+/// Reject a cluster timestamp before `MIN_CLUSTER_TIMESTAMP`
+pub fn check_cluster_timestamp(now: i64) -> Result<()> {
+    require!(now >= MIN_CLUSTER_TIMESTAMP, LockboxError::InvalidClockTimestamp);
+    Ok(())
+}
+
+/// Reject a recorded timestamp that is further ahead of `now` than the
+/// tolerated drift (0 means "never recorded" and always passes)
+///
+/// # Errors
+/// * `TimestampInFuture` - `recorded` is more than `MAX_CLOCK_DRIFT`
+///   seconds ahead of `now`
+pub fn check_recorded_timestamp(recorded: i64, now: i64) -> Result<()> {
+    require!(
+        recorded <= now.saturating_add(MAX_CLOCK_DRIFT),
+        LockboxError::TimestampInFuture
+    );
+    Ok(())
+}
+
+/// Seconds from `recorded` to `now`, never negative
+///
+/// A recorded time slightly ahead of the clock (drift) counts as just now.
+pub fn elapsed_since(recorded: i64, now: i64) -> i64 {
+    now.saturating_sub(recorded).max(0)
+}
+
+/// Whether `at` has been reached: a start time or deadline takes effect at
+/// its exact second
+pub fn has_reached(at: i64, now: i64) -> bool {
+    now >= at
+}
+
+/// Whether `now` falls in the half-open window `[start, end)`: open from
+/// its first second, closed from `end` on
+pub fn is_within(start: i64, end: i64, now: i64) -> bool {
+    has_reached(start, now) && !has_reached(end, now)
+}
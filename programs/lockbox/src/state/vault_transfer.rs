@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+/// Longest a vault may stay listed for transfer (30 days)
+pub const MAX_VAULT_TRANSFER_LISTING: i64 = 30 * 24 * 60 * 60;
+
+/// Stage of a vault transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum VaultTransferStatus {
+    /// Offered to the buyer; the seller still owns the vault
+    Listed,
+    /// Paid for; the buyer owns the vault and must re-key it
+    Accepted,
+}
+
+/// Escrowed sale of a vault to a named buyer
+///
+/// Created by `list_vault_transfer`. The buyer's `accept_vault_transfer`
+/// pays the seller and swaps ownership in one instruction; the record then
+/// stays open until the buyer has re-encrypted every entry under their own
+/// key and calls `complete_vault_rekey`.
+///
+/// # PDA Derivation
+/// Seeds: ["vault_transfer", master_lockbox]
+#[account]
+#[derive(InitSpace)]
+pub struct VaultTransfer {
+    /// Account schema version (bumped by `migrate_account_vX`)
+    pub version: u8,
+
+    /// Master lockbox being sold
+    pub master_lockbox: Pubkey,
+
+    /// Owner listing the vault; receives the price and the rent
+    pub seller: Pubkey,
+
+    /// Only wallet allowed to accept
+    pub buyer: Pubkey,
+
+    /// Price in lamports, paid to the seller on acceptance
+    pub price: u64,
+
+    /// Current stage
+    pub status: VaultTransferStatus,
+
+    /// Unix timestamp the vault was listed
+    pub listed_at: i64,
+
+    /// Listing can no longer be accepted from this time on
+    pub expires_at: i64,
+
+    /// Unix timestamp ownership changed hands (0 while listed)
+    pub accepted_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl VaultTransfer {
+    /// Current account schema version
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Seeds for PDA derivation
+    pub const SEEDS_PREFIX: &'static [u8] = b"vault_transfer";
+
+    /// Whether `buyer` may still accept at `current_timestamp`
+    pub fn is_open_to(&self, buyer: &Pubkey, current_timestamp: i64) -> bool {
+        self.status == VaultTransferStatus::Listed
+            && self.buyer == *buyer
+            && current_timestamp < self.expires_at
+    }
+}
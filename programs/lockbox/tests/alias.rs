@@ -0,0 +1,154 @@
+/*!
+ * HANDLE ALIAS TESTS
+ *
+ * Tests for:
+ * - Only the current holder can transfer a handle
+ * - Handles can only be transferred to owners with a vault
+ */
+
+#[cfg(test)]
+mod alias_tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{TransferAlias, TransferAliasBumps};
+    use lockbox::state::{
+        Alias, MasterLockbox, OwnerKind, SubscriptionStatus, SubscriptionTier, DEFAULT_DUNNING_WINDOW,
+        MAX_RECENT_ENTRIES,
+    };
+
+    const HANDLE: [u8; 32] = [0xA1; 32];
+
+    fn alias(owner: Pubkey) -> (Pubkey, Alias) {
+        let (address, bump) =
+            Pubkey::find_program_address(&[Alias::SEEDS_PREFIX, HANDLE.as_ref()], &lockbox::ID);
+        let alias = Alias {
+            version: Alias::CURRENT_VERSION,
+            handle_hash: HANDLE,
+            owner,
+            registered_at: 1_700_000_000,
+            bump,
+        };
+        (address, alias)
+    }
+
+    fn master_lockbox(owner: Pubkey) -> (Pubkey, MasterLockbox) {
+        let (address, bump) = Pubkey::find_program_address(
+            &[MasterLockbox::SEEDS_PREFIX, owner.as_ref()],
+            &lockbox::ID,
+        );
+        let lockbox = MasterLockbox {
+            version: MasterLockbox::CURRENT_VERSION,
+            owner,
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 1,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: owner,
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump,
+        };
+        (address, lockbox)
+    }
+
+    fn account_info(
+        key: Pubkey,
+        is_signer: bool,
+        owner: &'static Pubkey,
+        data: Vec<u8>,
+    ) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        account_info(key, false, &lockbox::ID, data)
+    }
+
+    fn transfer(
+        holder: Pubkey,
+        signer: Pubkey,
+        recipient_vault: AccountInfo<'static>,
+        new_owner: Pubkey,
+    ) -> Result<TransferAlias<'static>> {
+        let (alias_key, alias) = alias(holder);
+        let accounts = vec![
+            program_account(alias_key, &alias),
+            recipient_vault,
+            account_info(signer, true, &anchor_lang::system_program::ID, Vec::new()),
+        ];
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        let ix_data = new_owner.try_to_vec().unwrap();
+        TransferAlias::try_accounts(
+            &lockbox::ID,
+            &mut accounts,
+            &ix_data,
+            &mut TransferAliasBumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_transfer_requires_holder_and_recipient_vault() {
+        let (holder, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (vault_key, vault) = master_lockbox(recipient);
+        let vault_info = || program_account(vault_key, &vault);
+
+        assert!(transfer(holder, holder, vault_info(), recipient).is_ok());
+
+        let err = transfer(holder, recipient, vault_info(), recipient).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+
+        // Recipient without a master lockbox
+        let empty = account_info(vault_key, false, &anchor_lang::system_program::ID, Vec::new());
+        let err = transfer(holder, holder, empty, recipient).err().unwrap();
+        assert_eq!(err, ErrorCode::AccountOwnedByWrongProgram.into());
+
+        // Vault of someone other than the named recipient
+        let err = transfer(holder, holder, vault_info(), Pubkey::new_unique()).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+    }
+}
@@ -0,0 +1,371 @@
+/*!
+ * CATEGORY REGISTRY TESTS
+ *
+ * Tests for:
+ * - Per-category entry and byte usage tracking
+ * - Entry instructions must pass the registry and index once they exist
+ * - The category index: ordering, lookups, limit and rebuilds
+ * - Closing a non-empty chunk releases its entries
+ */
+
+#[cfg(test)]
+mod category_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::category_index::category_index_entries;
+    use lockbox::instructions::{release_chunk_entries, StorePasswordEntry, StorePasswordEntryBumps};
+    use lockbox::state::{
+        Category, CategoryIndex, CategoryRegistry, DataEntryHeader, MasterLockbox, OwnerKind, PasswordEntryType,
+        SecurityPolicy, StorageChunk, StorageType, SubscriptionStatus, SubscriptionTier, DEFAULT_DUNNING_WINDOW,
+        MAX_CATEGORY_INDEX_ENTRIES, MAX_RECENT_ENTRIES,
+    };
+    use std::collections::BTreeSet;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn registry_with(ids: &[u8]) -> CategoryRegistry {
+        let mut registry = CategoryRegistry {
+            version: CategoryRegistry::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            master_lockbox: Pubkey::new_unique(),
+            categories: Vec::new(),
+            next_category_id: 0,
+            created_at: NOW,
+            bump: 255,
+        };
+        for id in ids {
+            let category = Category::new(*id, vec![0u8; 40], 0, 0, None, NOW).unwrap();
+            registry.add_category(category).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn test_record_usage_tracks_entries_and_bytes() {
+        let mut registry = registry_with(&[1, 2]);
+
+        registry.record_usage(1, 1, 400);
+        registry.record_usage(1, 1, 600);
+        registry.record_usage(1, 0, -100);
+        registry.record_usage(2, 1, 50);
+        registry.record_usage(1, -1, -400);
+
+        let work = registry.get_category(1).unwrap();
+        assert_eq!((work.entry_count, work.bytes_used), (1, 500));
+        let personal = registry.get_category(2).unwrap();
+        assert_eq!((personal.entry_count, personal.bytes_used), (1, 50));
+    }
+
+    #[test]
+    fn test_record_usage_ignores_unknown_categories_and_saturates() {
+        let mut registry = registry_with(&[1]);
+
+        registry.record_usage(7, 1, 100);
+        registry.record_usage(1 << 16, 1, 100);
+        registry.record_usage(1, -1, -100);
+
+        let category = registry.get_category(1).unwrap();
+        assert_eq!((category.entry_count, category.bytes_used), (0, 0));
+        assert_eq!(registry.categories.len(), 1);
+    }
+
+    fn new_lockbox(owner: Pubkey, bump: u8) -> MasterLockbox {
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(owner, bump, NOW).unwrap();
+        lockbox
+    }
+
+    /// Account leaked for the `'static` context
+    fn leaked_account(key: Pubkey, is_signer: bool, owner: &'static Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        leaked_account(key, false, &lockbox::ID, data)
+    }
+
+    /// Omitted optional account
+    fn omitted() -> AccountInfo<'static> {
+        leaked_account(lockbox::ID, false, &lockbox::ID, Vec::new())
+    }
+
+    /// Accounts of `store_password_entry` for a vault with or without a
+    /// registry and index, passing them or not
+    fn store_accounts(
+        has_registry: bool,
+        pass_registry: bool,
+        has_index: bool,
+        pass_index: bool,
+    ) -> Result<StorePasswordEntry<'static>> {
+        let owner = Pubkey::new_unique();
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &lockbox::ID);
+        let (master, master_bump) = pda(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()]);
+        let (chunk_key, chunk_bump) = pda(&[StorageChunk::SEEDS_PREFIX, master.as_ref(), &0u16.to_le_bytes()]);
+        let (policy_key, _) = pda(&[SecurityPolicy::SEEDS_PREFIX, master.as_ref()]);
+        let (registry_key, registry_bump) = pda(&[CategoryRegistry::SEEDS_PREFIX, master.as_ref()]);
+        let (index_key, index_bump) = pda(&[CategoryIndex::SEEDS_PREFIX, master.as_ref()]);
+
+        let mut lockbox = new_lockbox(owner, master_bump);
+        lockbox.has_category_registry = has_registry;
+        lockbox.has_category_index = has_index;
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk.initialize(master, owner, 0, 1024, StorageType::Passwords, chunk_bump, NOW).unwrap();
+        let mut registry = registry_with(&[1]);
+        registry.bump = registry_bump;
+        let mut index = empty_index();
+        index.bump = index_bump;
+
+        let system = &anchor_lang::system_program::ID;
+        let accounts = vec![
+            program_account(master, &lockbox),
+            program_account(chunk_key, &chunk),
+            leaked_account(owner, true, system, Vec::new()),
+            leaked_account(policy_key, false, system, Vec::new()),
+            if pass_registry { program_account(registry_key, &registry) } else { omitted() },
+            if pass_index { program_account(index_key, &index) } else { omitted() },
+            omitted(),
+            omitted(),
+            omitted(),
+            omitted(),
+        ];
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        StorePasswordEntry::try_accounts(
+            &lockbox::ID,
+            &mut accounts,
+            &0u16.to_le_bytes(),
+            &mut StorePasswordEntryBumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_registry_required_once_initialized() {
+        assert!(store_accounts(false, false, false, false).is_ok());
+        assert!(store_accounts(true, true, false, false).is_ok());
+
+        // Leaving it out would let the counts drift
+        let err = store_accounts(true, false, false, false).err().unwrap();
+        assert_eq!(err, LockboxError::CategoryRegistryRequired.into());
+    }
+
+    #[test]
+    fn test_index_required_once_initialized() {
+        assert!(store_accounts(false, false, true, true).is_ok());
+        assert!(store_accounts(true, true, true, true).is_ok());
+
+        // Leaving it out would let the index drift
+        let err = store_accounts(false, false, true, false).err().unwrap();
+        assert_eq!(err, LockboxError::CategoryIndexRequired.into());
+    }
+
+    fn empty_index() -> CategoryIndex {
+        CategoryIndex {
+            version: CategoryIndex::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            entries: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_category_index_lookup() {
+        let mut index = empty_index();
+        index.insert(2, 9).unwrap();
+        index.insert(1, 5).unwrap();
+        index.insert(2, 3).unwrap();
+        index.insert(2, 3).unwrap();
+        assert_eq!(index.entries.len(), 3);
+        assert_eq!(index.entries_in(2), vec![3, 9]);
+        assert_eq!(index.entries_in(1), vec![5]);
+        assert!(index.entries_in(0).is_empty());
+
+        assert!(index.remove(2, 3));
+        assert!(!index.remove(2, 3));
+        assert!(!index.remove(1, 9));
+        assert_eq!(index.entries_in(2), vec![9]);
+
+        // Sized like the other growable accounts
+        let mut data = Vec::new();
+        index.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), CategoryIndex::space_for(index.entries.len()));
+    }
+
+    #[test]
+    fn test_category_index_is_bounded() {
+        let mut index = empty_index();
+        for entry_id in 0..MAX_CATEGORY_INDEX_ENTRIES as u64 {
+            index.insert(0, entry_id).unwrap();
+        }
+        let err = index.insert(1, 0).unwrap_err();
+        assert_eq!(err, LockboxError::CategoryIndexFull.into());
+        // Re-inserting an indexed entry is still fine
+        index.insert(0, 0).unwrap();
+    }
+
+    /// Chunk holding `(entry_id, category, flags)` entries of 40 bytes each
+    fn chunk_with(entries: &[(u64, u32, u8)]) -> StorageChunk {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(Pubkey::default(), Pubkey::default(), 0, 1024, StorageType::Passwords, 255, NOW)
+            .unwrap();
+        for (entry_id, category, flags) in entries {
+            let header = DataEntryHeader {
+                entry_id: *entry_id,
+                offset: chunk.current_size,
+                size: 40,
+                entry_type: PasswordEntryType::Login,
+                category: *category,
+                title_hash: [0u8; 32],
+                created_at: NOW,
+                last_modified: NOW,
+                access_count: 0,
+                flags: *flags,
+                next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+                next_offset: 0,
+                tag_mask: 0,
+                rotate_after: 0,
+                parent_entry: 0,
+                linked_entry_ids: Vec::new(),
+            };
+            chunk.add_entry(header, vec![0u8; 40], NOW).unwrap();
+        }
+        chunk
+    }
+
+    #[test]
+    fn test_category_index_rebuilt_from_headers() {
+        let chunk = chunk_with(&[
+            (4, 1, 0),
+            (2, 1, DataEntryHeader::FLAG_TRASHED),
+            (3, 0, DataEntryHeader::FLAG_ALIAS),
+            (5, 0, DataEntryHeader::FLAG_CONTINUATION),
+            (1, 7, 0),
+        ]);
+
+        // Trashed entries stay indexed until purged; aliases and
+        // continuation segments are never indexed
+        let mut index = empty_index();
+        index.entries = category_index_entries(&[chunk]);
+        assert_eq!(index.entries_in(1), vec![2, 4]);
+        assert_eq!(index.entries_in(7), vec![1]);
+        assert!(index.entries_in(0).is_empty());
+    }
+
+    #[test]
+    fn test_closing_non_empty_chunk_releases_categories() {
+        let chunk = chunk_with(&[
+            (4, 1, 0),
+            (2, 1, DataEntryHeader::FLAG_TRASHED),
+            (3, 1, DataEntryHeader::FLAG_ALIAS),
+            (5, 1, DataEntryHeader::FLAG_CONTINUATION),
+            (1, 2, 0),
+        ]);
+        let mut registry = registry_with(&[1, 2]);
+        // Entry 5 starts in another chunk, which stays open
+        registry.record_usage(1, 3, 200);
+        registry.record_usage(2, 1, 40);
+        let mut index = empty_index();
+        for (category, entry_id) in [(1, 2), (1, 4), (1, 5), (2, 1)] {
+            index.insert(category, entry_id).unwrap();
+        }
+        let mut lockbox = new_lockbox(Pubkey::new_unique(), 255);
+        lockbox.total_entries = 5;
+        lockbox.recent_entries_enabled = true;
+        lockbox.record_recent_entry(4);
+
+        release_chunk_entries(&mut lockbox, &chunk, Some(&mut registry), Some(&mut index));
+
+        // Aliases count as entries but not toward categories; the
+        // continuation's bytes leave with it while its entry lives on
+        assert_eq!(lockbox.total_entries, 1);
+        assert!(!lockbox.recent_entries.contains(&4));
+        let work = registry.get_category(1).unwrap();
+        assert_eq!((work.entry_count, work.bytes_used), (1, 80));
+        let personal = registry.get_category(2).unwrap();
+        assert_eq!((personal.entry_count, personal.bytes_used), (0, 0));
+        assert_eq!(index.entries_in(1), vec![5]);
+        assert!(index.entries_in(2).is_empty());
+    }
+}
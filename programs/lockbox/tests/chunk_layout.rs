@@ -0,0 +1,119 @@
+/*!
+ * CHUNK LAYOUT RESULTS
+ *
+ * Times the `chunk_layout` benchmark scenarios (median of a few runs each)
+ * and writes them as JSON for CI to track between commits, to
+ * `$LOCKBOX_BENCH_RESULTS` or `chunk_layout.json` in Cargo's test temp
+ * directory. `cargo bench -p lockbox --bench chunk_layout` gives the
+ * precise numbers; this keeps a coarse trend on every test run.
+ */
+
+#[path = "../benches/common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod chunk_layout_tests {
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    use anchor_lang::AnchorSerialize;
+    use lockbox::state::StorageChunk;
+
+    use super::common::{self, Layout};
+
+    const RUNS: usize = 51;
+
+    struct Timing {
+        group: &'static str,
+        op: &'static str,
+        layout: Layout,
+        median_ns: u128,
+    }
+
+    fn median_ns<T, U>(mut setup: impl FnMut() -> T, mut op: impl FnMut(T) -> U) -> u128 {
+        let mut samples: Vec<u128> = (0..RUNS)
+            .map(|_| {
+                let input = setup();
+                let start = Instant::now();
+                black_box(op(input));
+                start.elapsed().as_nanos()
+            })
+            .collect();
+        samples.sort_unstable();
+        samples[RUNS / 2]
+    }
+
+    /// Time a chunk operation, checking the chunk stays consistent
+    fn time_op(
+        layout: Layout,
+        chunk: StorageChunk,
+        op: impl Fn(&mut StorageChunk, Layout),
+    ) -> u128 {
+        median_ns(
+            || chunk.clone(),
+            |mut chunk| {
+                op(&mut chunk, layout);
+                assert!(chunk.is_entry_count_consistent());
+                assert_eq!(chunk.current_size as usize, chunk.encrypted_data.len());
+                chunk
+            },
+        )
+    }
+
+    fn to_json(timings: &[Timing]) -> String {
+        let results: Vec<String> = timings
+            .iter()
+            .map(|t| {
+                format!(
+                    "    {{\"group\": \"{}\", \"op\": \"{}\", \"capacity\": {}, \"entries\": {}, \"median_ns\": {}}}",
+                    t.group, t.op, t.layout.capacity, t.layout.entries, t.median_ns
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"benchmark\": \"chunk_layout\",\n  \"runs\": {},\n  \"results\": [\n{}\n  ]\n}}\n",
+            RUNS,
+            results.join(",\n")
+        )
+    }
+
+    #[test]
+    fn test_emit_chunk_layout_results() {
+        let mut timings = Vec::new();
+        for layout in common::layouts() {
+            let filled = common::filled_chunk(layout);
+            let ops: [(&'static str, u128); 4] = [
+                ("add", time_op(layout, common::chunk_with(layout, layout.entries - 1), common::add_last)),
+                ("update_in_place", time_op(layout, filled.clone(), common::update_in_place)),
+                ("update_grow", time_op(layout, filled.clone(), common::update_grow)),
+                ("delete", time_op(layout, filled.clone(), |chunk, _| common::delete_first(chunk))),
+            ];
+            for (op, median_ns) in ops {
+                timings.push(Timing { group: "chunk_ops", op, layout, median_ns });
+            }
+
+            // A header-split layout decodes only the headers
+            let account = common::account_data(&filled);
+            let headers = filled.entry_headers.try_to_vec().unwrap();
+            assert!(headers.len() < account.len());
+            timings.push(Timing {
+                group: "account_codec",
+                op: "full_chunk",
+                layout,
+                median_ns: median_ns(|| &account, |data| common::account_round_trip(data)),
+            });
+            timings.push(Timing {
+                group: "account_codec",
+                op: "headers_only",
+                layout,
+                median_ns: median_ns(|| &headers, |data| common::headers_round_trip(data)),
+            });
+        }
+        assert_eq!(timings.len(), common::layouts().len() * 6);
+
+        let path = std::env::var("LOCKBOX_BENCH_RESULTS")
+            .unwrap_or_else(|_| concat!(env!("CARGO_TARGET_TMPDIR"), "/chunk_layout.json").to_string());
+        std::fs::write(&path, to_json(&timings)).unwrap();
+        println!("Chunk layout results written to {}", path);
+    }
+}
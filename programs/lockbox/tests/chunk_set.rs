@@ -0,0 +1,301 @@
+/*!
+ * CHUNK SET TESTS
+ *
+ * Tests for:
+ * - Loading extra chunks from remaining accounts
+ * - Rejection of unregistered, read-only, duplicate and excluded chunks
+ * - Writing chunks back and syncing usage into the master lockbox
+ * - Layout epoch agreement between chunks and their registrations
+ * - Operations built before a move failing on the epoch they carry
+ * - Orphan detection against the chunk list
+ */
+
+#[cfg(test)]
+mod chunk_set_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{find_orphans, ChunkSet, OrphanKind, OrphanReport};
+    use lockbox::state::{
+        DataEntryHeader, MasterLockbox, OwnerKind, PasswordEntryType, StorageChunk,
+        StorageChunkInfo, StorageType, SubscriptionStatus, SubscriptionTier, DEFAULT_DUNNING_WINDOW,
+        MAX_RECENT_ENTRIES,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_lockbox(chunks: &[(Pubkey, u16)]) -> MasterLockbox {
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(Pubkey::new_unique(), 255, NOW).unwrap();
+        for (address, index) in chunks {
+            lockbox
+                .add_chunk(StorageChunkInfo {
+                    chunk_address: *address,
+                    chunk_index: *index,
+                    max_capacity: StorageChunk::MIN_CHUNK_SIZE,
+                    size_used: 0,
+                    data_type: StorageType::Passwords,
+                    created_at: NOW,
+                    last_modified: NOW,
+                    layout_epoch: 0,
+                })
+                .unwrap();
+        }
+        lockbox
+    }
+
+    fn chunk_data(master_lockbox: Pubkey, chunk_index: u16) -> Vec<u8> {
+        chunk_data_with_bump(master_lockbox, chunk_index, 255)
+    }
+
+    fn chunk_data_with_bump(master_lockbox: Pubkey, chunk_index: u16, bump: u8) -> Vec<u8> {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                master_lockbox,
+                Pubkey::new_unique(),
+                chunk_index,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                bump,
+                NOW,
+            )
+            .unwrap();
+
+        let mut data = Vec::new();
+        chunk.try_serialize(&mut data).unwrap();
+        data.resize(StorageChunk::BASE_SPACE + StorageChunk::MIN_CHUNK_SIZE as usize, 0);
+        data
+    }
+
+    fn header(entry_id: u64, size: u32) -> DataEntryHeader {
+        DataEntryHeader {
+            entry_id,
+            offset: 0,
+            size,
+            entry_type: PasswordEntryType::Login,
+            category: 0,
+            title_hash: [0u8; 32],
+            created_at: NOW,
+            last_modified: NOW,
+            access_count: 0,
+            flags: 0,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_load_validates_and_commits() {
+        let master_key = Pubkey::new_unique();
+        let (a, b, stray) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut master_data = Vec::new();
+        new_lockbox(&[(a, 1), (b, 2)]).try_serialize(&mut master_data).unwrap();
+        let mut master_lamports = 0u64;
+        let master_info = AccountInfo::new(
+            &master_key, false, true, &mut master_lamports, &mut master_data,
+            &lockbox::ID, false, 0,
+        );
+        let mut master = Account::<MasterLockbox>::try_from(&master_info).unwrap();
+
+        let (mut la, mut lb, mut ls) = (0u64, 0u64, 0u64);
+        let (mut da, mut db, mut ds) = (
+            chunk_data(master_key, 1),
+            chunk_data(master_key, 2),
+            chunk_data(master_key, 3),
+        );
+        let info_a = AccountInfo::new(&a, false, true, &mut la, &mut da, &lockbox::ID, false, 0);
+        let info_b = AccountInfo::new(&b, false, false, &mut lb, &mut db, &lockbox::ID, false, 0);
+        let info_stray = AccountInfo::new(&stray, false, true, &mut ls, &mut ds, &lockbox::ID, false, 0);
+
+        // Unregistered, read-only, duplicated, and excluded chunks
+        let rejected = [
+            (vec![info_stray.clone()], vec![]),
+            (vec![info_b.clone()], vec![]),
+            (vec![info_a.clone(), info_a.clone()], vec![]),
+            (vec![info_a.clone()], vec![a]),
+        ];
+        for (accounts, exclude) in &rejected {
+            let err = ChunkSet::load(accounts, &master, exclude).err().unwrap();
+            assert_eq!(err, LockboxError::InvalidChunkAccount.into());
+        }
+
+        let accounts = [info_a.clone()];
+        let mut chunks = ChunkSet::load(&accounts, &master, &[]).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks.get_mut(2).is_none());
+        chunks
+            .get_mut(1)
+            .unwrap()
+            .add_entry(header(7, 40), vec![7u8; 40], NOW)
+            .unwrap();
+        chunks.commit(&mut master).unwrap();
+
+        assert_eq!(master.storage_used, 40);
+        let written = StorageChunk::try_deserialize(&mut &info_a.try_borrow_data().unwrap()[..]).unwrap();
+        assert_eq!(written.get_entry_data(7).unwrap(), vec![7u8; 40]);
+    }
+
+    #[test]
+    fn test_load_rejects_stale_chunk_layout() {
+        let master_key = Pubkey::new_unique();
+        let a = Pubkey::new_unique();
+        let mut lockbox = new_lockbox(&[(a, 1)]);
+
+        // Restructure a copy of the chunk; the stored chunk keeps epoch 0
+        let mut da = chunk_data(master_key, 1);
+        let mut restructured = StorageChunk::try_deserialize(&mut &da[..]).unwrap();
+        lockbox.bump_chunk_layout(&mut restructured).unwrap();
+        assert_eq!(restructured.layout_epoch, 1);
+        assert_eq!(lockbox.storage_chunks[0].layout_epoch, 1);
+        lockbox.check_chunk_layout(&restructured).unwrap();
+
+        let mut master_data = Vec::new();
+        lockbox.try_serialize(&mut master_data).unwrap();
+        let mut master_lamports = 0u64;
+        let master_info = AccountInfo::new(
+            &master_key, false, true, &mut master_lamports, &mut master_data,
+            &lockbox::ID, false, 0,
+        );
+        let master = Account::<MasterLockbox>::try_from(&master_info).unwrap();
+
+        let mut la = 0u64;
+        let info_a = AccountInfo::new(&a, false, true, &mut la, &mut da, &lockbox::ID, false, 0);
+        let accounts = [info_a];
+        let err = ChunkSet::load(&accounts, &master, &[]).err().unwrap();
+        assert_eq!(err, LockboxError::StaleChunkLayout.into());
+    }
+
+    #[test]
+    fn test_move_invalidates_operations_built_before_it() {
+        let master_key = Pubkey::new_unique();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lockbox = new_lockbox(&[(a, 1), (b, 2)]);
+        let mut source = StorageChunk::try_deserialize(&mut &chunk_data(master_key, 1)[..]).unwrap();
+        let mut dest = StorageChunk::try_deserialize(&mut &chunk_data(master_key, 2)[..]).unwrap();
+        source.add_entry(header(7, 40), vec![7u8; 40], NOW).unwrap();
+        let mut second = header(8, 40);
+        second.offset = 40;
+        source.add_entry(second, vec![8u8; 40], NOW).unwrap();
+
+        // The client reads both chunks and queues two operations
+        let (source_epoch, dest_epoch) = (source.layout_epoch, dest.layout_epoch);
+        source.check_layout_epoch(source_epoch).unwrap();
+
+        // Another device moves entry 7 across, as move_password_entry does
+        let (mut moved, data) = source.take_entry(7, NOW).unwrap();
+        moved.offset = dest.current_size;
+        dest.add_entry(moved, data, NOW).unwrap();
+        lockbox.bump_chunk_layout(&mut source).unwrap();
+        lockbox.bump_chunk_layout(&mut dest).unwrap();
+
+        // The second operation carries the old epoch and fails on either chunk
+        for (chunk, epoch) in [(&source, source_epoch), (&dest, dest_epoch)] {
+            let err = chunk.check_layout_epoch(epoch).err().unwrap();
+            assert_eq!(err, LockboxError::StaleChunkLayout.into());
+            lockbox.check_chunk_layout(chunk).unwrap();
+        }
+
+        // Rebuilt against the fresh layout, it goes through
+        source.check_layout_epoch(source_epoch + 1).unwrap();
+        assert_eq!(source.get_entry_data(8).unwrap(), vec![8u8; 40]);
+    }
+
+    #[test]
+    fn test_find_orphans_reports_both_directions() {
+        let master_key = Pubkey::new_unique();
+        let chunk_pda = |index: u16| {
+            Pubkey::find_program_address(
+                &[StorageChunk::SEEDS_PREFIX, master_key.as_ref(), &index.to_le_bytes()],
+                &lockbox::ID,
+            )
+        };
+        let (live, live_bump) = chunk_pda(1);
+        let (missing, _) = chunk_pda(2);
+        let (stray, stray_bump) = chunk_pda(3);
+        let forged = Pubkey::new_unique();
+        let lockbox = new_lockbox(&[(live, 1), (missing, 2)]);
+
+        let (mut l1, mut l2, mut l3, mut l4) = (1u64, 0u64, 1u64, 1u64);
+        let (mut d1, mut d2, mut d3, mut d4) = (
+            chunk_data_with_bump(master_key, 1, live_bump),
+            Vec::new(),
+            chunk_data_with_bump(master_key, 3, stray_bump),
+            chunk_data_with_bump(master_key, 4, 255),
+        );
+        let system = Pubkey::default();
+        let candidates = [
+            AccountInfo::new(&live, false, false, &mut l1, &mut d1, &lockbox::ID, false, 0),
+            AccountInfo::new(&missing, false, false, &mut l2, &mut d2, &system, false, 0),
+            AccountInfo::new(&stray, false, false, &mut l3, &mut d3, &lockbox::ID, false, 0),
+            // Not at the canonical PDA for its index, so ignored
+            AccountInfo::new(&forged, false, false, &mut l4, &mut d4, &lockbox::ID, false, 0),
+        ];
+
+        let orphans = find_orphans(&lockbox, &master_key, &candidates);
+        assert_eq!(orphans, vec![
+            OrphanReport { account: missing, chunk_index: 2, kind: OrphanKind::MissingChunk },
+            OrphanReport { account: stray, chunk_index: 3, kind: OrphanKind::UnregisteredChunk },
+        ]);
+    }
+}
@@ -0,0 +1,80 @@
+/*!
+ * CONTACT BOOK TESTS
+ *
+ * Tests for:
+ * - Tier contact limits and duplicate rejection
+ * - Relabeling and removing contacts
+ * - Account space tracks the number of contacts
+ */
+
+#[cfg(test)]
+mod contact_book_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{Contact, ContactBook, SubscriptionTier};
+
+    fn empty_book() -> ContactBook {
+        ContactBook {
+            version: ContactBook::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            master_lockbox: Pubkey::new_unique(),
+            contacts: Vec::new(),
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_tier_limit_and_duplicates() {
+        let mut book = empty_book();
+        let limit = SubscriptionTier::Free.max_contacts();
+        let first = Pubkey::new_unique();
+        book.add_contact(first, vec![1u8; 40], limit, 0).unwrap();
+
+        let err = book.add_contact(first, vec![2u8; 40], limit, 1).unwrap_err();
+        assert_eq!(err, LockboxError::ContactBookEntryExists.into());
+
+        for i in 1..limit {
+            book.add_contact(Pubkey::new_unique(), vec![0u8; 40], limit, i as i64).unwrap();
+        }
+        let err = book.add_contact(Pubkey::new_unique(), vec![0u8; 40], limit, 99).unwrap_err();
+        assert_eq!(err, LockboxError::ContactBookFull.into());
+
+        // Upgrading raises the cap
+        let basic = SubscriptionTier::Basic.max_contacts();
+        book.add_contact(Pubkey::new_unique(), vec![0u8; 40], basic, 100).unwrap();
+
+        let err = book
+            .add_contact(Pubkey::new_unique(), vec![0u8; Contact::MAX_LABEL_SIZE + 1], basic, 0)
+            .unwrap_err();
+        assert_eq!(err, LockboxError::InvalidDataSize.into());
+    }
+
+    #[test]
+    fn test_update_and_remove_contact() {
+        let mut book = empty_book();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        book.add_contact(alice, vec![1u8; 40], 10, 0).unwrap();
+        book.add_contact(bob, vec![2u8; 40], 10, 0).unwrap();
+
+        book.get_contact_mut(&bob).unwrap().label_encrypted = vec![3u8; 48];
+        book.remove_contact(&alice).unwrap();
+
+        assert_eq!(book.contacts.len(), 1);
+        assert_eq!(book.contacts[0].label_encrypted, vec![3u8; 48]);
+        let err = book.remove_contact(&alice).unwrap_err();
+        assert_eq!(err, LockboxError::ContactBookEntryNotFound.into());
+    }
+
+    #[test]
+    fn test_space_fits_full_labels() {
+        let mut book = empty_book();
+        for _ in 0..3 {
+            book.add_contact(Pubkey::new_unique(), vec![0u8; Contact::MAX_LABEL_SIZE], 10, 0)
+                .unwrap();
+        }
+        let mut data = Vec::new();
+        book.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ContactBook::space_for(3));
+    }
+}
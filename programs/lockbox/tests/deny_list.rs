@@ -0,0 +1,57 @@
+/*!
+ * DENY-LIST TESTS
+ *
+ * Tests for:
+ * - Denying and allowing keys
+ * - Duplicate and unknown keys
+ * - The deny-list size limit
+ */
+
+#[cfg(test)]
+mod deny_list_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{DenyList, MAX_DENIED_KEYS};
+
+    fn empty_list() -> DenyList {
+        DenyList {
+            version: DenyList::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            keys: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_deny_and_allow_keys() {
+        let mut list = empty_list();
+        let (stolen, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        list.deny(stolen).unwrap();
+        assert!(list.is_denied(&stolen));
+        assert!(!list.is_denied(&other));
+
+        let err = list.deny(stolen).unwrap_err();
+        assert_eq!(err, LockboxError::KeyAlreadyDenied.into());
+
+        list.allow(&stolen).unwrap();
+        assert!(!list.is_denied(&stolen));
+        let err = list.allow(&stolen).unwrap_err();
+        assert_eq!(err, LockboxError::KeyNotDenied.into());
+    }
+
+    #[test]
+    fn test_deny_list_is_bounded() {
+        let mut list = empty_list();
+        for _ in 0..MAX_DENIED_KEYS {
+            list.deny(Pubkey::new_unique()).unwrap();
+        }
+        let err = list.deny(Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, LockboxError::DenyListFull.into());
+
+        // Allowing a key frees a slot
+        let first = list.keys[0];
+        list.allow(&first).unwrap();
+        list.deny(Pubkey::new_unique()).unwrap();
+    }
+}
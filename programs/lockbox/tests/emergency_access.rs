@@ -0,0 +1,235 @@
+/*!
+ * EMERGENCY ACCESS TESTS
+ *
+ * Tests for:
+ * - Grants recorded on activation
+ * - Countdown notification scheduling
+ * - Tier-dependent contact and guardian limits
+ * - Contact validation shared by single and batch adds
+ * - Lapsed contact invitations expire while accepted contacts stay
+ * - Export consent gates FullAccess-level grants
+ */
+
+#[cfg(test)]
+mod emergency_access_tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::hash::hash;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{
+        EmergencyAccess, EmergencyAccessLevel, EmergencyContact, EmergencyContactStatus,
+        EmergencyStatus, ExportConsent, Guardian, RecoveryConfig, SubscriptionTier, DEFAULT_GRACE_PERIOD,
+        DEFAULT_INACTIVITY_PERIOD, DEFAULT_INVITATION_TTL, MAX_EMERGENCY_CONTACTS, MAX_GUARDIANS, MIN_NOTIFICATION_INTERVAL,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn contact(status: EmergencyContactStatus, access_level: EmergencyAccessLevel) -> EmergencyContact {
+        EmergencyContact {
+            contact_pubkey: Pubkey::new_unique(),
+            contact_name_encrypted: vec![1u8; 16],
+            access_level,
+            encrypted_key: vec![7u8; 104],
+            added_at: NOW,
+            access_granted_at: None,
+            status,
+            invited_at: NOW,
+            expires_at: NOW + DEFAULT_INVITATION_TTL,
+        }
+    }
+
+    fn new_emergency_access(contacts: Vec<EmergencyContact>) -> EmergencyAccess {
+        EmergencyAccess {
+            version: EmergencyAccess::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            emergency_contacts: contacts,
+            inactivity_period: DEFAULT_INACTIVITY_PERIOD,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            notification_interval: 0,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            last_activity: NOW,
+            countdown_started: None,
+            last_notified_at: 0,
+            status: EmergencyStatus::Active,
+            created_at: NOW,
+            export_consent: None,
+            bump: 255,
+        }
+    }
+
+    fn consent(contacts: &[Pubkey]) -> ExportConsent {
+        ExportConsent {
+            contacts: contacts.to_vec(),
+            scope_hash: [1u8; 32],
+            conditions_hash: [2u8; 32],
+            signed_at: NOW,
+        }
+    }
+
+    #[test]
+    fn test_activation_returns_grants_for_active_contacts() {
+        let mut access = new_emergency_access(vec![
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::FullAccess),
+            contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly),
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::ViewOnly),
+        ]);
+        access.export_consent = Some(consent(&[access.emergency_contacts[0].contact_pubkey]));
+
+        let countdown = NOW + DEFAULT_INACTIVITY_PERIOD;
+        access.start_countdown(countdown);
+        assert!(!access.should_activate_emergency(countdown + DEFAULT_GRACE_PERIOD - 1));
+        assert!(access.should_activate_emergency(countdown + DEFAULT_GRACE_PERIOD));
+
+        let activated_at = countdown + DEFAULT_GRACE_PERIOD;
+        let grants = access.activate_emergency(activated_at);
+        assert_eq!(grants.len(), 2);
+        assert_eq!(grants[0].contact, access.emergency_contacts[0].contact_pubkey);
+        assert_eq!(grants[0].access_level, EmergencyAccessLevel::FullAccess);
+        assert_eq!(grants[1].contact, access.emergency_contacts[2].contact_pubkey);
+        assert_eq!(grants[1].key_hash, hash(&[7u8; 104]).to_bytes());
+
+        assert!(access.has_access_granted(&grants[1].contact));
+        assert!(!access.has_access_granted(&access.emergency_contacts[1].contact_pubkey));
+        assert_eq!(access.emergency_contacts[0].access_granted_at, Some(activated_at));
+    }
+
+    #[test]
+    fn test_countdown_notifications_follow_interval() {
+        let day = 86_400;
+        assert!(EmergencyAccess::is_notification_interval_valid(0, DEFAULT_GRACE_PERIOD));
+        assert!(EmergencyAccess::is_notification_interval_valid(day, DEFAULT_GRACE_PERIOD));
+        assert!(!EmergencyAccess::is_notification_interval_valid(MIN_NOTIFICATION_INTERVAL - 1, DEFAULT_GRACE_PERIOD));
+        assert!(!EmergencyAccess::is_notification_interval_valid(DEFAULT_GRACE_PERIOD + 1, DEFAULT_GRACE_PERIOD));
+
+        let mut access = new_emergency_access(vec![
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::ViewOnly),
+            contact(EmergencyContactStatus::Revoked, EmergencyAccessLevel::ViewOnly),
+        ]);
+        access.notification_interval = day;
+        assert!(!access.should_notify_contacts(NOW + day));
+
+        // The countdown start counts as the first notification
+        access.start_countdown(NOW);
+        assert_eq!(access.active_contacts().count(), 1);
+        assert!(!access.should_notify_contacts(NOW + day - 1));
+        assert!(access.should_notify_contacts(NOW + day));
+
+        access.last_notified_at = NOW + day;
+        assert!(!access.should_notify_contacts(NOW + day + 1));
+
+        access.notification_interval = 0;
+        assert!(!access.should_notify_contacts(NOW + 3 * day));
+        access.notification_interval = day;
+        access.cancel_countdown(NOW + 3 * day);
+        assert!(!access.should_notify_contacts(NOW + 5 * day));
+    }
+
+    #[test]
+    fn test_tier_limits_and_account_growth() {
+        assert_eq!(SubscriptionTier::Premium.max_emergency_contacts(), MAX_EMERGENCY_CONTACTS);
+        assert_eq!(SubscriptionTier::Pro.max_emergency_contacts(), 10);
+        assert_eq!(SubscriptionTier::Premium.max_guardians(), MAX_GUARDIANS);
+        assert_eq!(SubscriptionTier::Pro.max_guardians(), 20);
+
+        // Up to the default limit the account keeps its initial allocation
+        let base = 8 + EmergencyAccess::INIT_SPACE;
+        assert_eq!(EmergencyAccess::space_for(0), base);
+        assert_eq!(EmergencyAccess::space_for(MAX_EMERGENCY_CONTACTS), base);
+        assert_eq!(
+            EmergencyAccess::space_for(10),
+            base + 5 * EmergencyContact::INIT_SPACE
+        );
+
+        // A full Pro contact list still serializes into the grown account
+        let contacts = (0..10)
+            .map(|_| contact(EmergencyContactStatus::Active, EmergencyAccessLevel::FullAccess))
+            .map(|mut c| {
+                c.contact_name_encrypted = vec![1u8; 64];
+                c.encrypted_key = vec![7u8; 128];
+                c
+            })
+            .collect();
+        let mut data = Vec::new();
+        new_emergency_access(contacts).try_serialize(&mut data).unwrap();
+        assert!(data.len() <= EmergencyAccess::space_for(10));
+
+        assert_eq!(
+            RecoveryConfig::space_for(20),
+            8 + RecoveryConfig::INIT_SPACE + 10 * Guardian::INIT_SPACE
+        );
+    }
+
+    #[test]
+    fn test_contacts_unique_sized_and_limited() {
+        let mut access = new_emergency_access(Vec::new());
+        for _ in 0..MAX_EMERGENCY_CONTACTS {
+            access
+                .add_contact(
+                    contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly),
+                    MAX_EMERGENCY_CONTACTS,
+                )
+                .unwrap();
+        }
+        let extra = contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly);
+        let err = access.add_contact(extra.clone(), MAX_EMERGENCY_CONTACTS).unwrap_err();
+        assert_eq!(err, LockboxError::TooManyContacts.into());
+
+        let mut access = new_emergency_access(Vec::new());
+        access.add_contact(extra.clone(), MAX_EMERGENCY_CONTACTS).unwrap();
+        let err = access.add_contact(extra, MAX_EMERGENCY_CONTACTS).unwrap_err();
+        assert_eq!(err, LockboxError::ContactAlreadyExists.into());
+
+        let mut long_name = contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly);
+        long_name.contact_name_encrypted = vec![1u8; 65];
+        let err = access.add_contact(long_name, MAX_EMERGENCY_CONTACTS).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidNicknameSize.into());
+
+        let mut long_key = contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly);
+        long_key.encrypted_key = vec![7u8; 129];
+        let err = access.add_contact(long_key, MAX_EMERGENCY_CONTACTS).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidKeySize.into());
+        assert_eq!(access.emergency_contacts.len(), 1);
+    }
+
+    #[test]
+    fn test_lapsed_contact_invitations_expire() {
+        let pending = contact(EmergencyContactStatus::PendingAcceptance, EmergencyAccessLevel::ViewOnly);
+        let accepted = contact(EmergencyContactStatus::Active, EmergencyAccessLevel::FullAccess);
+        let mut access = new_emergency_access(vec![pending.clone(), accepted.clone()]);
+        let lapse = NOW + DEFAULT_INVITATION_TTL;
+
+        // Invitations lapse at `expires_at` itself
+        assert!(access.expire_pending_contacts(lapse - 1).is_empty());
+
+        let expired = access.expire_pending_contacts(lapse);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].contact_pubkey, pending.contact_pubkey);
+        assert!(access.get_contact(&pending.contact_pubkey).is_none());
+        assert_eq!(access.active_contact_count(), 1);
+        assert!(!accepted.is_invitation_expired(lapse + 1));
+    }
+
+    #[test]
+    fn test_export_consent_gates_full_access_grants() {
+        let mut access = new_emergency_access(vec![
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::ViewOnly),
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::FullAccess),
+            contact(EmergencyContactStatus::Active, EmergencyAccessLevel::TransferOwnership),
+        ]);
+        let [viewer, full, owner_heir] = [0, 1, 2].map(|i| access.emergency_contacts[i].clone());
+
+        // ViewOnly never needs consent; higher levels must be named
+        assert!(access.is_export_consented(&viewer));
+        assert!(!access.is_export_consented(&full));
+        access.export_consent = Some(consent(&[owner_heir.contact_pubkey]));
+        assert!(!access.is_export_consented(&full));
+        assert!(access.is_export_consented(&owner_heir));
+
+        // Unconsented contacts stay active rather than being granted
+        access.start_countdown(NOW);
+        let grants = access.activate_emergency(NOW + DEFAULT_GRACE_PERIOD);
+        let granted: Vec<Pubkey> = grants.iter().map(|g| g.contact).collect();
+        assert_eq!(granted, vec![viewer.contact_pubkey, owner_heir.contact_pubkey]);
+        assert_eq!(access.emergency_contacts[1].status, EmergencyContactStatus::Active);
+        assert!(!access.has_access_granted(&full.contact_pubkey));
+    }
+}
@@ -0,0 +1,156 @@
+/*!
+ * ENTRY COPY TRACKING TESTS
+ *
+ * Tests for:
+ * - Recording copies of an entry made in other vaults
+ * - Flagging copies stale when the source changes
+ * - Making room by dropping stale copies once the list is full
+ * - Listing an entry's grants (copies and emergency contacts)
+ */
+
+#[cfg(test)]
+mod entry_copies_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{entry_grants, EntryGrant};
+    use lockbox::state::{
+        EmergencyAccess, EmergencyAccessLevel, EmergencyContact, EmergencyContactStatus, EmergencyStatus,
+        EntryCopies, EntryCopy, MAX_ENTRY_COPIES, PRO_MAX_EMERGENCY_CONTACTS,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_list() -> EntryCopies {
+        EntryCopies {
+            version: EntryCopies::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            entry_id: 3,
+            copies: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    fn copy(entry_id: u64) -> EntryCopy {
+        EntryCopy {
+            target_vault: Pubkey::new_unique(),
+            entry_id,
+            copied_at: NOW + entry_id as i64,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_mark_stale_returns_only_fresh_copies() {
+        let mut list = new_list();
+        list.record(copy(1)).unwrap();
+        list.record(copy(2)).unwrap();
+
+        let flagged = list.mark_stale();
+        assert_eq!(flagged.iter().map(|c| c.entry_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(list.copies.iter().all(|c| c.stale));
+
+        // A later copy is fresh; earlier ones are not reported again
+        list.record(copy(3)).unwrap();
+        let flagged = list.mark_stale();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].entry_id, 3);
+        assert!(list.mark_stale().is_empty());
+    }
+
+    #[test]
+    fn test_full_list_drops_oldest_stale_copy() {
+        let mut list = new_list();
+        for id in 0..MAX_ENTRY_COPIES as u64 {
+            list.record(copy(id)).unwrap();
+        }
+        let err = list.record(copy(100)).unwrap_err();
+        assert_eq!(err, LockboxError::TooManyEntryCopies.into());
+
+        list.copies[4].stale = true;
+        list.copies[9].stale = true;
+        list.record(copy(100)).unwrap();
+
+        assert_eq!(list.copies.len(), MAX_ENTRY_COPIES);
+        assert!(list.copies.iter().all(|c| c.entry_id != 4));
+        assert!(list.copies.iter().any(|c| c.entry_id == 9));
+        assert_eq!(list.copies.last().unwrap().entry_id, 100);
+    }
+
+    fn contact(status: EmergencyContactStatus) -> EmergencyContact {
+        EmergencyContact {
+            contact_pubkey: Pubkey::new_unique(),
+            contact_name_encrypted: vec![],
+            access_level: EmergencyAccessLevel::ViewOnly,
+            encrypted_key: vec![],
+            added_at: NOW,
+            access_granted_at: None,
+            status,
+            invited_at: NOW,
+            expires_at: NOW,
+        }
+    }
+
+    fn emergency_access(contacts: Vec<EmergencyContact>) -> EmergencyAccess {
+        EmergencyAccess {
+            version: EmergencyAccess::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            emergency_contacts: contacts,
+            inactivity_period: 0,
+            grace_period: 0,
+            notification_interval: 0,
+            invitation_ttl: 0,
+            last_activity: NOW,
+            countdown_started: None,
+            last_notified_at: 0,
+            status: EmergencyStatus::Active,
+            created_at: NOW,
+            export_consent: None,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_entry_grants_lists_copies_then_live_contacts() {
+        assert!(entry_grants(None, None).is_empty());
+
+        let mut list = new_list();
+        list.record(copy(1)).unwrap();
+        list.mark_stale();
+        let contacts = vec![
+            contact(EmergencyContactStatus::PendingAcceptance),
+            contact(EmergencyContactStatus::Active),
+            contact(EmergencyContactStatus::Revoked),
+            contact(EmergencyContactStatus::AccessGranted),
+        ];
+        let (active, granted) = (contacts[1].contact_pubkey, contacts[3].contact_pubkey);
+        let config = emergency_access(contacts);
+
+        let grants = entry_grants(Some(&list), Some(&config));
+        assert_eq!(
+            grants,
+            vec![
+                EntryGrant::Copy { target_vault: list.copies[0].target_vault, entry_id: 1, stale: true },
+                EntryGrant::EmergencyContact { contact: active, access_level: EmergencyAccessLevel::ViewOnly },
+                EntryGrant::EmergencyAccessGranted {
+                    contact: granted,
+                    access_level: EmergencyAccessLevel::ViewOnly,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entry_grants_fit_return_data() {
+        let mut list = new_list();
+        for id in 0..MAX_ENTRY_COPIES as u64 {
+            list.record(copy(id)).unwrap();
+        }
+        let contacts = (0..PRO_MAX_EMERGENCY_CONTACTS)
+            .map(|_| contact(EmergencyContactStatus::Active))
+            .collect();
+        let config = emergency_access(contacts);
+
+        let grants = entry_grants(Some(&list), Some(&config));
+        assert!(grants.try_to_vec().unwrap().len() <= 1024);
+    }
+}
@@ -0,0 +1,58 @@
+/*!
+ * ENTRY NOTES TESTS
+ *
+ * Tests for:
+ * - Per-entry note cap and note size limit
+ * - Account space covers a full notes list
+ */
+
+#[cfg(test)]
+mod entry_notes_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::EntryNotes;
+
+    fn empty_notes() -> EntryNotes {
+        EntryNotes {
+            version: EntryNotes::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            entry_id: 1,
+            notes: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_note_cap_enforced() {
+        let mut notes = empty_notes();
+        for i in 0..EntryNotes::MAX_NOTES {
+            notes.append(vec![i as u8; 40], i as i64).unwrap();
+        }
+
+        let err = notes.append(vec![0u8; 40], 99).unwrap_err();
+        assert_eq!(err, LockboxError::EntryNotesFull.into());
+        assert_eq!(notes.notes.len(), EntryNotes::MAX_NOTES);
+    }
+
+    #[test]
+    fn test_oversized_note_rejected() {
+        let mut notes = empty_notes();
+        let err = notes
+            .append(vec![0u8; EntryNotes::MAX_NOTE_SIZE + 1], 0)
+            .unwrap_err();
+        assert_eq!(err, LockboxError::InvalidDataSize.into());
+    }
+
+    #[test]
+    fn test_full_notes_fit_allocated_space() {
+        let mut notes = empty_notes();
+        for _ in 0..EntryNotes::MAX_NOTES {
+            notes.append(vec![0u8; EntryNotes::MAX_NOTE_SIZE], 0).unwrap();
+        }
+
+        let mut data = Vec::new();
+        notes.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + EntryNotes::INIT_SPACE);
+    }
+}
@@ -0,0 +1,138 @@
+/*!
+ * ENTRY TAG TESTS
+ *
+ * Tests for:
+ * - Registering, reusing and freeing blind tag slots
+ * - The tag registry size limit
+ * - Listing and clearing tagged entries in a chunk
+ */
+
+#[cfg(test)]
+mod entry_tags_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{
+        DataEntryHeader, PasswordEntryType, StorageChunk, StorageType, TagRegistry, MAX_TAGS,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn empty_registry() -> TagRegistry {
+        TagRegistry {
+            version: TagRegistry::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            tags: [[0u8; 32]; MAX_TAGS],
+            bump: 255,
+        }
+    }
+
+    fn tagged_chunk() -> StorageChunk {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                0,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                255,
+                NOW,
+            )
+            .unwrap();
+
+        // Entry 3 is trashed and entry 4 a continuation segment
+        let entries = [
+            (1, 0b01, 0),
+            (2, 0b11, 0),
+            (3, 0b01, DataEntryHeader::FLAG_TRASHED),
+            (4, 0b01, DataEntryHeader::FLAG_CONTINUATION),
+        ];
+        for (entry_id, tag_mask, flags) in entries {
+            let header = DataEntryHeader {
+                entry_id,
+                offset: chunk.current_size,
+                size: 40,
+                entry_type: PasswordEntryType::Login,
+                category: 0,
+                title_hash: [0u8; 32],
+                created_at: NOW,
+                last_modified: NOW,
+                access_count: 0,
+                flags,
+                next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+                next_offset: 0,
+                tag_mask,
+                rotate_after: 0,
+                parent_entry: 0,
+                linked_entry_ids: Vec::new(),
+            };
+            chunk.add_entry(header, vec![entry_id as u8; 40], NOW).unwrap();
+        }
+        chunk
+    }
+
+    #[test]
+    fn test_register_reuses_and_frees_slots() {
+        let mut registry = empty_registry();
+        let (work, travel) = ([1u8; 32], [2u8; 32]);
+
+        assert_eq!(registry.register(work).unwrap(), 0b01);
+        assert_eq!(registry.register(travel).unwrap(), 0b10);
+        assert_eq!(registry.register(work).unwrap(), 0b01);
+        assert_eq!(registry.mask_of(&travel).unwrap(), 0b10);
+
+        // Freed slots keep the other tags' bits stable and are reused first
+        assert_eq!(registry.unregister(&work).unwrap(), 0b01);
+        let err = registry.mask_of(&work).unwrap_err();
+        assert_eq!(err, LockboxError::TagNotFound.into());
+        assert_eq!(registry.register([3u8; 32]).unwrap(), 0b01);
+        assert_eq!(registry.mask_of(&travel).unwrap(), 0b10);
+
+        let err = registry.register([0u8; 32]).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidTagHash.into());
+        let err = registry.mask_of(&[0u8; 32]).unwrap_err();
+        assert_eq!(err, LockboxError::TagNotFound.into());
+    }
+
+    #[test]
+    fn test_tag_registry_is_bounded() {
+        let mut registry = empty_registry();
+        for tag in 1..=MAX_TAGS as u8 {
+            registry.register([tag; 32]).unwrap();
+        }
+        let err = registry.register([0xFF; 32]).unwrap_err();
+        assert_eq!(err, LockboxError::TagRegistryFull.into());
+
+        // Registering a known tag needs no free slot
+        assert_eq!(registry.register([MAX_TAGS as u8; 32]).unwrap(), 1 << (MAX_TAGS - 1));
+    }
+
+    #[test]
+    fn test_lists_and_clears_tagged_entries() {
+        let mut chunk = tagged_chunk();
+        assert_eq!(chunk.entries_with_tag(0b01), vec![1, 2]);
+        assert_eq!(chunk.entries_with_tag(0b10), vec![2]);
+        assert!(chunk.entries_with_tag(0b100).is_empty());
+
+        // Clearing reaches trashed entries and segments too
+        assert_eq!(chunk.clear_tag(0b01), 4);
+        assert!(chunk.entries_with_tag(0b01).is_empty());
+        assert_eq!(chunk.entries_with_tag(0b10), vec![2]);
+        assert_eq!(chunk.get_entry_header(2).unwrap().tag_mask, 0b10);
+    }
+}
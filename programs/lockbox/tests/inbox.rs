@@ -0,0 +1,75 @@
+/*!
+ * INBOX TESTS
+ *
+ * Tests for:
+ * - Delivery skips duplicates and stops when the inbox is full
+ * - Accepting or dismissing removes exactly the matching item
+ * - Account space covers a full inbox
+ */
+
+#[cfg(test)]
+mod inbox_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{Inbox, InboxItem, InboxItemKind, MAX_INBOX_ITEMS};
+
+    fn empty_inbox() -> Inbox {
+        Inbox {
+            version: Inbox::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            items: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    fn item(kind: InboxItemKind, account: Pubkey) -> InboxItem {
+        InboxItem {
+            kind,
+            sender: Pubkey::new_unique(),
+            account,
+            received_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_deliver_skips_duplicates_and_overflow() {
+        let mut inbox = empty_inbox();
+        let config = Pubkey::new_unique();
+        assert!(inbox.deliver(item(InboxItemKind::GuardianInvitation, config)));
+        assert!(!inbox.deliver(item(InboxItemKind::GuardianInvitation, config)));
+
+        // Same account under a different kind is a separate item
+        assert!(inbox.deliver(item(InboxItemKind::EmergencyContact, config)));
+
+        while inbox.items.len() < MAX_INBOX_ITEMS {
+            assert!(inbox.deliver(item(InboxItemKind::SharedEntry, Pubkey::new_unique())));
+        }
+        assert!(!inbox.deliver(item(InboxItemKind::SharedEntry, Pubkey::new_unique())));
+    }
+
+    #[test]
+    fn test_take_removes_matching_item() {
+        let mut inbox = empty_inbox();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        inbox.deliver(item(InboxItemKind::GuardianInvitation, first));
+        inbox.deliver(item(InboxItemKind::EmergencyContact, second));
+
+        let err = inbox.take(InboxItemKind::EmergencyContact, &first).unwrap_err();
+        assert_eq!(err, LockboxError::InboxItemNotFound.into());
+
+        let taken = inbox.take(InboxItemKind::GuardianInvitation, &first).unwrap();
+        assert_eq!(taken.account, first);
+        assert_eq!(inbox.items.len(), 1);
+        assert_eq!(inbox.items[0].account, second);
+    }
+
+    #[test]
+    fn test_full_inbox_fits_init_space() {
+        let mut inbox = empty_inbox();
+        while inbox.deliver(item(InboxItemKind::SharedEntry, Pubkey::new_unique())) {}
+
+        let mut data = Vec::new();
+        inbox.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + Inbox::INIT_SPACE);
+    }
+}
@@ -0,0 +1,201 @@
+/*!
+ * ORGANIZATION INVOICE TESTS
+ *
+ * Tests for:
+ * - Invoice terms: paid tier, nonzero amount, bounded period
+ * - Paying renews the current tier or upgrades to a higher one
+ * - Invoices are paid once and only open ones can be voided by the admin
+ */
+
+#[cfg(test)]
+mod invoice_tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{VoidInvoice, VoidInvoiceBumps};
+    use lockbox::state::{
+        Invoice, InvoiceStatus, MasterLockbox, OwnerKind, ProgramConfig, SubscriptionStatus, SubscriptionTier,
+        DEFAULT_DUNNING_WINDOW, MAX_INVOICE_PERIOD, MAX_RECENT_ENTRIES,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+    const MONTH: i64 = 30 * 24 * 60 * 60;
+
+    fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &lockbox::ID)
+    }
+
+    fn new_lockbox(owner: Pubkey) -> (Pubkey, MasterLockbox) {
+        let (address, bump) = pda(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()]);
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(owner, bump, NOW).unwrap();
+        (address, lockbox)
+    }
+
+    fn invoice(master_lockbox: Pubkey, tier: SubscriptionTier, period: i64) -> (Pubkey, Invoice) {
+        let (address, bump) = pda(&[Invoice::SEEDS_PREFIX, master_lockbox.as_ref(), &1u64.to_le_bytes()]);
+        let invoice = Invoice {
+            version: Invoice::CURRENT_VERSION,
+            master_lockbox,
+            invoice_id: 1,
+            issued_by: Pubkey::new_unique(),
+            tier,
+            amount: 1_200_000_000,
+            period,
+            memo_hash: [7u8; 32],
+            status: InvoiceStatus::Open,
+            issued_at: NOW,
+            paid_at: 0,
+            bump,
+        };
+        (address, invoice)
+    }
+
+    fn leaked_account(key: Pubkey, is_signer: bool, owner: &'static Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        leaked_account(key, false, &lockbox::ID, data)
+    }
+
+    fn void(admin: Pubkey, signer: Pubkey, invoice: (Pubkey, &Invoice)) -> Result<VoidInvoice<'static>> {
+        let (config_address, bump) = pda(&[ProgramConfig::SEEDS_PREFIX]);
+        let config = ProgramConfig {
+            version: ProgramConfig::CURRENT_VERSION,
+            admin,
+            invoice_receiver: Pubkey::new_unique(),
+            refund_count: 0,
+            bump,
+        };
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(
+            vec![
+                program_account(config_address, &config),
+                program_account(invoice.0, invoice.1),
+                leaked_account(signer, true, &anchor_lang::system_program::ID, Vec::new()),
+            ]
+            .into_boxed_slice(),
+        );
+        VoidInvoice::try_accounts(
+            &lockbox::ID,
+            &mut accounts,
+            &invoice.1.invoice_id.to_le_bytes(),
+            &mut VoidInvoiceBumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_invoice_terms_checked() {
+        Invoice::check_terms(SubscriptionTier::Pro, 1, MAX_INVOICE_PERIOD).unwrap();
+        for (tier, amount, period) in [
+            (SubscriptionTier::Free, 1, MONTH),
+            (SubscriptionTier::Basic, 0, MONTH),
+            (SubscriptionTier::Basic, 1, 0),
+            (SubscriptionTier::Basic, 1, MAX_INVOICE_PERIOD + 1),
+        ] {
+            let err = Invoice::check_terms(tier, amount, period).unwrap_err();
+            assert_eq!(err, LockboxError::InvalidInvoice.into());
+        }
+    }
+
+    #[test]
+    fn test_paying_renews_or_upgrades_subscription() {
+        let (address, mut lockbox) = new_lockbox(Pubkey::new_unique());
+
+        // A Free vault is upgraded for the invoice's period from now
+        let (_, mut yearly) = invoice(address, SubscriptionTier::Premium, 12 * MONTH);
+        yearly.settle(&mut lockbox, NOW).unwrap();
+        assert_eq!(lockbox.subscription_tier, SubscriptionTier::Premium);
+        assert_eq!(lockbox.subscription_expires, NOW + 12 * MONTH);
+        assert_eq!((yearly.status, yearly.paid_at), (InvoiceStatus::Paid, NOW));
+
+        // Same tier extends from the current expiry
+        let (_, mut renewal) = invoice(address, SubscriptionTier::Premium, 6 * MONTH);
+        renewal.settle(&mut lockbox, NOW + MONTH).unwrap();
+        assert_eq!(lockbox.subscription_expires, NOW + 18 * MONTH);
+
+        // An invoice cannot be paid twice, or move the vault down a tier
+        let err = renewal.settle(&mut lockbox, NOW + MONTH).unwrap_err();
+        assert_eq!(err, LockboxError::InvoiceNotOpen.into());
+        let (_, mut lower) = invoice(address, SubscriptionTier::Basic, MONTH);
+        let err = lower.settle(&mut lockbox, NOW + MONTH).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidTierUpgrade.into());
+        assert_eq!(lower.status, InvoiceStatus::Open);
+
+        // A past-due vault is restored
+        lockbox.subscription_status = SubscriptionStatus::PastDue;
+        let (_, mut late) = invoice(address, SubscriptionTier::Premium, MONTH);
+        late.settle(&mut lockbox, NOW + 20 * MONTH).unwrap();
+        assert_eq!(lockbox.subscription_status, SubscriptionStatus::Active);
+        assert_eq!(lockbox.subscription_expires, NOW + 21 * MONTH);
+    }
+
+    #[test]
+    fn test_only_admin_voids_open_invoices() {
+        let admin = Pubkey::new_unique();
+        let (address, _) = new_lockbox(Pubkey::new_unique());
+        let (invoice_address, mut open) = invoice(address, SubscriptionTier::Basic, MONTH);
+
+        void(admin, admin, (invoice_address, &open)).unwrap();
+        let err = void(admin, Pubkey::new_unique(), (invoice_address, &open)).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+
+        open.status = InvoiceStatus::Paid;
+        let err = void(admin, admin, (invoice_address, &open)).err().unwrap();
+        assert_eq!(err, LockboxError::InvoiceNotOpen.into());
+    }
+}
@@ -0,0 +1,852 @@
+/*!
+ * MASTER LOCKBOX TESTS
+ *
+ * Tests for:
+ * - Manual space calculation matches the serialized account
+ * - Owner authorization for wallet and program-owned (PDA) vaults
+ * - CPI guard for security-sensitive instructions
+ * - Recently-used entry list (opt-in, move-to-front)
+ * - Auxiliary accounts must be closed before the master lockbox
+ * - Unregistering closed storage chunks
+ * - Reusing closed chunk indexes
+ * - Entry write fees and their tier waiver
+ * - Accounting invariant detection
+ * - Pre-flight store and expand results
+ * - Encrypted settings tier limits and account sizing
+ * - Billing periods, snapshots and the receipts ring
+ * - Capacity add-on packs and their treasury payment
+ * - Loyalty capacity from consecutive renewals
+ * - Dunning: past-due state and automatic downgrade
+ * - Vault deletion tombstone delay
+ * - Structured error context on failed checks
+ * - Per-entry ciphertext size limits by tier
+ * - Writer lease acquisition, fencing and takeover
+ * - Entry payload framing header validation
+ * - TOTP payload size bounds
+ * - Write burst limits per slot window
+ * - Opt-in heat map buckets and period rollover
+ */
+
+#[cfg(test)]
+mod master_lockbox_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::{require_with_context, ErrorContextEvent, ErrorField, LockboxError};
+    use lockbox::instructions::{
+        charge_entry_write_fee, check_entry_type_size, check_payload_framing, check_storage_capacity,
+        check_write_rate, ensure_accounts_closed, find_violation, preflight_expand, preflight_store,
+        Invariant, PreflightResult, PurchaseCapacityAddon, PurchaseCapacityAddonBumps, MAX_REALLOC_INCREMENT,
+        TOTP_MAX_PAYLOAD_SIZE, TOTP_MIN_PAYLOAD_SIZE,
+    };
+    use lockbox::state::{
+        BillingReceipts, MasterLockbox, OwnerKind, PasswordEntryType, SecurityPolicy, StorageChunk, StorageChunkInfo,
+        StorageType, SubscriptionStatus, SubscriptionTier, BILLING_PERIOD, CAPACITY_ADDON_SIZE,
+        DEFAULT_DUNNING_WINDOW, ENTRY_WRITE_FEE_LAMPORTS, MAX_BILLING_RECEIPTS, MAX_RECENT_ENTRIES,
+        MAX_SETTINGS_SIZE, MAX_WRITER_LEASE_DURATION, VAULT_DELETION_DELAY, WRITE_BURST_WINDOW_SLOTS,
+        WriterLease, HEAT_MAP_BUCKETS, HEAT_MAP_PERIOD, TREASURY_SEED,
+    };
+    use std::collections::BTreeSet;
+
+    fn new_lockbox(owner: Pubkey) -> MasterLockbox {
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(owner, 255, 1_700_000_000).unwrap();
+        lockbox
+    }
+
+    fn with_account_info<R>(key: Pubkey, is_signer: bool, f: impl FnOnce(&AccountInfo) -> R) -> R {
+        let mut lamports = 0u64;
+        let mut data = Vec::new();
+        let owner = anchor_lang::system_program::ID;
+        let info = AccountInfo::new(&key, is_signer, false, &mut lamports, &mut data, &owner, false, 0);
+        f(&info)
+    }
+
+    #[test]
+    fn test_space_matches_serialized_size() {
+        // Largest owner_kind and active_writer variants must fit exactly
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        lockbox.owner_kind = OwnerKind::ProgramDerived {
+            program_id: Pubkey::new_unique(),
+        };
+        lockbox.active_writer = Some(WriterLease { device: Pubkey::new_unique(), expires_at: 1 });
+        let mut data = Vec::new();
+        lockbox.try_serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), MasterLockbox::calculate_space(0));
+        assert_eq!(MasterLockbox::INIT_SPACE, MasterLockbox::calculate_space(0));
+        assert_eq!(
+            lockbox::MASTER_LOCKBOX_CHUNK_SPACE as usize,
+            MasterLockbox::calculate_space(1) - MasterLockbox::calculate_space(0)
+        );
+    }
+
+    #[test]
+    fn test_wallet_owner_authorization() {
+        let owner = Pubkey::new_unique();
+        let lockbox = new_lockbox(owner);
+        assert_eq!(lockbox.owner_kind, OwnerKind::Wallet);
+
+        assert!(with_account_info(owner, true, |info| lockbox.is_authorized_owner(info)));
+
+        // Must sign
+        assert!(!with_account_info(owner, false, |info| lockbox.is_authorized_owner(info)));
+
+        // Must be the recorded owner
+        assert!(!with_account_info(Pubkey::new_unique(), true, |info| {
+            lockbox.is_authorized_owner(info)
+        }));
+    }
+
+    #[test]
+    fn test_program_owned_vault_requires_cpi() {
+        let program_id = Pubkey::new_unique();
+        let (owner, _) = Pubkey::find_program_address(&[b"vault_authority"], &program_id);
+        let mut lockbox = new_lockbox(owner);
+        lockbox.owner_kind = OwnerKind::ProgramDerived { program_id };
+
+        // Outside the runtime there is no CPI stack, which is exactly the
+        // top-level case: a PDA owner invoked directly must be rejected
+        assert!(!with_account_info(owner, true, |info| lockbox.is_authorized_owner(info)));
+    }
+
+    #[test]
+    fn test_sensitive_instructions_allowed_at_top_level() {
+        // Off-chain the stack height is the top level, which both vault
+        // kinds accept; only wallet vaults reject a deeper stack
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        assert!(lockbox.allows_sensitive_invocation());
+
+        lockbox.owner_kind = OwnerKind::ProgramDerived {
+            program_id: Pubkey::new_unique(),
+        };
+        assert!(lockbox.allows_sensitive_invocation());
+    }
+
+    #[test]
+    fn test_recent_entries_opt_in_and_order() {
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+
+        // Disabled by default: nothing recorded
+        lockbox.record_recent_entry(1);
+        assert_eq!(lockbox.recent_entries, [0; MAX_RECENT_ENTRIES]);
+
+        lockbox.recent_entries_enabled = true;
+        for id in 1..=10 {
+            lockbox.record_recent_entry(id);
+        }
+        assert_eq!(lockbox.recent_entries, [10, 9, 8, 7, 6, 5, 4, 3]);
+
+        // Re-reading moves to the front without duplicating
+        lockbox.record_recent_entry(6);
+        assert_eq!(lockbox.recent_entries, [6, 10, 9, 8, 7, 5, 4, 3]);
+
+        lockbox.forget_recent_entry(8);
+        assert_eq!(lockbox.recent_entries, [6, 10, 9, 7, 5, 4, 3, 0]);
+    }
+
+    #[test]
+    fn test_close_requires_closed_auxiliary_accounts() {
+        let (open_key, closed_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let system = anchor_lang::system_program::ID;
+        let (mut open_lamports, mut closed_lamports) = (1_000_000u64, 0u64);
+        let (mut open_data, mut closed_data) = (vec![0u8; 64], Vec::new());
+        let open = AccountInfo::new(
+            &open_key, false, false, &mut open_lamports, &mut open_data, &lockbox::ID, false, 0,
+        );
+        let closed = AccountInfo::new(
+            &closed_key, false, false, &mut closed_lamports, &mut closed_data, &system, false, 0,
+        );
+
+        let only_closed = std::slice::from_ref(&closed);
+        ensure_accounts_closed(&[closed_key], only_closed).unwrap();
+
+        let err = ensure_accounts_closed(&[closed_key, open_key], only_closed).unwrap_err();
+        assert_eq!(err, LockboxError::AuxiliaryAccountMissing.into());
+
+        let err = ensure_accounts_closed(&[closed_key, open_key], &[open, closed.clone()]).unwrap_err();
+        assert_eq!(err, LockboxError::AuxiliaryAccountOpen.into());
+    }
+
+    #[test]
+    fn test_vault_deletion_waits_out_tombstone_delay() {
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        assert!(!lockbox.is_deletion_requested());
+        assert!(!lockbox.is_deletion_ready(now + 10 * VAULT_DELETION_DELAY));
+
+        lockbox.deletion_requested_at = now;
+        assert!(lockbox.is_deletion_requested());
+        assert!(!lockbox.is_deletion_ready(now + VAULT_DELETION_DELAY - 1));
+        assert!(lockbox.is_deletion_ready(now + VAULT_DELETION_DELAY));
+
+        // Cancelling clears the tombstone
+        lockbox.deletion_requested_at = 0;
+        assert!(!lockbox.is_deletion_ready(now + VAULT_DELETION_DELAY));
+    }
+
+    fn chunk_info(chunk_index: u16) -> StorageChunkInfo {
+        StorageChunkInfo {
+            chunk_address: Pubkey::new_unique(),
+            chunk_index,
+            max_capacity: 1024,
+            size_used: 0,
+            data_type: StorageType::Passwords,
+            created_at: 1_700_000_000,
+            last_modified: 1_700_000_000,
+            layout_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_remove_chunk_releases_usage() {
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        for index in 0..3 {
+            lockbox.add_chunk(chunk_info(index)).unwrap();
+        }
+        lockbox.update_chunk_usage(0, 300).unwrap();
+        lockbox.update_chunk_usage(1, 200).unwrap();
+
+        let removed = lockbox.remove_chunk(0).unwrap();
+        assert_eq!(removed.size_used, 300);
+        assert_eq!(lockbox.storage_used, 200);
+        assert_eq!(lockbox.total_capacity, 2048);
+        assert_eq!(lockbox.storage_chunks.len(), 2);
+        assert_eq!(lockbox.storage_chunks_count, 3);
+
+        // Closing the trailing chunk frees its index for the next chunk
+        lockbox.remove_chunk(2).unwrap();
+        assert_eq!(lockbox.storage_chunks_count, 2);
+        lockbox.remove_chunk(1).unwrap();
+        assert_eq!((lockbox.storage_chunks_count, lockbox.total_capacity, lockbox.storage_used), (0, 0, 0));
+
+        let err = lockbox.remove_chunk(0).unwrap_err();
+        assert_eq!(err, LockboxError::ChunkNotFound.into());
+    }
+
+    #[test]
+    fn test_closed_chunk_index_is_reused() {
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        for index in 0..3 {
+            assert_eq!(lockbox.next_chunk_index(), index);
+            lockbox.add_chunk(chunk_info(index)).unwrap();
+        }
+
+        lockbox.remove_chunk(1).unwrap();
+        assert_eq!(lockbox.next_chunk_index(), 1);
+        assert!(lockbox.is_chunk_index_available(1));
+        assert!(lockbox.is_chunk_index_available(3));
+        assert!(!lockbox.is_chunk_index_available(2));
+        assert!(!lockbox.is_chunk_index_available(4));
+
+        // Filling the hole doesn't move the high-water mark
+        lockbox.add_chunk(chunk_info(1)).unwrap();
+        assert_eq!(lockbox.storage_chunks_count, 3);
+        assert_eq!(lockbox.next_chunk_index(), 3);
+    }
+
+    #[test]
+    fn test_entry_write_fee_waived_on_paid_tiers() {
+        assert_eq!(SubscriptionTier::Free.entry_write_fee(), ENTRY_WRITE_FEE_LAMPORTS);
+        for tier in [SubscriptionTier::Basic, SubscriptionTier::Premium, SubscriptionTier::Pro] {
+            assert_eq!(tier.entry_write_fee(), 0);
+        }
+
+        // Waived fees need no treasury; due fees refuse to skip it
+        with_account_info(Pubkey::new_unique(), true, |owner| {
+            let charged = charge_entry_write_fee(SubscriptionTier::Pro, owner.clone(), None, None);
+            assert_eq!(charged.unwrap(), 0);
+
+            let err = charge_entry_write_fee(SubscriptionTier::Free, owner.clone(), None, None)
+                .unwrap_err();
+            assert_eq!(err, LockboxError::EntryFeeAccountsMissing.into());
+        });
+    }
+
+    #[test]
+    fn test_invariant_violations_detected() {
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        lockbox.add_chunk(chunk_info(0)).unwrap();
+        lockbox.update_chunk_usage(0, 300).unwrap();
+        let mut chunk = StorageChunk {
+            version: StorageChunk::CURRENT_VERSION,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 1024,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        assert_eq!(find_violation(&lockbox, &[&chunk]), None);
+
+        lockbox.storage_used += 1;
+        assert_eq!(find_violation(&lockbox, &[&chunk]), Some(Invariant::StorageUsed));
+
+        // Chunk-level breakage is reported first, naming the chunk
+        chunk.entry_count = 1;
+        assert_eq!(
+            find_violation(&lockbox, &[&chunk]),
+            Some(Invariant::ChunkEntryCount { chunk_index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_preflight_reports_first_failure() {
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        let mut chunk = StorageChunk {
+            version: StorageChunk::CURRENT_VERSION,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 100,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        let padded = SecurityPolicy {
+            version: SecurityPolicy::CURRENT_VERSION,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            delete_cosigner: None,
+            read_cooldown: 0,
+            padded_sizes: true,
+            privacy_flags: 0,
+            freeze_on_honeytoken: false,
+            updated_at: 0,
+            bump: 0,
+        };
+
+        lockbox.last_accessed = now;
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, None, 64, now),
+            PreflightResult::RateLimited { retry_at: now + 1 }
+        );
+
+        let later = now + 10;
+        assert_eq!(preflight_store(&lockbox, &chunk, None, 39, later), PreflightResult::InvalidDataSize);
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, Some(&padded), 40, later),
+            PreflightResult::UnpaddedCiphertext
+        );
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, Some(&padded), 64, later),
+            PreflightResult::Ok { fee_lamports: ENTRY_WRITE_FEE_LAMPORTS }
+        );
+
+        // A small shortfall can be covered by auto-expansion
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, None, 200, later),
+            PreflightResult::InsufficientChunkCapacity { available: 100, auto_expandable: true }
+        );
+
+        lockbox.storage_used = 1000;
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, None, 64, later),
+            PreflightResult::InsufficientStorageCapacity { available: 24 }
+        );
+
+        lockbox.subscription_tier = SubscriptionTier::Basic;
+        lockbox.subscription_expires = now;
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, None, 64, later),
+            PreflightResult::SubscriptionExpired { expired_at: now }
+        );
+
+        assert_eq!(preflight_expand(&chunk, 512, 7), PreflightResult::Ok { fee_lamports: 7 });
+        assert_eq!(
+            preflight_expand(&chunk, 0, 0),
+            PreflightResult::ReallocTooLarge { max_increment: MAX_REALLOC_INCREMENT }
+        );
+        chunk.max_capacity = StorageChunk::MAX_CHUNK_SIZE - 10;
+        assert_eq!(
+            preflight_expand(&chunk, 11, 0),
+            PreflightResult::ChunkTooLarge { max_additional: 10 }
+        );
+    }
+
+    #[test]
+    fn test_settings_limited_by_active_tier() {
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        lockbox.check_settings_size(128, now).unwrap();
+        let err = lockbox.check_settings_size(129, now).unwrap_err();
+        assert_eq!(err, LockboxError::SettingsTooLarge.into());
+
+        lockbox.subscription_tier = SubscriptionTier::Premium;
+        lockbox.subscription_expires = now + 1;
+        lockbox.check_settings_size(MAX_SETTINGS_SIZE, now).unwrap();
+
+        // Lapsed subscriptions fall back to the Free limit; clearing still works
+        assert!(lockbox.check_settings_size(256, now + 1).is_err());
+        lockbox.check_settings_size(0, now + 1).unwrap();
+
+        // Reallocs keep room for the settings blob
+        lockbox.encrypted_settings = vec![7u8; 200];
+        lockbox.owner_kind = OwnerKind::ProgramDerived { program_id: Pubkey::new_unique() };
+        lockbox.active_writer = Some(WriterLease { device: Pubkey::new_unique(), expires_at: 1 });
+        let mut data = Vec::new();
+        lockbox.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), lockbox.space_for_chunks(0));
+    }
+
+    #[test]
+    fn test_billing_snapshots_close_paid_periods() {
+        let created = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+
+        // Free vaults are never billed
+        assert!(!lockbox.is_billing_snapshot_due(created + 2 * BILLING_PERIOD));
+
+        // The period starts with the paid subscription, not at creation
+        let upgraded = created + 5 * BILLING_PERIOD;
+        lockbox.upgrade_subscription(SubscriptionTier::Basic, upgraded).unwrap();
+        lockbox.record_payment(SubscriptionTier::Basic.monthly_cost());
+        lockbox.storage_used = 900;
+        assert!(!lockbox.is_billing_snapshot_due(upgraded + BILLING_PERIOD - 1));
+        assert!(lockbox.is_billing_snapshot_due(upgraded + BILLING_PERIOD));
+
+        let closed = upgraded + BILLING_PERIOD + 60;
+        let receipt = lockbox.close_billing_period(closed);
+        assert_eq!(receipt.sequence, 0);
+        assert_eq!(receipt.tier, SubscriptionTier::Basic);
+        assert_eq!((receipt.period_start, receipt.period_end), (upgraded, closed));
+        assert_eq!(receipt.amount_paid, SubscriptionTier::Basic.monthly_cost());
+        assert_eq!(receipt.storage_used, 900);
+
+        // The next period starts at the snapshot, with nothing paid yet
+        assert!(!lockbox.is_billing_snapshot_due(closed + 1));
+        let receipt = lockbox.close_billing_period(closed + BILLING_PERIOD);
+        assert_eq!((receipt.sequence, receipt.amount_paid), (1, 0));
+
+        let mut receipts = BillingReceipts {
+            version: BillingReceipts::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            owner: lockbox.owner,
+            receipts: Vec::new(),
+            bump: 255,
+        };
+        for _ in 0..MAX_BILLING_RECEIPTS + 2 {
+            receipts.record(lockbox.close_billing_period(closed));
+        }
+        assert_eq!(receipts.receipts.len(), MAX_BILLING_RECEIPTS);
+        assert_eq!(receipts.receipts[0].sequence, 4);
+
+        let mut data = Vec::new();
+        receipts.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + BillingReceipts::INIT_SPACE);
+    }
+
+    #[test]
+    fn test_capacity_addon_extends_tier_limit_until_lapse() {
+        let now = 1_700_000_000;
+        let month = SubscriptionTier::Free.duration_seconds();
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        let free = SubscriptionTier::Free.max_capacity();
+
+        assert!(lockbox.purchase_capacity_addon(0, month, now).is_err());
+        assert!(lockbox.purchase_capacity_addon(21, month, now).is_err());
+
+        // Add-ons stack on any tier, Free included
+        assert!(!lockbox.has_capacity(free + 1, now));
+        lockbox.purchase_capacity_addon(1, month, now).unwrap();
+        assert_eq!(lockbox.capacity_limit(now), free + CAPACITY_ADDON_SIZE);
+        assert!(lockbox.has_capacity(free + CAPACITY_ADDON_SIZE, now));
+        assert!(!lockbox.has_capacity(free + CAPACITY_ADDON_SIZE + 1, now));
+
+        // Same pack count renews from the current expiry
+        lockbox.purchase_capacity_addon(1, month, now + 10).unwrap();
+        assert_eq!(lockbox.bonus_capacity_expires, now + 2 * month);
+
+        // A different count starts a new term now
+        lockbox.purchase_capacity_addon(3, month, now + 20).unwrap();
+        assert_eq!(lockbox.bonus_capacity, 3 * CAPACITY_ADDON_SIZE);
+        assert_eq!(lockbox.bonus_capacity_expires, now + 20 + month);
+
+        // Lapsed packs no longer count
+        let lapsed = lockbox.bonus_capacity_expires;
+        assert_eq!(lockbox.capacity_limit(lapsed), free);
+        assert!(!lockbox.has_capacity(free + 1, lapsed));
+    }
+
+    /// Account leaked for a `'static` context
+    fn leaked_account(key: Pubkey, is_signer: bool, owner: &'static Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn purchase_addon_accounts(owner: Pubkey, receiver: Pubkey) -> Result<PurchaseCapacityAddon<'static>> {
+        let (address, bump) = Pubkey::find_program_address(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()], &lockbox::ID);
+        let mut lockbox = new_lockbox(owner);
+        lockbox.bump = bump;
+        let mut data = Vec::new();
+        lockbox.try_serialize(&mut data).unwrap();
+
+        let system = &anchor_lang::system_program::ID;
+        let accounts = vec![
+            leaked_account(address, false, &lockbox::ID, data),
+            leaked_account(owner, true, system, Vec::new()),
+            leaked_account(receiver, false, system, Vec::new()),
+            AccountInfo::new(
+                system,
+                false,
+                false,
+                Box::leak(Box::new(1u64)),
+                Box::leak(Vec::new().into_boxed_slice()),
+                system,
+                true,
+                0,
+            ),
+        ];
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        PurchaseCapacityAddon::try_accounts(
+            &lockbox::ID,
+            &mut accounts,
+            &[],
+            &mut PurchaseCapacityAddonBumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_capacity_addon_pays_the_treasury() {
+        let owner = Pubkey::new_unique();
+        let treasury = Pubkey::find_program_address(&[TREASURY_SEED], &lockbox::ID).0;
+        assert!(purchase_addon_accounts(owner, treasury).is_ok());
+
+        // Paying any other account, the owner's own included, is rejected
+        for receiver in [owner, Pubkey::new_unique()] {
+            let err = purchase_addon_accounts(owner, receiver).err().unwrap();
+            assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+        }
+    }
+
+    #[test]
+    fn test_loyalty_capacity_grows_with_renewal_streak() {
+        let now = 1_700_000_000;
+        let month = SubscriptionTier::Basic.duration_seconds();
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        let basic = SubscriptionTier::Basic.max_capacity();
+
+        lockbox.upgrade_subscription(SubscriptionTier::Basic, now).unwrap();
+        assert_eq!(lockbox.loyalty_bonus_capacity(), 0);
+
+        // 1% of the tier per on-time renewal
+        lockbox.renew_subscription(month, now + 10);
+        lockbox.renew_subscription(month, now + 20);
+        assert_eq!(lockbox.consecutive_renewals, 2);
+        assert_eq!(lockbox.subscription_expires, now + 3 * month);
+        assert_eq!(lockbox.capacity_limit(now), basic + basic * 2 / 100);
+
+        // Capped at 10% of the tier
+        for _ in 0..20 {
+            lockbox.renew_subscription(month, now + 30);
+        }
+        assert_eq!(lockbox.loyalty_bonus_capacity(), basic / 10);
+
+        // Renewing after a lapse starts a new streak
+        let lapsed = lockbox.subscription_expires + 1;
+        lockbox.renew_subscription(month, lapsed);
+        assert_eq!(lockbox.consecutive_renewals, 1);
+        assert_eq!(lockbox.subscription_expires, lapsed + month);
+        assert_eq!(lockbox.loyalty_bonus_capacity(), basic / 100);
+    }
+
+    #[test]
+    fn test_dunning_moves_expired_subscription_to_past_due_then_free() {
+        let now = 1_700_000_000;
+        let month = SubscriptionTier::Premium.duration_seconds();
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        assert!(!lockbox.is_past_due_pending(now + 10 * month));
+
+        lockbox.upgrade_subscription(SubscriptionTier::Premium, now).unwrap();
+        lockbox.renew_subscription(month, now + 1);
+        let expired = lockbox.subscription_expires;
+        assert!(!lockbox.is_past_due_pending(expired - 1));
+        assert!(lockbox.is_past_due_pending(expired));
+
+        // Past due until the window ends; renewing restores good standing
+        lockbox.subscription_status = SubscriptionStatus::PastDue;
+        assert!(!lockbox.is_past_due_pending(expired));
+        assert_eq!(lockbox.dunning_deadline(), expired + DEFAULT_DUNNING_WINDOW);
+        assert!(!lockbox.is_dunning_over(expired + DEFAULT_DUNNING_WINDOW - 1));
+        assert!(lockbox.is_dunning_over(expired + DEFAULT_DUNNING_WINDOW));
+
+        lockbox.renew_subscription(month, expired + 60);
+        assert_eq!(lockbox.subscription_status, SubscriptionStatus::Active);
+        assert!(!lockbox.is_dunning_over(expired + DEFAULT_DUNNING_WINDOW));
+
+        // Left unpaid, the vault drops to Free with its data intact
+        lockbox.subscription_status = SubscriptionStatus::PastDue;
+        lockbox.storage_used = 5_000;
+        lockbox.downgrade_to_free();
+        assert_eq!(lockbox.subscription_tier, SubscriptionTier::Free);
+        assert_eq!(lockbox.subscription_status, SubscriptionStatus::Active);
+        assert_eq!((lockbox.subscription_expires, lockbox.consecutive_renewals), (0, 0));
+        assert_eq!(lockbox.storage_used, 5_000);
+        assert!(!lockbox.has_capacity(1, expired));
+        assert!(!lockbox.is_past_due_pending(expired + 10 * month));
+    }
+
+    #[test]
+    fn test_error_context_keeps_numeric_code() {
+        require_with_context(true, LockboxError::InvalidDataSize, ErrorField::DataSize, 40, 40)
+            .unwrap();
+        let err = require_with_context(
+            false,
+            LockboxError::InvalidDataSize,
+            ErrorField::DataSize,
+            40,
+            12,
+        )
+        .unwrap_err();
+        assert_eq!(err, LockboxError::InvalidDataSize.into());
+
+        // The event carries the same code the transaction fails with
+        let event = ErrorContextEvent {
+            code: LockboxError::InvalidDataSize.into(),
+            field: ErrorField::DataSize,
+            limit: 40,
+            actual: 12,
+        };
+        assert_eq!(event.code, 6000 + LockboxError::InvalidDataSize as u32);
+
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        lockbox.last_accessed = now;
+        let err = check_write_rate(&lockbox, now).unwrap_err();
+        assert_eq!(err, LockboxError::RateLimitExceeded.into());
+        check_write_rate(&lockbox, now + 1).unwrap();
+
+        let limit = lockbox.capacity_limit(now);
+        check_storage_capacity(&lockbox, limit, now).unwrap();
+        let err = check_storage_capacity(&lockbox, limit + 1, now).unwrap_err();
+        assert_eq!(err, LockboxError::InsufficientStorageCapacity.into());
+    }
+
+    #[test]
+    fn test_entry_size_limited_by_tier() {
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        let chunk = StorageChunk {
+            version: StorageChunk::CURRENT_VERSION,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 20_000,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+
+        lockbox.check_entry_size(1024, now).unwrap();
+        let err = lockbox.check_entry_size(1025, now).unwrap_err();
+        assert_eq!(err, LockboxError::EntryTooLarge.into());
+        assert_eq!(
+            preflight_store(&lockbox, &chunk, None, 1025, now + 10),
+            PreflightResult::EntryTooLarge { max_size: 1024 }
+        );
+
+        lockbox.subscription_tier = SubscriptionTier::Premium;
+        lockbox.subscription_expires = now + 1;
+        lockbox.check_entry_size(4096, now).unwrap();
+        assert!(lockbox.check_entry_size(4097, now).is_err());
+
+        lockbox.subscription_tier = SubscriptionTier::Pro;
+        lockbox.check_entry_size(16_384, now).unwrap();
+        assert!(lockbox.check_entry_size(16_385, now).is_err());
+
+        // Lapsed subscriptions fall back to the Free limit
+        assert!(lockbox.check_entry_size(2048, now + 1).is_err());
+    }
+
+    #[test]
+    fn test_writer_lease_fences_other_devices() {
+        let now = 1_700_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        let (laptop, phone) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Unfenced until a device takes the lease
+        lockbox.check_writer(None, now).unwrap();
+        let err = lockbox.acquire_writer_lease(laptop, 0, now).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidWriterLeaseDuration.into());
+        assert!(lockbox
+            .acquire_writer_lease(laptop, MAX_WRITER_LEASE_DURATION + 1, now)
+            .is_err());
+
+        assert!(!lockbox.acquire_writer_lease(laptop, 60, now).unwrap());
+        lockbox.check_writer(Some(&laptop), now).unwrap();
+        let err = lockbox.check_writer(Some(&phone), now).unwrap_err();
+        assert_eq!(err, LockboxError::WriterLeaseHeld.into());
+        assert!(lockbox.check_writer(None, now).is_err());
+        let err = lockbox.acquire_writer_lease(phone, 60, now + 59).unwrap_err();
+        assert_eq!(err, LockboxError::WriterLeaseHeld.into());
+
+        // The holder renews; another device takes over once it lapses
+        assert!(!lockbox.acquire_writer_lease(laptop, 60, now + 30).unwrap());
+        assert!(lockbox.check_writer(Some(&phone), now + 60).is_err());
+        lockbox.check_writer(Some(&phone), now + 90).unwrap();
+        assert!(lockbox.acquire_writer_lease(phone, 60, now + 90).unwrap());
+        assert_eq!(lockbox.active_writer_at(now + 90).unwrap().device, phone);
+        assert!(lockbox.active_writer_at(now + 150).is_none());
+    }
+
+    #[test]
+    fn test_payload_framing_required() {
+        let mut payload = vec![1u8, 24, 1];
+        payload.extend_from_slice(&[0u8; 40]);
+        check_payload_framing(&payload).unwrap();
+
+        // Unknown version, nonce length or suite, or a truncated body
+        for (index, value) in [(0, 2u8), (1, 12), (2, 0)] {
+            let mut bad = payload.clone();
+            bad[index] = value;
+            let err = check_payload_framing(&bad).unwrap_err();
+            assert_eq!(err, LockboxError::MalformedPayload.into());
+        }
+        assert!(check_payload_framing(&payload[..42]).is_err());
+
+        // Pre-framing payloads are no longer accepted for writes
+        assert!(check_payload_framing(&[7u8; 40]).is_err());
+    }
+
+    #[test]
+    fn test_totp_payload_size_bounds() {
+        for size in [TOTP_MIN_PAYLOAD_SIZE, TOTP_MAX_PAYLOAD_SIZE] {
+            check_entry_type_size(PasswordEntryType::Totp, size).unwrap();
+        }
+        for size in [TOTP_MIN_PAYLOAD_SIZE - 1, TOTP_MAX_PAYLOAD_SIZE + 1] {
+            let err = check_entry_type_size(PasswordEntryType::Totp, size).unwrap_err();
+            assert_eq!(err, LockboxError::InvalidTotpPayload.into());
+        }
+
+        // Other entry types only face the tier's size limit
+        check_entry_type_size(PasswordEntryType::Login, TOTP_MAX_PAYLOAD_SIZE + 1).unwrap();
+    }
+
+    #[test]
+    fn test_write_burst_limited_per_slot_window() {
+        let slot = 250_000_000;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+        assert_eq!(lockbox.effective_write_burst_limit(), 4096);
+
+        for _ in 0..4 {
+            lockbox.record_write_burst(1024, slot).unwrap();
+        }
+        let err = lockbox.record_write_burst(1, slot + 1).unwrap_err();
+        assert_eq!(err, LockboxError::WriteBurstLimited.into());
+        assert_eq!(lockbox.burst_bytes, 4096);
+
+        // A new window starts the budget over
+        lockbox.record_write_burst(1024, slot + WRITE_BURST_WINDOW_SLOTS).unwrap();
+        assert_eq!(lockbox.burst_bytes, 1024);
+
+        // Owners can lower the limit, but never above the tier's
+        lockbox.write_burst_limit = 2048;
+        assert_eq!(lockbox.effective_write_burst_limit(), 2048);
+        assert!(lockbox.record_write_burst(1025, slot + WRITE_BURST_WINDOW_SLOTS).is_err());
+        lockbox.write_burst_limit = 1_000_000;
+        assert_eq!(lockbox.effective_write_burst_limit(), 4096);
+
+        lockbox.subscription_tier = SubscriptionTier::Pro;
+        assert_eq!(lockbox.effective_write_burst_limit(), 262_144);
+    }
+
+    #[test]
+    fn test_heat_map_buckets_reads_by_period() {
+        let period_start = 2_800 * HEAT_MAP_PERIOD;
+        let mut lockbox = new_lockbox(Pubkey::new_unique());
+
+        assert!(lockbox.record_heat_map_read(3, period_start + 10).is_none());
+        assert!(lockbox.record_heat_map_read(3, period_start + 500).is_none());
+        // Categories share a bucket modulo HEAT_MAP_BUCKETS
+        assert!(lockbox
+            .record_heat_map_read(3 + HEAT_MAP_BUCKETS as u32, period_start + 900)
+            .is_none());
+        assert!(lockbox.record_heat_map_read(0, period_start + 900).is_none());
+        assert_eq!(lockbox.heat_map_period_start, period_start);
+
+        // The first read of the next period hands back the finished one
+        let (finished_start, reads) = lockbox
+            .record_heat_map_read(1, period_start + HEAT_MAP_PERIOD + 1)
+            .unwrap();
+        assert_eq!(finished_start, period_start);
+        assert_eq!(reads[3], 3);
+        assert_eq!(reads[0], 1);
+        assert_eq!(reads.iter().map(|&r| r as u32).sum::<u32>(), 4);
+        assert_eq!(lockbox.heat_map_reads[1], 1);
+
+        // Opting out drops the counts; nothing is handed back afterwards
+        lockbox.clear_heat_map();
+        assert!(lockbox
+            .record_heat_map_read(1, period_start + 3 * HEAT_MAP_PERIOD)
+            .is_none());
+        assert_eq!(lockbox.heat_map_reads.iter().map(|&r| r as u32).sum::<u32>(), 1);
+    }
+}
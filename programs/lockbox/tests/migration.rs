@@ -0,0 +1,603 @@
+/*!
+ * ACCOUNT MIGRATION TESTS
+ *
+ * Tests for:
+ * - v0 -> v1 MasterLockbox rewrite preserves every field and the space math
+ * - v1 -> v2 MasterLockbox rewrite starting billing periods at creation
+ * - Accounts that only gain the version byte
+ * - Owner binding and double-migration rejection
+ * - CategoryRegistry rewrite with per-category byte counters
+ * - v1 -> v2 -> v3 EmergencyAccess rewrites with notification settings
+ * - v2 -> v3 EmergencyAccess rewrite dating existing contact invitations
+ * - v1 -> v2 RecoveryConfig rewrite with guardian invitation expiry
+ * - v1 -> v2 RecoveryConfigV2 rewrite keeping the timestamp cooldown
+ * - v1 -> v2 StorageChunk and chunk registration rewrites with layout epochs
+ * - v2 -> v3 -> v4 StorageChunk rewrites leaving existing entries unsplit
+ *   untagged, unscheduled and unattached
+ */
+
+#[cfg(test)]
+mod migration_tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{
+        upgrade_to_v1, upgrade_to_v2, upgrade_to_v3, upgrade_to_v4, MASTER_LOCKBOX_V0_BASE_SPACE, MASTER_LOCKBOX_V1_BASE_SPACE,
+    };
+    use lockbox::state::{
+        Category, CategoryRegistry, DataEntryHeader, EmergencyAccess, EmergencyAccessLevel, EmergencyContact, EmergencyContactStatus,
+        CooldownMode, EmergencyStatus, Guardian, GuardianStatus, GuardianV2, MasterLockbox, OwnerKind, PasswordEntryType,
+        RecoveryConfig, RecoveryConfigV2, StorageChunk,
+        StorageChunkInfo, StorageType, SubscriptionStatus,
+        SubscriptionTier, DEFAULT_DUNNING_WINDOW, DEFAULT_GRACE_PERIOD, DEFAULT_INACTIVITY_PERIOD, DEFAULT_INVITATION_TTL,
+        DEFAULT_RECOVERY_COOLDOWN, DEFAULT_RECOVERY_DELAY,
+        DEFAULT_TRASH_RETENTION, MAX_RECENT_ENTRIES, PRO_MAX_EMERGENCY_CONTACTS,
+    };
+
+    fn v2_lockbox(owner: Pubkey) -> MasterLockbox {
+        MasterLockbox {
+            version: 2,
+            owner,
+            total_entries: 7,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Basic,
+            last_accessed: 1_700_000_100,
+            subscription_expires: 1_702_000_000,
+            total_capacity: 0,
+            storage_used: 512,
+            storage_chunks: Vec::new(),
+            encrypted_index: vec![0xAB; 16],
+            next_entry_id: 8,
+            categories_count: 2,
+            created_at: 1_700_000_000,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: DEFAULT_TRASH_RETENTION,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 1_700_000_000,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: true,
+            has_category_index: false,
+            creator: owner,
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 254,
+        }
+    }
+
+    /// Pre-versioning allocation holding the 16-byte index above
+    fn legacy_space() -> usize {
+        MASTER_LOCKBOX_V0_BASE_SPACE + 16
+    }
+
+    /// Strip the fields added in v2 (billing through shared vault
+    /// usage; empty settings and bump follow them)
+    fn to_v1(v2: &MasterLockbox) -> Vec<u8> {
+        let mut data = Vec::new();
+        v2.try_serialize(&mut data).unwrap();
+        let added_end = data.len() - 4 - 1;
+        data.drain(added_end - 161..added_end);
+        data[8] = 1;
+        data
+    }
+
+    /// Strip the fields added in v1 to get the pre-versioning layout
+    fn to_legacy(v2: &MasterLockbox, allocated: usize) -> Vec<u8> {
+        let mut data = to_v1(v2);
+        let bump = data.pop().unwrap();
+        data.truncate(data.len() - 4); // encrypted_settings (empty)
+        data.pop(); // owner_kind (Wallet)
+        data.truncate(data.len() - 8); // trash_retention
+        data.truncate(data.len() - 1 - 8 * MAX_RECENT_ENTRIES); // recent entries
+        data.remove(8); // version
+        data.push(bump);
+        data.resize(allocated, 0);
+        data
+    }
+
+    #[test]
+    fn test_master_lockbox_v0_upgrade() {
+        let owner = Pubkey::new_unique();
+        let expected = v2_lockbox(owner);
+        let legacy = to_legacy(&expected, legacy_space());
+
+        let upgraded = upgrade_to_v1(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), MASTER_LOCKBOX_V1_BASE_SPACE + 16);
+        let v1_data = to_v1(&expected);
+        assert_eq!(&upgraded[..v1_data.len()], &v1_data[..]);
+
+        let upgraded = upgrade_to_v2(&upgraded, &owner).unwrap();
+        assert_eq!(upgraded.len(), MasterLockbox::calculate_space(0) + 16);
+        let mut expected_data = Vec::new();
+        expected.try_serialize(&mut expected_data).unwrap();
+        assert_eq!(&upgraded[..expected_data.len()], &expected_data[..]);
+
+        let decoded = MasterLockbox::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(decoded.version, MasterLockbox::CURRENT_VERSION);
+        assert_eq!(decoded.owner_kind, OwnerKind::Wallet);
+        assert_eq!(decoded.encrypted_index, vec![0xAB; 16]);
+        assert_eq!(decoded.billing_period_start, decoded.created_at);
+        // The vault has categories, so it has a registry
+        assert!(decoded.has_category_registry);
+        // The PDA stays derived from the wallet that created the vault
+        assert_eq!(decoded.creator, owner);
+
+        let err = upgrade_to_v2(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+    }
+
+    #[test]
+    fn test_other_accounts_gain_version_byte() {
+        let owner = Pubkey::new_unique();
+        let mut legacy = EmergencyAccess::DISCRIMINATOR.to_vec();
+        legacy.extend_from_slice(owner.as_ref());
+        legacy.extend_from_slice(&[9u8; 40]);
+
+        let upgraded = upgrade_to_v1(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), legacy.len() + 1);
+        assert_eq!(&upgraded[..8], &EmergencyAccess::DISCRIMINATOR);
+        assert_eq!(upgraded[8], 1);
+        assert_eq!(&upgraded[9..], &legacy[8..]);
+    }
+
+    #[test]
+    fn test_migration_rejects_wrong_owner_and_repeats() {
+        let owner = Pubkey::new_unique();
+        let legacy = to_legacy(&v2_lockbox(owner), legacy_space());
+
+        let err = upgrade_to_v1(&legacy, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+
+        let upgraded = upgrade_to_v1(&legacy, &owner).unwrap();
+        let err = upgrade_to_v1(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+
+        let err = upgrade_to_v1(&[0u8; 64], &owner).unwrap_err();
+        assert_eq!(err, LockboxError::UnknownAccountType.into());
+    }
+
+    #[test]
+    fn test_category_registry_v0_upgrade() {
+        let owner = Pubkey::new_unique();
+        let mut expected = CategoryRegistry {
+            version: 1,
+            owner,
+            master_lockbox: Pubkey::new_unique(),
+            categories: Vec::new(),
+            next_category_id: 1,
+            created_at: 1_700_000_000,
+            bump: 253,
+        };
+        let mut category = Category::new(0, vec![0xCD; 40], 3, 4, None, 1_700_000_000).unwrap();
+        category.entry_count = 5;
+        expected.categories.push(category);
+
+        let mut v1_data = Vec::new();
+        expected.try_serialize(&mut v1_data).unwrap();
+
+        // discriminator + version + owner + master + vec len, then id, name,
+        // icon, color, parent_id and entry_count precede bytes_used
+        let bytes_used_at = 8 + 1 + 32 + 32 + 4 + 1 + (4 + 40) + 1 + 1 + 1 + 4;
+        let mut legacy = v1_data.clone();
+        legacy.drain(bytes_used_at..bytes_used_at + 8);
+        legacy.remove(8);
+
+        let upgraded = upgrade_to_v1(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), 8 + CategoryRegistry::INIT_SPACE);
+        assert_eq!(&upgraded[..v1_data.len()], &v1_data[..]);
+
+        let decoded = CategoryRegistry::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(decoded.categories[0].entry_count, 5);
+        assert_eq!(decoded.categories[0].bytes_used, 0);
+    }
+
+    #[test]
+    fn test_emergency_access_v1_upgrade() {
+        let owner = Pubkey::new_unique();
+        let expected = EmergencyAccess {
+            version: 3,
+            owner,
+            emergency_contacts: Vec::new(),
+            inactivity_period: DEFAULT_INACTIVITY_PERIOD,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            notification_interval: 0,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            last_activity: 1_700_000_000,
+            countdown_started: Some(1_708_000_000),
+            last_notified_at: 1_708_000_000,
+            status: EmergencyStatus::CountdownStarted,
+            created_at: 1_690_000_000,
+            export_consent: None,
+            bump: 252,
+        };
+        let mut v3_data = Vec::new();
+        expected.try_serialize(&mut v3_data).unwrap();
+
+        // discriminator + version + owner + empty contacts + both periods
+        // precede notification_interval, then invitation_ttl; last_activity
+        // and the Some(...) countdown precede last_notified_at
+        let interval_at = 8 + 1 + 32 + 4 + 8 + 8;
+        let ttl_at = interval_at + 8;
+        let notified_at = ttl_at + 8 + 8 + 9;
+        // v3 also adds the export consent (None) before the bump
+        let mut v2_data = v3_data.clone();
+        v2_data.remove(v2_data.len() - 2);
+        v2_data.drain(ttl_at..ttl_at + 8);
+        v2_data[8] = 2;
+        let mut legacy = v3_data.clone();
+        legacy.remove(legacy.len() - 2);
+        legacy.drain(notified_at..notified_at + 8);
+        legacy.drain(interval_at..ttl_at + 8);
+        legacy[8] = 1;
+
+        let upgraded = upgrade_to_v2(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), 8 + EmergencyAccess::INIT_SPACE);
+        assert_eq!(&upgraded[..v2_data.len()], &v2_data[..]);
+
+        let err = upgrade_to_v2(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v1(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v2(&legacy, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+        let err = upgrade_to_v3(&legacy, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::MigrationOutOfOrder.into());
+
+        let mut unversioned = legacy.clone();
+        unversioned.remove(8);
+        let err = upgrade_to_v2(&unversioned, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::MigrationOutOfOrder.into());
+
+        let upgraded = upgrade_to_v3(&upgraded, &owner).unwrap();
+        assert_eq!(upgraded.len(), 8 + EmergencyAccess::INIT_SPACE);
+        assert_eq!(&upgraded[..v3_data.len()], &v3_data[..]);
+        let err = upgrade_to_v3(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+    }
+
+    #[test]
+    fn test_emergency_contacts_v2_upgrade() {
+        let owner = Pubkey::new_unique();
+        let added_at = 1_700_000_000;
+        let contact = EmergencyContact {
+            contact_pubkey: Pubkey::new_unique(),
+            contact_name_encrypted: vec![5u8; 32],
+            access_level: EmergencyAccessLevel::ViewOnly,
+            encrypted_key: vec![6u8; 104],
+            added_at,
+            access_granted_at: None,
+            status: EmergencyContactStatus::PendingAcceptance,
+            invited_at: added_at,
+            expires_at: added_at + DEFAULT_INVITATION_TTL,
+        };
+        let expected = EmergencyAccess {
+            version: 3,
+            owner,
+            emergency_contacts: vec![contact; PRO_MAX_EMERGENCY_CONTACTS],
+            inactivity_period: DEFAULT_INACTIVITY_PERIOD,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            notification_interval: 0,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            last_activity: added_at,
+            countdown_started: None,
+            last_notified_at: 0,
+            status: EmergencyStatus::Active,
+            created_at: added_at,
+            export_consent: None,
+            bump: 252,
+        };
+        let mut v3_data = Vec::new();
+        expected.try_serialize(&mut v3_data).unwrap();
+
+        // Each contact ends with its invitation fields; invitation_ttl
+        // follows notification_interval after the contact list
+        let contacts_at = 8 + 1 + 32 + 4;
+        let contact_len = 32 + (4 + 32) + 1 + (4 + 104) + 8 + 1 + 1 + 16;
+        let ttl_at = contacts_at + PRO_MAX_EMERGENCY_CONTACTS * contact_len + 8 + 8 + 8;
+        let mut legacy = v3_data.clone();
+        legacy.remove(legacy.len() - 2);
+        legacy.drain(ttl_at..ttl_at + 8);
+        for i in (1..=PRO_MAX_EMERGENCY_CONTACTS).rev() {
+            let end = contacts_at + i * contact_len;
+            legacy.drain(end - 16..end);
+        }
+        legacy[8] = 2;
+
+        // Pro accounts grown past the default allocation keep their contacts
+        let upgraded = upgrade_to_v3(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), EmergencyAccess::space_for(PRO_MAX_EMERGENCY_CONTACTS));
+        assert_eq!(&upgraded[..v3_data.len()], &v3_data[..]);
+    }
+
+    #[test]
+    fn test_recovery_config_v1_upgrade() {
+        let owner = Pubkey::new_unique();
+        let added_at = 1_700_000_000;
+        let expected = RecoveryConfig {
+            version: 2,
+            owner,
+            threshold: 1,
+            total_guardians: 1,
+            guardians: vec![Guardian {
+                guardian_pubkey: Pubkey::new_unique(),
+                share_index: 1,
+                encrypted_share: vec![7u8; 104],
+                added_at,
+                nickname_encrypted: vec![3u8; 24],
+                status: GuardianStatus::PendingAcceptance,
+                invited_at: added_at,
+                expires_at: added_at + DEFAULT_INVITATION_TTL,
+                accepted_at: None,
+                acceptance_hash: [0u8; 32],
+            }],
+            recovery_delay: DEFAULT_RECOVERY_DELAY,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            recovery_disabled: false,
+            owner_activity_window: 0,
+            created_at: added_at,
+            last_modified: added_at,
+            last_request_id: 4,
+            bump: 253,
+        };
+        let mut v2_data = Vec::new();
+        expected.try_serialize(&mut v2_data).unwrap();
+
+        // The guardian's invitation and acceptance fields end it;
+        // invitation_ttl, recovery_disabled and owner_activity_window follow
+        // recovery_delay right after the guardian list
+        let added = 16 + 1 + 32;
+        let guardian_end = 8 + 1 + 32 + 1 + 1 + 4 + 32 + 1 + (4 + 104) + 8 + (4 + 24) + 1 + added;
+        let ttl_at = guardian_end + 8;
+        let mut legacy = v2_data.clone();
+        legacy.drain(ttl_at..ttl_at + 8 + 1 + 8);
+        legacy.drain(guardian_end - added..guardian_end);
+        legacy[8] = 1;
+
+        let upgraded = upgrade_to_v2(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), RecoveryConfig::space_for(1));
+        assert_eq!(&upgraded[..v2_data.len()], &v2_data[..]);
+
+        let err = upgrade_to_v2(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v2(&legacy, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+    }
+
+    #[test]
+    fn test_recovery_config_v2_v1_upgrade() {
+        let owner = Pubkey::new_unique();
+        let expected = RecoveryConfigV2 {
+            version: 2,
+            owner,
+            threshold: 1,
+            total_guardians: 1,
+            guardians: vec![GuardianV2 {
+                guardian_pubkey: Pubkey::new_unique(),
+                share_index: 1,
+                share_commitment: [5u8; 32],
+                added_at: 1_700_000_000,
+                nickname_encrypted: vec![3u8; 24],
+                status: GuardianStatus::Active,
+            }],
+            recovery_delay: DEFAULT_RECOVERY_DELAY,
+            created_at: 1_700_000_000,
+            last_modified: 1_700_000_000,
+            last_request_id: 2,
+            master_secret_hash: [9u8; 32],
+            last_recovery_attempt: 1_700_100_000,
+            cooldown_mode: CooldownMode::Timestamp,
+            recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+            last_recovery_attempt_slot: 0,
+            last_recovery_attempt_epoch: 0,
+            bump: 251,
+        };
+        let mut v2_data = Vec::new();
+        expected.try_serialize(&mut v2_data).unwrap();
+
+        // The cooldown mode and its three counters sit just before the bump
+        let mut legacy = v2_data.clone();
+        let bump_at = legacy.len() - 1;
+        legacy.drain(bump_at - (1 + 8 + 8 + 8)..bump_at);
+        legacy[8] = 1;
+
+        let upgraded = upgrade_to_v2(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), RecoveryConfigV2::space_for(1));
+        assert_eq!(&upgraded[..v2_data.len()], &v2_data[..]);
+
+        let err = upgrade_to_v2(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v2(&legacy, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+    }
+
+    #[test]
+    fn test_storage_chunk_v1_upgrade() {
+        let owner = Pubkey::new_unique();
+        let master_key = Pubkey::new_unique();
+        let mut expected = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        expected
+            .initialize(
+                master_key,
+                owner,
+                3,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                252,
+                1_700_000_000,
+            )
+            .unwrap();
+        let mut current_data = Vec::new();
+        expected.try_serialize(&mut current_data).unwrap();
+
+        // layout_epoch sits right before the bump
+        let allocated = StorageChunk::BASE_SPACE - 4 + StorageChunk::MIN_CHUNK_SIZE as usize;
+        let mut legacy = current_data.clone();
+        let bump_at = legacy.len() - 1;
+        legacy.drain(bump_at - 4..bump_at);
+        legacy[8] = 1;
+        legacy.resize(allocated, 0);
+
+        let upgraded = upgrade_to_v2(&legacy, &owner).unwrap();
+        assert_eq!(upgraded.len(), StorageChunk::BASE_SPACE + StorageChunk::MIN_CHUNK_SIZE as usize);
+        // Without entry headers, v2 only differs from v4 in the version byte
+        assert_eq!(upgraded[8], 2);
+        assert_eq!(&upgraded[9..current_data.len()], &current_data[9..]);
+
+        let err = upgrade_to_v2(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v2(&legacy, &master_key).unwrap_err();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+
+        let upgraded = upgrade_to_v3(&upgraded, &owner).unwrap();
+        let upgraded = upgrade_to_v4(&upgraded, &owner).unwrap();
+        let decoded = StorageChunk::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(decoded.version, StorageChunk::CURRENT_VERSION);
+
+        // Registered chunk infos gain the epoch too
+        let mut lockbox = v2_lockbox(owner);
+        lockbox.storage_chunks.push(StorageChunkInfo {
+            chunk_address: Pubkey::new_unique(),
+            chunk_index: 3,
+            max_capacity: StorageChunk::MIN_CHUNK_SIZE,
+            size_used: 0,
+            data_type: StorageType::Passwords,
+            created_at: 1_700_000_000,
+            last_modified: 1_700_000_000,
+            layout_epoch: 0,
+        });
+        lockbox.storage_chunks_count = 4;
+        let mut v1_data = to_v1(&lockbox);
+        // discriminator through the chunk vec length, then the v1 info
+        let epoch_at = 8 + 1 + 32 + 8 + 2 + 1 + 8 + 8 + 8 + 8 + 4 + 59;
+        v1_data.drain(epoch_at..epoch_at + 4);
+        v1_data.resize(MASTER_LOCKBOX_V1_BASE_SPACE + 59 + 16, 0);
+
+        let upgraded = upgrade_to_v2(&v1_data, &owner).unwrap();
+        assert_eq!(upgraded.len(), MasterLockbox::calculate_space(1) + 16);
+        let decoded = MasterLockbox::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(decoded.storage_chunks[0].chunk_index, 3);
+        assert_eq!(decoded.storage_chunks[0].layout_epoch, 0);
+        assert_eq!(decoded.encrypted_index, vec![0xAB; 16]);
+    }
+
+    #[test]
+    fn test_storage_chunk_v2_upgrade_keeps_entries_whole() {
+        let owner = Pubkey::new_unique();
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                Pubkey::new_unique(),
+                owner,
+                0,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                251,
+                1_700_000_000,
+            )
+            .unwrap();
+        for entry_id in 1..=2 {
+            let header = DataEntryHeader {
+                entry_id,
+                offset: chunk.current_size,
+                size: 43,
+                entry_type: PasswordEntryType::Login,
+                category: 0,
+                title_hash: [entry_id as u8; 32],
+                created_at: 1_700_000_000,
+                last_modified: 1_700_000_000,
+                access_count: 3,
+                flags: 0x01,
+                next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+                next_offset: 0,
+                tag_mask: 0,
+                rotate_after: 0,
+                parent_entry: 0,
+                linked_entry_ids: Vec::new(),
+            };
+            chunk.add_entry(header, vec![entry_id as u8; 43], 1_700_000_000).unwrap();
+        }
+        let mut current_data = Vec::new();
+        chunk.try_serialize(&mut current_data).unwrap();
+
+        // Each v2 header lacks the trailing next_chunk, next_offset, tag_mask,
+        // rotate_after, parent_entry and linked_entry_ids; each v3 header only
+        // the last four
+        let headers_at = 8 + 1 + 32 + 32 + 2 + 4 + 4 + 1 + 4 + 86 + 4;
+        let strip = |trailing: usize, version: u8| {
+            let mut legacy = current_data.clone();
+            for header in (0..2).rev() {
+                let tail_at = headers_at + (header + 1) * 102 - trailing;
+                legacy.drain(tail_at..tail_at + trailing);
+            }
+            legacy[8] = version;
+            legacy
+        };
+        let (v2_data, v3_data) = (strip(28, 2), strip(22, 3));
+
+        let upgraded = upgrade_to_v3(&v2_data, &owner).unwrap();
+        assert_eq!(upgraded, v3_data);
+        let err = upgrade_to_v3(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+        let err = upgrade_to_v4(&v2_data, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::MigrationOutOfOrder.into());
+
+        let upgraded = upgrade_to_v4(&upgraded, &owner).unwrap();
+        assert_eq!(upgraded, current_data);
+        let decoded = StorageChunk::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert!(decoded
+            .entry_headers
+            .iter()
+            .all(|h| !h.spans_chunks()
+                && h.tag_mask == 0
+                && h.rotate_after == 0
+                && h.parent_entry == 0
+                && h.linked_entry_ids.is_empty()));
+
+        let err = upgrade_to_v4(&upgraded, &owner).unwrap_err();
+        assert_eq!(err, LockboxError::AccountAlreadyMigrated.into());
+    }
+}
@@ -0,0 +1,210 @@
+/*!
+ * INDEX REBUILD TESTS
+ *
+ * Tests for:
+ * - Recomputing master lockbox usage, capacity and entry counts from chunks
+ * - Raising (never lowering) the next program-assigned entry ID
+ * - Recounting per-category entries and bytes
+ */
+
+#[cfg(test)]
+mod rebuild_indexes_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::instructions::{recount_categories, rebuild_master_aggregates};
+    use lockbox::state::{
+        Category, CategoryRegistry, DataEntryHeader, MasterLockbox, OwnerKind, PasswordEntryType,
+        StorageChunk, StorageChunkInfo, StorageType, SubscriptionStatus, SubscriptionTier,
+        DEFAULT_DUNNING_WINDOW, MAX_RECENT_ENTRIES,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_lockbox(chunk_indexes: &[u16]) -> MasterLockbox {
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(Pubkey::new_unique(), 255, NOW).unwrap();
+        for index in chunk_indexes {
+            lockbox
+                .add_chunk(StorageChunkInfo {
+                    chunk_address: Pubkey::new_unique(),
+                    chunk_index: *index,
+                    max_capacity: 1024,
+                    size_used: 0,
+                    data_type: StorageType::Passwords,
+                    created_at: NOW,
+                    last_modified: NOW,
+                    layout_epoch: 0,
+                })
+                .unwrap();
+        }
+        lockbox
+    }
+
+    /// Chunk holding `(entry_id, category, flags)` entries of 40 bytes each
+    fn chunk_with(chunk_index: u16, entries: &[(u64, u32, u8)]) -> StorageChunk {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(Pubkey::default(), Pubkey::default(), chunk_index, 1024, StorageType::Passwords, 255, NOW)
+            .unwrap();
+        for (entry_id, category, flags) in entries {
+            let header = DataEntryHeader {
+                entry_id: *entry_id,
+                offset: chunk.current_size,
+                size: 40,
+                entry_type: PasswordEntryType::Login,
+                category: *category,
+                title_hash: [0u8; 32],
+                created_at: NOW,
+                last_modified: NOW,
+                access_count: 0,
+                flags: *flags,
+                next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+                next_offset: 0,
+                tag_mask: 0,
+                rotate_after: 0,
+                parent_entry: 0,
+                linked_entry_ids: Vec::new(),
+            };
+            chunk.add_entry(header, vec![0u8; 40], NOW).unwrap();
+        }
+        chunk
+    }
+
+    #[test]
+    fn test_master_aggregates_recomputed_from_chunks() {
+        let mut lockbox = new_lockbox(&[0, 2]);
+        let chunks = [
+            chunk_with(0, &[(1, 0, 0), (2, 0, DataEntryHeader::FLAG_TRASHED), (3, 0, DataEntryHeader::FLAG_ALIAS)]),
+            chunk_with(2, &[(1, 0, DataEntryHeader::FLAG_CONTINUATION), (4, 0, 0)]),
+        ];
+
+        // Drift left behind by a broken write
+        lockbox.total_entries = 9;
+        lockbox.storage_used = 7;
+        lockbox.total_capacity = 1;
+        lockbox.storage_chunks_count = 5;
+        lockbox.storage_chunks[1].size_used = 500;
+        assert!(!lockbox.is_storage_used_consistent());
+
+        rebuild_master_aggregates(&mut lockbox, &chunks);
+
+        // Aliases and trashed entries count; continuation segments do not
+        assert_eq!(lockbox.total_entries, 4);
+        assert_eq!(lockbox.storage_chunks[0].size_used, 120);
+        assert_eq!(lockbox.storage_chunks[1].size_used, 80);
+        assert_eq!(lockbox.storage_used, 200);
+        assert_eq!(lockbox.total_capacity, 2048);
+        assert_eq!(lockbox.storage_chunks_count, 3);
+        assert!(lockbox.is_storage_used_consistent());
+        assert_eq!(lockbox.next_entry_id, 5);
+    }
+
+    #[test]
+    fn test_next_entry_id_only_raised_past_program_ids() {
+        let mut lockbox = new_lockbox(&[0]);
+        let client_id = MasterLockbox::CLIENT_ENTRY_ID_BIT | 42;
+        let chunks = [chunk_with(0, &[(3, 0, 0), (client_id, 0, 0)])];
+
+        lockbox.next_entry_id = 10;
+        rebuild_master_aggregates(&mut lockbox, &chunks);
+        assert_eq!(lockbox.next_entry_id, 10);
+
+        lockbox.next_entry_id = 2;
+        rebuild_master_aggregates(&mut lockbox, &chunks);
+        assert_eq!(lockbox.next_entry_id, 4);
+
+        // An empty vault keeps its counter and reports no usage
+        let mut empty = new_lockbox(&[]);
+        empty.next_entry_id = 6;
+        empty.total_entries = 3;
+        rebuild_master_aggregates(&mut empty, &[]);
+        assert_eq!((empty.next_entry_id, empty.total_entries, empty.storage_chunks_count), (6, 0, 0));
+    }
+
+    #[test]
+    fn test_category_counts_recounted_from_headers() {
+        let mut registry = CategoryRegistry {
+            version: CategoryRegistry::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            master_lockbox: Pubkey::new_unique(),
+            categories: Vec::new(),
+            next_category_id: 0,
+            created_at: NOW,
+            bump: 255,
+        };
+        for id in [1, 2] {
+            registry
+                .add_category(Category::new(id, vec![0u8; 40], 0, 0, None, NOW).unwrap())
+                .unwrap();
+        }
+        registry.record_usage(2, 5, 9_999);
+
+        let chunks = [
+            chunk_with(0, &[(1, 1, 0), (2, 1, DataEntryHeader::FLAG_ALIAS), (3, 7, 0)]),
+            chunk_with(1, &[(1, 1, DataEntryHeader::FLAG_CONTINUATION), (4, 1, DataEntryHeader::FLAG_TRASHED)]),
+        ];
+        recount_categories(&mut registry, &chunks);
+
+        // A spanning entry counts once with all its bytes; aliases and
+        // unregistered categories are not tracked
+        let work = registry.get_category(1).unwrap();
+        assert_eq!((work.entry_count, work.bytes_used), (2, 120));
+        let personal = registry.get_category(2).unwrap();
+        assert_eq!((personal.entry_count, personal.bytes_used), (0, 0));
+    }
+}
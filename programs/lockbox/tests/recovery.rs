@@ -0,0 +1,634 @@
+/*!
+ * SOCIAL RECOVERY TESTS
+ *
+ * Tests for:
+ * - Recovery requests that block closing the recovery config
+ * - Recovery configs bound to their owner's PDA
+ * - Recovery requests bound to their config (owner + request ID)
+ * - Owners can only cancel their own recovery requests
+ * - Only the requester or new owner can complete a V1 recovery
+ * - A seller's guardians cannot recover a vault that has been sold
+ * - Guardians can only approve requests made against their config
+ * - Lapsed guardian invitations expire while accepted guardians stay
+ * - Configs arm only once accepted guardians meet the threshold
+ * - V2 guardian limits and unique keys and share indexes
+ * - Acceptance hashes bind the config, share and agreed terms
+ * - Recent owner activity blocks recovery within the configured window
+ * - V2 recovery cooldowns measured in seconds, slots or epochs
+ */
+
+#[cfg(test)]
+mod recovery_tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::error::ErrorCode;
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{
+        AcceptGuardianship, AcceptGuardianshipBumps, ApproveRecovery, ApproveRecoveryBumps,
+        CancelRecovery, CancelRecoveryBumps,
+        CompleteRecovery, CompleteRecoveryBumps, CompleteRecoveryV2, CompleteRecoveryV2Bumps, ConfirmParticipation,
+        ConfirmParticipationBumps,
+    };
+    use lockbox::state::{
+        CooldownMode, Guardian, GuardianAgreement, GuardianStatus, GuardianV2, MasterLockbox, OwnerKind, RecoveryChallenge, RecoveryConfig,
+        RecoveryConfigV2, RecoveryRequest, RecoveryRequestV2, RecoveryStatus, SubscriptionStatus,
+        SubscriptionTier, DEFAULT_DUNNING_WINDOW, DEFAULT_INVITATION_TTL, DEFAULT_RECOVERY_COOLDOWN,
+        DEFAULT_RECOVERY_DELAY, MAX_RECENT_ENTRIES, RECOVERY_EXPIRATION_PERIOD,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_request(status: RecoveryStatus) -> RecoveryRequest {
+        let ready_at = NOW + DEFAULT_RECOVERY_DELAY;
+        RecoveryRequest {
+            version: RecoveryRequest::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            requester: Pubkey::new_unique(),
+            request_id: 1,
+            requested_at: NOW,
+            ready_at,
+            approvals: Vec::new(),
+            new_owner: None,
+            status,
+            expires_at: ready_at + RECOVERY_EXPIRATION_PERIOD,
+            bump: 255,
+        }
+    }
+
+    fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &lockbox::ID)
+    }
+
+    fn config_v1(owner: Pubkey) -> (Pubkey, RecoveryConfig) {
+        let (address, bump) = pda(&[b"recovery_config", owner.as_ref()]);
+        let config = RecoveryConfig {
+            version: RecoveryConfig::CURRENT_VERSION,
+            owner,
+            threshold: 1,
+            total_guardians: 0,
+            guardians: Vec::new(),
+            recovery_delay: DEFAULT_RECOVERY_DELAY,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            recovery_disabled: false,
+            owner_activity_window: 0,
+            created_at: NOW,
+            last_modified: NOW,
+            last_request_id: 0,
+            bump,
+        };
+        (address, config)
+    }
+
+    fn request_v1(owner: Pubkey, request_id: u64) -> (Pubkey, RecoveryRequest) {
+        let (address, bump) = pda(&[b"recovery_request", owner.as_ref(), &request_id.to_le_bytes()]);
+        let request = RecoveryRequest {
+            owner,
+            request_id,
+            bump,
+            ..new_request(RecoveryStatus::Pending)
+        };
+        (address, request)
+    }
+
+    fn master_lockbox(owner: Pubkey) -> (Pubkey, MasterLockbox) {
+        let (address, bump) = pda(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()]);
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Premium,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(owner, bump, NOW).unwrap();
+        (address, lockbox)
+    }
+
+    fn config_v2(owner: Pubkey) -> (Pubkey, RecoveryConfigV2) {
+        let (address, bump) = pda(&[b"recovery_config_v2", owner.as_ref()]);
+        let config = RecoveryConfigV2 {
+            version: RecoveryConfigV2::CURRENT_VERSION,
+            owner,
+            threshold: 1,
+            total_guardians: 0,
+            guardians: Vec::new(),
+            recovery_delay: DEFAULT_RECOVERY_DELAY,
+            created_at: NOW,
+            last_modified: NOW,
+            last_request_id: 1,
+            master_secret_hash: [0u8; 32],
+            last_recovery_attempt: NOW,
+            cooldown_mode: CooldownMode::Timestamp,
+            recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+            last_recovery_attempt_slot: 0,
+            last_recovery_attempt_epoch: 0,
+            bump,
+        };
+        (address, config)
+    }
+
+    fn request_v2(owner: Pubkey, request_id: u64) -> (Pubkey, RecoveryRequestV2) {
+        let (address, bump) = pda(&[b"recovery_request_v2", owner.as_ref(), &request_id.to_le_bytes()]);
+        let ready_at = NOW + DEFAULT_RECOVERY_DELAY;
+        let request = RecoveryRequestV2 {
+            version: RecoveryRequestV2::CURRENT_VERSION,
+            owner,
+            requester: Pubkey::new_unique(),
+            request_id,
+            requested_at: NOW,
+            ready_at,
+            expires_at: ready_at + RECOVERY_EXPIRATION_PERIOD,
+            challenge: RecoveryChallenge {
+                encrypted_challenge: vec![0u8; 80],
+                challenge_hash: [0u8; 32],
+                created_at: NOW,
+            },
+            participating_guardians: Vec::new(),
+            new_owner: None,
+            status: RecoveryStatus::Pending,
+            bump,
+        };
+        (address, request)
+    }
+
+    /// Program-owned account holding `value`, leaked for the `'static` context
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            &lockbox::ID,
+            false,
+            0,
+        )
+    }
+
+    /// Empty system-owned account
+    fn wallet(key: Pubkey, is_signer: bool) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(0u64)),
+            Box::leak(Vec::new().into_boxed_slice()),
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        )
+    }
+
+    /// Run the context's account validation, as the program entrypoint would
+    fn validate<T, B>(accounts: Vec<AccountInfo<'static>>) -> Result<T>
+    where
+        T: Accounts<'static, B>,
+        B: Default,
+    {
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        T::try_accounts(&lockbox::ID, &mut accounts, &[], &mut B::default(), &mut BTreeSet::new())
+    }
+
+    fn confirm_participation(
+        config: AccountInfo<'static>,
+        request: AccountInfo<'static>,
+    ) -> Result<ConfirmParticipation<'static>> {
+        validate::<_, ConfirmParticipationBumps>(vec![
+            config,
+            request,
+            wallet(Pubkey::new_unique(), true),
+        ])
+    }
+
+    #[test]
+    fn test_request_in_progress_until_finished_or_expired() {
+        let pending = new_request(RecoveryStatus::Pending);
+        assert!(pending.is_in_progress(NOW));
+        assert!(pending.is_in_progress(pending.expires_at - 1));
+        assert!(!pending.is_in_progress(pending.expires_at));
+
+        assert!(new_request(RecoveryStatus::ReadyForReconstruction).is_in_progress(NOW));
+        for status in [RecoveryStatus::Completed, RecoveryStatus::Cancelled, RecoveryStatus::Expired] {
+            assert!(!new_request(status).is_in_progress(NOW));
+        }
+    }
+
+    #[test]
+    fn test_lapsed_guardian_invitations_expire() {
+        let (_, mut config) = config_v1(Pubkey::new_unique());
+        let guardian = |share_index: u8, status: GuardianStatus, expires_at: i64| Guardian {
+            guardian_pubkey: Pubkey::new_unique(),
+            share_index,
+            encrypted_share: vec![0u8; 104],
+            added_at: NOW,
+            nickname_encrypted: Vec::new(),
+            status,
+            invited_at: NOW,
+            expires_at,
+            accepted_at: None,
+            acceptance_hash: [0u8; 32],
+        };
+        let lapsed = guardian(1, GuardianStatus::PendingAcceptance, NOW + 10);
+        let open = guardian(2, GuardianStatus::PendingAcceptance, NOW + 20);
+        let accepted = guardian(3, GuardianStatus::Active, NOW + 10);
+        config.guardians = vec![lapsed.clone(), open.clone(), accepted.clone()];
+        config.total_guardians = 3;
+
+        assert!(config.expire_invitations(NOW + 9).is_empty());
+
+        let expired = config.expire_invitations(NOW + 10);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].guardian_pubkey, lapsed.guardian_pubkey);
+        assert_eq!(config.total_guardians, 2);
+        assert!(config.get_guardian(&open.guardian_pubkey).is_some());
+        assert!(config.is_active_guardian(&accepted.guardian_pubkey));
+        assert!(open.is_invitation_expired(NOW + 20));
+        assert_eq!(config.invitation_ttl, DEFAULT_INVITATION_TTL);
+    }
+
+    #[test]
+    fn test_config_armed_by_accepted_guardians() {
+        let (_, mut config) = config_v1(Pubkey::new_unique());
+        config.threshold = 2;
+        for share_index in 1..=3 {
+            config.guardians.push(Guardian {
+                guardian_pubkey: Pubkey::new_unique(),
+                share_index,
+                encrypted_share: vec![0u8; 104],
+                added_at: NOW,
+                nickname_encrypted: Vec::new(),
+                status: GuardianStatus::PendingAcceptance,
+                invited_at: NOW,
+                expires_at: NOW + DEFAULT_INVITATION_TTL,
+                accepted_at: None,
+                acceptance_hash: [0u8; 32],
+            });
+        }
+
+        // Three guardians listed, but none accepted
+        assert!(!config.is_armed());
+        config.guardians[0].status = GuardianStatus::Active;
+        assert!(!config.is_armed());
+        config.guardians[2].status = GuardianStatus::Active;
+        assert!(config.is_armed());
+        config.guardians[2].status = GuardianStatus::Revoked;
+        assert!(!config.is_armed());
+
+        let (_, v2) = config_v2(Pubkey::new_unique());
+        assert!(!v2.is_armed());
+    }
+
+    #[test]
+    fn test_v2_guardians_unique_and_limited() {
+        let (_, mut config) = config_v2(Pubkey::new_unique());
+        let guardian = |share_index| GuardianV2 {
+            guardian_pubkey: Pubkey::new_unique(),
+            share_index,
+            share_commitment: [0u8; 32],
+            added_at: NOW,
+            nickname_encrypted: Vec::new(),
+            status: GuardianStatus::PendingAcceptance,
+        };
+
+        config.add_guardian(guardian(1), 2).unwrap();
+        assert_eq!(config.total_guardians, 1);
+
+        let mut same_key = guardian(2);
+        same_key.guardian_pubkey = config.guardians[0].guardian_pubkey;
+        let err = config.add_guardian(same_key, 2).unwrap_err();
+        assert_eq!(err, LockboxError::GuardianAlreadyExists.into());
+
+        let err = config.add_guardian(guardian(1), 2).unwrap_err();
+        assert_eq!(err, LockboxError::DuplicateShareIndex.into());
+        let err = config.add_guardian(guardian(0), 2).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidShareIndex.into());
+
+        let mut long_nickname = guardian(2);
+        long_nickname.nickname_encrypted = vec![0u8; 65];
+        let err = config.add_guardian(long_nickname, 2).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidNicknameSize.into());
+
+        config.add_guardian(guardian(2), 2).unwrap();
+        let err = config.add_guardian(guardian(3), 2).unwrap_err();
+        assert_eq!(err, LockboxError::TooManyGuardians.into());
+        assert_eq!(config.total_guardians, 2);
+    }
+
+    #[test]
+    fn test_recent_owner_activity_blocks_recovery() {
+        let (_, mut config) = config_v1(Pubkey::new_unique());
+        let last_accessed = NOW - 24 * 60 * 60;
+
+        // No window configured
+        assert!(!config.is_owner_recently_active(last_accessed, NOW));
+
+        config.owner_activity_window = 7 * 24 * 60 * 60;
+        assert!(config.is_owner_recently_active(last_accessed, NOW));
+        assert!(!config.is_owner_recently_active(last_accessed, last_accessed + config.owner_activity_window));
+        // A vault that was never accessed has no activity to protect
+        assert!(!config.is_owner_recently_active(0, NOW));
+    }
+
+    #[test]
+    fn test_acceptance_hash_binds_config_share_and_terms() {
+        let (address, _) = config_v1(Pubkey::new_unique());
+        let guardian = Guardian {
+            guardian_pubkey: Pubkey::new_unique(),
+            share_index: 1,
+            encrypted_share: vec![4u8; 104],
+            added_at: NOW,
+            nickname_encrypted: Vec::new(),
+            status: GuardianStatus::Active,
+            invited_at: NOW,
+            expires_at: NOW + DEFAULT_INVITATION_TTL,
+            accepted_at: Some(NOW),
+            acceptance_hash: [0u8; 32],
+        };
+        let terms = [9u8; 32];
+        let hash = guardian.acceptance_hash(&address, &terms, NOW);
+        assert_eq!(hash, guardian.acceptance_hash(&address, &terms, NOW));
+
+        let mut other_share = guardian.clone();
+        other_share.encrypted_share[0] ^= 1;
+        for other in [
+            guardian.acceptance_hash(&Pubkey::new_unique(), &terms, NOW),
+            guardian.acceptance_hash(&address, &[0u8; 32], NOW),
+            guardian.acceptance_hash(&address, &terms, NOW + 1),
+            other_share.acceptance_hash(&address, &terms, NOW),
+        ] {
+            assert_ne!(hash, other);
+        }
+    }
+
+    #[test]
+    fn test_accept_guardianship_requires_config_pda() {
+        let (address, config) = config_v1(Pubkey::new_unique());
+        let guardian = Pubkey::new_unique();
+        let accept = |config_address: Pubkey| {
+            let (agreement, _) = pda(&[
+                GuardianAgreement::SEEDS_PREFIX,
+                config_address.as_ref(),
+                guardian.as_ref(),
+            ]);
+            validate::<AcceptGuardianship, AcceptGuardianshipBumps>(vec![
+                program_account(config_address, &config),
+                wallet(agreement, false),
+                wallet(guardian, true),
+            ])
+        };
+
+        accept(address).unwrap();
+
+        // A copy of the config at any other address is rejected
+        let err = accept(Pubkey::new_unique()).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+    }
+
+    #[test]
+    fn test_confirm_participation_binds_request_to_config() {
+        let (owner, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_address, config) = config_v2(owner);
+        let (request_address, request) = request_v2(owner, 1);
+
+        confirm_participation(
+            program_account(config_address, &config),
+            program_account(request_address, &request),
+        )
+        .unwrap();
+
+        // Another owner's request, at its own valid PDA
+        let (foreign_address, foreign) = request_v2(other, 1);
+        let err = confirm_participation(
+            program_account(config_address, &config),
+            program_account(foreign_address, &foreign),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        // The owner's config substituted from a non-PDA address
+        let err = confirm_participation(
+            program_account(Pubkey::new_unique(), &config),
+            program_account(request_address, &request),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        // Request data naming a different owner than its address implies
+        let mut forged = request.clone();
+        forged.owner = other;
+        let err = confirm_participation(
+            program_account(config_address, &config),
+            program_account(request_address, &forged),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, LockboxError::RecoveryRequestMismatch.into());
+    }
+
+    #[test]
+    fn test_cancel_recovery_rejects_other_owners_request() {
+        let (owner, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_address, config) = config_v1(owner);
+        let cancel = |request_address: Pubkey, request: &RecoveryRequest| {
+            validate::<CancelRecovery, CancelRecoveryBumps>(vec![
+                program_account(config_address, &config),
+                program_account(request_address, request),
+                wallet(owner, true),
+            ])
+        };
+
+        let (address, request) = request_v1(owner, 1);
+        cancel(address, &request).unwrap();
+
+        let (foreign_address, foreign) = request_v1(other, 1);
+        let err = cancel(foreign_address, &foreign).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        let mut forged = request.clone();
+        forged.owner = other;
+        let err = cancel(address, &forged).err().unwrap();
+        assert_eq!(err, LockboxError::RecoveryRequestMismatch.into());
+    }
+
+    #[test]
+    fn test_complete_recovery_requires_requester_or_new_owner() {
+        let owner = Pubkey::new_unique();
+        let (config_address, config) = config_v1(owner);
+        let (master_address, master) = master_lockbox(owner);
+        let (request_address, mut request) = request_v1(owner, 1);
+        let new_owner = Pubkey::new_unique();
+        request.new_owner = Some(new_owner);
+        request.status = RecoveryStatus::ReadyForReconstruction;
+
+        let complete = |caller: Pubkey, is_signer: bool| {
+            validate::<CompleteRecovery, CompleteRecoveryBumps>(vec![
+                program_account(config_address, &config),
+                program_account(request_address, &request),
+                program_account(master_address, &master),
+                wallet(caller, is_signer),
+            ])
+        };
+
+        complete(request.requester, true).unwrap();
+        complete(new_owner, true).unwrap();
+
+        // Anyone else, even the owner, is rejected
+        for stranger in [Pubkey::new_unique(), owner] {
+            let err = complete(stranger, true).err().unwrap();
+            assert_eq!(err, LockboxError::Unauthorized.into());
+        }
+
+        // Naming the requester without their signature is not enough
+        let err = complete(request.requester, false).err().unwrap();
+        assert_eq!(err, ErrorCode::AccountNotSigner.into());
+    }
+
+    #[test]
+    fn test_sold_vault_not_recoverable_by_seller() {
+        let (seller, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (master_address, mut master) = master_lockbox(seller);
+
+        let (config_address, config) = config_v1(seller);
+        let (request_address, mut request) = request_v1(seller, 1);
+        request.status = RecoveryStatus::ReadyForReconstruction;
+        let complete = |master: &MasterLockbox| {
+            validate::<CompleteRecovery, CompleteRecoveryBumps>(vec![
+                program_account(config_address, &config),
+                program_account(request_address, &request),
+                program_account(master_address, master),
+                wallet(request.requester, true),
+            ])
+        };
+
+        let (config_v2_address, config_v2) = config_v2(seller);
+        let (request_v2_address, request_v2) = request_v2(seller, 1);
+        let complete_v2 = |master: &MasterLockbox| {
+            validate::<CompleteRecoveryV2, CompleteRecoveryV2Bumps>(vec![
+                program_account(config_v2_address, &config_v2),
+                program_account(request_v2_address, &request_v2),
+                program_account(master_address, master),
+                wallet(request_v2.requester, true),
+            ])
+        };
+
+        complete(&master).unwrap();
+        complete_v2(&master).unwrap();
+
+        // Once sold, the vault keeps its address but not the seller's guardians
+        master.owner = buyer;
+        let err = complete(&master).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+        let err = complete_v2(&master).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+    }
+
+    #[test]
+    fn test_approve_recovery_binds_request_to_config() {
+        let (owner, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_address, config) = config_v1(owner);
+        let approve = |request_address: Pubkey, request: &RecoveryRequest| {
+            validate::<ApproveRecovery, ApproveRecoveryBumps>(vec![
+                program_account(config_address, &config),
+                program_account(request_address, request),
+                wallet(Pubkey::new_unique(), true),
+            ])
+        };
+
+        let (address, request) = request_v1(owner, 1);
+        approve(address, &request).unwrap();
+
+        // A guardian of this config approving another owner's request
+        let (foreign_address, foreign) = request_v1(other, 1);
+        let err = approve(foreign_address, &foreign).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+
+        let mut forged = request.clone();
+        forged.owner = other;
+        let err = approve(address, &forged).err().unwrap();
+        assert_eq!(err, LockboxError::RecoveryRequestMismatch.into());
+    }
+
+    fn clock(unix_timestamp: i64, slot: u64, epoch: u64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp: unix_timestamp,
+            epoch,
+            leader_schedule_epoch: epoch + 1,
+            unix_timestamp,
+        }
+    }
+
+    #[test]
+    fn test_recovery_cooldown_modes() {
+        let (_, mut config) = config_v2(Pubkey::new_unique());
+        config.last_recovery_attempt = 0;
+        assert!(config.is_recovery_cooldown_over(&clock(NOW, 1_000, 10)));
+
+        config.record_recovery_attempt(&clock(NOW, 1_000, 10));
+        assert_eq!(config.last_recovery_attempt, NOW);
+        assert_eq!(config.last_recovery_attempt_slot, 1_000);
+        assert_eq!(config.last_recovery_attempt_epoch, 10);
+
+        // Default: one hour of cluster time, whatever the slot says
+        let cooldown = DEFAULT_RECOVERY_COOLDOWN as i64;
+        assert!(!config.is_recovery_cooldown_over(&clock(NOW + cooldown - 1, 1_000_000, 10)));
+        assert!(config.is_recovery_cooldown_over(&clock(NOW + cooldown, 1_001, 10)));
+
+        // Slots ignore a clock pushed forward
+        config.cooldown_mode = CooldownMode::Slot;
+        config.recovery_cooldown = 9_000;
+        assert!(!config.is_recovery_cooldown_over(&clock(NOW + 10 * cooldown, 9_999, 10)));
+        assert!(config.is_recovery_cooldown_over(&clock(NOW, 10_000, 10)));
+
+        config.cooldown_mode = CooldownMode::Epoch;
+        config.recovery_cooldown = 1;
+        assert!(!config.is_recovery_cooldown_over(&clock(NOW + 10 * cooldown, 1_000_000, 10)));
+        assert!(config.is_recovery_cooldown_over(&clock(NOW, 1_000, 11)));
+
+        for mode in [CooldownMode::Timestamp, CooldownMode::Slot, CooldownMode::Epoch] {
+            assert!(!mode.is_valid_cooldown(0));
+            assert!(mode.is_valid_cooldown(1));
+            assert!(mode.is_valid_cooldown(mode.max_cooldown()));
+            assert!(!mode.is_valid_cooldown(mode.max_cooldown() + 1));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+/*!
+ * TREASURY REFUND TESTS
+ *
+ * Tests for:
+ * - The treasury stays rent exempt after a payout
+ * - Refunds are netted out of the current billing period
+ */
+
+#[cfg(test)]
+mod refund_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::check_treasury_payout;
+    use lockbox::state::{MasterLockbox, OwnerKind, SubscriptionStatus, SubscriptionTier, DEFAULT_DUNNING_WINDOW, MAX_RECENT_ENTRIES};
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_treasury_payout_keeps_rent_exemption() {
+        let rent = Rent::default();
+        let reserve = rent.minimum_balance(0);
+
+        check_treasury_payout(reserve + 500, 500, &rent).unwrap();
+        for (balance, amount) in [(reserve + 500, 501), (reserve, 1), (0, 1)] {
+            let err = check_treasury_payout(balance, amount, &rent).unwrap_err();
+            assert_eq!(err, LockboxError::InsufficientTreasuryFunds.into());
+        }
+    }
+
+    #[test]
+    fn test_refund_netted_from_billing_period() {
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(Pubkey::new_unique(), 255, NOW).unwrap();
+        lockbox.upgrade_subscription(SubscriptionTier::Basic, NOW).unwrap();
+        lockbox.record_payment(SubscriptionTier::Basic.monthly_cost());
+
+        lockbox.record_refund(400_000);
+        assert_eq!(lockbox.billing_period_paid, 600_000);
+
+        // The snapshot reports what was kept; a refund of an earlier
+        // period's payment floors at zero
+        lockbox.record_refund(1_000_000);
+        assert_eq!(lockbox.billing_period_paid, 0);
+        let receipt = lockbox.close_billing_period(NOW + 31 * 24 * 60 * 60);
+        assert_eq!(receipt.amount_paid, 0);
+    }
+}
@@ -0,0 +1,73 @@
+/*!
+ * SEARCH FILTER TESTS
+ *
+ * Tests for:
+ * - Vault-wide and chunk-scoped Bloom filter lookups
+ * - Resetting the filter and its fill ratio
+ */
+
+#[cfg(test)]
+mod search_filter_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::state::{SearchFilter, SEARCH_FILTER_BYTES};
+
+    fn empty_filter() -> SearchFilter {
+        SearchFilter {
+            version: SearchFilter::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            bits: [0u8; SEARCH_FILTER_BYTES],
+            insertions: 0,
+            bump: 255,
+        }
+    }
+
+    /// Stand-in for an HMAC token: spread bytes derived from `seed`
+    fn token(seed: u32) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hash(&seed.to_le_bytes()).to_bytes()
+    }
+
+    #[test]
+    fn test_search_filter_membership() {
+        let mut filter = empty_filter();
+        assert!(!filter.contains(&token(1), None));
+
+        filter.insert(&token(1), 3);
+        assert!(filter.contains(&token(1), None));
+        assert!(filter.contains(&token(1), Some(3)));
+        assert!(!filter.contains(&token(1), Some(4)));
+        assert!(!filter.contains(&token(2), None));
+        assert_eq!(filter.insertions, 1);
+    }
+
+    #[test]
+    fn test_search_filter_has_no_false_negatives() {
+        let mut filter = empty_filter();
+        for seed in 0..500 {
+            filter.insert(&token(seed), (seed % 8) as u16);
+        }
+        for seed in 0..500 {
+            assert!(filter.contains(&token(seed), None));
+            assert!(filter.contains(&token(seed), Some((seed % 8) as u16)));
+        }
+
+        // 1000 keys over 8192 bits with 4 hashes: well under 5% false positives
+        let false_positives = (10_000..11_000)
+            .filter(|seed| filter.contains(&token(*seed), None))
+            .count();
+        assert!(false_positives < 50, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn test_search_filter_reset() {
+        let mut filter = empty_filter();
+        for seed in 0..100 {
+            filter.insert(&token(seed), 0);
+        }
+        assert!(filter.fill_ratio_bps() > 0);
+
+        filter.clear();
+        assert_eq!(filter.fill_ratio_bps(), 0);
+        assert_eq!(filter.insertions, 0);
+        assert!(!filter.contains(&token(0), None));
+    }
+}
@@ -0,0 +1,415 @@
+/*!
+ * SHARED VAULT TESTS
+ *
+ * Tests for:
+ * - Member limits, uniqueness and roles
+ * - Shared usage counts against the creating vault's capacity, apart from
+ *   its own usage
+ * - Entry writes require a writer and the creating vault
+ * - Entry bytes are attributed to the member who last wrote them
+ * - Checked-out entries can only be edited by the holder until it lapses
+ * - Token gates accept holders of the mint or of a verified collection NFT
+ * - Removing a member flags every entry for rotation until it is rewritten
+ */
+
+#[cfg(test)]
+mod shared_vault_tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{
+        apply_shared_usage, attribute_entry_bytes, check_token_gate, flag_entries_for_rotation, DeleteSharedEntry,
+        DeleteSharedEntryBumps,
+    };
+    use lockbox::state::{
+        MasterLockbox, OwnerKind, SharedEntry, SharedVault, SharedVaultGate, SharedVaultRole, SubscriptionStatus,
+        SubscriptionTier, DEFAULT_DUNNING_WINDOW, MAX_ENTRY_CHECKOUT_DURATION, MAX_RECENT_ENTRIES,
+        MAX_SHARED_VAULT_MEMBERS, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &lockbox::ID)
+    }
+
+    fn new_lockbox(owner: Pubkey) -> (Pubkey, MasterLockbox) {
+        let (address, bump) = pda(&[MasterLockbox::SEEDS_PREFIX, owner.as_ref()]);
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(owner, bump, NOW).unwrap();
+        (address, lockbox)
+    }
+
+    fn shared_vault(master_lockbox: Pubkey, creator: Pubkey) -> (Pubkey, SharedVault) {
+        let (address, bump) = pda(&[SharedVault::SEEDS_PREFIX, master_lockbox.as_ref(), &7u32.to_le_bytes()]);
+        let mut vault = SharedVault {
+            version: SharedVault::CURRENT_VERSION,
+            master_lockbox,
+            creator,
+            vault_id: 7,
+            gate: None,
+            members: Vec::new(),
+            storage_used: 0,
+            entry_count: 0,
+            rotation_pending: 0,
+            next_entry_id: 0,
+            created_at: NOW,
+            bump,
+        };
+        vault.add_member(creator, SharedVaultRole::Admin, vec![1u8; 88], NOW).unwrap();
+        (address, vault)
+    }
+
+    /// Entry 0 of `shared_vault`, last written by `author`
+    fn shared_entry(shared_vault: Pubkey, author: Pubkey) -> (Pubkey, SharedEntry) {
+        let (address, bump) = pda(&[SharedEntry::SEEDS_PREFIX, shared_vault.as_ref(), &0u64.to_le_bytes()]);
+        let entry = SharedEntry {
+            version: SharedEntry::CURRENT_VERSION,
+            shared_vault,
+            entry_id: 0,
+            last_modified_by: author,
+            checkout: None,
+            needs_rotation: false,
+            encrypted_data: vec![0u8; 40],
+            created_at: NOW,
+            last_modified: NOW,
+            bump,
+        };
+        (address, entry)
+    }
+
+    #[test]
+    fn test_members_limited_unique_and_roled() {
+        let creator = Pubkey::new_unique();
+        let (_, mut vault) = shared_vault(Pubkey::new_unique(), creator);
+        let (writer, reader) = (Pubkey::new_unique(), Pubkey::new_unique());
+        vault.add_member(writer, SharedVaultRole::Writer, vec![1u8; 88], NOW).unwrap();
+        vault.add_member(reader, SharedVaultRole::Reader, vec![1u8; 88], NOW).unwrap();
+
+        assert!(vault.is_admin(&creator) && vault.can_write(&creator));
+        assert!(!vault.is_admin(&writer) && vault.can_write(&writer));
+        assert!(!vault.can_write(&reader));
+        assert!(!vault.can_write(&Pubkey::new_unique()));
+
+        let err = vault.add_member(reader, SharedVaultRole::Writer, vec![1u8; 88], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::SharedVaultMemberExists.into());
+        let err = vault.add_member(Pubkey::new_unique(), SharedVaultRole::Reader, vec![1u8; 129], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidDataSize.into());
+
+        // The creator stays; anyone else can be removed once
+        assert_eq!(vault.remove_member(&creator).unwrap_err(), LockboxError::Unauthorized.into());
+        assert_eq!(vault.remove_member(&reader).unwrap().role, SharedVaultRole::Reader);
+        assert_eq!(vault.remove_member(&reader).unwrap_err(), LockboxError::SharedVaultMemberNotFound.into());
+
+        while vault.members.len() < MAX_SHARED_VAULT_MEMBERS {
+            vault.add_member(Pubkey::new_unique(), SharedVaultRole::Reader, vec![1u8; 88], NOW).unwrap();
+        }
+        let err = vault.add_member(Pubkey::new_unique(), SharedVaultRole::Reader, vec![1u8; 88], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::SharedVaultFull.into());
+    }
+
+    #[test]
+    fn test_shared_usage_counts_against_creator_capacity() {
+        let (address, mut lockbox) = new_lockbox(Pubkey::new_unique());
+        let (_, mut vault) = shared_vault(address, lockbox.owner);
+        let limit = lockbox.capacity_limit(NOW);
+        lockbox.storage_used = limit - 1_000;
+        assert!(lockbox.has_capacity(1_000, NOW));
+
+        apply_shared_usage(&mut lockbox, &mut vault, 600);
+        assert_eq!((vault.storage_used, lockbox.shared_storage_used), (600, 600));
+        // Shared bytes are reported apart from the vault's own
+        assert_eq!(lockbox.storage_used, limit - 1_000);
+        assert_eq!(lockbox.total_storage_used(), limit - 400);
+        assert!(lockbox.has_capacity(400, NOW));
+        assert!(!lockbox.has_capacity(401, NOW));
+
+        apply_shared_usage(&mut lockbox, &mut vault, -600);
+        assert_eq!((vault.storage_used, lockbox.shared_storage_used), (0, 0));
+        assert!(lockbox.has_capacity(1_000, NOW));
+    }
+
+    #[test]
+    fn test_entry_bytes_follow_last_author() {
+        let creator = Pubkey::new_unique();
+        let (_, mut vault) = shared_vault(Pubkey::new_unique(), creator);
+        let writer = Pubkey::new_unique();
+        vault.add_member(writer, SharedVaultRole::Writer, vec![1u8; 88], NOW).unwrap();
+        let bytes = |vault: &SharedVault, member| vault.get_member(&member).unwrap().bytes_written;
+
+        // Store, then a same-author update moves only the difference
+        assert_eq!(attribute_entry_bytes(&mut vault, None, Some((writer, 100))), vec![(writer, 100)]);
+        assert_eq!(attribute_entry_bytes(&mut vault, Some((writer, 100)), Some((writer, 60))), vec![(writer, 60)]);
+        assert!(attribute_entry_bytes(&mut vault, Some((writer, 60)), Some((writer, 60))).is_empty());
+
+        // Another member's update takes the entry over
+        let changes = attribute_entry_bytes(&mut vault, Some((writer, 60)), Some((creator, 80)));
+        assert_eq!(changes, vec![(writer, 0), (creator, 80)]);
+
+        // Deleting releases the last author's bytes
+        attribute_entry_bytes(&mut vault, None, Some((writer, 40)));
+        assert_eq!(attribute_entry_bytes(&mut vault, Some((creator, 80)), None), vec![(creator, 0)]);
+        assert_eq!((bytes(&vault, writer), bytes(&vault, creator)), (40, 0));
+
+        // A departed author's bytes are no longer tracked
+        vault.remove_member(&writer).unwrap();
+        assert!(attribute_entry_bytes(&mut vault, Some((writer, 40)), None).is_empty());
+    }
+
+    #[test]
+    fn test_checkout_locks_out_other_members() {
+        let (holder, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (_, mut entry) = shared_entry(Pubkey::new_unique(), holder);
+        entry.check_editor(&other, NOW).unwrap();
+
+        for duration in [0, MAX_ENTRY_CHECKOUT_DURATION + 1] {
+            let err = entry.check_out(holder, duration, NOW).unwrap_err();
+            assert_eq!(err, LockboxError::InvalidEntryCheckoutDuration.into());
+        }
+
+        assert!(!entry.check_out(holder, 600, NOW).unwrap());
+        entry.check_editor(&holder, NOW + 599).unwrap();
+        let err = entry.check_editor(&other, NOW + 599).unwrap_err();
+        assert_eq!(err, LockboxError::EntryCheckedOut.into());
+        let err = entry.check_out(other, 600, NOW + 1).unwrap_err();
+        assert_eq!(err, LockboxError::EntryCheckedOut.into());
+
+        // The holder can renew; once it lapses, anyone can edit or take over
+        assert!(!entry.check_out(holder, 600, NOW + 300).unwrap());
+        assert_eq!(entry.checkout_at(NOW + 899).unwrap().member, holder);
+        assert!(entry.checkout_at(NOW + 900).is_none());
+        entry.check_editor(&other, NOW + 900).unwrap();
+        assert!(entry.check_out(other, 600, NOW + 900).unwrap());
+    }
+
+    /// Account leaked for the `'static` context
+    fn leaked_account(key: Pubkey, is_signer: bool, owner: &'static Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        leaked_account(key, false, &lockbox::ID, data)
+    }
+
+    /// SPL token account holding `amount` of `mint` for `owner`
+    fn token_account(program: &'static Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> AccountInfo<'static> {
+        let mut data = vec![0u8; 165];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+        leaked_account(Pubkey::new_unique(), false, program, data)
+    }
+
+    /// Metaplex metadata of `mint`, in `collection` (verified or not)
+    fn token_metadata(mint: Pubkey, collection: Pubkey, verified: bool) -> AccountInfo<'static> {
+        let (address, _) = Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+        let mut data = vec![4u8];
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(mint.as_ref());
+        for field in ["Member #1", "MBR", "https://example.com/1.json"] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        data.extend_from_slice(&500u16.to_le_bytes());
+        // One creator, then primary sale, mutability, edition nonce and standard
+        data.extend_from_slice(&[1, 1, 0, 0, 0]);
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&[1, 100, 0, 1, 1, 254, 1, 0]);
+        data.push(1);
+        data.push(verified as u8);
+        data.extend_from_slice(collection.as_ref());
+        // Metadata accounts are allocated at a fixed size, zero-padded
+        data.resize(679, 0);
+        leaked_account(address, false, &TOKEN_METADATA_PROGRAM_ID, data)
+    }
+
+    #[test]
+    fn test_token_gate_checks_holding() {
+        let (holder, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let gate = SharedVaultGate::Mint(mint);
+        let denied = || Err(LockboxError::TokenGateNotSatisfied.into());
+
+        check_token_gate(&gate, &holder, &token_account(&SPL_TOKEN_PROGRAM_ID, mint, holder, 1), None).unwrap();
+        check_token_gate(&gate, &holder, &token_account(&SPL_TOKEN_2022_PROGRAM_ID, mint, holder, 5), None).unwrap();
+
+        // Empty, someone else's, another mint, or not a token account
+        let cases = [
+            token_account(&SPL_TOKEN_PROGRAM_ID, mint, holder, 0),
+            token_account(&SPL_TOKEN_PROGRAM_ID, mint, Pubkey::new_unique(), 1),
+            token_account(&SPL_TOKEN_PROGRAM_ID, Pubkey::new_unique(), holder, 1),
+            token_account(&lockbox::ID, mint, holder, 1),
+        ];
+        for account in cases {
+            assert_eq!(check_token_gate(&gate, &holder, &account, None), denied());
+        }
+    }
+
+    #[test]
+    fn test_collection_gate_needs_verified_metadata() {
+        let (holder, nft, collection) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let gate = SharedVaultGate::Collection(collection);
+        let nft_account = token_account(&SPL_TOKEN_PROGRAM_ID, nft, holder, 1);
+        let denied = || Err(LockboxError::TokenGateNotSatisfied.into());
+
+        let metadata = token_metadata(nft, collection, true);
+        check_token_gate(&gate, &holder, &nft_account, Some(&metadata)).unwrap();
+
+        assert_eq!(check_token_gate(&gate, &holder, &nft_account, None), denied());
+        let unverified = token_metadata(nft, collection, false);
+        assert_eq!(check_token_gate(&gate, &holder, &nft_account, Some(&unverified)), denied());
+        let other_collection = token_metadata(nft, Pubkey::new_unique(), true);
+        assert_eq!(check_token_gate(&gate, &holder, &nft_account, Some(&other_collection)), denied());
+        // Metadata of a different NFT in the collection
+        let other_nft = token_metadata(Pubkey::new_unique(), collection, true);
+        assert_eq!(check_token_gate(&gate, &holder, &nft_account, Some(&other_nft)), denied());
+    }
+
+    #[test]
+    fn test_token_gated_members_join_as_keyless_readers() {
+        let creator = Pubkey::new_unique();
+        let (_, mut vault) = shared_vault(Pubkey::new_unique(), creator);
+        let holder = Pubkey::new_unique();
+
+        vault.add_token_gated_member(holder, NOW).unwrap();
+        let member = vault.get_member(&holder).unwrap();
+        assert!(member.token_gated && member.wrapped_vault_key.is_empty());
+        assert_eq!(member.role, SharedVaultRole::Reader);
+        assert!(!vault.can_write(&holder));
+        let err = vault.add_token_gated_member(holder, NOW).unwrap_err();
+        assert_eq!(err, LockboxError::SharedVaultMemberExists.into());
+
+        // An admin delivers the key afterwards
+        vault.set_member_vault_key(&holder, vec![2u8; 88]).unwrap();
+        assert_eq!(vault.get_member(&holder).unwrap().wrapped_vault_key, vec![2u8; 88]);
+        let err = vault.set_member_vault_key(&Pubkey::new_unique(), vec![2u8; 88]).unwrap_err();
+        assert_eq!(err, LockboxError::SharedVaultMemberNotFound.into());
+    }
+
+    fn delete(
+        master: (Pubkey, &MasterLockbox),
+        vault: (Pubkey, &SharedVault),
+        signer: Pubkey,
+    ) -> Result<DeleteSharedEntry<'static>> {
+        let (entry_address, entry) = shared_entry(vault.0, signer);
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(
+            vec![
+                program_account(master.0, master.1),
+                program_account(vault.0, vault.1),
+                program_account(entry_address, &entry),
+                leaked_account(signer, true, &anchor_lang::system_program::ID, Vec::new()),
+            ]
+            .into_boxed_slice(),
+        );
+        DeleteSharedEntry::try_accounts(
+            &lockbox::ID,
+            &mut accounts,
+            &0u64.to_le_bytes(),
+            &mut DeleteSharedEntryBumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_entry_writes_need_writer_and_creating_vault() {
+        let owner = Pubkey::new_unique();
+        let (address, lockbox) = new_lockbox(owner);
+        let (vault_address, mut vault) = shared_vault(address, owner);
+        let (writer, reader) = (Pubkey::new_unique(), Pubkey::new_unique());
+        vault.add_member(writer, SharedVaultRole::Writer, vec![1u8; 88], NOW).unwrap();
+        vault.add_member(reader, SharedVaultRole::Reader, vec![1u8; 88], NOW).unwrap();
+        let master = (address, &lockbox);
+        let shared = (vault_address, &vault);
+
+        delete(master, shared, writer).unwrap();
+        delete(master, shared, owner).unwrap();
+        let err = delete(master, shared, reader).err().unwrap();
+        assert_eq!(err, LockboxError::SharedVaultAccessDenied.into());
+
+        // Usage cannot be booked against another vault
+        let (other_address, other) = new_lockbox(Pubkey::new_unique());
+        let err = delete((other_address, &other), shared, writer).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintAddress.into());
+    }
+
+    #[test]
+    fn test_member_removal_flags_entries_for_rotation() {
+        let creator = Pubkey::new_unique();
+        let (vault_address, mut vault) = shared_vault(Pubkey::new_unique(), creator);
+        let leaver = Pubkey::new_unique();
+        vault.add_member(leaver, SharedVaultRole::Writer, vec![1u8; 88], NOW).unwrap();
+        let mut entries = [shared_entry(vault_address, creator).1, shared_entry(vault_address, leaver).1];
+        entries[1].entry_id = 1;
+        vault.entry_count = 2;
+
+        vault.remove_member(&leaver).unwrap();
+        assert_eq!(flag_entries_for_rotation(&mut vault, &mut entries), vec![0, 1]);
+        assert!(entries.iter().all(|entry| entry.needs_rotation));
+        assert_eq!(vault.rotation_pending, 2);
+
+        // A second removal before rotation does not count entries twice
+        let other = Pubkey::new_unique();
+        vault.add_member(other, SharedVaultRole::Reader, vec![1u8; 88], NOW).unwrap();
+        vault.remove_member(&other).unwrap();
+        entries[0].needs_rotation = false;
+        vault.rotation_pending = 1;
+        assert_eq!(flag_entries_for_rotation(&mut vault, &mut entries), vec![0]);
+        assert_eq!(vault.rotation_pending, 2);
+    }
+}
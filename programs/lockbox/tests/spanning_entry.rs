@@ -0,0 +1,139 @@
+/*!
+ * CROSS-CHUNK ENTRY TESTS
+ *
+ * Tests for:
+ * - Splitting an entry over the free space of several chunks
+ * - Reassembling segments in link order, whatever order chunks are passed
+ * - Missing chunks and broken segment links
+ * - Spanning entries count once toward transfer re-key progress
+ */
+
+#[cfg(test)]
+mod spanning_entry_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{plan_segments, reassemble_entry};
+    use lockbox::state::{DataEntryHeader, PasswordEntryType, StorageChunk, StorageType};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_chunk(chunk_index: u16) -> StorageChunk {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                Pubkey::default(),
+                Pubkey::default(),
+                chunk_index,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                255,
+                NOW,
+            )
+            .unwrap();
+        chunk
+    }
+
+    fn segment(chunk: &mut StorageChunk, data: &[u8], flags: u8, next_chunk: u16, next_offset: u32) {
+        let header = DataEntryHeader {
+            entry_id: 7,
+            offset: chunk.current_size,
+            size: data.len() as u32,
+            entry_type: PasswordEntryType::SecureNote,
+            category: 0,
+            title_hash: [0u8; 32],
+            created_at: NOW,
+            last_modified: NOW,
+            access_count: 0,
+            flags,
+            next_chunk,
+            next_offset,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        };
+        chunk.add_entry(header, data.to_vec(), NOW).unwrap();
+    }
+
+    /// Entry 7 split 600 / 500 / 100 bytes over chunks 0, 4 and 2
+    fn spanning_chunks() -> Vec<StorageChunk> {
+        let data: Vec<u8> = (0..1200u32).map(|i| i as u8).collect();
+        let (mut head, mut middle, mut tail) = (new_chunk(0), new_chunk(4), new_chunk(2));
+        segment(&mut head, &data[..600], 0, 4, 600);
+        segment(&mut middle, &data[600..1100], DataEntryHeader::FLAG_CONTINUATION, 2, 1100);
+        segment(
+            &mut tail,
+            &data[1100..],
+            DataEntryHeader::FLAG_CONTINUATION,
+            DataEntryHeader::NO_NEXT_CHUNK,
+            0,
+        );
+        vec![head, middle, tail]
+    }
+
+    #[test]
+    fn test_plan_fills_chunks_in_order() {
+        assert_eq!(plan_segments(&[600, 0, 1024], 1200).unwrap(), vec![600, 0, 600]);
+        // Trailing chunks are left alone once the entry is placed
+        assert_eq!(plan_segments(&[1024, 1024], 100).unwrap(), vec![100]);
+
+        let err = plan_segments(&[600, 500], 1200).unwrap_err();
+        assert_eq!(err, LockboxError::InsufficientChunkCapacity.into());
+    }
+
+    #[test]
+    fn test_reassembles_in_link_order() {
+        let expected: Vec<u8> = (0..1200u32).map(|i| i as u8).collect();
+        let mut chunks = spanning_chunks();
+        assert_eq!(reassemble_entry(&chunks, 7).unwrap(), expected);
+
+        chunks.reverse();
+        assert_eq!(reassemble_entry(&chunks, 7).unwrap(), expected);
+
+        let err = reassemble_entry(&chunks, 8).unwrap_err();
+        assert_eq!(err, LockboxError::EntryNotFound.into());
+    }
+
+    #[test]
+    fn test_missing_or_broken_segments_rejected() {
+        let mut chunks = spanning_chunks();
+        chunks.remove(1);
+        let err = reassemble_entry(&chunks, 7).unwrap_err();
+        assert_eq!(err, LockboxError::EntrySegmentMissing.into());
+
+        // A link that disagrees with the bytes before it
+        let mut chunks = spanning_chunks();
+        chunks[0].get_entry_header_mut(7).unwrap().next_offset = 500;
+        let err = reassemble_entry(&chunks, 7).unwrap_err();
+        assert_eq!(err, LockboxError::EntrySegmentMissing.into());
+
+        // A chain looping back on itself
+        let mut chunks = spanning_chunks();
+        chunks[2].get_entry_header_mut(7).unwrap().next_chunk = 4;
+        assert!(reassemble_entry(&chunks, 7).is_err());
+    }
+
+    #[test]
+    fn test_spanning_entry_counts_once_for_rekey() {
+        let chunks = spanning_chunks();
+        assert!(chunks.iter().all(|chunk| chunk.entry_headers[0].spans_chunks()));
+
+        let written: u32 = chunks.iter().map(|chunk| chunk.entries_written_before(NOW + 1)).sum();
+        assert_eq!(written, 1);
+    }
+}
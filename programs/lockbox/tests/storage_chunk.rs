@@ -0,0 +1,398 @@
+/*!
+ * STORAGE CHUNK TESTS
+ *
+ * Tests for:
+ * - Entry ID uniqueness within a chunk
+ * - Client-assigned entry ID range, unique across the vault's chunks
+ * - Alias decoding and alias-aware deletion
+ * - Moving entries between chunks (cold archive migration)
+ * - Expired trash selection and restoring trashed entries
+ * - Client-settable entry flags
+ * - Entry link lists
+ * - Search index postings
+ * - Title hash lookup across chunks
+ * - Rotation-due selection
+ * - Chunk health statistics
+ */
+
+#[cfg(test)]
+mod storage_chunk_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::client_entry_ids_in;
+    use lockbox::instructions::title_lookup::{entries_with_title, EntryLocation};
+    use lockbox::state::{
+        AliasTarget, ChunkHealth, ClientEntryIds, DataEntryHeader, MasterLockbox, PasswordEntryType, StorageChunk,
+        StorageType, MAX_ENTRY_LINKS, MAX_SEARCH_TOKENS_PER_ENTRY, SEARCH_POSTING_SIZE,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn new_chunk() -> StorageChunk {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                0,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                255,
+                NOW,
+            )
+            .unwrap();
+        chunk
+    }
+
+    fn header(entry_id: u64, offset: u32, size: u32) -> DataEntryHeader {
+        DataEntryHeader {
+            entry_id,
+            offset,
+            size,
+            entry_type: PasswordEntryType::Login,
+            category: 0,
+            title_hash: [0u8; 32],
+            created_at: NOW,
+            last_modified: NOW,
+            access_count: 0,
+            flags: 0,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_entry_id_rejected() {
+        let mut chunk = new_chunk();
+        chunk.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        assert!(chunk.has_entry(1));
+
+        let err = chunk.add_entry(header(1, 40, 40), vec![2u8; 40], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::DuplicateEntryId.into());
+        assert_eq!(chunk.entry_count, 1);
+        assert_eq!(chunk.current_size, 40);
+    }
+
+    #[test]
+    fn test_client_entry_id_range() {
+        assert!(!MasterLockbox::is_client_entry_id(1));
+        assert!(!MasterLockbox::is_client_entry_id(u64::MAX >> 1));
+        assert!(MasterLockbox::is_client_entry_id(MasterLockbox::CLIENT_ENTRY_ID_BIT | 7));
+
+        let mut chunk = new_chunk();
+        let client_id = MasterLockbox::CLIENT_ENTRY_ID_BIT | 0xDEAD_BEEF;
+        chunk.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        chunk.add_entry(header(client_id, 40, 40), vec![2u8; 40], NOW).unwrap();
+        assert_eq!(chunk.get_entry_data(client_id).unwrap(), vec![2u8; 40]);
+    }
+
+    #[test]
+    fn test_client_entry_id_unique_across_chunks() {
+        let client_id = MasterLockbox::CLIENT_ENTRY_ID_BIT | 0xDEAD_BEEF;
+        let mut ids = ClientEntryIds {
+            version: ClientEntryIds::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            ids: Vec::new(),
+            bump: 255,
+        };
+        let (mut first, mut second) = (new_chunk(), new_chunk());
+        second.chunk_index = 1;
+
+        // Stored in chunk 0
+        ids.reserve(client_id).unwrap();
+        first.add_entry(header(client_id, 0, 40), vec![1u8; 40], NOW).unwrap();
+
+        // Chunk 1 alone would accept the ID; the vault-wide reservation does not
+        assert!(!second.has_entry(client_id));
+        let err = ids.reserve(client_id).unwrap_err();
+        assert_eq!(err, LockboxError::DuplicateEntryId.into());
+        ids.reserve(client_id + 1).unwrap();
+        assert_eq!(ids.ids, vec![client_id, client_id + 1]);
+
+        // Seeding the account finds IDs already stored, and refuses a vault
+        // that already holds one twice
+        second.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        assert_eq!(client_entry_ids_in(&[first.clone(), second.clone()]).unwrap(), vec![client_id]);
+        second.add_entry(header(client_id, 40, 40), vec![2u8; 40], NOW).unwrap();
+        let err = client_entry_ids_in(&[first, second]).unwrap_err();
+        assert_eq!(err, LockboxError::DuplicateEntryId.into());
+    }
+
+    fn add_alias(chunk: &mut StorageChunk, entry_id: u64, target: AliasTarget) {
+        let mut h = header(entry_id, chunk.current_size, AliasTarget::SIZE as u32);
+        h.flags = DataEntryHeader::FLAG_ALIAS;
+        chunk.add_entry(h, target.try_to_vec().unwrap(), NOW).unwrap();
+    }
+
+    #[test]
+    fn test_alias_aware_delete() {
+        let mut chunk = new_chunk();
+        let target = AliasTarget { chunk_index: 0, entry_id: 1 };
+        let other = AliasTarget { chunk_index: 3, entry_id: 9 };
+
+        chunk.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        add_alias(&mut chunk, 2, target);
+        add_alias(&mut chunk, 3, other);
+        chunk.add_entry(header(4, chunk.current_size, 40), vec![4u8; 40], NOW).unwrap();
+
+        assert!(chunk.get_entry_header(2).unwrap().is_alias());
+        assert_eq!(chunk.get_alias_target(2).unwrap(), target);
+        let err = chunk.get_alias_target(1).unwrap_err();
+        assert_eq!(err, LockboxError::EntryNotAlias.into());
+
+        // Deleting the target's aliases leaves unrelated aliases and entries intact
+        chunk.delete_entry(1, NOW).unwrap();
+        assert_eq!(chunk.delete_aliases_of(target, NOW).unwrap(), 1);
+        assert!(!chunk.has_entry(2));
+        assert_eq!(chunk.get_alias_target(3).unwrap(), other);
+        assert_eq!(chunk.get_entry_data(4).unwrap(), vec![4u8; 40]);
+        assert_eq!(chunk.current_size, 40 + AliasTarget::SIZE as u32);
+    }
+
+    #[test]
+    fn test_move_entry_retargets_aliases() {
+        let mut hot = new_chunk();
+        let mut cold = new_chunk();
+        cold.chunk_index = 1;
+        cold.data_type = StorageType::ArchivedPasswords;
+
+        let mut archived = header(1, 0, 40);
+        archived.set_archived(true);
+        hot.add_entry(archived, vec![1u8; 40], NOW).unwrap();
+        add_alias(&mut hot, 2, AliasTarget { chunk_index: 0, entry_id: 1 });
+
+        let (mut moved, data) = hot.take_entry(1, NOW).unwrap();
+        assert!(moved.is_archived());
+        moved.offset = cold.current_size;
+        cold.add_entry(moved, data, NOW).unwrap();
+
+        let from = AliasTarget { chunk_index: 0, entry_id: 1 };
+        let to = AliasTarget { chunk_index: 1, entry_id: 1 };
+        assert_eq!(hot.retarget_aliases(from, to, NOW).unwrap(), 1);
+
+        assert!(!hot.has_entry(1));
+        assert_eq!(hot.get_alias_target(2).unwrap(), to);
+        assert_eq!(cold.get_entry_data(1).unwrap(), vec![1u8; 40]);
+    }
+
+    #[test]
+    fn test_expired_trash_respects_retention() {
+        let retention = 30 * 86_400;
+        let mut chunk = new_chunk();
+        chunk.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        chunk.add_entry(header(2, 40, 40), vec![2u8; 40], NOW).unwrap();
+        chunk.add_entry(header(3, 80, 40), vec![3u8; 40], NOW).unwrap();
+
+        for (id, trashed_at) in [(1, NOW), (2, NOW + 86_400)] {
+            let h = chunk.get_entry_header_mut(id).unwrap();
+            h.set_trashed(true);
+            h.last_modified = trashed_at;
+        }
+
+        assert!(chunk.expired_trash(NOW + retention - 1, retention).is_empty());
+        assert_eq!(chunk.expired_trash(NOW + retention, retention), vec![(1, NOW)]);
+        assert_eq!(chunk.expired_trash(NOW + 2 * retention, retention).len(), 2);
+    }
+
+    #[test]
+    fn test_restore_trashed_within_retention() {
+        let retention = 30 * 86_400;
+        let mut chunk = new_chunk();
+        chunk.add_entry(header(1, 0, 40), vec![1u8; 40], NOW).unwrap();
+        chunk.add_entry(header(2, 40, 40), vec![2u8; 40], NOW).unwrap();
+
+        let err = chunk.restore_trashed(1, NOW, retention).unwrap_err();
+        assert_eq!(err, LockboxError::EntryNotTrashed.into());
+
+        for id in [1, 2] {
+            chunk.get_entry_header_mut(id).unwrap().set_trashed(true);
+        }
+        chunk.restore_trashed(1, NOW + retention - 1, retention).unwrap();
+        let restored = chunk.get_entry_header(1).unwrap();
+        assert!(!restored.is_trashed());
+        assert_eq!(restored.last_modified, NOW);
+
+        let err = chunk.restore_trashed(2, NOW + retention, retention).unwrap_err();
+        assert_eq!(err, LockboxError::TrashRetentionElapsed.into());
+        assert_eq!(chunk.expired_trash(NOW + retention, retention), vec![(2, NOW)]);
+    }
+
+    #[test]
+    fn test_rotation_due_skips_unscheduled_and_trashed() {
+        let mut chunk = new_chunk();
+        for id in 1..=4 {
+            chunk.add_entry(header(id, (id as u32 - 1) * 40, 40), vec![id as u8; 40], NOW).unwrap();
+        }
+        // Entry 1 has no schedule, 4 is trashed
+        chunk.get_entry_header_mut(2).unwrap().rotate_after = NOW + 10;
+        chunk.get_entry_header_mut(3).unwrap().rotate_after = NOW + 20;
+        chunk.get_entry_header_mut(4).unwrap().rotate_after = NOW;
+        chunk.get_entry_header_mut(4).unwrap().set_trashed(true);
+
+        assert!(chunk.rotation_due(NOW + 9).is_empty());
+        assert_eq!(chunk.rotation_due(NOW + 10), vec![(2, NOW + 10)]);
+        assert_eq!(chunk.rotation_due(NOW + 20), vec![(2, NOW + 10), (3, NOW + 20)]);
+    }
+
+    #[test]
+    fn test_user_flags_keep_structural_flags() {
+        let mut h = header(1, 0, 40);
+        h.flags = DataEntryHeader::FLAG_ALIAS;
+
+        h.set_user_flags(DataEntryHeader::FLAG_FAVORITE | DataEntryHeader::FLAG_ARCHIVED).unwrap();
+        assert!(h.is_favorite() && h.is_archived() && h.is_alias());
+
+        h.set_user_flags(DataEntryHeader::FLAG_ARCHIVED).unwrap();
+        assert!(!h.is_favorite() && h.is_archived() && h.is_alias());
+
+        let err = h.set_user_flags(DataEntryHeader::FLAG_TRASHED).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidEntryFlags.into());
+        assert!(!h.is_trashed());
+    }
+
+    #[test]
+    fn test_entry_links_are_unique_and_bounded() {
+        let mut h = header(1, 0, 40);
+        h.add_link(2).unwrap();
+        assert!(h.is_linked_to(2));
+
+        let err = h.add_link(2).unwrap_err();
+        assert_eq!(err, LockboxError::EntryAlreadyLinked.into());
+        let err = h.add_link(1).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidEntryLink.into());
+
+        for entry_id in 3..=MAX_ENTRY_LINKS as u64 + 1 {
+            h.add_link(entry_id).unwrap();
+        }
+        let err = h.add_link(100).unwrap_err();
+        assert_eq!(err, LockboxError::TooManyEntryLinks.into());
+
+        assert!(h.remove_link(2));
+        assert!(!h.remove_link(2));
+        assert!(!h.is_linked_to(2));
+        h.add_link(100).unwrap();
+        assert_eq!(h.linked_entry_ids.len(), MAX_ENTRY_LINKS);
+    }
+
+    #[test]
+    fn test_search_index_postings() {
+        let mut chunk = new_chunk();
+        let err = chunk.set_search_tokens(1, &[[1u8; 32]], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::NotSearchIndexChunk.into());
+
+        chunk.data_type = StorageType::SearchIndex;
+        let err = chunk.add_entry(header(1, 0, 40), vec![0u8; 40], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::SearchIndexChunk.into());
+
+        // Duplicate tokens are indexed once
+        let size = chunk.set_search_tokens(1, &[[1u8; 32], [2u8; 32], [1u8; 32]], NOW).unwrap();
+        assert_eq!(size as usize, 2 * SEARCH_POSTING_SIZE);
+        chunk.set_search_tokens(2, &[[2u8; 32]], NOW).unwrap();
+        assert_eq!(chunk.entries_for_token(&[2u8; 32]), vec![1, 2]);
+        assert_eq!(chunk.entries_for_token(&[1u8; 32]), vec![1]);
+
+        // Re-indexing replaces an entry's tokens; an empty list removes it
+        chunk.set_search_tokens(1, &[[3u8; 32]], NOW).unwrap();
+        assert!(chunk.entries_for_token(&[1u8; 32]).is_empty());
+        assert_eq!(chunk.entries_for_token(&[2u8; 32]), vec![2]);
+        chunk.set_search_tokens(2, &[], NOW).unwrap();
+        assert_eq!(chunk.current_size as usize, SEARCH_POSTING_SIZE);
+
+        let err = chunk.set_search_tokens(3, &[[0u8; 32]], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::InvalidSearchToken.into());
+        let too_many = vec![[4u8; 32]; MAX_SEARCH_TOKENS_PER_ENTRY + 1];
+        let err = chunk.set_search_tokens(3, &too_many, NOW).unwrap_err();
+        assert_eq!(err, LockboxError::TooManySearchTokens.into());
+
+        // Postings are bounded by the chunk's capacity
+        let capacity = StorageChunk::MIN_CHUNK_SIZE as usize / SEARCH_POSTING_SIZE;
+        let tokens: Vec<[u8; 32]> = (0..MAX_SEARCH_TOKENS_PER_ENTRY as u8).map(|i| [i + 10; 32]).collect();
+        chunk.set_search_tokens(4, &tokens[..capacity - 1], NOW).unwrap();
+        let err = chunk.set_search_tokens(5, &tokens[..2], NOW).unwrap_err();
+        assert_eq!(err, LockboxError::SearchIndexFull.into());
+    }
+
+    #[test]
+    fn test_entries_with_title() {
+        let title = [7u8; 32];
+        let titled = |entry_id: u64, offset: u32, flags: u8| {
+            let mut h = header(entry_id, offset, 40);
+            h.title_hash = title;
+            h.flags = flags;
+            h
+        };
+
+        let mut first = new_chunk();
+        first.add_entry(titled(1, 0, 0), vec![1u8; 40], NOW).unwrap();
+        first.add_entry(header(2, 40, 40), vec![2u8; 40], NOW).unwrap();
+        first
+            .add_entry(titled(3, 80, DataEntryHeader::FLAG_TRASHED), vec![3u8; 40], NOW)
+            .unwrap();
+
+        let mut second = new_chunk();
+        second.chunk_index = 1;
+        second
+            .add_entry(titled(1, 0, DataEntryHeader::FLAG_CONTINUATION), vec![1u8; 40], NOW)
+            .unwrap();
+        second.add_entry(titled(4, 40, 0), vec![4u8; 40], NOW).unwrap();
+
+        // Titles are not unique; trashed entries and continuations are skipped
+        let matches = entries_with_title(&[first, second], &title);
+        assert_eq!(
+            matches,
+            vec![
+                EntryLocation { chunk_index: 0, entry_id: 1 },
+                EntryLocation { chunk_index: 1, entry_id: 4 },
+            ]
+        );
+        assert!(entries_with_title(&[new_chunk()], &title).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_health_reports_free_blocks() {
+        let mut chunk = new_chunk();
+        let empty = chunk.health();
+        assert_eq!(empty.slack_bytes, StorageChunk::MIN_CHUNK_SIZE);
+        assert_eq!(empty.largest_free_block, StorageChunk::MIN_CHUNK_SIZE);
+        assert_eq!(empty.fragmentation_bps, 0);
+
+        chunk.add_entry(header(1, 0, 100), vec![1u8; 100], NOW).unwrap();
+        chunk.add_entry(header(2, 100, 100), vec![2u8; 100], NOW).unwrap();
+        chunk.get_entry_header_mut(2).unwrap().set_trashed(true);
+
+        // Packed entries leave a single free block at the tail
+        let packed = chunk.health();
+        assert_eq!(packed, ChunkHealth {
+            slack_bytes: 824,
+            largest_free_block: 824,
+            fragmentation_bps: 0,
+            trashed_bytes: 100,
+        });
+
+        // A hole between entries counts as slack outside the largest block
+        chunk.entry_headers[1].offset = 300;
+        let holed = chunk.health();
+        assert_eq!(holed.slack_bytes, 824);
+        assert_eq!(holed.largest_free_block, 624);
+        assert_eq!(holed.fragmentation_bps, 2427);
+    }
+}
@@ -0,0 +1,106 @@
+/*!
+ * TIMING TESTS
+ *
+ * Tests for:
+ * - Cluster clock plausibility and tolerated drift
+ * - Start times and deadlines taking effect at their exact second
+ * - Time-based checks staying sane when recorded times run ahead of the clock
+ */
+
+#[cfg(test)]
+mod timing_tests {
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::state::{
+        check_cluster_timestamp, check_recorded_timestamp, elapsed_since, has_reached, is_within,
+        CooldownMode, EmergencyAccess, EmergencyStatus, RecoveryConfigV2, DEFAULT_GRACE_PERIOD, DEFAULT_INACTIVITY_PERIOD,
+        DEFAULT_INVITATION_TTL, DEFAULT_RECOVERY_COOLDOWN, DEFAULT_RECOVERY_DELAY, MAX_CLOCK_DRIFT, MIN_CLUSTER_TIMESTAMP,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_cluster_timestamp_plausibility() {
+        check_cluster_timestamp(NOW).unwrap();
+        check_cluster_timestamp(MIN_CLUSTER_TIMESTAMP).unwrap();
+        for broken in [0, -1, MIN_CLUSTER_TIMESTAMP - 1] {
+            let err = check_cluster_timestamp(broken).unwrap_err();
+            assert_eq!(err, LockboxError::InvalidClockTimestamp.into());
+        }
+    }
+
+    #[test]
+    fn test_recorded_timestamps_tolerate_drift() {
+        check_recorded_timestamp(0, NOW).unwrap();
+        check_recorded_timestamp(NOW - 1, NOW).unwrap();
+        check_recorded_timestamp(NOW + MAX_CLOCK_DRIFT, NOW).unwrap();
+        let err = check_recorded_timestamp(NOW + MAX_CLOCK_DRIFT + 1, NOW).unwrap_err();
+        assert_eq!(err, LockboxError::TimestampInFuture.into());
+        check_recorded_timestamp(i64::MAX, i64::MAX).unwrap();
+
+        assert_eq!(elapsed_since(NOW - 30, NOW), 30);
+        assert_eq!(elapsed_since(NOW + 30, NOW), 0);
+        assert_eq!(elapsed_since(i64::MIN, NOW), i64::MAX);
+    }
+
+    #[test]
+    fn test_windows_are_half_open() {
+        assert!(!has_reached(NOW, NOW - 1));
+        assert!(has_reached(NOW, NOW));
+
+        assert!(!is_within(NOW, NOW + 10, NOW - 1));
+        assert!(is_within(NOW, NOW + 10, NOW));
+        assert!(is_within(NOW, NOW + 10, NOW + 9));
+        assert!(!is_within(NOW, NOW + 10, NOW + 10));
+    }
+
+    #[test]
+    fn test_checks_survive_clock_stepping_back() {
+        // Activity recorded a few seconds "after" the current clock reading
+        let mut access = EmergencyAccess {
+            version: EmergencyAccess::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            emergency_contacts: Vec::new(),
+            inactivity_period: DEFAULT_INACTIVITY_PERIOD,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            notification_interval: 0,
+            invitation_ttl: DEFAULT_INVITATION_TTL,
+            last_activity: NOW + 5,
+            countdown_started: None,
+            last_notified_at: 0,
+            status: EmergencyStatus::Active,
+            created_at: NOW,
+            export_consent: None,
+            bump: 255,
+        };
+        assert!(!access.should_start_countdown(NOW));
+        assert!(access.should_start_countdown(NOW + 5 + DEFAULT_INACTIVITY_PERIOD));
+
+        access.start_countdown(NOW + 5);
+        assert!(!access.should_activate_emergency(NOW));
+        assert!(access.should_activate_emergency(NOW + 5 + DEFAULT_GRACE_PERIOD));
+
+        // A recovery attempt recorded just ahead of the clock still counts
+        // as just now for the cooldown
+        let config = RecoveryConfigV2 {
+            version: RecoveryConfigV2::CURRENT_VERSION,
+            owner: Pubkey::new_unique(),
+            threshold: 2,
+            total_guardians: 0,
+            guardians: Vec::new(),
+            recovery_delay: DEFAULT_RECOVERY_DELAY,
+            master_secret_hash: [0u8; 32],
+            created_at: NOW,
+            last_modified: NOW,
+            last_request_id: 1,
+            last_recovery_attempt: NOW + 5,
+            cooldown_mode: CooldownMode::Timestamp,
+            recovery_cooldown: DEFAULT_RECOVERY_COOLDOWN,
+            last_recovery_attempt_slot: 0,
+            last_recovery_attempt_epoch: 0,
+            bump: 255,
+        };
+        assert!(!config.check_recovery_rate_limit(NOW, 3600));
+        assert!(config.check_recovery_rate_limit(NOW + 5 + 3600, 3600));
+    }
+}
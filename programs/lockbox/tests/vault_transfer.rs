@@ -0,0 +1,319 @@
+/*!
+ * VAULT TRANSFER TESTS
+ *
+ * Tests for:
+ * - Listings accept only the named buyer before expiry
+ * - Re-key progress counts live entries written before the handover
+ * - A sold vault is addressed by its buyer, not its seller
+ * - Listed vaults refuse entry writes, deletes and deletion requests
+ */
+
+#[cfg(test)]
+mod vault_transfer_tests {
+    use std::collections::BTreeSet;
+
+    use anchor_lang::prelude::*;
+    use lockbox::errors::LockboxError;
+    use lockbox::instructions::{
+        StorePasswordEntry, StorePasswordEntryBumps, TrashPasswordEntry, TrashPasswordEntryBumps, VaultDeletion,
+        VaultDeletionBumps,
+    };
+    use lockbox::state::{
+        DataEntryHeader, MasterLockbox, OwnerKind, PasswordEntryType, SecurityPolicy, StorageChunk, StorageType,
+        SubscriptionStatus, SubscriptionTier, VaultTransfer, VaultTransferStatus, DEFAULT_DUNNING_WINDOW,
+        MAX_RECENT_ENTRIES,
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn listing(buyer: Pubkey) -> VaultTransfer {
+        VaultTransfer {
+            version: VaultTransfer::CURRENT_VERSION,
+            master_lockbox: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            buyer,
+            price: 5_000_000_000,
+            status: VaultTransferStatus::Listed,
+            listed_at: NOW,
+            expires_at: NOW + 3600,
+            accepted_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn header(entry_id: u64, offset: u32, last_modified: i64, flags: u8) -> DataEntryHeader {
+        DataEntryHeader {
+            entry_id,
+            offset,
+            size: 40,
+            entry_type: PasswordEntryType::Login,
+            category: 0,
+            title_hash: [0u8; 32],
+            created_at: NOW,
+            last_modified,
+            access_count: 0,
+            flags,
+            next_chunk: DataEntryHeader::NO_NEXT_CHUNK,
+            next_offset: 0,
+            tag_mask: 0,
+            rotate_after: 0,
+            parent_entry: 0,
+            linked_entry_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_listing_open_to_buyer_until_expiry() {
+        let buyer = Pubkey::new_unique();
+        let mut transfer = listing(buyer);
+
+        assert!(transfer.is_open_to(&buyer, NOW));
+        assert!(!transfer.is_open_to(&Pubkey::new_unique(), NOW));
+        assert!(!transfer.is_open_to(&buyer, NOW + 3600));
+
+        transfer.status = VaultTransferStatus::Accepted;
+        assert!(!transfer.is_open_to(&buyer, NOW));
+    }
+
+    #[test]
+    fn test_entries_written_before_handover() {
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                0,
+                StorageChunk::MIN_CHUNK_SIZE,
+                StorageType::Passwords,
+                255,
+                NOW,
+            )
+            .unwrap();
+
+        let accepted_at = NOW + 100;
+        chunk.add_entry(header(1, 0, NOW, 0), vec![1u8; 40], NOW).unwrap();
+        chunk.add_entry(header(2, 40, NOW, 0), vec![2u8; 40], NOW).unwrap();
+        // Neither aliases nor trashed entries hold ciphertext to re-key
+        chunk
+            .add_entry(header(3, 80, NOW, DataEntryHeader::FLAG_ALIAS), vec![3u8; 40], NOW)
+            .unwrap();
+        chunk
+            .add_entry(header(4, 120, NOW, DataEntryHeader::FLAG_TRASHED), vec![4u8; 40], NOW)
+            .unwrap();
+        assert_eq!(chunk.entries_written_before(accepted_at), 2);
+
+        chunk.update_entry(1, vec![9u8; 40], accepted_at).unwrap();
+        assert_eq!(chunk.entries_written_before(accepted_at), 1);
+        chunk.update_entry(2, vec![9u8; 40], accepted_at + 1).unwrap();
+        assert_eq!(chunk.entries_written_before(accepted_at), 0);
+    }
+
+    fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &lockbox::ID)
+    }
+
+    /// `seller`'s vault and its first chunk, handed to `owner`
+    fn vault(seller: Pubkey, owner: Pubkey) -> (Pubkey, MasterLockbox, Pubkey, StorageChunk) {
+        let (address, bump) = pda(&[MasterLockbox::SEEDS_PREFIX, seller.as_ref()]);
+        let mut lockbox = MasterLockbox {
+            version: 1,
+            owner: Pubkey::default(),
+            total_entries: 0,
+            storage_chunks_count: 0,
+            subscription_tier: SubscriptionTier::Free,
+            last_accessed: 0,
+            subscription_expires: 0,
+            total_capacity: 0,
+            storage_used: 0,
+            storage_chunks: Vec::new(),
+            encrypted_index: Vec::new(),
+            next_entry_id: 0,
+            categories_count: 0,
+            created_at: 0,
+            recent_entries_enabled: false,
+            recent_entries: [0; MAX_RECENT_ENTRIES],
+            trash_retention: 0,
+            owner_kind: OwnerKind::Wallet,
+            billing_sequence: 0,
+            billing_period_start: 0,
+            billing_period_paid: 0,
+            bonus_capacity: 0,
+            bonus_capacity_expires: 0,
+            consecutive_renewals: 0,
+            subscription_status: SubscriptionStatus::Active,
+            dunning_window: DEFAULT_DUNNING_WINDOW,
+            deletion_requested_at: 0,
+            active_writer: None,
+            burst_window_start: 0,
+            burst_bytes: 0,
+            write_burst_limit: 0,
+            heat_map_period_start: 0,
+            heat_map_reads: [0; 16],
+            has_category_registry: false,
+            has_category_index: false,
+            creator: Pubkey::default(),
+            listed_for_transfer: false,
+            shared_storage_used: 0,
+            encrypted_settings: Vec::new(),
+            bump: 0,
+        };
+        lockbox.initialize(seller, bump, NOW).unwrap();
+        lockbox.owner = owner;
+
+        let (chunk_address, chunk_bump) = pda(&[StorageChunk::SEEDS_PREFIX, address.as_ref(), &0u16.to_le_bytes()]);
+        let mut chunk = StorageChunk {
+            version: 0,
+            master_lockbox: Pubkey::default(),
+            owner: Pubkey::default(),
+            chunk_index: 0,
+            max_capacity: 0,
+            current_size: 0,
+            data_type: StorageType::Passwords,
+            encrypted_data: Vec::new(),
+            entry_headers: Vec::new(),
+            entry_count: 0,
+            created_at: 0,
+            last_modified: 0,
+            layout_epoch: 0,
+            bump: 0,
+        };
+        chunk
+            .initialize(address, owner, 0, StorageChunk::MIN_CHUNK_SIZE, StorageType::Passwords, chunk_bump, NOW)
+            .unwrap();
+        (address, lockbox, chunk_address, chunk)
+    }
+
+    /// Account leaked for the `'static` context
+    fn leaked_account(key: Pubkey, is_signer: bool, owner: &'static Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            owner,
+            false,
+            0,
+        )
+    }
+
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        leaked_account(key, false, &lockbox::ID, data)
+    }
+
+    fn wallet(key: Pubkey) -> AccountInfo<'static> {
+        leaked_account(key, true, &anchor_lang::system_program::ID, Vec::new())
+    }
+
+    /// Omitted optional account
+    fn omitted() -> AccountInfo<'static> {
+        leaked_account(lockbox::ID, false, &lockbox::ID, Vec::new())
+    }
+
+    /// Run the context's account validation, as the program entrypoint would
+    fn validate<T, B>(accounts: Vec<AccountInfo<'static>>, ix_data: &[u8]) -> Result<T>
+    where
+        T: Accounts<'static, B>,
+        B: Default,
+    {
+        let mut accounts: &'static [AccountInfo<'static>] = Box::leak(accounts.into_boxed_slice());
+        T::try_accounts(&lockbox::ID, &mut accounts, ix_data, &mut B::default(), &mut BTreeSet::new())
+    }
+
+    fn request_deletion(master: (Pubkey, &MasterLockbox), signer: Pubkey) -> Result<VaultDeletion<'static>> {
+        validate::<_, VaultDeletionBumps>(vec![program_account(master.0, master.1), wallet(signer)], &[])
+    }
+
+    fn trash(
+        master: (Pubkey, &MasterLockbox),
+        chunk: (Pubkey, &StorageChunk),
+        signer: Pubkey,
+    ) -> Result<TrashPasswordEntry<'static>> {
+        validate::<_, TrashPasswordEntryBumps>(
+            vec![program_account(master.0, master.1), program_account(chunk.0, chunk.1), wallet(signer)],
+            &0u16.to_le_bytes(),
+        )
+    }
+
+    fn store(
+        master: (Pubkey, &MasterLockbox),
+        chunk: (Pubkey, &StorageChunk),
+        signer: Pubkey,
+    ) -> Result<StorePasswordEntry<'static>> {
+        let (policy, _) = pda(&[SecurityPolicy::SEEDS_PREFIX, master.0.as_ref()]);
+        validate::<_, StorePasswordEntryBumps>(
+            vec![
+                program_account(master.0, master.1),
+                program_account(chunk.0, chunk.1),
+                wallet(signer),
+                leaked_account(policy, false, &anchor_lang::system_program::ID, Vec::new()),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+            ],
+            &0u16.to_le_bytes(),
+        )
+    }
+
+    #[test]
+    fn test_sold_vault_addressed_by_buyer() {
+        let (seller, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (address, lockbox, chunk_address, chunk) = vault(seller, buyer);
+        let master = (address, &lockbox);
+        let chunk = (chunk_address, &chunk);
+
+        // The PDA is still the seller's, but only the buyer can use it
+        assert_eq!(lockbox.creator, seller);
+        store(master, chunk, buyer).unwrap();
+        trash(master, chunk, buyer).unwrap();
+        request_deletion(master, buyer).unwrap();
+
+        let err = store(master, chunk, seller).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+        let err = request_deletion(master, seller).err().unwrap();
+        assert_eq!(err, LockboxError::Unauthorized.into());
+    }
+
+    #[test]
+    fn test_listed_vault_is_frozen() {
+        let owner = Pubkey::new_unique();
+        let (address, mut lockbox, chunk_address, chunk) = vault(owner, owner);
+        lockbox.listed_for_transfer = true;
+        let master = (address, &lockbox);
+        let chunk = (chunk_address, &chunk);
+
+        let err = store(master, chunk, owner).err().unwrap();
+        assert_eq!(err, LockboxError::VaultListedForTransfer.into());
+        let err = trash(master, chunk, owner).err().unwrap();
+        assert_eq!(err, LockboxError::VaultListedForTransfer.into());
+        let err = request_deletion(master, owner).err().unwrap();
+        assert_eq!(err, LockboxError::VaultListedForTransfer.into());
+
+        // Cancelling the listing thaws it
+        lockbox.listed_for_transfer = false;
+        let master = (address, &lockbox);
+        store(master, chunk, owner).unwrap();
+        trash(master, chunk, owner).unwrap();
+        request_deletion(master, owner).unwrap();
+    }
+}
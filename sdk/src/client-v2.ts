@@ -63,6 +63,7 @@
 
 import { Program, AnchorProvider, BN } from '@coral-xyz/anchor';
 import {
+  AccountMeta,
   Connection,
   PublicKey,
   SystemProgram,
@@ -343,6 +344,33 @@ const INSTRUCTION_DISCRIMINATORS = {
   forceCloseOrphanedChunk: Buffer.from([0xc5, 0x70, 0x4f, 0xae, 0x0f, 0xf1, 0x23, 0x60]),
 };
 
+/**
+ * Entry payload framing header: version, nonce length, suite ID
+ *
+ * Entries are stored as header + nonce + ciphertext; the program rejects
+ * payloads without it. Entries written before framing are bare nonce +
+ * ciphertext and are still read.
+ */
+const NONCE_LENGTH = 24;
+const PAYLOAD_HEADER = new Uint8Array([1, NONCE_LENGTH, 1]);
+
+function isFramedPayload(payload: Uint8Array): boolean {
+  return payload.length > PAYLOAD_HEADER.length && PAYLOAD_HEADER.every((byte, i) => payload[i] === byte);
+}
+
+/**
+ * Account meta for an optional instruction account
+ *
+ * Anchor reads the program ID in an optional account's slot as "not passed",
+ * so absent accounts keep their position in the list.
+ */
+function optionalAccount(pubkey: PublicKey | null, isWritable: boolean, isSigner = false): AccountMeta {
+  if (!pubkey) {
+    return { pubkey: PROGRAM_ID, isSigner: false, isWritable: false };
+  }
+  return { pubkey, isSigner, isWritable };
+}
+
 /**
  * Main client for Lockbox v2.0 Password Manager
  */
@@ -436,6 +464,71 @@ export class LockboxV2Client {
     );
   }
 
+  /**
+   * Get security policy PDA (the account may not exist)
+   */
+  getSecurityPolicyAddress(): [PublicKey, number] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('security_policy'), masterLockbox.toBuffer()],
+      PROGRAM_ID
+    );
+  }
+
+  /**
+   * Get category registry PDA
+   */
+  getCategoryRegistryAddress(): [PublicKey, number] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('category_registry'), masterLockbox.toBuffer()],
+      PROGRAM_ID
+    );
+  }
+
+  /**
+   * Get category index PDA
+   */
+  getCategoryIndexAddress(): [PublicKey, number] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('category_index'), masterLockbox.toBuffer()],
+      PROGRAM_ID
+    );
+  }
+
+  /**
+   * Get the PDA listing copies of an entry in other vaults (may not exist)
+   */
+  getEntryCopiesAddress(entryId: number): [PublicKey, number] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    const entryIdBuffer = Buffer.alloc(8);
+    entryIdBuffer.writeBigUInt64LE(BigInt(entryId));
+
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('entry_copies'), masterLockbox.toBuffer(), entryIdBuffer],
+      PROGRAM_ID
+    );
+  }
+
+  /**
+   * Get the PDA reserving client-chosen entry IDs across the vault
+   */
+  getClientEntryIdsAddress(): [PublicKey, number] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('client_entry_ids'), masterLockbox.toBuffer()],
+      PROGRAM_ID
+    );
+  }
+
+  /**
+   * Get treasury PDA (collects entry write fees)
+   */
+  getTreasuryAddress(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync([Buffer.from('treasury')], PROGRAM_ID);
+  }
+
   // ============================================================================
   // Encryption & Key Management
   // ============================================================================
@@ -460,30 +553,38 @@ export class LockboxV2Client {
 
   /**
    * Encrypt password entry data with versioning and integrity checking
+   *
+   * Returns the framed payload stored on chain: header + nonce + ciphertext.
    */
-  private encryptEntry(entry: PasswordEntry, key: Uint8Array): { ciphertext: Uint8Array; nonce: Uint8Array } {
+  private encryptEntry(entry: PasswordEntry, key: Uint8Array): Uint8Array {
     // Validate entry before encryption
     const validated = PasswordEntrySchema.parse(entry);
 
     // Serialize with versioning and checksum
     const json = serializeEntry(validated);
 
-    const nonce = nacl.randomBytes(24);
+    const nonce = nacl.randomBytes(NONCE_LENGTH);
     const messageUint8 = util.decodeUTF8(json);
     const ciphertext = nacl.secretbox(messageUint8, nonce, key);
 
-    return { ciphertext, nonce };
+    const payload = new Uint8Array(PAYLOAD_HEADER.length + nonce.length + ciphertext.length);
+    payload.set(PAYLOAD_HEADER);
+    payload.set(nonce, PAYLOAD_HEADER.length);
+    payload.set(ciphertext, PAYLOAD_HEADER.length + nonce.length);
+    return payload;
   }
 
   /**
    * Decrypt password entry data with validation and migration support
    */
-  private decryptEntry(ciphertext: Uint8Array, key: Uint8Array): PasswordEntry | null {
-    // Extract nonce from the first 24 bytes
-    const nonce = ciphertext.slice(0, 24);
-    const encrypted = ciphertext.slice(24);
-
-    const decrypted = nacl.secretbox.open(encrypted, nonce, key);
+  private decryptEntry(payload: Uint8Array, key: Uint8Array): PasswordEntry | null {
+    // Nonce in the first 24 bytes, ciphertext after it
+    const open = (sealed: Uint8Array) =>
+      nacl.secretbox.open(sealed.slice(NONCE_LENGTH), sealed.slice(0, NONCE_LENGTH), key);
+
+    // Skip the framing header; fall back to legacy unframed entries, whose
+    // random nonce may happen to start with the header bytes
+    const decrypted = (isFramedPayload(payload) && open(payload.slice(PAYLOAD_HEADER.length))) || open(payload);
     if (!decrypted) {
       console.error('Decryption failed: Authentication tag mismatch (wrong key or corrupted data)');
       return null;
@@ -879,8 +980,19 @@ export class LockboxV2Client {
 
   /**
    * Store a new password entry
+   *
+   * Pass `clientEntryId` (high bit set) to store the entry under a stable ID
+   * chosen by a sync engine instead of the vault's counter. The vault's client
+   * entry ID account must exist (`initialize_client_entry_ids`).
    */
-  async storePassword(entry: PasswordEntry): Promise<{ txSignature: string; entryId: number }> {
+  async storePassword(
+    entry: PasswordEntry,
+    clientEntryId?: bigint
+  ): Promise<{ txSignature: string; entryId: number | bigint }> {
+    if (clientEntryId !== undefined && (clientEntryId >> BigInt(63)) !== BigInt(1)) {
+      throw new Error('Client entry IDs must have the high bit set');
+    }
+
     // Prevent duplicate password creation attempts (in case user double-clicks "Save")
     const operationKey = `store-${entry.title}-${Date.now()}`;
     if (this.pendingTransactions.has(operationKey)) {
@@ -894,12 +1006,7 @@ export class LockboxV2Client {
       const sessionKey = await this.getSessionKey();
 
       // Encrypt the entry
-      const { ciphertext, nonce } = this.encryptEntry(entry, sessionKey);
-
-    // Combine nonce + ciphertext for storage
-    const combined = new Uint8Array(nonce.length + ciphertext.length);
-    combined.set(nonce);
-    combined.set(ciphertext, nonce.length);
+      const combined = this.encryptEntry(entry, sessionKey);
 
     console.log(`[storePassword] Encrypted data size: ${combined.length} bytes`);
 
@@ -1001,7 +1108,9 @@ export class LockboxV2Client {
 
     // Build instruction data: discriminator + args
     // Args: chunk_index (u16) + encrypted_data (vec<u8>) + entry_type (u8) + category (u32) + title_hash ([u8; 32])
-    const argsBuffer = Buffer.alloc(2 + 4 + combined.length + 1 + 4 + 32);
+    //       + client_entry_id (Option<u64>)
+    const hasClientEntryId = clientEntryId !== undefined;
+    const argsBuffer = Buffer.alloc(2 + 4 + combined.length + 1 + 4 + 32 + (hasClientEntryId ? 9 : 1));
     let offset = 0;
 
     // chunk_index (u16)
@@ -1028,19 +1137,35 @@ export class LockboxV2Client {
     titleHash.forEach((byte, i) => {
       argsBuffer[offset + i] = byte;
     });
+    offset += 32;
+
+    // client_entry_id (Option<u64>)
+    argsBuffer.writeUInt8(hasClientEntryId ? 1 : 0, offset);
+    offset += 1;
+    if (hasClientEntryId) {
+      argsBuffer.writeBigUInt64LE(clientEntryId, offset);
+    }
 
     const instructionData = Buffer.concat([
       INSTRUCTION_DISCRIMINATORS.storePasswordEntry,
       argsBuffer,
     ]);
 
+    // Account order matches StorePasswordEntry; the category accounts are
+    // required once the vault has them
     const instruction = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
         { pubkey: masterLockbox, isSigner: false, isWritable: true },
         { pubkey: storageChunk, isSigner: false, isWritable: true },
         { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: this.getSecurityPolicyAddress()[0], isSigner: false, isWritable: false },
+        optionalAccount(master.hasCategoryRegistry ? this.getCategoryRegistryAddress()[0] : null, true),
+        optionalAccount(master.hasCategoryIndex ? this.getCategoryIndexAddress()[0] : null, true),
+        optionalAccount(this.getTreasuryAddress()[0], true),
+        optionalAccount(null, true), // payer (auto-expand)
+        optionalAccount(SystemProgram.programId, false),
+        optionalAccount(hasClientEntryId ? this.getClientEntryIdsAddress()[0] : null, true),
       ],
       data: instructionData,
     });
@@ -1137,9 +1262,14 @@ export class LockboxV2Client {
 
       console.log('✅ Password entry stored successfully!');
 
-      // Fetch updated master to get entry ID
-      const updatedMaster = await this.getMasterLockbox();
-      const entryId = safeBigIntToNumber(updatedMaster.nextEntryId, 'nextEntryId') - 1;
+      // Client IDs are stored as given; otherwise fetch updated master to get entry ID
+      let entryId: number | bigint;
+      if (hasClientEntryId) {
+        entryId = clientEntryId;
+      } else {
+        const updatedMaster = await this.getMasterLockbox();
+        entryId = safeBigIntToNumber(updatedMaster.nextEntryId, 'nextEntryId') - 1;
+      }
 
       console.log(`[storePassword] Entry ID: ${entryId}`);
 
@@ -1247,10 +1377,50 @@ export class LockboxV2Client {
     return this.decryptEntry(encryptedEntry, sessionKey);
   }
 
+  /**
+   * Accounts of update_password_entry, in UpdatePasswordEntry order
+   *
+   * The category registry is required once the vault has one; the treasury
+   * collects the tier's entry write fee.
+   */
+  private updateAccounts(master: MasterLockbox, storageChunk: PublicKey, entryId: number): AccountMeta[] {
+    const [masterLockbox] = this.getMasterLockboxAddress();
+    return [
+      { pubkey: masterLockbox, isSigner: false, isWritable: true },
+      { pubkey: storageChunk, isSigner: false, isWritable: true },
+      { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
+      { pubkey: this.getSecurityPolicyAddress()[0], isSigner: false, isWritable: false },
+      optionalAccount(master.hasCategoryRegistry ? this.getCategoryRegistryAddress()[0] : null, true),
+      { pubkey: this.getEntryCopiesAddress(entryId)[0], isSigner: false, isWritable: true },
+      optionalAccount(this.getTreasuryAddress()[0], true),
+      optionalAccount(SystemProgram.programId, false),
+    ];
+  }
+
+  /**
+   * Current layout epoch of a chunk, as registered on the master lockbox
+   */
+  private chunkLayoutEpoch(master: MasterLockbox, chunkIndex: number): number {
+    const chunkInfo = master.storageChunks.find(c => c.chunkIndex === chunkIndex);
+    if (!chunkInfo) {
+      throw new Error(`Storage chunk ${chunkIndex} is not registered`);
+    }
+    return chunkInfo.layoutEpoch;
+  }
+
   /**
    * Update an existing password entry
+   *
+   * Pass the `layoutEpoch` of the chunk the entry was read from so the program
+   * rejects the update if a move or compaction has restructured the chunk
+   * since; defaults to the chunk's current epoch.
    */
-  async updatePassword(chunkIndex: number, entryId: number, updatedEntry: PasswordEntry): Promise<string> {
+  async updatePassword(
+    chunkIndex: number,
+    entryId: number,
+    updatedEntry: PasswordEntry,
+    expectedLayoutEpoch?: number
+  ): Promise<string> {
     // Prevent duplicate update attempts (in case user double-clicks "Update")
     const operationKey = `update-${chunkIndex}-${entryId}`;
     if (this.pendingTransactions.has(operationKey)) {
@@ -1263,17 +1433,15 @@ export class LockboxV2Client {
 
       const sessionKey = await this.getSessionKey();
 
-      const { ciphertext, nonce } = this.encryptEntry(updatedEntry, sessionKey);
-      const combined = new Uint8Array(nonce.length + ciphertext.length);
-      combined.set(nonce);
-      combined.set(ciphertext, nonce.length);
+      const combined = this.encryptEntry(updatedEntry, sessionKey);
 
-      const [masterLockbox] = this.getMasterLockboxAddress();
+      const master = await this.getMasterLockbox();
       const [storageChunk] = this.getStorageChunkAddress(chunkIndex);
+      const layoutEpoch = expectedLayoutEpoch ?? this.chunkLayoutEpoch(master, chunkIndex);
 
       // Build instruction data: discriminator + args
-      // Args: chunk_index (u16) + entry_id (u64) + new_encrypted_data (vec<u8>)
-      const argsBuffer = Buffer.alloc(2 + 8 + 4 + combined.length);
+      // Args: chunk_index (u16) + entry_id (u64) + new_encrypted_data (vec<u8>) + expected_layout_epoch (u32)
+      const argsBuffer = Buffer.alloc(2 + 8 + 4 + combined.length + 4);
       let offset = 0;
 
       // chunk_index (u16)
@@ -1290,6 +1458,10 @@ export class LockboxV2Client {
       combined.forEach((byte, i) => {
         argsBuffer[offset + i] = byte;
       });
+      offset += combined.length;
+
+      // expected_layout_epoch (u32)
+      argsBuffer.writeUInt32LE(layoutEpoch, offset);
 
       const instructionData = Buffer.concat([
         INSTRUCTION_DISCRIMINATORS.updatePasswordEntry,
@@ -1298,11 +1470,7 @@ export class LockboxV2Client {
 
       const instruction = new TransactionInstruction({
         programId: PROGRAM_ID,
-        keys: [
-          { pubkey: masterLockbox, isSigner: false, isWritable: true },
-          { pubkey: storageChunk, isSigner: false, isWritable: true },
-          { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
-        ],
+        keys: this.updateAccounts(master, storageChunk, entryId),
         data: instructionData,
       });
 
@@ -1366,6 +1534,7 @@ export class LockboxV2Client {
     chunkIndex: number;
     entryId: number;
     updatedEntry: PasswordEntry;
+    expectedLayoutEpoch?: number;
   }>): Promise<string> {
     if (updates.length === 0) {
       throw new Error('No updates provided');
@@ -1379,19 +1548,17 @@ export class LockboxV2Client {
 
     const sessionKey = await this.getSessionKey();
     const transaction = new Transaction();
-    const [masterLockbox] = this.getMasterLockboxAddress();
+    const master = await this.getMasterLockbox();
 
     // Build an update instruction for each entry
-    for (const { chunkIndex, entryId, updatedEntry } of updates) {
-      const { ciphertext, nonce } = this.encryptEntry(updatedEntry, sessionKey);
-      const combined = new Uint8Array(nonce.length + ciphertext.length);
-      combined.set(nonce);
-      combined.set(ciphertext, nonce.length);
+    for (const { chunkIndex, entryId, updatedEntry, expectedLayoutEpoch } of updates) {
+      const combined = this.encryptEntry(updatedEntry, sessionKey);
 
       const [storageChunk] = this.getStorageChunkAddress(chunkIndex);
+      const layoutEpoch = expectedLayoutEpoch ?? this.chunkLayoutEpoch(master, chunkIndex);
 
       // Build instruction data: discriminator + args
-      const argsBuffer = Buffer.alloc(2 + 8 + 4 + combined.length);
+      const argsBuffer = Buffer.alloc(2 + 8 + 4 + combined.length + 4);
       let offset = 0;
 
       // chunk_index (u16)
@@ -1408,6 +1575,10 @@ export class LockboxV2Client {
       combined.forEach((byte, i) => {
         argsBuffer[offset + i] = byte;
       });
+      offset += combined.length;
+
+      // expected_layout_epoch (u32)
+      argsBuffer.writeUInt32LE(layoutEpoch, offset);
 
       const instructionData = Buffer.concat([
         INSTRUCTION_DISCRIMINATORS.updatePasswordEntry,
@@ -1416,11 +1587,7 @@ export class LockboxV2Client {
 
       const instruction = new TransactionInstruction({
         programId: PROGRAM_ID,
-        keys: [
-          { pubkey: masterLockbox, isSigner: false, isWritable: true },
-          { pubkey: storageChunk, isSigner: false, isWritable: true },
-          { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
-        ],
+        keys: this.updateAccounts(master, storageChunk, entryId),
         data: instructionData,
       });
 
@@ -1463,8 +1630,10 @@ export class LockboxV2Client {
 
   /**
    * Delete a password entry
+   *
+   * `expectedLayoutEpoch` works as in `updatePassword`.
    */
-  async deletePassword(chunkIndex: number, entryId: number): Promise<string> {
+  async deletePassword(chunkIndex: number, entryId: number, expectedLayoutEpoch?: number): Promise<string> {
     // Prevent duplicate delete attempts (in case user double-clicks "Delete")
     const operationKey = `delete-${chunkIndex}-${entryId}`;
     if (this.pendingTransactions.has(operationKey)) {
@@ -1475,26 +1644,35 @@ export class LockboxV2Client {
     try {
       this.pendingTransactions.add(operationKey);
 
+      const master = await this.getMasterLockbox();
       const [masterLockbox] = this.getMasterLockboxAddress();
       const [storageChunk] = this.getStorageChunkAddress(chunkIndex);
 
       // Build instruction data: discriminator + args
-      // Args: chunk_index (u16) + entry_id (u64)
-      const argsBuffer = Buffer.alloc(2 + 8);
+      // Args: chunk_index (u16) + entry_id (u64) + expected_layout_epoch (u32)
+      const argsBuffer = Buffer.alloc(2 + 8 + 4);
       argsBuffer.writeUInt16LE(chunkIndex, 0);
       argsBuffer.writeBigUInt64LE(BigInt(entryId), 2);
+      argsBuffer.writeUInt32LE(expectedLayoutEpoch ?? this.chunkLayoutEpoch(master, chunkIndex), 10);
 
       const instructionData = Buffer.concat([
         INSTRUCTION_DISCRIMINATORS.deletePasswordEntry,
         argsBuffer,
       ]);
 
+      // Account order matches DeletePasswordEntry; the category accounts are
+      // required once the vault has them
       const instruction = new TransactionInstruction({
         programId: PROGRAM_ID,
         keys: [
           { pubkey: masterLockbox, isSigner: false, isWritable: true },
           { pubkey: storageChunk, isSigner: false, isWritable: true },
           { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
+          { pubkey: this.getSecurityPolicyAddress()[0], isSigner: false, isWritable: false },
+          optionalAccount(master.hasCategoryRegistry ? this.getCategoryRegistryAddress()[0] : null, true),
+          optionalAccount(master.hasCategoryIndex ? this.getCategoryIndexAddress()[0] : null, true),
+          { pubkey: this.getEntryCopiesAddress(entryId)[0], isSigner: false, isWritable: true },
+          optionalAccount(null, false), // delete_cosigner
         ],
         data: instructionData,
       });
@@ -1965,6 +2143,10 @@ export class LockboxV2Client {
     try {
       let offset = 0;
 
+      // Read version (u8, 1 byte)
+      const version = data.readUInt8(offset);
+      offset += 1;
+
       // Read owner (32 bytes)
       const owner = new PublicKey(data.slice(offset, offset + 32));
       offset += 32;
@@ -2032,6 +2214,10 @@ export class LockboxV2Client {
         const lastModified = safeBigIntToNumber(data.readBigInt64LE(offset), 'lastModified');
         offset += 8;
 
+        // layout_epoch (u32, 4 bytes)
+        const layoutEpoch = data.readUInt32LE(offset);
+        offset += 4;
+
         storageChunks.push({
           chunkAddress,
           chunkIndex,
@@ -2040,6 +2226,7 @@ export class LockboxV2Client {
           dataType,
           createdAt: chunkCreatedAt,
           lastModified,
+          layoutEpoch,
         });
       }
 
@@ -2061,6 +2248,46 @@ export class LockboxV2Client {
       const createdAt = safeBigIntToNumber(data.readBigInt64LE(offset), 'createdAt');
       offset += 8;
 
+      // Skip recent_entries_enabled (bool), recent_entries ([u64; 8]) and
+      // trash_retention (i64)
+      offset += 1 + 8 * 8 + 8;
+
+      // Skip owner_kind (enum; ProgramDerived carries a 32-byte program ID)
+      offset += data.readUInt8(offset) === 0 ? 1 : 1 + 32;
+
+      // Skip billing_sequence, billing_period_start, billing_period_paid,
+      // bonus_capacity, bonus_capacity_expires (8 bytes each),
+      // consecutive_renewals (u32) and subscription_status (enum)
+      offset += 5 * 8 + 4 + 1;
+
+      // Skip dunning_window and deletion_requested_at (i64 each)
+      offset += 2 * 8;
+
+      // Skip active_writer (Option<WriterLease>: device + expires_at)
+      offset += data.readUInt8(offset) === 0 ? 1 : 1 + 32 + 8;
+
+      // Skip burst_window_start (u64), burst_bytes (u32), write_burst_limit
+      // (u32), heat_map_period_start (i64) and heat_map_reads ([u16; 16])
+      offset += 8 + 4 + 4 + 8 + 16 * 2;
+
+      // Read has_category_registry and has_category_index (bool, 1 byte each)
+      const hasCategoryRegistry = data.readUInt8(offset) !== 0;
+      offset += 1;
+      const hasCategoryIndex = data.readUInt8(offset) !== 0;
+      offset += 1;
+
+      // Read creator (32 bytes); the PDA stays derived from it after a transfer
+      const creator = new PublicKey(data.slice(offset, offset + 32));
+      offset += 32;
+
+      // Read listed_for_transfer (bool, 1 byte)
+      const listedForTransfer = data.readUInt8(offset) !== 0;
+      offset += 1;
+
+      // Skip shared_storage_used (u64) and encrypted_settings (vec<u8>)
+      offset += 8;
+      offset += 4 + data.readUInt32LE(offset);
+
       // Read bump (u8, 1 byte)
       const bump = data.readUInt8(offset);
       offset += 1;
@@ -2072,6 +2299,7 @@ export class LockboxV2Client {
       console.log('  Storage Chunks:', storageChunksCount);
 
       return {
+        version,
         owner,
         totalEntries,
         storageChunksCount,
@@ -2085,6 +2313,10 @@ export class LockboxV2Client {
         nextEntryId,
         categoriesCount,
         createdAt,
+        hasCategoryRegistry,
+        hasCategoryIndex,
+        creator,
+        listedForTransfer,
         bump,
       } as MasterLockbox;
     } catch (error) {
@@ -2113,6 +2345,10 @@ export class LockboxV2Client {
     try {
       let offset = 0;
 
+      // Read version (u8, 1 byte)
+      const version = data.readUInt8(offset);
+      offset += 1;
+
       // Read master_lockbox (32 bytes)
       const masterLockbox = new PublicKey(data.slice(offset, offset + 32));
       offset += 32;
@@ -2189,6 +2425,13 @@ export class LockboxV2Client {
         const flags = data.readUInt8(offset);
         offset += 1;
 
+        // Skip next_chunk (u16), next_offset (u32), tag_mask (u16),
+        // rotate_after (i64) and parent_entry (u64)
+        offset += 2 + 4 + 2 + 8 + 8;
+
+        // Skip linked_entry_ids (vec<u64>)
+        offset += 4 + data.readUInt32LE(offset) * 8;
+
         entryHeaders.push({
           entryId,
           offset: entryOffset,
@@ -2215,6 +2458,10 @@ export class LockboxV2Client {
       const lastModified = safeBigIntToNumber(data.readBigInt64LE(offset), 'lastModified');
       offset += 8;
 
+      // Read layout_epoch (u32, 4 bytes)
+      const layoutEpoch = data.readUInt32LE(offset);
+      offset += 4;
+
       // Read bump (u8, 1 byte)
       const bump = data.readUInt8(offset);
       offset += 1;
@@ -2224,6 +2471,7 @@ export class LockboxV2Client {
       console.log(`  Entry headers: ${entryHeaders.length}`);
 
       return {
+        version,
         masterLockbox,
         owner,
         chunkIndex: chunkIndexRead,
@@ -2235,6 +2483,7 @@ export class LockboxV2Client {
         entryCount,
         createdAt,
         lastModified,
+        layoutEpoch,
         bump,
       } as StorageChunk;
     } catch (error) {
@@ -2305,17 +2554,19 @@ export class LockboxV2Client {
    * Close a storage chunk and reclaim rent
    *
    * @param chunkIndex Index of the chunk to close
+   * @param allowNonEmpty Destroy entries still stored in the chunk
    * @returns Transaction signature
    */
-  async closeStorageChunk(chunkIndex: number): Promise<string> {
+  async closeStorageChunk(chunkIndex: number, allowNonEmpty = false): Promise<string> {
     const [masterLockbox] = this.getMasterLockboxAddress();
     const [storageChunk] = this.getStorageChunkAddress(chunkIndex);
 
     console.log(`[closeStorageChunk] Closing chunk ${chunkIndex} at ${storageChunk.toBase58()}`);
 
-    // Build instruction data: discriminator + chunk_index (u16)
-    const argsBuffer = Buffer.alloc(2);
+    // Build instruction data: discriminator + chunk_index (u16) + allow_non_empty (bool)
+    const argsBuffer = Buffer.alloc(3);
     argsBuffer.writeUInt16LE(chunkIndex, 0);
+    argsBuffer.writeUInt8(allowNonEmpty ? 1 : 0, 2);
 
     const instructionData = Buffer.concat([
       INSTRUCTION_DISCRIMINATORS.closeStorageChunk,
@@ -2326,8 +2577,9 @@ export class LockboxV2Client {
       programId: PROGRAM_ID,
       keys: [
         { pubkey: storageChunk, isSigner: false, isWritable: true },
-        { pubkey: masterLockbox, isSigner: false, isWritable: false },
+        { pubkey: masterLockbox, isSigner: false, isWritable: true },
         { pubkey: this.wallet.publicKey, isSigner: true, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       ],
       data: instructionData,
     });
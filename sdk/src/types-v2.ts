@@ -48,6 +48,7 @@ export interface StorageChunkInfo {
   dataType: StorageType;
   createdAt: number;
   lastModified: number;
+  layoutEpoch: number;
 }
 
 /**
@@ -70,6 +71,7 @@ export interface DataEntryHeader {
  * Master lockbox account structure
  */
 export interface MasterLockbox {
+  version: number;
   owner: PublicKey;
   totalEntries: number;
   storageChunksCount: number;
@@ -83,6 +85,10 @@ export interface MasterLockbox {
   nextEntryId: number;
   categoriesCount: number;
   createdAt: number;
+  hasCategoryRegistry: boolean;
+  hasCategoryIndex: boolean;
+  creator: PublicKey;
+  listedForTransfer: boolean;
   bump: number;
 }
 
@@ -90,6 +96,7 @@ export interface MasterLockbox {
  * Storage chunk account structure
  */
 export interface StorageChunk {
+  version: number;
   masterLockbox: PublicKey;
   owner: PublicKey;
   chunkIndex: number;
@@ -101,6 +108,7 @@ export interface StorageChunk {
   entryCount: number;
   createdAt: number;
   lastModified: number;
+  layoutEpoch: number;
   bump: number;
 }
 